@@ -69,6 +69,23 @@ fn parse_attribute(r: &mut Reader, cp: &ConstantPool) -> Result<Type> {
         Tag::AnnotationDefault => parse_annotation_default(r, cp, length),
         Tag::BootstrapMethods => parse_bootstrap_methods(r),
         Tag::MethodParameters => parse_method_parameters(r),
+        Tag::Module => parse_module(r),
+        Tag::ModulePackages => {
+            let packages = read_u16_list(r)?;
+            Ok(Type::ModulePackages { packages })
+        }
+        Tag::ModuleMainClass => {
+            let main_class_index = r.read_u16()?;
+            Ok(Type::ModuleMainClass { main_class_index })
+        }
+        Tag::NestHost => {
+            let host_class_index = r.read_u16()?;
+            Ok(Type::NestHost { host_class_index })
+        }
+        Tag::NestMembers => {
+            let classes = read_u16_list(r)?;
+            Ok(Type::NestMembers { classes })
+        }
         Tag::Unknown => {
             r.read_bytes(length as usize)?;
             Ok(Type::Unknown)
@@ -330,6 +347,71 @@ fn parse_method_parameters(r: &mut Reader) -> Result<Type> {
     Ok(Type::MethodParameters { parameters })
 }
 
+// ── Module (JVM spec 4.7.25) ────────────────────────────────────────────
+
+fn parse_module(r: &mut Reader) -> Result<Type> {
+    let name_index = r.read_u16()?;
+    let flags = r.read_u16()?;
+    let version_index = r.read_u16()?;
+
+    let requires_count = r.read_u16()?;
+    let mut requires = Vec::with_capacity(requires_count as usize);
+    for _ in 0..requires_count {
+        requires.push(ModuleRequires {
+            requires_index: r.read_u16()?,
+            requires_flags: r.read_u16()?,
+            requires_version_index: r.read_u16()?,
+        });
+    }
+
+    let exports = parse_module_exports(r)?;
+    let opens = parse_module_exports(r)?;
+    let uses = read_u16_list(r)?;
+
+    let provides_count = r.read_u16()?;
+    let mut provides = Vec::with_capacity(provides_count as usize);
+    for _ in 0..provides_count {
+        provides.push(ModuleProvides {
+            provides_index: r.read_u16()?,
+            with: read_u16_list(r)?,
+        });
+    }
+
+    Ok(Type::Module(Module {
+        name_index,
+        flags,
+        version_index,
+        requires,
+        exports,
+        opens,
+        uses,
+        provides,
+    }))
+}
+
+//'exports' and 'opens' share the layout
+fn parse_module_exports(r: &mut Reader) -> Result<Vec<ModuleExports>> {
+    let count = r.read_u16()?;
+    let mut entries = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        entries.push(ModuleExports {
+            package_index: r.read_u16()?,
+            flags: r.read_u16()?,
+            to: read_u16_list(r)?,
+        });
+    }
+    Ok(entries)
+}
+
+fn read_u16_list(r: &mut Reader) -> Result<Vec<U2>> {
+    let count = r.read_u16()?;
+    let mut v = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        v.push(r.read_u16()?);
+    }
+    Ok(v)
+}
+
 // ── Annotation parsing (needs ConstantPool) ─────────────────────────────
 
 fn parse_annotations_list(r: &mut Reader, cp: &ConstantPool) -> Result<Vec<AnnotationEntry>> {
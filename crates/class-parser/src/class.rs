@@ -1,4 +1,4 @@
-use classfile::ClassFile;
+use classfile::{ClassFile, ConstantPool};
 
 use crate::attributes;
 use crate::constant_pool;
@@ -30,16 +30,18 @@ use crate::reader::{Error, Reader, Result};
 /// }
 /// ```
 pub fn parse_class_file(r: &mut Reader) -> Result<ClassFile> {
-    // magic
-    let magic = r.read_u32()?;
-    if magic != 0xCAFEBABE {
-        return Err(Error::BadMagic(magic));
-    }
-
-    let version = parse_version(r)?;
+    let version = parse_header(r)?;
     let cp = constant_pool::parse_constant_pool(r)?;
-    let cp_arc = std::sync::Arc::new(cp);
+    parse_class_body(r, version, std::sync::Arc::new(cp))
+}
 
+/// Parse the part of a class file after the constant pool, reusing a
+/// constant pool that was parsed before.
+pub(crate) fn parse_class_body(
+    r: &mut Reader,
+    version: classfile::Version,
+    cp_arc: ConstantPool,
+) -> Result<ClassFile> {
     let access_flags = r.read_u16()?;
     let this_class = r.read_u16()?;
     let super_class = r.read_u16()?;
@@ -62,6 +64,15 @@ pub fn parse_class_file(r: &mut Reader) -> Result<ClassFile> {
     })
 }
 
+/// Check the magic number and read the version.
+pub(crate) fn parse_header(r: &mut Reader) -> Result<classfile::Version> {
+    let magic = r.read_u32()?;
+    if magic != 0xCAFEBABE {
+        return Err(Error::BadMagic(magic));
+    }
+    parse_version(r)
+}
+
 fn parse_version(r: &mut Reader) -> Result<classfile::Version> {
     let minor = r.read_u16()?;
     let major = r.read_u16()?;
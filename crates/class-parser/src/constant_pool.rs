@@ -118,6 +118,14 @@ fn parse_cp_entry(r: &mut Reader) -> Result<Type> {
                 name_and_type_index,
             })
         }
+        Tag::Module => {
+            let name_index = r.read_u16()?;
+            Ok(Type::Module { name_index })
+        }
+        Tag::Package => {
+            let name_index = r.read_u16()?;
+            Ok(Type::Package { name_index })
+        }
     }
 }
 
@@ -28,3 +28,25 @@ pub fn parse(data: &[u8]) -> std::result::Result<classfile::ClassFile, Error> {
 pub fn parse_class(data: &[u8]) -> std::result::Result<classfile::ClassFile, Error> {
     parse(data)
 }
+
+/// Offset of the `access_flags` item, the first byte after the constant pool.
+pub fn constant_pool_end(data: &[u8]) -> std::result::Result<usize, Error> {
+    let mut r = reader::Reader::new(data.to_vec());
+    class::parse_header(&mut r)?;
+    constant_pool::parse_constant_pool(&mut r)?;
+    Ok(r.position())
+}
+
+/// Parse a class file with a constant pool that was parsed before, e.g.
+/// one restored from a shared archive. `cp_end` is the value returned by
+/// `constant_pool_end` for the same bytes.
+pub fn parse_with_cp(
+    data: &[u8],
+    cp: classfile::ConstantPool,
+    cp_end: usize,
+) -> std::result::Result<classfile::ClassFile, Error> {
+    let mut r = reader::Reader::new(data.get(..8).ok_or(Error::UnexpectedEof)?.to_vec());
+    let version = class::parse_header(&mut r)?;
+    let body = data.get(cp_end..).ok_or(Error::UnexpectedEof)?;
+    class::parse_class_body(&mut reader::Reader::new(body.to_vec()), version, cp)
+}
@@ -114,3 +114,20 @@ fn simple_calc_has_constant_pool() {
         .count();
     assert!(utf8_count > 2);
 }
+
+#[test]
+fn parse_with_parsed_constant_pool() {
+    let path = fixture_dir().join("AllTypes.class");
+    let data = fs::read(&path).unwrap();
+    let cf = parse(&data).unwrap();
+
+    let cp_end = class_parser::constant_pool_end(&data).unwrap();
+    let cf2 = class_parser::parse_with_cp(&data, cf.cp.clone(), cp_end).unwrap();
+    assert!(std::sync::Arc::ptr_eq(&cf.cp, &cf2.cp));
+    assert_eq!(cf2.this_class, cf.this_class);
+    assert_eq!(cf2.fields.len(), 12);
+    assert_eq!(cf2.methods.len(), cf.methods.len());
+    assert_eq!(cf2.version.major, cf.version.major);
+
+    assert!(class_parser::parse_with_cp(&data, cf.cp.clone(), data.len() + 1).is_err());
+}
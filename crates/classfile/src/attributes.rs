@@ -75,6 +75,19 @@ pub enum Type {
     MethodParameters {
         parameters: Vec<MethodParameter>,
     },
+    Module(Module),
+    ModulePackages {
+        packages: Vec<U2>,
+    },
+    ModuleMainClass {
+        main_class_index: U2,
+    },
+    NestHost {
+        host_class_index: U2,
+    },
+    NestMembers {
+        classes: Vec<U2>,
+    },
     Unknown,
 }
 
@@ -103,6 +116,11 @@ pub enum Tag {
     AnnotationDefault,
     BootstrapMethods,
     MethodParameters,
+    Module,
+    ModulePackages,
+    ModuleMainClass,
+    NestHost,
+    NestMembers,
     Unknown,
 }
 
@@ -132,6 +150,11 @@ impl From<&[u8]> for Tag {
             b"AnnotationDefault" => Tag::AnnotationDefault,
             b"BootstrapMethods" => Tag::BootstrapMethods,
             b"MethodParameters" => Tag::MethodParameters,
+            b"Module" => Tag::Module,
+            b"ModulePackages" => Tag::ModulePackages,
+            b"ModuleMainClass" => Tag::ModuleMainClass,
+            b"NestHost" => Tag::NestHost,
+            b"NestMembers" => Tag::NestMembers,
             _ => {
                 info!("Unknown attr {}", unsafe {
                     std::str::from_utf8_unchecked(raw)
@@ -417,3 +440,36 @@ pub struct EnclosingMethod {
     pub class_index: U2,
     pub method_index: U2,
 }
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub name_index: U2,
+    pub flags: U2,
+    pub version_index: U2,
+    pub requires: Vec<ModuleRequires>,
+    pub exports: Vec<ModuleExports>,
+    pub opens: Vec<ModuleExports>,
+    pub uses: Vec<U2>,
+    pub provides: Vec<ModuleProvides>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ModuleRequires {
+    pub requires_index: U2,
+    pub requires_flags: U2,
+    pub requires_version_index: U2,
+}
+
+//an 'exports' or 'opens' entry, 'to' is empty when unqualified
+#[derive(Debug, Clone)]
+pub struct ModuleExports {
+    pub package_index: U2,
+    pub flags: U2,
+    pub to: Vec<U2>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleProvides {
+    pub provides_index: U2,
+    pub with: Vec<U2>,
+}
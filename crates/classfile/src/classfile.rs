@@ -1,4 +1,4 @@
-use crate::attributes::InnerClass;
+use crate::attributes::{InnerClass, Module};
 use crate::{attributes::Type, field_info::FieldInfo, method_info::MethodInfo, version::Version};
 use crate::{ConstantPool, U2};
 
@@ -35,4 +35,39 @@ impl ClassFile {
 
         None
     }
+
+    pub fn module(&self) -> Option<&Module> {
+        self.attrs.iter().find_map(|it| match it {
+            Type::Module(module) => Some(module),
+            _ => None,
+        })
+    }
+
+    pub fn module_packages(&self) -> Option<&Vec<U2>> {
+        self.attrs.iter().find_map(|it| match it {
+            Type::ModulePackages { packages } => Some(packages),
+            _ => None,
+        })
+    }
+
+    pub fn module_main_class(&self) -> Option<usize> {
+        self.attrs.iter().find_map(|it| match it {
+            Type::ModuleMainClass { main_class_index } => Some(*main_class_index as usize),
+            _ => None,
+        })
+    }
+
+    pub fn nest_host(&self) -> Option<usize> {
+        self.attrs.iter().find_map(|it| match it {
+            Type::NestHost { host_class_index } => Some(*host_class_index as usize),
+            _ => None,
+        })
+    }
+
+    pub fn nest_members(&self) -> Option<&Vec<U2>> {
+        self.attrs.iter().find_map(|it| match it {
+            Type::NestMembers { classes } => Some(classes),
+            _ => None,
+        })
+    }
 }
@@ -96,6 +96,20 @@ pub fn get_utf8(cp: &ConstantPool, idx: usize) -> &BytesRef {
     }
 }
 
+pub fn get_module_name(cp: &ConstantPool, idx: usize) -> &BytesRef {
+    match cp.get(idx) {
+        Some(Type::Module { name_index }) => get_utf8(cp, *name_index as usize),
+        _ => unreachable!(),
+    }
+}
+
+pub fn get_package_name(cp: &ConstantPool, idx: usize) -> &BytesRef {
+    match cp.get(idx) {
+        Some(Type::Package { name_index }) => get_utf8(cp, *name_index as usize),
+        _ => unreachable!(),
+    }
+}
+
 pub fn get_string(cp: &ConstantPool, idx: usize) -> String {
     match cp.get(idx) {
         Some(Type::String { string_index }) => {
@@ -207,6 +221,12 @@ pub enum Type {
         bootstrap_method_attr_index: u16,
         name_and_type_index: u16,
     },
+    Module {
+        name_index: u16,
+    },
+    Package {
+        name_index: u16,
+    },
     Unknown,
 }
 
@@ -226,6 +246,8 @@ pub enum Tag {
     MethodHandle,
     MethodType,
     InvokeDynamic,
+    Module,
+    Package,
 }
 
 impl From<u8> for Tag {
@@ -245,6 +267,8 @@ impl From<u8> for Tag {
             15 => Tag::MethodHandle,
             16 => Tag::MethodType,
             18 => Tag::InvokeDynamic,
+            19 => Tag::Module,
+            20 => Tag::Package,
             _ => unreachable!(),
         }
     }
@@ -46,6 +46,9 @@ pub const J_NASE: &[u8] = b"java/lang/NegativeArraySizeException";
 pub const J_CCE: &[u8] = b"java/lang/ClassCastException";
 pub const J_NSME: &[u8] = b"java/lang/NoSuchMethodError";
 pub const J_THROWABLE: &[u8] = b"java/lang/Throwable";
+pub const J_IMSE: &[u8] = b"java/lang/IllegalMonitorStateException";
+pub const J_INTERRUPTED_EX: &[u8] = b"java/lang/InterruptedException";
+pub const J_ILLEGAL_ACCESS_ERROR: &[u8] = b"java/lang/IllegalAccessError";
 
 pub const CONSTANT_METHOD_REF_TAG: u8 = 10;
 pub const CONSTANT_INTERFACE_METHOD_REF_TAG: u8 = 11;
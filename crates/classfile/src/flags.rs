@@ -25,4 +25,11 @@ def_acc!(ACC_SYNTHETIC, 0x1000);
 def_acc!(ACC_ANNOTATION, 0x2000);
 def_acc!(ACC_ENUM, 0x4000);
 def_acc!(ACC_MIRANDA, 0x8000);
+def_acc!(ACC_MODULE, 0x8000);
 def_acc!(ACC_REFLECT_MASK, 0xffff);
+
+//module, requires, exports and opens flags of the Module attribute
+def_acc!(ACC_OPEN, 0x0020);
+def_acc!(ACC_TRANSITIVE, 0x0020);
+def_acc!(ACC_STATIC_PHASE, 0x0040);
+def_acc!(ACC_MANDATED, 0x8000);
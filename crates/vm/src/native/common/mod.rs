@@ -1,2 +1,3 @@
 mod check_format;
 pub mod reflect;
+pub mod resource;
@@ -21,9 +21,7 @@ pub fn new_field(fir: FieldIdRef) -> Oop {
     let signature = util::oop::new_java_lang_string2(desc);
 
     let field_name = unsafe { std::str::from_utf8_unchecked(fir.field.name.as_slice()) };
-    let mut desc = Vec::new();
-    desc.push(b'(');
-    let mut args: Vec<Oop> = vec![
+    let mut fields = vec![
         ("clazz", "Ljava/lang/Class;", clazz),
         (
             "name",
@@ -35,13 +33,29 @@ pub fn new_field(fir: FieldIdRef) -> Oop {
         ("slot", "I", Oop::new_int(fir.offset as i32)),
         ("signature", "Ljava/lang/String;", signature),
         ("annotations", "[B", Oop::Null),
-    ]
-    .iter()
-    .map(|(_, t, v)| {
-        desc.extend_from_slice(t.as_bytes());
-        v.clone()
-    })
-    .collect();
+    ];
+
+    //JDK 9+: trustedFinal follows modifiers, set for static final fields
+    let trusted_final_desc =
+        "(Ljava/lang/Class;Ljava/lang/String;Ljava/lang/Class;IZILjava/lang/String;[B)V";
+    let has_trusted_final = field_cls
+        .get_class()
+        .get_this_class_method(&crate::new_br("<init>"), &crate::new_br(trusted_final_desc))
+        .is_ok();
+    if has_trusted_final {
+        let trusted_final = fir.field.is_static() && fir.field.is_final();
+        fields.insert(4, ("trustedFinal", "Z", Oop::new_int(trusted_final as i32)));
+    }
+
+    let mut desc = Vec::new();
+    desc.push(b'(');
+    let mut args: Vec<Oop> = fields
+        .iter()
+        .map(|(_, t, v)| {
+            desc.extend_from_slice(t.as_bytes());
+            v.clone()
+        })
+        .collect();
     desc.extend_from_slice(b")V");
 
     let oop = Oop::new_inst(field_cls.clone());
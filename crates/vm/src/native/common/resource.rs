@@ -0,0 +1,99 @@
+//! Java objects for the resource natives: the class path manager finds
+//! resources by name and returns them as URL strings and bytes.
+
+use crate::native::JNIResult;
+use crate::new_br;
+use crate::oop::{self, Class, Oop};
+use crate::runtime::{self, exception, thread, JavaCall};
+use crate::types::{ClassRef, MethodIdRef};
+use crate::util;
+use std::io;
+
+/// `new URL(spec)`, `None` if the constructor threw.
+pub fn new_url(spec: &str) -> Option<Oop> {
+    let cls = oop::class::load_and_init(b"java/net/URL");
+    let url = Oop::new_inst(cls.clone());
+    let args = vec![url.clone(), util::oop::new_java_lang_string2(spec)];
+    runtime::invoke::invoke_ctor(cls, new_br("(Ljava/lang/String;)V"), args);
+    (!thread::is_meet_ex()).then_some(url)
+}
+
+/// The URL of the first resource found, null if there is none.
+pub fn url_or_null(url: Option<String>) -> JNIResult {
+    match url {
+        Some(url) => Ok(new_url(&url)),
+        None => Ok(Some(Oop::Null)),
+    }
+}
+
+/// An `Enumeration<URL>` of `urls`, in order.
+pub fn new_url_enumeration(urls: Vec<String>) -> JNIResult {
+    let cls = oop::class::load_and_init(b"java/util/Vector");
+    let vector = Oop::new_inst(cls.clone());
+    runtime::invoke::invoke_ctor(cls.clone(), new_br("()V"), vec![vector.clone()]);
+    let add = method(&cls, "addElement", "(Ljava/lang/Object;)V");
+    for url in urls {
+        let url = match new_url(&url) {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+        call(add.clone(), vec![vector.clone(), url])?;
+    }
+    let elements = method(&cls, "elements", "()Ljava/util/Enumeration;");
+    call(elements, vec![vector])
+}
+
+/// A `ByteArrayInputStream` over the content of `url`. A resource that is
+/// gone throws `FileNotFoundException` with `not_found`, other errors throw
+/// `IOException`.
+pub fn open_url(url: &str, not_found: impl FnOnce() -> String) -> JNIResult {
+    let bytes = match runtime::read_resource_url(url) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(exception::new(
+                b"java/io/FileNotFoundException",
+                Some(not_found()),
+            ))
+        }
+        Err(e) => return Err(exception::new(b"java/io/IOException", Some(e.to_string()))),
+    };
+    let cls = oop::class::load_and_init(b"java/io/ByteArrayInputStream");
+    let stream = Oop::new_inst(cls.clone());
+    let args = vec![stream.clone(), Oop::new_byte_ary2(bytes)];
+    runtime::invoke::invoke_ctor(cls, new_br("([B)V"), args);
+    Ok((!thread::is_meet_ex()).then_some(stream))
+}
+
+/// The spec of the `url` field of a `URLConnection`.
+pub fn connection_url(conn: &Oop) -> Option<String> {
+    let cls = oop::class::load_and_init(b"java/net/URLConnection");
+    let fid = cls
+        .get_class()
+        .get_field_id(&new_br("url"), &new_br("Ljava/net/URL;"), false);
+    let url = Class::get_field_value(conn.extract_ref(), fid);
+    let url_cls = oop::class::load_and_init(b"java/net/URL");
+    let to_string = method(&url_cls, "toExternalForm", "()Ljava/lang/String;");
+    match call(to_string, vec![url]) {
+        Ok(Some(spec)) => Some(Oop::java_lang_string(spec.extract_ref())),
+        _ => None,
+    }
+}
+
+fn method(cls: &ClassRef, name: &str, desc: &str) -> MethodIdRef {
+    cls.get_class()
+        .get_virtual_method(&new_br(name), &new_br(desc))
+        .unwrap()
+}
+
+//Ok(None) leaves the exception the method threw pending
+fn call(mir: MethodIdRef, args: Vec<Oop>) -> JNIResult {
+    let is_void = mir.method.desc.ends_with(b")V");
+    let mut jc = JavaCall::new_with_args(mir, args);
+    let area = runtime::DataArea::new(1);
+    jc.invoke(Some(&area), true);
+    if thread::is_meet_ex() || is_void {
+        return Ok(None);
+    }
+    let v = area.stack.borrow_mut().pop_ref();
+    Ok(Some(v))
+}
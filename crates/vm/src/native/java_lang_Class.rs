@@ -95,6 +95,11 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             "()Lsun/reflect/ConstantPool;",
             Box::new(jvm_getConstantPool),
         ),
+        new_fn(
+            "getConstantPool",
+            "()Ljdk/internal/reflect/ConstantPool;",
+            Box::new(jvm_getConstantPool9),
+        ),
         new_fn(
             "getDeclaredClasses0",
             "()[Ljava/lang/Class;",
@@ -105,6 +110,19 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             "()Ljava/lang/String;",
             Box::new(jvm_getGenericSignature0),
         ),
+        // JDK 9+: getName() caches the name computed by initClassName()
+        new_fn(
+            "initClassName",
+            "()Ljava/lang/String;",
+            Box::new(jvm_initClassName),
+        ),
+        new_fn("isHidden", "()Z", Box::new(jvm_isHidden)),
+        new_fn("isRecord0", "()Z", Box::new(jvm_isRecord0)),
+        new_fn(
+            "getProtectionDomain0",
+            "()Ljava/security/ProtectionDomain;",
+            Box::new(jvm_getProtectionDomain0),
+        ),
     ]
 }
 
@@ -119,7 +137,6 @@ static PRIM_MIRROS: OnceLock<RwLock<FxHashMap<String, Oop>>> = OnceLock::new();
 static SIGNATURE_DIC: OnceLock<FxHashMap<&'static str, &'static str>> = OnceLock::new();
 static DELAYED_MIRROS: OnceLock<RwLock<Vec<String>>> = OnceLock::new();
 static DELAYED_ARY_MIRROS: OnceLock<RwLock<Vec<ClassRef>>> = OnceLock::new();
-
 fn build_signature_dic() -> FxHashMap<&'static str, &'static str> {
     [
         ("byte", "B"),
@@ -166,6 +183,9 @@ pub fn create_mirror(cls: ClassRef) {
         trace!("mirror created: {}", unsafe {
             std::str::from_utf8_unchecked(cls.name.as_slice())
         });
+        if let Some(module) = runtime::module::module_of(cls.name.as_slice()) {
+            Class::put_field_value2(mirror.extract_ref(), module_field_offset(), module);
+        }
         cls.set_mirror(mirror);
     } else {
         let cls_back = cls.clone();
@@ -246,6 +266,59 @@ pub fn create_delayed_ary_mirrors() {
     }
 }
 
+/// Set the module of the mirrors made before their module was defined, or
+/// still in the unnamed module; called when java.base is defined and when
+/// the boot layer is complete.
+pub fn update_mirror_modules() {
+    let offset = module_field_offset();
+    let unnamed = runtime::module::boot_unnamed_module();
+    //the module table is read outside the heap lock
+    let pending: Vec<(u32, Option<ClassRef>)> = oop::with_heap(|heap| {
+        let unnamed = match &unnamed {
+            Some(Oop::Ref(rf)) => Some(*rf),
+            _ => None,
+        };
+        heap.iter()
+            .filter_map(|(id, desc)| {
+                let guard = desc.read().unwrap();
+                //the primitive mirrors are made before java/lang/Class has fields
+                match &guard.v {
+                    oop::RefKind::Mirror(mirror) => match mirror.field_values.get(offset) {
+                        Some(Oop::Null) => Some((id, mirror.target.clone())),
+                        Some(Oop::Ref(rf))
+                            if unnamed.is_some_and(|u| heap.is_same_slot(*rf, u)) =>
+                        {
+                            Some((id, mirror.target.clone()))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            })
+            .collect()
+    });
+
+    for (id, target) in pending {
+        let module = match target {
+            Some(cls) => runtime::module::module_of(cls.name.as_slice()),
+            None => runtime::module::find_module("java.base"),
+        };
+        if let Some(module) = module {
+            Class::put_field_value2(id, offset, module);
+        }
+    }
+}
+
+fn module_field_offset() -> usize {
+    let cls = require_class3(None, b"java/lang/Class").unwrap();
+    let fid = cls.get_field_id(
+        util::S_MODULE.get().unwrap(),
+        util::S_JAVA_LANG_MODULE.get().unwrap(),
+        false,
+    );
+    fid.offset
+}
+
 fn jvm_registerNatives(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
     Ok(None)
 }
@@ -334,6 +407,66 @@ fn jvm_getName0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     Ok(Some(v))
 }
 
+fn jvm_initClassName(env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let name = jvm_getName0(env, args)?.unwrap();
+    let this = args.get(0).unwrap();
+    let cls = require_class3(None, cls_consts::J_CLASS).unwrap();
+    let fid = cls.get_class().get_field_id(
+        util::S_NAME.get().unwrap(),
+        util::S_JAVA_LANG_STRING.get().unwrap(),
+        false,
+    );
+    Class::put_field_value2(this.extract_ref(), fid.offset, name.clone());
+    Ok(Some(name))
+}
+
+//hidden classes come from Lookup.defineHiddenClass, which isn't supported
+fn jvm_isHidden(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(0)))
+}
+
+fn jvm_isRecord0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let v = match Oop::mirror_target(args.get(0).unwrap().extract_ref()) {
+        Some(target) => {
+            let cls = target.get_class();
+            match cls.get_super_class() {
+                Some(sup) => (sup.get_class().name.as_slice() == b"java/lang/Record") as i32,
+                None => 0,
+            }
+        }
+        None => 0,
+    };
+    Ok(Some(Oop::new_int(v)))
+}
+
+//classes aren't loaded with a code source
+fn jvm_getProtectionDomain0(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::Null))
+}
+
+//the VM defines the classes of the JDK's own loaders itself, they are
+//looked up like those of the boot loader
+fn is_builtin_loader(loader: &Oop) -> bool {
+    let cls = match loader {
+        Oop::Null => return true,
+        Oop::Ref(rf) => oop::with_heap(|heap| {
+            let desc = heap.get(*rf);
+            let guard = desc.read().unwrap();
+            guard.v.extract_inst().class.clone()
+        }),
+        _ => return false,
+    };
+    let cls = cls.get_class();
+    matches!(
+        cls.name.as_slice(),
+        b"jdk/internal/loader/ClassLoaders$AppClassLoader"
+            | b"jdk/internal/loader/ClassLoaders$PlatformClassLoader"
+            | b"jdk/internal/loader/ClassLoaders$BootClassLoader"
+            | b"sun/misc/Launcher$AppClassLoader"
+            | b"sun/misc/Launcher$ExtClassLoader"
+    )
+}
+
 fn jvm_forName0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let arg0 = args.get(0).unwrap();
     let java_name = {
@@ -345,13 +478,6 @@ fn jvm_forName0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
         arg1.extract_int() != 0
     };
     let java_cls_loader = args.get(2).unwrap();
-    {
-        match java_cls_loader {
-            Oop::Null => (),
-            _ => unimplemented!("app class loader, unimpl"),
-        }
-    }
-
     let _caller_mirror = args.get(3).unwrap();
 
     if java_name.contains('/') {
@@ -360,14 +486,21 @@ fn jvm_forName0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
         return Err(ex);
     }
 
-    let java_name = java_name.replace(".", "/");
-    let cls = {
+    let cls = if is_builtin_loader(java_cls_loader) {
+        let java_name = java_name.replace(".", "/");
         if java_name == "sun/nio/cs/ext/ExtendedCharsets" {
             //fixme: skip, cause jvm start very slow
             None
         } else {
             require_class3(None, java_name.as_bytes())
         }
+    } else {
+        //other loaders define nothing themselves, ask them like HotSpot does
+        match load_with(java_cls_loader, &java_name) {
+            Some(Oop::Null) => None,
+            Some(mirror) => Oop::mirror_target(mirror.extract_ref()),
+            None => return Ok(None),
+        }
     };
 
     match cls {
@@ -389,6 +522,31 @@ fn jvm_forName0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     }
 }
 
+/// `loader.loadClass(name)`, None if it threw.
+fn load_with(loader: &Oop, name: &str) -> Option<Oop> {
+    let cls = oop::with_heap(|heap| {
+        let desc = heap.get(loader.extract_ref());
+        let guard = desc.read().unwrap();
+        guard.v.extract_inst().class.clone()
+    });
+    let mir = cls
+        .get_class()
+        .get_virtual_method(
+            &crate::new_br("loadClass"),
+            &crate::new_br("(Ljava/lang/String;)Ljava/lang/Class;"),
+        )
+        .unwrap();
+    let args = vec![loader.clone(), util::oop::new_java_lang_string2(name)];
+    let mut jc = runtime::invoke::JavaCall::new_with_args(mir, args);
+    let area = runtime::DataArea::new(1);
+    jc.invoke(Some(&area), true);
+    if runtime::thread::is_meet_ex() {
+        return None;
+    }
+    let mirror = area.stack.borrow_mut().pop_ref();
+    Some(mirror)
+}
+
 fn jvm_isPrimitive(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let v = args.get(0).unwrap();
     let v = {
@@ -549,7 +707,8 @@ fn jvm_getComponentType(_env: JNIEnv, args: &[Oop]) -> JNIResult {
         let cls = component.get_class();
         Some(cls.get_mirror())
     } else {
-        unreachable!()
+        //not an array class
+        Some(Oop::Null)
     };
     Ok(v)
 }
@@ -648,26 +807,37 @@ fn jvm_isInstance(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let arg0 = args.get(0).unwrap();
     let arg1 = args.get(1).unwrap();
 
+    //no object is an instance of a primitive type
     let target_cls = {
         let slot_id = arg0.extract_ref();
-        oop::with_heap(|heap| {
+        let target = oop::with_heap(|heap| {
             let desc = heap.get(slot_id);
             let guard = desc.read().unwrap();
             match &guard.v {
-                oop::RefKind::Inst(inst) => inst.class.clone(),
-                oop::RefKind::Mirror(mirror) => mirror.target.clone().unwrap(),
+                oop::RefKind::Inst(inst) => Some(inst.class.clone()),
+                oop::RefKind::Mirror(mirror) => mirror.target.clone(),
                 _ => unreachable!(),
             }
-        })
+        });
+        match target {
+            Some(target) => target,
+            None => return Ok(Some(Oop::new_int(0))),
+        }
     };
-    let obj_cls = {
-        let slot_id = arg1.extract_ref();
-        oop::with_heap(|heap| {
-            let desc = heap.get(slot_id);
-            let guard = desc.read().unwrap();
-            guard.v.extract_inst().class.clone()
-        })
+    let slot_id = match arg1 {
+        Oop::Null => return Ok(Some(Oop::new_int(0))),
+        v => v.extract_ref(),
     };
+    let obj_cls = oop::with_heap(|heap| {
+        let desc = heap.get(slot_id);
+        let guard = desc.read().unwrap();
+        match &guard.v {
+            oop::RefKind::Inst(inst) => inst.class.clone(),
+            oop::RefKind::Array(ary) => ary.class.clone(),
+            oop::RefKind::TypeArray(ary) => require_class3(None, &ary.class_name()).unwrap(),
+            oop::RefKind::Mirror(_) => require_class3(None, cls_consts::J_CLASS).unwrap(),
+        }
+    });
 
     let v = if runtime::cmp::instance_of(obj_cls, target_cls) {
         1
@@ -752,10 +922,19 @@ fn jvm_getRawAnnotations(_env: JNIEnv, args: &[Oop]) -> JNIResult {
 }
 
 fn jvm_getConstantPool(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    new_constant_pool(b"sun/reflect/ConstantPool", args)
+}
+
+// JDK 9+
+fn jvm_getConstantPool9(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    new_constant_pool(b"jdk/internal/reflect/ConstantPool", args)
+}
+
+fn new_constant_pool(cp_cls_name: &[u8], args: &[Oop]) -> JNIResult {
     let this = args.get(0).unwrap();
     let cp_oop = match this {
         Oop::Ref(_rf) => {
-            let cp_cls = require_class3(None, b"sun/reflect/ConstantPool").unwrap();
+            let cp_cls = require_class3(None, cp_cls_name).unwrap();
             let cp_oop = Oop::new_inst(cp_cls.clone());
 
             let cls = cp_cls.get_class();
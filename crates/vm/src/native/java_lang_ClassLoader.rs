@@ -1,8 +1,9 @@
 #![allow(non_snake_case)]
 
+use crate::native::common::resource;
 use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
-use crate::oop::Oop;
-use crate::runtime;
+use crate::oop::{self, Oop};
+use crate::runtime::{self, ResourceScope};
 use crate::util;
 use tracing::{debug, error, info, trace, warn};
 
@@ -24,6 +25,17 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             "(Ljava/lang/String;)Ljava/lang/Class;",
             Box::new(jvm_findBootstrapClass),
         ),
+        //JDK 8: Java methods the VM takes over (see init_vm::hack_classes)
+        new_fn(
+            "getBootstrapResource",
+            "(Ljava/lang/String;)Ljava/net/URL;",
+            Box::new(jvm_getBootstrapResource),
+        ),
+        new_fn(
+            "getBootstrapResources",
+            "(Ljava/lang/String;)Ljava/util/Enumeration;",
+            Box::new(jvm_getBootstrapResources),
+        ),
     ]
 }
 
@@ -53,8 +65,31 @@ fn jvm_findLoadedClass0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     Ok(Some(v))
 }
 
-// fixme: Is this correct? uncertain
+//an instance method in JDK 8 and a static one since, the name is the last
+//argument; the boot loader loads the class if it isn't loaded yet
 fn jvm_findBootstrapClass(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    info!("findBootstrapClass");
-    jvm_findLoadedClass0(_env, args)
+    let name = args.last().unwrap();
+    let name = Oop::java_lang_string(name.extract_ref());
+    info!("findBootstrapClass: {}", name);
+    let name = name.replace('.', util::FILE_SEP);
+    let v = match runtime::require_class3(None, name.as_bytes()) {
+        Some(cls) => {
+            oop::class::init_class(&cls);
+            let cls = cls.get_class();
+            cls.get_mirror()
+        }
+        None => Oop::Null,
+    };
+    Ok(Some(v))
+}
+
+fn jvm_getBootstrapResource(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let name = Oop::java_lang_string(args.first().unwrap().extract_ref());
+    let url = runtime::find_resources(&name, ResourceScope::Boot, false).pop();
+    resource::url_or_null(url)
+}
+
+fn jvm_getBootstrapResources(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let name = Oop::java_lang_string(args.first().unwrap().extract_ref());
+    resource::new_url_enumeration(runtime::find_resources(&name, ResourceScope::Boot, true))
 }
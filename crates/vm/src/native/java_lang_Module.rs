@@ -0,0 +1,99 @@
+#![allow(non_snake_case)]
+
+use crate::native::{java_lang_Class, new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::{self, Oop};
+use crate::runtime::module::{self, ExportTarget};
+use tracing::debug;
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn(
+            "defineModule0",
+            "(Ljava/lang/Module;ZLjava/lang/String;Ljava/lang/String;[Ljava/lang/Object;)V",
+            Box::new(jvm_defineModule0),
+        ),
+        new_fn(
+            "addReads0",
+            "(Ljava/lang/Module;Ljava/lang/Module;)V",
+            Box::new(jvm_addReads0),
+        ),
+        new_fn(
+            "addExports0",
+            "(Ljava/lang/Module;Ljava/lang/String;Ljava/lang/Module;)V",
+            Box::new(jvm_addExports0),
+        ),
+        new_fn(
+            "addExportsToAll0",
+            "(Ljava/lang/Module;Ljava/lang/String;)V",
+            Box::new(jvm_addExportsToAll0),
+        ),
+        new_fn(
+            "addExportsToAllUnnamed0",
+            "(Ljava/lang/Module;Ljava/lang/String;)V",
+            Box::new(jvm_addExportsToAllUnnamed0),
+        ),
+    ]
+}
+
+fn jvm_defineModule0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let module = args.get(0).unwrap();
+    let is_open = args.get(1).unwrap().extract_int() != 0;
+    let packages = args.get(4).unwrap();
+    let packages = match packages {
+        Oop::Null => vec![],
+        Oop::Ref(rf) => {
+            let pkgs = oop::with_heap(|heap| {
+                let desc = heap.get(*rf);
+                let guard = desc.read().unwrap();
+                guard.v.extract_array().elements.clone()
+            });
+            pkgs.iter()
+                .map(|pkg| Oop::java_lang_string(pkg.extract_ref()))
+                .collect()
+        }
+        _ => unreachable!(),
+    };
+    let name = module::module_name(module).unwrap_or_default();
+    debug!("defineModule0: {}", name);
+
+    module::define_module(module.clone(), &name, is_open, packages);
+    if name == "java.base" {
+        java_lang_Class::update_mirror_modules();
+    }
+
+    Ok(None)
+}
+
+//a null 'to' is every unnamed module
+fn jvm_addReads0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let from = args.get(0).unwrap();
+    let to = args.get(1).unwrap();
+    let to = match to {
+        Oop::Null => None,
+        to => Some(to),
+    };
+    module::add_reads(from, to);
+    Ok(None)
+}
+
+fn jvm_addExports0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let pkg = args.get(1).unwrap();
+    let to = args.get(2).unwrap();
+    let pkg = Oop::java_lang_string(pkg.extract_ref());
+    module::add_exports(&pkg, ExportTarget::Module(to));
+    Ok(None)
+}
+
+fn jvm_addExportsToAll0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let pkg = args.get(1).unwrap();
+    let pkg = Oop::java_lang_string(pkg.extract_ref());
+    module::add_exports(&pkg, ExportTarget::All);
+    Ok(None)
+}
+
+fn jvm_addExportsToAllUnnamed0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let pkg = args.get(1).unwrap();
+    let pkg = Oop::java_lang_string(pkg.extract_ref());
+    module::add_exports(&pkg, ExportTarget::AllUnnamed);
+    Ok(None)
+}
@@ -2,6 +2,9 @@
 
 use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
 use crate::oop::{self, Oop};
+use crate::runtime::thread::status;
+use crate::runtime::{self, exception};
+use classfile::consts as cls_const;
 use std::time::Duration;
 
 pub fn get_native_methods() -> Vec<JNINativeMethod> {
@@ -10,8 +13,11 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
         new_fn("hashCode", "()I", Box::new(jvm_hashCode)),
         new_fn("clone", "()Ljava/lang/Object;", Box::new(jvm_clone)),
         new_fn("getClass", "()Ljava/lang/Class;", Box::new(jvm_getClass)),
+        new_fn("notify", "()V", Box::new(jvm_notify)),
         new_fn("notifyAll", "()V", Box::new(jvm_notifyAll)),
         new_fn("wait", "(J)V", Box::new(jvm_wait)),
+        // JDK 21
+        new_fn("wait0", "(J)V", Box::new(jvm_wait)),
     ]
 }
 
@@ -51,9 +57,22 @@ fn jvm_getClass(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     Ok(Some(mirror))
 }
 
+//a thin lock has no waiters: waiting inflates it
+fn jvm_notify(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let this = args.get(0).unwrap();
+    check_owner(this)?;
+    if let Some(monitor) = this.inflated_monitor() {
+        monitor.notify_one();
+    }
+    Ok(None)
+}
+
 fn jvm_notifyAll(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let this = args.get(0).unwrap();
-    this.notify_all();
+    check_owner(this)?;
+    if let Some(monitor) = this.inflated_monitor() {
+        monitor.notify_all();
+    }
     Ok(None)
 }
 
@@ -61,11 +80,50 @@ fn jvm_wait(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let this = args.get(0).unwrap();
     let millis = args.get(1).unwrap().extract_long();
 
-    if millis == 0 {
-        this.wait();
+    if millis < 0 {
+        let ex = exception::new(b"java/lang/IllegalArgumentException", None);
+        return Err(ex);
+    }
+
+    check_owner(this)?;
+    let monitor = this.monitor().unwrap();
+
+    let jt = runtime::thread::current_java_thread();
+    let (parker, timeout) = {
+        let jt = jt.read().unwrap();
+        if millis == 0 {
+            jt.set_status(status::IN_OBJECT_WAIT);
+            (jt.parker.clone(), None)
+        } else {
+            jt.set_status(status::IN_OBJECT_WAIT_TIMED);
+            (
+                jt.parker.clone(),
+                Some(Duration::from_millis(millis as u64)),
+            )
+        }
+    };
+
+    let completed = parker.wait_on(&monitor, timeout);
+
+    {
+        let jt = jt.read().unwrap();
+        jt.set_status(status::RUNNABLE);
+        if !completed {
+            jt.clear_interrupted();
+        }
+    }
+    //the thread must not be locked while the exception is constructed
+    if completed {
+        Ok(None)
     } else {
-        this.wait_timeout(Duration::from_millis(millis as u64));
+        Err(exception::new(cls_const::J_INTERRUPTED_EX, None))
     }
+}
 
-    Ok(None)
+/// `IllegalMonitorStateException` unless the current thread owns the lock of `obj`.
+fn check_owner(obj: &Oop) -> Result<(), Oop> {
+    match obj {
+        Oop::Ref(_) if obj.is_locked_by_current() => Ok(()),
+        _ => Err(exception::new(cls_const::J_IMSE, None)),
+    }
 }
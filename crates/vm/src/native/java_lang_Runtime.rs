@@ -11,6 +11,7 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             Box::new(jvm_availableProcessors),
         ),
         new_fn("gc", "()V", Box::new(jvm_gc)),
+        new_fn("maxMemory", "()J", Box::new(jvm_maxMemory)),
     ]
 }
 
@@ -22,3 +23,8 @@ fn jvm_availableProcessors(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
 fn jvm_gc(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
     Ok(None)
 }
+
+//the heap has no limit
+fn jvm_maxMemory(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_long(i64::MAX)))
+}
@@ -0,0 +1,30 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+use crate::runtime::vm;
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn("halt0", "(I)V", Box::new(jvm_halt0)),
+        // JDK 9+
+        new_fn("beforeHalt", "()V", Box::new(jvm_beforeHalt)),
+        // JDK 8, only with 'runFinalizersOnExit'
+        new_fn("runAllFinalizers", "()V", Box::new(jvm_runAllFinalizers)),
+    ]
+}
+
+//'Runtime.halt' and the end of 'Runtime.exit', after the shutdown hooks
+fn jvm_halt0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let code = args.first().unwrap().extract_int();
+    vm::halt(code)
+}
+
+fn jvm_beforeHalt(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(None)
+}
+
+//fixme: finalizers are never run
+fn jvm_runAllFinalizers(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(None)
+}
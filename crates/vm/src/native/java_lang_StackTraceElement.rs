@@ -0,0 +1,36 @@
+#![allow(non_snake_case)]
+
+use crate::native::{java_lang_Throwable, new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::{self, Oop};
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![new_fn(
+        "initStackTraceElements",
+        "([Ljava/lang/StackTraceElement;Ljava/lang/Throwable;)V",
+        Box::new(jvm_initStackTraceElements),
+    )]
+}
+
+//the elements are made by Throwable.fillInStackTrace already
+fn jvm_initStackTraceElements(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let elements = args.get(0).unwrap().extract_ref();
+    let throwable = args.get(1).unwrap();
+
+    if let Oop::Ref(rf) = java_lang_Throwable::get_backtrace(throwable) {
+        let elms = oop::with_heap(|heap| {
+            let desc = heap.get(rf);
+            let guard = desc.read().unwrap();
+            guard.v.extract_array().elements.clone()
+        });
+        oop::with_heap(|heap| {
+            let desc = heap.get(elements);
+            let mut guard = desc.write().unwrap();
+            let ary = guard.v.extract_mut_array();
+            for (dst, src) in ary.elements.iter_mut().zip(elms.iter()) {
+                *dst = src.clone();
+            }
+        });
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,12 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![new_fn("isBigEndian", "()Z", Box::new(jvm_isBigEndian))]
+}
+
+fn jvm_isBigEndian(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(cfg!(target_endian = "big") as i32)))
+}
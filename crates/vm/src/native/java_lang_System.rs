@@ -40,11 +40,6 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
         ),
         new_fn("nanoTime", "()J", Box::new(jvm_nanoTime)),
         new_fn("currentTimeMillis", "()J", Box::new(jvm_currentTimeMillis)),
-        new_fn(
-            "getProperty",
-            "(Ljava/lang/String;)Ljava/lang/String;",
-            Box::new(jvm_getProperty),
-        ),
     ]
 }
 
@@ -145,8 +140,11 @@ fn jvm_initProperties(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let v = v.to_str().expect("home_dir to_str failed");
     put_props_kv(props_oop, "user.home", v);
 
-    //JAVA_HOME
-    let v = std::env::var("JAVA_HOME").expect("Please Setup JAVA_HOME env");
+    //java.home, the JDK the boot class path was found in
+    let v = runtime::java_home()
+        .map(String::from)
+        .or_else(|| std::env::var("JAVA_HOME").ok())
+        .expect("Please Setup JAVA_HOME env");
     put_props_kv(props_oop, "java.home", v.as_str());
 
     //test.src for jdk/test/java/lang/Character/CheckProp.java
@@ -236,14 +234,12 @@ fn jvm_mapLibraryName(_env: JNIEnv, args: &[Oop]) -> JNIResult {
         name.push_str(&s);
         name.push_str(".dylib");
     } else if cfg!(target_os = "windows") {
-        unimplemented!();
-    //        name.extend_from_slice(s.as_bytes());
-    //        name.extend_from_slice(".dll".as_bytes());
+        name.push_str(&s);
+        name.push_str(".dll");
     } else if cfg!(target_os = "linux") {
-        unimplemented!();
-    //        name.extend_from_slice("lib".as_bytes());
-    //        name.extend_from_slice(s.as_bytes());
-    //        name.extend_from_slice(".so".as_bytes());
+        name.push_str("lib");
+        name.push_str(&s);
+        name.push_str(".so");
     } else {
         unimplemented!()
     }
@@ -262,12 +258,6 @@ fn jvm_identityHashCode(env: JNIEnv, args: &[Oop]) -> JNIResult {
     native::java_lang_Object::jvm_hashCode(env, args)
 }
 
-fn jvm_getProperty(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    // props field is not properly initialized in our minimal JVM,
-    // so just return null for any getProperty call
-    Ok(Some(Oop::Null))
-}
-
 // Copy elements within the same array object (identified by slot_id)
 fn arraycopy_same_obj(slot_id: u32, src_pos: usize, dest_pos: usize, length: usize) {
     let is_type_ary = oop::with_heap(|heap| {
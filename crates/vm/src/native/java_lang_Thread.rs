@@ -1,10 +1,17 @@
 #![allow(non_snake_case)]
 
 use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
-use crate::new_br;
 use crate::oop::{self, Class, Oop};
+use crate::runtime::thread::{
+    get_thread_field, priority, retire_current_worker, safepoint, set_thread_field, status,
+    thread_dump, vm_operation,
+};
 use crate::runtime::vm::get_vm;
-use crate::runtime::{self, vm, JavaCall, JavaThread};
+use crate::runtime::{self, exception, require_class3, vm, JavaCall, JavaThread};
+use crate::types::{JavaThreadRef, MethodIdRef};
+use crate::{new_br, util};
+use classfile::consts as cls_const;
+use std::time::Duration;
 use tracing::{debug, error, info, trace, warn};
 
 pub fn get_native_methods() -> Vec<JNINativeMethod> {
@@ -19,12 +26,42 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
         new_fn("isAlive", "()Z", Box::new(jvm_isAlive)),
         new_fn("start0", "()V", Box::new(jvm_start0)),
         new_fn("isInterrupted", "(Z)Z", Box::new(jvm_isInterrupted)),
-        // JDK 9+: dispatchUncaughtException calls threadState/isTerminated which aren't implemented.
-        // Implement as native to print exception and skip the cascade.
+        new_fn("interrupt0", "()V", Box::new(jvm_interrupt0)),
         new_fn(
-            "dispatchUncaughtException",
-            "(Ljava/lang/Throwable;)V",
-            Box::new(jvm_dispatchUncaughtException),
+            "clearInterruptEvent",
+            "()V",
+            Box::new(jvm_clearInterruptEvent),
+        ),
+        new_fn("sleep", "(J)V", Box::new(jvm_sleep)),
+        // JDK 21: sleep0 takes nanoseconds
+        new_fn("sleep0", "(J)V", Box::new(jvm_sleep0)),
+        new_fn("yield", "()V", Box::new(jvm_yield)),
+        new_fn("yield0", "()V", Box::new(jvm_yield)),
+        new_fn(
+            "holdsLock",
+            "(Ljava/lang/Object;)Z",
+            Box::new(jvm_holdsLock),
+        ),
+        new_fn(
+            "getThreads",
+            "()[Ljava/lang/Thread;",
+            Box::new(jvm_getThreads),
+        ),
+        new_fn(
+            "dumpThreads",
+            "([Ljava/lang/Thread;)[[Ljava/lang/StackTraceElement;",
+            Box::new(jvm_dumpThreads),
+        ),
+        // JDK 19+
+        new_fn(
+            "getStackTrace0",
+            "()Ljava/lang/Object;",
+            Box::new(jvm_getStackTrace0),
+        ),
+        new_fn(
+            "setNativeName",
+            "(Ljava/lang/String;)V",
+            Box::new(jvm_setNativeName),
         ),
     ]
 }
@@ -39,21 +76,25 @@ fn jvm_currentThread(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
     Ok(obj)
 }
 
-fn jvm_setPriority0(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+//a thread not started yet gets its priority in 'start0'
+fn jvm_setPriority0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let this = args.first().unwrap();
+    let priority = args.get(1).unwrap().extract_int();
+    if let Some(jt) = find_thread(this) {
+        jt.read().unwrap().set_priority(priority);
+    }
     Ok(None)
 }
 
 //'_jt' is caller's thread context, can't be used here
-//should find by 'eetop' in thread pool
+//should find by the thread object in thread pool
 fn jvm_isAlive(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let this = args.get(0).unwrap();
-    let eetop = Oop::java_lang_thread_eetop(this.extract_ref());
-    let vm = get_vm();
-
-    let r = match vm.threads.find_java_thread(eetop) {
+    let r = match find_thread(this) {
         Some(jt) => {
-            info!("native thread tag = {}", jt.read().unwrap().tag);
-            if jt.read().unwrap().is_alive {
+            let jt = jt.read().unwrap();
+            info!("native thread tag = {}", jt.tag);
+            if jt.is_alive {
                 1
             } else {
                 0
@@ -80,64 +121,305 @@ fn jvm_start0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     } else {
         let vm = vm::get_vm();
 
+        //setup the thread before it runs, so 'isAlive' and 'join' see it
+        //as soon as 'start0' returns
         let jt = JavaThread::new(None, vm.threads.next_id());
+        {
+            let mut t = jt.write().unwrap();
+            t.java_thread_obj = Some(thread_oop.clone());
+            t.is_alive = true;
+
+            let fid = cls.get_field_id(&new_br("eetop"), &new_br("J"), false);
+            Class::put_field_value2(thread_oop.extract_ref(), fid.offset, Oop::new_long(t.eetop));
+            t.set_status(status::RUNNABLE);
+            t.daemon = matches!(
+                get_thread_field(&thread_oop, "daemon", "Z"),
+                Some(Oop::Int(v)) if v != 0
+            );
+
+            //interrupted before started
+            if matches!(
+                get_thread_field(&thread_oop, "interrupted", "Z"),
+                Some(Oop::Int(v)) if v != 0
+            ) {
+                t.parker.interrupt();
+            }
+        }
         vm.threads.attach_java_thread(jt.clone());
 
         let args = vec![thread_oop.clone()];
         vm.threads.spawn_java_thread(move || {
             //setup current thread
             let current_thread = jt.clone();
+            current_thread.write().unwrap().bind_os_thread();
             runtime::thread::THREAD.with(|t| {
                 *t.borrow_mut() = current_thread;
             });
+            safepoint::attach_current_thread();
+            let worker_nice = priority::current_nice();
+            if let Some(Oop::Int(priority)) = get_thread_field(&thread_oop, "priority", "I") {
+                jt.read().unwrap().set_priority(priority);
+            }
 
-            let cls = clazz.get_class();
-            let mir = {
-                //setup eetop
-                let eetop = jt.read().unwrap().eetop;
-                let fid = cls.get_field_id(&new_br("eetop"), &new_br("J"), false);
-                Class::put_field_value2(thread_oop.extract_ref(), fid.offset, Oop::new_long(eetop));
-
-                //obtain 'run' method
-                cls.get_virtual_method(&new_br("run"), &new_br("()V"))
-                    .unwrap()
-            };
+            //obtain 'run' method
+            let mir = clazz
+                .get_class()
+                .get_virtual_method(&new_br("run"), &new_br("()V"))
+                .unwrap();
 
             //invoke 'run'
             let mut jc = JavaCall::new_with_args(mir, args);
-            jt.write().unwrap().is_alive = true;
-            jt.write().unwrap().java_thread_obj = Some(thread_oop.clone());
             jc.invoke(None, false);
-            jt.write().unwrap().is_alive = false;
+            runtime::thread::exit_thread(&thread_oop);
 
-            //notify thread that invoke 'join'
-            let v = thread_oop.extract_ref();
-            Oop::Ref(v).notify_all();
+            //notify threads that invoke 'join', holding the monitor so that
+            //a joiner between 'isAlive' and 'wait' doesn't miss it
+            let monitor = thread_oop.monitor().unwrap();
+            monitor.lock();
+            {
+                let mut t = jt.write().unwrap();
+                t.is_alive = false;
+                t.set_status(status::TERMINATED);
+            }
+            //JDK 17+ 'isAlive' is 'eetop != 0'
+            set_thread_field(&thread_oop, "eetop", "J", Oop::new_long(0));
+            monitor.notify_all();
+            monitor.unlock();
 
             vm.threads.detach_current_thread();
+
+            //the worker runs other threads next, with its own nice value;
+            //going back to a lower one may be refused, then it's not reused
+            if priority::current_nice() != worker_nice
+                && !priority::set_nice(priority::current_tid(), worker_nice)
+            {
+                retire_current_worker();
+            }
         });
 
         Ok(None)
     }
 }
 
-fn jvm_isInterrupted(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    //todo: fix me
-    let v = Oop::new_int(0);
-    Ok(Some(v))
+fn jvm_isInterrupted(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let this = args.get(0).unwrap();
+    let clear = args.get(1).unwrap().extract_int() != 0;
+    let r = match find_thread(this) {
+        Some(jt) => {
+            let jt = jt.read().unwrap();
+            let v = jt.parker.is_interrupted(false);
+            if v && clear {
+                jt.clear_interrupted();
+            }
+            v
+        }
+        None => false,
+    };
+    Ok(Some(Oop::new_int(r as i32)))
+}
+
+//JDK 14+: 'interrupt' has set the 'interrupted' field, wake the thread up
+fn jvm_interrupt0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let this = args.get(0).unwrap();
+    if let Some(jt) = find_thread(this) {
+        let parker = jt.read().unwrap().parker.clone();
+        parker.interrupt();
+    }
+    Ok(None)
+}
+
+//JDK 14+: 'Thread.interrupted' has cleared the 'interrupted' field
+fn jvm_clearInterruptEvent(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    let jt = runtime::thread::current_java_thread();
+    jt.read().unwrap().parker.is_interrupted(true);
+    Ok(None)
+}
+
+fn jvm_sleep(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let millis = args.get(0).unwrap().extract_long();
+    if millis < 0 {
+        let ex = exception::new(b"java/lang/IllegalArgumentException", None);
+        return Err(ex);
+    }
+    sleep(Duration::from_millis(millis as u64))
 }
 
-fn jvm_dispatchUncaughtException(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let ex = args.get(1).unwrap();
-    let cls = {
-        let rf = ex.extract_ref();
-        oop::with_heap(|heap| {
-            let desc = heap.get(rf);
+fn jvm_sleep0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let nanos = args.get(0).unwrap().extract_long();
+    sleep(Duration::from_nanos(nanos.max(0) as u64))
+}
+
+fn sleep(dur: Duration) -> JNIResult {
+    let jt = runtime::thread::current_java_thread();
+    let parker = {
+        let jt = jt.read().unwrap();
+        jt.set_status(status::SLEEPING);
+        jt.parker.clone()
+    };
+
+    let completed = parker.sleep(dur);
+
+    {
+        let jt = jt.read().unwrap();
+        jt.set_status(status::RUNNABLE);
+        if !completed {
+            jt.clear_interrupted();
+        }
+    }
+    //the thread must not be locked while the exception is constructed
+    if completed {
+        Ok(None)
+    } else {
+        Err(exception::new(cls_const::J_INTERRUPTED_EX, None))
+    }
+}
+
+fn jvm_yield(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    std::thread::yield_now();
+    Ok(None)
+}
+
+fn jvm_holdsLock(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let obj = args.get(0).unwrap();
+    match obj {
+        Oop::Ref(_) => Ok(Some(Oop::new_int(obj.is_locked_by_current() as i32))),
+        _ => Err(exception::new(cls_const::J_NPE, None)),
+    }
+}
+
+fn jvm_getThreads(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    let threads: Vec<Oop> = get_vm()
+        .threads
+        .all()
+        .iter()
+        .filter_map(|jt| {
+            let jt = jt.read().unwrap();
+            if jt.is_alive {
+                jt.java_thread_obj.clone()
+            } else {
+                None
+            }
+        })
+        .collect();
+    let ary_cls = require_class3(None, b"[Ljava/lang/Thread;").unwrap();
+    Ok(Some(Oop::new_ref_ary2(ary_cls, threads)))
+}
+
+fn jvm_dumpThreads(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let threads = args.get(0).unwrap();
+    let threads = match threads {
+        Oop::Ref(rf) => oop::with_heap(|heap| {
+            let desc = heap.get(*rf);
             let guard = desc.read().unwrap();
-            guard.v.extract_inst().class.clone()
+            guard.v.extract_array().elements.clone()
+        }),
+        _ => return Err(exception::new(cls_const::J_NPE, None)),
+    };
+
+    let threads: Vec<Option<JavaThreadRef>> = threads.iter().map(find_thread).collect();
+    let traces = snapshot_frames(threads)
+        .into_iter()
+        .map(|frames| match frames {
+            Some(frames) => build_stack_trace(frames),
+            None => Oop::Null,
         })
+        .collect();
+    let ary_cls = require_class3(None, b"[[Ljava/lang/StackTraceElement;").unwrap();
+    Ok(Some(Oop::new_ref_ary2(ary_cls, traces)))
+}
+
+fn jvm_getStackTrace0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let this = args.get(0).unwrap();
+    let jt = find_thread(this).filter(|jt| jt.read().unwrap().is_alive);
+    let v = match snapshot_frames(vec![jt]).pop().flatten() {
+        Some(frames) => build_stack_trace(frames),
+        None => Oop::Null,
     };
-    let cls_name = String::from_utf8_lossy(cls.get_class().name.as_slice());
-    error!("Uncaught exception: {}", cls_name);
+    Ok(Some(v))
+}
+
+fn jvm_setNativeName(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let this = args.get(0).unwrap();
+    let name = Oop::java_lang_string(args.get(1).unwrap().extract_ref());
+    if let Some(jt) = find_thread(this) {
+        let is_current = std::sync::Arc::ptr_eq(&jt, &runtime::thread::current_java_thread());
+        jt.write().unwrap().tag = name.clone();
+
+        //the OS only allows naming the calling thread
+        #[cfg(target_os = "linux")]
+        if is_current {
+            //linux limits thread names to 15 bytes
+            let mut end = name.len().min(15);
+            while !name.is_char_boundary(end) {
+                end -= 1;
+            }
+            if let Ok(name) = std::ffi::CString::new(&name[..end]) {
+                unsafe {
+                    libc::pthread_setname_np(libc::pthread_self(), name.as_ptr());
+                }
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = is_current;
+    }
     Ok(None)
 }
+
+fn find_thread(thread: &Oop) -> Option<JavaThreadRef> {
+    get_vm().threads.find_by_thread_obj(thread)
+}
+
+/// Take the (method, pc) of every frame, innermost first, with all threads
+/// stopped at a safepoint so that the stacks are consistent.
+fn snapshot_frames(threads: Vec<Option<JavaThreadRef>>) -> Vec<Option<Vec<(MethodIdRef, i32)>>> {
+    vm_operation::execute("ThreadDump", move || {
+        threads
+            .iter()
+            .map(|jt| {
+                jt.as_ref()
+                    .map(|jt| thread_dump::snapshot_frames(&jt.read().unwrap()))
+            })
+            .collect()
+    })
+}
+
+/// Build `StackTraceElement[]` of `frames`, the innermost frame first.
+fn build_stack_trace(frames: Vec<(MethodIdRef, i32)>) -> Oop {
+    let cls = require_class3(None, b"java/lang/StackTraceElement").unwrap();
+    oop::class::init_class(&cls);
+    oop::class::init_class_fully(&cls);
+
+    let mut elms = Vec::with_capacity(frames.len());
+    for (mir, pc) in frames {
+        let method = &mir.method;
+        let cls_name = String::from_utf8_lossy(method.cls_name.as_slice()).replace('/', ".");
+        let name = String::from_utf8_lossy(method.name.as_slice()).to_string();
+        let file = method.class.get_class().get_source_file();
+        let line = if method.is_native() {
+            -2
+        } else {
+            method.get_line_num(pc.max(0) as u16)
+        };
+
+        let elm = Oop::new_inst(cls.clone());
+        let args = vec![
+            elm.clone(),
+            util::oop::new_java_lang_string2(&cls_name),
+            util::oop::new_java_lang_string2(&name),
+            match file {
+                Some(file) => util::oop::new_java_lang_string2(&String::from_utf8_lossy(&file)),
+                None => Oop::Null,
+            },
+            Oop::new_int(line),
+        ];
+        runtime::invoke::invoke_ctor(
+            cls.clone(),
+            new_br("(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;I)V"),
+            args,
+        );
+        elms.push(elm);
+    }
+
+    let ary_cls = require_class3(None, b"[Ljava/lang/StackTraceElement;").unwrap();
+    Oop::new_ref_ary2(ary_cls, elms)
+}
@@ -1,8 +1,11 @@
 #![allow(non_snake_case)]
 
 use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
-use crate::new_br;
 use crate::oop::{self, Class, Oop};
+use crate::runtime::thread::thread_dump;
+use crate::runtime::{self, require_class3};
+use crate::types::{ClassRef, MethodIdRef};
+use crate::{new_br, util};
 
 pub fn get_native_methods() -> Vec<JNINativeMethod> {
     vec![
@@ -30,11 +33,9 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
     ]
 }
 
+/// Records the stack as a `StackTraceElement[]` in `backtrace`, without the
+/// frames that fill it in and construct the throwable, as HotSpot does.
 fn jvm_fillInStackTrace(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    Ok(Some(args.get(0).unwrap().clone()))
-}
-
-fn jvm_getStackTraceDepth(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let throwable = args.get(0).unwrap();
     let cls = {
         let rf = throwable.extract_ref();
@@ -44,12 +45,96 @@ fn jvm_getStackTraceDepth(_env: JNIEnv, args: &[Oop]) -> JNIResult {
             guard.v.extract_inst().class.clone()
         })
     };
-    let backtrace = {
-        let cls = cls.get_class();
-        let id = cls.get_field_id(&new_br("backtrace"), &new_br("Ljava/lang/Object;"), false);
-        Class::get_field_value(throwable.extract_ref(), id)
+
+    let jt = runtime::thread::current_java_thread();
+    let frames = thread_dump::snapshot_frames(&jt.read().unwrap());
+    let mut frames = frames.iter().peekable();
+    while let Some((mir, _)) = frames.peek() {
+        if mir.method.name.as_slice() != b"fillInStackTrace" {
+            break;
+        }
+        frames.next();
+    }
+    while let Some((mir, _)) = frames.peek() {
+        let method = &mir.method;
+        if method.name.as_slice() != b"<init>"
+            || !runtime::cmp::check_inherit(cls.clone(), method.class.clone())
+        {
+            break;
+        }
+        frames.next();
+    }
+
+    let ste_cls = require_class3(None, b"java/lang/StackTraceElement").unwrap();
+    let elms: Vec<Oop> = frames
+        .map(|(mir, pc)| new_stack_trace_element(&ste_cls, mir, *pc))
+        .collect();
+    let depth = elms.len();
+    let ary_cls = require_class3(None, b"[Ljava/lang/StackTraceElement;").unwrap();
+    let backtrace = Oop::new_ref_ary2(ary_cls, elms);
+
+    let cls = cls.get_class();
+    let id = cls.get_field_id(&new_br("backtrace"), &new_br("Ljava/lang/Object;"), false);
+    Class::put_field_value2(throwable.extract_ref(), id.offset, backtrace);
+    //JDK 9+
+    if let Ok(id) = cls.get_field_id_safe(&new_br("depth"), &new_br("I"), false) {
+        Class::put_field_value2(
+            throwable.extract_ref(),
+            id.offset,
+            Oop::new_int(depth as i32),
+        );
+    }
+
+    Ok(Some(throwable.clone()))
+}
+
+fn new_stack_trace_element(ste_cls: &ClassRef, mir: &MethodIdRef, pc: i32) -> Oop {
+    let method = &mir.method;
+    let holder = method.class.get_class();
+    let class_name = String::from_utf8_lossy(holder.name.as_slice()).replace('/', ".");
+    let file_name = holder
+        .get_source_file()
+        .map(|file| util::oop::new_java_lang_string3(file.as_slice()))
+        .unwrap_or(Oop::Null);
+    let line_num = if method.is_native() {
+        -2
+    } else {
+        //'pc' has moved past the invoke, or the instruction that threw
+        method.get_line_num((pc - 1).max(0) as u16)
     };
 
+    let ste = Oop::new_inst(ste_cls.clone());
+    let rf = ste.extract_ref();
+    let put = |name: &str, desc: &str, v: Oop| {
+        if let Ok(id) = ste_cls.get_field_id_safe(&new_br(name), &new_br(desc), false) {
+            Class::put_field_value2(rf, id.offset, v);
+        }
+    };
+    put(
+        "declaringClass",
+        "Ljava/lang/String;",
+        util::oop::new_java_lang_string2(&class_name),
+    );
+    put(
+        "methodName",
+        "Ljava/lang/String;",
+        util::oop::new_java_lang_string3(method.name.as_slice()),
+    );
+    put("fileName", "Ljava/lang/String;", file_name);
+    put("lineNumber", "I", Oop::new_int(line_num));
+    //JDK 9+, dropped once StackTraceElement.computeFormat has used it
+    put(
+        "declaringClassObject",
+        "Ljava/lang/Class;",
+        holder.get_mirror(),
+    );
+    ste
+}
+
+fn jvm_getStackTraceDepth(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let throwable = args.get(0).unwrap();
+    let backtrace = get_backtrace(throwable);
+
     let v = match backtrace {
         Oop::Null => Oop::new_int(0),
         Oop::Ref(rf) => {
@@ -69,19 +154,7 @@ fn jvm_getStackTraceDepth(_env: JNIEnv, args: &[Oop]) -> JNIResult {
 fn jvm_getStackTraceElement(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let throwable = args.get(0).unwrap();
     let index = args.get(1).unwrap().extract_int();
-    let cls = {
-        let rf = throwable.extract_ref();
-        oop::with_heap(|heap| {
-            let desc = heap.get(rf);
-            let guard = desc.read().unwrap();
-            guard.v.extract_inst().class.clone()
-        })
-    };
-    let backtrace = {
-        let cls = cls.get_class();
-        let id = cls.get_field_id(&new_br("backtrace"), &new_br("Ljava/lang/Object;"), false);
-        Class::get_field_value(throwable.extract_ref(), id)
-    };
+    let backtrace = get_backtrace(throwable);
 
     let v = {
         let rf = backtrace.extract_ref();
@@ -99,3 +172,18 @@ fn jvm_getStackTraceElement(_env: JNIEnv, args: &[Oop]) -> JNIResult {
 
     Ok(Some(v))
 }
+
+/// The `StackTraceElement[]` recorded by `fillInStackTrace`, or null.
+pub fn get_backtrace(throwable: &Oop) -> Oop {
+    let cls = {
+        let rf = throwable.extract_ref();
+        oop::with_heap(|heap| {
+            let desc = heap.get(rf);
+            let guard = desc.read().unwrap();
+            guard.v.extract_inst().class.clone()
+        })
+    };
+    let cls = cls.get_class();
+    let id = cls.get_field_id(&new_br("backtrace"), &new_br("Ljava/lang/Object;"), false);
+    Class::get_field_value(throwable.extract_ref(), id)
+}
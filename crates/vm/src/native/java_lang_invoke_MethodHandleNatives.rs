@@ -0,0 +1,193 @@
+#![allow(non_snake_case)]
+
+use crate::native::sun_misc_Unsafe::STATIC_FIELD_TAG;
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::new_br;
+use crate::oop::{Class, Oop};
+use crate::runtime::{exception, require_class3};
+use crate::types::FieldIdRef;
+use classfile::flags::{ACC_FINAL, ACC_STATIC};
+
+//MethodHandleNatives.Constants
+const MN_IS_FIELD: i32 = 0x0004_0000;
+const MN_TRUSTED_FINAL: i32 = 0x0020_0000;
+const MN_REFERENCE_KIND_SHIFT: i32 = 24;
+const MN_REFERENCE_KIND_MASK: i32 = 0x0F;
+const REF_GET_FIELD: i32 = 1;
+const REF_GET_STATIC: i32 = 2;
+const REF_PUT_FIELD: i32 = 3;
+const REF_PUT_STATIC: i32 = 4;
+
+/// Only field members resolve, which is what `findVarHandle` and
+/// `findStaticVarHandle` need; `VarHandles.makeFieldHandle` builds the
+/// handle from the offsets below. Method and constructor handles are not
+/// supported.
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn("registerNatives", "()V", Box::new(jvm_registerNatives)),
+        new_fn(
+            "resolve",
+            "(Ljava/lang/invoke/MemberName;Ljava/lang/Class;IZ)Ljava/lang/invoke/MemberName;",
+            Box::new(jvm_resolve),
+        ),
+        new_fn(
+            "objectFieldOffset",
+            "(Ljava/lang/invoke/MemberName;)J",
+            Box::new(jvm_objectFieldOffset),
+        ),
+        new_fn(
+            "staticFieldOffset",
+            "(Ljava/lang/invoke/MemberName;)J",
+            Box::new(jvm_staticFieldOffset),
+        ),
+        new_fn(
+            "staticFieldBase",
+            "(Ljava/lang/invoke/MemberName;)Ljava/lang/Object;",
+            Box::new(jvm_staticFieldBase),
+        ),
+    ]
+}
+
+fn jvm_registerNatives(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(None)
+}
+
+fn jvm_resolve(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let mn = args.get(0).unwrap();
+    let speculative = args.get(3).unwrap().extract_int() != 0;
+    let flags = get_member(mn, "flags", "I").extract_int();
+
+    let fid = if flags & MN_IS_FIELD != 0 {
+        find_field(mn)
+    } else {
+        None
+    };
+    let fid = match fid {
+        Some(fid) => fid,
+        None if speculative => return Ok(Some(Oop::Null)),
+        None if flags & MN_IS_FIELD != 0 => {
+            let name = Oop::java_lang_string(get_member(mn, "name", "Ljava/lang/String;").extract_ref());
+            return Err(exception::new(b"java/lang/NoSuchFieldError", Some(name)));
+        }
+        None => {
+            return Err(exception::new(
+                b"java/lang/LinkageError",
+                Some(String::from("method handles are not supported")),
+            ))
+        }
+    };
+
+    //the reference kind follows the field, a getter stays a getter
+    let is_static = fid.field.is_static();
+    let ref_kind = (flags >> MN_REFERENCE_KIND_SHIFT) & MN_REFERENCE_KIND_MASK;
+    let is_setter = ref_kind == REF_PUT_FIELD || ref_kind == REF_PUT_STATIC;
+    let ref_kind = match (is_static, is_setter) {
+        (false, false) => REF_GET_FIELD,
+        (true, false) => REF_GET_STATIC,
+        (false, true) => REF_PUT_FIELD,
+        (true, true) => REF_PUT_STATIC,
+    };
+    let acc_flags = fid.field.acc_flags;
+    let mut flags = MN_IS_FIELD | (ref_kind << MN_REFERENCE_KIND_SHIFT) | acc_flags as i32;
+    if acc_flags & ACC_FINAL != 0 && acc_flags & ACC_STATIC != 0 {
+        flags |= MN_TRUSTED_FINAL;
+    }
+    put_member(mn, "flags", "I", Oop::new_int(flags));
+    let clazz = fid.field.class.get_class().get_mirror();
+    put_member(mn, "clazz", "Ljava/lang/Class;", clazz);
+
+    Ok(Some(mn.clone()))
+}
+
+fn jvm_objectFieldOffset(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let fid = resolved_field(args.get(0).unwrap())?;
+    Ok(Some(Oop::new_long(fid.offset as i64)))
+}
+
+fn jvm_staticFieldOffset(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let fid = resolved_field(args.get(0).unwrap())?;
+    Ok(Some(Oop::new_long(fid.offset as i64 | STATIC_FIELD_TAG)))
+}
+
+//static fields live in the declaring class, Unsafe addresses them through its mirror
+fn jvm_staticFieldBase(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let fid = resolved_field(args.get(0).unwrap())?;
+    Ok(Some(fid.field.class.get_class().get_mirror()))
+}
+
+fn resolved_field(mn: &Oop) -> Result<FieldIdRef, Oop> {
+    find_field(mn).ok_or_else(|| {
+        exception::new(
+            b"java/lang/InternalError",
+            Some(String::from("not a resolved field")),
+        )
+    })
+}
+
+/// The field a `MemberName` names: `clazz` or one of its super classes
+/// declares it, `type` is its `Class` or its descriptor.
+fn find_field(mn: &Oop) -> Option<FieldIdRef> {
+    let clazz = get_member(mn, "clazz", "Ljava/lang/Class;");
+    let name = get_member(mn, "name", "Ljava/lang/String;");
+    let typ = get_member(mn, "type", "Ljava/lang/Object;");
+    if clazz.is_null() || name.is_null() || typ.is_null() {
+        return None;
+    }
+
+    let cls = Oop::mirror_target(clazz.extract_ref())?;
+    let name = new_br(&Oop::java_lang_string(name.extract_ref()));
+    let desc = if Oop::is_java_lang_string(typ.extract_ref()) {
+        Oop::java_lang_string(typ.extract_ref()).into_bytes()
+    } else {
+        type_desc(&typ)?
+    };
+    let desc = std::sync::Arc::new(desc);
+
+    let cls = cls.get_class();
+    if !cls.is_instance() {
+        return None;
+    }
+    cls.get_field_id_safe(&name, &desc, false)
+        .or_else(|_| cls.get_field_id_safe(&name, &desc, true))
+        .ok()
+}
+
+/// The descriptor of the type a `Class` mirror stands for.
+fn type_desc(mirror: &Oop) -> Option<Vec<u8>> {
+    let (target, vt) = Oop::mirror_target_and_vt(mirror.extract_ref());
+    match target {
+        Some(cls) => {
+            let cls = cls.get_class();
+            let name = cls.name.as_slice();
+            if name.first() == Some(&b'[') {
+                Some(name.to_vec())
+            } else {
+                let mut desc = Vec::with_capacity(name.len() + 2);
+                desc.push(b'L');
+                desc.extend_from_slice(name);
+                desc.push(b';');
+                Some(desc)
+            }
+        }
+        None => {
+            let desc: &[u8] = vt.into();
+            Some(desc.to_vec())
+        }
+    }
+}
+
+fn get_member(mn: &Oop, name: &str, desc: &str) -> Oop {
+    let cls = require_class3(None, b"java/lang/invoke/MemberName").unwrap();
+    let fid = cls
+        .get_class()
+        .get_field_id(&new_br(name), &new_br(desc), false);
+    Class::get_field_value(mn.extract_ref(), fid)
+}
+
+fn put_member(mn: &Oop, name: &str, desc: &str, v: Oop) {
+    let cls = require_class3(None, b"java/lang/invoke/MemberName").unwrap();
+    let fid = cls
+        .get_class()
+        .get_field_id(&new_br(name), &new_br(desc), false);
+    Class::put_field_value2(mn.extract_ref(), fid.offset, v);
+}
@@ -0,0 +1,206 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNIResult};
+use crate::new_br;
+use crate::oop::{self, Oop};
+use crate::runtime::method::{Method, MethodId};
+use crate::runtime::{self, exception, require_class3, thread, DataArea, JavaCall};
+use crate::types::{ClassRef, MethodIdRef};
+use class_parser::MethodSignature;
+use classfile::{BytesRef, SignatureType};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+/// Link a call site of a signature polymorphic access method, such as
+/// `VarHandle.compareAndSet:(LFoo;II)Z`, to a native method with the call
+/// site's descriptor.
+///
+/// Every concrete `VarHandle` class (`VarHandleInts$FieldInstanceReadWrite`,
+/// `VarHandleReferences$Array`, ...) implements an access mode as a static
+/// method taking the handle followed by the coordinates and values. The
+/// native method picks that method by the class of the handle it is called
+/// on and converts the arguments and the result between the two types,
+/// where HotSpot would go through the handle's `VarForm` and lambda forms.
+pub fn link(name: &BytesRef, desc: &BytesRef) -> Option<MethodIdRef> {
+    let var_handle = require_class3(None, b"java/lang/invoke/VarHandle")?;
+    let polymorphic = var_handle
+        .get_class()
+        .get_all_methods()?
+        .into_iter()
+        .find(|((n, d), m)| {
+            n == name && d.starts_with(b"([Ljava/lang/Object;)") && m.method.is_native()
+        })
+        .map(|(_, m)| m)?;
+
+    let mut method: Method = polymorphic.method.clone();
+    method.desc = desc.clone();
+    method.signature = MethodSignature::new(desc.as_slice());
+
+    let access = Access {
+        name: name.clone(),
+        signature: method.signature.clone(),
+        targets: Mutex::new(Vec::new()),
+    };
+    let native_impl = new_fn(
+        "<access mode>",
+        "([Ljava/lang/Object;)Ljava/lang/Object;",
+        Box::new(move |env, args| access.invoke(env, args)),
+    );
+
+    Some(Arc::new(MethodId {
+        offset: polymorphic.offset,
+        method,
+        native_impl: Some(native_impl),
+        jit_impl: Mutex::new(None),
+        not_compilable: AtomicBool::new(false),
+        intrinsic: None,
+    }))
+}
+
+/// An access mode invoked with the types of one call site.
+struct Access {
+    name: BytesRef,
+    signature: MethodSignature,
+    /// The implementing method per handle class seen at the call site
+    targets: Mutex<Vec<(ClassRef, MethodIdRef)>>,
+}
+
+impl Access {
+    fn invoke(&self, _env: JNIEnv, args: &[Oop]) -> JNIResult {
+        let handle_cls = oop::with_heap(|heap| {
+            let desc = heap.get(args[0].extract_ref());
+            let guard = desc.read().unwrap();
+            guard.v.extract_inst().class.clone()
+        });
+        let target = match self.target(&handle_cls) {
+            Some(target) => target,
+            None => {
+                let msg = String::from_utf8_lossy(self.name.as_slice()).to_string();
+                return Err(exception::new(
+                    b"java/lang/UnsupportedOperationException",
+                    Some(msg),
+                ));
+            }
+        };
+
+        let mut target_args = Vec::with_capacity(args.len());
+        target_args.push(args[0].clone());
+        let types = self.signature.args.iter().zip(&target.method.signature.args[1..]);
+        for (v, (from, to)) in args[1..].iter().zip(types) {
+            match convert(v, from, to) {
+                Some(v) => target_args.push(v),
+                None => return Err(wrong_method_type(from, to)),
+            }
+        }
+
+        let retype = &target.method.signature.retype;
+        let mut jc = JavaCall::new_with_args(target.clone(), target_args);
+        let area = DataArea::new(2);
+        jc.invoke(Some(&area), true);
+        if thread::is_meet_ex() {
+            return Ok(None);
+        }
+
+        let v = {
+            let mut stack = area.stack.borrow_mut();
+            match retype {
+                SignatureType::Void => return Ok(None),
+                SignatureType::Long => Oop::new_long(stack.pop_long()),
+                SignatureType::Float => Oop::new_float(stack.pop_float()),
+                SignatureType::Double => Oop::new_double(stack.pop_double()),
+                SignatureType::Object(..) | SignatureType::Array(_) => stack.pop_ref(),
+                _ => Oop::new_int(stack.pop_int()),
+            }
+        };
+        match &self.signature.retype {
+            SignatureType::Void => Ok(None),
+            to => convert(&v, retype, to)
+                .map(Some)
+                .ok_or_else(|| wrong_method_type(retype, to)),
+        }
+    }
+
+    fn target(&self, handle_cls: &ClassRef) -> Option<MethodIdRef> {
+        let mut targets = self.targets.lock().unwrap();
+        if let Some((_, mir)) = targets.iter().find(|(cls, _)| Arc::ptr_eq(cls, handle_cls)) {
+            return Some(mir.clone());
+        }
+
+        //the handle, then the arguments of the call site
+        let arity = self.signature.args.len() + 1;
+        let mut cur = Some(handle_cls.clone());
+        while let Some(cls) = cur {
+            let cls = cls.get_class();
+            let found = cls.get_all_methods().and_then(|methods| {
+                methods.into_values().find(|m| {
+                    m.method.is_static()
+                        && m.method.name == self.name
+                        && m.method.signature.args.len() == arity
+                })
+            });
+            if let Some(mir) = found {
+                targets.push((handle_cls.clone(), mir.clone()));
+                return Some(mir);
+            }
+            cur = cls.get_super_class();
+        }
+        None
+    }
+}
+
+/// Convert a value between the type of the call site and the type of the
+/// access method: the same kind, a widening primitive conversion, or
+/// boxing. `None` if the types don't convert.
+fn convert(v: &Oop, from: &SignatureType, to: &SignatureType) -> Option<Oop> {
+    use SignatureType::*;
+    let is_ref = |t: &SignatureType| matches!(t, Object(..) | Array(_));
+    let is_int = |t: &SignatureType| matches!(t, Byte | Boolean | Char | Short | Int);
+    match (from, to) {
+        (f, t) if is_ref(f) && is_ref(t) => Some(v.clone()),
+        (f, t) if is_int(f) && is_int(t) => Some(v.clone()),
+        (Long, Long) | (Float, Float) | (Double, Double) => Some(v.clone()),
+        (f, Long) if is_int(f) => Some(Oop::new_long(v.extract_int() as i64)),
+        (f, Float) if is_int(f) => Some(Oop::new_float(v.extract_int() as f32)),
+        (f, Double) if is_int(f) => Some(Oop::new_double(v.extract_int() as f64)),
+        (Long, Float) => Some(Oop::new_float(v.extract_long() as f32)),
+        (Long, Double) => Some(Oop::new_double(v.extract_long() as f64)),
+        (Float, Double) => Some(Oop::new_double(v.extract_float() as f64)),
+        (f, t) if is_ref(t) => box_value(v, f),
+        _ => None,
+    }
+}
+
+/// `valueOf` of the wrapper class of a primitive type.
+fn box_value(v: &Oop, t: &SignatureType) -> Option<Oop> {
+    let (wrapper, desc) = match t {
+        SignatureType::Boolean => ("java/lang/Boolean", "(Z)Ljava/lang/Boolean;"),
+        SignatureType::Byte => ("java/lang/Byte", "(B)Ljava/lang/Byte;"),
+        SignatureType::Char => ("java/lang/Character", "(C)Ljava/lang/Character;"),
+        SignatureType::Short => ("java/lang/Short", "(S)Ljava/lang/Short;"),
+        SignatureType::Int => ("java/lang/Integer", "(I)Ljava/lang/Integer;"),
+        SignatureType::Long => ("java/lang/Long", "(J)Ljava/lang/Long;"),
+        SignatureType::Float => ("java/lang/Float", "(F)Ljava/lang/Float;"),
+        SignatureType::Double => ("java/lang/Double", "(D)Ljava/lang/Double;"),
+        _ => return None,
+    };
+    let cls = require_class3(None, wrapper.as_bytes())?;
+    let mir = cls
+        .get_class()
+        .get_static_method(&new_br("valueOf"), &new_br(desc))
+        .ok()?;
+    let area = DataArea::new(1);
+    let mut jc = JavaCall::new_with_args(mir, vec![v.clone()]);
+    jc.invoke(Some(&area), true);
+    if runtime::thread::is_meet_ex() {
+        return Some(Oop::Null);
+    }
+    let v = area.stack.borrow_mut().pop_ref();
+    Some(v)
+}
+
+fn wrong_method_type(from: &SignatureType, to: &SignatureType) -> Oop {
+    exception::new(
+        b"java/lang/invoke/WrongMethodTypeException",
+        Some(format!("cannot convert {:?} to {:?}", from, to)),
+    )
+}
@@ -0,0 +1,72 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::new_br;
+use crate::oop::{self, Class, Oop};
+use crate::runtime::{self, require_class3};
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn(
+            "getAndClearReferencePendingList",
+            "()Ljava/lang/ref/Reference;",
+            Box::new(jvm_getAndClearReferencePendingList),
+        ),
+        new_fn(
+            "hasReferencePendingList",
+            "()Z",
+            Box::new(jvm_hasReferencePendingList),
+        ),
+        new_fn(
+            "waitForReferencePendingList",
+            "()V",
+            Box::new(jvm_waitForReferencePendingList),
+        ),
+        new_fn(
+            "refersTo0",
+            "(Ljava/lang/Object;)Z",
+            Box::new(jvm_refersTo0),
+        ),
+        new_fn("clear0", "()V", Box::new(jvm_clear0)),
+    ]
+}
+
+//there's no collector, so no reference is ever pending
+fn jvm_getAndClearReferencePendingList(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::Null))
+}
+
+fn jvm_hasReferencePendingList(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(0)))
+}
+
+//the Reference Handler thread waits here forever
+fn jvm_waitForReferencePendingList(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    runtime::thread::safepoint::blocked(|| loop {
+        std::thread::park();
+    })
+}
+
+fn jvm_refersTo0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let this = args.get(0).unwrap();
+    let o = args.get(1).unwrap();
+    let referent = Class::get_field_value2(this.extract_ref(), referent_offset());
+    let v = match (&referent, o) {
+        (Oop::Null, Oop::Null) => true,
+        (Oop::Ref(a), Oop::Ref(b)) => oop::with_heap(|heap| heap.is_same_slot(*a, *b)),
+        _ => false,
+    };
+    Ok(Some(Oop::new_int(v as i32)))
+}
+
+fn jvm_clear0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let this = args.get(0).unwrap();
+    Class::put_field_value2(this.extract_ref(), referent_offset(), Oop::Null);
+    Ok(None)
+}
+
+fn referent_offset() -> usize {
+    let cls = require_class3(None, b"java/lang/ref/Reference").unwrap();
+    let fid = cls.get_field_id(&new_br("referent"), &new_br("Ljava/lang/Object;"), false);
+    fid.offset
+}
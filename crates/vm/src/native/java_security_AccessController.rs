@@ -23,6 +23,11 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             Box::new(jvm_getStackAccessControlContext),
         ),
         new_fn("doPrivileged", "(Ljava/security/PrivilegedExceptionAction;Ljava/security/AccessControlContext;)Ljava/lang/Object;", Box::new(jvm_doPrivileged3)),
+        new_fn(
+            "ensureMaterializedForStackWalk",
+            "(Ljava/lang/Object;)V",
+            Box::new(jvm_ensureMaterializedForStackWalk),
+        ),
     ]
 }
 
@@ -76,3 +81,9 @@ fn jvm_doPrivileged3(env: JNIEnv, args: &[Oop]) -> JNIResult {
 fn jvm_getStackAccessControlContext(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
     Ok(Some(Oop::Null))
 }
+
+//JDK 9+: keeps the context of executePrivileged alive for stack walks,
+//which never see it here since getStackAccessControlContext is null
+fn jvm_ensureMaterializedForStackWalk(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(None)
+}
@@ -0,0 +1,58 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::{self, Oop};
+use crate::runtime::{self, RuntimeImage};
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// The mapped boot image, kept for the life of the VM because the
+/// `DirectByteBuffer` handed to `BasicImageReader` points into it.
+static IMAGE: OnceLock<Option<Mmap>> = OnceLock::new();
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![new_fn(
+        "getNativeMap",
+        "(Ljava/lang/String;)Ljava/nio/ByteBuffer;",
+        Box::new(jvm_getNativeMap),
+    )]
+}
+
+//Like libjimage, only the runtime image the VM booted from is mapped;
+//for any other file null makes BasicImageReader open it itself.
+fn jvm_getNativeMap(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let path = Oop::java_lang_string(args.first().unwrap().extract_ref());
+    let boot =
+        runtime::java_home().and_then(|home| match runtime::probe_runtime_image(Path::new(home)) {
+            Some(RuntimeImage::JImage(modules)) => Some(modules),
+            _ => None,
+        });
+    if boot.as_deref() != Some(Path::new(&path)) {
+        return Ok(Some(Oop::Null));
+    }
+
+    let map = IMAGE.get_or_init(|| {
+        let file = File::open(&path).ok()?;
+        unsafe { Mmap::map(&file) }.ok()
+    });
+    let map = match map {
+        Some(map) if map.len() <= i32::MAX as usize => map,
+        _ => return Ok(Some(Oop::Null)),
+    };
+
+    //DirectByteBuffer(long addr, int cap), the constructor JNI's NewDirectByteBuffer uses
+    let cls = runtime::require_class3(None, b"java/nio/DirectByteBuffer").unwrap();
+    oop::class::init_class(&cls);
+    oop::class::init_class_fully(&cls);
+    let buf = Oop::new_inst(cls.clone());
+    let args = vec![
+        buf.clone(),
+        Oop::new_long(map.as_ptr() as i64),
+        Oop::new_int(map.len() as i32),
+    ];
+    runtime::invoke::invoke_ctor(cls, Arc::new(b"(JI)V".to_vec()), args);
+
+    Ok(Some(buf))
+}
@@ -0,0 +1,42 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+use crate::runtime::{module, require_class3};
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn(
+            "getSystemPackageNames",
+            "()[Ljava/lang/String;",
+            Box::new(jvm_getSystemPackageNames),
+        ),
+        new_fn(
+            "getSystemPackageLocation",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            Box::new(jvm_getSystemPackageLocation),
+        ),
+        new_fn(
+            "setBootLoaderUnnamedModule0",
+            "(Ljava/lang/Module;)V",
+            Box::new(jvm_setBootLoaderUnnamedModule0),
+        ),
+    ]
+}
+
+//packages aren't tracked
+fn jvm_getSystemPackageNames(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    let cls = require_class3(None, b"[Ljava/lang/String;").unwrap();
+    Ok(Some(Oop::new_ref_ary2(cls, vec![])))
+}
+
+fn jvm_getSystemPackageLocation(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::Null))
+}
+
+//the classes of the boot loader outside the named modules are in it
+fn jvm_setBootLoaderUnnamedModule0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let module = args.get(0).unwrap();
+    module::set_boot_unnamed_module(module.clone());
+    Ok(None)
+}
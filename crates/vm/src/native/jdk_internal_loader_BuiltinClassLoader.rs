@@ -0,0 +1,135 @@
+#![allow(non_snake_case)]
+
+use crate::native::common::resource;
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::{self, Oop};
+use crate::runtime::{self, module, require_class3, ResourceScope};
+
+//Java methods, the VM takes them over (see init_vm::hack_classes): the
+//loaders search the class path manager instead of their module readers
+//and URLClassPath, which need NIO and zip natives.
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn(
+            "loadClassOrNull",
+            "(Ljava/lang/String;Z)Ljava/lang/Class;",
+            Box::new(jvm_loadClassOrNull),
+        ),
+        new_fn(
+            "findClass",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/Class;",
+            Box::new(jvm_findClass),
+        ),
+        new_fn(
+            "findResource",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/net/URL;",
+            Box::new(jvm_findResource),
+        ),
+        new_fn(
+            "findResourceAsStream",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/io/InputStream;",
+            Box::new(jvm_findResourceAsStream),
+        ),
+        new_fn(
+            "findResource",
+            "(Ljava/lang/String;)Ljava/net/URL;",
+            Box::new(jvm_findResource2),
+        ),
+        new_fn(
+            "findResources",
+            "(Ljava/lang/String;)Ljava/util/Enumeration;",
+            Box::new(jvm_findResources),
+        ),
+    ]
+}
+
+//the modules are defined to the boot loader, the platform loader has
+//nothing of its own
+fn loader_scope(loader: &Oop) -> Option<ResourceScope> {
+    let cls = oop::with_heap(|heap| {
+        let desc = heap.get(loader.extract_ref());
+        let guard = desc.read().unwrap();
+        guard.v.extract_inst().class.clone()
+    });
+    let cls = cls.get_class();
+    match cls.name.as_slice() {
+        b"jdk/internal/loader/ClassLoaders$BootClassLoader" => Some(ResourceScope::Boot),
+        b"jdk/internal/loader/ClassLoaders$AppClassLoader" => Some(ResourceScope::App),
+        _ => None,
+    }
+}
+
+//a null module name means the class path
+fn find_in_module(args: &[Oop]) -> Option<String> {
+    let scope = loader_scope(args.first().unwrap())?;
+    let name = Oop::java_lang_string(args.get(2).unwrap().extract_ref());
+    match args.get(1).unwrap() {
+        Oop::Null => runtime::find_class_path_resource(&name, scope),
+        mn => {
+            let mn = Oop::java_lang_string(mn.extract_ref());
+            runtime::find_module_resource(&mn, &name, scope)
+        }
+    }
+}
+
+//the VM defines every class itself, whichever loader asks
+fn load_class(cn: &Oop) -> Option<Oop> {
+    let name = Oop::java_lang_string(cn.extract_ref());
+    //binary names only, no arrays
+    if name.contains('/') || name.starts_with('[') {
+        return None;
+    }
+    let name = name.replace('.', "/");
+    let cls = require_class3(None, name.as_bytes())?;
+    oop::class::init_class(&cls);
+    let mirror = cls.get_class().get_mirror();
+    Some(mirror)
+}
+
+fn jvm_loadClassOrNull(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    Ok(Some(load_class(args.get(1).unwrap()).unwrap_or(Oop::Null)))
+}
+
+//a class of module `mn`, any class for a null module name
+fn jvm_findClass(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let cn = args.get(2).unwrap();
+    if let Oop::Ref(mn) = args.get(1).unwrap() {
+        let mn = Oop::java_lang_string(*mn);
+        let name = Oop::java_lang_string(cn.extract_ref()).replace('.', "/");
+        let package = name.rsplit_once('/').map_or("", |(p, _)| p);
+        if module::package_module(package).map(|(m, _)| m) != Some(mn) {
+            return Ok(Some(Oop::Null));
+        }
+    }
+    Ok(Some(load_class(cn).unwrap_or(Oop::Null)))
+}
+
+fn jvm_findResource(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    resource::url_or_null(find_in_module(args))
+}
+
+fn jvm_findResourceAsStream(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    match find_in_module(args) {
+        Some(url) => resource::open_url(&url, || url.clone()),
+        None => Ok(Some(Oop::Null)),
+    }
+}
+
+fn jvm_findResource2(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let url = loader_scope(args.first().unwrap()).and_then(|scope| {
+        let name = Oop::java_lang_string(args.get(1).unwrap().extract_ref());
+        runtime::find_resources(&name, scope, false).pop()
+    });
+    resource::url_or_null(url)
+}
+
+fn jvm_findResources(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let urls = match loader_scope(args.first().unwrap()) {
+        Some(scope) => {
+            let name = Oop::java_lang_string(args.get(1).unwrap().extract_ref());
+            runtime::find_resources(&name, scope, true)
+        }
+        None => vec![],
+    };
+    resource::new_url_enumeration(urls)
+}
@@ -0,0 +1,53 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+use crate::util;
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn(
+            "findBuiltinLib",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            Box::new(jvm_findBuiltinLib),
+        ),
+        new_fn(
+            "load",
+            "(Ljdk/internal/loader/NativeLibraries$NativeLibraryImpl;Ljava/lang/String;ZZZ)Z",
+            Box::new(jvm_load),
+        ),
+        new_fn("unload", "(Ljava/lang/String;ZZJ)V", Box::new(jvm_unload)),
+        new_fn(
+            "findEntry0",
+            "(Ljdk/internal/loader/NativeLibraries$NativeLibraryImpl;Ljava/lang/String;)J",
+            Box::new(jvm_findEntry0),
+        ),
+    ]
+}
+
+//the natives of the JDK are built into the VM, every library is a builtin
+//one: "libnet.so" -> "net"
+fn jvm_findBuiltinLib(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let v = args.get(0).unwrap();
+    let s = Oop::java_lang_string(v.extract_ref());
+    let name = s.strip_prefix("lib").unwrap_or(&s);
+    let name = name
+        .strip_suffix(".so")
+        .or_else(|| name.strip_suffix(".dylib"))
+        .or_else(|| name.strip_suffix(".dll"))
+        .unwrap_or(name);
+    Ok(Some(util::oop::new_java_lang_string2(name)))
+}
+
+fn jvm_load(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(1)))
+}
+
+fn jvm_unload(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(None)
+}
+
+//'System.load' of a user library isn't supported
+fn jvm_findEntry0(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_long(0)))
+}
@@ -0,0 +1,52 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+
+/// No class data archive is mapped or dumped, so `CDS` reports sharing off
+/// and classes initialize from their own `<clinit>`.
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn("isDumpingClassList0", "()Z", Box::new(jvm_false)),
+        new_fn("isDumpingArchive0", "()Z", Box::new(jvm_false)),
+        new_fn("isSharingEnabled0", "()Z", Box::new(jvm_false)),
+        new_fn(
+            "logLambdaFormInvoker",
+            "(Ljava/lang/String;)V",
+            Box::new(jvm_nop),
+        ),
+        new_fn(
+            "initializeFromArchive",
+            "(Ljava/lang/Class;)V",
+            Box::new(jvm_nop),
+        ),
+        new_fn(
+            "defineArchivedModules",
+            "(Ljava/lang/ClassLoader;Ljava/lang/ClassLoader;)V",
+            Box::new(jvm_nop),
+        ),
+        new_fn(
+            "getRandomSeedForDumping",
+            "()J",
+            Box::new(jvm_getRandomSeedForDumping),
+        ),
+        new_fn("dumpClassList", "(Ljava/lang/String;)V", Box::new(jvm_nop)),
+        new_fn(
+            "dumpDynamicArchive",
+            "(Ljava/lang/String;)V",
+            Box::new(jvm_nop),
+        ),
+    ]
+}
+
+fn jvm_false(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(0)))
+}
+
+fn jvm_nop(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(None)
+}
+
+fn jvm_getRandomSeedForDumping(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_long(0)))
+}
@@ -0,0 +1,178 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+use crate::runtime::{self, require_class3};
+use crate::types::ClassRef;
+use crate::{new_br, util};
+
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn(
+            "platformProperties",
+            "()[Ljava/lang/String;",
+            Box::new(jvm_platformProperties),
+        ),
+        new_fn(
+            "vmProperties",
+            "()[Ljava/lang/String;",
+            Box::new(jvm_vmProperties),
+        ),
+    ]
+}
+
+/// The array is indexed by the `_xxx_NDX` constants of `SystemProps$Raw`,
+/// properties left null get their defaults in `SystemProps.initProperties`.
+fn jvm_platformProperties(env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    let cls = env.read().unwrap().class.clone();
+    let len = static_int(&cls, "FIXED_LENGTH").expect("SystemProps$Raw.FIXED_LENGTH");
+    let mut props = vec![Oop::Null; len as usize];
+
+    let (os_name, os_version) = uname();
+    let user_dir = std::env::current_dir().expect("current_dir failed");
+    let user_home = dirs::home_dir().expect("get home_dir failed");
+    let user_name = std::env::var("USER").unwrap_or_else(|_| "?".to_string());
+    let values = [
+        ("_file_encoding_NDX", "UTF-8".to_string()),
+        ("_file_separator_NDX", util::FILE_SEP.to_string()),
+        ("_java_io_tmpdir_NDX", tmpdir()),
+        ("_line_separator_NDX", util::LINE_SEP.to_string()),
+        ("_os_arch_NDX", os_arch().to_string()),
+        ("_os_name_NDX", os_name),
+        ("_os_version_NDX", os_version),
+        ("_path_separator_NDX", util::PATH_SEP.to_string()),
+        ("_sun_arch_data_model_NDX", "64".to_string()),
+        ("_sun_cpu_endian_NDX", cpu_endian().to_string()),
+        ("_sun_cpu_isalist_NDX", "".to_string()),
+        (
+            "_sun_io_unicode_encoding_NDX",
+            unicode_encoding().to_string(),
+        ),
+        ("_sun_jnu_encoding_NDX", "UTF-8".to_string()),
+        ("_sun_os_patch_level_NDX", "unknown".to_string()),
+        ("_user_dir_NDX", user_dir.to_string_lossy().to_string()),
+        ("_user_home_NDX", user_home.to_string_lossy().to_string()),
+        ("_user_name_NDX", user_name),
+    ];
+    for (name, v) in values {
+        if let Some(i) = static_int(&cls, name) {
+            props[i as usize] = util::oop::new_java_lang_string2(&v);
+        }
+    }
+
+    let ary_cls = require_class3(None, b"[Ljava/lang/String;").unwrap();
+    Ok(Some(Oop::new_ref_ary2(ary_cls, props)))
+}
+
+/// Key, value pairs of the properties the VM defines.
+fn jvm_vmProperties(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    let java_home = runtime::java_home()
+        .map(String::from)
+        .or_else(|| std::env::var("JAVA_HOME").ok())
+        .expect("Please Setup JAVA_HOME env");
+    let boot_library_path = format!("{}{}lib", java_home, util::FILE_SEP);
+
+    let mut props = vec![
+        ("java.home", java_home),
+        ("sun.boot.library.path", boot_library_path),
+        ("java.class.path", runtime::app_class_path()),
+        (
+            "java.library.path",
+            std::env::var("LD_LIBRARY_PATH").unwrap_or_default(),
+        ),
+        (
+            "java.vm.specification.name",
+            "Java Virtual Machine Specification".to_string(),
+        ),
+        (
+            "java.vm.specification.vendor",
+            "Oracle Corporation".to_string(),
+        ),
+        ("java.vm.specification.version", "17".to_string()),
+        ("java.vm.name", "jvm".to_string()),
+        ("java.vm.vendor", "Chuan".to_string()),
+        ("java.vm.version", env!("CARGO_PKG_VERSION").to_string()),
+        ("java.vm.info", "interpreted mode".to_string()),
+        ("jdk.debug", "release".to_string()),
+        ("sun.java.launcher", "SUN_STANDARD".to_string()),
+    ];
+
+    //test.src for jdk/test/java/lang/Character/CheckProp.java
+    if let Ok(v) = std::env::var("TEST_SRC") {
+        props.push(("test.src", v));
+    }
+
+    let props = props
+        .iter()
+        .flat_map(|(k, v)| {
+            [
+                util::oop::new_java_lang_string2(k),
+                util::oop::new_java_lang_string2(v),
+            ]
+        })
+        .collect();
+    let ary_cls = require_class3(None, b"[Ljava/lang/String;").unwrap();
+    Ok(Some(Oop::new_ref_ary2(ary_cls, props)))
+}
+
+fn static_int(cls: &ClassRef, name: &str) -> Option<i32> {
+    let fid = cls
+        .get_field_id_safe(&new_br(name), &new_br("I"), true)
+        .ok()?;
+    Some(cls.get_static_field_value(fid).extract_int())
+}
+
+fn uname() -> (String, String) {
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut name) } != 0 {
+        return (std::env::consts::OS.to_string(), "unknown".to_string());
+    }
+    let field = |v: &[libc::c_char]| {
+        let v: Vec<u8> = v
+            .iter()
+            .take_while(|&&c| c != 0)
+            .map(|&c| c as u8)
+            .collect();
+        String::from_utf8_lossy(&v).to_string()
+    };
+    let sysname = field(&name.sysname);
+    //the name HotSpot reports on macOS
+    let sysname = if sysname == "Darwin" {
+        "Mac OS X".to_string()
+    } else {
+        sysname
+    };
+    (sysname, field(&name.release))
+}
+
+fn os_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "x86" => "i386",
+        arch => arch,
+    }
+}
+
+fn tmpdir() -> String {
+    if cfg!(unix) {
+        "/tmp".to_string()
+    } else {
+        std::env::temp_dir().to_string_lossy().to_string()
+    }
+}
+
+fn cpu_endian() -> &'static str {
+    if cfg!(target_endian = "little") {
+        "little"
+    } else {
+        "big"
+    }
+}
+
+fn unicode_encoding() -> &'static str {
+    if cfg!(target_endian = "little") {
+        "UnicodeLittle"
+    } else {
+        "UnicodeBig"
+    }
+}
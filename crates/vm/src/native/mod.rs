@@ -11,29 +11,42 @@ mod java_io_FileDescriptor;
 mod java_io_FileInputStream;
 mod java_io_FileOutputStream;
 mod java_io_ObjectStreamClass;
-mod java_io_PrintStream;
 mod java_io_UnixFileSystem;
 pub mod java_lang_Class;
 mod java_lang_ClassLoader;
 mod java_lang_Double;
 mod java_lang_Float;
+mod java_lang_Module;
 mod java_lang_Object;
 mod java_lang_Runtime;
+mod java_lang_Shutdown;
+mod java_lang_StackTraceElement;
 mod java_lang_String;
+mod java_lang_StringUTF16;
 mod java_lang_System;
 mod java_lang_Thread;
 mod java_lang_Throwable;
+mod java_lang_invoke_MethodHandleNatives;
+pub mod java_lang_invoke_VarHandle;
+mod java_lang_ref_Reference;
 mod java_lang_reflect_Array;
 mod java_lang_reflect_Proxy;
 mod java_security_AccessController;
-mod java_security_Provider;
 mod java_util_concurrent_atomic_AtomicLong;
-mod jdk_internal_reflect_ReflectionFactory;
+mod jdk_internal_jimage_NativeImageBuffer;
+mod jdk_internal_loader_BootLoader;
+mod jdk_internal_loader_BuiltinClassLoader;
+mod jdk_internal_loader_NativeLibraries;
+mod jdk_internal_misc_CDS;
+mod jdk_internal_misc_ScopedMemoryAccess;
+mod jdk_internal_util_SystemProps_Raw;
 mod sun_misc_Signal;
 mod sun_misc_URLClassPath;
-mod sun_misc_Unsafe;
+pub mod sun_misc_Unsafe;
 mod sun_misc_VM;
-mod sun_nio_cs_StreamEncoder;
+mod sun_net_www_protocol_jar_JarURLConnection;
+mod sun_net_www_protocol_jrt_JavaRuntimeURLConnection;
+mod sun_nio_fs_UnixNativeDispatcher;
 mod sun_reflect_ConstantPool;
 mod sun_reflect_NativeConstructorAccessorImpl;
 mod sun_reflect_NativeMethodAccessorImpl;
@@ -115,19 +128,40 @@ fn create_native_fn_tables(
             "java/io/UnixFileSystem",
             java_io_UnixFileSystem::get_native_methods(),
         ),
-        (
-            "java/io/PrintStream",
-            java_io_PrintStream::get_native_methods(),
-        ),
         (
             "java/io/ObjectStreamClass",
             java_io_ObjectStreamClass::get_native_methods(),
         ),
         (
-            "jdk/internal/reflect/ReflectionFactory",
-            jdk_internal_reflect_ReflectionFactory::get_native_methods(),
+            "jdk/internal/jimage/NativeImageBuffer",
+            jdk_internal_jimage_NativeImageBuffer::get_native_methods(),
+        ),
+        (
+            "jdk/internal/loader/BootLoader",
+            jdk_internal_loader_BootLoader::get_native_methods(),
+        ),
+        (
+            "jdk/internal/loader/BuiltinClassLoader",
+            jdk_internal_loader_BuiltinClassLoader::get_native_methods(),
+        ),
+        (
+            "jdk/internal/loader/NativeLibraries",
+            jdk_internal_loader_NativeLibraries::get_native_methods(),
+        ),
+        (
+            "jdk/internal/misc/CDS",
+            jdk_internal_misc_CDS::get_native_methods(),
+        ),
+        (
+            "jdk/internal/misc/ScopedMemoryAccess",
+            jdk_internal_misc_ScopedMemoryAccess::get_native_methods(),
+        ),
+        (
+            "jdk/internal/util/SystemProps$Raw",
+            jdk_internal_util_SystemProps_Raw::get_native_methods(),
         ),
         ("java/lang/Class", java_lang_Class::get_native_methods()),
+        ("java/lang/Module", java_lang_Module::get_native_methods()),
         (
             "java/lang/ClassLoader",
             java_lang_ClassLoader::get_native_methods(),
@@ -135,18 +169,42 @@ fn create_native_fn_tables(
         ("java/lang/Double", java_lang_Double::get_native_methods()),
         ("java/lang/Float", java_lang_Float::get_native_methods()),
         ("java/lang/Object", java_lang_Object::get_native_methods()),
+        (
+            "java/lang/ref/Reference",
+            java_lang_ref_Reference::get_native_methods(),
+        ),
+        (
+            "java/lang/ref/PhantomReference",
+            java_lang_ref_Reference::get_native_methods(),
+        ),
         (
             "java/lang/reflect/Array",
             java_lang_reflect_Array::get_native_methods(),
         ),
+        (
+            "java/lang/invoke/MethodHandleNatives",
+            java_lang_invoke_MethodHandleNatives::get_native_methods(),
+        ),
         (
             "java/lang/reflect/Proxy",
             java_lang_reflect_Proxy::get_native_methods(),
         ),
         ("java/lang/Runtime", java_lang_Runtime::get_native_methods()),
+        (
+            "java/lang/Shutdown",
+            java_lang_Shutdown::get_native_methods(),
+        ),
         ("java/lang/String", java_lang_String::get_native_methods()),
+        (
+            "java/lang/StringUTF16",
+            java_lang_StringUTF16::get_native_methods(),
+        ),
         ("java/lang/System", java_lang_System::get_native_methods()),
         ("java/lang/Thread", java_lang_Thread::get_native_methods()),
+        (
+            "java/lang/StackTraceElement",
+            java_lang_StackTraceElement::get_native_methods(),
+        ),
         (
             "java/lang/Throwable",
             java_lang_Throwable::get_native_methods(),
@@ -155,15 +213,16 @@ fn create_native_fn_tables(
             "java/security/AccessController",
             java_security_AccessController::get_native_methods(),
         ),
-        (
-            "java/security/Provider",
-            java_security_Provider::get_native_methods(),
-        ),
         (
             "java/util/concurrent/atomic/AtomicLong",
             java_util_concurrent_atomic_AtomicLong::get_native_methods(),
         ),
         ("sun/misc/Signal", sun_misc_Signal::get_native_methods()),
+        // JDK 9+: Signal moved to jdk.internal.misc
+        (
+            "jdk/internal/misc/Signal",
+            sun_misc_Signal::get_native_methods(),
+        ),
         ("sun/misc/Unsafe", sun_misc_Unsafe::get_native_methods()),
         // JDK 9+: Unsafe moved to jdk.internal.misc
         (
@@ -175,14 +234,27 @@ fn create_native_fn_tables(
             sun_misc_URLClassPath::get_native_methods(),
         ),
         ("sun/misc/VM", sun_misc_VM::get_native_methods()),
+        (
+            "sun/net/www/protocol/jar/JarURLConnection",
+            sun_net_www_protocol_jar_JarURLConnection::get_native_methods(),
+        ),
+        (
+            "sun/net/www/protocol/jrt/JavaRuntimeURLConnection",
+            sun_net_www_protocol_jrt_JavaRuntimeURLConnection::get_native_methods(),
+        ),
+        (
+            "sun/nio/fs/UnixNativeDispatcher",
+            sun_nio_fs_UnixNativeDispatcher::get_native_methods(),
+        ),
         // JDK 9+: VM moved to jdk/internal/misc/VM
         ("jdk/internal/misc/VM", sun_misc_VM::get_native_methods()),
         (
-            "sun/nio/cs/StreamEncoder",
-            sun_nio_cs_StreamEncoder::get_native_methods(),
+            "sun/reflect/ConstantPool",
+            sun_reflect_ConstantPool::get_native_methods(),
         ),
+        // JDK 9+: ConstantPool moved to jdk.internal.reflect
         (
-            "sun/reflect/ConstantPool",
+            "jdk/internal/reflect/ConstantPool",
             sun_reflect_ConstantPool::get_native_methods(),
         ),
         (
@@ -193,10 +265,24 @@ fn create_native_fn_tables(
             "sun/reflect/NativeMethodAccessorImpl",
             sun_reflect_NativeMethodAccessorImpl::get_native_methods(),
         ),
+        // JDK 9+: the accessors moved to jdk.internal.reflect
+        (
+            "jdk/internal/reflect/NativeConstructorAccessorImpl",
+            sun_reflect_NativeConstructorAccessorImpl::get_native_methods(),
+        ),
+        (
+            "jdk/internal/reflect/NativeMethodAccessorImpl",
+            sun_reflect_NativeMethodAccessorImpl::get_native_methods(),
+        ),
         (
             "sun/reflect/Reflection",
             sun_reflect_Reflection::get_native_methods(),
         ),
+        // JDK 9+: Reflection moved to jdk.internal.reflect
+        (
+            "jdk/internal/reflect/Reflection",
+            sun_reflect_Reflection::get_native_methods(),
+        ),
     ];
 
     {
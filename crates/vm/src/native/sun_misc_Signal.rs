@@ -2,6 +2,7 @@
 
 use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
 use crate::oop::Oop;
+use crate::runtime::signal;
 
 pub fn get_native_methods() -> Vec<JNINativeMethod> {
     vec![
@@ -10,16 +11,42 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             "(Ljava/lang/String;)I",
             Box::new(jvm_findSignal),
         ),
+        // JDK 9+: jdk.internal.misc.Signal
+        new_fn(
+            "findSignal0",
+            "(Ljava/lang/String;)I",
+            Box::new(jvm_findSignal),
+        ),
         new_fn("handle0", "(IJ)J", Box::new(jvm_handle0)),
+        new_fn("raise0", "(I)V", Box::new(jvm_raise0)),
     ]
 }
 
-//todo: impl me
-fn jvm_findSignal(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    Ok(Some(Oop::new_int(1)))
+fn jvm_findSignal(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let name = match args.first() {
+        Some(Oop::Ref(slot_id)) => Oop::java_lang_string(*slot_id),
+        _ => return Ok(Some(Oop::new_int(-1))),
+    };
+    Ok(Some(Oop::new_int(signal::find(&name))))
+}
+
+fn jvm_handle0(env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let sig = args.first().unwrap().extract_int();
+    let handler = args.get(1).unwrap().extract_long();
+
+    //'Signal.dispatch' of the class registering the handler
+    let dispatch_class: &'static [u8] =
+        if env.read().unwrap().class.name.as_slice() == b"jdk/internal/misc/Signal" {
+            b"jdk/internal/misc/Signal"
+        } else {
+            b"sun/misc/Signal"
+        };
+    let old = signal::install(sig, handler, dispatch_class);
+    Ok(Some(Oop::new_long(old)))
 }
 
-//todo: impl me
-fn jvm_handle0(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    Ok(Some(Oop::new_long(0)))
+fn jvm_raise0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let sig = args.first().unwrap().extract_int();
+    signal::raise(sig);
+    Ok(None)
 }
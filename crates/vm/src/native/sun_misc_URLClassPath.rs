@@ -1,16 +1,43 @@
 #![allow(non_snake_case)]
 
+use crate::native::common::resource;
 use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
 use crate::oop::Oop;
+use crate::runtime::{self, ResourceScope};
 
 pub fn get_native_methods() -> Vec<JNINativeMethod> {
-    vec![new_fn(
-        "getLookupCacheURLs",
-        "(Ljava/lang/ClassLoader;)[Ljava/net/URL;",
-        Box::new(jvm_getLookupCacheURLs),
-    )]
+    vec![
+        new_fn(
+            "getLookupCacheURLs",
+            "(Ljava/lang/ClassLoader;)[Ljava/net/URL;",
+            Box::new(jvm_getLookupCacheURLs),
+        ),
+        //JDK 8: Java methods the VM takes over (see init_vm::hack_classes),
+        //the loaders are served from the application class path
+        new_fn(
+            "findResource",
+            "(Ljava/lang/String;Z)Ljava/net/URL;",
+            Box::new(jvm_findResource),
+        ),
+        new_fn(
+            "findResources",
+            "(Ljava/lang/String;Z)Ljava/util/Enumeration;",
+            Box::new(jvm_findResources),
+        ),
+    ]
 }
 
 fn jvm_getLookupCacheURLs(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
     Ok(Some(Oop::Null))
 }
+
+fn jvm_findResource(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let name = Oop::java_lang_string(args.get(1).unwrap().extract_ref());
+    let url = runtime::find_resources(&name, ResourceScope::App, false).pop();
+    resource::url_or_null(url)
+}
+
+fn jvm_findResources(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let name = Oop::java_lang_string(args.get(1).unwrap().extract_ref());
+    resource::new_url_enumeration(runtime::find_resources(&name, ResourceScope::App, true))
+}
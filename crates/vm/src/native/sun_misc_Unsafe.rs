@@ -1,19 +1,89 @@
 #![allow(non_snake_case)]
 
-use crate::native::{java_lang_System, new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
 use crate::oop;
-use crate::oop::{Class, Oop};
-use crate::runtime::require_class3;
+use crate::oop::{Class, Oop, RefKind, ValueType};
+use crate::runtime::thread::status;
+use crate::runtime::{self, exception, require_class3, vm};
+use crate::types::{ClassRef, FieldIdRef};
 use crate::util;
+use classfile::consts as cls_const;
 use classfile::flags::ACC_STATIC;
 use classfile::BytesRef;
-use std::os::raw::c_void;
+use rustc_hash::FxHashMap;
 use std::sync::atomic;
-use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Offset of the first element reported by `arrayBaseOffset`.
+///
+/// Arrays have no object header here, so any value works as long as it is
+/// used consistently; 16 matches HotSpot and keeps offsets of the first
+/// elements distinct from field offsets in diagnostics.
+const ARRAY_BASE_OFFSET: i64 = 16;
+
+/// Index scale of reference arrays, as with compressed oops.
+const REF_INDEX_SCALE: i64 = 4;
+
+/// Set in offsets returned by `staticFieldOffset`. Static fields live in the
+/// declaring class, and `staticFieldBase` returns its mirror; the tag tells
+/// a static field of the mirror's class from an instance field of the
+/// mirror itself (such as `Class.reflectionData`).
+pub const STATIC_FIELD_TAG: i64 = 1 << 32;
+
+/// Serializes read-modify-write operations on off-heap memory.
+static RAW_MEMORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Typed accessors `get<T>(Object, long)`, `put<T>(Object, long, T)` and
+/// their volatile variants.
+macro_rules! accessors {
+    ($($name:literal, $desc:literal, $vt:expr;)*) => {
+        vec![$(
+            new_fn(
+                concat!("get", $name),
+                concat!("(Ljava/lang/Object;J)", $desc),
+                Box::new(|_env, args| get(args, $vt, false)),
+            ),
+            new_fn(
+                concat!("put", $name),
+                concat!("(Ljava/lang/Object;J", $desc, ")V"),
+                Box::new(|_env, args| put(args, $vt, false)),
+            ),
+            new_fn(
+                concat!("get", $name, "Volatile"),
+                concat!("(Ljava/lang/Object;J)", $desc),
+                Box::new(|_env, args| get(args, $vt, true)),
+            ),
+            new_fn(
+                concat!("put", $name, "Volatile"),
+                concat!("(Ljava/lang/Object;J", $desc, ")V"),
+                Box::new(|_env, args| put(args, $vt, true)),
+            ),
+        )*]
+    };
+}
+
+/// JDK 8 accessors of off-heap memory, `get<T>(long)` and `put<T>(long, T)`.
+/// Later JDKs implement them in Java on top of the `(Object, long)` forms.
+macro_rules! raw_accessors {
+    ($($name:literal, $desc:literal, $vt:expr;)*) => {
+        vec![$(
+            new_fn(
+                concat!("get", $name),
+                concat!("(J)", $desc),
+                Box::new(|_env, args| get_raw(args, $vt)),
+            ),
+            new_fn(
+                concat!("put", $name),
+                concat!("(J", $desc, ")V"),
+                Box::new(|_env, args| put_raw(args, $vt)),
+            ),
+        )*]
+    };
+}
 
 pub fn get_native_methods() -> Vec<JNINativeMethod> {
-    let methods = vec![
+    let mut methods = vec![
         new_fn("registerNatives", "()V", Box::new(jvm_registerNatives)),
         new_fn(
             "arrayBaseOffset",
@@ -26,81 +96,93 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             Box::new(jvm_arrayIndexScale),
         ),
         new_fn("addressSize", "()I", Box::new(jvm_addressSize)),
+        new_fn("pageSize", "()I", Box::new(jvm_pageSize)),
         new_fn(
             "objectFieldOffset",
             "(Ljava/lang/reflect/Field;)J",
             Box::new(jvm_objectFieldOffset),
         ),
         new_fn(
-            "compareAndSwapObject",
-            "(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Z",
-            Box::new(jvm_compareAndSwapObject),
+            "staticFieldOffset",
+            "(Ljava/lang/reflect/Field;)J",
+            Box::new(jvm_staticFieldOffset),
         ),
         new_fn(
-            "getIntVolatile",
-            "(Ljava/lang/Object;J)I",
-            Box::new(jvm_getIntVolatile),
+            "staticFieldBase",
+            "(Ljava/lang/reflect/Field;)Ljava/lang/Object;",
+            Box::new(jvm_staticFieldBase),
         ),
         new_fn(
-            "compareAndSwapInt",
-            "(Ljava/lang/Object;JII)Z",
-            Box::new(jvm_compareAndSwapInt),
+            "ensureClassInitialized",
+            "(Ljava/lang/Class;)V",
+            Box::new(jvm_ensureClassInitialized),
         ),
-        new_fn("allocateMemory", "(J)J", Box::new(jvm_allocateMemory)),
-        new_fn("freeMemory", "(J)V", Box::new(jvm_freeMemory)),
-        new_fn("putLong", "(JJ)V", Box::new(jvm_putLong)),
-        new_fn("getByte", "(J)B", Box::new(jvm_getByte)),
         new_fn(
-            "compareAndSwapLong",
-            "(Ljava/lang/Object;JJJ)Z",
-            Box::new(jvm_compareAndSwapLong),
+            "shouldBeInitialized",
+            "(Ljava/lang/Class;)Z",
+            Box::new(jvm_shouldBeInitialized0),
         ),
         new_fn(
-            "getObjectVolatile",
-            "(Ljava/lang/Object;J)Ljava/lang/Object;",
-            Box::new(jvm_getObjectVolatile),
+            "allocateInstance",
+            "(Ljava/lang/Class;)Ljava/lang/Object;",
+            Box::new(jvm_allocateInstance),
         ),
-        new_fn("pageSize", "()I", Box::new(jvm_pageSize)),
         new_fn(
-            "getLongVolatile",
-            "(Ljava/lang/Object;J)J",
-            Box::new(jvm_getLongVolatile),
+            "throwException",
+            "(Ljava/lang/Throwable;)V",
+            Box::new(jvm_throwException),
         ),
+        new_fn("allocateMemory", "(J)J", Box::new(jvm_allocateMemory)),
+        new_fn("reallocateMemory", "(JJ)J", Box::new(jvm_reallocateMemory)),
+        new_fn("freeMemory", "(J)V", Box::new(jvm_freeMemory)),
         new_fn(
             "setMemory",
             "(Ljava/lang/Object;JJB)V",
             Box::new(jvm_setMemory),
         ),
-        new_fn("putChar", "(JC)V", Box::new(jvm_putChar)),
         new_fn(
             "copyMemory",
             "(Ljava/lang/Object;JLjava/lang/Object;JJ)V",
             Box::new(jvm_copyMemory),
         ),
-        new_fn("getChar", "(J)C", Box::new(jvm_getChar)),
+        new_fn("getAddress", "(J)J", Box::new(jvm_getAddress)),
+        new_fn("putAddress", "(JJ)V", Box::new(jvm_putAddress)),
+        new_fn("getLoadAverage", "([DI)I", Box::new(jvm_getLoadAverage)),
         new_fn(
-            "putObject",
+            "putOrderedObject",
             "(Ljava/lang/Object;JLjava/lang/Object;)V",
-            Box::new(jvm_putObject),
+            Box::new(jvm_putOrdered),
         ),
         new_fn(
-            "ensureClassInitialized",
-            "(Ljava/lang/Class;)V",
-            Box::new(jvm_ensureClassInitialized),
+            "putOrderedInt",
+            "(Ljava/lang/Object;JI)V",
+            Box::new(jvm_putOrdered),
         ),
         new_fn(
-            "staticFieldOffset",
-            "(Ljava/lang/reflect/Field;)J",
-            Box::new(jvm_staticFieldOffset),
+            "putOrderedLong",
+            "(Ljava/lang/Object;JJ)V",
+            Box::new(jvm_putOrdered),
         ),
         new_fn(
-            "staticFieldBase",
-            "(Ljava/lang/reflect/Field;)Ljava/lang/Object;",
-            Box::new(jvm_staticFieldBase),
+            "compareAndSwapObject",
+            "(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Z",
+            Box::new(jvm_compareAndSwapObject),
+        ),
+        new_fn(
+            "compareAndSwapInt",
+            "(Ljava/lang/Object;JII)Z",
+            Box::new(jvm_compareAndSwapInt),
+        ),
+        new_fn(
+            "compareAndSwapLong",
+            "(Ljava/lang/Object;JJJ)Z",
+            Box::new(jvm_compareAndSwapLong),
         ),
-        new_fn("putByte", "(Ljava/lang/Object;JB)V", Box::new(jvm_putByte)),
-        new_fn("getByte", "(Ljava/lang/Object;J)B", Box::new(jvm_getByte2)),
         new_fn("park", "(ZJ)V", Box::new(jvm_park)),
+        new_fn("unpark", "(Ljava/lang/Object;)V", Box::new(jvm_unpark)),
+        new_fn("fullFence", "()V", Box::new(jvm_fullFence)),
+        new_fn("loadFence", "()V", Box::new(jvm_loadFence)),
+        new_fn("storeFence", "()V", Box::new(jvm_storeFence)),
         // JDK 9+ jdk/internal/misc/Unsafe uses "0"-suffixed names
         new_fn(
             "arrayBaseOffset0",
@@ -117,67 +199,122 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             "(Ljava/lang/reflect/Field;)J",
             Box::new(jvm_objectFieldOffset),
         ),
+        new_fn(
+            "staticFieldOffset0",
+            "(Ljava/lang/reflect/Field;)J",
+            Box::new(jvm_staticFieldOffset),
+        ),
+        new_fn(
+            "staticFieldBase0",
+            "(Ljava/lang/reflect/Field;)Ljava/lang/Object;",
+            Box::new(jvm_staticFieldBase),
+        ),
+        new_fn(
+            "shouldBeInitialized0",
+            "(Ljava/lang/Class;)Z",
+            Box::new(jvm_shouldBeInitialized0),
+        ),
+        new_fn(
+            "ensureClassInitialized0",
+            "(Ljava/lang/Class;)V",
+            Box::new(jvm_ensureClassInitialized0),
+        ),
         new_fn("allocateMemory0", "(J)J", Box::new(jvm_allocateMemory)),
+        new_fn("reallocateMemory0", "(JJ)J", Box::new(jvm_reallocateMemory)),
         new_fn("freeMemory0", "(J)V", Box::new(jvm_freeMemory)),
         new_fn(
-            "getObjectVolatile0",
-            "(Ljava/lang/Object;J)Ljava/lang/Object;",
-            Box::new(jvm_getObjectVolatile),
+            "setMemory0",
+            "(Ljava/lang/Object;JJB)V",
+            Box::new(jvm_setMemory),
+        ),
+        new_fn(
+            "copyMemory0",
+            "(Ljava/lang/Object;JLjava/lang/Object;JJ)V",
+            Box::new(jvm_copyMemory),
         ),
         new_fn(
-            "getLongVolatile0",
-            "(Ljava/lang/Object;J)J",
-            Box::new(jvm_getLongVolatile),
+            "copySwapMemory0",
+            "(Ljava/lang/Object;JLjava/lang/Object;JJJ)V",
+            Box::new(jvm_copySwapMemory0),
         ),
+        new_fn("getLoadAverage0", "([DI)I", Box::new(jvm_getLoadAverage)),
         new_fn("addressSize0", "()I", Box::new(jvm_addressSize)),
         new_fn("pageSize0", "()I", Box::new(jvm_pageSize)),
+        // JDK 9-13, replaced by UnsafeConstants afterwards
+        new_fn("isBigEndian0", "()Z", Box::new(jvm_isBigEndian0)),
+        new_fn("unalignedAccess0", "()Z", Box::new(jvm_unalignedAccess0)),
         // JDK 21+ objectFieldOffset1(Class, String)
         new_fn(
             "objectFieldOffset1",
             "(Ljava/lang/Class;Ljava/lang/String;)J",
             Box::new(jvm_objectFieldOffset1),
         ),
-        // JDK 21+ fullFence - memory fence, no-op for now
-        new_fn("fullFence", "()V", Box::new(jvm_fullFence)),
-        // JDK 21+ unpark
-        new_fn("unpark", "(Ljava/lang/Object;)V", Box::new(jvm_unpark)),
-        // JDK 21+ shouldBeInitialized0
+        // JDK 9+ jdk/internal/misc/Unsafe names of the CAS primitives
         new_fn(
-            "shouldBeInitialized0",
-            "(Ljava/lang/Class;)Z",
-            Box::new(jvm_shouldBeInitialized0),
+            "compareAndSetInt",
+            "(Ljava/lang/Object;JII)Z",
+            Box::new(jvm_compareAndSwapInt),
         ),
-        // JDK 21+ ensureClassInitialized0
         new_fn(
-            "ensureClassInitialized0",
-            "(Ljava/lang/Class;)V",
-            Box::new(jvm_ensureClassInitialized0),
+            "compareAndSetLong",
+            "(Ljava/lang/Object;JJJ)Z",
+            Box::new(jvm_compareAndSwapLong),
         ),
-        // JDK 21+ staticFieldOffset0
         new_fn(
-            "staticFieldOffset0",
-            "(Ljava/lang/reflect/Field;)J",
-            Box::new(jvm_staticFieldOffset),
+            "compareAndSetObject",
+            "(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Z",
+            Box::new(jvm_compareAndSwapObject),
         ),
-        // JDK 21+ staticFieldBase0
         new_fn(
-            "staticFieldBase0",
-            "(Ljava/lang/reflect/Field;)Ljava/lang/Object;",
-            Box::new(jvm_staticFieldBase),
+            "compareAndSetReference",
+            "(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Z",
+            Box::new(jvm_compareAndSwapObject),
         ),
-        // JDK 21+ arrayBaseOffset0
         new_fn(
-            "arrayBaseOffset0",
-            "(Ljava/lang/Class;)I",
-            Box::new(jvm_arrayBaseOffset),
+            "compareAndExchangeInt",
+            "(Ljava/lang/Object;JII)I",
+            Box::new(jvm_compareAndExchangeInt),
         ),
-        // JDK 21+ arrayIndexScale0
         new_fn(
-            "arrayIndexScale0",
-            "(Ljava/lang/Class;)I",
-            Box::new(jvm_arrayIndexScale),
+            "compareAndExchangeLong",
+            "(Ljava/lang/Object;JJJ)J",
+            Box::new(jvm_compareAndExchangeLong),
+        ),
+        new_fn(
+            "compareAndExchangeObject",
+            "(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+            Box::new(jvm_compareAndExchangeObject),
+        ),
+        new_fn(
+            "compareAndExchangeReference",
+            "(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+            Box::new(jvm_compareAndExchangeObject),
         ),
     ];
+
+    // "Reference" is the JDK 12+ name of the "Object" accessors
+    methods.extend(accessors! {
+        "Boolean", "Z", ValueType::BOOLEAN;
+        "Byte", "B", ValueType::BYTE;
+        "Short", "S", ValueType::SHORT;
+        "Char", "C", ValueType::CHAR;
+        "Int", "I", ValueType::INT;
+        "Long", "J", ValueType::LONG;
+        "Float", "F", ValueType::FLOAT;
+        "Double", "D", ValueType::DOUBLE;
+        "Object", "Ljava/lang/Object;", ValueType::OBJECT;
+        "Reference", "Ljava/lang/Object;", ValueType::OBJECT;
+    });
+    methods.extend(raw_accessors! {
+        "Byte", "B", ValueType::BYTE;
+        "Short", "S", ValueType::SHORT;
+        "Char", "C", ValueType::CHAR;
+        "Int", "I", ValueType::INT;
+        "Long", "J", ValueType::LONG;
+        "Float", "F", ValueType::FLOAT;
+        "Double", "D", ValueType::DOUBLE;
+    });
+
     methods
 }
 
@@ -185,107 +322,395 @@ fn jvm_registerNatives(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
     Ok(None)
 }
 
-fn jvm_arrayBaseOffset(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    Ok(Some(Oop::new_int(0)))
+/// What an `(Object base, long offset)` pair addresses.
+enum Location {
+    /// Off-heap memory, `base` is null and `offset` the address
+    Raw(usize),
+    /// A field of an instance or mirror, or an element of a reference array
+    Field(u32, usize),
+    /// A static field of a class
+    Static(ClassRef, usize),
+    /// Bytes of a primitive array, starting at the byte offset
+    Bytes(u32, usize),
 }
 
-fn jvm_arrayIndexScale(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    Ok(Some(Oop::new_int(1)))
+/// Resolve `base` and `offset` for an access of `len` bytes;
+/// `is_ref` accesses hold a reference and need a field or reference array.
+fn locate(base: &Oop, offset: i64, len: usize, is_ref: bool) -> Result<Location, String> {
+    let slot_id = match base {
+        Oop::Null => return Ok(Location::Raw(offset as usize)),
+        Oop::Ref(slot_id) => *slot_id,
+        t => unreachable!("base = {:?}", t),
+    };
+    let field = |n: usize| {
+        if offset >= 0 && (offset as usize) < n {
+            Ok(Location::Field(slot_id, offset as usize))
+        } else {
+            Err(format!("bad field offset {}", offset))
+        }
+    };
+
+    oop::with_heap(|heap| {
+        let desc = heap.get(slot_id);
+        let guard = desc.read().unwrap();
+        match &guard.v {
+            RefKind::Inst(inst) => field(inst.field_values.len()),
+            RefKind::Mirror(mirror) if offset & STATIC_FIELD_TAG != 0 => match &mirror.target {
+                Some(cls) => Ok(Location::Static(
+                    cls.clone(),
+                    (offset & !STATIC_FIELD_TAG) as usize,
+                )),
+                None => Err(String::from("static field of a primitive type")),
+            },
+            RefKind::Mirror(mirror) => field(mirror.field_values.len()),
+            RefKind::Array(ary) => {
+                let pos = offset - ARRAY_BASE_OFFSET;
+                let idx = pos / REF_INDEX_SCALE;
+                if pos >= 0 && pos % REF_INDEX_SCALE == 0 && (idx as usize) < ary.elements.len() {
+                    Ok(Location::Field(slot_id, idx as usize))
+                } else {
+                    Err(format!("bad array offset {}", offset))
+                }
+            }
+            RefKind::TypeArray(_) if is_ref => {
+                Err(String::from("reference access to a primitive array"))
+            }
+            RefKind::TypeArray(ary) => {
+                let pos = offset - ARRAY_BASE_OFFSET;
+                if pos >= 0 && pos as usize + len <= ary.len() * ary.elem_size() {
+                    Ok(Location::Bytes(slot_id, pos as usize))
+                } else {
+                    Err(format!("bad array offset {}", offset))
+                }
+            }
+        }
+    })
 }
 
-fn jvm_addressSize(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    let v = std::mem::size_of::<*mut u8>();
-    Ok(Some(Oop::new_int(v as i32)))
+/// Resolve the `(Object, long)` pair at `args[1..3]` for an access of `vt`.
+fn resolve(args: &[Oop], vt: ValueType) -> Result<Location, Oop> {
+    let base = args.get(1).unwrap();
+    let offset = args.get(2).unwrap().extract_long();
+    let is_ref = vt == ValueType::OBJECT;
+    if is_ref && base.is_null() {
+        return Err(internal_error("reference access to off-heap memory"));
+    }
+    locate(base, offset, value_size(vt), is_ref).map_err(|msg| internal_error(&msg))
 }
 
-fn jvm_objectFieldOffset(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let field = args.get(1).unwrap();
-    objectFieldOffset(field, false)
+/// Resolve a region of `len` bytes for the bulk memory operations,
+/// which only work on off-heap memory and primitive arrays.
+fn resolve_memory(base: &Oop, offset: i64, len: usize) -> Result<Location, Oop> {
+    match locate(base, offset, len, false) {
+        Ok(Location::Field(..)) | Ok(Location::Static(..)) => {
+            Err(internal_error("bulk access to an object"))
+        }
+        Ok(loc) => Ok(loc),
+        Err(msg) => Err(internal_error(&msg)),
+    }
 }
 
-fn jvm_objectFieldOffset1(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    // args: [this, class, fieldName]
-    let class_oop = args.get(1).unwrap();
-    let name_oop = args.get(2).unwrap();
-    let cls_slot = class_oop.extract_ref();
-    let (cls_ref, _) = Oop::mirror_target_and_vt(cls_slot);
-    if let Some(cls_ref) = cls_ref {
-        let cls = cls_ref.get_class();
-        let name_bytes: Vec<u8> = Oop::java_lang_string_value(name_oop.extract_ref())
-            .iter()
-            .map(|&c| c as u8)
-            .collect();
-        let name_ref: BytesRef = Arc::new(name_bytes);
-        // Try to find the field with common descriptors
-        let descs: &[&[u8]] = &[
-            b"Ljava/lang/Object;",
-            b"Z",
-            b"B",
-            b"S",
-            b"C",
-            b"I",
-            b"J",
-            b"F",
-            b"D",
-        ];
-        for desc in descs {
-            let desc_ref: BytesRef = Arc::new(desc.to_vec());
-            if let Ok(fid) = cls.get_field_id_safe(&name_ref, &desc_ref, false) {
-                return Ok(Some(Oop::new_long(fid.offset as i64)));
-            }
+fn internal_error(msg: &str) -> Oop {
+    exception::new(cls_const::J_INTERNAL_ERROR, Some(String::from(msg)))
+}
+
+fn value_size(vt: ValueType) -> usize {
+    match vt {
+        ValueType::BOOLEAN | ValueType::BYTE => 1,
+        ValueType::SHORT | ValueType::CHAR => 2,
+        ValueType::INT | ValueType::FLOAT => 4,
+        ValueType::LONG | ValueType::DOUBLE => 8,
+        _ => REF_INDEX_SCALE as usize,
+    }
+}
+
+/// The bits of a primitive value, as stored in memory.
+fn to_bits(vt: ValueType, v: &Oop) -> u64 {
+    match vt {
+        ValueType::LONG => v.extract_long() as u64,
+        ValueType::FLOAT => v.extract_float().to_bits() as u64,
+        ValueType::DOUBLE => v.extract_double().to_bits(),
+        _ => v.extract_int() as u32 as u64,
+    }
+}
+
+/// A primitive value from its bits in memory, widened the way the
+/// interpreter keeps sub-int values.
+fn from_bits(vt: ValueType, bits: u64) -> Oop {
+    match vt {
+        ValueType::BOOLEAN => Oop::new_int((bits as u8 != 0) as i32),
+        ValueType::BYTE => Oop::new_int(bits as u8 as i8 as i32),
+        ValueType::SHORT => Oop::new_int(bits as u16 as i16 as i32),
+        ValueType::CHAR => Oop::new_int(bits as u16 as i32),
+        ValueType::LONG => Oop::new_long(bits as i64),
+        ValueType::FLOAT => Oop::new_float(f32::from_bits(bits as u32)),
+        ValueType::DOUBLE => Oop::new_double(f64::from_bits(bits)),
+        _ => Oop::new_int(bits as u32 as i32),
+    }
+}
+
+fn read_bytes(bytes: &[u8], pos: usize, len: usize) -> u64 {
+    let b = &bytes[pos..pos + len];
+    match len {
+        1 => b[0] as u64,
+        2 => u16::from_ne_bytes(b.try_into().unwrap()) as u64,
+        4 => u32::from_ne_bytes(b.try_into().unwrap()) as u64,
+        _ => u64::from_ne_bytes(b.try_into().unwrap()),
+    }
+}
+
+fn write_bytes(bytes: &mut [u8], pos: usize, len: usize, bits: u64) {
+    let b = &mut bytes[pos..pos + len];
+    match len {
+        1 => b[0] = bits as u8,
+        2 => b.copy_from_slice(&(bits as u16).to_ne_bytes()),
+        4 => b.copy_from_slice(&(bits as u32).to_ne_bytes()),
+        _ => b.copy_from_slice(&bits.to_ne_bytes()),
+    }
+}
+
+fn read_raw(addr: usize, len: usize) -> u64 {
+    let bytes = unsafe { std::slice::from_raw_parts(addr as *const u8, len) };
+    read_bytes(bytes, 0, len)
+}
+
+fn write_raw(addr: usize, len: usize, bits: u64) {
+    let bytes = unsafe { std::slice::from_raw_parts_mut(addr as *mut u8, len) };
+    write_bytes(bytes, 0, len, bits);
+}
+
+fn load(loc: &Location, vt: ValueType) -> Oop {
+    match loc {
+        Location::Field(slot_id, offset) => Class::get_field_value2(*slot_id, *offset),
+        Location::Static(cls, offset) => cls.update_static_field_value2(*offset, |_| None),
+        Location::Bytes(slot_id, pos) => {
+            let bits = oop::with_heap(|heap| {
+                let desc = heap.get(*slot_id);
+                let guard = desc.read().unwrap();
+                let ary = guard.v.extract_type_array();
+                read_bytes(ary.as_bytes(), *pos, value_size(vt))
+            });
+            from_bits(vt, bits)
         }
-        // Try static fields too
-        for desc in descs {
-            let desc_ref: BytesRef = Arc::new(desc.to_vec());
-            if let Ok(fid) = cls.get_field_id_safe(&name_ref, &desc_ref, true) {
-                return Ok(Some(Oop::new_long(fid.offset as i64)));
+        Location::Raw(addr) => from_bits(vt, read_raw(*addr, value_size(vt))),
+    }
+}
+
+fn store(loc: &Location, vt: ValueType, v: Oop) {
+    match loc {
+        Location::Field(slot_id, offset) => Class::put_field_value2(*slot_id, *offset, v),
+        Location::Static(cls, offset) => {
+            cls.update_static_field_value2(*offset, |_| Some(v));
+        }
+        Location::Bytes(slot_id, pos) => oop::with_heap(|heap| {
+            let desc = heap.get(*slot_id);
+            let mut guard = desc.write().unwrap();
+            let ary = guard.v.extract_mut_type_array();
+            write_bytes(ary.as_mut_bytes(), *pos, value_size(vt), to_bits(vt, &v));
+        }),
+        Location::Raw(addr) => write_raw(*addr, value_size(vt), to_bits(vt, &v)),
+    }
+}
+
+/// Atomically replace the value at `loc` with what `f` returns, if anything.
+/// Returns the previous value.
+fn update(loc: &Location, vt: ValueType, f: impl FnOnce(&Oop) -> Option<Oop>) -> Oop {
+    let prev = match loc {
+        Location::Field(slot_id, offset) => Class::update_field_value2(*slot_id, *offset, f),
+        Location::Static(cls, offset) => cls.update_static_field_value2(*offset, f),
+        Location::Bytes(slot_id, pos) => oop::with_heap(|heap| {
+            let desc = heap.get(*slot_id);
+            let mut guard = desc.write().unwrap();
+            let bytes = guard.v.extract_mut_type_array().as_mut_bytes();
+            let len = value_size(vt);
+            let prev = from_bits(vt, read_bytes(bytes, *pos, len));
+            if let Some(v) = f(&prev) {
+                write_bytes(bytes, *pos, len, to_bits(vt, &v));
+            }
+            prev
+        }),
+        Location::Raw(addr) => {
+            let _guard = RAW_MEMORY_LOCK.lock().unwrap();
+            let len = value_size(vt);
+            let prev = from_bits(vt, read_raw(*addr, len));
+            if let Some(v) = f(&prev) {
+                write_raw(*addr, len, to_bits(vt, &v));
             }
+            prev
         }
+    };
+    // read-modify-write operations are full barriers, as in HotSpot
+    atomic::fence(atomic::Ordering::SeqCst);
+    prev
+}
+
+/// Value equality for CAS: primitives by bits, references by identity.
+fn same_value(a: &Oop, b: &Oop) -> bool {
+    match (a, b) {
+        (Oop::Int(a), Oop::Int(b)) => a == b,
+        (Oop::Long(a), Oop::Long(b)) => a == b,
+        (Oop::Float(a), Oop::Float(b)) => a.to_bits() == b.to_bits(),
+        (Oop::Double(a), Oop::Double(b)) => a.to_bits() == b.to_bits(),
+        (Oop::Null, Oop::Null) => true,
+        (Oop::Ref(a), Oop::Ref(b)) => a == b,
+        _ => false,
     }
-    Ok(Some(Oop::new_long(-1)))
 }
 
-// fixme: The semantic requirement here is atomic operation, which needs to be re-implemented here
-fn jvm_compareAndSwapObject(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let owner = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long();
-    let old_data = args.get(3).unwrap();
-    let new_data = args.get(4).unwrap();
+/// Compare-and-exchange of `args = [this, o, offset, expected, x]`,
+/// returning whether `x` was stored and the previous value.
+fn cas(args: &[Oop], vt: ValueType) -> Result<(bool, Oop), Oop> {
+    let loc = resolve(args, vt)?;
+    let expected = args.get(3).unwrap();
+    let x = args.get(4).unwrap();
+    let prev = update(&loc, vt, |cur| {
+        if same_value(cur, expected) {
+            Some(x.clone())
+        } else {
+            None
+        }
+    });
+    Ok((same_value(&prev, expected), prev))
+}
 
-    let v_at_offset = Class::get_field_value2(owner.extract_ref(), offset as usize);
+fn get(args: &[Oop], vt: ValueType, volatile: bool) -> JNIResult {
+    let loc = resolve(args, vt)?;
+    let v = load(&loc, vt);
+    if volatile {
+        atomic::fence(atomic::Ordering::Acquire);
+    }
+    Ok(Some(v))
+}
 
-    if Oop::is_eq(v_at_offset.extract_ref(), old_data.extract_ref()) {
-        Class::put_field_value2(owner.extract_ref(), offset as usize, new_data.clone());
-        Ok(Some(Oop::new_int(1)))
-    } else {
-        Ok(Some(Oop::new_int(0)))
+fn put(args: &[Oop], vt: ValueType, volatile: bool) -> JNIResult {
+    let loc = resolve(args, vt)?;
+    let v = args.get(3).unwrap().clone();
+    if volatile {
+        atomic::fence(atomic::Ordering::Release);
     }
+    store(&loc, vt, v);
+    if volatile {
+        atomic::fence(atomic::Ordering::SeqCst);
+    }
+    Ok(None)
 }
 
-fn jvm_getIntVolatile(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let owner = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long();
-    let v = Class::get_field_value2(owner.extract_ref(), offset as usize);
-    Ok(Some(v))
+fn get_raw(args: &[Oop], vt: ValueType) -> JNIResult {
+    let addr = args.get(1).unwrap().extract_long() as usize;
+    Ok(Some(load(&Location::Raw(addr), vt)))
 }
 
-fn jvm_compareAndSwapInt(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let owner = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long();
-    let old_data = args.get(3).unwrap().extract_int();
-    let new_data = args.get(4).unwrap();
+fn put_raw(args: &[Oop], vt: ValueType) -> JNIResult {
+    let addr = args.get(1).unwrap().extract_long() as usize;
+    let v = args.get(2).unwrap().clone();
+    store(&Location::Raw(addr), vt, v);
+    Ok(None)
+}
 
-    let v_at_offset = {
-        let v = Class::get_field_value2(owner.extract_ref(), offset as usize);
-        v.extract_int()
+/// `getAndAdd{Int,Long}`, a Java loop over CAS in the JDK, done as one
+/// atomic update (see `runtime::intrinsics`).
+pub fn get_and_add(args: &[Oop], vt: ValueType) -> JNIResult {
+    let loc = resolve(args, vt)?;
+    let delta = args.get(3).unwrap();
+    let prev = update(&loc, vt, |cur| match (cur, delta) {
+        (Oop::Int(v), Oop::Int(d)) => Some(Oop::new_int(v.wrapping_add(*d))),
+        (Oop::Long(v), Oop::Long(d)) => Some(Oop::new_long(v.wrapping_add(*d))),
+        t => unreachable!("getAndAdd {:?}", t),
+    });
+    Ok(Some(prev))
+}
+
+/// `getAndSet{Int,Long,Object,Reference}`, see `get_and_add`.
+pub fn get_and_set(args: &[Oop], vt: ValueType) -> JNIResult {
+    let loc = resolve(args, vt)?;
+    let v = args.get(3).unwrap();
+    let prev = update(&loc, vt, |_| Some(v.clone()));
+    Ok(Some(prev))
+}
+
+/// `compareAndExchange{Byte,Short}`. The JDK implements them in Java with
+/// an int CAS on the enclosing aligned word, which doesn't work for fields
+/// here since field offsets are indexes, not byte offsets.
+pub fn compare_and_exchange(args: &[Oop], vt: ValueType) -> JNIResult {
+    let (_, prev) = cas(args, vt)?;
+    Ok(Some(prev))
+}
+
+fn jvm_arrayBaseOffset(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(ARRAY_BASE_OFFSET as i32)))
+}
+
+fn jvm_arrayIndexScale(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let clazz = args.get(1).unwrap();
+    let scale = match Oop::mirror_target(clazz.extract_ref()) {
+        Some(cls) => match cls.name.get(1) {
+            Some(b'Z') | Some(b'B') => 1,
+            Some(b'S') | Some(b'C') => 2,
+            Some(b'I') | Some(b'F') => 4,
+            Some(b'J') | Some(b'D') => 8,
+            _ => REF_INDEX_SCALE as i32,
+        },
+        None => REF_INDEX_SCALE as i32,
     };
+    Ok(Some(Oop::new_int(scale)))
+}
 
-    if v_at_offset == old_data {
-        Class::put_field_value2(owner.extract_ref(), offset as usize, new_data.clone());
-        Ok(Some(Oop::new_int(1)))
-    } else {
-        Ok(Some(Oop::new_int(0)))
-    }
+fn jvm_addressSize(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    let v = std::mem::size_of::<*mut u8>();
+    Ok(Some(Oop::new_int(v as i32)))
+}
+
+fn jvm_pageSize(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(4 * 1024)))
+}
+
+fn jvm_isBigEndian0(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(cfg!(target_endian = "big") as i32)))
+}
+
+fn jvm_unalignedAccess0(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(1)))
+}
+
+fn jvm_compareAndSwapObject(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let (r, _) = cas(args, ValueType::OBJECT)?;
+    Ok(Some(Oop::new_int(r as i32)))
+}
+
+fn jvm_compareAndSwapInt(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let (r, _) = cas(args, ValueType::INT)?;
+    Ok(Some(Oop::new_int(r as i32)))
+}
+
+fn jvm_compareAndSwapLong(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let (r, _) = cas(args, ValueType::LONG)?;
+    Ok(Some(Oop::new_int(r as i32)))
+}
+
+fn jvm_compareAndExchangeObject(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    compare_and_exchange(args, ValueType::OBJECT)
+}
+
+fn jvm_compareAndExchangeInt(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    compare_and_exchange(args, ValueType::INT)
+}
+
+fn jvm_compareAndExchangeLong(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    compare_and_exchange(args, ValueType::LONG)
+}
+
+/// `putOrdered{Object,Int,Long}`: a release store without the trailing
+/// full fence of a volatile store.
+fn jvm_putOrdered(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let vt = match args.get(3).unwrap() {
+        Oop::Int(_) => ValueType::INT,
+        Oop::Long(_) => ValueType::LONG,
+        _ => ValueType::OBJECT,
+    };
+    let loc = resolve(args, vt)?;
+    atomic::fence(atomic::Ordering::Release);
+    store(&loc, vt, args.get(3).unwrap().clone());
+    Ok(None)
 }
 
 fn jvm_allocateMemory(_env: JNIEnv, args: &[Oop]) -> JNIResult {
@@ -296,205 +721,196 @@ fn jvm_allocateMemory(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     Ok(Some(Oop::new_long(v)))
 }
 
-fn jvm_freeMemory(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+fn jvm_reallocateMemory(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let ptr = args.get(1).unwrap().extract_long() as *mut libc::c_void;
+    let size = args.get(2).unwrap().extract_long() as usize;
+    let v = unsafe { libc::realloc(ptr, size) };
 
-    unsafe {
-        libc::free(ptr);
-    }
-
-    Ok(None)
+    Ok(Some(Oop::new_long(v as i64)))
 }
 
-fn jvm_putLong(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+fn jvm_freeMemory(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let ptr = args.get(1).unwrap().extract_long() as *mut libc::c_void;
-    let l = args.get(2).unwrap().extract_long();
-    let v = l.to_be_bytes();
+
     unsafe {
-        libc::memcpy(ptr, v.as_ptr() as *const c_void, 8);
+        libc::free(ptr);
     }
 
     Ok(None)
 }
 
-fn jvm_getByte(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let ptr = args.get(1).unwrap().extract_long() as *const u8;
-    let v = unsafe { *ptr };
-    Ok(Some(Oop::new_int(v as i32)))
+fn jvm_getAddress(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let addr = args.get(1).unwrap().extract_long() as usize;
+    let v = read_raw(addr, std::mem::size_of::<usize>());
+    Ok(Some(Oop::new_long(v as i64)))
 }
 
-fn jvm_compareAndSwapLong(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let owner = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long();
-    let old_data = args.get(3).unwrap().extract_long();
-    let new_data = args.get(4).unwrap();
-
-    let v_at_offset = {
-        let v = Class::get_field_value2(owner.extract_ref(), offset as usize);
-        v.extract_long()
-    };
-
-    if v_at_offset == old_data {
-        Class::put_field_value2(owner.extract_ref(), offset as usize, new_data.clone());
-        Ok(Some(Oop::new_int(1)))
-    } else {
-        Ok(Some(Oop::new_int(0)))
-    }
-}
-
-fn jvm_getObjectVolatile(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let owner = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long();
-    let v_at_offset = Class::get_field_value2(owner.extract_ref(), offset as usize);
-    Ok(Some(v_at_offset))
+fn jvm_putAddress(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let addr = args.get(1).unwrap().extract_long() as usize;
+    let v = args.get(2).unwrap().extract_long();
+    write_raw(addr, std::mem::size_of::<usize>(), v as u64);
+    Ok(None)
 }
 
-fn jvm_pageSize(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    Ok(Some(Oop::new_int(4 * 1024)))
+/// Copy `len` bytes out of an off-heap region or primitive array.
+fn read_memory(loc: &Location, len: usize) -> Vec<u8> {
+    match loc {
+        Location::Raw(addr) => {
+            unsafe { std::slice::from_raw_parts(*addr as *const u8, len) }.to_vec()
+        }
+        Location::Bytes(slot_id, pos) => oop::with_heap(|heap| {
+            let desc = heap.get(*slot_id);
+            let guard = desc.read().unwrap();
+            let ary = guard.v.extract_type_array();
+            ary.as_bytes()[*pos..*pos + len].to_vec()
+        }),
+        _ => unreachable!(),
+    }
 }
 
-fn jvm_getLongVolatile(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let owner = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long();
-    let v_at_offset = Class::get_field_value2(owner.extract_ref(), offset as usize);
-    Ok(Some(v_at_offset))
+/// Apply `f` to `len` bytes of an off-heap region or primitive array.
+fn with_memory(loc: &Location, len: usize, f: impl FnOnce(&mut [u8])) {
+    match loc {
+        Location::Raw(addr) => f(unsafe { std::slice::from_raw_parts_mut(*addr as *mut u8, len) }),
+        Location::Bytes(slot_id, pos) => oop::with_heap(|heap| {
+            let desc = heap.get(*slot_id);
+            let mut guard = desc.write().unwrap();
+            let ary = guard.v.extract_mut_type_array();
+            f(&mut ary.as_mut_bytes()[*pos..*pos + len])
+        }),
+        _ => unreachable!(),
+    }
 }
 
 fn jvm_setMemory(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let _this = args.get(0).unwrap();
     let obj = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long() as usize;
+    let offset = args.get(2).unwrap().extract_long();
     let size = args.get(3).unwrap().extract_long() as usize;
-    let value = args.get(4).unwrap().extract_int();
+    let value = args.get(4).unwrap().extract_int() as u8;
 
-    match obj {
-        Oop::Null => {
-            let dest = offset as *mut libc::c_void;
-            unsafe {
-                libc::memset(dest, value, size as usize);
-            }
-        }
-        Oop::Ref(rf) => {
-            oop::with_heap_mut(|heap| {
-                let desc = heap.get(*rf);
-                let mut guard = desc.write().unwrap();
-                let ary = guard.v.extract_mut_type_array();
-                let bytes = ary.extract_mut_bytes();
-                unsafe {
-                    let addr = bytes.as_mut_ptr().add(offset);
-                    std::ptr::write_bytes(addr, value as u8, size);
-                }
-            });
-        }
-        _ => unimplemented!(),
-    }
+    let loc = resolve_memory(obj, offset, size)?;
+    with_memory(&loc, size, |bytes| bytes.fill(value));
 
     Ok(None)
 }
 
-fn jvm_putChar(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let dest = args.get(1).unwrap().extract_long() as *mut libc::c_void;
-    let value = args.get(2).unwrap().extract_int();
+fn jvm_copyMemory(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let src_obj = args.get(1).unwrap();
+    let src_offset = args.get(2).unwrap().extract_long();
+    let dest_obj = args.get(3).unwrap();
+    let dest_offset = args.get(4).unwrap().extract_long();
+    let size = args.get(5).unwrap().extract_long() as usize;
 
-    unsafe {
-        libc::memset(dest, value, 1);
-    }
+    let src = resolve_memory(src_obj, src_offset, size)?;
+    let dest = resolve_memory(dest_obj, dest_offset, size)?;
+    //through a buffer: source and destination may be the same array
+    let data = read_memory(&src, size);
+    with_memory(&dest, size, |bytes| bytes.copy_from_slice(&data));
 
     Ok(None)
 }
 
-fn jvm_copyMemory(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let _this = args.get(0).unwrap();
+fn jvm_copySwapMemory0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let src_obj = args.get(1).unwrap();
-    let src_offset = args.get(2).unwrap().extract_long() as usize;
+    let src_offset = args.get(2).unwrap().extract_long();
     let dest_obj = args.get(3).unwrap();
-    let dest_offset = args.get(4).unwrap().extract_long() as usize;
+    let dest_offset = args.get(4).unwrap().extract_long();
     let size = args.get(5).unwrap().extract_long() as usize;
+    let elem_size = args.get(6).unwrap().extract_long() as usize;
 
-    match src_obj {
-        Oop::Null => {
-            match dest_obj {
-                //raw -> raw
-                Oop::Null => {
-                    let src_ptr = src_offset as *const u8;
-                    let dest_ptr = dest_offset as *mut u8;
-                    unsafe {
-                        std::ptr::copy(src_ptr, dest_ptr, size);
-                    }
-                }
-                //raw -> byte[]
-                Oop::Ref(dest_slot) => {
-                    oop::with_heap_mut(|heap| {
-                        let desc = heap.get(*dest_slot);
-                        let mut guard = desc.write().unwrap();
-                        let dest_ary = guard.v.extract_mut_type_array();
-                        let dest = dest_ary.extract_mut_bytes();
-                        let dest = &mut dest[dest_offset..];
-                        let src_ptr = src_offset as *const u8;
-                        unsafe {
-                            for i in 0..size {
-                                let p = src_ptr.add(i);
-                                dest[i] = *p;
-                            }
-                        }
-                    });
-                }
-                _ => unimplemented!(),
-            }
-        }
-        Oop::Ref(src_slot) => {
-            match dest_obj {
-                //byte[] -> raw
-                Oop::Null => {
-                    let src_bytes = oop::with_heap(|heap| {
-                        let desc = heap.get(*src_slot);
-                        let guard = desc.read().unwrap();
-                        let src_ary = guard.v.extract_type_array();
-                        src_ary.extract_bytes().to_vec()
-                    });
-                    let ptr = dest_offset as *mut u8;
-                    unsafe {
-                        for i in 0..size {
-                            let p = ptr.add(i);
-                            *p = src_bytes[src_offset + i];
-                        }
-                    }
-                }
-                //byte[] -> byte[]
-                Oop::Ref(dest_slot) => {
-                    java_lang_System::arraycopy_diff_obj(
-                        *src_slot,
-                        src_offset,
-                        *dest_slot,
-                        dest_offset,
-                        size,
-                    );
-                }
-                _ => unimplemented!(),
-            }
-        }
-        _ => unreachable!(),
+    let src = resolve_memory(src_obj, src_offset, size)?;
+    let dest = resolve_memory(dest_obj, dest_offset, size)?;
+    let mut data = read_memory(&src, size);
+    for elem in data.chunks_mut(elem_size) {
+        elem.reverse();
     }
+    with_memory(&dest, size, |bytes| bytes.copy_from_slice(&data));
 
     Ok(None)
 }
 
-fn jvm_getChar(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let arg1 = args.get(1).unwrap();
-    let ptr = arg1.extract_long() as *const u16;
-    let v = unsafe { *ptr };
-    Ok(Some(Oop::new_int(v as i32)))
+fn jvm_getLoadAverage(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let loadavg = args.get(1).unwrap();
+    let nelems = args.get(2).unwrap().extract_int().clamp(0, 3);
+
+    let mut values = [0f64; 3];
+    let n = unsafe { libc::getloadavg(values.as_mut_ptr(), nelems) };
+    if n > 0 {
+        oop::with_heap(|heap| {
+            let desc = heap.get(loadavg.extract_ref());
+            let mut guard = desc.write().unwrap();
+            if let oop::TypeArrayDesc::Double(ary) = guard.v.extract_mut_type_array() {
+                ary[..n as usize].copy_from_slice(&values[..n as usize]);
+            }
+        });
+    }
+
+    Ok(Some(Oop::new_int(n)))
 }
 
-fn jvm_putObject(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let _this = args.get(0).unwrap();
-    let o = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long() as usize;
-    let x = args.get(3).unwrap();
+fn jvm_allocateInstance(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let clazz = args.get(1).unwrap();
+    //primitive and array mirrors, interfaces and abstract classes have no instances
+    let (target, vt) = Oop::mirror_target_and_vt(clazz.extract_ref());
+    let target = match target {
+        Some(target) => {
+            let cls = target.get_class();
+            if cls.is_instance() && !cls.is_interface() && !cls.is_abstract() {
+                target.clone()
+            } else {
+                let name = String::from_utf8_lossy(cls.name.as_slice()).replace('/', ".");
+                return Err(exception::new(b"java/lang/InstantiationException", Some(name)));
+            }
+        }
+        None => {
+            let name = String::from_utf8_lossy(vt.get_primitive_name()).to_string();
+            return Err(exception::new(b"java/lang/InstantiationException", Some(name)));
+        }
+    };
+    oop::class::init_class(&target);
+    oop::class::init_class_fully(&target);
+    Ok(Some(Oop::new_inst(target)))
+}
 
-    let rf = o.extract_ref();
-    Class::put_field_value2(rf, offset, x.clone());
-    Ok(None)
+fn jvm_throwException(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    Err(args.get(1).unwrap().clone())
+}
+
+fn jvm_objectFieldOffset(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let field = args.get(1).unwrap();
+    objectFieldOffset(field, false)
+}
+
+fn jvm_objectFieldOffset1(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    // args: [this, class, fieldName]
+    let class_oop = args.get(1).unwrap();
+    let name_oop = args.get(2).unwrap();
+    let cls_slot = class_oop.extract_ref();
+    let (cls_ref, _) = Oop::mirror_target_and_vt(cls_slot);
+    if let Some(cls_ref) = cls_ref {
+        let cls = cls_ref.get_class();
+        let name_bytes: Vec<u8> = Oop::java_lang_string_value(name_oop.extract_ref())
+            .iter()
+            .map(|&c| c as u8)
+            .collect();
+        let name_ref: BytesRef = Arc::new(name_bytes);
+        //a field declared by the class itself, of any type
+        let own = |fields: Option<FxHashMap<(BytesRef, BytesRef, BytesRef), FieldIdRef>>| {
+            fields.and_then(|fields| {
+                fields
+                    .into_iter()
+                    .find(|((c, n, _), _)| *c == cls.name && *n == name_ref)
+                    .map(|(_, fid)| fid)
+            })
+        };
+        if let Some(fid) = own(cls.get_inst_fields()) {
+            return Ok(Some(Oop::new_long(fid.offset as i64)));
+        }
+        if let Some(fid) = own(cls.get_static_fields()) {
+            return Ok(Some(Oop::new_long(fid.offset as i64 | STATIC_FIELD_TAG)));
+        }
+    }
+    Ok(Some(Oop::new_long(-1)))
 }
 
 fn jvm_ensureClassInitialized(_env: JNIEnv, args: &[Oop]) -> JNIResult {
@@ -528,91 +944,65 @@ fn jvm_staticFieldBase(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     Ok(Some(v))
 }
 
-fn jvm_putByte(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let _this = args.get(0).unwrap();
-    let obj = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long() as usize;
-    let x = args.get(3).unwrap().extract_int();
-
-    match obj {
-        Oop::Null => {
-            let dest = offset as *mut libc::c_void;
-            unsafe {
-                libc::memset(dest, x, 1);
-            }
-        }
-        Oop::Ref(rf) => {
-            oop::with_heap_mut(|heap| {
-                let desc = heap.get(*rf);
-                let mut guard = desc.write().unwrap();
-                let ary = guard.v.extract_mut_type_array();
-                let bytes = ary.extract_mut_bytes();
-                bytes[offset] = x as u8;
-            });
-        }
-        t => unimplemented!("{:?}", t),
-    }
-
-    Ok(None)
-}
-
-fn jvm_getByte2(_env: JNIEnv, args: &[Oop]) -> JNIResult {
-    let _this = args.get(0).unwrap();
-    let obj = args.get(1).unwrap();
-    let offset = args.get(2).unwrap().extract_long() as usize;
-
-    let v = match obj {
-        Oop::Null => {
-            let ptr = offset as *const u8;
-            let v = unsafe { *ptr };
-            Oop::new_int(v as i32)
-        }
-        Oop::Ref(rf) => {
-            let v = oop::with_heap(|heap| {
-                let desc = heap.get(*rf);
-                let guard = desc.read().unwrap();
-                let ary = guard.v.extract_type_array();
-                let bytes = ary.extract_bytes();
-                bytes[offset]
-            });
-            Oop::new_int(v as i32)
-        }
-        t => unimplemented!("{:?}", t),
-    };
-
-    Ok(Some(v))
-}
-
 fn jvm_park(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let _this = args.get(0).unwrap();
     let is_absolute = args.get(1).unwrap().extract_int() != 0;
-    let time = args.get(2).unwrap().extract_long() as u64;
+    let time = args.get(2).unwrap().extract_long();
 
-    if is_absolute {
+    //absolute: deadline in epoch millis; relative: nanos, 0 means indefinitely
+    let now = Instant::now();
+    let deadline = if is_absolute {
         let epoch_duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        let diff = Duration::from_millis(time) - epoch_duration;
-        std::thread::park_timeout(diff);
+        let diff = Duration::from_millis(time.max(0) as u64).saturating_sub(epoch_duration);
+        Some(now + diff)
+    } else if time > 0 {
+        Some(now + Duration::from_nanos(time as u64))
+    } else if time == 0 {
+        None
     } else {
-        if time != 0 {
-            std::thread::park_timeout(Duration::from_nanos(time));
+        return Ok(None);
+    };
+
+    let jt = runtime::thread::current_java_thread();
+    let parker = {
+        let jt = jt.read().unwrap();
+        if deadline.is_some() {
+            jt.set_status(status::PARKED_TIMED);
         } else {
-            std::thread::park();
+            jt.set_status(status::PARKED);
         }
-    }
+        jt.parker.clone()
+    };
+
+    parker.park(deadline);
 
+    jt.read().unwrap().set_status(status::RUNNABLE);
     Ok(None)
 }
 
 fn jvm_fullFence(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
-    // No-op: full memory fence. For correctness, we use compiler fence.
-    atomic::compiler_fence(atomic::Ordering::SeqCst);
+    atomic::fence(atomic::Ordering::SeqCst);
+    Ok(None)
+}
+
+fn jvm_loadFence(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    atomic::fence(atomic::Ordering::Acquire);
+    Ok(None)
+}
+
+fn jvm_storeFence(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    atomic::fence(atomic::Ordering::Release);
     Ok(None)
 }
 
 fn jvm_unpark(_env: JNIEnv, args: &[Oop]) -> JNIResult {
     let _this = args.get(0).unwrap();
-    let _thread = args.get(1).unwrap();
-    // No-op: thread unpark
+    let thread = args.get(1).unwrap();
+    //a thread not started yet has nothing to unpark
+    if let Some(jt) = vm::get_vm().threads.find_by_thread_obj(thread) {
+        let parker = jt.read().unwrap().parker.clone();
+        parker.unpark();
+    }
     Ok(None)
 }
 
@@ -668,5 +1058,10 @@ fn objectFieldOffset(field: &Oop, is_static: bool) -> JNIResult {
         v.extract_int()
     };
 
-    Ok(Some(Oop::new_long(slot as i64)))
+    let offset = if is_static {
+        slot as i64 | STATIC_FIELD_TAG
+    } else {
+        slot as i64
+    };
+    Ok(Some(Oop::new_long(offset)))
 }
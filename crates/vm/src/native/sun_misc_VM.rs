@@ -2,11 +2,74 @@
 
 use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
 use crate::oop::Oop;
+use crate::runtime::require_class3;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn get_native_methods() -> Vec<JNINativeMethod> {
-    vec![new_fn("initialize", "()V", Box::new(jvm_initialize))]
+    vec![
+        new_fn("initialize", "()V", Box::new(jvm_initialize)),
+        new_fn(
+            "latestUserDefinedLoader0",
+            "()Ljava/lang/ClassLoader;",
+            Box::new(jvm_latestUserDefinedLoader0),
+        ),
+        new_fn("getuid", "()J", Box::new(jvm_getuid)),
+        new_fn("geteuid", "()J", Box::new(jvm_geteuid)),
+        new_fn("getgid", "()J", Box::new(jvm_getgid)),
+        new_fn("getegid", "()J", Box::new(jvm_getegid)),
+        new_fn(
+            "getNanoTimeAdjustment",
+            "(J)J",
+            Box::new(jvm_getNanoTimeAdjustment),
+        ),
+        new_fn(
+            "getRuntimeArguments",
+            "()[Ljava/lang/String;",
+            Box::new(jvm_getRuntimeArguments),
+        ),
+    ]
 }
 
 fn jvm_initialize(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
     Ok(None)
 }
+
+//only the boot loader loads classes
+fn jvm_latestUserDefinedLoader0(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::Null))
+}
+
+fn jvm_getuid(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_long(unsafe { libc::getuid() } as i64)))
+}
+
+fn jvm_geteuid(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_long(unsafe { libc::geteuid() } as i64)))
+}
+
+fn jvm_getgid(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_long(unsafe { libc::getgid() } as i64)))
+}
+
+fn jvm_getegid(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_long(unsafe { libc::getegid() } as i64)))
+}
+
+/// Nanoseconds from `offset` seconds since the epoch to now, -1 if that
+/// doesn't fit the range `Instant` accepts.
+fn jvm_getNanoTimeAdjustment(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let offset = args.get(0).unwrap().extract_long();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let diff = now.as_secs() as i64 - offset;
+    let v = if diff.abs() > u32::MAX as i64 {
+        -1
+    } else {
+        diff * 1_000_000_000 + now.subsec_nanos() as i64
+    };
+    Ok(Some(Oop::new_long(v)))
+}
+
+fn jvm_getRuntimeArguments(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    let cls = require_class3(None, b"[Ljava/lang/String;").unwrap();
+    Ok(Some(Oop::new_ref_ary2(cls, vec![])))
+}
@@ -0,0 +1,28 @@
+#![allow(non_snake_case)]
+
+use crate::native::common::resource;
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+
+//a Java method the VM takes over (see init_vm::hack_classes), JarFile
+//needs the zip natives
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![new_fn(
+        "getInputStream",
+        "()Ljava/io/InputStream;",
+        Box::new(jvm_getInputStream),
+    )]
+}
+
+fn jvm_getInputStream(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let url = match resource::connection_url(args.first().unwrap()) {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+    resource::open_url(&url, || {
+        //same message as JarURLConnection.connect
+        let (jar, entry) = url.split_once("!/").unwrap_or((&url, ""));
+        let jar = jar.trim_start_matches("jar:").trim_start_matches("file:");
+        format!("JAR entry {} not found in {}", entry, jar)
+    })
+}
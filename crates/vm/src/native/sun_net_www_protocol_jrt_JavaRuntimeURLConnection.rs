@@ -0,0 +1,23 @@
+#![allow(non_snake_case)]
+
+use crate::native::common::resource;
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+
+//Java methods the VM takes over (see init_vm::hack_classes): the content
+//comes from the runtime image the VM has open
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![new_fn(
+        "getInputStream",
+        "()Ljava/io/InputStream;",
+        Box::new(jvm_getInputStream),
+    )]
+}
+
+fn jvm_getInputStream(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let url = match resource::connection_url(args.first().unwrap()) {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+    resource::open_url(&url, || format!("{} not found", url))
+}
@@ -0,0 +1,30 @@
+#![allow(non_snake_case)]
+
+use crate::native::{new_fn, JNIEnv, JNINativeMethod, JNIResult};
+use crate::oop::Oop;
+use crate::runtime::exception;
+use std::os::unix::ffi::OsStringExt;
+
+/// Only enough of the dispatcher for `FileSystems.getDefault()` and
+/// `Paths.get`, which `ImageReaderFactory` needs to open the runtime image.
+pub fn get_native_methods() -> Vec<JNINativeMethod> {
+    vec![
+        new_fn("init", "()I", Box::new(jvm_init)),
+        new_fn("getcwd", "()[B", Box::new(jvm_getcwd)),
+    ]
+}
+
+//no optional capabilities (openat, futimes, futimens, lutimes, birthtime)
+fn jvm_init(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    Ok(Some(Oop::new_int(0)))
+}
+
+fn jvm_getcwd(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
+    match std::env::current_dir() {
+        Ok(dir) => Ok(Some(Oop::new_byte_ary2(dir.into_os_string().into_vec()))),
+        Err(e) => Err(exception::new(
+            b"java/lang/InternalError",
+            Some(e.to_string()),
+        )),
+    }
+}
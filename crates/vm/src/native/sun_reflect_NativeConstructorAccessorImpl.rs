@@ -42,6 +42,9 @@ fn jvm_newInstance0(_env: JNIEnv, args: &[Oop]) -> JNIResult {
         }
     }
 
+    oop::class::init_class(&target_cls);
+    oop::class::init_class_fully(&target_cls);
+
     let oop = Oop::new_inst(target_cls.clone());
     ctor_args.insert(0, oop.clone());
     runtime::invoke::invoke_ctor(target_cls, new_br(signature.as_str()), ctor_args);
@@ -3,6 +3,8 @@
 use crate::native::{self, new_fn, JNIEnv, JNINativeMethod, JNIResult};
 use crate::oop::Oop;
 use crate::runtime;
+use crate::types::ClassRef;
+use std::sync::Arc;
 use tracing::{debug, error, info, trace, warn};
 
 pub fn get_native_methods() -> Vec<JNINativeMethod> {
@@ -17,6 +19,11 @@ pub fn get_native_methods() -> Vec<JNINativeMethod> {
             "(Ljava/lang/Class;)I",
             Box::new(jvm_getClassAccessFlags),
         ),
+        new_fn(
+            "areNestMates",
+            "(Ljava/lang/Class;Ljava/lang/Class;)Z",
+            Box::new(jvm_areNestMates),
+        ),
     ]
 }
 
@@ -39,9 +46,13 @@ fn jvm_getCallerClass(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
             let caller = caller.try_read().unwrap();
             caller.mir.clone()
         };
+        //JDK 9+ moved the annotation to jdk.internal.reflect
         if caller
             .method
             .check_annotation(b"Lsun/reflect/CallerSensitive;")
+            || caller
+                .method
+                .check_annotation(b"Ljdk/internal/reflect/CallerSensitive;")
         {
             continue;
         }
@@ -55,3 +66,17 @@ fn jvm_getCallerClass(_env: JNIEnv, _args: &[Oop]) -> JNIResult {
 fn jvm_getClassAccessFlags(env: JNIEnv, args: &[Oop]) -> JNIResult {
     native::java_lang_Class::jvm_getModifiers(env, args)
 }
+
+//JDK 11+: private members are accessible within a nest
+fn jvm_areNestMates(_env: JNIEnv, args: &[Oop]) -> JNIResult {
+    let nest_host = |mirror: &Oop| -> Option<ClassRef> {
+        let cls = Oop::mirror_target(mirror.extract_ref())?;
+        let host = cls.get_class().get_nest_host();
+        Some(host.unwrap_or(cls))
+    };
+    let v = match (nest_host(&args[0]), nest_host(&args[1])) {
+        (Some(a), Some(b)) => Arc::ptr_eq(&a, &b),
+        _ => false,
+    };
+    Ok(Some(Oop::new_int(v as i32)))
+}
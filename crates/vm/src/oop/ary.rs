@@ -68,6 +68,55 @@ impl TypeArrayDesc {
 }
 
 impl TypeArrayDesc {
+    /// Size of one element in bytes.
+    pub fn elem_size(&self) -> usize {
+        match self {
+            TypeArrayDesc::Byte(_) | TypeArrayDesc::Bool(_) => 1,
+            TypeArrayDesc::Char(_) | TypeArrayDesc::Short(_) => 2,
+            TypeArrayDesc::Int(_) | TypeArrayDesc::Float(_) => 4,
+            TypeArrayDesc::Long(_) | TypeArrayDesc::Double(_) => 8,
+        }
+    }
+
+    /// The elements as bytes in native byte order, for `Unsafe` accesses
+    /// that address an array by byte offset.
+    pub fn as_bytes(&self) -> &[u8] {
+        fn bytes<T>(v: &[T]) -> &[u8] {
+            // any initialized primitive array is also a valid byte array
+            unsafe { std::slice::from_raw_parts(v.as_ptr() as *const u8, std::mem::size_of_val(v)) }
+        }
+        match self {
+            TypeArrayDesc::Byte(v) => bytes(v),
+            TypeArrayDesc::Bool(v) => bytes(v),
+            TypeArrayDesc::Char(v) => bytes(v),
+            TypeArrayDesc::Short(v) => bytes(v),
+            TypeArrayDesc::Float(v) => bytes(v),
+            TypeArrayDesc::Double(v) => bytes(v),
+            TypeArrayDesc::Int(v) => bytes(v),
+            TypeArrayDesc::Long(v) => bytes(v),
+        }
+    }
+
+    /// Mutable version of `as_bytes`.
+    pub fn as_mut_bytes(&mut self) -> &mut [u8] {
+        fn bytes<T>(v: &mut [T]) -> &mut [u8] {
+            // primitive element types accept any bit pattern
+            unsafe {
+                std::slice::from_raw_parts_mut(v.as_mut_ptr() as *mut u8, std::mem::size_of_val(v))
+            }
+        }
+        match self {
+            TypeArrayDesc::Byte(v) => bytes(v),
+            TypeArrayDesc::Bool(v) => bytes(v),
+            TypeArrayDesc::Char(v) => bytes(v),
+            TypeArrayDesc::Short(v) => bytes(v),
+            TypeArrayDesc::Float(v) => bytes(v),
+            TypeArrayDesc::Double(v) => bytes(v),
+            TypeArrayDesc::Int(v) => bytes(v),
+            TypeArrayDesc::Long(v) => bytes(v),
+        }
+    }
+
     pub fn extract_chars(&self) -> &CharAry {
         match self {
             TypeArrayDesc::Char(v) => v,
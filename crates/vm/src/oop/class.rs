@@ -12,8 +12,9 @@ use classfile::{
 
 use crate::oop::field;
 use crate::oop::heap::Heap;
-use crate::oop::{self, consts as oop_consts, Oop, RefKindDesc, ValueType};
+use crate::oop::{self, consts as oop_consts, Monitor, Oop, RefKindDesc, ValueType};
 use crate::oop::{with_heap, with_heap_mut};
+use crate::runtime::cds::SharedLayout;
 use crate::runtime::method::MethodId;
 use crate::runtime::thread::ReentrantMutex;
 use crate::runtime::{
@@ -130,6 +131,9 @@ pub struct ClassObject {
     pub inner_classes: Option<Vec<InnerClass>>,
 
     pub cp_cache: ConstantPoolCache,
+
+    /// Layout restored from the CDS archive, consumed by linking.
+    shared: Option<SharedLayout>,
 }
 
 pub struct ArrayClassObject {
@@ -152,7 +156,9 @@ pub fn init_class(class: &ClassRef) {
     let need = { class.get_class_state() == State::Linked };
     if need {
         let clinit_mutex = class.clinit_mutex.clone();
-        let _l = clinit_mutex.lock().unwrap();
+        //another thread may hold it while running <clinit>, and be stopped
+        //at a safepoint
+        let _l = runtime::thread::safepoint::blocked(|| clinit_mutex.lock().unwrap());
 
         class.set_class_state(State::BeingIni);
         if let Some(super_class) = class.get_super_class() {
@@ -315,13 +321,15 @@ impl Class {
         (self.acc_flags & ACC_INTERFACE) == ACC_INTERFACE
     }
 
-    pub fn monitor_enter(&self) {
-        // Class-level monitor — using the clinit_mutex as a simple stand-in
-        let _guard = self.clinit_mutex.lock();
-    }
-
-    pub fn monitor_exit(&self) {
-        // MutexGuard is dropped, releasing the lock
+    /// The class mirror, locked by `static synchronized` methods. None
+    /// before the mirror is created during bootstrap.
+    pub fn lock_obj(&self) -> Option<Oop> {
+        let kind = self.kind_read();
+        match kind.deref() {
+            ClassKind::Instance(cls_obj) => cls_obj.mirror.clone(),
+            ClassKind::TypeArray(typ_ary) => typ_ary.mirror.clone(),
+            ClassKind::ObjectArray(obj_ary) => obj_ary.mirror.clone(),
+        }
     }
 }
 
@@ -368,6 +376,9 @@ impl Class {
                         None => 0,
                     };
                     class_obj.link_fields(self_ref.clone(), self.name.clone(), n);
+                    //preparation: static fields start at their default or
+                    //ConstantValue, before '<clinit>' runs
+                    class_obj.init_static_fields();
                     class_obj.link_interfaces();
                     class_obj.link_methods(self_ref, self.name.clone());
                     class_obj.link_attributes();
@@ -435,6 +446,50 @@ impl Class {
         }
     }
 
+    /// The constant pool and the linked layout, written to the CDS archive.
+    pub fn shared_layout(&self) -> Option<(classfile::ConstantPool, SharedLayout)> {
+        if self.get_class_state() < State::Linked {
+            return None;
+        }
+        let super_fields = self
+            .get_super_class()
+            .and_then(|c| c.get_n_inst_fields())
+            .unwrap_or(0);
+
+        let kind = self.kind_read();
+        match kind.deref() {
+            ClassKind::Instance(cls_obj) => {
+                let cf = &cls_obj.class_file;
+                let field_offsets = cf
+                    .fields
+                    .iter()
+                    .map(|it| {
+                        let name = get_cp_utf8(&cf.cp, it.name_index as usize);
+                        let desc = get_cp_utf8(&cf.cp, it.desc_index as usize);
+                        let k = (self.name.clone(), name.clone(), desc.clone());
+                        cls_obj
+                            .inst_fields
+                            .get(&k)
+                            .or_else(|| cls_obj.static_fields.get(&k))
+                            .map(|fid| fid.offset as u16)
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                let mut v_table: Vec<u16> =
+                    cls_obj.v_table.values().map(|m| m.offset as u16).collect();
+                v_table.sort_unstable();
+
+                let layout = SharedLayout {
+                    super_fields: super_fields as u16,
+                    n_inst_fields: cls_obj.n_inst_fields as u16,
+                    field_offsets,
+                    v_table,
+                };
+                Some((cf.cp.clone(), layout))
+            }
+            _ => None,
+        }
+    }
+
     /// Get instance and static field maps (only for instance classes).
     pub fn get_inst_and_static_fields(
         &self,
@@ -492,7 +547,7 @@ impl Class {
     pub fn get_cp_field(&self, idx: usize, is_static: bool) -> Option<FieldIdRef> {
         let kind = self.kind_read();
         match kind.deref() {
-            ClassKind::Instance(cls_obj) => Some(cls_obj.cp_cache.get_field(idx, is_static)),
+            ClassKind::Instance(cls_obj) => cls_obj.cp_cache.get_field(idx, is_static),
             _ => None,
         }
     }
@@ -560,6 +615,33 @@ impl Class {
         }
     }
 
+    /// The class named by the NestHost attribute, if that class lists this
+    /// one in its NestMembers. `None` means the class is its own nest host.
+    pub fn get_nest_host(&self) -> Option<ClassRef> {
+        let host_index = match self.kind_read().deref() {
+            ClassKind::Instance(cls_obj) => cls_obj.class_file.nest_host()?,
+            _ => return None,
+        };
+        let host = self.resolve_cp_class_by_index(host_index as u16)?;
+
+        let is_member = match host.get_class().kind_read().deref() {
+            ClassKind::Instance(cls_obj) => {
+                let cp = &cls_obj.class_file.cp;
+                cls_obj.class_file.nest_members().is_some_and(|members| {
+                    members.iter().any(|it| {
+                        classfile::constant_pool::get_class_name(cp, *it as usize) == &self.name
+                    })
+                })
+            }
+            _ => false,
+        };
+        if is_member {
+            Some(host)
+        } else {
+            None
+        }
+    }
+
     pub fn get_inner_classes(&self) -> Option<Vec<InnerClass>> {
         let kind = self.kind_read();
         match kind.deref() {
@@ -713,8 +795,13 @@ impl Class {
         self.get_class_method_inner(name, desc, true)
     }
 
+    /// Resolves a method of this class, its super classes, then its super
+    /// interfaces (JVMS 5.4.3.3).
     pub fn get_class_method(&self, name: &BytesRef, desc: &BytesRef) -> Result<MethodIdRef, ()> {
-        self.get_class_method_inner(name, desc, true)
+        self.get_class_method_inner(name, desc, true).or_else(|_| {
+            self.find_super_interface_method(&(name.clone(), desc.clone()))
+                .ok_or(())
+        })
     }
 
     pub fn get_this_class_method(
@@ -841,6 +928,32 @@ impl Class {
         })
     }
 
+    /// Read-modify-write a field value: `f` gets the current value and
+    /// returns the value to store, if any. The read and the write happen
+    /// under one object lock, so concurrent updates of a field serialize.
+    /// Returns the previous value.
+    pub fn update_field_value2(
+        slot_id: u32,
+        offset: usize,
+        f: impl FnOnce(&Oop) -> Option<Oop>,
+    ) -> Oop {
+        with_heap(|heap| {
+            let desc = heap.get(slot_id);
+            let mut guard = desc.write().unwrap();
+            let cur = match &mut guard.v {
+                oop::RefKind::Inst(inst) => &mut inst.field_values[offset],
+                oop::RefKind::Mirror(mirror) => &mut mirror.field_values[offset],
+                oop::RefKind::Array(ary) => &mut ary.elements[offset],
+                t => unreachable!("t = {:?}", t),
+            };
+            let prev = cur.clone();
+            if let Some(v) = f(&prev) {
+                *cur = v;
+            }
+            prev
+        })
+    }
+
     /// Convenience wrapper: get a field value using a FieldIdRef.
     pub fn get_field_value(slot_id: u32, fid: FieldIdRef) -> Oop {
         Self::get_field_value2(slot_id, fid.offset)
@@ -868,6 +981,28 @@ impl Class {
         }
     }
 
+    /// Read-modify-write the static field at `offset` declared by this class
+    /// (`Unsafe` addresses static fields by declaring class and offset),
+    /// see `update_field_value2`. Returns the previous value.
+    pub fn update_static_field_value2(
+        &self,
+        offset: usize,
+        f: impl FnOnce(&Oop) -> Option<Oop>,
+    ) -> Oop {
+        let mut kind = self.kind_write();
+        match kind.deref_mut() {
+            ClassKind::Instance(cls_obj) => {
+                let cur = &mut cls_obj.static_field_values[offset];
+                let prev = cur.clone();
+                if let Some(v) = f(&prev) {
+                    *cur = v;
+                }
+                prev
+            }
+            _ => unreachable!(),
+        }
+    }
+
     pub fn get_static_field_value(&self, fid: FieldIdRef) -> Oop {
         let kind = self.kind_read();
         match kind.deref() {
@@ -971,7 +1106,8 @@ impl Class {
             source_file: None,
             enclosing_method: None,
             inner_classes: None,
-            cp_cache: ConstantPoolCache::new(cp),
+            cp_cache: ConstantPoolCache::new(name.clone(), cp),
+            shared: None,
         };
 
         Self {
@@ -985,6 +1121,20 @@ impl Class {
         }
     }
 
+    /// A class with a layout from the CDS archive. Linking takes the archived
+    /// field offsets and vtable if the super class layout still matches.
+    pub fn new_shared_class(
+        class_file: ClassFileRef,
+        class_loader: Option<ClassLoader>,
+        layout: SharedLayout,
+    ) -> Self {
+        let class = Self::new_class(class_file, class_loader);
+        if let ClassKind::Instance(class_obj) = class.kind.write().unwrap().deref_mut() {
+            class_obj.shared = Some(layout);
+        }
+        class
+    }
+
     pub fn new_object_ary(class_loader: ClassLoader, component: ClassRef, elm_name: &[u8]) -> Self {
         let name = Vec::from(elm_name);
         let name = Arc::new(name);
@@ -1113,23 +1263,38 @@ impl ClassObject {
         let cls_file = self.class_file.clone();
         let cp = &cls_file.cp;
 
+        // the super class has a different number of instance fields than at
+        // dump time, the archived offsets are stale
+        if let Some(layout) = &self.shared {
+            if layout.super_fields as usize != num_field_of_super
+                || layout.field_offsets.len() != cls_file.fields.len()
+            {
+                debug!(
+                    "shared layout of {} does not match its super class",
+                    String::from_utf8_lossy(cls_name.as_slice())
+                );
+                self.shared = None;
+            }
+        }
+
         let mut n_static = 0;
         let mut offset_field = num_field_of_super;
 
-        cls_file.fields.iter().for_each(|it| {
+        cls_file.fields.iter().enumerate().for_each(|(i, it)| {
             let field = field::Field::new(cp, it, cls_name.clone(), self_ref.clone());
             let k = (cls_name.clone(), field.name.clone(), field.desc.clone());
+            let shared = self.shared.as_ref().map(|l| l.field_offsets[i] as usize);
 
             if field.is_static() {
                 let fid = field::FieldId {
-                    offset: n_static,
+                    offset: shared.unwrap_or(n_static),
                     field,
                 };
                 self.static_fields.insert(k, Arc::new(fid));
                 n_static += 1;
             } else {
                 let fid = field::FieldId {
-                    offset: offset_field,
+                    offset: shared.unwrap_or(offset_field),
                     field,
                 };
                 self.inst_fields.insert(k, Arc::new(fid));
@@ -1137,7 +1302,10 @@ impl ClassObject {
             }
         });
 
-        self.n_inst_fields = offset_field;
+        self.n_inst_fields = match &self.shared {
+            Some(layout) => layout.n_inst_fields as usize,
+            None => offset_field,
+        };
         self.static_field_values = vec![Oop::Null; n_static];
     }
 
@@ -1179,10 +1347,15 @@ impl ClassObject {
             let k = (name, desc);
             self.all_methods.insert(k.clone(), method_id.clone());
 
-            if !method_id.method.is_static() {
+            let is_virtual = match &self.shared {
+                Some(layout) => layout.v_table.binary_search(&(i as u16)).is_ok(),
+                None => !method_id.method.is_static(),
+            };
+            if is_virtual {
                 self.v_table.insert(k, method_id);
             }
         });
+        self.shared = None;
     }
 
     fn link_attributes(&mut self) {
@@ -1242,10 +1415,12 @@ impl Class {
                     return Ok(m.clone());
                 }
             }
-            ClassKind::ObjectArray(_) => {
+            ClassKind::ObjectArray(_) | ClassKind::TypeArray(_) => {
                 // use java/lang/Object methods
+                drop(kind);
+                let object = runtime::require_class3(None, consts::J_OBJECT).unwrap();
+                return object.get_class().get_class_method_inner(name, desc, false);
             }
-            _ => unreachable!(),
         }
         drop(kind);
 
@@ -1294,27 +1469,82 @@ impl Class {
         let k = (name.clone(), desc.clone());
         let kind = self.kind_read();
         match kind.deref() {
-            ClassKind::Instance(cls_obj) => match cls_obj.v_table.get(&k) {
-                Some(m) => return Ok(m.clone()),
-                None => {
-                    for (_, itf) in cls_obj.interfaces.iter() {
-                        let cls = itf.get_class();
-                        let m = cls.get_interface_method(name, desc);
-                        if m.is_ok() {
-                            return m;
-                        }
-                    }
+            //static and private methods of an interface aren't in the v-table
+            ClassKind::Instance(cls_obj) => {
+                if let Some(m) = cls_obj
+                    .v_table
+                    .get(&k)
+                    .or_else(|| cls_obj.all_methods.get(&k))
+                {
+                    return Ok(m.clone());
                 }
-            },
+            }
             _ => unreachable!(),
         }
         drop(kind);
 
-        match self.get_super_class() {
+        //the super classes come first, then the default methods of the
+        //super interfaces, an abstract method only if nothing implements it
+        let mut found = match self.get_super_class() {
             Some(super_class) => super_class
                 .get_class()
-                .get_interface_method_inner(name, desc),
-            None => Err(()),
+                .get_virtual_method_inner(name, desc)
+                .ok(),
+            None => None,
+        };
+        if let Some(m) = found.as_ref().filter(|m| !m.method.is_abstract()) {
+            return Ok(m.clone());
+        }
+
+        match self.find_super_interface_method(&k) {
+            Some(m) if !m.method.is_abstract() => Ok(m),
+            m => found.or(m).ok_or(()),
+        }
+    }
+
+    //the super interfaces of this class and its super classes
+    fn find_super_interface_method(&self, k: &(BytesRef, BytesRef)) -> Option<MethodIdRef> {
+        let mut found = None;
+        let mut m = self.find_interface_method(k);
+        let mut super_class = self.get_super_class();
+        loop {
+            if let Some(m) = m {
+                if !m.method.is_abstract() {
+                    return Some(m);
+                }
+                found.get_or_insert(m);
+            }
+            match super_class {
+                Some(cls) => {
+                    m = cls.find_interface_method(k);
+                    super_class = cls.get_super_class();
+                }
+                None => return found,
+            }
+        }
+    }
+
+    //depth first, so a sub interface is seen before the interfaces it extends
+    fn find_interface_method(&self, k: &(BytesRef, BytesRef)) -> Option<MethodIdRef> {
+        let interfaces: Vec<ClassRef> = match self.kind_read().deref() {
+            ClassKind::Instance(cls_obj) => cls_obj.interfaces.values().cloned().collect(),
+            _ => return None,
+        };
+
+        let mut found = None;
+        for itf in interfaces.iter() {
+            let m = match itf.kind_read().deref() {
+                ClassKind::Instance(cls_obj) => cls_obj.v_table.get(k).cloned(),
+                _ => None,
+            };
+            let m = m.or_else(|| itf.find_interface_method(k));
+            if let Some(m) = m {
+                if !m.method.is_abstract() {
+                    return Some(m);
+                }
+                found.get_or_insert(m);
+            }
         }
+        found
     }
 }
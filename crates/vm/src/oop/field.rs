@@ -10,6 +10,7 @@ use classfile::{
 };
 use std::fmt;
 use std::ops::Deref;
+use std::sync::atomic::{self, Ordering};
 use std::sync::Arc;
 
 pub fn get_field_ref(cp: &ConstantPool, idx: usize, is_static: bool) -> FieldIdRef {
@@ -89,6 +90,33 @@ pub struct FieldId {
     pub field: Field,
 }
 
+/// Memory barriers around a field access, following the JSR-133 cookbook:
+/// a volatile load acquires, a volatile store releases and is followed by a
+/// full fence so it can't be reordered with a later volatile load.
+/// Accesses to plain fields need no barrier.
+impl FieldId {
+    #[inline]
+    pub fn after_load(&self) {
+        if self.field.is_volatile() {
+            atomic::fence(Ordering::Acquire);
+        }
+    }
+
+    #[inline]
+    pub fn before_store(&self) {
+        if self.field.is_volatile() {
+            atomic::fence(Ordering::Release);
+        }
+    }
+
+    #[inline]
+    pub fn after_store(&self) {
+        if self.field.is_volatile() {
+            atomic::fence(Ordering::SeqCst);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Field {
     pub class: ClassRef,
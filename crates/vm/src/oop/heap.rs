@@ -39,6 +39,14 @@ impl Heap {
         self.slots[slot_id as usize].clone().expect("slot is alive")
     }
 
+    /// Live objects with their slot IDs.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &Arc<RwLock<RefKindDesc>>)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(id, slot)| slot.as_ref().map(|desc| (id as u32, desc)))
+    }
+
     /// Free a slot, making its slot_id available for reuse.
     pub fn free(&mut self, slot_id: u32) {
         self.slots[slot_id as usize] = None;
@@ -0,0 +1,220 @@
+//! Thin locks: the lock word of an object.
+//!
+//! Locking an object no other thread locks is a CAS on the lock word in its
+//! `RefKindDesc`, no `Monitor` is allocated. The word is inflated to a
+//! `Monitor` when a second thread contends for the lock or a thread waits on
+//! the object, and stays inflated.
+//!
+//! Word layout:
+//! - `0`: unlocked
+//! - `owner << 32 | count`: locked `count` times by the thread with lock id
+//!   `owner`; only that thread changes the count
+//! - `INFLATED`: the `Monitor` holds the lock state
+
+use crate::oop::Monitor;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread::ThreadId;
+
+const INFLATED: u64 = 1 << 63;
+const COUNT_MASK: u64 = u32::MAX as u64;
+
+/// Threads by lock id, id `n` is at `n - 1`.
+static OWNERS: Mutex<Vec<ThreadId>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static LOCK_ID: u32 = register_current_thread();
+}
+
+fn register_current_thread() -> u32 {
+    let mut owners = OWNERS.lock().unwrap();
+    owners.push(std::thread::current().id());
+    let id = owners.len() as u32;
+    assert!(id < 1 << 31, "lock ids exhausted");
+    id
+}
+
+fn current_lock_id() -> u32 {
+    LOCK_ID.with(|id| *id)
+}
+
+fn owner_of(word: u64) -> u32 {
+    (word >> 32) as u32
+}
+
+fn count_of(word: u64) -> usize {
+    (word & COUNT_MASK) as usize
+}
+
+fn thread_of(lock_id: u32) -> ThreadId {
+    OWNERS.lock().unwrap()[lock_id as usize - 1]
+}
+
+pub struct LockWord(AtomicU64);
+
+impl LockWord {
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    /// Lock for the current thread if the object is unlocked or already
+    /// thin-locked by it. False if inflated or owned by another thread.
+    pub fn try_lock(&self) -> bool {
+        let me = current_lock_id();
+        let word = self.0.load(Ordering::Relaxed);
+        let new = match word {
+            0 => (me as u64) << 32 | 1,
+            w if w & INFLATED == 0 && owner_of(w) == me && count_of(w) < COUNT_MASK as usize => {
+                w + 1
+            }
+            _ => return false,
+        };
+        self.0
+            .compare_exchange(word, new, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    /// Release one level of a thin lock held by the current thread. False
+    /// if inflated or not thin-locked by it.
+    pub fn try_unlock(&self) -> bool {
+        let word = self.0.load(Ordering::Relaxed);
+        if word & INFLATED != 0 || word == 0 || owner_of(word) != current_lock_id() {
+            return false;
+        }
+        let new = if count_of(word) == 1 { 0 } else { word - 1 };
+        //fails only if another thread inflated it meanwhile
+        self.0
+            .compare_exchange(word, new, Ordering::Release, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    pub fn is_inflated(&self) -> bool {
+        self.0.load(Ordering::Acquire) & INFLATED != 0
+    }
+
+    /// The thread holding a thin lock.
+    pub fn thin_owner(&self) -> Option<ThreadId> {
+        match self.0.load(Ordering::Acquire) {
+            0 => None,
+            w if w & INFLATED != 0 => None,
+            w => Some(thread_of(owner_of(w))),
+        }
+    }
+
+    /// Whether the current thread holds a thin lock.
+    pub fn is_thin_locked_by_current(&self) -> bool {
+        let word = self.0.load(Ordering::Acquire);
+        word != 0 && word & INFLATED == 0 && owner_of(word) == current_lock_id()
+    }
+
+    /// Hand the lock state to `monitor`: the owner and count of a thin lock
+    /// become the monitor's, whichever thread inflates.
+    pub fn inflate(&self, monitor: &Monitor) {
+        loop {
+            let word = self.0.load(Ordering::Acquire);
+            if word & INFLATED != 0 {
+                return;
+            }
+
+            let mut moved = false;
+            monitor.inflate(|| {
+                let r =
+                    self.0
+                        .compare_exchange(word, INFLATED, Ordering::AcqRel, Ordering::Acquire);
+                moved = r.is_ok();
+                match word {
+                    0 => None,
+                    w if moved => Some((thread_of(owner_of(w)), count_of(w))),
+                    _ => None,
+                }
+            });
+            if moved {
+                return;
+            }
+        }
+    }
+}
+
+impl Default for LockWord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn t_thin_recursion() {
+        let word = LockWord::new();
+        assert!(word.try_lock());
+        assert!(word.try_lock());
+        assert!(word.is_thin_locked_by_current());
+        assert_eq!(word.thin_owner(), Some(std::thread::current().id()));
+
+        let other = std::thread::scope(|s| {
+            s.spawn(|| (word.try_lock(), word.try_unlock()))
+                .join()
+                .unwrap()
+        });
+        assert_eq!(other, (false, false));
+
+        assert!(word.try_unlock());
+        assert!(word.try_unlock());
+        assert!(!word.try_unlock());
+        assert_eq!(word.thin_owner(), None);
+        assert!(!word.is_inflated());
+    }
+
+    #[test]
+    fn t_inflate_moves_owner() {
+        let word = LockWord::new();
+        let monitor = Monitor::new();
+        assert!(word.try_lock());
+        assert!(word.try_lock());
+
+        word.inflate(&monitor);
+        assert!(word.is_inflated());
+        assert!(!word.try_lock());
+        assert!(!word.try_unlock());
+        assert!(monitor.is_owned_by_current());
+
+        monitor.unlock();
+        assert!(monitor.is_owned_by_current());
+        monitor.unlock();
+        assert_eq!(monitor.owner(), None);
+    }
+
+    #[test]
+    fn t_contention_inflates() {
+        let word = Arc::new(LockWord::new());
+        let monitor = Arc::new(Monitor::new());
+        let released = Arc::new(AtomicBool::new(false));
+        assert!(word.try_lock());
+
+        let contender = {
+            let (word, monitor, released) = (word.clone(), monitor.clone(), released.clone());
+            std::thread::spawn(move || {
+                assert!(!word.try_lock());
+                word.inflate(&monitor);
+                monitor.lock();
+                let after_release = released.load(Ordering::SeqCst);
+                monitor.unlock();
+                after_release
+            })
+        };
+
+        while !word.is_inflated() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        //the owner finds the word inflated and releases the monitor
+        assert!(!word.try_unlock());
+        released.store(true, Ordering::SeqCst);
+        monitor.unlock();
+        assert!(contender.join().unwrap());
+    }
+}
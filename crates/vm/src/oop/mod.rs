@@ -9,7 +9,11 @@ use crate::new_br;
 use crate::oop::class::ClassObject;
 use crate::runtime::{require_class3, ClassLoader};
 use crate::types::*;
-use crate::util::oop::{get_java_lang_integer_value_offset, get_java_lang_string_value_offset};
+use crate::util;
+use crate::util::oop::{
+    get_java_lang_integer_value_offset, get_java_lang_string_coder_offset,
+    get_java_lang_string_value_offset,
+};
 
 pub mod ary;
 pub mod class;
@@ -17,6 +21,7 @@ pub mod consts;
 pub mod field;
 pub mod heap;
 pub mod inst;
+pub mod lock_word;
 pub mod mirror;
 pub mod reference;
 pub mod values;
@@ -26,7 +31,7 @@ pub use self::class::{Class, ClassKind};
 pub use self::heap::Heap;
 pub use self::inst::InstOopDesc;
 pub use self::mirror::MirrorOopDesc;
-pub use self::reference::{RefKind, RefKindDesc};
+pub use self::reference::{Monitor, RefKind, RefKindDesc};
 pub use self::values::ValueType;
 
 use std::sync::Mutex;
@@ -384,6 +389,9 @@ impl Oop {
         let offset = get_java_lang_string_value_offset();
         let v = Class::get_field_value2(slot_id, offset);
         let slot_id2 = v.extract_ref();
+        let utf16 = get_java_lang_string_coder_offset()
+            .map(|offset| Class::get_field_value2(slot_id, offset).extract_int())
+            == Some(util::oop::STRING_UTF16);
         with_heap(|heap| {
             let desc = heap.get(slot_id2);
             let guard = desc.read().unwrap();
@@ -391,6 +399,16 @@ impl Oop {
             // JDK 9+: String.value is byte[], JDK 8: char[]
             match ary {
                 TypeArrayDesc::Char(chars) => chars.to_vec(),
+                TypeArrayDesc::Byte(bytes) if utf16 => bytes
+                    .chunks_exact(2)
+                    .map(|b| {
+                        if cfg!(target_endian = "big") {
+                            u16::from_be_bytes([b[0], b[1]])
+                        } else {
+                            u16::from_le_bytes([b[0], b[1]])
+                        }
+                    })
+                    .collect(),
                 TypeArrayDesc::Byte(bytes) => bytes.iter().map(|&b| b as u16).collect(),
                 _ => unreachable!(),
             }
@@ -398,20 +416,12 @@ impl Oop {
     }
 
     pub fn java_lang_string_hash(slot_id: u32) -> i32 {
-        let offset = get_java_lang_string_value_offset();
-        let v = Class::get_field_value2(slot_id, offset);
-        let slot_id2 = v.extract_ref();
-        with_heap(|heap| {
-            let desc = heap.get(slot_id2);
-            let guard = desc.read().unwrap();
-            let chars = guard.v.extract_type_array().extract_chars();
-
-            let mut h = 0i32;
-            for v in chars.iter() {
-                h = h.wrapping_mul(31).wrapping_add(*v as i32);
-            }
-            h
-        })
+        let chars = Self::java_lang_string_value(slot_id);
+        let mut h = 0i32;
+        for v in chars.iter() {
+            h = h.wrapping_mul(31).wrapping_add(*v as i32);
+        }
+        h
     }
 
     pub fn java_lang_integer_value(slot_id: u32) -> i32 {
@@ -449,43 +459,41 @@ impl Oop {
         })
     }
 
-    /// Monitor operations — acquire the monitor for this object and notify all waiters.
-    pub fn notify_all(&self) {
+    /// The monitor of this object, for blocking on it without holding the heap lock.
+    /// Inflates the lock, `try_lock`/`unlock` are cheaper for `synchronized`.
+    pub fn monitor(&self) -> Option<Arc<Monitor>> {
         let slot_id = match self {
             Oop::Ref(id) => *id,
-            _ => return,
+            _ => return None,
         };
-        with_heap(|heap| {
-            let desc = heap.get(slot_id);
-            let guard = desc.read().unwrap();
-            guard.notify_all();
-        })
+        Some(with_heap(|heap| {
+            heap.get(slot_id).read().unwrap().monitor()
+        }))
     }
 
-    /// Wait indefinitely on this object's monitor.
-    pub fn wait(&self) {
-        let slot_id = match self {
-            Oop::Ref(id) => *id,
-            _ => return,
-        };
-        with_heap(|heap| {
-            let desc = heap.get(slot_id);
-            let guard = desc.read().unwrap();
-            guard.wait();
-        })
+    /// Lock this object if that doesn't block, else return the monitor to
+    /// block on without holding the heap lock.
+    pub fn try_lock(&self) -> Result<(), Arc<Monitor>> {
+        let slot_id = self.extract_ref();
+        with_heap(|heap| heap.get(slot_id).read().unwrap().try_lock())
     }
 
-    /// Wait with a timeout on this object's monitor.
-    pub fn wait_timeout(&self, dur: std::time::Duration) {
-        let slot_id = match self {
-            Oop::Ref(id) => *id,
-            _ => return,
-        };
-        with_heap(|heap| {
-            let desc = heap.get(slot_id);
-            let guard = desc.read().unwrap();
-            guard.wait_timeout(dur);
-        })
+    /// Release one lock level. False if the current thread doesn't own it.
+    pub fn unlock(&self) -> bool {
+        let slot_id = self.extract_ref();
+        with_heap(|heap| heap.get(slot_id).read().unwrap().unlock())
+    }
+
+    /// Whether the current thread owns the lock of this object.
+    pub fn is_locked_by_current(&self) -> bool {
+        let slot_id = self.extract_ref();
+        with_heap(|heap| heap.get(slot_id).read().unwrap().is_locked_by_current())
+    }
+
+    /// The monitor, if the lock of this object is inflated.
+    pub fn inflated_monitor(&self) -> Option<Arc<Monitor>> {
+        let slot_id = self.extract_ref();
+        with_heap(|heap| heap.get(slot_id).read().unwrap().inflated_monitor())
     }
 
     /// Object identity equality. Also compares java.lang.String content.
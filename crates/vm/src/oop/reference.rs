@@ -1,23 +1,47 @@
+use crate::oop::lock_word::LockWord;
 use crate::oop::{ArrayOopDesc, InstOopDesc, MirrorOopDesc, TypeArrayDesc};
+use std::collections::VecDeque;
 use std::fmt;
 use std::fmt::Formatter;
-use std::sync::{Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
 use std::thread::ThreadId;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-/// Reentrant mutex for Java monitor semantics.
+/// Java object monitor: a reentrant lock plus a wait set.
 /// Uses std::sync::{Mutex, Condvar} — no unsafe, no pthread.
+///
+/// Threads blocked in `lock` wait on `entry`; threads in `wait` wait on
+/// `wait_set` until their ticket is notified, so `notify` wakes exactly one
+/// waiter, in arrival order, and never a thread that is only trying to lock.
 pub struct Monitor {
-    /// (owner_thread, recursion_count)
-    state: Mutex<(Option<ThreadId>, usize)>,
-    condvar: Condvar,
+    state: Mutex<MonitorState>,
+    entry: Condvar,
+    wait_set: Condvar,
+}
+
+struct MonitorState {
+    owner: Option<ThreadId>,
+    recursions: usize,
+    /// Tickets of the threads in `wait`, in arrival order
+    waiters: VecDeque<u64>,
+    /// Tickets moved out of `waiters` by `notify`/`notify_all`
+    notified: Vec<u64>,
+    next_ticket: u64,
 }
 
 impl Monitor {
     pub fn new() -> Self {
         Self {
-            state: Mutex::new((None, 0)),
-            condvar: Condvar::new(),
+            state: Mutex::new(MonitorState {
+                owner: None,
+                recursions: 0,
+                waiters: VecDeque::new(),
+                notified: Vec::new(),
+                next_ticket: 0,
+            }),
+            entry: Condvar::new(),
+            wait_set: Condvar::new(),
         }
     }
 
@@ -26,29 +50,34 @@ impl Monitor {
         let current = std::thread::current().id();
         let mut guard = self.state.lock().unwrap();
 
-        match guard.0 {
-            Some(owner) if owner == current => {
-                // Same thread re-entering
-                guard.1 += 1;
-            }
-            Some(_) => {
-                // Another thread owns it — wait
-                loop {
-                    guard = self.condvar.wait(guard).unwrap();
-                    if guard.0.is_none() || guard.0 == Some(current) {
-                        guard.0 = Some(current);
-                        guard.1 = 1;
-                        break;
-                    }
-                    // Spurious wakeup or still locked by another thread
-                }
-            }
+        if guard.owner == Some(current) {
+            // Same thread re-entering
+            guard.recursions += 1;
+            return;
+        }
+
+        while guard.owner.is_some() {
+            guard = self.entry.wait(guard).unwrap();
+        }
+        guard.owner = Some(current);
+        guard.recursions = 1;
+    }
+
+    /// Acquire the monitor lock only if it is free or already owned by the
+    /// current thread.
+    pub fn try_lock(&self) -> bool {
+        let current = std::thread::current().id();
+        let mut guard = self.state.lock().unwrap();
+
+        match guard.owner {
+            Some(owner) if owner == current => guard.recursions += 1,
+            Some(_) => return false,
             None => {
-                // Uncontended acquisition
-                guard.0 = Some(current);
-                guard.1 = 1;
+                guard.owner = Some(current);
+                guard.recursions = 1;
             }
         }
+        true
     }
 
     /// Release the monitor lock. Must be called by the owning thread.
@@ -56,90 +85,153 @@ impl Monitor {
         let current = std::thread::current().id();
         let mut guard = self.state.lock().unwrap();
 
-        if guard.0 != Some(current) {
+        if guard.owner != Some(current) {
             panic!("unlock called by non-owner thread");
         }
 
-        guard.1 -= 1;
-        if guard.1 == 0 {
-            guard.0 = None;
-            self.condvar.notify_one();
+        guard.recursions -= 1;
+        if guard.recursions == 0 {
+            guard.owner = None;
+            self.entry.notify_one();
         }
     }
 
     /// Wait on this monitor, releasing the lock. Re-acquires on wakeup.
     pub fn wait(&self) {
-        // Must be called while holding the lock
+        self.wait_inner(None, None);
+    }
+
+    /// Wait with timeout. Returns true if notified, false if timed out.
+    pub fn wait_timeout(&self, dur: Duration) -> bool {
+        self.wait_inner(Some(dur), None)
+    }
+
+    /// Wait like `wait`/`wait_timeout`, but return as soon as `interrupted`
+    /// is set. The flag is checked under the state lock, and `wake_all` takes
+    /// the same lock, so an interrupt that calls `wake_all` is never lost.
+    ///
+    /// Returns true if notified.
+    pub fn wait_interruptibly(&self, timeout: Option<Duration>, interrupted: &AtomicBool) -> bool {
+        self.wait_inner(timeout, Some(interrupted))
+    }
+
+    fn wait_inner(&self, timeout: Option<Duration>, interrupted: Option<&AtomicBool>) -> bool {
         let current = std::thread::current().id();
         let mut guard = self.state.lock().unwrap();
 
-        if guard.0 != Some(current) {
+        if guard.owner != Some(current) {
             panic!("wait called by non-owner thread");
         }
 
-        // Release ownership and wait
-        guard.0 = None;
-        // Remember recursion count — we'll restore it on wakeup
-        let saved_count = guard.1;
-        guard.1 = 0;
-
+        // Join the wait set, then release the lock completely
+        let ticket = guard.next_ticket;
+        guard.next_ticket += 1;
+        guard.waiters.push_back(ticket);
+        let saved_recursions = guard.recursions;
+        guard.owner = None;
+        guard.recursions = 0;
+        self.entry.notify_one();
+
+        let deadline = timeout.map(|dur| Instant::now() + dur);
+        let is_interrupted = || interrupted.is_some_and(|v| v.load(Ordering::SeqCst));
         loop {
-            guard = self.condvar.wait(guard).unwrap();
-            if guard.0.is_none() {
-                // Re-acquire with original recursion count
-                guard.0 = Some(current);
-                guard.1 = saved_count;
+            if guard.notified.contains(&ticket) || is_interrupted() {
                 break;
             }
+            // Spurious wake-ups just go around the loop again
+            guard = match deadline {
+                None => self.wait_set.wait(guard).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    self.wait_set.wait_timeout(guard, deadline - now).unwrap().0
+                }
+            };
         }
-    }
 
-    /// Wait with timeout. Returns true if notified, false if timed out.
-    pub fn wait_timeout(&self, dur: Duration) -> bool {
-        let current = std::thread::current().id();
-        let mut guard = self.state.lock().unwrap();
+        // Leave the wait set, whether notified, timed out or interrupted
+        let notified = match guard.notified.iter().position(|&t| t == ticket) {
+            Some(i) => {
+                guard.notified.swap_remove(i);
+                true
+            }
+            None => {
+                guard.waiters.retain(|&t| t != ticket);
+                false
+            }
+        };
 
-        if guard.0 != Some(current) {
-            panic!("wait_timeout called by non-owner thread");
+        // Re-acquire with the original recursion count
+        while guard.owner.is_some() {
+            guard = self.entry.wait(guard).unwrap();
         }
+        guard.owner = Some(current);
+        guard.recursions = saved_recursions;
 
-        let saved_count = guard.1;
-        guard.0 = None;
-        guard.1 = 0;
-
-        let (new_guard, result) = self.condvar.wait_timeout(guard, dur).unwrap();
-        guard = new_guard;
+        notified
+    }
 
-        if !result.timed_out() || guard.0.is_none() {
-            guard.0 = Some(current);
-            guard.1 = saved_count;
-        } else {
-            // Timed out — re-acquire the lock before returning
-            drop(guard);
-            self.lock();
+    /// Inflation of a thin lock: `transfer` runs with the state locked and
+    /// returns the thin owner and count it took over, so no thread gets the
+    /// monitor before that owner is installed.
+    pub(crate) fn inflate(&self, transfer: impl FnOnce() -> Option<(ThreadId, usize)>) {
+        let mut guard = self.state.lock().unwrap();
+        if let Some((owner, recursions)) = transfer() {
+            guard.owner = Some(owner);
+            guard.recursions = recursions;
         }
+    }
 
-        !result.timed_out()
+    /// The thread owning this monitor, if any.
+    pub fn owner(&self) -> Option<ThreadId> {
+        self.state.lock().unwrap().owner
     }
 
-    /// Wake up all threads waiting on this monitor.
-    pub fn notify_all(&self) {
-        self.condvar.notify_all();
+    /// Whether the current thread owns this monitor.
+    pub fn is_owned_by_current(&self) -> bool {
+        self.state.lock().unwrap().owner == Some(std::thread::current().id())
     }
 
+    /// Wake every waiter without notifying it (used by interrupts), each one
+    /// re-checks its own wake-up condition.
+    pub fn wake_all(&self) {
+        let _guard = self.state.lock().unwrap();
+        self.wait_set.notify_all();
+    }
+
+    /// Wake up the longest waiting thread. Must be called by the owning thread.
     pub fn notify_one(&self) {
-        self.condvar.notify_one();
+        let mut guard = self.state.lock().unwrap();
+        if let Some(ticket) = guard.waiters.pop_front() {
+            guard.notified.push(ticket);
+            self.wait_set.notify_all();
+        }
+    }
+
+    /// Wake up all threads waiting on this monitor. Must be called by the
+    /// owning thread.
+    pub fn notify_all(&self) {
+        let mut guard = self.state.lock().unwrap();
+        if !guard.waiters.is_empty() {
+            let waiters: Vec<u64> = guard.waiters.drain(..).collect();
+            guard.notified.extend(waiters);
+            self.wait_set.notify_all();
+        }
     }
 }
 
 /// Describes a heap-allocated object.
 ///
-/// Contains the actual object data (`RefKind`) and monitor state for
-/// `synchronized` blocks. Each `RefKindDesc` lives in a `Heap` slot.
+/// Contains the actual object data (`RefKind`) and lock state for
+/// `synchronized` blocks: a thin lock word, inflated to a `Monitor` on
+/// contention or `wait`. Each `RefKindDesc` lives in a `Heap` slot.
 pub struct RefKindDesc {
     pub v: RefKind,
     pub hash_code: Option<i32>,
-    monitor: Monitor,
+    lock: LockWord,
+    monitor: OnceLock<Arc<Monitor>>,
 }
 
 impl RefKindDesc {
@@ -147,32 +239,70 @@ impl RefKindDesc {
         Self {
             v,
             hash_code: None,
-            monitor: Monitor::new(),
+            lock: LockWord::new(),
+            monitor: OnceLock::new(),
         }
     }
 
-    pub fn monitor_enter(&self) {
-        self.monitor.lock();
+    /// The object's monitor, inflating the lock. Blocking operations should
+    /// clone it and release the heap and object locks before waiting on it.
+    pub fn monitor(&self) -> Arc<Monitor> {
+        let monitor = self.monitor.get_or_init(|| Arc::new(Monitor::new()));
+        self.lock.inflate(monitor);
+        monitor.clone()
     }
 
-    pub fn monitor_exit(&self) {
-        self.monitor.unlock();
+    /// The monitor, if the lock is inflated.
+    pub fn inflated_monitor(&self) -> Option<Arc<Monitor>> {
+        if self.lock.is_inflated() {
+            self.monitor.get().cloned()
+        } else {
+            None
+        }
     }
 
-    pub fn wait(&self) {
-        self.monitor.wait();
+    /// Lock without blocking: thin-lock the object, or lock its monitor if
+    /// it's free. Otherwise returns the monitor to block on.
+    pub fn try_lock(&self) -> Result<(), Arc<Monitor>> {
+        if self.lock.try_lock() {
+            return Ok(());
+        }
+        let monitor = self.monitor();
+        if monitor.try_lock() {
+            Ok(())
+        } else {
+            Err(monitor)
+        }
     }
 
-    pub fn wait_timeout(&self, duration: Duration) {
-        self.monitor.wait_timeout(duration);
+    /// Release one lock level. False if the current thread doesn't own it.
+    pub fn unlock(&self) -> bool {
+        if self.lock.try_unlock() {
+            return true;
+        }
+        match self.inflated_monitor() {
+            Some(monitor) if monitor.is_owned_by_current() => {
+                monitor.unlock();
+                true
+            }
+            _ => false,
+        }
     }
 
-    pub fn notify_all(&self) {
-        self.monitor.notify_all();
+    /// Whether the current thread owns the lock.
+    pub fn is_locked_by_current(&self) -> bool {
+        self.lock.is_thin_locked_by_current()
+            || self
+                .inflated_monitor()
+                .is_some_and(|monitor| monitor.is_owned_by_current())
     }
 
-    pub fn notify_one(&self) {
-        self.monitor.notify_one();
+    /// The thread owning the lock, if any.
+    pub fn lock_owner(&self) -> Option<ThreadId> {
+        match self.inflated_monitor() {
+            Some(monitor) => monitor.owner(),
+            None => self.lock.thin_owner(),
+        }
     }
 }
 
@@ -253,3 +383,64 @@ impl RefKind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Monitor;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn t_reentrant_wait() {
+        let m = Arc::new(Monitor::new());
+        m.lock();
+        m.lock();
+        assert!(!m.wait_timeout(Duration::from_millis(10)));
+        assert!(m.is_owned_by_current());
+        m.unlock();
+        assert!(m.is_owned_by_current());
+        m.unlock();
+        assert!(!m.is_owned_by_current());
+    }
+
+    #[test]
+    fn t_notify_one() {
+        let m = Arc::new(Monitor::new());
+        let waiter = {
+            let m = m.clone();
+            std::thread::spawn(move || {
+                m.lock();
+                let notified = m.wait_timeout(Duration::from_secs(10));
+                m.unlock();
+                notified
+            })
+        };
+        // wait until the waiter is in the wait set
+        loop {
+            m.lock();
+            let waiting = !m.state.lock().unwrap().waiters.is_empty();
+            if waiting {
+                m.notify_one();
+                // nobody else is waiting, the notification isn't kept
+                m.notify_one();
+                m.unlock();
+                break;
+            }
+            m.unlock();
+            std::thread::yield_now();
+        }
+        assert!(waiter.join().unwrap());
+        assert!(m.state.lock().unwrap().notified.is_empty());
+    }
+
+    #[test]
+    fn t_interrupted_wait() {
+        let m = Monitor::new();
+        let interrupted = AtomicBool::new(true);
+        m.lock();
+        assert!(!m.wait_interruptibly(None, &interrupted));
+        assert!(m.is_owned_by_current());
+        m.unlock();
+    }
+}
@@ -80,7 +80,8 @@ impl ValueType {
             ValueType::LONG => b"long",
             ValueType::FLOAT => b"float",
             ValueType::DOUBLE => b"double",
-            ValueType::VOID | ValueType::OBJECT | ValueType::ARRAY => unreachable!(),
+            ValueType::VOID => b"void",
+            ValueType::OBJECT | ValueType::ARRAY => unreachable!(),
         }
     }
 }
@@ -0,0 +1,234 @@
+//! 引导类路径：定位 JDK，选择运行时镜像，处理 `--patch-module` 与
+//! `-Xbootclasspath/a:`。
+//!
+//! 搜索顺序与 HotSpot 一致：patch 的类优先于镜像中的同名类，追加的路径
+//! 排在镜像之后。引导类路径必须在应用类路径之前加入。镜像的 CDS 归档
+//! 也在这里按 `-Xshare` 打开，见 `cds`。
+
+use crate::runtime::cds::{self, Share};
+use crate::runtime::class_path_manager;
+use crate::util;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::{debug, info};
+
+static JAVA_HOME: OnceLock<String> = OnceLock::new();
+
+/// 常见的 JDK 安装位置，`JAVA_HOME` 未设置时依次尝试。
+const CANDIDATES: &[&str] = &[
+    "/usr/lib/jvm/default",
+    "/usr/lib/jvm/default-java",
+    "/usr/lib/jvm/java",
+    "/usr/lib64/jvm/default",
+];
+
+/// 启动器给出的引导类路径选项。
+#[derive(Debug, Default)]
+pub struct BootClassPath {
+    /// `--java-home`，优先于 `JAVA_HOME` 环境变量
+    pub java_home: Option<String>,
+    /// `--patch-module MODULE=PATH[:PATH...]`
+    pub patch_modules: Vec<String>,
+    /// `-Xbootclasspath/a:PATH[:PATH...]`
+    pub append: Vec<String>,
+    /// `-Xshare:off|auto|on|dump`
+    pub share: Share,
+    /// `-XX:SharedArchiveFile=FILE`，默认在缓存目录下按镜像校验和命名
+    pub shared_archive_file: Option<String>,
+}
+
+/// JDK 的运行时镜像。
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeImage {
+    /// JDK 9+ 的 `lib/modules`
+    JImage(PathBuf),
+    /// JDK 8 的 `jre/lib/rt.jar`
+    RtJar(PathBuf),
+}
+
+/// 在 `home` 下查找运行时镜像，`home` 可以是 JDK 或 JRE 目录。
+pub fn probe(home: &Path) -> Option<RuntimeImage> {
+    let modules = home.join("lib").join("modules");
+    if modules.is_file() {
+        return Some(RuntimeImage::JImage(modules));
+    }
+    [home.join("jre/lib/rt.jar"), home.join("lib/rt.jar")]
+        .into_iter()
+        .find(|p| p.is_file())
+        .map(RuntimeImage::RtJar)
+}
+
+/// 按 `--java-home`、`JAVA_HOME`、系统默认位置的顺序定位 JDK。
+/// 显式给出的目录不可用时直接报错，不再回退。
+pub fn discover(java_home: Option<&str>) -> Result<(PathBuf, RuntimeImage), String> {
+    if let Some(home) = java_home {
+        let home = PathBuf::from(home);
+        return match probe(&home) {
+            Some(image) => Ok((home, image)),
+            None => Err(format!(
+                "--java-home {}: no lib/modules or jre/lib/rt.jar found",
+                home.display()
+            )),
+        };
+    }
+
+    let mut tried = Vec::new();
+    if let Ok(home) = std::env::var("JAVA_HOME") {
+        let home = PathBuf::from(home);
+        match probe(&home) {
+            Some(image) => return Ok((home, image)),
+            None => tried.push(format!("JAVA_HOME={}", home.display())),
+        }
+    }
+
+    for home in system_homes() {
+        if let Some(image) = probe(&home) {
+            return Ok((home, image));
+        }
+        tried.push(home.display().to_string());
+    }
+
+    Err(format!(
+        "no Java runtime image found (tried {}); set JAVA_HOME or pass --java-home \
+         to a JDK with lib/modules (JDK 9+) or jre/lib/rt.jar (JDK 8)",
+        if tried.is_empty() {
+            "nothing".to_string()
+        } else {
+            tried.join(", ")
+        }
+    ))
+}
+
+fn system_homes() -> Vec<PathBuf> {
+    let mut homes = Vec::new();
+
+    // macOS 专用：/usr/libexec/java_home
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(output) = std::process::Command::new("/usr/libexec/java_home").output() {
+            if output.status.success() {
+                let home = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                homes.push(PathBuf::from(home));
+            }
+        }
+    }
+
+    homes.extend(CANDIDATES.iter().map(PathBuf::from));
+
+    // 最后尝试 /usr/lib/jvm 下的各个 JDK，版本高的优先
+    if let Ok(dir) = std::fs::read_dir("/usr/lib/jvm") {
+        let mut installed: Vec<PathBuf> = dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect();
+        installed.sort();
+        installed.reverse();
+        homes.extend(installed);
+    }
+    homes
+}
+
+/// 定位 JDK 并按 patch、镜像、追加路径的顺序建立引导类路径。
+pub fn init(opts: &BootClassPath) -> Result<(), String> {
+    let (home, image) = discover(opts.java_home.as_deref())?;
+    debug!("java home: {}, image: {:?}", home.display(), image);
+
+    for spec in &opts.patch_modules {
+        let (module, paths) = parse_patch_module(spec)?;
+        for path in paths.split(util::PATH_SEP).filter(|p| !p.is_empty()) {
+            class_path_manager::add_boot_path(path)
+                .map_err(|e| format!("--patch-module {}: {}: {}", module, path, e))?;
+            info!("Module {} patched from: {}", module, path);
+        }
+    }
+
+    // JDK 8 的 java.home 是 rt.jar 所在的 JRE 目录
+    let java_home = match &image {
+        RuntimeImage::JImage(path) => {
+            let archive = cds::open_for_image(
+                path,
+                opts.share,
+                opts.shared_archive_file.as_deref(),
+                !opts.patch_modules.is_empty(),
+            )?;
+            class_path_manager::add_boot_jimage(&path.to_string_lossy(), archive)?;
+            home
+        }
+        RuntimeImage::RtJar(path) => {
+            cds::check_unsupported(opts.share)?;
+            let jar = path.to_string_lossy();
+            class_path_manager::add_boot_path(&jar).map_err(|e| format!("{}: {}", jar, e))?;
+            path.parent().and_then(Path::parent).unwrap().to_path_buf()
+        }
+    };
+
+    for paths in &opts.append {
+        for path in paths.split(util::PATH_SEP).filter(|p| !p.is_empty()) {
+            class_path_manager::add_boot_path(path)
+                .map_err(|e| format!("-Xbootclasspath/a: {}: {}", path, e))?;
+        }
+    }
+
+    let _ = JAVA_HOME.set(java_home.to_string_lossy().to_string());
+    Ok(())
+}
+
+/// `MODULE=PATH[:PATH...]`
+fn parse_patch_module(spec: &str) -> Result<(&str, &str), String> {
+    match spec.split_once('=') {
+        Some((module, paths)) if !module.is_empty() && !paths.is_empty() => Ok((module, paths)),
+        _ => Err(format!(
+            "--patch-module {}: expected MODULE=PATH[{}PATH...]",
+            spec,
+            util::PATH_SEP
+        )),
+    }
+}
+
+/// 已定位的 JDK 目录，作为 `java.home`。
+pub fn java_home() -> Option<&'static str> {
+    JAVA_HOME.get().map(|s| s.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_probe_image() {
+        let home = std::env::temp_dir().join(format!("jvm-java-home-{}", std::process::id()));
+        let jdk8 = home.join("jdk8");
+        let jdk17 = home.join("jdk17");
+        std::fs::create_dir_all(jdk8.join("jre/lib")).unwrap();
+        std::fs::create_dir_all(jdk17.join("lib")).unwrap();
+        std::fs::write(jdk8.join("jre/lib/rt.jar"), b"").unwrap();
+        std::fs::write(jdk17.join("lib/modules"), b"").unwrap();
+
+        assert_eq!(
+            probe(&jdk8),
+            Some(RuntimeImage::RtJar(jdk8.join("jre/lib/rt.jar")))
+        );
+        assert_eq!(
+            probe(&jdk17),
+            Some(RuntimeImage::JImage(jdk17.join("lib/modules")))
+        );
+        assert_eq!(probe(&home), None);
+
+        let home_str = home.to_string_lossy().to_string();
+        let e = discover(Some(&home_str)).unwrap_err();
+        assert!(e.contains("--java-home"), "{}", e);
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn t_parse_patch_module() {
+        assert_eq!(
+            parse_patch_module("java.base=a:b"),
+            Ok(("java.base", "a:b"))
+        );
+        assert!(parse_patch_module("java.base").is_err());
+        assert!(parse_patch_module("=a").is_err());
+        assert!(parse_patch_module("java.base=").is_err());
+    }
+}
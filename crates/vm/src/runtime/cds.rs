@@ -0,0 +1,838 @@
+//! 类数据共享（CDS）归档：`-Xshare:dump` 把引导类的类文件、解析与链接的
+//! 结果，以及运行时镜像的类索引写入归档，之后的启动以 `-Xshare:auto`
+//! （默认）映射归档，不再遍历 `lib/modules` 的全部资源来建立索引。
+//!
+//! 每个归档的类除类文件外还保存：
+//!
+//! - 解析后的常量池，加载时直接还原，只解析常量池之后的部分；
+//! - 链接算出的字段偏移（实例字段含父类字段在内的偏移、静态字段的槽位）
+//!   与虚方法表，链接时父类的实例字段数与归档一致就直接采用。
+//!
+//! 链接结果中指向堆对象（镜像类、静态字段值）与其他 `Class` 的引用跨进程
+//! 没有意义，不归档，仍在加载时建立。
+//!
+//! ## 格式
+//!
+//! 小端序，偏移都相对文件开头：
+//!
+//! ```text
+//! header   magic(8) version(4) modules(4) classes(4)
+//!          table_off(4) strings_off(4) data_off(4)
+//!          jimage checksum(16) vm version(16)
+//! module   name_off(4) name_len(2) flags(2)            * modules
+//! class    name_off(4) name_len(2) module(2)
+//!          data_off(4) data_len(4)
+//!          meta_off(4) meta_len(4)                     * classes，按名字排序
+//! strings  模块名与类名（`java/lang/String`）
+//! data     类文件字节与链接元数据
+//! ```
+//!
+//! `data_len` 为 0 表示类在镜像中但没有归档，从镜像读取；`meta_len` 为 0
+//! 表示只归档了类文件。链接元数据：
+//!
+//! ```text
+//! meta     cp_end(4) super_fields(2) n_inst_fields(2)
+//!          fields(2) v_table(2) cp_len(2)
+//!          field offset(2)                             * fields，类文件中的顺序
+//!          method index(2)                             * v_table
+//!          tag(1) 常量                                  * cp_len
+//! ```
+//!
+//! 常量池项的 tag 与类文件相同，下标 0 与 long、double 之后的空位记作 0；
+//! 常量按类文件的写法，只是改为小端序、utf8 的长度为 2 字节前缀。
+//!
+//! 镜像校验和是 `lib/modules` 头部与索引区（资源名、偏移、大小）的 md5，
+//! JDK 更新后归档自动失效。
+
+use crate::oop::class::ClassKindType;
+use crate::runtime::{class_path_manager, init_vm, sys_dic, vm};
+use crate::types::ClassRef;
+use classfile::{ConstantPool, ConstantPoolType};
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tracing::{debug, info};
+
+const MAGIC: &[u8; 8] = b"JVMCDS\0\0";
+// 格式变化时递增，旧归档直接拒绝
+const VERSION: u32 = 2;
+const HEADER_SIZE: usize = 64;
+const MODULE_ENTRY_SIZE: usize = 8;
+const CLASS_ENTRY_SIZE: usize = 24;
+// 模块含 `module-info.class`
+const MODULE_HAS_INFO: u16 = 1;
+
+const JIMAGE_MAGIC: u32 = 0xCAFE_DADA;
+const JIMAGE_HEADER_SIZE: usize = 28;
+
+/// `-Xshare` 模式。
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Share {
+    /// 不使用归档
+    Off,
+    /// 归档可用时使用，否则静默忽略
+    #[default]
+    Auto,
+    /// 必须使用归档，不可用时报错
+    On,
+    /// 运行结束时写出归档
+    Dump,
+}
+
+impl FromStr for Share {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Share::Off),
+            "auto" => Ok(Share::Auto),
+            "on" => Ok(Share::On),
+            "dump" => Ok(Share::Dump),
+            _ => Err(format!(
+                "invalid -Xshare mode `{}`, expected off, auto, on or dump",
+                s
+            )),
+        }
+    }
+}
+
+// `-Xshare:dump` 时的归档路径，由 `dump` 写出
+static DUMP_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// 映射到内存的归档。
+pub(crate) struct Archive {
+    map: Mmap,
+    modules: usize,
+    classes: usize,
+    table_off: usize,
+}
+
+/// 归档中的一个类。
+pub(crate) struct ArchivedClass<'a> {
+    pub module: &'a str,
+    /// 归档的类文件，`None` 时从镜像读取
+    pub bytes: Option<&'a [u8]>,
+    /// 编码后的链接元数据
+    meta: Option<&'a [u8]>,
+}
+
+impl ArchivedClass<'_> {
+    /// 还原归档的常量池与链接结果，没有或已损坏时返回 `None`。
+    pub(crate) fn shared(&self) -> Option<SharedClass> {
+        decode_meta(self.meta?)
+    }
+}
+
+/// 链接时算出的类布局。
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedLayout {
+    /// 父类的实例字段数，与链接时实际的父类不一致则不能采用
+    pub super_fields: u16,
+    /// 含父类字段在内的实例字段数
+    pub n_inst_fields: u16,
+    /// 按类文件 `fields` 的顺序：实例字段的偏移或静态字段的槽位
+    pub field_offsets: Vec<u16>,
+    /// 进入虚方法表的方法在类文件 `methods` 中的下标
+    pub v_table: Vec<u16>,
+}
+
+/// 归档的解析与链接结果。
+pub struct SharedClass {
+    /// 常量池之后第一个字节（`access_flags`）在类文件中的偏移
+    pub cp_end: usize,
+    pub cp: ConstantPool,
+    pub layout: SharedLayout,
+}
+
+fn u16_at(buf: &[u8], off: usize) -> Option<u16> {
+    buf.get(off..off + 2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+}
+
+fn u32_at(buf: &[u8], off: usize) -> Option<usize> {
+    buf.get(off..off + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize)
+}
+
+/// 版本号补零到 16 字节。
+fn vm_version() -> [u8; 16] {
+    let mut v = [0u8; 16];
+    let s = env!("CARGO_PKG_VERSION").as_bytes();
+    let n = s.len().min(v.len());
+    v[..n].copy_from_slice(&s[..n]);
+    v
+}
+
+impl Archive {
+    /// 映射归档并校验格式、VM 版本与镜像校验和。
+    pub(crate) fn open(path: &Path, checksum: &[u8; 16]) -> Result<Self, String> {
+        let f = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        // 归档由 `dump` 先写临时文件再改名，映射期间不会被原地改写
+        let map = unsafe { Mmap::map(&f) }.map_err(|e| format!("{}: {}", path.display(), e))?;
+        let bad = |what: &str| format!("{}: {}", path.display(), what);
+
+        if map.len() < HEADER_SIZE || &map[..8] != MAGIC {
+            return Err(bad("not a shared archive"));
+        }
+        if u32_at(&map, 8) != Some(VERSION as usize) {
+            return Err(bad("archive format version mismatch"));
+        }
+        if map[48..64] != vm_version() {
+            return Err(bad("archive was created by a different VM version"));
+        }
+        if &map[32..48] != checksum {
+            return Err(bad("archive does not match the runtime image"));
+        }
+
+        let modules = u32_at(&map, 12).unwrap();
+        let classes = u32_at(&map, 16).unwrap();
+        let table_off = u32_at(&map, 20).unwrap();
+        let strings_off = u32_at(&map, 24).unwrap();
+        let data_off = u32_at(&map, 28).unwrap();
+        if HEADER_SIZE + modules * MODULE_ENTRY_SIZE != table_off
+            || table_off + classes * CLASS_ENTRY_SIZE != strings_off
+            || strings_off > data_off
+            || data_off > map.len()
+        {
+            return Err(bad("corrupted archive"));
+        }
+
+        Ok(Self {
+            map,
+            modules,
+            classes,
+            table_off,
+        })
+    }
+
+    fn str_at(&self, off: usize, len: usize) -> Option<&str> {
+        self.map
+            .get(off..off + len)
+            .and_then(|b| std::str::from_utf8(b).ok())
+    }
+
+    fn module(&self, i: usize) -> Option<(&str, u16)> {
+        if i >= self.modules {
+            return None;
+        }
+        let e = HEADER_SIZE + i * MODULE_ENTRY_SIZE;
+        let name = self.str_at(u32_at(&self.map, e)?, u16_at(&self.map, e + 4)? as usize)?;
+        Some((name, u16_at(&self.map, e + 6)?))
+    }
+
+    fn class_name(&self, i: usize) -> &[u8] {
+        let e = self.table_off + i * CLASS_ENTRY_SIZE;
+        let off = u32_at(&self.map, e).unwrap();
+        let len = u16_at(&self.map, e + 4).unwrap() as usize;
+        self.map.get(off..off + len).unwrap_or_default()
+    }
+
+    /// 在按名字排序的类表中二分查找，`name` 形如 `java/lang/String`。
+    pub(crate) fn find(&self, name: &str) -> Option<ArchivedClass<'_>> {
+        let (mut lo, mut hi) = (0, self.classes);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.class_name(mid).cmp(name.as_bytes()) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => {
+                    let e = self.table_off + mid * CLASS_ENTRY_SIZE;
+                    let (module, _) = self.module(u16_at(&self.map, e + 6)? as usize)?;
+                    let off = u32_at(&self.map, e + 8)?;
+                    let len = u32_at(&self.map, e + 12)?;
+                    let bytes = match len {
+                        0 => None,
+                        _ => Some(self.map.get(off..off + len)?),
+                    };
+                    let off = u32_at(&self.map, e + 16)?;
+                    let len = u32_at(&self.map, e + 20)?;
+                    let meta = match len {
+                        0 => None,
+                        _ => Some(self.map.get(off..off + len)?),
+                    };
+                    return Some(ArchivedClass {
+                        module,
+                        bytes,
+                        meta,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// 含 `module-info.class` 的模块。
+    pub(crate) fn module_names(&self) -> Vec<String> {
+        (0..self.modules)
+            .filter_map(|i| self.module(i))
+            .filter(|(_, flags)| flags & MODULE_HAS_INFO != 0)
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+}
+
+/// `lib/modules` 头部与索引区的 md5。索引区记录了每个资源的名字、偏移
+/// 与大小，只读这一部分（约 1.5MB）即可识别镜像，不必读整个文件。
+pub fn jimage_checksum(path: &Path) -> io::Result<[u8; 16]> {
+    let mut f = File::open(path)?;
+    let mut header = [0u8; JIMAGE_HEADER_SIZE];
+    f.read_exact(&mut header)?;
+    let field = |i: usize| u32_at(&header, i * 4).unwrap();
+    if field(0) as u32 != JIMAGE_MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: not a jimage", path.display()),
+        ));
+    }
+    // table_length、locations_size、strings_size
+    let (table_length, locations_size, strings_size) = (field(4), field(5), field(6));
+    let index_size = table_length * 8 + locations_size + strings_size;
+    let mut index = Vec::with_capacity(JIMAGE_HEADER_SIZE + index_size);
+    index.extend_from_slice(&header);
+    f.take(index_size as u64).read_to_end(&mut index)?;
+    Ok(md5::compute(&index).0)
+}
+
+/// 未指定 `-XX:SharedArchiveFile` 时的归档路径，以镜像校验和命名，
+/// 多个 JDK 的归档可以共存。
+fn default_archive_path(checksum: &[u8; 16]) -> Option<PathBuf> {
+    let name: String = checksum.iter().map(|b| format!("{:02x}", b)).collect();
+    dirs::cache_dir().map(|dir| dir.join("jvm").join(format!("classes-{}.jsa", name)))
+}
+
+/// 按 `-Xshare` 模式为镜像 `image` 打开归档。`auto` 时归档不可用返回
+/// `Ok(None)`，`on` 时返回错误；`dump` 记下归档路径，留给 `dump` 写出。
+pub(crate) fn open_for_image(
+    image: &Path,
+    share: Share,
+    archive: Option<&str>,
+    patched: bool,
+) -> Result<Option<Archive>, String> {
+    if share == Share::Off {
+        return Ok(None);
+    }
+    // 与 HotSpot 一致：patch 的类可能替换归档中的类
+    if patched {
+        return match share {
+            Share::Auto => Ok(None),
+            _ => Err("CDS is disabled when java.base module is patched.".to_string()),
+        };
+    }
+
+    let checksum = jimage_checksum(image).map_err(|e| e.to_string())?;
+    let path = match archive {
+        Some(path) => PathBuf::from(path),
+        None => default_archive_path(&checksum)
+            .ok_or_else(|| "no cache directory for the shared archive".to_string())?,
+    };
+
+    if share == Share::Dump {
+        let _ = DUMP_PATH.set(path);
+        return Ok(None);
+    }
+
+    match Archive::open(&path, &checksum) {
+        Ok(archive) => {
+            info!(
+                "Shared archive mapped from: {}, {} classes",
+                path.display(),
+                archive.classes
+            );
+            Ok(Some(archive))
+        }
+        Err(e) if share == Share::Auto => {
+            debug!("shared archive not used: {}", e);
+            Ok(None)
+        }
+        Err(e) => Err(format!("Unable to use shared archive: {}", e)),
+    }
+}
+
+/// 运行时镜像不是 jimage（JDK 8）时检查 `-Xshare` 模式。
+pub(crate) fn check_unsupported(share: Share) -> Result<(), String> {
+    match share {
+        Share::Off | Share::Auto => Ok(()),
+        _ => Err("class data sharing needs a JDK 9+ runtime image (lib/modules)".to_string()),
+    }
+}
+
+/// 是否处于 `-Xshare:dump`。
+pub fn dumping() -> bool {
+    DUMP_PATH.get().is_some()
+}
+
+/// 写出归档：镜像的全部类名及其模块，加上当前已加载、来自镜像的类的
+/// 类文件与链接元数据。VM 必须已经初始化。返回归档路径与归档的类数。
+pub fn dump() -> Result<(PathBuf, usize), String> {
+    let path = DUMP_PATH
+        .get()
+        .ok_or_else(|| "not started with -Xshare:dump".to_string())?;
+    let (image, index) =
+        class_path_manager::jimage_index().ok_or_else(|| "no runtime image".to_string())?;
+    let checksum = jimage_checksum(Path::new(&image)).map_err(|e| e.to_string())?;
+
+    let mut loaded: Vec<(String, ClassRef)> = sys_dic::classes()
+        .into_iter()
+        .filter(|cls| cls.get_class_kind_type() == ClassKindType::Instance)
+        .map(|cls| {
+            (
+                String::from_utf8_lossy(cls.name.as_slice()).to_string(),
+                cls,
+            )
+        })
+        .collect();
+    loaded.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut classes = Vec::with_capacity(index.classes.len());
+    let mut archived = 0;
+    for (name, module) in index.classes {
+        let (bytes, meta) = match loaded.binary_search_by(|(it, _)| it.cmp(&name)) {
+            Ok(i) => match class_path_manager::find_jimage_class(&name) {
+                Some(bytes) => {
+                    let meta = encode_class(&loaded[i].1, &bytes);
+                    (Some(bytes), meta)
+                }
+                None => (None, None),
+            },
+            Err(_) => (None, None),
+        };
+        archived += bytes.is_some() as usize;
+        classes.push(DumpedClass {
+            name,
+            module,
+            bytes,
+            meta,
+        });
+    }
+
+    write(path, &checksum, &index.modules, classes)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok((path.clone(), archived))
+}
+
+/// 只启动 VM（与 MainThread 相同的初始化）并写出归档，不运行主类。
+pub fn dump_boot() -> Result<(PathBuf, usize), String> {
+    let vm = vm::VM::new(1);
+    vm.threads.attach_current_thread();
+    init_vm::initialize_jvm();
+    dump()
+}
+
+/// 镜像的类索引，供 `dump` 写入归档。
+pub(crate) struct ImageIndex {
+    /// 类名与所在模块
+    pub classes: Vec<(String, String)>,
+    /// 模块名，以及是否含 `module-info.class`
+    pub modules: Vec<(String, bool)>,
+}
+
+/// 写入归档的一个类。
+struct DumpedClass {
+    name: String,
+    module: String,
+    /// 类文件，`None` 时从镜像读取
+    bytes: Option<Vec<u8>>,
+    /// 编码后的链接元数据，只在有 `bytes` 时写入
+    meta: Option<Vec<u8>>,
+}
+
+fn write(
+    path: &Path,
+    checksum: &[u8; 16],
+    modules: &[(String, bool)],
+    mut classes: Vec<DumpedClass>,
+) -> io::Result<()> {
+    classes.sort_by(|a, b| a.name.cmp(&b.name));
+    classes.dedup_by(|a, b| a.name == b.name);
+
+    let table_off = HEADER_SIZE + modules.len() * MODULE_ENTRY_SIZE;
+    let strings_off = table_off + classes.len() * CLASS_ENTRY_SIZE;
+    let mut strings = Vec::new();
+    let mut string = |s: &str| {
+        let off = strings_off + strings.len();
+        strings.extend_from_slice(s.as_bytes());
+        (off as u32, s.len() as u16)
+    };
+
+    let mut module_table = Vec::with_capacity(modules.len() * MODULE_ENTRY_SIZE);
+    for (name, has_info) in modules {
+        let (off, len) = string(name);
+        let flags = if *has_info { MODULE_HAS_INFO } else { 0 };
+        module_table.extend_from_slice(&off.to_le_bytes());
+        module_table.extend_from_slice(&len.to_le_bytes());
+        module_table.extend_from_slice(&flags.to_le_bytes());
+    }
+
+    let mut class_table = Vec::with_capacity(classes.len() * CLASS_ENTRY_SIZE);
+    let mut class_names = Vec::with_capacity(classes.len());
+    for DumpedClass { name, module, .. } in &classes {
+        let module = modules
+            .iter()
+            .position(|(m, _)| m == module)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown module {}", module),
+                )
+            })?;
+        class_names.push((string(name), module as u16));
+    }
+    let data_off = strings_off + strings.len();
+    let mut data = Vec::new();
+    let mut append = |part: &Option<Vec<u8>>| match part {
+        Some(part) => {
+            let at = data_off + data.len();
+            data.extend_from_slice(part);
+            (at as u32, part.len() as u32)
+        }
+        None => (0, 0),
+    };
+    for (((off, len), module), DumpedClass { bytes, meta, .. }) in
+        class_names.into_iter().zip(&classes)
+    {
+        let (bytes_off, bytes_len) = append(bytes);
+        let (meta_off, meta_len) = match bytes {
+            Some(_) => append(meta),
+            None => (0, 0),
+        };
+        class_table.extend_from_slice(&off.to_le_bytes());
+        class_table.extend_from_slice(&len.to_le_bytes());
+        class_table.extend_from_slice(&module.to_le_bytes());
+        for v in [bytes_off, bytes_len, meta_off, meta_len] {
+            class_table.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(MAGIC);
+    for v in [
+        VERSION,
+        modules.len() as u32,
+        classes.len() as u32,
+        table_off as u32,
+        strings_off as u32,
+        data_off as u32,
+    ] {
+        header.extend_from_slice(&v.to_le_bytes());
+    }
+    header.extend_from_slice(checksum);
+    header.extend_from_slice(&vm_version());
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // 先写临时文件再改名，正在映射旧归档的进程不受影响
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut f = io::BufWriter::new(File::create(&tmp)?);
+        for part in [&header, &module_table, &class_table, &strings, &data] {
+            f.write_all(part)?;
+        }
+        f.flush()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+/// 编码已链接类的常量池与布局，`bytes` 是类的类文件。
+fn encode_class(class: &ClassRef, bytes: &[u8]) -> Option<Vec<u8>> {
+    let (cp, layout) = class.shared_layout()?;
+    // constant_pool_count 与已加载的常量池不符，说明类不是从这份类文件加载的
+    if bytes
+        .get(8..10)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+        != Some(cp.len())
+    {
+        return None;
+    }
+    let cp_end = class_parser::constant_pool_end(bytes).ok()?;
+    encode_meta(cp_end, &cp, &layout)
+}
+
+fn encode_meta(cp_end: usize, cp: &ConstantPool, layout: &SharedLayout) -> Option<Vec<u8>> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(cp_end as u32).to_le_bytes());
+    for v in [
+        layout.super_fields,
+        layout.n_inst_fields,
+        layout.field_offsets.len() as u16,
+        layout.v_table.len() as u16,
+        cp.len() as u16,
+    ] {
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+    for v in layout.field_offsets.iter().chain(&layout.v_table) {
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    let put = |buf: &mut Vec<u8>, tag: u8, vs: &[u16]| {
+        buf.push(tag);
+        for v in vs {
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+    };
+    for it in cp.iter() {
+        match it {
+            ConstantPoolType::Nop => buf.push(0),
+            ConstantPoolType::Utf8 { bytes } => {
+                put(&mut buf, 1, &[bytes.len() as u16]);
+                buf.extend_from_slice(bytes);
+            }
+            ConstantPoolType::Integer { v } => {
+                buf.push(3);
+                buf.extend_from_slice(v);
+            }
+            ConstantPoolType::Float { v } => {
+                buf.push(4);
+                buf.extend_from_slice(v);
+            }
+            ConstantPoolType::Long { v } => {
+                buf.push(5);
+                buf.extend_from_slice(v);
+            }
+            ConstantPoolType::Double { v } => {
+                buf.push(6);
+                buf.extend_from_slice(v);
+            }
+            ConstantPoolType::Class { name_index } => put(&mut buf, 7, &[*name_index]),
+            ConstantPoolType::String { string_index } => put(&mut buf, 8, &[*string_index]),
+            ConstantPoolType::FieldRef {
+                class_index,
+                name_and_type_index,
+            } => put(&mut buf, 9, &[*class_index, *name_and_type_index]),
+            ConstantPoolType::MethodRef {
+                class_index,
+                name_and_type_index,
+            } => put(&mut buf, 10, &[*class_index, *name_and_type_index]),
+            ConstantPoolType::InterfaceMethodRef {
+                class_index,
+                name_and_type_index,
+            } => put(&mut buf, 11, &[*class_index, *name_and_type_index]),
+            ConstantPoolType::NameAndType {
+                name_index,
+                desc_index,
+            } => put(&mut buf, 12, &[*name_index, *desc_index]),
+            ConstantPoolType::MethodHandle {
+                ref_kind,
+                ref_index,
+            } => {
+                buf.extend_from_slice(&[15, *ref_kind]);
+                buf.extend_from_slice(&ref_index.to_le_bytes());
+            }
+            ConstantPoolType::MethodType { desc_index } => put(&mut buf, 16, &[*desc_index]),
+            ConstantPoolType::InvokeDynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => put(
+                &mut buf,
+                18,
+                &[*bootstrap_method_attr_index, *name_and_type_index],
+            ),
+            ConstantPoolType::Module { name_index } => put(&mut buf, 19, &[*name_index]),
+            ConstantPoolType::Package { name_index } => put(&mut buf, 20, &[*name_index]),
+            ConstantPoolType::Unknown => return None,
+        }
+    }
+    Some(buf)
+}
+
+/// 依次读取链接元数据的游标，越界时返回 `None`。
+struct MetaReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> MetaReader<'a> {
+    fn bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let b = self.buf.get(self.pos..self.pos + n)?;
+        self.pos += n;
+        Some(b)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u16s(&mut self, n: usize) -> Option<Vec<u16>> {
+        (0..n).map(|_| self.u16()).collect()
+    }
+}
+
+fn decode_meta(buf: &[u8]) -> Option<SharedClass> {
+    let cp_end = u32_at(buf, 0)?;
+    let mut r = MetaReader { buf, pos: 4 };
+    let super_fields = r.u16()?;
+    let n_inst_fields = r.u16()?;
+    let (fields, v_table, cp_len) = (r.u16()?, r.u16()?, r.u16()?);
+    let field_offsets = r.u16s(fields as usize)?;
+    let v_table = r.u16s(v_table as usize)?;
+
+    let mut cp = Vec::with_capacity(cp_len as usize);
+    for _ in 0..cp_len {
+        let it = match r.u8()? {
+            0 => ConstantPoolType::Nop,
+            1 => {
+                let len = r.u16()? as usize;
+                ConstantPoolType::Utf8 {
+                    bytes: std::sync::Arc::new(r.bytes(len)?.to_vec()),
+                }
+            }
+            3 => ConstantPoolType::Integer {
+                v: r.bytes(4)?.try_into().ok()?,
+            },
+            4 => ConstantPoolType::Float {
+                v: r.bytes(4)?.try_into().ok()?,
+            },
+            5 => ConstantPoolType::Long {
+                v: r.bytes(8)?.try_into().ok()?,
+            },
+            6 => ConstantPoolType::Double {
+                v: r.bytes(8)?.try_into().ok()?,
+            },
+            7 => ConstantPoolType::Class {
+                name_index: r.u16()?,
+            },
+            8 => ConstantPoolType::String {
+                string_index: r.u16()?,
+            },
+            9 => ConstantPoolType::FieldRef {
+                class_index: r.u16()?,
+                name_and_type_index: r.u16()?,
+            },
+            10 => ConstantPoolType::MethodRef {
+                class_index: r.u16()?,
+                name_and_type_index: r.u16()?,
+            },
+            11 => ConstantPoolType::InterfaceMethodRef {
+                class_index: r.u16()?,
+                name_and_type_index: r.u16()?,
+            },
+            12 => ConstantPoolType::NameAndType {
+                name_index: r.u16()?,
+                desc_index: r.u16()?,
+            },
+            15 => ConstantPoolType::MethodHandle {
+                ref_kind: r.u8()?,
+                ref_index: r.u16()?,
+            },
+            16 => ConstantPoolType::MethodType {
+                desc_index: r.u16()?,
+            },
+            18 => ConstantPoolType::InvokeDynamic {
+                bootstrap_method_attr_index: r.u16()?,
+                name_and_type_index: r.u16()?,
+            },
+            19 => ConstantPoolType::Module {
+                name_index: r.u16()?,
+            },
+            20 => ConstantPoolType::Package {
+                name_index: r.u16()?,
+            },
+            _ => return None,
+        };
+        cp.push(it);
+    }
+
+    Some(SharedClass {
+        cp_end,
+        cp: std::sync::Arc::new(cp),
+        layout: SharedLayout {
+            super_fields,
+            n_inst_fields,
+            field_offsets,
+            v_table,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_archive_round_trip() {
+        let path = std::env::temp_dir().join(format!("jvm-cds-{}.jsa", std::process::id()));
+        let checksum = [7u8; 16];
+        let modules = vec![
+            ("java.base".to_string(), true),
+            ("jdk.internal".to_string(), false),
+        ];
+        let cp: ConstantPool = std::sync::Arc::new(vec![
+            ConstantPoolType::Nop,
+            ConstantPoolType::Utf8 {
+                bytes: std::sync::Arc::new(b"value".to_vec()),
+            },
+            ConstantPoolType::Long { v: [9; 8] },
+            ConstantPoolType::Nop,
+            ConstantPoolType::FieldRef {
+                class_index: 6,
+                name_and_type_index: 7,
+            },
+            ConstantPoolType::MethodHandle {
+                ref_kind: 5,
+                ref_index: 4,
+            },
+        ]);
+        let layout = SharedLayout {
+            super_fields: 2,
+            n_inst_fields: 4,
+            field_offsets: vec![2, 0, 3],
+            v_table: vec![0, 3],
+        };
+        let classes = vec![
+            DumpedClass {
+                name: "java/lang/String".to_string(),
+                module: "java.base".to_string(),
+                bytes: Some(vec![1, 2, 3]),
+                meta: encode_meta(42, &cp, &layout),
+            },
+            DumpedClass {
+                name: "java/lang/Object".to_string(),
+                module: "java.base".to_string(),
+                bytes: Some(vec![4]),
+                meta: None,
+            },
+            DumpedClass {
+                name: "jdk/internal/Foo".to_string(),
+                module: "jdk.internal".to_string(),
+                bytes: None,
+                meta: None,
+            },
+        ];
+        write(&path, &checksum, &modules, classes).unwrap();
+
+        assert!(Archive::open(&path, &[0u8; 16]).is_err());
+        let archive = Archive::open(&path, &checksum).unwrap();
+        assert_eq!(archive.module_names(), vec!["java.base"]);
+
+        let c = archive.find("java/lang/Object").unwrap();
+        assert_eq!((c.module, c.bytes), ("java.base", Some(&[4u8][..])));
+        assert!(c.shared().is_none());
+        let c = archive.find("java/lang/String").unwrap();
+        assert_eq!(c.bytes, Some(&[1u8, 2, 3][..]));
+        let shared = c.shared().unwrap();
+        assert_eq!((shared.cp_end, &shared.layout), (42, &layout));
+        assert_eq!(format!("{:?}", shared.cp), format!("{:?}", cp));
+        let c = archive.find("jdk/internal/Foo").unwrap();
+        assert_eq!((c.module, c.bytes), ("jdk.internal", None));
+        assert!(archive.find("java/lang/Integer").is_none());
+        assert!(archive.find("").is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn t_share_mode() {
+        assert_eq!("dump".parse(), Ok(Share::Dump));
+        assert_eq!("auto".parse(), Ok(Share::Auto));
+        assert!("yes".parse::<Share>().is_err());
+    }
+}
@@ -1,11 +1,13 @@
 use crate::native;
 use crate::oop::{self, Class, ValueType};
-use crate::runtime::{self, ClassPathResult};
+use crate::runtime::thread::safepoint;
+use crate::runtime::{self, class_path_manager, ClassPathResult};
 use crate::types::*;
 use crate::util;
 use class_parser::parse_class;
 use classfile::{constant_pool, BytesRef, ConstantPool, U2};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, ThreadId};
 use tracing::{debug, error, info, trace, warn};
 
 #[derive(Debug, Copy, Clone)]
@@ -14,6 +16,52 @@ pub enum ClassLoader {
     Bootstrap,
 }
 
+/// Held while the bootstrap loader defines a class, so that two threads
+/// don't both define it and no thread sees it before it is linked and has
+/// a mirror. Reentrant: loading a class loads its super types on the same
+/// thread.
+static LOADING: LoadingLock = LoadingLock {
+    owner: Mutex::new((None, 0)),
+    released: Condvar::new(),
+};
+
+struct LoadingLock {
+    /// The loading thread and how many loads it has nested
+    owner: Mutex<(Option<ThreadId>, usize)>,
+    released: Condvar,
+}
+
+struct LoadingGuard;
+
+impl LoadingLock {
+    fn lock(&'static self) -> LoadingGuard {
+        let me = thread::current().id();
+        let mut owner = self.owner.lock().unwrap();
+        if owner.0 != Some(me) {
+            //the owner may be stopped at a safepoint
+            owner = safepoint::blocked(|| {
+                self.released
+                    .wait_while(owner, |(thread, _)| thread.is_some())
+                    .unwrap()
+            });
+            owner.0 = Some(me);
+        }
+        owner.1 += 1;
+        LoadingGuard
+    }
+}
+
+impl Drop for LoadingGuard {
+    fn drop(&mut self) {
+        let mut owner = LOADING.owner.lock().unwrap();
+        owner.1 -= 1;
+        if owner.1 == 0 {
+            owner.0 = None;
+            LOADING.released.notify_one();
+        }
+    }
+}
+
 pub fn require_class(class_loader: Option<ClassLoader>, name: &BytesRef) -> Option<ClassRef> {
     require_class3(class_loader, name.as_slice())
 }
@@ -33,16 +81,24 @@ impl ClassLoader {
     fn load_class(&self, name: &[u8]) -> Option<ClassRef> {
         debug_assert!(!name.contains(&b'.'));
         //        error!("load_class name = {}", String::from_utf8_lossy(name));
-        match self {
-            ClassLoader::Base => (),
+        let _loading = match self {
+            ClassLoader::Base => None,
             ClassLoader::Bootstrap => {
                 let it = runtime::sys_dic_find(name);
                 if it.is_some() {
                     //                    info!("load_class in dic: {}", String::from_utf8_lossy(name));
                     return it;
                 }
+
+                //another thread may have loaded it while this one waited
+                let loading = LOADING.lock();
+                let it = runtime::sys_dic_find(name);
+                if it.is_some() {
+                    return it;
+                }
+                Some(loading)
             }
-        }
+        };
 
         if is_array(name) {
             self.load_array_class(name)
@@ -52,7 +108,6 @@ impl ClassLoader {
                 match self {
                     ClassLoader::Base => (),
                     ClassLoader::Bootstrap => {
-                        runtime::sys_dic_put(name, class.clone());
                         let this_ref = class.clone();
 
                         {
@@ -62,6 +117,11 @@ impl ClassLoader {
                         }
 
                         native::java_lang_Class::create_mirror(class.clone());
+                        //only published once linked and with a mirror
+                        runtime::sys_dic_put(name, class.clone());
+
+                        //a new subclass may break JIT devirtualization assumptions
+                        runtime::jit::cha::notify_class_loaded(class);
                     }
                 }
             }
@@ -87,6 +147,8 @@ impl ClassLoader {
                                     let mut class = class.get_mut_class();
                                     class.link_class(this_ref);
                                 }
+                                native::java_lang_Class::create_mirror(class.clone());
+
                                 match self {
                                     ClassLoader::Base => (),
                                     ClassLoader::Bootstrap => {
@@ -94,8 +156,6 @@ impl ClassLoader {
                                     }
                                 }
 
-                                native::java_lang_Class::create_mirror(class.clone());
-
                                 Some(class)
                             }
                             None => None,
@@ -137,6 +197,8 @@ impl ClassLoader {
                     Some(down_type) => {
                         let class = Class::new_wrapped_ary(*self, down_type);
                         let class = Arc::new(class);
+                        native::java_lang_Class::create_mirror(class.clone());
+
                         match self {
                             ClassLoader::Base => (),
                             ClassLoader::Bootstrap => {
@@ -144,8 +206,6 @@ impl ClassLoader {
                             }
                         }
 
-                        native::java_lang_Class::create_mirror(class.clone());
-
                         Some(class)
                     }
 
@@ -158,15 +218,31 @@ impl ClassLoader {
     fn load_class_from_path(&self, name: &[u8]) -> Option<ClassRef> {
         let name = unsafe { std::str::from_utf8_unchecked(name) };
         match runtime::find_class_in_classpath(name) {
-            Ok(ClassPathResult(_, buf)) => match parse_class(&buf) {
-                Ok(cf) => {
-                    let cfr = Arc::new(Box::new(cf));
-                    let class = Class::new_class(cfr, Some(*self));
-                    Some(Arc::new(class))
-                }
+            Ok(ClassPathResult(path, buf)) => {
+                //CDS archive: reuse the parsed constant pool and linked layout
+                let shared = class_path_manager::find_shared_class(&path, name);
+                let cf = match &shared {
+                    Some(shared) => {
+                        class_parser::parse_with_cp(&buf, shared.cp.clone(), shared.cp_end)
+                    }
+                    None => parse_class(&buf),
+                };
+                match cf {
+                    Ok(cf) => {
+                        runtime::jit::aot::record_class_bytes(name, &buf);
+                        let cfr = Arc::new(Box::new(cf));
+                        let class = match shared {
+                            Some(shared) => {
+                                Class::new_shared_class(cfr, Some(*self), shared.layout)
+                            }
+                            None => Class::new_class(cfr, Some(*self)),
+                        };
+                        Some(Arc::new(class))
+                    }
 
-                Err(e) => unreachable!("name={}, {}", name, e),
-            },
+                    Err(e) => unreachable!("name={}, {}", name, e),
+                }
+            }
 
             Err(_) => None,
         }
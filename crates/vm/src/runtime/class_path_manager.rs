@@ -1,10 +1,13 @@
+use crate::runtime::{cds, module};
 use crate::util;
 use jimage_rs::JImage;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
 use std::path::{self, Path};
-use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 use tracing::{debug, error, info, trace, warn};
 use zip::ZipArchive;
 
@@ -21,67 +24,434 @@ pub fn find_class(name: &str) -> Result<ClassPathResult, io::Error> {
 
 pub fn add_path(path: &str) {
     let mut cpm = CPM.get().unwrap().write().unwrap();
+    cpm.app_class_path.push(path.to_string());
     cpm.add_class_path(path);
 }
 
 pub fn add_paths(path: &str) {
     let mut cpm = CPM.get().unwrap().write().unwrap();
+    cpm.app_class_path
+        .extend(path.split(util::PATH_SEP).map(String::from));
     cpm.add_class_paths(path);
 }
 
-/// 添加 JImage（JDK 9+ modules 文件）作为引导类路径源。
-pub fn add_boot_jimage(path: &str) {
+/// 应用类路径（不含引导类路径），即 `java.class.path`。
+pub fn app_class_path() -> String {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    cpm.app_class_path.join(util::PATH_SEP)
+}
+
+/// 添加引导类路径中的目录或 jar，失败时把错误交给调用者。
+pub fn add_boot_path(path: &str) -> Result<(), io::Error> {
     let mut cpm = CPM.get().unwrap().write().unwrap();
-    if let Err(e) = cpm.add_jimage_path(path) {
-        warn!("Failed to add boot JImage path '{}': {}", path, e);
-    } else {
-        info!("Boot JImage loaded from: {}", path);
-    }
+    cpm.add_entry_path(path, EntryKind::Boot)
+}
+
+/// 添加模块路径上已解析模块 `module` 的目录或 jar，它们不属于
+/// `java.class.path`。
+pub fn add_module_path(module: &str, path: &str) -> Result<(), io::Error> {
+    let mut cpm = CPM.get().unwrap().write().unwrap();
+    cpm.add_entry_path(path, EntryKind::Module(module.to_string()))
+}
+
+/// 运行时镜像中的系统模块名，JDK 8 没有模块，返回空。
+pub fn system_module_names() -> Vec<String> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    cpm.runtime_class_path
+        .iter()
+        .filter_map(|it| match &it.source {
+            ClassSource::JIMAGE(src) => Some(src.modules()),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// 读取系统模块的 `module-info.class`。
+pub fn find_system_module_info(module: &str) -> Option<Vec<u8>> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    let path = format!("/{}/module-info.class", module);
+    cpm.runtime_class_path
+        .iter()
+        .find_map(|it| match &it.source {
+            ClassSource::JIMAGE(src) => match src.jimage.find_resource(&path) {
+                Ok(Some(data)) => Some(data.into_owned()),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+/// 添加 JImage（JDK 9+ modules 文件）作为引导类路径源，`archive` 是已
+/// 校验过的 CDS 归档。
+pub(crate) fn add_boot_jimage(path: &str, archive: Option<cds::Archive>) -> Result<(), String> {
+    let mut cpm = CPM.get().unwrap().write().unwrap();
+    cpm.add_jimage_path(path, archive)?;
+    info!("Boot JImage loaded from: {}", path);
+    Ok(())
+}
+
+/// 从运行时镜像读取类文件，不经过其他类路径。
+pub(crate) fn find_jimage_class(name: &str) -> Option<Vec<u8>> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    cpm.runtime_class_path
+        .iter()
+        .find_map(|it| match &it.source {
+            ClassSource::JIMAGE(src) => src.find_class(name),
+            _ => None,
+        })
+}
+
+/// 类文件取自路径为 `path` 的运行时镜像且归档了链接元数据时，返回归档
+/// 的常量池与布局。
+pub(crate) fn find_shared_class(path: &str, name: &str) -> Option<cds::SharedClass> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    cpm.runtime_class_path
+        .iter()
+        .filter(|it| it.path == path)
+        .find_map(|it| match &it.source {
+            ClassSource::JIMAGE(src) => src.archive.as_ref()?.find(name)?.shared(),
+            _ => None,
+        })
+}
+
+/// 运行时镜像的路径及其完整类索引，供 CDS 写出归档。
+pub(crate) fn jimage_index() -> Option<(String, cds::ImageIndex)> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    cpm.runtime_class_path
+        .iter()
+        .find_map(|it| match &it.source {
+            ClassSource::JIMAGE(src) => {
+                let index = src.index();
+                let mut modules: Vec<(String, bool)> =
+                    index.modules.iter().map(|m| (m.clone(), true)).collect();
+                for module in index.classes.values() {
+                    if !modules.iter().any(|(m, _)| m == module) {
+                        modules.push((module.clone(), false));
+                    }
+                }
+                let classes = index
+                    .classes
+                    .iter()
+                    .map(|(name, module)| (name.clone(), module.clone()))
+                    .collect();
+                Some((it.path.clone(), cds::ImageIndex { classes, modules }))
+            }
+            _ => None,
+        })
+}
+
+/// 查找资源 `name`（`a/b/c.txt` 形式），返回 `file:`、`jar:` 或 `jrt:` URL。
+/// `all` 为 false 时只返回第一个。命名模块的包中的资源只在所属模块中找，
+/// 除类文件外模块须开放；其他资源先找本范围的模块，再找类路径。
+pub fn find_resources(name: &str, scope: ResourceScope, all: bool) -> Vec<String> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    cpm.find_resources(name, scope, all)
+}
+
+/// 只在类路径（不含模块）中查找资源。
+pub fn find_class_path_resource(name: &str, scope: ResourceScope) -> Option<String> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    cpm.search_resources(name, scope, &[false], false).pop()
+}
+
+/// 在模块 `module` 中查找资源，不检查封装。
+pub fn find_module_resource(module: &str, name: &str, scope: ResourceScope) -> Option<String> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    let it = cpm.module_entry(module, scope)?;
+    cpm.entry_resource(it, name, Some(module))
+}
+
+/// 读取 `find_resources` 返回的 URL 的内容，资源不存在时为 `NotFound`。
+pub fn read_url(url: &str) -> io::Result<Vec<u8>> {
+    let cpm = CPM.get().unwrap().read().unwrap();
+    cpm.read_url(url)
+}
+
+/// 资源查找的范围，对应 JDK 的引导类加载器与应用类加载器。
+/// 运行时镜像与模块路径上的模块都由引导类加载器定义。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceScope {
+    Boot,
+    App,
 }
 
 #[derive(Debug)]
 pub struct ClassPathResult(pub String, pub Vec<u8>);
 
-type ZipRef = Arc<Mutex<Box<ZipArchive<File>>>>;
+/// 可执行 jar 的 `META-INF/MANIFEST.MF` 主段中启动用到的属性。
+#[derive(Debug, Default, PartialEq)]
+pub struct Manifest {
+    pub main_class: Option<String>,
+    /// `Class-Path` 条目，已相对 jar 所在目录解析为路径
+    pub class_path: Vec<String>,
+    /// 启动前运行的 agent，暂不支持，由启动器提示
+    pub launcher_agent_class: Option<String>,
+    /// 放在模块路径上时作为自动模块的模块名
+    pub automatic_module_name: Option<String>,
+}
+
+impl Manifest {
+    /// 解析主段：到第一个空行为止，以空格开头的行续接上一行的值，
+    /// 属性名不区分大小写。
+    fn parse(text: &str, base: &Path) -> Self {
+        let mut attrs: Vec<(String, String)> = Vec::new();
+        for line in text.lines() {
+            if line.is_empty() {
+                break;
+            }
+            if let Some(rest) = line.strip_prefix(' ') {
+                if let Some((_, v)) = attrs.last_mut() {
+                    v.push_str(rest);
+                }
+            } else if let Some((k, v)) = line.split_once(':') {
+                attrs.push((k.trim().to_ascii_lowercase(), v.trim_start().to_string()));
+            }
+        }
+
+        let get = |name: &str| {
+            attrs
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let class_path = get("class-path")
+            .map(|v| {
+                v.split_whitespace()
+                    .map(|url| resolve_class_path_url(url, base))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            main_class: get("main-class"),
+            class_path,
+            launcher_agent_class: get("launcher-agent-class"),
+            automatic_module_name: get("automatic-module-name"),
+        }
+    }
+}
+
+/// 读取 jar 的 manifest，没有 manifest 时返回空的 `Manifest`。
+pub fn read_manifest(jar: &str) -> Result<Manifest, io::Error> {
+    let f = File::open(jar)?;
+    let mut z = ZipArchive::new(f)?;
+    let mut text = String::new();
+    match z.by_name("META-INF/MANIFEST.MF") {
+        Ok(mut zf) => {
+            zf.read_to_string(&mut text)?;
+        }
+        Err(_) => return Ok(Manifest::default()),
+    }
+    let base = Path::new(jar).parent().unwrap_or_else(|| Path::new(""));
+    Ok(Manifest::parse(&text, base))
+}
 
-/// JImage 源，包含 JImage 实例和后缀索引。
-/// 索引将短路径（如 "java/lang/String"）映射到完整 JImage 资源路径
-///（如 "/java.base/java/lang/String.class"）。
+/// `Class-Path` 的条目是相对 jar 的 URL：解码 `%XX`，去掉 `file:`。
+fn resolve_class_path_url(url: &str, base: &Path) -> String {
+    let url = url.strip_prefix("file:").unwrap_or(url);
+    let path = decode_url_path(url);
+    if Path::new(&path).is_absolute() {
+        path
+    } else {
+        base.join(path).to_string_lossy().to_string()
+    }
+}
+
+/// 解码 URL 路径中的 `%XX`。
+fn decode_url_path(url: &str) -> String {
+    let bytes = url.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// 与 `sun.net.www.ParseUtil.encodePath` 一样编码 URL 路径，
+/// 保留字母数字与 `/-_.!~*'();:@&=+$,`。
+fn encode_url_path(path: &str) -> String {
+    let mut url = String::with_capacity(path.len());
+    for &b in path.as_bytes() {
+        if b.is_ascii_alphanumeric() || b"/-_.!~*'();:@&=+$,".contains(&b) {
+            url.push(b as char);
+        } else {
+            url.push_str(&format!("%{:02X}", b));
+        }
+    }
+    url
+}
+
+/// jar 文件的只读句柄。按偏移读（pread），不共享文件游标，
+/// 所以克隆出的 `ZipArchive` 可以在多个线程中同时读同一个 jar。
+#[derive(Clone)]
+struct SharedFile {
+    file: Arc<File>,
+    pos: u64,
+}
+
+impl SharedFile {
+    fn new(file: File) -> Self {
+        Self {
+            file: Arc::new(file),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for SharedFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(unix)]
+        let n = std::os::unix::fs::FileExt::read_at(&*self.file, buf, self.pos)?;
+        #[cfg(windows)]
+        let n = std::os::windows::fs::FileExt::seek_read(&*self.file, buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for SharedFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n);
+            }
+            SeekFrom::End(n) => (self.file.metadata()?.len(), n),
+            SeekFrom::Current(n) => (self.pos, n),
+        };
+        match base.checked_add_signed(offset) {
+            Some(n) => {
+                self.pos = n;
+                Ok(n)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+/// 已解析中央目录的 jar，读取时克隆一份（只复制 `Arc`）。
+type ZipRef = ZipArchive<SharedFile>;
+
+/// JImage 源，包含 JImage 实例和类索引。
+/// 有 CDS 归档时由归档回答查找，否则首次查找时遍历镜像建立索引。
 struct JImageSource {
     jimage: JImage,
-    index: HashMap<String, String>,
+    archive: Option<cds::Archive>,
+    index: OnceLock<JImageIndex>,
 }
 
-impl JImageSource {
-    fn new(jimage: JImage) -> Self {
-        let mut index = HashMap::new();
+/// 索引将短路径（如 "java/lang/String"）映射到所在模块（如 "java.base"），
+/// 完整 JImage 资源路径为 "/java.base/java/lang/String.class"。
+struct JImageIndex {
+    classes: HashMap<String, String>,
+    /// 含 `module-info.class` 的模块
+    modules: Vec<String>,
+}
+
+impl JImageIndex {
+    fn new(jimage: &JImage) -> Self {
+        let mut classes = HashMap::new();
+        let mut modules = Vec::new();
         for rn in jimage.resource_names_iter() {
             if let Ok(rn) = rn {
                 let module = rn.module.to_string();
                 let parent = rn.parent.to_string();
                 let base = rn.base.to_string();
                 let extension = rn.extension.to_string();
-                if extension == "class" {
+                if extension == "class" && parent.is_empty() && base == "module-info" {
+                    modules.push(module);
+                } else if extension == "class" {
                     // 索引 key: "java/lang/String"（不含模块前缀）
                     // search_class 传入的是 "java/lang/String" 格式
                     let key = if parent.is_empty() {
-                        base.clone()
+                        base
                     } else {
                         format!("{}/{}", parent, base)
                     };
-                    // JImage find_resource 需要的完整路径: "/java.base/java/lang/String.class"
-                    let full_path = if parent.is_empty() {
-                        format!("/{}/{}.{}", module, base, extension)
-                    } else {
-                        format!("/{}/{}/{}.{}", module, parent, base, extension)
-                    };
                     // 同一个 key 可能被多个模块提供（如 jdk.internal.*），
                     // 取第一个即可（bootstrap 类通常在 java.base）
-                    index.entry(key).or_insert(full_path);
+                    classes.entry(key).or_insert(module);
                 }
             }
         }
-        Self { jimage, index }
+        Self { classes, modules }
+    }
+}
+
+impl JImageSource {
+    fn new(jimage: JImage, archive: Option<cds::Archive>) -> Self {
+        Self {
+            jimage,
+            archive,
+            index: OnceLock::new(),
+        }
+    }
+
+    fn index(&self) -> &JImageIndex {
+        self.index.get_or_init(|| JImageIndex::new(&self.jimage))
+    }
+
+    fn read(&self, module: &str, name: &str) -> Option<Vec<u8>> {
+        let full_path = format!("/{}/{}.class", module, name);
+        match self.jimage.find_resource(&full_path) {
+            Ok(Some(data)) => Some(data.into_owned()),
+            Ok(None) => {
+                // 哈希冲突或索引不一致，继续下一个 entry
+                trace!(
+                    "JImage index mismatch for {}, full_path={}",
+                    name,
+                    full_path
+                );
+                None
+            }
+            Err(e) => {
+                error!("JImage find_resource error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// 查找类文件，`name` 形如 "java/lang/String"。
+    fn find_class(&self, name: &str) -> Option<Vec<u8>> {
+        match &self.archive {
+            Some(archive) => {
+                let class = archive.find(name)?;
+                match class.bytes {
+                    Some(bytes) => Some(bytes.to_vec()),
+                    None => self.read(class.module, name),
+                }
+            }
+            None => {
+                let module = self.index().classes.get(name)?;
+                self.read(module, name)
+            }
+        }
+    }
+
+    fn modules(&self) -> Vec<String> {
+        match &self.archive {
+            Some(archive) => archive.module_names(),
+            None => self.index().modules.clone(),
+        }
     }
 }
 
@@ -91,31 +461,114 @@ enum ClassSource {
     JIMAGE(JImageSource),
 }
 
-struct ClassPathEntry(ClassSource, String);
+/// 条目属于哪个类加载器，决定资源查找的范围。
+#[derive(Debug, Clone, PartialEq)]
+enum EntryKind {
+    Boot,
+    /// 模块路径上的模块及其模块名
+    Module(String),
+    /// `java.class.path`
+    App,
+}
+
+struct ClassPathEntry {
+    source: ClassSource,
+    path: String,
+    kind: EntryKind,
+    /// 条目中资源 URL 的前缀：`file:/dir/`、`jar:file:/a.jar!/` 或 `jrt:/`
+    url: String,
+}
+
+impl ClassPathEntry {
+    fn new(source: ClassSource, path: &str, kind: EntryKind) -> Self {
+        // 与 `URLClassPath` 一样使用规范路径
+        let canonical = std::fs::canonicalize(path)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string());
+        let canonical = encode_url_path(canonical.trim_end_matches('/'));
+        let url = match &source {
+            ClassSource::DIR => format!("file:{}/", canonical),
+            ClassSource::JAR(_) => format!("jar:file:{}!/", canonical),
+            ClassSource::JIMAGE(_) => "jrt:/".to_string(),
+        };
+        Self {
+            source,
+            path: path.to_string(),
+            kind,
+            url,
+        }
+    }
+
+    fn in_scope(&self, scope: ResourceScope) -> bool {
+        match scope {
+            ResourceScope::Boot => self.kind != EntryKind::App,
+            ResourceScope::App => self.kind == EntryKind::App,
+        }
+    }
+
+    /// 模块（运行时镜像或模块路径上的模块），而不是类路径上的目录或 jar
+    fn is_module(&self) -> bool {
+        matches!(self.source, ClassSource::JIMAGE(_)) || matches!(self.kind, EntryKind::Module(_))
+    }
+}
 
 struct ClassPathManager {
     runtime_class_path: Vec<ClassPathEntry>,
+    app_class_path: Vec<String>,
+    /// 包名（如 "java/lang"）到含有该包的 jar 在 `runtime_class_path` 中的
+    /// 下标，按类路径顺序，在 `add_class_path` 时建立
+    packages: FxHashMap<String, Vec<usize>>,
+    /// 每次查找都要探测的条目下标：目录的内容可能在运行时变化，不建索引；
+    /// 镜像自带索引或 CDS 归档
+    unindexed: Vec<usize>,
+    /// jar 与镜像中都没有的类，类路径增加条目时清空；目录仍照常探测
+    misses: RwLock<FxHashSet<String>>,
 }
 
 impl ClassPathManager {
     fn new() -> Self {
         Self {
             runtime_class_path: vec![],
+            app_class_path: vec![],
+            packages: FxHashMap::default(),
+            unindexed: vec![],
+            misses: RwLock::new(FxHashSet::default()),
         }
     }
 
+    fn push_entry(&mut self, entry: ClassPathEntry) {
+        let idx = self.runtime_class_path.len();
+        match &entry.source {
+            ClassSource::JAR(z) => {
+                // 类与其他资源共用一个索引，目录条目 "a/b/" 记在 "a/b" 下
+                let packages: FxHashSet<&str> = z.file_names().map(package_of).collect();
+                for package in packages {
+                    self.packages
+                        .entry(package.to_string())
+                        .or_default()
+                        .push(idx);
+                }
+            }
+            ClassSource::DIR | ClassSource::JIMAGE(_) => self.unindexed.push(idx),
+        }
+        self.runtime_class_path.push(entry);
+        // 新条目可能提供之前找不到的类
+        self.misses.write().unwrap().clear();
+    }
+
     pub fn add_class_path(&mut self, path: &str) -> Result<(), io::Error> {
+        self.add_entry_path(path, EntryKind::App)
+    }
+
+    fn add_entry_path(&mut self, path: &str, kind: EntryKind) -> Result<(), io::Error> {
         let p = Path::new(path);
-        if p.is_dir() {
-            self.runtime_class_path
-                .push(ClassPathEntry(ClassSource::DIR, path.to_string()));
+        let source = if p.is_dir() {
+            ClassSource::DIR
         } else {
             let f = File::open(p)?;
-            let mut z = ZipArchive::new(f)?;
-            let handle = Arc::new(Mutex::new(Box::new(z)));
-            self.runtime_class_path
-                .push(ClassPathEntry(ClassSource::JAR(handle), path.to_string()));
-        }
+            ClassSource::JAR(ZipArchive::new(SharedFile::new(f))?)
+        };
+        self.push_entry(ClassPathEntry::new(source, path, kind));
 
         Ok(())
     }
@@ -129,74 +582,69 @@ impl ClassPathManager {
     }
 
     /// 添加 JImage（JDK 9+ modules 文件）作为类路径源。
-    pub fn add_jimage_path(&mut self, path: &str) -> Result<(), String> {
+    pub fn add_jimage_path(
+        &mut self,
+        path: &str,
+        archive: Option<cds::Archive>,
+    ) -> Result<(), String> {
         let jimage = JImage::open(path).map_err(|e| format!("Failed to open JImage: {}", e))?;
-        let source = JImageSource::new(jimage);
-        self.runtime_class_path.push(ClassPathEntry(
+        let source = JImageSource::new(jimage, archive);
+        self.push_entry(ClassPathEntry::new(
             ClassSource::JIMAGE(source),
-            path.to_string(),
+            path,
+            EntryKind::Boot,
         ));
         Ok(())
     }
 
+    /// 按类路径顺序查找类，`name` 形如 "java/lang/String"，也接受 "java.lang.String"。
+    /// 只探测含有该包的 jar、目录与镜像。
     pub fn search_class(&self, name: &str) -> Result<ClassPathResult, io::Error> {
-        let name = name.replace("/", util::FILE_SEP);
-        let name = name.replace(".", util::FILE_SEP);
+        let name: Cow<str> = if name.contains('.') {
+            Cow::Owned(name.replace('.', "/"))
+        } else {
+            Cow::Borrowed(name)
+        };
+        let name = name.as_ref();
 
         trace!("search_class: {}", name);
 
-        for it in self.runtime_class_path.iter() {
-            match &it.0 {
+        let known_miss = self.misses.read().unwrap().contains(name);
+        let jars = match known_miss {
+            true => &[][..],
+            false => self.indexed(package_of(name)),
+        };
+
+        for idx in self.candidates(jars) {
+            let it = &self.runtime_class_path[idx];
+            match &it.source {
                 ClassSource::DIR => {
-                    let mut p = String::from(&it.1);
-                    p.push_str(util::FILE_SEP);
-                    p.push_str(&name);
-                    p.push_str(".class");
+                    let p = Path::new(&it.path).join(format!("{}.class", name));
                     if let Ok(data) = std::fs::read(&p) {
-                        return Ok(ClassPathResult(p, data));
+                        return Ok(ClassPathResult(p.to_string_lossy().to_string(), data));
                     }
                 }
 
-                ClassSource::JAR(handle) => {
-                    let mut p = String::from(&name);
-                    p.push_str(".class");
-
-                    let mut handle = handle.lock().unwrap();
-                    let mut zf = handle.by_name(&p);
-
-                    if let Ok(mut zf) = zf {
-                        let mut v = Vec::with_capacity(zf.size() as usize);
-                        let r = zf.read_to_end(&mut v);
-                        debug_assert!(r.is_ok());
-                        return Ok(ClassPathResult(it.1.clone(), v));
+                ClassSource::JAR(z) => {
+                    if let Some(v) = read_jar_entry(z, &format!("{}.class", name))? {
+                        return Ok(ClassPathResult(it.path.clone(), v));
                     }
                 }
 
                 ClassSource::JIMAGE(src) => {
-                    // JImage 索引使用 / 分隔符，需要还原原始格式
-                    let jimage_key = name.replace(util::FILE_SEP, "/");
-                    if let Some(full_path) = src.index.get(&jimage_key) {
-                        match src.jimage.find_resource(full_path) {
-                            Ok(Some(data)) => {
-                                return Ok(ClassPathResult(it.1.clone(), data.into_owned()));
-                            }
-                            Ok(None) => {
-                                // 哈希冲突或索引不一致，继续下一个 entry
-                                trace!(
-                                    "JImage index mismatch for {}, full_path={}",
-                                    name,
-                                    full_path
-                                );
-                            }
-                            Err(e) => {
-                                error!("JImage find_resource error: {:?}", e);
-                            }
+                    if !known_miss {
+                        if let Some(data) = src.find_class(name) {
+                            return Ok(ClassPathResult(it.path.clone(), data));
                         }
                     }
                 }
             }
         }
 
+        if !known_miss {
+            self.misses.write().unwrap().insert(name.to_string());
+        }
+
         Err(io::Error::new(
             io::ErrorKind::NotFound,
             format!("Search class failed: {}", name),
@@ -206,6 +654,193 @@ impl ClassPathManager {
     pub fn size(&self) -> usize {
         self.runtime_class_path.len()
     }
+
+    /// jar 索引中含有包 `package` 的条目下标
+    fn indexed(&self, package: &str) -> &[usize] {
+        self.packages.get(package).map_or(&[][..], |v| v.as_slice())
+    }
+
+    /// 要探测的条目下标：`jars` 与 `unindexed` 都按类路径顺序，归并后依次返回
+    fn candidates<'a>(&'a self, jars: &'a [usize]) -> impl Iterator<Item = usize> + 'a {
+        let (mut i, mut j) = (0, 0);
+        std::iter::from_fn(move || match (jars.get(i), self.unindexed.get(j)) {
+            (Some(&a), Some(&b)) if a < b => {
+                i += 1;
+                Some(a)
+            }
+            (Some(&a), None) => {
+                i += 1;
+                Some(a)
+            }
+            (_, Some(&b)) => {
+                j += 1;
+                Some(b)
+            }
+            (None, None) => None,
+        })
+    }
+
+    fn find_resources(&self, name: &str, scope: ResourceScope, all: bool) -> Vec<String> {
+        trace!("find_resources: {}", name);
+
+        // 与 `Resources.toPackageName` 一样，以 '/' 结尾的名字不属于任何包
+        let package = match name.rsplit_once('/') {
+            Some((package, rest)) if !rest.is_empty() => package,
+            _ => "",
+        };
+        if let Some((module, open)) = module::package_module(package) {
+            if let Some(it) = self.module_entry(&module, scope) {
+                if !name.ends_with(".class") && !open {
+                    return vec![];
+                }
+                return self
+                    .entry_resource(it, name, Some(&module))
+                    .into_iter()
+                    .collect();
+            }
+        }
+
+        // 先找模块中不在包里的资源（如 META-INF/services），再找类路径
+        self.search_resources(name, scope, &[true, false], all)
+    }
+
+    /// 依次在模块（`true`）或类路径（`false`）的条目中查找资源
+    fn search_resources(
+        &self,
+        name: &str,
+        scope: ResourceScope,
+        passes: &[bool],
+        all: bool,
+    ) -> Vec<String> {
+        let mut urls = vec![];
+        for &in_module in passes {
+            for idx in self.candidates(self.indexed(package_of(name))) {
+                let it = &self.runtime_class_path[idx];
+                if !it.in_scope(scope) || it.is_module() != in_module {
+                    continue;
+                }
+                if let Some(url) = self.entry_resource(it, name, None) {
+                    urls.push(url);
+                    if !all {
+                        return urls;
+                    }
+                }
+            }
+        }
+        urls
+    }
+
+    /// 定义模块 `module` 的条目：模块路径上的模块或运行时镜像，
+    /// 它们都由引导类加载器定义
+    fn module_entry(&self, module: &str, scope: ResourceScope) -> Option<&ClassPathEntry> {
+        if scope != ResourceScope::Boot {
+            return None;
+        }
+        let mut image = None;
+        for it in &self.runtime_class_path {
+            match (&it.kind, &it.source) {
+                (EntryKind::Module(m), _) if m == module => return Some(it),
+                (_, ClassSource::JIMAGE(_)) => image = Some(it),
+                _ => (),
+            }
+        }
+        image
+    }
+
+    /// 条目中资源 `name` 的 URL，镜像中只找模块 `module`，为 `None` 时找所有模块
+    fn entry_resource(
+        &self,
+        it: &ClassPathEntry,
+        name: &str,
+        module: Option<&str>,
+    ) -> Option<String> {
+        // 不允许跳出条目
+        if name.starts_with('/') || name.split('/').any(|s| s == "..") {
+            return None;
+        }
+        let url = |prefix: &str| format!("{}{}", prefix, encode_url_path(name));
+        match &it.source {
+            ClassSource::DIR => Path::new(&it.path)
+                .join(name)
+                .exists()
+                .then(|| url(&it.url)),
+            ClassSource::JAR(z) => z.clone().by_name(name).is_ok().then(|| url(&it.url)),
+            ClassSource::JIMAGE(src) => {
+                let found = |m: &str| {
+                    matches!(
+                        src.jimage.find_resource(&format!("/{}/{}", m, name)),
+                        Ok(Some(_))
+                    )
+                };
+                match module {
+                    Some(m) => found(m).then(|| url(&format!("jrt:/{}/", m))),
+                    None => src
+                        .modules()
+                        .into_iter()
+                        .find(|m| found(m))
+                        .map(|m| url(&format!("jrt:/{}/", m))),
+                }
+            }
+        }
+    }
+
+    fn read_url(&self, url: &str) -> io::Result<Vec<u8>> {
+        let not_found = || io::Error::new(io::ErrorKind::NotFound, format!("{} not found", url));
+        if let Some(path) = url.strip_prefix("file:") {
+            std::fs::read(decode_url_path(path))
+        } else if let Some(rest) = url.strip_prefix("jar:") {
+            let (jar, entry) = rest.split_once("!/").ok_or_else(not_found)?;
+            let entry = decode_url_path(entry);
+            let prefix = format!("jar:{}!/", jar);
+            let known = self
+                .runtime_class_path
+                .iter()
+                .find_map(|it| match &it.source {
+                    ClassSource::JAR(z) if it.url == prefix => Some(z.clone()),
+                    _ => None,
+                });
+            let z = match known {
+                Some(z) => z,
+                None => {
+                    let path = jar.strip_prefix("file:").ok_or_else(not_found)?;
+                    let f = File::open(decode_url_path(path))?;
+                    ZipArchive::new(SharedFile::new(f))?
+                }
+            };
+            read_jar_entry(&z, &entry)?.ok_or_else(not_found)
+        } else if let Some(path) = url.strip_prefix("jrt:") {
+            let path = decode_url_path(path);
+            self.runtime_class_path
+                .iter()
+                .find_map(|it| match &it.source {
+                    ClassSource::JIMAGE(src) => match src.jimage.find_resource(&path) {
+                        Ok(Some(data)) => Some(data.into_owned()),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .ok_or_else(not_found)
+        } else {
+            Err(not_found())
+        }
+    }
+}
+
+/// 读取 jar 中的条目，没有该条目时返回 `None`。
+fn read_jar_entry(z: &ZipRef, name: &str) -> io::Result<Option<Vec<u8>>> {
+    let mut z = z.clone();
+    let mut zf = match z.by_name(name) {
+        Ok(zf) => zf,
+        Err(_) => return Ok(None),
+    };
+    let mut v = Vec::with_capacity(zf.size() as usize);
+    zf.read_to_end(&mut v)?;
+    Ok(Some(v))
+}
+
+/// 类名或资源名所在的包，默认包为空串。
+fn package_of(name: &str) -> &str {
+    name.rsplit_once('/').map_or("", |(package, _)| package)
 }
 
 #[cfg(test)]
@@ -223,6 +858,174 @@ mod tests {
         }
     }
 
+    #[test]
+    fn t_parse_manifest() {
+        let text = "Manifest-Version: 1.0\r\n\
+                    Main-Class: com.example.app.Ma\r\n in\r\n\
+                    class-path: lib/a.jar lib/my%20b.jar\r\n  /opt/c.jar\r\n\
+                    Launcher-Agent-Class: com.example.Agent\r\n\
+                    Automatic-Module-Name: com.example.app\r\n\
+                    \r\n\
+                    Name: com/example/\r\n\
+                    Main-Class: Ignored\r\n";
+        let m = super::Manifest::parse(text, super::Path::new("/app"));
+        assert_eq!(m.main_class.as_deref(), Some("com.example.app.Main"));
+        assert_eq!(
+            m.class_path,
+            vec!["/app/lib/a.jar", "/app/lib/my b.jar", "/opt/c.jar"]
+        );
+        assert_eq!(m.launcher_agent_class.as_deref(), Some("com.example.Agent"));
+        assert_eq!(m.automatic_module_name.as_deref(), Some("com.example.app"));
+
+        let m = super::Manifest::parse("Manifest-Version: 1.0\n", super::Path::new(""));
+        assert_eq!(m, super::Manifest::default());
+    }
+
+    #[test]
+    fn t_read_manifest() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("jvm-manifest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let jar = dir.join("app.jar");
+        {
+            let mut w = zip::ZipWriter::new(super::File::create(&jar).unwrap());
+            let opts = zip::write::FileOptions::default();
+            w.start_file("META-INF/MANIFEST.MF", opts).unwrap();
+            w.write_all(b"Main-Class: Hello\nClass-Path: dep.jar\n")
+                .unwrap();
+            w.start_file("Hello.class", opts).unwrap();
+            w.finish().unwrap();
+        }
+
+        let jar = jar.to_string_lossy().to_string();
+        let m = super::read_manifest(&jar).unwrap();
+        assert_eq!(m.main_class.as_deref(), Some("Hello"));
+        assert_eq!(
+            m.class_path,
+            vec![dir.join("dep.jar").to_string_lossy().to_string()]
+        );
+        assert!(super::read_manifest(&format!("{}.missing", jar)).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn t_search_indexed() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("jvm-cp-index-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("classes/b")).unwrap();
+        let jar = |name: &str, files: &[(&str, &[u8])]| {
+            let path = dir.join(name);
+            let mut w = zip::ZipWriter::new(super::File::create(&path).unwrap());
+            for (file, data) in files {
+                w.start_file(*file, zip::write::FileOptions::default())
+                    .unwrap();
+                w.write_all(data).unwrap();
+            }
+            w.finish().unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let a = jar("a.jar", &[("a/A.class", b"a1"), ("Top.class", b"top")]);
+        let b = jar("b.jar", &[("a/A.class", b"a2"), ("b/B.class", b"b2")]);
+        let classes = dir.join("classes").to_string_lossy().to_string();
+
+        let mut cpm = super::ClassPathManager::new();
+        cpm.add_class_path(&a).unwrap();
+        cpm.add_class_path(&classes).unwrap();
+        cpm.add_class_path(&b).unwrap();
+        assert_eq!(cpm.packages["a"], vec![0, 2]);
+
+        // 先出现的条目优先
+        assert_eq!(cpm.search_class("a/A").unwrap().1, b"a1");
+        assert_eq!(cpm.search_class("a.A").unwrap().1, b"a1");
+        assert_eq!(cpm.search_class("Top").unwrap().1, b"top");
+
+        // 目录排在 b.jar 之前，并且在缓存找不到之后仍然探测
+        assert_eq!(cpm.search_class("b/B").unwrap().1, b"b2");
+        assert!(cpm.search_class("b/C").is_err());
+        assert!(cpm.misses.read().unwrap().contains("b/C"));
+        std::fs::write(dir.join("classes/b/C.class"), b"c").unwrap();
+        std::fs::write(dir.join("classes/b/B.class"), b"b1").unwrap();
+        assert_eq!(cpm.search_class("b/C").unwrap().1, b"c");
+        assert_eq!(cpm.search_class("b/B").unwrap().1, b"b1");
+
+        // 新增条目清空缓存
+        assert!(cpm.search_class("c/D").is_err());
+        let c = jar("c.jar", &[("c/D.class", b"d")]);
+        cpm.add_class_path(&c).unwrap();
+        assert!(cpm.misses.read().unwrap().is_empty());
+        assert_eq!(cpm.search_class("c/D").unwrap().1, b"d");
+
+        // 多个线程同时读同一个 jar
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..50 {
+                        assert_eq!(cpm.search_class("a/A").unwrap().1, b"a1");
+                        assert_eq!(cpm.search_class("Top").unwrap().1, b"top");
+                    }
+                });
+            }
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn t_find_resources() {
+        use super::{EntryKind, ResourceScope};
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("jvm-cp-res-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("res dir/conf")).unwrap();
+        std::fs::write(dir.join("res dir/conf/app.properties"), b"dir").unwrap();
+        let jar = dir.join("lib.jar");
+        let mut w = zip::ZipWriter::new(super::File::create(&jar).unwrap());
+        w.start_file("conf/app.properties", zip::write::FileOptions::default())
+            .unwrap();
+        w.write_all(b"jar").unwrap();
+        w.finish().unwrap();
+        let jar = jar.to_string_lossy().to_string();
+        let res = dir.join("res dir").to_string_lossy().to_string();
+
+        let mut cpm = super::ClassPathManager::new();
+        cpm.add_class_path(&jar).unwrap();
+        cpm.add_class_path(&res).unwrap();
+        cpm.add_entry_path(&res, EntryKind::Boot).unwrap();
+
+        let canonical = std::fs::canonicalize(&dir).unwrap();
+        let base = canonical.to_string_lossy();
+        let urls = cpm.find_resources("conf/app.properties", ResourceScope::App, true);
+        assert_eq!(
+            urls,
+            vec![
+                format!("jar:file:{}/lib.jar!/conf/app.properties", base),
+                format!("file:{}/res%20dir/conf/app.properties", base),
+            ]
+        );
+        assert_eq!(cpm.read_url(&urls[0]).unwrap(), b"jar");
+        assert_eq!(cpm.read_url(&urls[1]).unwrap(), b"dir");
+
+        // 引导类路径只看到引导条目，目录本身也是资源
+        let boot = cpm.find_resources("conf/", ResourceScope::Boot, true);
+        assert_eq!(boot, vec![format!("file:{}/res%20dir/conf/", base)]);
+
+        assert!(cpm
+            .find_resources("conf/missing", ResourceScope::App, true)
+            .is_empty());
+        assert!(cpm
+            .find_resources("../lib.jar", ResourceScope::App, false)
+            .is_empty());
+        let missing = urls[0].replace("app.properties", "missing");
+        let e = cpm.read_url(&missing).unwrap_err();
+        assert_eq!(e.kind(), super::io::ErrorKind::NotFound);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn t_replace_all() {
         let class = "java.lang.String";
@@ -294,7 +1097,7 @@ mod tests {
         }
 
         let mut cpm = super::ClassPathManager::new();
-        assert!(cpm.add_jimage_path(&modules_path).is_ok());
+        assert!(cpm.add_jimage_path(&modules_path, None).is_ok());
         assert_eq!(cpm.size(), 1);
 
         // 验证 java/lang/String 可被找到
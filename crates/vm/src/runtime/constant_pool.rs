@@ -3,7 +3,7 @@ use std::sync::Mutex;
 use rustc_hash::FxHashMap;
 use tracing::{debug, error, info, trace, warn};
 
-use classfile::ConstantPool;
+use classfile::{constant_pool, BytesRef, ConstantPool};
 
 use crate::oop::field;
 use crate::types::{FieldIdRef, MethodIdRef};
@@ -31,32 +31,48 @@ impl CacheType {
 }
 
 pub struct ConstantPoolCache {
+    /// 常量池所属的类，解析时检查它能否访问引用的类
+    owner: BytesRef,
     cp: ConstantPool,
     cache: Mutex<FxHashMap<usize, CacheType>>,
 }
 
 impl ConstantPoolCache {
-    pub fn new(cp: ConstantPool) -> Self {
+    pub fn new(owner: BytesRef, cp: ConstantPool) -> Self {
         Self {
+            owner,
             cp,
             cache: Mutex::new(FxHashMap::default()),
         }
     }
 
-    pub fn get_field(&self, idx: usize, is_static: bool) -> FieldIdRef {
+    /// 不能访问字段所在的类时抛出 `IllegalAccessError`，返回 None。
+    pub fn get_field(&self, idx: usize, is_static: bool) -> Option<FieldIdRef> {
         let cache = self.cache.lock().unwrap();
         let it = cache.get(&idx);
         match it {
-            Some(it) => it.extract_field(),
+            Some(it) => Some(it.extract_field()),
             None => {
                 drop(cache);
+                let (class_index, _) = constant_pool::get_field_ref(&self.cp, idx);
+                if !self.check_access(class_index) {
+                    return None;
+                }
                 let fid = field::get_field_ref(&self.cp, idx, is_static);
                 self.cache_field(idx, fid.clone());
-                fid
+                Some(fid)
             }
         }
     }
 
+    /// 常量池所属的类能否访问 `class_index` 引用的类，只在第一次解析时检查。
+    fn check_access(&self, class_index: u16) -> bool {
+        match runtime::require_class2(class_index, &self.cp) {
+            Some(cls) => runtime::module::check_access(self.owner.as_slice(), &cls),
+            None => true,
+        }
+    }
+
     fn cache_field(&self, k: usize, v: FieldIdRef) {
         let mut cache = self.cache.lock().unwrap();
         let v = CacheType::Field(v);
@@ -70,6 +86,10 @@ impl ConstantPoolCache {
             Some(it) => Some(it.extract_method()),
             None => {
                 drop(cache);
+                let (_, class_index, _) = constant_pool::get_method_ref(&self.cp, idx);
+                if !self.check_access(class_index) {
+                    return None;
+                }
                 match runtime::method::get_method_ref(&self.cp, idx) {
                     Ok(m) => {
                         self.cache_method(idx, m.clone());
@@ -15,9 +15,17 @@ pub fn new(name: &[u8], msg: Option<String>) -> Oop {
 
     let ex = Oop::new_inst(cls.clone());
 
-    // Always use no-arg constructor to avoid String-related issues in <init> chain
-    let args = vec![ex.clone()];
-    runtime::invoke::invoke_ctor(cls, new_br("()V"), args);
+    match msg {
+        Some(msg) => {
+            let msg = util::oop::new_java_lang_string2(&msg);
+            let args = vec![ex.clone(), msg];
+            runtime::invoke::invoke_ctor(cls, new_br("(Ljava/lang/String;)V"), args);
+        }
+        None => {
+            let args = vec![ex.clone()];
+            runtime::invoke::invoke_ctor(cls, new_br("()V"), args);
+        }
+    }
 
     ex
 }
@@ -1,7 +1,7 @@
 use crate::oop;
 use crate::oop::{Class, Oop};
-use crate::runtime::{self, require_class3};
-use crate::types::JavaThreadRef;
+use crate::runtime::{self, require_class3, thread};
+use crate::types::{ClassRef, JavaThreadRef, MethodIdRef};
 use crate::util;
 use crate::{native, new_br};
 use classfile::consts::{
@@ -9,51 +9,401 @@ use classfile::consts::{
     J_INTERNAL_ERROR, J_IOEXCEPTION, J_METHOD_CTOR, J_NPE, J_OBJECT, J_PRINT_STREAM,
     J_SECURITY_MANAGER, J_SERIALIZABLE, J_STRING, J_SYSTEM, J_THREAD, J_THREAD_GROUP, J_THROWABLE,
 };
+use classfile::flags as acc;
 use std::borrow::BorrowMut;
 use std::sync::Arc;
 
+/// Boot the JDK like HotSpot's `create_vm`: core classes, the initial thread,
+/// then `System.initPhase1` (properties, standard streams, signal handlers),
+/// `initPhase2` (module system) and `initPhase3` (security manager, system
+/// class loader). JDK 8 has `System.initializeSystemClass` instead.
 pub fn initialize_jvm() {
-    // MUST happen before initialize_vm_structs: hack certain classes' `<clinit>`
-    // as native to bypass JDK 9+ APIs (Set.of(), Map.of()) we don't implement.
-    pre_hack_clinit();
-
     initialize_vm_structs();
 
     let thread_cls = oop::class::load_and_init(J_THREAD);
-
-    // Create a minimal Thread instance
-    let init_thread_oop = oop::Oop::new_inst(thread_cls.clone());
+    let init_thread_oop = oop::Oop::new_inst(thread_cls);
 
     let jt = runtime::thread::current_java_thread();
     jt.write()
         .unwrap()
         .set_java_thread_obj(init_thread_oop.clone());
 
-    let _ = oop::class::load_and_init(J_INPUT_STREAM);
-    let _ = oop::class::load_and_init(J_PRINT_STREAM);
+    init_main_thread(&init_thread_oop);
+
+    //reflection reads the current thread when it initializes
+    let _ = oop::class::load_and_init(J_FIELD);
+    let _ = oop::class::load_and_init(J_METHOD_CTOR);
 
     hack_classes();
 
-    // Try System.initializeSystemClass() if it exists (JDK 8)
-    // In JDK 9+ this method doesn't exist and clinit handles initialization
-    if let Some(cls) = require_class3(None, J_SYSTEM) {
-        if let Ok(method) = cls
-            .get_class()
-            .get_static_method(&new_br("initializeSystemClass"), &new_br("()V"))
-        {
-            let mut jc = runtime::invoke::JavaCall::new_with_args(method, vec![]);
-            jc.invoke(None, false);
+    let system = oop::class::load_and_init(J_SYSTEM);
+    if has_static_method(&system, "initPhase1", "()V") {
+        call_static(&system, "initPhase1", "()V", vec![]);
+        initialize_exception_classes();
+
+        let args = vec![Oop::new_int(1), Oop::new_int(1)];
+        let r = call_static(&system, "initPhase2", "(ZZ)I", args);
+        if r.map(|r| r.extract_int()) != Some(0) {
+            //initPhase2 has printed the cause
+            runtime::vm::halt(1);
+        }
+        define_app_modules();
+        runtime::module::start_access_checks();
+        native::java_lang_Class::update_mirror_modules();
+
+        call_static(&system, "initPhase3", "()V", vec![]);
+    } else {
+        call_static(&system, "initializeSystemClass", "()V", vec![]);
+        initialize_exception_classes();
+    }
+}
+
+/// Define the modules resolved from the module path to the boot loader, as
+/// the boot layer would: a `ModuleDescriptor` of what the VM read, then the
+/// reads, exports and opens it declares. They aren't in `ModuleLayer.boot()`.
+fn define_app_modules() {
+    let app_modules = runtime::module::app_modules();
+    if app_modules.is_empty() {
+        return;
+    }
+
+    let modules = oop::class::load_and_init(b"jdk/internal/module/Modules");
+    let mut defined = Vec::with_capacity(app_modules.len());
+    for m in app_modules {
+        let descriptor = new_module_descriptor(&m.descriptor);
+        let uri = file_uri(&m.location);
+        let module = call_static(
+            &modules,
+            "defineModule",
+            "(Ljava/lang/ClassLoader;Ljava/lang/module/ModuleDescriptor;Ljava/net/URI;)Ljava/lang/Module;",
+            vec![Oop::Null, descriptor, uri],
+        )
+        .unwrap();
+        defined.push((&m.descriptor, module));
+    }
+
+    for (d, module) in &defined {
+        for name in runtime::module::reads_of(d) {
+            if let Some(other) = runtime::module::find_module(&name) {
+                call_static(
+                    &modules,
+                    "addReads",
+                    "(Ljava/lang/Module;Ljava/lang/Module;)V",
+                    vec![module.clone(), other],
+                );
+            }
+        }
+
+        //open and automatic modules export and open every package
+        if d.open || d.automatic {
+            continue;
+        }
+        init_exports_and_opens(d, module);
+    }
+}
+
+/// Like `Module.initExportsAndOpens`: fill `openPackages` and
+/// `exportedPackages` of the module and export the packages in the VM. The
+/// `Modules.addExports` methods can't be used, they record the exports with
+/// lambdas.
+fn init_exports_and_opens(d: &runtime::module::ModuleDescriptor, module: &Oop) {
+    use runtime::module::{add_exports, find_module, ExportTarget};
+    let module_cls = oop::class::load_and_init(b"java/lang/Module");
+    let everyone = static_field(&module_cls, "EVERYONE_SET", "Ljava/util/Set;");
+
+    //an export of an open package adds nothing
+    let opened_to_all: Vec<&String> = d
+        .opens
+        .iter()
+        .filter(|(_, to)| to.is_empty())
+        .map(|(pkg, _)| pkg)
+        .collect();
+    let exports = d
+        .exports
+        .iter()
+        .filter(|(pkg, _)| !opened_to_all.contains(&pkg))
+        .collect();
+    let map_cls = oop::class::load_and_init(b"java/util/HashMap");
+    for (field, packages) in [
+        ("openPackages", d.opens.iter().collect::<Vec<_>>()),
+        ("exportedPackages", exports),
+    ] {
+        let map = Oop::new_inst(map_cls.clone());
+        runtime::invoke::invoke_ctor(map_cls.clone(), new_br("()V"), vec![map.clone()]);
+        for (pkg, to) in packages {
+            let targets = if to.is_empty() {
+                add_exports(pkg, ExportTarget::All);
+                everyone.clone()
+            } else {
+                let targets: Vec<Oop> = to.iter().filter_map(|t| find_module(t)).collect();
+                if targets.is_empty() {
+                    continue;
+                }
+                for target in &targets {
+                    add_exports(pkg, ExportTarget::Module(target));
+                }
+                new_set(targets)
+            };
+            call_method(
+                &map_cls,
+                "put",
+                "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+                vec![
+                    map.clone(),
+                    new_java_string(&pkg.replace('/', ".")),
+                    targets,
+                ],
+            );
+        }
+        let cls = module_cls.get_class();
+        let fid = cls.get_field_id(&new_br(field), &new_br("Ljava/util/Map;"), false);
+        Class::put_field_value2(module.extract_ref(), fid.offset, map);
+    }
+}
+
+/// Build a `java.lang.module.ModuleDescriptor` with its `Builder`.
+fn new_module_descriptor(d: &runtime::module::ModuleDescriptor) -> Oop {
+    const BUILDER: &str = "Ljava/lang/module/ModuleDescriptor$Builder;";
+    let builder_cls = oop::class::load_and_init(b"java/lang/module/ModuleDescriptor$Builder");
+
+    //new Builder(name, false, modifiers): the strict builder of
+    //ModuleDescriptor.newModule checks names with lambdas, which need method
+    //handles; the descriptor was checked when it was parsed, as ModuleInfo does
+    let modifier = if d.automatic {
+        Some("AUTOMATIC")
+    } else if d.open {
+        Some("OPEN")
+    } else {
+        None
+    };
+    let modifiers = modifier
+        .map(|name| {
+            let cls = oop::class::load_and_init(b"java/lang/module/ModuleDescriptor$Modifier");
+            static_field(&cls, name, "Ljava/lang/module/ModuleDescriptor$Modifier;")
+        })
+        .into_iter()
+        .collect();
+    let builder = Oop::new_inst(builder_cls.clone());
+    runtime::invoke::invoke_ctor(
+        builder_cls.clone(),
+        new_br("(Ljava/lang/String;ZLjava/util/Set;)V"),
+        vec![
+            builder.clone(),
+            new_java_string(&d.name),
+            Oop::new_int(0),
+            new_set(modifiers),
+        ],
+    );
+    let call = |name: &str, params: &str, args: Vec<Oop>| {
+        let mut all = vec![builder.clone()];
+        all.extend(args);
+        call_method(&builder_cls, name, &format!("({}){}", params, BUILDER), all);
+    };
+    let dotted = |s: &String| new_java_string(&s.replace('/', "."));
+    let strings = |v: &Vec<String>| v.iter().map(dotted).collect::<Vec<_>>();
+
+    //an automatic module has no requires, exports, opens or uses
+    if !d.automatic {
+        for (name, flags) in &d.requires {
+            let modifiers = new_set(requires_modifiers(*flags));
+            call(
+                "requires",
+                "Ljava/util/Set;Ljava/lang/String;",
+                vec![modifiers, new_java_string(name)],
+            );
+        }
+        for (pkg, to) in &d.exports {
+            match to.is_empty() {
+                true => call("exports", "Ljava/lang/String;", vec![dotted(pkg)]),
+                false => call(
+                    "exports",
+                    "Ljava/lang/String;Ljava/util/Set;",
+                    vec![dotted(pkg), new_set(strings(to))],
+                ),
+            }
         }
+        //an open module opens every package already
+        for (pkg, to) in d.opens.iter().filter(|_| !d.open) {
+            match to.is_empty() {
+                true => call("opens", "Ljava/lang/String;", vec![dotted(pkg)]),
+                false => call(
+                    "opens",
+                    "Ljava/lang/String;Ljava/util/Set;",
+                    vec![dotted(pkg), new_set(strings(to))],
+                ),
+            }
+        }
+        for service in &d.uses {
+            call("uses", "Ljava/lang/String;", vec![dotted(service)]);
+        }
+    }
+    for (service, providers) in &d.provides {
+        let list_cls = oop::class::load_and_init(b"java/util/List");
+        let providers = call_static(
+            &list_cls,
+            "of",
+            "([Ljava/lang/Object;)Ljava/util/List;",
+            vec![new_object_array(strings(providers))],
+        )
+        .unwrap();
+        call(
+            "provides",
+            "Ljava/lang/String;Ljava/util/List;",
+            vec![dotted(service), providers],
+        );
+    }
+    call(
+        "packages",
+        "Ljava/util/Set;",
+        vec![new_set(strings(&d.packages))],
+    );
+    if let Some(version) = &d.version {
+        call(
+            "version",
+            "Ljava/lang/String;",
+            vec![new_java_string(version)],
+        );
     }
+    if let Some(main_class) = &d.main_class {
+        call("mainClass", "Ljava/lang/String;", vec![dotted(main_class)]);
+    }
+
+    call_method(
+        &builder_cls,
+        "build",
+        "()Ljava/lang/module/ModuleDescriptor;",
+        vec![builder],
+    )
+    .unwrap()
+}
 
-    // JDK 9+: System.out/in/err are not initialized by clinit alone.
-    // Manually create PrintStream instances and set them as static fields.
-    setup_system_streams();
+/// The `ModuleDescriptor.Requires.Modifier`s of the `requires_flags`.
+fn requires_modifiers(flags: u16) -> Vec<Oop> {
+    const MODIFIER: &str = "Ljava/lang/module/ModuleDescriptor$Requires$Modifier;";
+    let cls = oop::class::load_and_init(b"java/lang/module/ModuleDescriptor$Requires$Modifier");
+    [
+        (acc::ACC_TRANSITIVE, "TRANSITIVE"),
+        (acc::ACC_STATIC_PHASE, "STATIC"),
+        (acc::ACC_SYNTHETIC, "SYNTHETIC"),
+        (acc::ACC_MANDATED, "MANDATED"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags & flag != 0)
+    .map(|(_, name)| static_field(&cls, name, MODIFIER))
+    .collect()
+}
 
-    //setup security (best effort) — skipped: Provider.<clinit> uses invokedynamic
-    // lambdas (LambdaMetafactory) that require full java.lang.invoke infrastructure.
-    // let _ = oop::class::load_and_init(b"sun/security/provider/Sun");
-    // let _ = oop::class::load_and_init(b"sun/security/rsa/SunRsaSign");
+fn static_field(cls: &ClassRef, name: &str, desc: &str) -> Oop {
+    let cls = cls.get_class();
+    let fid = cls.get_field_id(&new_br(name), &new_br(desc), true);
+    cls.get_static_field_value(fid)
+}
+
+fn new_java_string(s: &str) -> Oop {
+    util::oop::new_java_lang_string2(s)
+}
+
+fn new_object_array(elements: Vec<Oop>) -> Oop {
+    let cls = require_class3(None, b"[Ljava/lang/Object;").unwrap();
+    Oop::new_ref_ary2(cls, elements)
+}
+
+//Set.of
+fn new_set(elements: Vec<Oop>) -> Oop {
+    let set_cls = oop::class::load_and_init(b"java/util/Set");
+    call_static(
+        &set_cls,
+        "of",
+        "([Ljava/lang/Object;)Ljava/util/Set;",
+        vec![new_object_array(elements)],
+    )
+    .unwrap()
+}
+
+//new File(path).toURI()
+fn file_uri(path: &str) -> Oop {
+    let file_cls = oop::class::load_and_init(b"java/io/File");
+    let file = Oop::new_inst(file_cls.clone());
+    runtime::invoke::invoke_ctor(
+        file_cls.clone(),
+        new_br("(Ljava/lang/String;)V"),
+        vec![file.clone(), new_java_string(path)],
+    );
+    call_method(&file_cls, "toURI", "()Ljava/net/URI;", vec![file]).unwrap()
+}
+
+fn has_static_method(cls: &ClassRef, name: &str, desc: &str) -> bool {
+    cls.get_class()
+        .get_static_method(&new_br(name), &new_br(desc))
+        .is_ok()
+}
+
+/// Run a static method of the boot sequence; an exception leaves the VM
+/// unusable, so it is reported and the VM exits.
+fn call_static(cls: &ClassRef, name: &str, desc: &str, args: Vec<Oop>) -> Option<Oop> {
+    let mir = cls
+        .get_class()
+        .get_static_method(&new_br(name), &new_br(desc))
+        .unwrap();
+    call(mir, name, desc, args)
+}
+
+/// Like `call_static`, for an instance method of `cls`; the receiver is the
+/// first argument.
+fn call_method(cls: &ClassRef, name: &str, desc: &str, args: Vec<Oop>) -> Option<Oop> {
+    let mir = cls
+        .get_class()
+        .get_class_method(&new_br(name), &new_br(desc))
+        .unwrap();
+    call(mir, name, desc, args)
+}
+
+fn call(mir: MethodIdRef, name: &str, desc: &str, args: Vec<Oop>) -> Option<Oop> {
+    let mut jc = runtime::invoke::JavaCall::new_with_args(mir, args);
+    let area = runtime::DataArea::new(1);
+    jc.invoke(Some(&area), true);
+
+    if thread::is_meet_ex() {
+        let jt = runtime::thread::current_java_thread();
+        let ex = jt.write().unwrap().take_ex().unwrap();
+        eprintln!(
+            "Error occurred during initialization of VM\n{}: {}",
+            name,
+            describe_exception(&ex)
+        );
+        runtime::vm::halt(1);
+    }
+
+    if desc.ends_with(")V") {
+        None
+    } else {
+        Some(area.stack.borrow_mut().pop_ref())
+    }
+}
+
+/// "java.lang.Error: message" of a pending exception.
+fn describe_exception(ex: &Oop) -> String {
+    let cls = {
+        let rf = ex.extract_ref();
+        oop::with_heap(|heap| {
+            let desc = heap.get(rf);
+            let guard = desc.read().unwrap();
+            guard.v.extract_inst().class.clone()
+        })
+    };
+    let cls = cls.get_class();
+    let fid = cls.get_field_id(
+        &new_br("detailMessage"),
+        &new_br("Ljava/lang/String;"),
+        false,
+    );
+    let msg = Class::get_field_value(ex.extract_ref(), fid);
+    let name = String::from_utf8_lossy(cls.name.as_slice()).replace('/', ".");
+    if msg.is_null() {
+        name
+    } else {
+        format!("{}: {}", name, Oop::java_lang_string(msg.extract_ref()))
+    }
 }
 
 fn initialize_vm_structs() {
@@ -68,6 +418,9 @@ fn initialize_vm_structs() {
         // JDK 9+: value is byte[] ([B); pre-JDK 9: char[] ([C)
         let fir = cls.get_field_id(&new_br("value"), &new_br("[B"), false);
         util::oop::set_java_lang_string_value_offset(fir.offset);
+        if let Ok(fir) = cls.get_field_id_safe(&new_br("coder"), &new_br("B"), false) {
+            util::oop::set_java_lang_string_coder_offset(fir.offset);
+        }
     }
 
     let integer_cls = oop::class::load_and_init(b"java/lang/Integer");
@@ -84,139 +437,192 @@ fn initialize_vm_structs() {
     let _ = oop::class::load_and_init(J_CLASS_NOT_FOUND);
     let _ = oop::class::load_and_init(J_INTERNAL_ERROR);
     let _ = oop::class::load_and_init(J_IOEXCEPTION);
-    let _ = oop::class::load_and_init(J_FIELD);
-    let _ = oop::class::load_and_init(J_METHOD_CTOR);
     let _ = oop::class::load_and_init(J_THROWABLE);
-}
 
-fn hack_classes() {
-    let charset_cls = oop::class::load_and_init(b"java/nio/charset/Charset");
-
-    let charset_cls = oop::class::load_and_init(b"java/nio/charset/Charset");
-    let ascii_charset_cls = oop::class::load_and_init(b"sun/nio/cs/US_ASCII");
-
-    let ascii_inst = oop::Oop::new_inst(ascii_charset_cls.clone());
-    let args = vec![ascii_inst.clone()];
-    runtime::invoke::invoke_ctor(ascii_charset_cls, new_br("()V"), args);
+    init_unsafe_constants();
+}
 
-    {
-        let cls = charset_cls.get_class();
-        let id = cls.get_field_id(
-            &new_br("defaultCharset"),
-            &new_br("Ljava/nio/charset/Charset;"),
-            true,
-        );
-        cls.put_static_field_value(id, ascii_inst);
-    }
+/// JDK 14+ `jdk.internal.misc.UnsafeConstants` is filled in by the VM after
+/// its `<clinit>` zeroes the fields; `Unsafe` reads it during its own
+/// initialization, so this runs before anything touches `Unsafe`.
+fn init_unsafe_constants() {
+    let cls = match require_class3(None, b"jdk/internal/misc/UnsafeConstants") {
+        Some(cls) => cls,
+        None => return,
+    };
+    oop::class::init_class(&cls);
+    oop::class::init_class_fully(&cls);
 
-    let encoder = oop::class::load_and_init(b"sun/nio/cs/StreamEncoder");
-    {
-        let cls = encoder.get_class();
-        cls.hack_as_native(b"forOutputStreamWriter", b"(Ljava/io/OutputStream;Ljava/lang/Object;Ljava/lang/String;)Lsun/nio/cs/StreamEncoder;");
+    let values = [
+        ("ADDRESS_SIZE0", "I", std::mem::size_of::<usize>() as i32),
+        ("PAGE_SIZE", "I", 4 * 1024),
+        ("BIG_ENDIAN", "Z", cfg!(target_endian = "big") as i32),
+        ("UNALIGNED_ACCESS", "Z", 1),
+        ("DATA_CACHE_LINE_FLUSH_SIZE", "I", 0),
+    ];
+    let cls = cls.get_class();
+    for (name, desc, v) in values {
+        let id = cls.get_field_id(&new_br(name), &new_br(desc), true);
+        cls.put_static_field_value(id, Oop::new_int(v));
     }
+}
 
-    let system = oop::class::load_and_init(b"java/lang/System");
-
+fn hack_classes() {
+    let system = oop::class::load_and_init(J_SYSTEM);
     {
         let cls = system.get_class();
         cls.hack_as_native(b"load", b"(Ljava/lang/String;)V");
         cls.hack_as_native(b"loadLibrary", b"(Ljava/lang/String;)V");
-        // hack System.getProperty as native to bypass props access
-        // (props static field is not properly initialized in JDK 9+)
-        cls.hack_as_native(b"getProperty", b"(Ljava/lang/String;)Ljava/lang/String;");
-        cls.hack_as_native(
-            b"setProperty",
-            b"(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/String;",
-        );
-        cls.hack_as_native(b"clearProperty", b"(Ljava/lang/String;)Ljava/lang/String;");
-        cls.hack_as_native(b"getSecurityManager", b"()Ljava/lang/SecurityManager;");
-        cls.hack_as_native(b"setSecurityManager", b"(Ljava/lang/SecurityManager;)V");
     }
 
-    // JDK 9+: fillInStackTrace() delegates to fillInStackTrace(int) internally.
-    // Make () variant native to bypass JDK bytecode that causes NPE cascade.
-    let throwable = oop::class::load_and_init(b"java/lang/Throwable");
+    // JDK 9+: getComponentType() reads the componentType field, the VM
+    // doesn't fill it in, the component mirror is looked up instead.
+    let class_cls = oop::class::load_and_init(J_CLASS);
     {
-        let cls = throwable.get_class();
-        cls.hack_as_native(b"fillInStackTrace", b"()Ljava/lang/Throwable;");
+        let cls = class_cls.get_class();
+        cls.hack_as_native(b"getComponentType", b"()Ljava/lang/Class;");
     }
 
-    // JDK 9+: dispatchUncaughtException calls threadState/isTerminated which aren't implemented.
-    let thread_cls = oop::class::load_and_init(b"java/lang/Thread");
-    {
-        let cls = thread_cls.get_class();
-        cls.hack_as_native(b"dispatchUncaughtException", b"(Ljava/lang/Throwable;)V");
+    // Classes and resources of the built-in loaders come from the VM.
+    // `loadClassOrNull` and `findClass` would read the bytes and define the
+    // class with `defineClass1/2` into the loader's own namespace, but the
+    // VM keeps one dictionary, filled by the bootstrap loader, and has no
+    // per-loader namespaces, so they look the class up there. Loaders of
+    // the application's own still run their Java `loadClass` (see
+    // `Class.forName0`) and get here through parent delegation. For
+    // resources, the JDK's module readers, URLClassPath and JarFile need
+    // NIO and zip natives, so the jar and jrt connections read through the
+    // class path manager as well.
+    let methods: [(&str, &str, &str); 12] = [
+        (
+            "jdk/internal/loader/BuiltinClassLoader",
+            "loadClassOrNull",
+            "(Ljava/lang/String;Z)Ljava/lang/Class;",
+        ),
+        (
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findClass",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/Class;",
+        ),
+        (
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findResource",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/net/URL;",
+        ),
+        (
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findResourceAsStream",
+            "(Ljava/lang/String;Ljava/lang/String;)Ljava/io/InputStream;",
+        ),
+        (
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findResource",
+            "(Ljava/lang/String;)Ljava/net/URL;",
+        ),
+        (
+            "jdk/internal/loader/BuiltinClassLoader",
+            "findResources",
+            "(Ljava/lang/String;)Ljava/util/Enumeration;",
+        ),
+        (
+            "sun/net/www/protocol/jar/JarURLConnection",
+            "getInputStream",
+            "()Ljava/io/InputStream;",
+        ),
+        (
+            "sun/net/www/protocol/jrt/JavaRuntimeURLConnection",
+            "getInputStream",
+            "()Ljava/io/InputStream;",
+        ),
+        // JDK 8
+        (
+            "java/lang/ClassLoader",
+            "getBootstrapResource",
+            "(Ljava/lang/String;)Ljava/net/URL;",
+        ),
+        (
+            "java/lang/ClassLoader",
+            "getBootstrapResources",
+            "(Ljava/lang/String;)Ljava/util/Enumeration;",
+        ),
+        (
+            "sun/misc/URLClassPath",
+            "findResource",
+            "(Ljava/lang/String;Z)Ljava/net/URL;",
+        ),
+        (
+            "sun/misc/URLClassPath",
+            "findResources",
+            "(Ljava/lang/String;Z)Ljava/util/Enumeration;",
+        ),
+    ];
+    for (cls, name, desc) in methods {
+        hack_if_present(cls, name, desc);
     }
 }
 
-/// Manually create a dummy System.out PrintStream and hack PrintStream methods as native.
-/// PrintStream's Java bytecode accesses fields in FilterOutputStream which aren't set up.
-/// By making println/writeln native, we bypass the bytecode entirely.
-fn setup_system_streams() {
-    // Load PrintStream without initializing (avoids clinit which needs IO infrastructure)
-    let ps_cls = runtime::require_class3(None, b"java/io/PrintStream").unwrap();
-
-    // Hack PrintStream methods as native to bypass bytecode that accesses FilterOutputStream fields
-    {
-        let cls = ps_cls.get_class();
-        // println variants
-        cls.hack_as_native(b"println", b"(I)V");
-        cls.hack_as_native(b"println", b"(J)V");
-        cls.hack_as_native(b"println", b"(F)V");
-        cls.hack_as_native(b"println", b"(D)V");
-        cls.hack_as_native(b"println", b"(Z)V");
-        cls.hack_as_native(b"println", b"(C)V");
-        cls.hack_as_native(b"println", b"(Ljava/lang/String;)V");
-        cls.hack_as_native(b"println", b"(Ljava/lang/Object;)V");
-        cls.hack_as_native(b"println", b"()V");
-        // print variants
-        cls.hack_as_native(b"print", b"(I)V");
-        cls.hack_as_native(b"print", b"(Ljava/lang/String;)V");
-        cls.hack_as_native(b"print", b"(Ljava/lang/Object;)V");
-        // Internal methods that access out field
-        cls.hack_as_native(b"write", b"(I)V");
-        cls.hack_as_native(b"write", b"([B)V");
-        cls.hack_as_native(b"write", b"([BII)V");
-        cls.hack_as_native(b"flush", b"()V");
-        cls.hack_as_native(b"close", b"()V");
-    }
-
-    // Create instance (clinit won't run until first proper use)
-    let ps_out = Oop::new_inst(ps_cls.clone());
-
-    // Set System.out
-    let system = oop::class::load_and_init(b"java/lang/System");
-    {
-        let cls = system.get_class();
-        let fid = cls.get_field_id(&new_br("out"), &new_br("Ljava/io/PrintStream;"), true);
-        cls.put_static_field_value(fid, ps_out.clone());
+/// `hack_as_native` for a method only some JDK versions have.
+fn hack_if_present(cls: &str, name: &str, desc: &str) {
+    if let Some(cls) = require_class3(None, cls.as_bytes()) {
+        let cls = cls.get_class();
+        if cls
+            .get_this_class_method(&new_br(name), &new_br(desc))
+            .is_ok()
+        {
+            cls.hack_as_native(name.as_bytes(), desc.as_bytes());
+        }
     }
+}
 
-    // Set System.err (same PrintStream)
-    {
-        let cls = system.get_class();
-        let fid = cls.get_field_id(&new_br("err"), &new_br("Ljava/io/PrintStream;"), true);
-        cls.put_static_field_value(fid, ps_out.clone());
+/// Exceptions the VM throws itself, initialized once the system classes are
+/// up, as HotSpot does after phase 1.
+fn initialize_exception_classes() {
+    let classes: [&[u8]; 8] = [
+        b"java/lang/OutOfMemoryError",
+        b"java/lang/NullPointerException",
+        b"java/lang/ClassCastException",
+        b"java/lang/ArrayStoreException",
+        b"java/lang/ArithmeticException",
+        b"java/lang/StackOverflowError",
+        b"java/lang/IllegalMonitorStateException",
+        b"java/lang/IllegalArgumentException",
+    ];
+    for name in classes {
+        let _ = oop::class::load_and_init(name);
     }
 }
 
-/// Load a class WITHOUT initializing it, hack its `<clinit>` as native,
-/// then let normal initialization proceed. Must be called BEFORE any other
-/// class transitively loads the target class.
-fn hack_clinit_as_native(class_name: &[u8]) {
-    let Some(cls_ref) = runtime::require_class3(None, class_name) else {
-        return;
-    };
-    let cls = cls_ref.get_class();
-    cls.hack_as_native(b"<clinit>", b"()V");
-    oop::class::init_class(&cls_ref);
-    oop::class::init_class_fully(&cls_ref);
-}
-
-/// Pre-hack classes whose `<clinit>` calls JDK 9+ APIs we don't implement.
-fn pre_hack_clinit() {
-    // ObjectStreamClass.<clinit> calls Set.of()/Map.of()
-    hack_clinit_as_native(b"java/io/ObjectStreamClass");
-    // ReflectionFactory.<clinit> calls Set.of()
-    hack_clinit_as_native(b"jdk/internal/reflect/ReflectionFactory");
+/// Create the "system" thread group and its "main" child, then construct
+/// the main thread in "main". `Thread.<init>` of the threads it creates reads
+/// the group and priority of the current thread.
+fn init_main_thread(thread_oop: &Oop) {
+    let group_cls = oop::class::load_and_init(J_THREAD_GROUP);
+
+    let system = Oop::new_inst(group_cls.clone());
+    runtime::invoke::invoke_ctor(group_cls.clone(), new_br("()V"), vec![system.clone()]);
+
+    let main = Oop::new_inst(group_cls.clone());
+    let name = util::oop::new_java_lang_string2("main");
+    runtime::invoke::invoke_ctor(
+        group_cls.clone(),
+        new_br("(Ljava/lang/ThreadGroup;Ljava/lang/String;)V"),
+        vec![main.clone(), system, name.clone()],
+    );
+
+    //the ctor copies the priority of the current thread, this one
+    thread::set_thread_field(thread_oop, "priority", "I", Oop::new_int(5));
+    let thread_cls = require_class3(None, J_THREAD).unwrap();
+    runtime::invoke::invoke_ctor(
+        thread_cls,
+        new_br("(Ljava/lang/ThreadGroup;Ljava/lang/String;)V"),
+        vec![thread_oop.clone(), main, name],
+    );
+
+    //the main thread isn't started, 'initPhase1' adds it to its group
+
+    let jt = runtime::thread::current_java_thread();
+    let jt = jt.read().unwrap();
+    //JDK 17+ 'isAlive' is 'eetop != 0'
+    thread::set_thread_field(thread_oop, "eetop", "J", Oop::new_long(jt.eetop));
+    jt.set_status(thread::status::RUNNABLE);
 }
@@ -103,7 +103,7 @@ impl<'a> Interp<'a> {
                 Some("divide by zero".to_string()),
             );
         } else {
-            stack.push_int(v1 / v2);
+            stack.push_int(v1.wrapping_div(v2));
         }
     }
     #[inline]
@@ -118,7 +118,7 @@ impl<'a> Interp<'a> {
                 Some("divide by zero".to_string()),
             );
         } else {
-            stack.push_long(v1 / v2);
+            stack.push_long(v1.wrapping_div(v2));
         }
     }
     #[inline]
@@ -164,7 +164,7 @@ impl<'a> Interp<'a> {
                 Some("divide by zero".to_string()),
             );
         } else {
-            stack.push_int(v1 - (v1 / v2) * v2);
+            stack.push_int(v1.wrapping_rem(v2));
         }
     }
     #[inline]
@@ -179,7 +179,7 @@ impl<'a> Interp<'a> {
                 Some("divide by zero".to_string()),
             );
         } else {
-            stack.push_long(v1 - (v1 / v2) * v2);
+            stack.push_long(v1.wrapping_rem(v2));
         }
     }
     #[inline]
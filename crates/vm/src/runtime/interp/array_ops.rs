@@ -465,6 +465,9 @@ impl<'a> Interp<'a> {
 
 fn check_bounds(len: usize, pos: i32) {
     if pos < 0 || pos as usize >= len {
-        exception::meet_ex(cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS, None);
+        exception::meet_ex(
+            cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS,
+            Some(format!("length is {}, but index is {}", len, pos)),
+        );
     }
 }
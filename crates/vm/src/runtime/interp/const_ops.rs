@@ -38,6 +38,10 @@ impl<'a> Interp<'a> {
                 let name = unsafe { std::str::from_utf8_unchecked(name.as_slice()) };
                 let cl = { self.frame.class.get_class().class_loader };
                 let class = runtime::require_class3(cl, name.as_bytes()).unwrap();
+                let from = self.frame.class.get_class().name.clone();
+                if !runtime::module::check_access(from.as_slice(), &class) {
+                    return;
+                }
                 oop::class::init_class(&class);
                 oop::class::init_class_fully(&class);
                 let mirror = { class.get_class().get_mirror() };
@@ -1,5 +1,6 @@
 use super::Interp;
 use crate::oop::Oop;
+use crate::runtime::thread::safepoint;
 use std::sync::atomic::Ordering;
 
 impl<'a> Interp<'a> {
@@ -14,6 +15,10 @@ impl<'a> Interp<'a> {
     pub fn goto_by_offset_with_occupied(&self, branch: i32, occupied: i32) {
         self.goto_by_offset(branch);
         self.goto_by_offset(-(occupied - 1));
+        //back-edge, safepoint poll
+        if branch <= 0 {
+            safepoint::poll();
+        }
     }
 
     pub fn goto_by_offset_hardcoded(&self, occupied: i32) {
@@ -26,8 +31,13 @@ impl<'a> Interp<'a> {
     }
 
     pub fn goto_abs_with_occupied(&self, pc: i32, occupied: i32) {
+        let from = self.frame.pc.load(Ordering::Relaxed);
         self.goto_abs(pc);
         self.goto_by_offset(-(occupied - 1));
+        //back-edge, safepoint poll
+        if self.frame.pc.load(Ordering::Relaxed) < from {
+            safepoint::poll();
+        }
     }
 
     #[inline]
@@ -173,7 +183,7 @@ impl<'a> Interp<'a> {
         let mut stack = self.frame.area.stack.borrow_mut();
         let v2 = stack.pop_ref();
         let v1 = stack.pop_ref();
-        if crate::oop::Oop::is_eq(v1.extract_ref(), v2.extract_ref()) {
+        if is_same_ref(&v1, &v2) {
             drop(stack);
             self.goto_by_offset_hardcoded(2);
         } else {
@@ -185,7 +195,7 @@ impl<'a> Interp<'a> {
         let mut stack = self.frame.area.stack.borrow_mut();
         let v2 = stack.pop_ref();
         let v1 = stack.pop_ref();
-        if !crate::oop::Oop::is_eq(v1.extract_ref(), v2.extract_ref()) {
+        if !is_same_ref(&v1, &v2) {
             drop(stack);
             self.goto_by_offset_hardcoded(2);
         } else {
@@ -335,3 +345,13 @@ impl<'a> Interp<'a> {
         panic!("Use of deprecated instruction jsr_w, please check your Java compiler");
     }
 }
+
+//null is only equal to null
+fn is_same_ref(v1: &crate::oop::Oop, v2: &crate::oop::Oop) -> bool {
+    use crate::oop::Oop;
+    match (v1, v2) {
+        (Oop::Null, Oop::Null) => true,
+        (Oop::Ref(l), Oop::Ref(r)) => Oop::is_eq(*l, *r),
+        _ => false,
+    }
+}
@@ -25,7 +25,11 @@ impl<'a> Interp<'a> {
 
     pub fn get_field_helper(&self, receiver: Oop, idx: usize, is_static: bool) {
         let cls = self.frame.class.get_class();
-        let fir = cls.get_cp_field(idx, is_static).unwrap();
+        let fir = match cls.get_cp_field(idx, is_static) {
+            Some(fir) => fir,
+            //IllegalAccessError is pending
+            None => return,
+        };
         debug_assert_eq!(fir.field.is_static(), is_static);
         let value_type = fir.field.value_type;
         let v = if is_static {
@@ -35,26 +39,36 @@ impl<'a> Interp<'a> {
             let rf = receiver.extract_ref();
             crate::oop::Class::get_field_value2(rf, fir.offset)
         };
+        fir.after_load();
         let with_nop = matches!(value_type, ValueType::DOUBLE | ValueType::LONG);
         self.frame.area.stack.borrow_mut().push_ref(v, with_nop);
     }
 
     pub fn put_field_helper(&self, idx: usize, is_static: bool) {
         let cls = self.frame.class.get_class();
-        let fir = cls.get_cp_field(idx, is_static).unwrap();
+        let fir = match cls.get_cp_field(idx, is_static) {
+            Some(fir) => fir,
+            //IllegalAccessError is pending
+            None => return,
+        };
         debug_assert_eq!(fir.field.is_static(), is_static);
         let value_type = fir.field.value_type;
         let v = self.pop_value(value_type);
+        fir.before_store();
         if is_static {
             let mut class = fir.field.class.get_mut_class();
             class.put_static_field_value(fir.clone(), v);
         } else {
             let receiver = { self.frame.area.stack.borrow_mut().pop_ref() };
             match receiver {
-                Oop::Null => exception::meet_ex(cls_const::J_NPE, None),
+                Oop::Null => {
+                    exception::meet_ex(cls_const::J_NPE, None);
+                    return;
+                }
                 _ => crate::oop::Class::put_field_value2(receiver.extract_ref(), fir.offset, v),
             }
         }
+        fir.after_store();
     }
 
     #[inline]
@@ -97,6 +111,13 @@ impl<'a> Interp<'a> {
         let target_cls = super::require_class2(cp_idx as u16, &self.cp).unwrap();
         let obj_rf = self.pop_value(ValueType::OBJECT);
         let obj_rf_clone = obj_rf.clone();
+        //like HotSpot, a null reference passes without resolving the class
+        if !obj_rf.is_null() {
+            let from = self.frame.class.get_class().name.clone();
+            if !crate::runtime::module::check_access(from.as_slice(), &target_cls) {
+                return;
+            }
+        }
         match obj_rf {
             Oop::Null => {
                 let mut stack = self.frame.area.stack.borrow_mut();
@@ -143,11 +164,11 @@ impl<'a> Interp<'a> {
                                 self.op_instance_of(r);
                             }
                         }
-                        oop::RefKind::Mirror(mirror) => {
-                            let obj_cls = mirror.target.clone().unwrap();
-                            let target_name = target_cls.get_class().name.as_slice();
-                            let r = target_name == b"java/lang/Class"
-                                || cmp::instance_of(obj_cls.clone(), target_cls.clone());
+                        //a mirror, of a primitive type too, is a java/lang/Class
+                        oop::RefKind::Mirror(_) => {
+                            use crate::runtime::require_class3;
+                            let obj_cls = require_class3(None, cls_const::J_CLASS).unwrap();
+                            let r = cmp::instance_of(obj_cls.clone(), target_cls.clone());
                             if is_cast {
                                 if !r {
                                     self.op_check_cast(r, obj_cls, target_cls);
@@ -0,0 +1,600 @@
+//! `LambdaMetafactory.metafactory` call sites.
+//!
+//! Like HotSpot's `InnerClassLambdaMetafactory`, each call site gets a
+//! class spun from bytecode: it implements the functional interface, keeps
+//! the captured arguments in fields, and its interface method loads them
+//! followed by its own arguments, adapts them to the implementation method
+//! (casts, boxing, unboxing, widening), invokes it and adapts the result.
+
+use crate::native;
+use crate::oop::{self, Class, Oop};
+use crate::runtime::{self, DataArea};
+use crate::types::ClassRef;
+use class_parser::parse_class;
+use classfile::attributes::BootstrapMethod;
+use classfile::{constant_pool, BytesRef, ConstantPool, ConstantPoolType};
+use rustc_hash::FxHashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::trace;
+
+// method handle kinds (JVMS 5.4.3.5)
+const REF_INVOKE_VIRTUAL: u8 = 5;
+const REF_INVOKE_STATIC: u8 = 6;
+const REF_INVOKE_SPECIAL: u8 = 7;
+const REF_NEW_INVOKE_SPECIAL: u8 = 8;
+const REF_INVOKE_INTERFACE: u8 = 9;
+
+const ACC_PUBLIC: u16 = 0x0001;
+const ACC_PRIVATE: u16 = 0x0002;
+const ACC_FINAL: u16 = 0x0010;
+const ACC_SUPER: u16 = 0x0020;
+const ACC_SYNTHETIC: u16 = 0x1000;
+
+const TAG_UTF8: u8 = 1;
+const TAG_CLASS: u8 = 7;
+const TAG_FIELD_REF: u8 = 9;
+const TAG_METHOD_REF: u8 = 10;
+const TAG_INTERFACE_METHOD_REF: u8 = 11;
+const TAG_NAME_AND_TYPE: u8 = 12;
+
+const ALOAD_0: u8 = 0x2a;
+const POP: u8 = 0x57;
+const POP2: u8 = 0x58;
+const DUP: u8 = 0x59;
+const I2L: u8 = 0x85;
+const I2F: u8 = 0x86;
+const I2D: u8 = 0x87;
+const L2F: u8 = 0x89;
+const L2D: u8 = 0x8a;
+const F2D: u8 = 0x8d;
+const RETURN: u8 = 0xb1;
+const GETFIELD: u8 = 0xb4;
+const INVOKEVIRTUAL: u8 = 0xb6;
+const INVOKESPECIAL: u8 = 0xb7;
+const INVOKESTATIC: u8 = 0xb8;
+const INVOKEINTERFACE: u8 = 0xb9;
+const NEW: u8 = 0xbb;
+const CHECKCAST: u8 = 0xc0;
+
+/// The lambda class of each call site: (caller class, invokedynamic cp index)
+static CALL_SITES: OnceLock<Mutex<FxHashMap<(BytesRef, u16), ClassRef>>> = OnceLock::new();
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Link the call site `cp_idx` of `caller` and push the lambda object, with
+/// the captured arguments popped from the operand stack of `area`.
+pub fn metafactory(
+    area: &DataArea,
+    caller: &ClassRef,
+    cp: &ConstantPool,
+    cp_idx: u16,
+    bsm: &BootstrapMethod,
+    name: &[u8],
+    desc: &[u8],
+) {
+    let key = (caller.get_class().name.clone(), cp_idx);
+    let sites = CALL_SITES.get_or_init(|| Mutex::new(FxHashMap::default()));
+    let cached = sites.lock().unwrap().get(&key).cloned();
+    let class = match cached {
+        Some(class) => class,
+        None => {
+            let class = spin_class(caller, cp, bsm, name, desc);
+            sites
+                .lock()
+                .unwrap()
+                .entry(key)
+                .or_insert(class)
+                .clone()
+        }
+    };
+
+    let (captured, _) = split_desc(desc);
+    let mut values = Vec::with_capacity(captured.len());
+    {
+        let mut stack = area.stack.borrow_mut();
+        for ty in captured.iter().rev() {
+            let v = match ty[0] {
+                b'J' => Oop::Long(stack.pop_long()),
+                b'F' => Oop::Float(stack.pop_float()),
+                b'D' => Oop::Double(stack.pop_double()),
+                b'L' | b'[' => stack.pop_ref(),
+                _ => Oop::Int(stack.pop_int()),
+            };
+            values.push(v);
+        }
+    }
+    values.reverse();
+
+    let lambda = Oop::new_inst(class.clone());
+    let slot_id = lambda.extract_ref();
+    let cls = class.get_class();
+    for (i, (ty, v)) in captured.iter().zip(values).enumerate() {
+        let name = Arc::new(format!("arg${}", i + 1).into_bytes());
+        let fid = cls.get_field_id(&name, &Arc::new(ty.to_vec()), false);
+        Class::put_field_value2(slot_id, fid.offset, v);
+    }
+    area.stack.borrow_mut().push_ref(lambda, false);
+}
+
+fn spin_class(
+    caller: &ClassRef,
+    cp: &ConstantPool,
+    bsm: &BootstrapMethod,
+    name: &[u8],
+    desc: &[u8],
+) -> ClassRef {
+    // samMethodType, implMethod, instantiatedMethodType
+    let method_type = |i: usize| match cp.get(bsm.args[i] as usize) {
+        Some(ConstantPoolType::MethodType { desc_index }) => {
+            constant_pool::get_utf8(cp, *desc_index as usize).clone()
+        }
+        _ => unreachable!("metafactory argument {} is not a MethodType", i),
+    };
+    let sam_desc = method_type(0);
+    let instantiated = method_type(2);
+    let (ref_kind, ref_index) = constant_pool::get_method_handle_ref(cp, bsm.args[1] as usize);
+    let (impl_class, impl_name, impl_desc) =
+        constant_pool::get_method_handle_target(cp, ref_index);
+    let impl_is_interface = matches!(
+        cp.get(ref_index as usize),
+        Some(ConstantPoolType::InterfaceMethodRef { .. })
+    );
+
+    let (captured, iface) = split_desc(desc);
+    let iface = &iface[1..iface.len() - 1];
+    let caller_name = caller.get_class().name.clone();
+    let class_name = format!(
+        "{}$$Lambda${}",
+        String::from_utf8_lossy(&caller_name),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    );
+    trace!(
+        "metafactory: {} implements {}.{} with {}.{}{}",
+        class_name,
+        String::from_utf8_lossy(iface),
+        String::from_utf8_lossy(name),
+        String::from_utf8_lossy(impl_class),
+        String::from_utf8_lossy(impl_name),
+        String::from_utf8_lossy(impl_desc),
+    );
+
+    let mut w = ClassWriter::default();
+    let this_class = w.class(class_name.as_bytes());
+    let super_class = w.class(b"java/lang/Object");
+    let iface_class = w.class(iface);
+
+    // the arguments of the implementation method, receiver first
+    let (impl_params, impl_ret) = split_desc(impl_desc);
+    let receiver = [b"L", impl_class.as_slice(), b";"].concat();
+    let mut impl_args: Vec<&[u8]> = Vec::with_capacity(impl_params.len() + 1);
+    if matches!(
+        ref_kind,
+        REF_INVOKE_VIRTUAL | REF_INVOKE_SPECIAL | REF_INVOKE_INTERFACE
+    ) {
+        impl_args.push(&receiver);
+    }
+    impl_args.extend(impl_params.iter());
+    let impl_ret: &[u8] = match ref_kind {
+        REF_NEW_INVOKE_SPECIAL => &receiver,
+        _ => impl_ret,
+    };
+
+    let (sam_params, sam_ret) = split_desc(&sam_desc);
+    let (inst_params, _) = split_desc(&instantiated);
+    assert_eq!(
+        captured.len() + sam_params.len(),
+        impl_args.len(),
+        "metafactory: arity of {} does not match",
+        String::from_utf8_lossy(impl_name)
+    );
+
+    let mut code = Code::default();
+    if ref_kind == REF_NEW_INVOKE_SPECIAL {
+        let c = w.class(impl_class);
+        code.op_u2(NEW, c);
+        code.op(DUP);
+        code.push(2);
+    }
+    let mut fields = Vec::with_capacity(captured.len());
+    for (i, (ty, to)) in captured.iter().zip(&impl_args).enumerate() {
+        let field_name = format!("arg${}", i + 1);
+        let f = w.member(TAG_FIELD_REF, class_name.as_bytes(), field_name.as_bytes(), ty);
+        fields.push((w.utf8(field_name.as_bytes()), w.utf8(ty)));
+        code.op(ALOAD_0);
+        code.push(1);
+        code.op_u2(GETFIELD, f);
+        code.push(slots(ty) - 1);
+        adapt(&mut w, &mut code, ty, to);
+    }
+    let mut local = 1;
+    for (i, (ty, to)) in sam_params
+        .iter()
+        .zip(&impl_args[captured.len()..])
+        .enumerate()
+    {
+        code.load(ty, local);
+        local += slots(ty);
+        code.push(slots(ty));
+        // a reference argument is known to be of the instantiated type
+        let from = match (ty[0], inst_params.get(i)) {
+            (b'L' | b'[', Some(inst)) => *inst,
+            _ => *ty,
+        };
+        adapt(&mut w, &mut code, from, to);
+    }
+
+    let arg_slots: u16 = impl_args.iter().map(|ty| slots(ty)).sum();
+    let tag = match impl_is_interface {
+        true => TAG_INTERFACE_METHOD_REF,
+        false => TAG_METHOD_REF,
+    };
+    let impl_ref = w.member(tag, impl_class, impl_name, impl_desc);
+    match ref_kind {
+        REF_INVOKE_STATIC => code.op_u2(INVOKESTATIC, impl_ref),
+        REF_INVOKE_VIRTUAL => code.op_u2(INVOKEVIRTUAL, impl_ref),
+        REF_INVOKE_SPECIAL | REF_NEW_INVOKE_SPECIAL => code.op_u2(INVOKESPECIAL, impl_ref),
+        REF_INVOKE_INTERFACE => {
+            code.op_u2(INVOKEINTERFACE, impl_ref);
+            code.bytes.extend_from_slice(&[arg_slots as u8, 0]);
+        }
+        _ => unreachable!("metafactory: method handle kind {}", ref_kind),
+    }
+    code.pop(arg_slots + (ref_kind == REF_NEW_INVOKE_SPECIAL) as u16);
+    code.push(slots(impl_ret));
+    adapt(&mut w, &mut code, impl_ret, sam_ret);
+    code.op(match sam_ret[0] {
+        b'V' => RETURN,
+        b'J' => 0xad,
+        b'F' => 0xae,
+        b'D' => 0xaf,
+        b'L' | b'[' => 0xb0,
+        _ => 0xac,
+    });
+
+    let bytes = w.finish(
+        this_class,
+        super_class,
+        iface_class,
+        &fields,
+        (name, &sam_desc),
+        &code,
+        local,
+    );
+    let cf = match parse_class(&bytes) {
+        Ok(cf) => cf,
+        Err(e) => unreachable!("metafactory: spun {} is malformed: {}", class_name, e),
+    };
+    let class = Arc::new(Class::new_class(
+        Arc::new(Box::new(cf)),
+        caller.get_class().class_loader,
+    ));
+    //its own getfield resolves the class by name
+    runtime::sys_dic_put(class_name.as_bytes(), class.clone());
+    {
+        let this_ref = class.clone();
+        let cls = class.get_mut_class();
+        cls.set_class_state(oop::class::State::Loaded);
+        cls.link_class(this_ref);
+    }
+    native::java_lang_Class::create_mirror(class.clone());
+    oop::class::init_class(&class);
+    oop::class::init_class_fully(&class);
+    class
+}
+
+/// Convert the value of type `from` on top of the stack to type `to`.
+fn adapt(w: &mut ClassWriter, code: &mut Code, from: &[u8], to: &[u8]) {
+    if from == to {
+        return;
+    }
+    let is_ref = |t: &[u8]| matches!(t[0], b'L' | b'[');
+    match (from[0], to[0]) {
+        (_, b'V') => match slots(from) {
+            0 => (),
+            1 => code.op(POP),
+            _ => code.op(POP2),
+        },
+        _ if is_ref(from) && is_ref(to) => {
+            if to != b"Ljava/lang/Object;" {
+                code.op_u2(CHECKCAST, w.class(internal_name(to)));
+            }
+        }
+        // boxing
+        (p, _) if is_ref(to) => {
+            let wrapper = wrapper_of(p);
+            let desc = format!("({})L{};", p as char, wrapper);
+            let m = w.member(
+                TAG_METHOD_REF,
+                wrapper.as_bytes(),
+                b"valueOf",
+                desc.as_bytes(),
+            );
+            code.op_u2(INVOKESTATIC, m);
+            code.pop(slots(from));
+            code.push(1);
+        }
+        // unboxing, then widening
+        (_, p) if is_ref(from) => {
+            let name = internal_name(from);
+            let prim = match name {
+                b"java/lang/Byte" => b'B',
+                b"java/lang/Short" => b'S',
+                b"java/lang/Character" => b'C',
+                b"java/lang/Integer" => b'I',
+                b"java/lang/Long" => b'J',
+                b"java/lang/Float" => b'F',
+                b"java/lang/Double" => b'D',
+                b"java/lang/Boolean" => b'Z',
+                _ => p,
+            };
+            let wrapper = wrapper_of(prim);
+            code.op_u2(CHECKCAST, w.class(wrapper.as_bytes()));
+            let method = format!("{}Value", primitive_name(prim));
+            let desc = format!("(){}", prim as char);
+            let m = w.member(
+                TAG_METHOD_REF,
+                wrapper.as_bytes(),
+                method.as_bytes(),
+                desc.as_bytes(),
+            );
+            code.op_u2(INVOKEVIRTUAL, m);
+            code.push(slots(&[prim]) - 1);
+            adapt(w, code, &[prim], to);
+        }
+        // primitive widening, the int types need no conversion
+        (f, t) => {
+            let op = match (f, t) {
+                (b'J', b'F') => L2F,
+                (b'J', b'D') => L2D,
+                (b'F', b'D') => F2D,
+                (b'J', _) | (b'F', _) | (b'D', _) => return,
+                (_, b'J') => I2L,
+                (_, b'F') => I2F,
+                (_, b'D') => I2D,
+                _ => return,
+            };
+            code.op(op);
+            code.push(slots(to));
+            code.pop(slots(from));
+        }
+    }
+}
+
+fn wrapper_of(prim: u8) -> &'static str {
+    match prim {
+        b'B' => "java/lang/Byte",
+        b'S' => "java/lang/Short",
+        b'C' => "java/lang/Character",
+        b'I' => "java/lang/Integer",
+        b'J' => "java/lang/Long",
+        b'F' => "java/lang/Float",
+        b'D' => "java/lang/Double",
+        b'Z' => "java/lang/Boolean",
+        _ => unreachable!("not a primitive type: {}", prim as char),
+    }
+}
+
+fn primitive_name(prim: u8) -> &'static str {
+    match prim {
+        b'B' => "byte",
+        b'S' => "short",
+        b'C' => "char",
+        b'I' => "int",
+        b'J' => "long",
+        b'F' => "float",
+        b'D' => "double",
+        _ => "boolean",
+    }
+}
+
+/// `java/lang/String` of `Ljava/lang/String;`, arrays stay descriptors
+fn internal_name(desc: &[u8]) -> &[u8] {
+    match desc[0] {
+        b'L' => &desc[1..desc.len() - 1],
+        _ => desc,
+    }
+}
+
+fn slots(ty: &[u8]) -> u16 {
+    match ty[0] {
+        b'V' => 0,
+        b'J' | b'D' => 2,
+        _ => 1,
+    }
+}
+
+/// Parameter and return types of a method descriptor.
+fn split_desc(desc: &[u8]) -> (Vec<&[u8]>, &[u8]) {
+    let mut params = Vec::new();
+    let mut i = 1;
+    while desc[i] != b')' {
+        let start = i;
+        while desc[i] == b'[' {
+            i += 1;
+        }
+        if desc[i] == b'L' {
+            while desc[i] != b';' {
+                i += 1;
+            }
+        }
+        i += 1;
+        params.push(&desc[start..i]);
+    }
+    (params, &desc[i + 1..])
+}
+
+/// Bytecode of the interface method and its operand stack depth.
+#[derive(Default)]
+struct Code {
+    bytes: Vec<u8>,
+    depth: u16,
+    max_stack: u16,
+}
+
+impl Code {
+    fn op(&mut self, op: u8) {
+        self.bytes.push(op);
+    }
+
+    fn op_u2(&mut self, op: u8, v: u16) {
+        self.bytes.push(op);
+        self.bytes.extend_from_slice(&v.to_be_bytes());
+    }
+
+    /// `iload`, `lload`, `fload`, `dload` or `aload` of local `n`
+    fn load(&mut self, ty: &[u8], n: u16) {
+        let op = match ty[0] {
+            b'J' => 0x16,
+            b'F' => 0x17,
+            b'D' => 0x18,
+            b'L' | b'[' => 0x19,
+            _ => 0x15,
+        };
+        if n <= 0xff {
+            self.bytes.extend_from_slice(&[op, n as u8]);
+        } else {
+            // wide
+            self.bytes.extend_from_slice(&[0xc4, op]);
+            self.bytes.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+
+    fn push(&mut self, n: u16) {
+        self.depth += n;
+        self.max_stack = self.max_stack.max(self.depth);
+    }
+
+    fn pop(&mut self, n: u16) {
+        self.depth -= n;
+    }
+}
+
+/// Writes the class file of a lambda class.
+#[derive(Default)]
+struct ClassWriter {
+    cp: Vec<u8>,
+    count: u16,
+    entries: FxHashMap<Vec<u8>, u16>,
+}
+
+impl ClassWriter {
+    fn entry(&mut self, entry: Vec<u8>) -> u16 {
+        if let Some(i) = self.entries.get(&entry) {
+            return *i;
+        }
+        self.count += 1;
+        self.cp.extend_from_slice(&entry);
+        self.entries.insert(entry, self.count);
+        self.count
+    }
+
+    fn utf8(&mut self, s: &[u8]) -> u16 {
+        let mut e = vec![TAG_UTF8];
+        e.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        e.extend_from_slice(s);
+        self.entry(e)
+    }
+
+    fn class(&mut self, name: &[u8]) -> u16 {
+        let name = self.utf8(name);
+        let mut e = vec![TAG_CLASS];
+        e.extend_from_slice(&name.to_be_bytes());
+        self.entry(e)
+    }
+
+    /// A field, method or interface method reference
+    fn member(&mut self, tag: u8, class: &[u8], name: &[u8], desc: &[u8]) -> u16 {
+        let class = self.class(class);
+        let name = self.utf8(name);
+        let desc = self.utf8(desc);
+        let mut nat = vec![TAG_NAME_AND_TYPE];
+        nat.extend_from_slice(&name.to_be_bytes());
+        nat.extend_from_slice(&desc.to_be_bytes());
+        let nat = self.entry(nat);
+        let mut e = vec![tag];
+        e.extend_from_slice(&class.to_be_bytes());
+        e.extend_from_slice(&nat.to_be_bytes());
+        self.entry(e)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn finish(
+        mut self,
+        this_class: u16,
+        super_class: u16,
+        iface: u16,
+        fields: &[(u16, u16)],
+        (name, desc): (&[u8], &[u8]),
+        code: &Code,
+        max_locals: u16,
+    ) -> Vec<u8> {
+        let name = self.utf8(name);
+        let desc = self.utf8(desc);
+        let code_attr = self.utf8(b"Code");
+
+        let u2 = |buf: &mut Vec<u8>, v: u16| buf.extend_from_slice(&v.to_be_bytes());
+        let mut buf = Vec::with_capacity(self.cp.len() + code.bytes.len() + 128);
+        buf.extend_from_slice(&0xCAFE_BABEu32.to_be_bytes());
+        // Java 8, the VM doesn't verify, so no StackMapTable is needed
+        u2(&mut buf, 0);
+        u2(&mut buf, 52);
+        u2(&mut buf, self.count + 1);
+        buf.extend_from_slice(&self.cp);
+        u2(&mut buf, ACC_FINAL | ACC_SUPER | ACC_SYNTHETIC);
+        u2(&mut buf, this_class);
+        u2(&mut buf, super_class);
+        u2(&mut buf, 1);
+        u2(&mut buf, iface);
+
+        u2(&mut buf, fields.len() as u16);
+        for (name, desc) in fields {
+            u2(&mut buf, ACC_PRIVATE | ACC_FINAL);
+            u2(&mut buf, *name);
+            u2(&mut buf, *desc);
+            u2(&mut buf, 0);
+        }
+
+        u2(&mut buf, 1);
+        u2(&mut buf, ACC_PUBLIC);
+        u2(&mut buf, name);
+        u2(&mut buf, desc);
+        u2(&mut buf, 1);
+        u2(&mut buf, code_attr);
+        buf.extend_from_slice(&(12 + code.bytes.len() as u32).to_be_bytes());
+        u2(&mut buf, code.max_stack);
+        u2(&mut buf, max_locals);
+        buf.extend_from_slice(&(code.bytes.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&code.bytes);
+        // exception table and attributes
+        u2(&mut buf, 0);
+        u2(&mut buf, 0);
+
+        // class attributes
+        u2(&mut buf, 0);
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_split_desc() {
+        let (params, ret) = split_desc(b"(I[[JLjava/lang/String;[Ljava/lang/Object;D)V");
+        assert_eq!(
+            params,
+            vec![
+                &b"I"[..],
+                b"[[J",
+                b"Ljava/lang/String;",
+                b"[Ljava/lang/Object;",
+                b"D"
+            ]
+        );
+        assert_eq!(ret, b"V");
+        let (params, ret) = split_desc(b"()Ljava/util/function/Supplier;");
+        assert!(params.is_empty());
+        assert_eq!(ret, b"Ljava/util/function/Supplier;");
+    }
+}
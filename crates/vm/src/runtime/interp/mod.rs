@@ -23,6 +23,7 @@ mod control_flow;
 mod conversion;
 mod exception;
 mod field_ops;
+mod lambda;
 mod load_store;
 mod monitor_ops;
 mod object_ops;
@@ -56,14 +57,15 @@ impl<'a> Interp<'a> {
         let jt = runtime::thread::current_java_thread();
         let codes = self.code.clone();
 
+        //method entry, safepoint poll
+        runtime::thread::safepoint::poll();
+
         loop {
             let code = self::read::read_byte(&self.frame.pc, &codes);
             let code = OpCode::from(code);
             match code {
-                OpCode::athrow => {
-                    self.athrow(jt);
-                    break;
-                }
+                //the handler lookup below may catch it in this frame
+                OpCode::athrow => self.athrow(jt.clone()),
                 OpCode::ireturn => {
                     self.ireturn();
                     break;
@@ -1,6 +1,7 @@
 use super::Interp;
 use crate::oop::{self, Oop};
 use crate::runtime::exception;
+use crate::runtime::thread;
 use crate::types::JavaThreadRef;
 use classfile::consts as cls_const;
 
@@ -62,13 +63,8 @@ impl<'a> Interp<'a> {
         drop(stack);
         match v {
             Oop::Null => exception::meet_ex(cls_const::J_NPE, None),
-            Oop::Ref(slot_id) => {
-                oop::with_heap(|heap| {
-                    let desc = heap.get(slot_id);
-                    let guard = desc.read().unwrap();
-                    guard.monitor_enter();
-                });
-            }
+            //block outside the heap lock, the owner may need it to proceed
+            Oop::Ref(_) => thread::monitor_enter(&v),
             _ => unreachable!(),
         }
     }
@@ -79,12 +75,10 @@ impl<'a> Interp<'a> {
         drop(stack);
         match v {
             Oop::Null => exception::meet_ex(cls_const::J_NPE, None),
-            Oop::Ref(slot_id) => {
-                oop::with_heap(|heap| {
-                    let desc = heap.get(slot_id);
-                    let guard = desc.read().unwrap();
-                    guard.monitor_exit();
-                });
+            Oop::Ref(_) => {
+                if !v.unlock() {
+                    exception::meet_ex(cls_const::J_IMSE, None);
+                }
             }
             _ => unreachable!(),
         }
@@ -20,8 +20,11 @@ impl<'a> Interp<'a> {
         let mir = match cls.get_cp_method(idx) {
             Some(m) => m,
             None => {
-                warn!("Method resolution failed at constant pool index {}", idx);
-                exception::meet_ex(cls_const::J_NSME, None);
+                //a failed access check has thrown IllegalAccessError
+                if !runtime::thread::is_meet_ex() {
+                    warn!("Method resolution failed at constant pool index {}", idx);
+                    exception::meet_ex(cls_const::J_NSME, None);
+                }
                 return;
             }
         };
@@ -146,7 +149,15 @@ impl<'a> Interp<'a> {
         } else if target_class.as_slice() == b"java/lang/invoke/LambdaMetafactory"
             && target_name.as_slice() == b"metafactory"
         {
-            handle_lambda_metafactory(&self.frame.area, &self.cp, bsm, &name, &desc);
+            super::lambda::metafactory(
+                &self.frame.area,
+                &self.frame.class,
+                &self.cp,
+                cp_idx as u16,
+                bsm,
+                &name,
+                &desc,
+            );
         } else {
             warn!(
                 "invokedynamic: unsupported bootstrap method {}::{}",
@@ -172,6 +183,10 @@ impl<'a> Interp<'a> {
         let idx = super::read::read_u2(pc, codes);
         let class = match runtime::require_class2(idx as u16, &self.cp) {
             Some(class) => {
+                let from = self.frame.class.get_class().name.clone();
+                if !runtime::module::check_access(from.as_slice(), &class) {
+                    return;
+                }
                 oop::class::init_class(&class);
                 oop::class::init_class_fully(&class);
                 class
@@ -214,8 +229,7 @@ impl<'a> Interp<'a> {
                 Some(class) => class,
                 None => panic!("Cannot get class info from constant pool"),
             };
-            oop::class::init_class(&class);
-            oop::class::init_class_fully(&class);
+            //the component class is resolved, not initialized
             let (name, cl) = {
                 let class = class.get_class();
                 let t = class.get_class_kind_type();
@@ -378,13 +392,59 @@ fn handle_string_concat_factory(
     };
 
     // Step 4: Build result string by replacing \u0001 placeholders
-    let result = build_concat_string(&format_string, &args);
+    let mut values = Vec::with_capacity(args.len());
+    for (ty, v) in arg_types.iter().zip(args.iter()) {
+        match concat_arg_to_string(ty, v) {
+            Some(v) => values.push(v),
+            //toString threw, the exception is pending
+            None => return,
+        }
+    }
+    let result = build_concat_string(&format_string, &values);
 
     // Step 5: Create java.lang.String and push result
     let string_oop = crate::util::oop::new_java_lang_string_direct(&result);
     caller.stack.borrow_mut().push_ref(string_oop, false);
 }
 
+/// `String.valueOf` of an argument, as the JDK's concat strategies do, so
+/// floats, chars, booleans and objects print as in Java. `None` if it threw.
+fn concat_arg_to_string(ty: &classfile::SignatureType, v: &Oop) -> Option<String> {
+    use classfile::SignatureType;
+
+    let desc = match ty {
+        SignatureType::Int | SignatureType::Short | SignatureType::Byte => "(I)",
+        SignatureType::Long => "(J)",
+        SignatureType::Float => "(F)",
+        SignatureType::Double => "(D)",
+        SignatureType::Char => "(C)",
+        SignatureType::Boolean => "(Z)",
+        _ => match v {
+            Oop::Null => return Some("null".to_string()),
+            Oop::Ref(slot_id) if Oop::is_java_lang_string(*slot_id) => {
+                return Some(Oop::java_lang_string(*slot_id))
+            }
+            _ => "(Ljava/lang/Object;)",
+        },
+    };
+    let desc = format!("{}Ljava/lang/String;", desc);
+
+    let string_cls = runtime::require_class3(None, b"java/lang/String").unwrap();
+    let mir = string_cls
+        .get_class()
+        .get_static_method(&crate::new_br("valueOf"), &crate::new_br(&desc))
+        .unwrap();
+    let area = runtime::DataArea::new(1);
+    let mut jc = runtime::invoke::JavaCall::new_with_args(mir, vec![v.clone()]);
+    jc.invoke(Some(&area), true);
+    if runtime::thread::is_meet_ex() {
+        return None;
+    }
+
+    let v = area.stack.borrow_mut().pop_ref();
+    Some(Oop::java_lang_string(v.extract_ref()))
+}
+
 fn parse_method_type_args(desc: &[u8]) -> Vec<classfile::SignatureType> {
     use classfile::SignatureType;
     use std::sync::Arc;
@@ -441,7 +501,7 @@ fn parse_one_type(desc: &[u8], i: usize) -> (classfile::SignatureType, usize) {
     }
 }
 
-fn build_concat_string(format: &str, args: &[Oop]) -> String {
+fn build_concat_string(format: &str, args: &[String]) -> String {
     let mut result = String::new();
     let mut arg_idx = 0;
     let placeholder = '\u{0001}';
@@ -449,7 +509,7 @@ fn build_concat_string(format: &str, args: &[Oop]) -> String {
     for ch in format.chars() {
         if ch == placeholder {
             if arg_idx < args.len() {
-                result.push_str(&oop_to_string(&args[arg_idx]));
+                result.push_str(&args[arg_idx]);
                 arg_idx += 1;
             } else {
                 result.push(ch);
@@ -461,106 +521,3 @@ fn build_concat_string(format: &str, args: &[Oop]) -> String {
 
     result
 }
-
-fn oop_to_string(v: &Oop) -> String {
-    match v {
-        Oop::Int(val) => val.to_string(),
-        Oop::Long(val) => val.to_string(),
-        Oop::Float(val) => {
-            if val.is_infinite() {
-                if *val > 0.0 {
-                    "Infinity".to_string()
-                } else {
-                    "-Infinity".to_string()
-                }
-            } else if val.is_nan() {
-                "NaN".to_string()
-            } else {
-                val.to_string()
-            }
-        }
-        Oop::Double(val) => {
-            if val.is_infinite() {
-                if *val > 0.0 {
-                    "Infinity".to_string()
-                } else {
-                    "-Infinity".to_string()
-                }
-            } else if val.is_nan() {
-                "NaN".to_string()
-            } else {
-                val.to_string()
-            }
-        }
-        Oop::Ref(slot_id) => {
-            if Oop::is_java_lang_string(*slot_id) {
-                Oop::java_lang_string(*slot_id)
-            } else {
-                format!("<object:{}>", slot_id)
-            }
-        }
-        Oop::Null => "null".to_string(),
-        Oop::ConstUtf8(bytes) => String::from_utf8_lossy(bytes).to_string(),
-    }
-}
-
-/// Handle LambdaMetafactory.metafactory invokedynamic calls.
-/// Creates a minimal lambda proxy object that implements the SAM (Single Abstract Method)
-/// functional interface. The proxy returns default values (null/0/false) when invoked.
-fn handle_lambda_metafactory(
-    caller: &runtime::DataArea,
-    cp: &classfile::ConstantPool,
-    bsm: &classfile::attributes::BootstrapMethod,
-    _method_name: &[u8],
-    method_desc: &[u8],
-) {
-    // LambdaMetafactory.metafactory bootstrap args:
-    //   args[0]: MethodType (samMethodType - the functional interface method signature)
-    //   args[1]: MethodHandle (implMethod - the actual implementation method)
-    //   args[2]: MethodType (instantiatedMethodType)
-    //
-    // The invokedynamic descriptor tells us the return type = the functional interface type.
-    // E.g.: (Ljava/util/function/Supplier;)Ljava/util/function/Supplier;
-    //       -> return type is Ljava/util/function/Supplier;
-
-    // Extract the SAM interface type from the method descriptor return type
-    let sam_interface_desc = {
-        let mut i = 0;
-        while i < method_desc.len() && method_desc[i] != b')' {
-            i += 1;
-        }
-        // skip ')'
-        i += 1;
-        if i < method_desc.len() {
-            &method_desc[i..]
-        } else {
-            b"Ljava/lang/Object;"
-        }
-    };
-
-    // Build the internal class name from the descriptor
-    let class_name = if sam_interface_desc.starts_with(b"L") {
-        // Ljava/util/function/Supplier; -> java/util/function/Supplier
-        &sam_interface_desc[1..sam_interface_desc.len() - 1]
-    } else {
-        b"java/lang/Object"
-    };
-
-    // Try to load the SAM interface class
-    let proxy = match runtime::require_class3(None, class_name) {
-        Some(cls_ref) => {
-            oop::class::init_class(&cls_ref);
-            oop::class::init_class_fully(&cls_ref);
-            Oop::new_inst(cls_ref)
-        }
-        None => {
-            warn!(
-                "LambdaMetafactory: cannot load SAM interface {:?}",
-                String::from_utf8_lossy(class_name)
-            );
-            Oop::Null
-        }
-    };
-
-    caller.stack.borrow_mut().push_ref(proxy, false);
-}
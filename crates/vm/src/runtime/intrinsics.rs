@@ -0,0 +1,683 @@
+// ============================================================
+// 热点 JDK 方法的 intrinsic
+//
+// 一些 JDK 方法调用极其频繁，但走的是完整的 native 或 bytecode 路径：
+// `System.arraycopy` 每次都要压栈帧、构造 JNIEnv；`Math.max` 要解释执行
+// 一个方法体。本模块维护一张 intrinsic 表，按 (类, 方法名, 描述符) 识别
+// 这些方法，并直接用 Rust 实现它们。
+//
+// 表被两处使用：
+//
+// 1. 解释器：`MethodId::new` 查表并把结果缓存在 `MethodId::intrinsic`，
+//    `JavaCall::invoke` 在虚方法解析之后、压栈帧之前调用 `invoke`。
+//    JIT 代码经 `jit_invoke_*` 运行时函数发起的调用也走同一条检查。
+// 2. JIT：`builder` 按调用点的符号引用查表（不需要解析，AOT 代码同样适用）。
+//    `Math` 的方法直接生成 LLVM 指令（`llvm.sqrt`、`llvm.smin` 等），
+//    其他方法生成对 `jit_invoke_intrinsic` 的调用，跳过方法解析与栈帧。
+//
+// Rust 实现遇到不处理的情况（例如元素类型不同的数组拷贝、尚未初始化的
+// `Integer$IntegerCache`）时返回 None，调用回到原来的路径，由原实现给出
+// 正确的结果或异常。
+// ============================================================
+
+use crate::native::sun_misc_Unsafe as unsafe_ops;
+use crate::native::JNIResult;
+use crate::new_br;
+use crate::oop::{self, class, Class, Oop, RefKind, TypeArrayDesc, ValueType};
+use crate::runtime::{self, exception};
+use crate::util;
+use classfile::consts as cls_const;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intrinsic {
+    ArrayCopy,
+    MathSqrt,
+    MathAbsInt,
+    MathAbsLong,
+    MathAbsFloat,
+    MathAbsDouble,
+    MathMinInt,
+    MathMinLong,
+    MathMinFloat,
+    MathMinDouble,
+    MathMaxInt,
+    MathMaxLong,
+    MathMaxFloat,
+    MathMaxDouble,
+    StringEquals,
+    StringHashCode,
+    ArraysFill,
+    IntegerValueOf,
+    ObjectHashCode,
+    UnsafeGetAndAddInt,
+    UnsafeGetAndAddLong,
+    UnsafeGetAndSetInt,
+    UnsafeGetAndSetLong,
+    UnsafeGetAndSetObject,
+    UnsafeCompareAndExchangeByte,
+    UnsafeCompareAndExchangeShort,
+}
+
+/// (类, 方法名, 描述符, intrinsic)
+type Entry = (&'static str, &'static str, &'static str, Intrinsic);
+
+const TABLE: &[Entry] = &[
+    (
+        "java/lang/System",
+        "arraycopy",
+        "(Ljava/lang/Object;ILjava/lang/Object;II)V",
+        Intrinsic::ArrayCopy,
+    ),
+    ("java/lang/Math", "sqrt", "(D)D", Intrinsic::MathSqrt),
+    ("java/lang/Math", "abs", "(I)I", Intrinsic::MathAbsInt),
+    ("java/lang/Math", "abs", "(J)J", Intrinsic::MathAbsLong),
+    ("java/lang/Math", "abs", "(F)F", Intrinsic::MathAbsFloat),
+    ("java/lang/Math", "abs", "(D)D", Intrinsic::MathAbsDouble),
+    ("java/lang/Math", "min", "(II)I", Intrinsic::MathMinInt),
+    ("java/lang/Math", "min", "(JJ)J", Intrinsic::MathMinLong),
+    ("java/lang/Math", "min", "(FF)F", Intrinsic::MathMinFloat),
+    ("java/lang/Math", "min", "(DD)D", Intrinsic::MathMinDouble),
+    ("java/lang/Math", "max", "(II)I", Intrinsic::MathMaxInt),
+    ("java/lang/Math", "max", "(JJ)J", Intrinsic::MathMaxLong),
+    ("java/lang/Math", "max", "(FF)F", Intrinsic::MathMaxFloat),
+    ("java/lang/Math", "max", "(DD)D", Intrinsic::MathMaxDouble),
+    (
+        "java/lang/String",
+        "equals",
+        "(Ljava/lang/Object;)Z",
+        Intrinsic::StringEquals,
+    ),
+    (
+        "java/lang/String",
+        "hashCode",
+        "()I",
+        Intrinsic::StringHashCode,
+    ),
+    ("java/util/Arrays", "fill", "([II)V", Intrinsic::ArraysFill),
+    ("java/util/Arrays", "fill", "([JJ)V", Intrinsic::ArraysFill),
+    ("java/util/Arrays", "fill", "([BB)V", Intrinsic::ArraysFill),
+    ("java/util/Arrays", "fill", "([ZZ)V", Intrinsic::ArraysFill),
+    ("java/util/Arrays", "fill", "([CC)V", Intrinsic::ArraysFill),
+    ("java/util/Arrays", "fill", "([SS)V", Intrinsic::ArraysFill),
+    ("java/util/Arrays", "fill", "([FF)V", Intrinsic::ArraysFill),
+    ("java/util/Arrays", "fill", "([DD)V", Intrinsic::ArraysFill),
+    (
+        "java/lang/Integer",
+        "valueOf",
+        "(I)Ljava/lang/Integer;",
+        Intrinsic::IntegerValueOf,
+    ),
+    (
+        "java/lang/Object",
+        "hashCode",
+        "()I",
+        Intrinsic::ObjectHashCode,
+    ),
+    // Unsafe 的原子读改写在 JDK 中是 CAS 循环，这里一次完成
+    (
+        "sun/misc/Unsafe",
+        "getAndAddInt",
+        "(Ljava/lang/Object;JI)I",
+        Intrinsic::UnsafeGetAndAddInt,
+    ),
+    (
+        "sun/misc/Unsafe",
+        "getAndAddLong",
+        "(Ljava/lang/Object;JJ)J",
+        Intrinsic::UnsafeGetAndAddLong,
+    ),
+    (
+        "sun/misc/Unsafe",
+        "getAndSetInt",
+        "(Ljava/lang/Object;JI)I",
+        Intrinsic::UnsafeGetAndSetInt,
+    ),
+    (
+        "sun/misc/Unsafe",
+        "getAndSetLong",
+        "(Ljava/lang/Object;JJ)J",
+        Intrinsic::UnsafeGetAndSetLong,
+    ),
+    (
+        "sun/misc/Unsafe",
+        "getAndSetObject",
+        "(Ljava/lang/Object;JLjava/lang/Object;)Ljava/lang/Object;",
+        Intrinsic::UnsafeGetAndSetObject,
+    ),
+    (
+        "jdk/internal/misc/Unsafe",
+        "getAndAddInt",
+        "(Ljava/lang/Object;JI)I",
+        Intrinsic::UnsafeGetAndAddInt,
+    ),
+    (
+        "jdk/internal/misc/Unsafe",
+        "getAndAddLong",
+        "(Ljava/lang/Object;JJ)J",
+        Intrinsic::UnsafeGetAndAddLong,
+    ),
+    (
+        "jdk/internal/misc/Unsafe",
+        "getAndSetInt",
+        "(Ljava/lang/Object;JI)I",
+        Intrinsic::UnsafeGetAndSetInt,
+    ),
+    (
+        "jdk/internal/misc/Unsafe",
+        "getAndSetLong",
+        "(Ljava/lang/Object;JJ)J",
+        Intrinsic::UnsafeGetAndSetLong,
+    ),
+    (
+        "jdk/internal/misc/Unsafe",
+        "getAndSetObject",
+        "(Ljava/lang/Object;JLjava/lang/Object;)Ljava/lang/Object;",
+        Intrinsic::UnsafeGetAndSetObject,
+    ),
+    (
+        "jdk/internal/misc/Unsafe",
+        "getAndSetReference",
+        "(Ljava/lang/Object;JLjava/lang/Object;)Ljava/lang/Object;",
+        Intrinsic::UnsafeGetAndSetObject,
+    ),
+    (
+        "jdk/internal/misc/Unsafe",
+        "compareAndExchangeByte",
+        "(Ljava/lang/Object;JBB)B",
+        Intrinsic::UnsafeCompareAndExchangeByte,
+    ),
+    (
+        "jdk/internal/misc/Unsafe",
+        "compareAndExchangeShort",
+        "(Ljava/lang/Object;JSS)S",
+        Intrinsic::UnsafeCompareAndExchangeShort,
+    ),
+];
+
+/// 按 (类, 方法名, 描述符) 查找 intrinsic。
+pub fn find(class: &[u8], name: &[u8], desc: &[u8]) -> Option<Intrinsic> {
+    TABLE
+        .iter()
+        .find(|(c, n, d, _)| c.as_bytes() == class && n.as_bytes() == name && d.as_bytes() == desc)
+        .map(|(_, _, _, intrinsic)| *intrinsic)
+}
+
+impl Intrinsic {
+    /// 符号引用是否可能被子类覆盖。为 true 时只有解析出的目标方法
+    /// 本身才能用 intrinsic，JIT 不能按调用点的符号引用替换。
+    pub fn needs_dispatch(self) -> bool {
+        matches!(self, Intrinsic::ObjectHashCode)
+    }
+
+    /// 执行 intrinsic。返回 None 表示不处理，调用方应回到原来的路径。
+    pub fn invoke(self, args: &[Oop]) -> Option<JNIResult> {
+        let v = match self {
+            Intrinsic::ArrayCopy => return arraycopy(args),
+            Intrinsic::MathSqrt => Oop::new_double(args.first()?.extract_double().sqrt()),
+            Intrinsic::MathAbsInt => Oop::new_int(args.first()?.extract_int().wrapping_abs()),
+            Intrinsic::MathAbsLong => Oop::new_long(args.first()?.extract_long().wrapping_abs()),
+            Intrinsic::MathAbsFloat => Oop::new_float(args.first()?.extract_float().abs()),
+            Intrinsic::MathAbsDouble => Oop::new_double(args.first()?.extract_double().abs()),
+            Intrinsic::MathMinInt => {
+                Oop::new_int(args[0].extract_int().min(args.get(1)?.extract_int()))
+            }
+            Intrinsic::MathMinLong => {
+                Oop::new_long(args[0].extract_long().min(args.get(1)?.extract_long()))
+            }
+            Intrinsic::MathMinFloat => {
+                let (a, b) = (args[0].extract_float(), args.get(1)?.extract_float());
+                Oop::new_float(java_min(a as f64, b as f64) as f32)
+            }
+            Intrinsic::MathMinDouble => {
+                let (a, b) = (args[0].extract_double(), args.get(1)?.extract_double());
+                Oop::new_double(java_min(a, b))
+            }
+            Intrinsic::MathMaxInt => {
+                Oop::new_int(args[0].extract_int().max(args.get(1)?.extract_int()))
+            }
+            Intrinsic::MathMaxLong => {
+                Oop::new_long(args[0].extract_long().max(args.get(1)?.extract_long()))
+            }
+            Intrinsic::MathMaxFloat => {
+                let (a, b) = (args[0].extract_float(), args.get(1)?.extract_float());
+                Oop::new_float(java_max(a as f64, b as f64) as f32)
+            }
+            Intrinsic::MathMaxDouble => {
+                let (a, b) = (args[0].extract_double(), args.get(1)?.extract_double());
+                Oop::new_double(java_max(a, b))
+            }
+            Intrinsic::StringEquals => return string_equals(args),
+            Intrinsic::StringHashCode => return string_hash_code(args),
+            Intrinsic::ArraysFill => return arrays_fill(args),
+            Intrinsic::IntegerValueOf => return integer_value_of(args),
+            Intrinsic::ObjectHashCode => Oop::new_int(args.first()?.hash_code()),
+            Intrinsic::UnsafeGetAndAddInt => {
+                return Some(unsafe_ops::get_and_add(args, ValueType::INT))
+            }
+            Intrinsic::UnsafeGetAndAddLong => {
+                return Some(unsafe_ops::get_and_add(args, ValueType::LONG))
+            }
+            Intrinsic::UnsafeGetAndSetInt => {
+                return Some(unsafe_ops::get_and_set(args, ValueType::INT))
+            }
+            Intrinsic::UnsafeGetAndSetLong => {
+                return Some(unsafe_ops::get_and_set(args, ValueType::LONG))
+            }
+            Intrinsic::UnsafeGetAndSetObject => {
+                return Some(unsafe_ops::get_and_set(args, ValueType::OBJECT))
+            }
+            Intrinsic::UnsafeCompareAndExchangeByte => {
+                return Some(unsafe_ops::compare_and_exchange(args, ValueType::BYTE))
+            }
+            Intrinsic::UnsafeCompareAndExchangeShort => {
+                return Some(unsafe_ops::compare_and_exchange(args, ValueType::SHORT))
+            }
+        };
+        Some(Ok(Some(v)))
+    }
+}
+
+/// `Math.min` 的浮点语义：任一参数为 NaN 时结果为 NaN，且 -0.0 小于 0.0。
+///
+/// float 版本转换成 double 计算：转换无损，结果总是某个参数（或 NaN）。
+fn java_min(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == b {
+        if a.is_sign_negative() {
+            a
+        } else {
+            b
+        }
+    } else if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// `Math.max` 的浮点语义，见 `java_min`。
+fn java_max(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == b {
+        if a.is_sign_negative() {
+            b
+        } else {
+            a
+        }
+    } else if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+fn throw(cls_name: &[u8]) -> Option<JNIResult> {
+    Some(Err(exception::new(cls_name, None)))
+}
+
+/// 检查 `[pos, pos + len)` 是否落在长度为 `ary_len` 的数组内。
+fn in_bounds(pos: i32, len: i32, ary_len: usize) -> bool {
+    pos >= 0 && len >= 0 && (pos as i64 + len as i64) <= ary_len as i64
+}
+
+fn ary_len(v: &RefKind) -> Option<usize> {
+    match v {
+        RefKind::TypeArray(ary) => Some(ary.len()),
+        RefKind::Array(ary) => Some(ary.elements.len()),
+        _ => None,
+    }
+}
+
+macro_rules! copy_type_ary {
+    ($src:expr, $dst:expr, $sp:expr, $dp:expr, $len:expr, $($variant:ident),*) => {
+        match ($src, $dst) {
+            $(
+                (TypeArrayDesc::$variant(s), TypeArrayDesc::$variant(d)) => {
+                    d[$dp..$dp + $len].copy_from_slice(&s[$sp..$sp + $len]);
+                    true
+                }
+            )*
+            _ => false,
+        }
+    };
+}
+
+macro_rules! copy_within_type_ary {
+    ($ary:expr, $sp:expr, $dp:expr, $len:expr, $($variant:ident),*) => {
+        match $ary {
+            $(TypeArrayDesc::$variant(a) => a.copy_within($sp..$sp + $len, $dp),)*
+        }
+    };
+}
+
+enum CopyResult {
+    Done,
+    OutOfBounds,
+    Unsupported,
+}
+
+/// `System.arraycopy`：检查 null 与越界后直接拷贝元素。
+///
+/// 基本类型数组要求两端类型相同；引用数组要求两端是同一个数组类，
+/// 其余情况（需要逐个元素做 ArrayStoreException 检查）交给 native 实现。
+fn arraycopy(args: &[Oop]) -> Option<JNIResult> {
+    let (src, dst) = match (args.first()?, args.get(2)?) {
+        (Oop::Ref(src), Oop::Ref(dst)) => (*src, *dst),
+        _ => return throw(cls_const::J_NPE),
+    };
+    let src_pos = args.get(1)?.extract_int();
+    let dst_pos = args.get(3)?.extract_int();
+    let len = args.get(4)?.extract_int();
+
+    let result = oop::with_heap(|heap| {
+        let src_desc = heap.get(src);
+        if src == dst {
+            let mut guard = src_desc.write().unwrap();
+            let n = match ary_len(&guard.v) {
+                Some(n) => n,
+                None => return CopyResult::Unsupported,
+            };
+            if !in_bounds(src_pos, len, n) || !in_bounds(dst_pos, len, n) {
+                return CopyResult::OutOfBounds;
+            }
+            let (sp, dp, len) = (src_pos as usize, dst_pos as usize, len as usize);
+            match &mut guard.v {
+                RefKind::TypeArray(ary) => copy_within_type_ary!(
+                    ary, sp, dp, len, Byte, Bool, Char, Short, Float, Double, Int, Long
+                ),
+                RefKind::Array(ary) => {
+                    let tmp = ary.elements[sp..sp + len].to_vec();
+                    ary.elements[dp..dp + len].clone_from_slice(&tmp);
+                }
+                _ => unreachable!(),
+            }
+            return CopyResult::Done;
+        }
+
+        let src_guard = src_desc.read().unwrap();
+        let dst_desc = heap.get(dst);
+        let mut dst_guard = dst_desc.write().unwrap();
+        let (src_len, dst_len) = match (ary_len(&src_guard.v), ary_len(&dst_guard.v)) {
+            (Some(s), Some(d)) => (s, d),
+            _ => return CopyResult::Unsupported,
+        };
+        if !in_bounds(src_pos, len, src_len) || !in_bounds(dst_pos, len, dst_len) {
+            return CopyResult::OutOfBounds;
+        }
+        let (sp, dp, len) = (src_pos as usize, dst_pos as usize, len as usize);
+        let copied = match (&src_guard.v, &mut dst_guard.v) {
+            (RefKind::TypeArray(s), RefKind::TypeArray(d)) => {
+                copy_type_ary!(s, d, sp, dp, len, Byte, Bool, Char, Short, Float, Double, Int, Long)
+            }
+            (RefKind::Array(s), RefKind::Array(d))
+                if s.class.get_class().name == d.class.get_class().name =>
+            {
+                d.elements[dp..dp + len].clone_from_slice(&s.elements[sp..sp + len]);
+                true
+            }
+            _ => false,
+        };
+        if copied {
+            CopyResult::Done
+        } else {
+            CopyResult::Unsupported
+        }
+    });
+
+    match result {
+        CopyResult::Done => Some(Ok(None)),
+        CopyResult::OutOfBounds => throw(cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS),
+        CopyResult::Unsupported => None,
+    }
+}
+
+/// `Arrays.fill(T[], T)`（基本类型版本）。
+fn arrays_fill(args: &[Oop]) -> Option<JNIResult> {
+    let ary = match args.first()? {
+        Oop::Ref(ary) => *ary,
+        _ => return throw(cls_const::J_NPE),
+    };
+    let v = args.get(1)?;
+
+    let filled = oop::with_heap(|heap| {
+        let desc = heap.get(ary);
+        let mut guard = desc.write().unwrap();
+        match &mut guard.v {
+            RefKind::TypeArray(ary) => {
+                match ary {
+                    TypeArrayDesc::Byte(a) => a.fill(v.extract_int() as u8),
+                    TypeArrayDesc::Bool(a) => a.fill(v.extract_int() as u8),
+                    TypeArrayDesc::Char(a) => a.fill(v.extract_int() as u16),
+                    TypeArrayDesc::Short(a) => a.fill(v.extract_int() as i16),
+                    TypeArrayDesc::Int(a) => a.fill(v.extract_int()),
+                    TypeArrayDesc::Long(a) => a.fill(v.extract_long()),
+                    TypeArrayDesc::Float(a) => a.fill(v.extract_float()),
+                    TypeArrayDesc::Double(a) => a.fill(v.extract_double()),
+                }
+                true
+            }
+            _ => false,
+        }
+    });
+
+    if filled {
+        Some(Ok(None))
+    } else {
+        None
+    }
+}
+
+/// `String.coder` 字段的偏移；JDK 8 的 String 没有该字段。
+fn string_coder_offset() -> Option<usize> {
+    static OFFSET: OnceLock<Option<usize>> = OnceLock::new();
+    *OFFSET.get_or_init(|| {
+        let cls = runtime::sys_dic_find(cls_const::J_STRING)?;
+        let cls = cls.get_class();
+        cls.get_field_id_safe(&new_br("coder"), &new_br("B"), false)
+            .ok()
+            .map(|fid| fid.offset)
+    })
+}
+
+/// 读取 String 实例的 (value 数组, coder)。`slot_id` 不是 String 时返回 None。
+fn string_parts(slot_id: u32) -> Option<(u32, i32)> {
+    if !Oop::is_java_lang_string(slot_id) {
+        return None;
+    }
+    let value = Class::get_field_value2(slot_id, util::oop::get_java_lang_string_value_offset());
+    let coder = match string_coder_offset() {
+        Some(offset) => Class::get_field_value2(slot_id, offset).extract_int(),
+        None => 1,
+    };
+    match value {
+        Oop::Ref(value) => Some((value, coder)),
+        _ => None,
+    }
+}
+
+/// `String.equals`：比较 coder 与 value 数组的内容。
+fn string_equals(args: &[Oop]) -> Option<JNIResult> {
+    let this = args.first()?.extract_ref();
+    let other = match args.get(1)? {
+        Oop::Ref(other) => *other,
+        _ => return Some(Ok(Some(Oop::new_int(0)))),
+    };
+    if this == other {
+        return Some(Ok(Some(Oop::new_int(1))));
+    }
+
+    let (this_value, this_coder) = string_parts(this)?;
+    let (other_value, other_coder) = match string_parts(other) {
+        Some(parts) => parts,
+        None => return Some(Ok(Some(Oop::new_int(0)))),
+    };
+    if this_coder != other_coder {
+        return Some(Ok(Some(Oop::new_int(0))));
+    }
+    if this_value == other_value {
+        return Some(Ok(Some(Oop::new_int(1))));
+    }
+
+    let eq = oop::with_heap(|heap| {
+        let a = heap.get(this_value);
+        let b = heap.get(other_value);
+        let a = a.read().unwrap();
+        let b = b.read().unwrap();
+        match (&a.v, &b.v) {
+            (
+                RefKind::TypeArray(TypeArrayDesc::Byte(a)),
+                RefKind::TypeArray(TypeArrayDesc::Byte(b)),
+            ) => Some(a == b),
+            (
+                RefKind::TypeArray(TypeArrayDesc::Char(a)),
+                RefKind::TypeArray(TypeArrayDesc::Char(b)),
+            ) => Some(a == b),
+            _ => None,
+        }
+    })?;
+    Some(Ok(Some(Oop::new_int(eq as i32))))
+}
+
+/// `String.hashCode`：`s[0]*31^(n-1) + ... + s[n-1]`，按 char 计算。
+///
+/// JDK 9+ 的 value 是 byte[]：coder 为 0（LATIN1）时每个字节是一个 char，
+/// 否则每两个字节按本机字节序组成一个 char（与 `StringUTF16` 一致）。
+fn string_hash_code(args: &[Oop]) -> Option<JNIResult> {
+    let (value, coder) = string_parts(args.first()?.extract_ref())?;
+    let h = oop::with_heap(|heap| {
+        let desc = heap.get(value);
+        let guard = desc.read().unwrap();
+        let hash = |chars: &mut dyn Iterator<Item = u16>| {
+            chars.fold(0i32, |h, c| h.wrapping_mul(31).wrapping_add(c as i32))
+        };
+        match &guard.v {
+            RefKind::TypeArray(TypeArrayDesc::Char(chars)) => {
+                Some(hash(&mut chars.iter().copied()))
+            }
+            RefKind::TypeArray(TypeArrayDesc::Byte(bytes)) if coder == 0 => {
+                Some(hash(&mut bytes.iter().map(|b| *b as u16)))
+            }
+            RefKind::TypeArray(TypeArrayDesc::Byte(bytes)) => Some(hash(
+                &mut bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_ne_bytes([pair[0], pair[1]])),
+            )),
+            _ => None,
+        }
+    })?;
+    Some(Ok(Some(Oop::new_int(h))))
+}
+
+/// `Integer.valueOf(int)`：缓存范围内返回 `Integer$IntegerCache.cache` 中的
+/// 实例，范围外直接分配新的 Integer。
+///
+/// `IntegerCache` 尚未初始化时返回 None，由 bytecode 触发它的 <clinit>。
+fn integer_value_of(args: &[Oop]) -> Option<JNIResult> {
+    let i = args.first()?.extract_int();
+
+    let cache = runtime::sys_dic_find(b"java/lang/Integer$IntegerCache")?;
+    if cache.get_class_state() != class::State::FullyIni {
+        return None;
+    }
+    let cache = cache.get_class();
+    let static_int = |name: &str| {
+        cache
+            .get_field_id_safe(&new_br(name), &new_br("I"), true)
+            .ok()
+            .map(|fid| cache.get_static_field_value(fid).extract_int())
+    };
+    let (low, high) = (static_int("low")?, static_int("high")?);
+
+    if (low..=high).contains(&i) {
+        let fid = cache
+            .get_field_id_safe(&new_br("cache"), &new_br("[Ljava/lang/Integer;"), true)
+            .ok()?;
+        let cached = match cache.get_static_field_value(fid) {
+            Oop::Ref(ary) => ary,
+            _ => return None,
+        };
+        let v = oop::with_heap(|heap| {
+            let desc = heap.get(cached);
+            let guard = desc.read().unwrap();
+            match &guard.v {
+                RefKind::Array(ary) => ary.elements.get((i - low) as usize).cloned(),
+                _ => None,
+            }
+        })?;
+        return Some(Ok(Some(v)));
+    }
+
+    let integer = runtime::sys_dic_find(b"java/lang/Integer")?;
+    let v = Oop::new_inst(integer);
+    Class::put_field_value2(
+        v.extract_ref(),
+        util::oop::get_java_lang_integer_value_offset(),
+        Oop::new_int(i),
+    );
+    Some(Ok(Some(v)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_find() {
+        assert_eq!(
+            find(b"java/lang/Math", b"max", b"(JJ)J"),
+            Some(Intrinsic::MathMaxLong)
+        );
+        assert_eq!(
+            find(b"java/util/Arrays", b"fill", b"([DD)V"),
+            Some(Intrinsic::ArraysFill)
+        );
+        assert_eq!(find(b"java/lang/Math", b"max", b"(JI)J"), None);
+        assert_eq!(find(b"java/lang/StrictMath", b"sqrt", b"(D)D"), None);
+    }
+
+    #[test]
+    fn t_math() {
+        let call = |intrinsic: Intrinsic, args: &[Oop]| match intrinsic.invoke(args) {
+            Some(Ok(Some(v))) => v,
+            _ => panic!("{:?} not handled", intrinsic),
+        };
+
+        assert_eq!(
+            call(Intrinsic::MathAbsInt, &[Oop::new_int(i32::MIN)]).extract_int(),
+            i32::MIN
+        );
+        assert_eq!(
+            call(
+                Intrinsic::MathMaxLong,
+                &[Oop::new_long(-3), Oop::new_long(2)]
+            )
+            .extract_long(),
+            2
+        );
+        assert!(call(
+            Intrinsic::MathMinDouble,
+            &[Oop::new_double(1.0), Oop::new_double(f64::NAN)]
+        )
+        .extract_double()
+        .is_nan());
+        assert!(call(
+            Intrinsic::MathMinFloat,
+            &[Oop::new_float(0.0), Oop::new_float(-0.0)]
+        )
+        .extract_float()
+        .is_sign_negative());
+        assert!(call(
+            Intrinsic::MathMaxDouble,
+            &[Oop::new_double(-0.0), Oop::new_double(0.0)]
+        )
+        .extract_double()
+        .is_sign_positive());
+        assert_eq!(
+            call(Intrinsic::MathSqrt, &[Oop::new_double(2.25)]).extract_double(),
+            1.5
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use crate::native;
 use crate::native::JNINativeMethodStruct;
-use crate::oop::{self, Oop, ValueType};
+use crate::oop::{self, Monitor, Oop, ValueType};
 use crate::runtime::jit;
-use crate::runtime::jit::runtime::{set_invoke_ctx, JitInvokeCtx};
+use crate::runtime::jit::runtime::{
+    decode_value, encode_value, get_invoke_ctx, restore_invoke_ctx, set_invoke_ctx, JitInvokeCtx,
+};
 use crate::runtime::local::Local;
 use crate::runtime::{self, exception, frame::Frame, thread, DataArea, Interp};
 use crate::types::{ClassRef, FrameRef, JavaThreadRef, MethodIdRef};
@@ -11,7 +13,7 @@ use class_parser::MethodSignature;
 use classfile::{consts as cls_const, BytesRef, SignatureType};
 use std::borrow::BorrowMut;
 use std::cell::RefCell;
-use std::sync::{Arc, Mutex};
+use std::sync::{atomic, Arc, Mutex};
 use tracing::{debug, error, info, trace, warn};
 
 pub struct JavaCall {
@@ -19,6 +21,8 @@ pub struct JavaCall {
     pub args: Vec<Oop>,
     pub is_return_void: bool,
     pub is_interface: bool,
+    //monitor held by a synchronized method
+    sync_obj: Option<Oop>,
 }
 
 pub fn invoke_ctor(cls: ClassRef, desc: BytesRef, args: Vec<Oop>) {
@@ -49,6 +53,7 @@ impl JavaCall {
             args,
             is_return_void,
             is_interface: false,
+            sync_obj: None,
         }
     }
 
@@ -70,7 +75,7 @@ impl JavaCall {
                     cls.name.clone()
                 };
 
-                error!("Java new failed, null this: {:?}", mir.method);
+                debug!("Java new failed, null this: {:?}", mir.method);
 
                 //Fail fast, avoid a lot of logs, and it is not easy to locate the problem
                 //                        panic!();
@@ -102,136 +107,90 @@ impl JavaCall {
         self.resolve_virtual_method(force_no_resolve);
         self.debug();
 
+        // intrinsic 不需要栈帧
+        if self.invoke_intrinsic(caller) {
+            return;
+        }
+
+        // native 方法运行期间线程对 safepoint 是安全的；
+        // 回到 Java 代码前需要等待正在进行的 safepoint 结束
         if self.mir.method.is_native() {
-            self.invoke_native(caller);
+            thread::safepoint::in_native(|| self.invoke_native(caller));
         } else {
-            self.invoke_java(caller);
+            thread::safepoint::in_java(|| self.invoke_java(caller));
         }
 
         let jt = runtime::thread::current_java_thread();
         let _ = jt.write().unwrap().frames.pop();
     }
 
+    /// 用 intrinsic 表中的 Rust 实现执行方法。
+    /// 返回 false 表示方法不是 intrinsic 或实现不处理这组参数。
+    fn invoke_intrinsic(&self, caller: Option<&DataArea>) -> bool {
+        let result = match self.mir.intrinsic.and_then(|i| i.invoke(&self.args)) {
+            Some(result) => result,
+            None => return false,
+        };
+        match result {
+            Ok(v) => {
+                if let (Some(caller), Some(v)) = (caller, v) {
+                    set_return(caller, &self.mir.method.signature.retype, v);
+                }
+            }
+            Err(ex) => {
+                let jt = runtime::thread::current_java_thread();
+                jt.write().unwrap().set_ex(ex);
+            }
+        }
+        true
+    }
+
     /// 执行 JIT 编译后的方法。
-    /// 调用方必须已获取 `jit_impl` 的锁。
+    ///
+    /// 不持有 `jit_impl` 的锁：被调方法可能递归调用自身，
+    /// 也可能因去优化而清空 `jit_impl`。
     fn invoke_jit_with(
         &self,
         caller: Option<&DataArea>,
         jit_fn: &Arc<runtime::method::JITCompiledMethod>,
     ) {
-        let max_locals = self.mir.method.get_max_locals();
-        let max_stack = self.mir.method.get_max_stack();
-
-        // 构建 locals 数组（i32 缓冲区），填充方法参数
-        let mut jit_locals = vec![0i32; max_locals.max(1)];
-        self.copy_args_to_locals(&mut jit_locals);
-
-        // 分配 stack 缓冲区
-        let mut jit_stack = vec![0i32; max_stack.max(1)];
+        // 参数按 JitFn 的约定编码。参数不多时放在栈上，避免每次调用分配
+        let mut inline_args = [0i64; JIT_INLINE_ARGS];
+        let mut heap_args = Vec::new();
+        let args: &mut [i64] = if self.args.len() <= JIT_INLINE_ARGS {
+            &mut inline_args[..self.args.len()]
+        } else {
+            heap_args.resize(self.args.len(), 0);
+            &mut heap_args
+        };
+        for (slot, arg) in args.iter_mut().zip(self.args.iter()) {
+            *slot = encode_value(arg);
+        }
 
-        // 设置 JIT invoke 上下文（供 invoke* runtime 函数使用）
+        // 设置 JIT invoke 上下文（供 invoke* runtime 函数使用），
+        // 返回后恢复调用方的上下文
+        let prev_ctx = get_invoke_ctx();
         set_invoke_ctx(Some(JitInvokeCtx {
             method_class: self.mir.method.class.clone(),
+            mir: self.mir.clone(),
+            compiled: jit_fn.clone(),
         }));
 
         // 调用 JIT 函数
-        unsafe {
-            (jit_fn.fn_ptr)(jit_locals.as_mut_ptr(), jit_stack.as_mut_ptr());
-        }
+        let ret = (jit_fn.fn_ptr)(args.as_ptr());
 
-        // 清除上下文
-        set_invoke_ctx(None);
+        restore_invoke_ctx(prev_ctx);
 
-        // 如果方法有返回值且未发生异常，从 stack 顶部读取
+        // 如果方法有返回值且未发生异常，解码返回值
         if !self.is_return_void && !thread::is_meet_ex() {
             let caller = caller.unwrap();
             let return_type = &self.mir.method.signature.retype;
-            match return_type {
-                SignatureType::Byte
-                | SignatureType::Boolean
-                | SignatureType::Int
-                | SignatureType::Char
-                | SignatureType::Short => {
-                    let v = jit_stack[0];
-                    set_return(caller, return_type, Oop::Int(v));
-                }
-                SignatureType::Long => {
-                    let lo = jit_stack[0] as i64;
-                    let hi = jit_stack[1] as i64;
-                    let v = lo | (hi << 32);
-                    set_return(caller, return_type, Oop::Long(v));
-                }
-                SignatureType::Float => {
-                    let v = f32::from_bits(jit_stack[0] as u32);
-                    set_return(caller, return_type, Oop::Float(v));
-                }
-                SignatureType::Double => {
-                    let lo = jit_stack[0] as u64;
-                    let hi = jit_stack[1] as u64;
-                    let bits = lo | (hi << 32);
-                    set_return(caller, return_type, Oop::Double(f64::from_bits(bits)));
-                }
-                SignatureType::Object(_, _, _) | SignatureType::Array(_) => {
-                    let slot_id = jit_stack[0] as u32;
-                    set_return(caller, return_type, Oop::Ref(slot_id));
-                }
-                SignatureType::Void => {}
-                t => {
-                    warn!(
-                        "JIT: unsupported return type {:?}, falling back",
-                        return_type
-                    );
-                }
-            }
-        }
-    }
-
-    /// 将方法参数拷贝到 locals i32 数组。
-    /// 支持所有 JVM 类型参数。
-    fn copy_args_to_locals(&self, locals: &mut [i32]) {
-        let mut pos = 0;
-        // 非静态方法的第一个参数是 this（引用），跳过
-        let arg_start = if self.mir.method.is_static() { 0 } else { 1 };
-        for arg in self.args.iter().skip(arg_start) {
-            if pos >= locals.len() {
-                break;
-            }
-            match arg {
-                Oop::Int(v) => {
-                    locals[pos] = *v;
-                    pos += 1;
-                }
-                Oop::Long(v) => {
-                    if pos + 1 < locals.len() {
-                        locals[pos] = (*v & 0xFFFFFFFF) as i32;
-                        locals[pos + 1] = ((*v >> 32) & 0xFFFFFFFF) as i32;
-                        pos += 2;
-                    }
-                }
-                Oop::Float(v) => {
-                    locals[pos] = v.to_bits() as i32;
-                    pos += 1;
-                }
-                Oop::Double(v) => {
-                    if pos + 1 < locals.len() {
-                        let bits = v.to_bits();
-                        locals[pos] = (bits & 0xFFFFFFFF) as i32;
-                        locals[pos + 1] = ((bits >> 32) & 0xFFFFFFFF) as i32;
-                        pos += 2;
-                    }
-                }
-                Oop::Ref(slot_id) => {
-                    locals[pos] = *slot_id as i32;
-                    pos += 1;
-                }
-                Oop::Null => {
-                    locals[pos] = 0;
-                    pos += 1;
-                }
-                _ => {
-                    warn!("JIT: copy_args_to_locals unsupported type {:?}", arg);
-                    pos += 1;
-                }
+            match decode_value(return_type, ret) {
+                Some(v) => set_return(caller, return_type, v),
+                None => warn!(
+                    "JIT: unsupported return type {:?}, falling back",
+                    return_type
+                ),
             }
         }
     }
@@ -240,53 +199,62 @@ impl JavaCall {
     /// 返回 true 表示使用了 JIT 路径，false 表示回退到解释器。
     pub fn try_jit_invoke(&mut self, caller: Option<&DataArea>) -> bool {
         // 尝试获取已缓存的编译结果
-        {
-            let jit_impl = self.mir.jit_impl.lock().unwrap();
-            if let Some(ref compiled) = *jit_impl {
-                self.invoke_jit_with(caller, compiled);
-                return true;
-            }
+        let cached = self.mir.jit_impl.lock().unwrap().clone();
+        if let Some(compiled) = cached {
+            self.invoke_jit_with(caller, &compiled);
+            return true;
+        }
+
+        // 编译失败过的方法直接走解释器
+        if self.mir.not_compilable.load(atomic::Ordering::Relaxed) {
+            return false;
         }
 
-        // 尝试编译
-        if let Some(compiled) = jit::try_compile(&self.mir) {
+        // 先找 AOT 缓存中的预编译方法体，没有再尝试编译
+        let compiled = jit::aot::lookup(&self.mir).or_else(|| jit::try_compile(&self.mir));
+        if let Some(compiled) = compiled {
             // 缓存编译结果
-            {
+            let compiled = {
                 let mut jit_impl = self.mir.jit_impl.lock().unwrap();
                 // Double-check: 可能有其他线程先编译了
-                if jit_impl.is_some() {
-                    self.invoke_jit_with(caller, jit_impl.as_ref().unwrap());
-                    return true;
-                }
-                *jit_impl = Some(compiled.clone());
-            }
+                jit_impl.get_or_insert(compiled).clone()
+            };
             self.invoke_jit_with(caller, &compiled);
             return true;
         }
 
-        // JIT 不可用，回退到解释器
+        // JIT 不可用，回退到解释器；JIT 关闭时不是编译失败，不做标记
+        if jit::opt::level_for(&self.mir.method) != jit::opt::JitLevel::Off {
+            self.mir
+                .not_compilable
+                .store(true, atomic::Ordering::Relaxed);
+        }
         false
     }
 }
 
+/// `invoke_jit_with` 在栈上准备的参数个数，超过时才在堆上分配。
+const JIT_INLINE_ARGS: usize = 16;
+
 impl JavaCall {
     fn invoke_java(&mut self, caller: Option<&DataArea>) {
         self.prepare_sync();
 
         let jt = runtime::thread::current_java_thread();
 
-        // 尝试 JIT 路径
-        if self.try_jit_invoke(caller) {
-            self.fin_sync();
-            return;
-        }
-
         match self.prepare_frame() {
             Ok(frame) => {
                 {
                     jt.write().unwrap().frames.push(frame.clone());
                 }
 
+                // 尝试 JIT 路径。frame 已入栈，invoke 结束时统一弹出
+                if self.try_jit_invoke(caller) {
+                    self.freeze_finals();
+                    self.fin_sync();
+                    return;
+                }
+
                 let local = self.build_local();
                 let frame_h = frame.try_read().unwrap();
                 let mut interp = Interp::new(frame_h, local);
@@ -304,6 +272,7 @@ impl JavaCall {
                     let return_v = return_v.unwrap();
                     set_return(caller, &self.mir.method.signature.retype, return_v);
                 }
+                self.freeze_finals();
             }
 
             Err(ex) => {
@@ -314,6 +283,16 @@ impl JavaCall {
         self.fin_sync();
     }
 
+    //constructor barrier: the fields a constructor wrote, 'final' ones in
+    //particular, are visible to any thread that reads the reference after
+    //it is published (JLS 17.5). Constructors are never inlined, so every
+    //one returns through here.
+    fn freeze_finals(&self) {
+        if self.mir.method.name.as_slice() == b"<init>" {
+            atomic::fence(atomic::Ordering::Release);
+        }
+    }
+
     fn invoke_native(&mut self, caller: Option<&DataArea>) {
         self.prepare_sync();
 
@@ -361,35 +340,25 @@ impl JavaCall {
 
     fn prepare_sync(&mut self) {
         if self.mir.method.is_synchronized() {
-            if self.mir.method.is_static() {
-                let class = self.mir.method.class.get_class();
-                class.monitor_enter();
+            let obj = if self.mir.method.is_static() {
+                self.mir.method.class.get_class().lock_obj()
             } else {
-                let v = self.args.first().unwrap();
-                let v = v.extract_ref();
-                oop::with_heap(|heap| {
-                    let desc = heap.get(v);
-                    let guard = desc.read().unwrap();
-                    guard.monitor_enter();
-                });
+                self.args
+                    .first()
+                    .filter(|v| matches!(v, Oop::Ref(_)))
+                    .cloned()
+            };
+            if let Some(obj) = &obj {
+                thread::monitor_enter(obj);
             }
+            self.sync_obj = obj;
         }
     }
 
     fn fin_sync(&mut self) {
-        if self.mir.method.is_synchronized() {
-            if self.mir.method.is_static() {
-                let class = self.mir.method.class.get_class();
-                class.monitor_exit();
-            } else {
-                let v = self.args.first().unwrap();
-                let v = v.extract_ref();
-                oop::with_heap(|heap| {
-                    let desc = heap.get(v);
-                    let guard = desc.read().unwrap();
-                    guard.monitor_exit();
-                });
-            }
+        if let Some(obj) = self.sync_obj.take() {
+            let owned = obj.unlock();
+            debug_assert!(owned, "synchronized method exits without the lock");
         }
     }
 
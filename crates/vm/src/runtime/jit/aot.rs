@@ -0,0 +1,528 @@
+// ============================================================
+// AOT（Ahead-of-Time）编译缓存
+//
+// 每次启动都要先解释执行、再等 JIT 预热。AOT 模式复用 builder 的翻译
+// 流程，提前把一个 jar 中所有方法编译成共享库，启动时直接装入。
+//
+// ## 编译（`--aot-compile app.jar --aot-output app.so`）
+//
+// 1. 启动一个最小 VM（与 MainThread 相同的初始化），加载 jar 中的类
+// 2. 逐个方法调用 `builder::compile_method(.., aot = true)`：生成的代码
+//    不嵌入本进程的任何地址（不做 CHA 去虚化 / 内联 / 内联缓存），
+//    安全点的有效标志经全局变量 `<fn>_valid` 间接读取
+// 3. 所有方法链接进一个 Module，按 JIT 级别跑 pass pipeline
+// 4. 以 PIC 输出目标文件，用系统链接器 `cc -shared` 链接成共享库
+// 5. 写出清单 `app.so.manifest`，记录每个方法的：
+//    - 类名、方法名、描述符
+//    - 类文件内容的 md5（类被修改后缓存自动失效）
+//    - 入口函数（`JitFn`）的符号名、编译级别
+//    - 各安全点的去优化元数据
+//
+// 共享库中对 `jit_invoke_*` 等运行时函数的引用在 dlopen 时从 jvm 可执行
+// 文件解析，所以 jvm 以 `-rdynamic` 链接。
+//
+// ## 加载（`--aot-cache app.so`）
+//
+// - 启动时 dlopen 共享库并读取清单
+// - 类加载器读到类文件时调用 `record_class_bytes` 记录其 md5
+// - `JavaCall::try_jit_invoke` 在 `jit_impl` 为空时先调用 `lookup`：
+//   md5 与清单一致则 dlsym 取得函数，写入有效标志地址，安装为
+//   `jit_impl`。每个方法体只安装一次，失效后回到 JIT / 解释器
+//
+// ## 清单格式
+//
+// 文本，字段以 tab 分隔：
+//
+// ```text
+// jvm-aot 3
+// method  <class>  <name>  <desc>  <md5>  <symbol>  <level>
+// point   <bci>    <locals>  <stack>
+// ```
+//
+// point 行属于前面最近的 method 行；locals / stack 每个字符是一个槽的
+// 类型：T(top) I F J(long) D L(ref)。
+// ============================================================
+
+use super::builder;
+use super::cha::Assumption;
+use super::deopt::{DeoptInfo, DeoptPoint};
+use super::dump;
+use super::frame_state::ValueKind;
+use super::opt::JitLevel;
+use crate::runtime::method::{JITCompiledMethod, JitFn};
+use crate::runtime::{self, init_vm, vm};
+use crate::types::MethodIdRef;
+use inkwell::context::Context;
+use inkwell::passes::PassBuilderOptions;
+use inkwell::targets::{
+    CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine,
+};
+use std::collections::{HashMap, HashSet};
+use std::ffi::{c_void, CStr, CString};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::{info, warn};
+
+// 版本号随 JitFn 调用约定或清单格式变化而递增，旧缓存直接拒绝加载
+const MANIFEST_HEADER: &str = "jvm-aot 3";
+
+/// 共享库对应的清单路径：`<lib>.manifest`。
+pub fn manifest_path(lib: &Path) -> PathBuf {
+    let mut path = lib.as_os_str().to_owned();
+    path.push(".manifest");
+    PathBuf::from(path)
+}
+
+fn class_hash(bytes: &[u8]) -> String {
+    format!("{:x}", md5::compute(bytes))
+}
+
+fn kind_to_char(kind: &ValueKind) -> char {
+    match kind {
+        ValueKind::Top => 'T',
+        ValueKind::Int => 'I',
+        ValueKind::Float => 'F',
+        ValueKind::Long => 'J',
+        ValueKind::Double => 'D',
+        ValueKind::Ref => 'L',
+    }
+}
+
+fn char_to_kind(c: char) -> Option<ValueKind> {
+    match c {
+        'T' => Some(ValueKind::Top),
+        'I' => Some(ValueKind::Int),
+        'F' => Some(ValueKind::Float),
+        'J' => Some(ValueKind::Long),
+        'D' => Some(ValueKind::Double),
+        'L' => Some(ValueKind::Ref),
+        _ => None,
+    }
+}
+
+fn encode_kinds(kinds: &[ValueKind]) -> String {
+    kinds.iter().map(kind_to_char).collect()
+}
+
+fn decode_kinds(s: &str) -> Option<Vec<ValueKind>> {
+    s.chars().map(char_to_kind).collect()
+}
+
+// ------------------------------------------------------------
+// 编译
+// ------------------------------------------------------------
+
+/// 清单中的一个方法。
+struct ManifestMethod {
+    class: String,
+    name: String,
+    desc: String,
+    hash: String,
+    symbol: String,
+    level: JitLevel,
+    points: Vec<DeoptPoint>,
+}
+
+impl ManifestMethod {
+    fn write(&self, out: &mut String) {
+        out.push_str(&format!(
+            "method\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            self.class, self.name, self.desc, self.hash, self.symbol, self.level
+        ));
+        for point in &self.points {
+            out.push_str(&format!(
+                "point\t{}\t{}\t{}\n",
+                point.bci,
+                encode_kinds(&point.locals),
+                encode_kinds(&point.stack)
+            ));
+        }
+    }
+}
+
+/// 读出 jar 中所有类的 (类名, md5)。
+fn read_jar_classes(jar: &str) -> Result<Vec<(String, String)>, String> {
+    let file = File::open(jar).map_err(|e| format!("cannot open '{}': {}", jar, e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("cannot read '{}': {}", jar, e))?;
+
+    let mut classes = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("cannot read '{}': {}", jar, e))?;
+        let name = match entry.name().strip_suffix(".class") {
+            Some(name) if !name.starts_with("META-INF/") && name != "module-info" => {
+                name.to_string()
+            }
+            _ => continue,
+        };
+
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("cannot read '{}' in '{}': {}", name, jar, e))?;
+        classes.push((name, class_hash(&bytes)));
+    }
+    Ok(classes)
+}
+
+/// 把 `jar` 中所有方法编译为共享库 `output`，同时写出清单。
+/// 返回编译成功的方法数。
+///
+/// 需要在尚未启动 VM 的进程中调用：这里会自己完成 VM 初始化。
+pub fn compile_jar(jar: &str, output: &str, level: JitLevel) -> Result<usize, String> {
+    let classes = read_jar_classes(jar)?;
+    runtime::add_class_path(jar);
+
+    let vm = vm::VM::new(1);
+    vm.threads.attach_current_thread();
+    init_vm::initialize_jvm();
+
+    let context = Context::create();
+    let builder = context.create_builder();
+    let module = context.create_module("jvm_aot");
+    let mut manifest = Vec::new();
+
+    for (class_name, hash) in &classes {
+        let class = match runtime::require_class3(None, class_name.as_bytes()) {
+            Some(class) => class,
+            None => {
+                warn!("AOT: cannot load class {}", class_name);
+                continue;
+            }
+        };
+        let methods = match class.get_class().get_all_methods() {
+            Some(methods) => methods,
+            None => continue,
+        };
+
+        for mir in methods.values() {
+            let method = &mir.method;
+            // all_methods 也包含继承来的方法，只编译本类声明的
+            if method.cls_name.as_slice() != class_name.as_bytes() {
+                continue;
+            }
+            if method.code.is_none() {
+                continue;
+            }
+
+            let fn_name = builder::function_name(method);
+            // 链接时重名的符号会被 LLVM 改名，清单就会指向别的方法体
+            if module.get_function(&fn_name).is_some() {
+                return Err(format!("duplicate symbol {} for {:?}", fn_name, method));
+            }
+            let method_module = context.create_module(&fn_name);
+            let compiled =
+                builder::compile_method(&context, &method_module, &builder, method, &fn_name, true);
+            let compiled = match compiled {
+                Some(compiled) if method_module.verify().is_ok() => compiled,
+                _ => {
+                    warn!("AOT: skip {:?}", method);
+                    continue;
+                }
+            };
+
+            manifest.push(ManifestMethod {
+                class: class_name.clone(),
+                name: String::from_utf8_lossy(method.name.as_slice()).to_string(),
+                desc: String::from_utf8_lossy(method.desc.as_slice()).to_string(),
+                hash: hash.clone(),
+                symbol: fn_name,
+                level,
+                points: compiled.deopt.into_points(),
+            });
+            module
+                .link_in_module(method_module)
+                .map_err(|e| format!("link {:?} failed: {}", method, e))?;
+        }
+    }
+
+    vm.threads.detach_current_thread();
+
+    let target_machine = create_target_machine(level)?;
+    if let Some(passes) = level.passes() {
+        let options = PassBuilderOptions::create();
+        options.set_loop_vectorization(level >= JitLevel::O3);
+        options.set_loop_slp_vectorization(level >= JitLevel::O3);
+        module
+            .run_passes(passes, &target_machine, options)
+            .map_err(|e| format!("running passes '{}' failed: {}", passes, e))?;
+    }
+
+    let object = format!("{}.o", output);
+    target_machine
+        .write_to_file(&module, FileType::Object, Path::new(&object))
+        .map_err(|e| format!("cannot write '{}': {}", object, e))?;
+    link_shared_library(&object, output)?;
+    let _ = fs::remove_file(&object);
+
+    let mut text = String::new();
+    text.push_str(MANIFEST_HEADER);
+    text.push('\n');
+    for method in &manifest {
+        method.write(&mut text);
+    }
+    let manifest_path = manifest_path(Path::new(output));
+    fs::write(&manifest_path, text)
+        .map_err(|e| format!("cannot write '{}': {}", manifest_path.display(), e))?;
+
+    info!("AOT: compiled {} methods into {}", manifest.len(), output);
+    Ok(manifest.len())
+}
+
+fn create_target_machine(level: JitLevel) -> Result<TargetMachine, String> {
+    Target::initialize_native(&InitializationConfig::default())
+        .map_err(|e| format!("Failed to initialize native target: {}", e))?;
+    let triple = TargetMachine::get_default_triple();
+    let target = Target::from_triple(&triple)
+        .map_err(|e| format!("Failed to look up native target: {}", e))?;
+    target
+        .create_target_machine(
+            &triple,
+            &TargetMachine::get_host_cpu_name().to_string(),
+            &TargetMachine::get_host_cpu_features().to_string(),
+            level.codegen_level(),
+            // 共享库要求位置无关代码
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .ok_or_else(|| "Failed to create target machine".to_string())
+}
+
+/// 用系统链接器把目标文件链接为共享库。
+/// 运行时函数留作未定义符号，加载时从 jvm 可执行文件解析。
+fn link_shared_library(object: &str, output: &str) -> Result<(), String> {
+    let mut cmd = Command::new("cc");
+    cmd.arg("-shared").arg("-o").arg(output).arg(object);
+    if cfg!(target_os = "macos") {
+        cmd.arg("-undefined").arg("dynamic_lookup");
+    }
+    let status = cmd
+        .status()
+        .map_err(|e| format!("cannot run linker: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("linker failed: {}", status))
+    }
+}
+
+// ------------------------------------------------------------
+// 加载
+// ------------------------------------------------------------
+
+/// 缓存中的一个方法体。
+struct AotMethod {
+    hash: String,
+    symbol: String,
+    level: JitLevel,
+    /// 去优化元数据；安装时取走，所以每个方法体只安装一次。
+    points: Mutex<Option<Vec<DeoptPoint>>>,
+}
+
+struct AotCache {
+    /// dlopen 句柄，进程退出前不关闭。
+    handle: usize,
+    /// (类名, 方法名, 描述符) → 方法体
+    methods: HashMap<(String, String, String), AotMethod>,
+    /// 缓存中出现的类，只为它们计算 md5。
+    classes: HashSet<String>,
+    /// 类加载器实际读到的类文件的 md5。
+    loaded: Mutex<HashMap<String, String>>,
+}
+
+static CACHE: OnceLock<AotCache> = OnceLock::new();
+
+fn parse_manifest(text: &str) -> Result<HashMap<(String, String, String), AotMethod>, String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, MANIFEST_HEADER)) => (),
+        _ => return Err("unsupported manifest version".to_string()),
+    }
+
+    let mut methods = HashMap::new();
+    let mut current: Option<((String, String, String), AotMethod)> = None;
+    for (no, line) in lines {
+        let bad = || format!("bad manifest line {}: {}", no + 1, line);
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields.as_slice() {
+            ["method", class, name, desc, hash, symbol, level] => {
+                if let Some((key, method)) = current.take() {
+                    methods.insert(key, method);
+                }
+                let key = (class.to_string(), name.to_string(), desc.to_string());
+                current = Some((
+                    key,
+                    AotMethod {
+                        hash: hash.to_string(),
+                        symbol: symbol.to_string(),
+                        level: level.parse()?,
+                        points: Mutex::new(Some(Vec::new())),
+                    },
+                ));
+            }
+            ["point", bci, locals, stack] => {
+                let (_, method) = current.as_mut().ok_or_else(bad)?;
+                let point = DeoptPoint {
+                    bci: bci.parse().map_err(|_| bad())?,
+                    locals: decode_kinds(locals).ok_or_else(bad)?,
+                    stack: decode_kinds(stack).ok_or_else(bad)?,
+                };
+                method
+                    .points
+                    .get_mut()
+                    .unwrap()
+                    .as_mut()
+                    .unwrap()
+                    .push(point);
+            }
+            [""] => (),
+            _ => return Err(bad()),
+        }
+    }
+    if let Some((key, method)) = current.take() {
+        methods.insert(key, method);
+    }
+    Ok(methods)
+}
+
+fn dl_error() -> String {
+    let err = unsafe { libc::dlerror() };
+    if err.is_null() {
+        "unknown error".to_string()
+    } else {
+        unsafe { CStr::from_ptr(err) }.to_string_lossy().to_string()
+    }
+}
+
+/// 加载 AOT 缓存（共享库及其清单）。返回缓存中的方法数。
+pub fn load(lib: &str) -> Result<usize, String> {
+    let manifest_path = manifest_path(Path::new(lib));
+    let text = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("cannot read '{}': {}", manifest_path.display(), e))?;
+    let methods = parse_manifest(&text)?;
+
+    let c_lib = CString::new(lib).map_err(|e| e.to_string())?;
+    let handle = unsafe { libc::dlopen(c_lib.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+    if handle.is_null() {
+        return Err(format!("cannot load '{}': {}", lib, dl_error()));
+    }
+
+    let classes = methods.keys().map(|(class, _, _)| class.clone()).collect();
+    let n = methods.len();
+    let cache = AotCache {
+        handle: handle as usize,
+        methods,
+        classes,
+        loaded: Mutex::new(HashMap::new()),
+    };
+    CACHE
+        .set(cache)
+        .map_err(|_| "AOT cache already loaded".to_string())?;
+
+    info!("AOT: loaded {} methods from {}", n, lib);
+    Ok(n)
+}
+
+/// 类加载器读到类文件时调用，记录其 md5 供 `lookup` 比对。
+pub fn record_class_bytes(name: &str, bytes: &[u8]) {
+    if let Some(cache) = CACHE.get() {
+        if cache.classes.contains(name) {
+            cache
+                .loaded
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), class_hash(bytes));
+        }
+    }
+}
+
+/// 查找 `mir` 的预编译方法体；类文件的 md5 与缓存一致时返回可安装的结果。
+pub fn lookup(mir: &MethodIdRef) -> Option<Arc<JITCompiledMethod>> {
+    let cache = CACHE.get()?;
+    let method = &mir.method;
+    let class = String::from_utf8_lossy(method.cls_name.as_slice()).to_string();
+    let key = (
+        class,
+        String::from_utf8_lossy(method.name.as_slice()).to_string(),
+        String::from_utf8_lossy(method.desc.as_slice()).to_string(),
+    );
+    let entry = cache.methods.get(&key)?;
+
+    let matches = cache.loaded.lock().unwrap().get(&key.0) == Some(&entry.hash);
+    if !matches {
+        return None;
+    }
+    let points = entry.points.lock().unwrap().take()?;
+
+    let fn_sym = dl_sym(cache.handle, &entry.symbol)?;
+    let valid_sym = dl_sym(cache.handle, &builder::code_valid_symbol(&entry.symbol))?;
+
+    let mut deopt = DeoptInfo::new();
+    for point in points {
+        deopt.add_point(point);
+    }
+    // 安装前写入有效标志的地址，之后代码才可能执行到安全点
+    unsafe {
+        *(valid_sym as *mut *const Assumption) = deopt.valid;
+    }
+    let fn_ptr = unsafe { std::mem::transmute::<*mut c_void, JitFn>(fn_sym) };
+
+    let compile_id = dump::next_compile_id();
+    dump::log_aot_loaded(compile_id, entry.level, method);
+    Some(Arc::new(JITCompiledMethod {
+        fn_ptr,
+        deopt,
+        compile_id,
+        level: entry.level,
+    }))
+}
+
+fn dl_sym(handle: usize, symbol: &str) -> Option<*mut c_void> {
+    let c_symbol = CString::new(symbol).ok()?;
+    let sym = unsafe { libc::dlsym(handle as *mut c_void, c_symbol.as_ptr()) };
+    if sym.is_null() {
+        warn!("AOT: symbol {} not found: {}", symbol, dl_error());
+        None
+    } else {
+        Some(sym)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_manifest_round_trip() {
+        let method = ManifestMethod {
+            class: "Foo".to_string(),
+            name: "bar".to_string(),
+            desc: "(IJ)I".to_string(),
+            hash: "0123".to_string(),
+            symbol: "jit_Foo_bar".to_string(),
+            level: JitLevel::O2,
+            points: vec![DeoptPoint {
+                bci: 7,
+                locals: vec![ValueKind::Ref, ValueKind::Long, ValueKind::Top],
+                stack: vec![],
+            }],
+        };
+        let mut text = format!("{}\n", MANIFEST_HEADER);
+        method.write(&mut text);
+
+        let methods = parse_manifest(&text).unwrap();
+        let key = ("Foo".to_string(), "bar".to_string(), "(IJ)I".to_string());
+        let parsed = methods.get(&key).unwrap();
+        assert_eq!(parsed.symbol, "jit_Foo_bar");
+        assert_eq!(parsed.level, JitLevel::O2);
+        let points = parsed.points.lock().unwrap().take().unwrap();
+        assert_eq!(points[0].bci, 7);
+        assert_eq!(points[0].locals.len(), 3);
+        assert!(points[0].stack.is_empty());
+    }
+}
@@ -8,9 +8,12 @@
 // JVM 是基于栈的虚拟机，而 LLVM IR 是基于寄存器（SSA）的。
 // 转换策略：
 //
-// 1. 为本地变量数组分配 `alloca` 空间（LLVM 的栈内存分配指令）
-// 2. 为操作数栈分配 `alloca` 空间
-// 3. 维护一个 `stack_top` 指针，跟踪当前栈顶位置
+// 1. 先做帧状态分析（`frame_state`），得到每条指令执行前各 local /
+//    操作数栈槽的值类型和栈深度
+// 2. 每个 local 槽、每个操作数栈槽按值类型各分配一个 `alloca`：
+//    int/引用 → i32，long → i64，float → float，double → double，
+//    long/double 只用低位槽
+// 3. 栈深度在编译期已知，不需要运行时的栈顶指针
 // 4. JVM 的 `push` 对应 LLVM 的 `store` 到 alloca 位置
 // 5. JVM 的 `pop` 对应 LLVM 的 `load` 从 alloca 位置
 //
@@ -32,68 +35,165 @@
 // 我们用 `.expect()` 处理——IR 生成阶段的错误是编译 bug，应该 panic。
 // ============================================================
 
-use crate::runtime::method::Method;
-use classfile::{OpCode, U1};
+use crate::oop::class;
+use crate::runtime::intrinsics::{self, Intrinsic};
+use crate::runtime::jit::cha::{self, Assumption, Dependency};
+use crate::runtime::jit::deopt::{DeoptInfo, DeoptPoint};
+use crate::runtime::jit::frame_state::{self, FrameState, ValueKind};
+use crate::runtime::jit::inline_cache::InlineCache;
+use crate::runtime::jit::inliner;
+use crate::runtime::thread::safepoint;
+use crate::runtime::{self, method::Method};
+use crate::types::MethodIdRef;
+use class_parser::MethodSignature;
+use classfile::{constant_pool, consts, ConstantPoolType, OpCode, U1};
 use inkwell::basic_block::BasicBlock;
 use inkwell::builder::Builder;
 use inkwell::context::Context;
-use inkwell::module::Module;
-use inkwell::values::{AnyValue, BasicValueEnum, FunctionValue, IntValue, PointerValue};
-use std::collections::HashMap;
+use inkwell::module::{Linkage, Module};
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, IntType};
+use inkwell::values::{
+    AnyValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum, FloatValue, FunctionValue,
+    IntValue, PointerValue,
+};
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, error, info, trace, warn};
 
-/// JIT invoke 运行时外部函数签名。
-/// extern "C" fn(cp_idx: u16, locals: *mut i32, stack: *mut i32, stack_top: u32)
-type RuntimeInvokeFn<'ctx> = inkwell::values::FunctionValue<'ctx>;
+/// `compile_method` 的产物。
+pub struct CompiledFunction<'ctx> {
+    /// 入口函数（`JitFn`），类型化的方法体是它调用的 internal 函数。
+    pub function: FunctionValue<'ctx>,
+    /// 去虚化调用点依赖的 CHA 假设，编译成功后登记到 `cha`。
+    pub dependencies: Vec<Dependency>,
+    /// 各安全点的去优化元数据。
+    pub deopt: DeoptInfo,
+}
+
+/// 方法对应的 LLVM 函数名：`jit_` + 类名 + 方法名 + 描述符，三者以 `__` 分隔，
+/// 重载方法因描述符不同而不会重名。
+///
+/// 各部分经 `mangle` 转义，映射是单射的：`Foo$Bar` 与 `Foo_Bar` 不会撞名。
+pub fn function_name(method: &Method) -> String {
+    format!(
+        "jit_{}__{}__{}",
+        mangle(&method.cls_name),
+        mangle(&method.name),
+        mangle(&method.desc)
+    )
+}
+
+/// 把名字转义为符号名：ASCII 字母数字原样保留，`_` 和其他字节写成
+/// `_` 加两位大写十六进制。转义后 `_` 之后总是十六进制数字，
+/// 所以 `__` 可以用作分隔符。
+fn mangle(name: &[u8]) -> String {
+    let mut s = String::with_capacity(name.len());
+    for &b in name {
+        if b.is_ascii_alphanumeric() {
+            s.push(b as char);
+        } else {
+            s.push_str(&format!("_{:02X}", b));
+        }
+    }
+    s
+}
+
+/// 编译后代码引用的 safepoint 请求标志的符号名，见 `safepoint::POLL`。
+const SAFEPOINT_POLL_SYMBOL: &str = "jvm_safepoint_poll";
+
+/// AOT 代码中保存有效标志地址的全局变量名。
+///
+/// JIT 代码把有效标志的地址直接嵌入机器码；AOT 代码编译时地址还不存在，
+/// 改为从这个全局变量读取，由加载器在安装方法体时写入。
+pub fn code_valid_symbol(fn_name: &str) -> String {
+    format!("{}_valid", fn_name)
+}
 
 /// 编译单个方法，生成 LLVM IR 函数。
 ///
 /// ## 参数
-/// - `bytecode`: 方法的字节码数组
-/// - `max_locals`: 最大本地变量槽数
-/// - `max_stack`: 最大操作数栈深度
+/// - `method`: 要编译的方法，bytecode 与 max_locals 取自它的 Code 属性
+/// - `fn_name`: 生成的函数名，同一 Module / ExecutionEngine 内必须唯一
+/// - `aot`: 为 AOT 编译生成代码。生成的代码不能依赖本进程的地址和类层次：
+///   不做 CHA 去虚化、内联和内联缓存，有效标志经全局变量间接读取
 ///
 /// ## 返回
-/// 生成的 LLVM `FunctionValue`。如果编译失败（如遇到不支持的 opcode），返回 None。
+/// 生成的 LLVM `FunctionValue` 及其 CHA 依赖和去优化元数据。
+/// 如果编译失败（如遇到不支持的 opcode），返回 None。
 pub fn compile_method<'a, 'ctx: 'a>(
     context: &'ctx Context,
     module: &'a Module<'ctx>,
     builder: &'a Builder<'ctx>,
-    method: &Method,
-    bytecode: &'a [U1],
-    max_locals: usize,
-    max_stack: usize,
-) -> Option<FunctionValue<'ctx>> {
+    method: &'a Method,
+    fn_name: &str,
+    aot: bool,
+) -> Option<CompiledFunction<'ctx>> {
+    // native 方法和 abstract 方法没有 bytecode
+    let code = method.code.as_ref()?;
+    // 编译后的代码不做异常分派，异常总是直接传播给调用方
+    if !code.exceptions.is_empty() {
+        return None;
+    }
+    let bytecode = code.code.as_slice();
+
     // ============================================================
-    // 步骤 1: 创建 LLVM 函数
+    // 步骤 1: 帧状态分析
     // ============================================================
     //
-    // 函数签名: void fn(i8* locals, i8* stack)
-    //
-    // 选择 i8* 而不是类型化的指针，因为：
-    // 1. 简化签名——不需要为不同参数类型生成不同函数
-    // 2. 调用方可以直接传递 `Slot` 数组的指针
-    // 3. 在函数内部，我们用 getelementptr (GEP) 计算具体槽位
-    //
-    // GEP 是 LLVM 的地址计算指令。它不访问内存，只计算地址。
-    // 类比 C 语言: `ptr + offset`
+    // 每条指令读写的 local / 栈槽的类型由帧状态给出，分析不了的方法不编译。
 
-    let i8_type = context.i8_type();
-    let ptr_type = i8_type.ptr_type(inkwell::AddressSpace::default());
+    let frame_states = frame_state::analyze(method)?;
 
-    // 生成唯一的函数名。使用 "jit_" 前缀 + 类名 + 方法名，便于调试。
-    let cls_name = String::from_utf8_lossy(&method.cls_name).replace("/", "_");
-    let method_name = String::from_utf8_lossy(&method.name);
-    let fn_name = format!("jit_{}_{}", cls_name, method_name);
+    let mut params = Vec::new();
+    if !method.is_static() {
+        params.push(ValueKind::Ref);
+    }
+    for arg in &method.signature.args {
+        params.push(ValueKind::from_signature(arg)?);
+    }
+    let ret_kind = ValueKind::from_signature(&method.signature.retype);
 
-    // 在 LLVM Module 中创建函数。
-    let fn_type = context
-        .void_type()
-        .fn_type(&[ptr_type.into(), ptr_type.into()], false);
-    let function = module.add_function(&fn_name, fn_type, None);
+    // ============================================================
+    // 步骤 2: 创建 LLVM 函数
+    // ============================================================
+    //
+    // 每个方法生成两个函数：
+    //
+    // - 方法体 `<fn_name>_typed`：按 Java 签名生成的类型化函数，
+    //   参数（含 this）与返回值都走寄存器：
+    //   int/byte/char/short/boolean → i32, long → i64, float → float,
+    //   double → double, 引用 → i32（堆 slot id，0 为 null）
+    // - 入口 `<fn_name>`：`i64 fn(i64* args)`，即 `JitFn`。VM 无法按任意
+    //   签名直接调用函数指针，入口从 args 数组（每个 Java 参数一个 i64）
+    //   取出参数，调用方法体，把返回值放进 i64 返回。方法体是 internal
+    //   链接，优化时会被内联进入口
+
+    let ptr_type = context.ptr_type(inkwell::AddressSpace::default());
+
+    let param_types: Vec<BasicMetadataTypeEnum<'ctx>> = params
+        .iter()
+        .map(|&kind| kind_type(context, kind).into())
+        .collect();
+    let body_type = match ret_kind {
+        None => context.void_type().fn_type(&param_types, false),
+        Some(kind) => kind_type(context, kind).fn_type(&param_types, false),
+    };
+    let function = module.add_function(
+        &format!("{}_typed", fn_name),
+        body_type,
+        Some(Linkage::Internal),
+    );
+
+    // AOT：有效标志的地址由加载器写入全局变量
+    let code_valid = if aot {
+        let global = module.add_global(ptr_type, None, &code_valid_symbol(fn_name));
+        global.set_initializer(&ptr_type.const_null());
+        Some(global.as_pointer_value())
+    } else {
+        None
+    };
 
     // ============================================================
-    // 步骤 2: 创建 BasicBlock
+    // 步骤 3: 创建 BasicBlock
     // ============================================================
     //
     // BasicBlock 是 LLVM IR 的基本控制流单位。每个 block 以终止符
@@ -101,104 +201,17 @@ pub fn compile_method<'a, 'ctx: 'a>(
     //
     // 我们扫描所有跳转目标，为每个目标创建一个 BasicBlock。
 
-    let jump_targets = collect_jump_targets(bytecode);
-
-    // 为每个跳转目标创建 BasicBlock
-    let mut bb_map: HashMap<usize, BasicBlock<'ctx>> = HashMap::new();
-
-    // 创建入口 block
-    let entry_bb = context.append_basic_block(function, "entry");
-    bb_map.insert(0, entry_bb);
-
-    // 为每个跳转目标创建 block
-    for offset in &jump_targets {
-        if *offset != 0 && *offset < bytecode.len() {
-            let name = format!("bb_{}", offset);
-            let bb = context.append_basic_block(function, &name);
-            bb_map.insert(*offset, bb);
-        }
-    }
+    let bb_map = block_map(context, function, bytecode, "bb");
 
-    // 创建返回 block（方法正常结束时的汇聚点）
+    // 返回 block（方法正常结束时的汇聚点）
     let return_bb = context.append_basic_block(function, "return");
 
     // ============================================================
-    // 步骤 3: 在 entry block 中分配局部变量
-    // ============================================================
-
-    builder.position_at_end(entry_bb);
-
-    // 将 locals 指针转换为 i32* 类型
-    let locals_ptr = function.get_first_param().unwrap().into_pointer_value();
-    let locals_i32_ptr = builder
-        .build_pointer_cast(
-            locals_ptr,
-            context
-                .i32_type()
-                .ptr_type(inkwell::AddressSpace::default()),
-            "locals_cast",
-        )
-        .expect("build_pointer_cast failed");
-
-    // 创建本地变量数组（alloca 分配）
-    // 每个 local 对应一个 alloca，初始值从参数指针加载。
-    let num_locals = max_locals.max(1);
-    let local_vars: Vec<PointerValue<'ctx>> = (0..num_locals)
-        .map(|i| {
-            let alloca = builder
-                .build_alloca(context.i32_type(), &format!("local_{}", i))
-                .expect("build_alloca failed");
-            // 从参数指针加载初始值
-            let idx = context.i32_type().const_int(i as u64, false);
-            let ptr = unsafe {
-                builder
-                    .build_in_bounds_gep(context.i32_type(), locals_i32_ptr, &[idx], "local_ptr")
-                    .expect("build_gep failed")
-            };
-            let loaded = builder
-                .build_load(context.i32_type(), ptr, &format!("load_local_{}", i))
-                .expect("build_load failed")
-                .into_int_value();
-            builder
-                .build_store(alloca, loaded)
-                .expect("build_store failed");
-            alloca
-        })
-        .collect();
-
-    // 创建操作数栈数组（alloca 分配）
-    let stack_size = max_stack.max(1);
-    let stack_vars: Vec<PointerValue<'ctx>> = (0..stack_size)
-        .map(|i| {
-            builder
-                .build_alloca(context.i32_type(), &format!("stack_{}", i))
-                .expect("build_alloca failed")
-        })
-        .collect();
-
-    // 当前栈顶指针（指向下一个可用的栈槽位）
-    let stack_top = builder
-        .build_alloca(context.i32_type(), "stack_top")
-        .expect("build_alloca failed");
-    builder
-        .build_store(stack_top, context.i32_type().const_int(0, false))
-        .expect("build_store failed");
-
-    // ============================================================
-    // 步骤 4: 开始 bytecode 翻译
+    // 步骤 4: 翻译 bytecode
     // ============================================================
-
-    // 将 stack 参数也转换为 i32* 类型，用于 ireturn 写入返回值
-    let stack_ptr = function.get_nth_param(1).unwrap().into_pointer_value();
-    let stack_i32_ptr = builder
-        .build_pointer_cast(
-            stack_ptr,
-            context
-                .i32_type()
-                .ptr_type(inkwell::AddressSpace::default()),
-            "stack_cast",
-        )
-        .expect("build_pointer_cast failed");
+    //
+    // locals 与操作数栈的每个槽按值类型各有一个 alloca，见 `local_ptr` /
+    // `stack_ptr`。mem2reg 会把它们提升为 SSA 寄存器。
 
     let mut interp = BytecodeInterpreter {
         context,
@@ -206,34 +219,195 @@ pub fn compile_method<'a, 'ctx: 'a>(
         bb_map,
         function,
         return_bb,
-        local_vars,
-        stack_vars,
-        stack_top,
+        unwind_bb: return_bb,
+        locals: HashMap::new(),
+        stack: HashMap::new(),
+        depth: 0,
+        ret_kind,
+        ret_slot: None,
         bytecode,
-        max_stack,
-        stack_param: Some(stack_i32_ptr),
         module: module as *const Module<'ctx>,
+        method: Some(method),
+        dependencies: Vec::new(),
+        cur_pc: 0,
+        frame_states,
+        safepoints: true,
+        deopt_bufs: None,
+        deopt: DeoptInfo::new(),
+        aot,
+        code_valid,
+        loop_headers: collect_loop_headers(bytecode),
+        failed: false,
     };
+    if !interp.build_body(&params) {
+        return None;
+    }
+
+    // 打印生成的 IR（调试用）
+    info!("JIT: Generated LLVM IR for {}", fn_name);
 
-    // 从 entry block 开始翻译
-    let entry_bb = *interp.bb_map.get(&0).unwrap();
-    builder.position_at_end(entry_bb);
-    interp.translate_bytecode(0);
+    // ============================================================
+    // 步骤 5: 生成入口函数
+    // ============================================================
 
-    // 对于因分支指令提前返回而遗漏的基本块，
-    // 逐一处理（这些块可能从条件分支的 fallthrough 或 target 创建）
-    interp.translate_remaining_blocks();
+    let entry = build_entry(
+        context, module, builder, fn_name, function, &params, ret_kind,
+    );
 
-    // 确保 return_bb 有正确的终止符
-    if return_bb.get_terminator().is_none() {
-        builder.position_at_end(return_bb);
-        builder.build_return(None).expect("build_return failed");
+    Some(CompiledFunction {
+        function: entry,
+        dependencies: interp.dependencies,
+        deopt: interp.deopt,
+    })
+}
+
+/// 值类型对应的 LLVM 类型。引用是 i32 slot id。
+fn kind_type(context: &Context, kind: ValueKind) -> BasicTypeEnum<'_> {
+    match kind {
+        ValueKind::Int | ValueKind::Ref => context.i32_type().into(),
+        ValueKind::Long => context.i64_type().into(),
+        ValueKind::Float => context.f32_type().into(),
+        ValueKind::Double => context.f64_type().into(),
+        ValueKind::Top => unreachable!("Top slot has no storage"),
     }
+}
 
-    // 打印生成的 IR（调试用）
-    info!("JIT: Generated LLVM IR for {}:\n{}", fn_name, fn_name);
+/// 按 `runtime::encode_value` 的约定把一个 `kind` 类型的值编码为 i64：
+/// int 与引用符号扩展，float 取位模式零扩展，long 原样，double 取位模式。
+fn encode_i64<'ctx>(
+    context: &'ctx Context,
+    builder: &Builder<'ctx>,
+    kind: ValueKind,
+    v: BasicValueEnum<'ctx>,
+) -> IntValue<'ctx> {
+    let i64_type = context.i64_type();
+    match kind {
+        ValueKind::Int | ValueKind::Ref => builder
+            .build_int_s_extend(v.into_int_value(), i64_type, "enc_i")
+            .expect("sext failed"),
+        ValueKind::Long => v.into_int_value(),
+        ValueKind::Float => {
+            let bits = builder
+                .build_bit_cast(v, context.i32_type(), "enc_f_bits")
+                .expect("bitcast failed")
+                .into_int_value();
+            builder
+                .build_int_z_extend(bits, i64_type, "enc_f")
+                .expect("zext failed")
+        }
+        ValueKind::Double => builder
+            .build_bit_cast(v, i64_type, "enc_d")
+            .expect("bitcast failed")
+            .into_int_value(),
+        ValueKind::Top => unreachable!("Top slot has no value"),
+    }
+}
+
+/// `encode_i64` 的逆操作。
+fn decode_i64<'ctx>(
+    context: &'ctx Context,
+    builder: &Builder<'ctx>,
+    kind: ValueKind,
+    raw: IntValue<'ctx>,
+) -> BasicValueEnum<'ctx> {
+    let i32_type = context.i32_type();
+    match kind {
+        ValueKind::Int | ValueKind::Ref => builder
+            .build_int_truncate(raw, i32_type, "dec_i")
+            .expect("truncate failed")
+            .into(),
+        ValueKind::Long => raw.into(),
+        ValueKind::Float => {
+            let bits = builder
+                .build_int_truncate(raw, i32_type, "dec_f_bits")
+                .expect("truncate failed");
+            builder
+                .build_bit_cast(bits, context.f32_type(), "dec_f")
+                .expect("bitcast failed")
+        }
+        ValueKind::Double => builder
+            .build_bit_cast(raw, context.f64_type(), "dec_d")
+            .expect("bitcast failed"),
+        ValueKind::Top => unreachable!("Top slot has no value"),
+    }
+}
+
+/// 为 bci 0 和 bytecode 中的每个跳转目标创建 BasicBlock，块名以 `prefix` 开头。
+fn block_map<'ctx>(
+    context: &'ctx Context,
+    function: FunctionValue<'ctx>,
+    bytecode: &[U1],
+    prefix: &str,
+) -> HashMap<usize, BasicBlock<'ctx>> {
+    let mut bb_map = HashMap::new();
+    bb_map.insert(
+        0,
+        context.append_basic_block(function, &format!("{}_0", prefix)),
+    );
+    for offset in collect_jump_targets(bytecode) {
+        if offset != 0 && offset < bytecode.len() {
+            let bb = context.append_basic_block(function, &format!("{}_{}", prefix, offset));
+            bb_map.insert(offset, bb);
+        }
+    }
+    bb_map
+}
+
+/// 生成入口函数 `i64 fn_name(i64* args)`：按 `params` 解码参数，调用类型化的
+/// 方法体 `body`，把返回值编码进 i64（void 返回 0），编码方式见 `encode_i64`。
+fn build_entry<'ctx>(
+    context: &'ctx Context,
+    module: &Module<'ctx>,
+    builder: &Builder<'ctx>,
+    fn_name: &str,
+    body: FunctionValue<'ctx>,
+    params: &[ValueKind],
+    ret_kind: Option<ValueKind>,
+) -> FunctionValue<'ctx> {
+    let i64_type = context.i64_type();
+    let ptr_type = context.ptr_type(inkwell::AddressSpace::default());
+
+    let entry_type = i64_type.fn_type(&[ptr_type.into()], false);
+    let entry = module.add_function(fn_name, entry_type, None);
+    let bb = context.append_basic_block(entry, "entry");
+    builder.position_at_end(bb);
+
+    let args_ptr = entry.get_first_param().unwrap().into_pointer_value();
+    let args: Vec<BasicMetadataValueEnum<'ctx>> = params
+        .iter()
+        .enumerate()
+        .map(|(i, &kind)| {
+            let ptr = unsafe {
+                builder
+                    .build_in_bounds_gep(
+                        i64_type,
+                        args_ptr,
+                        &[i64_type.const_int(i as u64, false)],
+                        "arg_ptr",
+                    )
+                    .expect("gep failed")
+            };
+            let raw = builder
+                .build_load(i64_type, ptr, "arg_raw")
+                .expect("load failed")
+                .into_int_value();
+            decode_i64(context, builder, kind, raw).into()
+        })
+        .collect();
 
-    Some(function)
+    let ret = builder
+        .build_call(body, &args, "body")
+        .expect("call failed")
+        .try_as_basic_value()
+        .basic();
+    let ret = match (ret_kind, ret) {
+        (Some(kind), Some(v)) => encode_i64(context, builder, kind, v),
+        _ => i64_type.const_zero(),
+    };
+    builder
+        .build_return(Some(&ret))
+        .expect("build_return failed");
+    entry
 }
 
 /// Bytecode 翻译器的上下文。
@@ -243,30 +417,116 @@ struct BytecodeInterpreter<'ctx, 'a> {
     bb_map: HashMap<usize, BasicBlock<'ctx>>,
     function: FunctionValue<'ctx>,
     return_bb: BasicBlock<'ctx>,
-    local_vars: Vec<PointerValue<'ctx>>,
-    stack_vars: Vec<PointerValue<'ctx>>,
-    stack_top: PointerValue<'ctx>,
+    /// 有待处理的异常时跳转的块：最外层方法的返回块。
+    /// 内联的方法体与调用方共用它。
+    unwind_bb: BasicBlock<'ctx>,
+    /// local 槽的存储：每个 (槽号, 值类型) 一个 alloca。
+    locals: HashMap<(usize, ValueKind), PointerValue<'ctx>>,
+    /// 操作数栈槽的存储，同 `locals`。
+    stack: HashMap<(usize, ValueKind), PointerValue<'ctx>>,
+    /// 当前栈深度（槽数）。每条指令开始时取自帧状态，翻译过程中随 push / pop 变化。
+    depth: usize,
+    /// 返回值类型；void 方法为 None。
+    ret_kind: Option<ValueKind>,
+    /// *return 写入返回值的位置：方法体的返回值 alloca，
+    /// 或内联时调用方接收返回值的栈槽。
+    ret_slot: Option<PointerValue<'ctx>>,
     bytecode: &'a [U1],
-    max_stack: usize,
-    /// 调用方的 stack 缓冲区指针（i32*）。用于在 ireturn 时写入返回值。
-    stack_param: Option<PointerValue<'ctx>>,
     /// 模块引用（用于声明外部函数）。
     module: *const Module<'ctx>,
+    /// 正在翻译的方法（用于解析调用点）。没有方法上下文时不做去虚化和内联。
+    method: Option<&'a Method>,
+    /// 去虚化调用点收集到的 CHA 依赖。
+    dependencies: Vec<Dependency>,
+    /// 正在翻译的指令的 bci。
+    cur_pc: usize,
+    /// 各 bci 的帧状态，决定每条指令读写的槽的类型。
+    frame_states: HashMap<usize, FrameState>,
+    /// 是否在调用点之后插入去优化检查。内联方法体没有自己的栈帧，不插入。
+    safepoints: bool,
+    /// 去优化时写出 locals / 操作数栈的缓冲区（每槽一个 i64），首次使用时分配。
+    deopt_bufs: Option<(PointerValue<'ctx>, PointerValue<'ctx>)>,
+    /// 安全点去优化元数据。
+    deopt: DeoptInfo,
+    /// 为 AOT 编译生成代码，见 `compile_method`。
+    aot: bool,
+    /// AOT 代码中保存有效标志地址的全局变量；JIT 代码为 None。
+    code_valid: Option<PointerValue<'ctx>>,
+    /// 循环头（回边的目标），块入口处插入 safepoint 轮询。
+    loop_headers: HashSet<usize>,
+    /// 遇到了不支持的指令，放弃编译。
+    failed: bool,
 }
 
 impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
+    /// 生成方法体：参数存入 locals，翻译全部 bytecode，最后生成返回块。
+    ///
+    /// `params` 是函数参数（含 this）的值类型。遇到不支持的指令时返回 false。
+    fn build_body(&mut self, params: &[ValueKind]) -> bool {
+        // entry block 只放 alloca、参数和方法入口的 safepoint 轮询。
+        // bci 0 可能是循环头，而 entry block 不能有前驱
+        let entry_bb = self.context.prepend_basic_block(self.bb_map[&0], "entry");
+        self.builder.position_at_end(entry_bb);
+
+        let mut pos = 0;
+        for (param, &kind) in self.function.get_param_iter().zip(params) {
+            self.store_local(pos, kind, param);
+            pos += slot_width(kind);
+        }
+        if let Some(kind) = self.ret_kind {
+            self.ret_slot = Some(self.build_entry_alloca(self.kind_type(kind), "ret_val"));
+        }
+
+        self.build_safepoint_poll();
+        self.builder
+            .build_unconditional_branch(self.bb_map[&0])
+            .expect("entry branch failed");
+        self.translate_remaining_blocks();
+
+        // return block：*return 与去优化都把返回值写在 ret_slot
+        self.builder.position_at_end(self.return_bb);
+        match (self.ret_kind, self.ret_slot) {
+            (Some(kind), Some(ret_slot)) => {
+                let v = self
+                    .builder
+                    .build_load(self.kind_type(kind), ret_slot, "ret")
+                    .expect("load return value failed");
+                self.builder
+                    .build_return(Some(&v))
+                    .expect("build_return failed");
+            }
+            _ => {
+                self.builder
+                    .build_return(None)
+                    .expect("build_return failed");
+            }
+        }
+        !self.failed
+    }
+
     /// 主翻译循环。
     fn translate_bytecode(&mut self, start_pc: usize) {
         let mut pc = start_pc;
 
         loop {
-            if pc >= self.bytecode.len() {
-                self.builder
-                    .build_unconditional_branch(self.return_bb)
-                    .expect("branch failed");
-                break;
+            // 顺序执行进入另一个基本块：跳过去，由它自己翻译，不重复生成代码
+            if pc != start_pc && self.bb_map.contains_key(&pc) {
+                self.branch_to(pc);
+                return;
             }
 
+            // 没有帧状态说明该指令不可达（或越过了方法末尾）
+            let depth = match self.frame_states.get(&pc) {
+                Some(state) => state.stack.len(),
+                None => {
+                    self.builder
+                        .build_unreachable()
+                        .expect("unreachable failed");
+                    return;
+                }
+            };
+            self.depth = depth;
+            self.cur_pc = pc;
             let opcode = self.bytecode[pc];
             let opcode = OpCode::from(opcode);
 
@@ -319,45 +579,45 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // ============================================================
                 OpCode::iload => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.load_local_int(idx);
+                    self.push_local(idx, ValueKind::Int);
                     pc += 2;
                 }
                 OpCode::iload_0 => {
-                    self.load_local_int(0);
+                    self.push_local(0, ValueKind::Int);
                     pc += 1;
                 }
                 OpCode::iload_1 => {
-                    self.load_local_int(1);
+                    self.push_local(1, ValueKind::Int);
                     pc += 1;
                 }
                 OpCode::iload_2 => {
-                    self.load_local_int(2);
+                    self.push_local(2, ValueKind::Int);
                     pc += 1;
                 }
                 OpCode::iload_3 => {
-                    self.load_local_int(3);
+                    self.push_local(3, ValueKind::Int);
                     pc += 1;
                 }
 
                 OpCode::istore => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.store_local_int(idx);
+                    self.pop_to_local(idx, ValueKind::Int);
                     pc += 2;
                 }
                 OpCode::istore_0 => {
-                    self.store_local_int(0);
+                    self.pop_to_local(0, ValueKind::Int);
                     pc += 1;
                 }
                 OpCode::istore_1 => {
-                    self.store_local_int(1);
+                    self.pop_to_local(1, ValueKind::Int);
                     pc += 1;
                 }
                 OpCode::istore_2 => {
-                    self.store_local_int(2);
+                    self.pop_to_local(2, ValueKind::Int);
                     pc += 1;
                 }
                 OpCode::istore_3 => {
-                    self.store_local_int(3);
+                    self.pop_to_local(3, ValueKind::Int);
                     pc += 1;
                 }
 
@@ -366,44 +626,44 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // ============================================================
                 OpCode::aload => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.load_local_ref(idx);
+                    self.push_local(idx, ValueKind::Ref);
                     pc += 2;
                 }
                 OpCode::aload_0 => {
-                    self.load_local_ref(0);
+                    self.push_local(0, ValueKind::Ref);
                     pc += 1;
                 }
                 OpCode::aload_1 => {
-                    self.load_local_ref(1);
+                    self.push_local(1, ValueKind::Ref);
                     pc += 1;
                 }
                 OpCode::aload_2 => {
-                    self.load_local_ref(2);
+                    self.push_local(2, ValueKind::Ref);
                     pc += 1;
                 }
                 OpCode::aload_3 => {
-                    self.load_local_ref(3);
+                    self.push_local(3, ValueKind::Ref);
                     pc += 1;
                 }
                 OpCode::astore => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.store_local_ref(idx);
+                    self.pop_to_local(idx, ValueKind::Ref);
                     pc += 2;
                 }
                 OpCode::astore_0 => {
-                    self.store_local_ref(0);
+                    self.pop_to_local(0, ValueKind::Ref);
                     pc += 1;
                 }
                 OpCode::astore_1 => {
-                    self.store_local_ref(1);
+                    self.pop_to_local(1, ValueKind::Ref);
                     pc += 1;
                 }
                 OpCode::astore_2 => {
-                    self.store_local_ref(2);
+                    self.pop_to_local(2, ValueKind::Ref);
                     pc += 1;
                 }
                 OpCode::astore_3 => {
-                    self.store_local_ref(3);
+                    self.pop_to_local(3, ValueKind::Ref);
                     pc += 1;
                 }
 
@@ -412,44 +672,44 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // ============================================================
                 OpCode::fload => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.load_local_float(idx);
+                    self.push_local(idx, ValueKind::Float);
                     pc += 2;
                 }
                 OpCode::fload_0 => {
-                    self.load_local_float(0);
+                    self.push_local(0, ValueKind::Float);
                     pc += 1;
                 }
                 OpCode::fload_1 => {
-                    self.load_local_float(1);
+                    self.push_local(1, ValueKind::Float);
                     pc += 1;
                 }
                 OpCode::fload_2 => {
-                    self.load_local_float(2);
+                    self.push_local(2, ValueKind::Float);
                     pc += 1;
                 }
                 OpCode::fload_3 => {
-                    self.load_local_float(3);
+                    self.push_local(3, ValueKind::Float);
                     pc += 1;
                 }
                 OpCode::fstore => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.store_local_float(idx);
+                    self.pop_to_local(idx, ValueKind::Float);
                     pc += 2;
                 }
                 OpCode::fstore_0 => {
-                    self.store_local_float(0);
+                    self.pop_to_local(0, ValueKind::Float);
                     pc += 1;
                 }
                 OpCode::fstore_1 => {
-                    self.store_local_float(1);
+                    self.pop_to_local(1, ValueKind::Float);
                     pc += 1;
                 }
                 OpCode::fstore_2 => {
-                    self.store_local_float(2);
+                    self.pop_to_local(2, ValueKind::Float);
                     pc += 1;
                 }
                 OpCode::fstore_3 => {
-                    self.store_local_float(3);
+                    self.pop_to_local(3, ValueKind::Float);
                     pc += 1;
                 }
 
@@ -458,44 +718,44 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // ============================================================
                 OpCode::dload => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.load_local_double(idx);
+                    self.push_local(idx, ValueKind::Double);
                     pc += 2;
                 }
                 OpCode::dload_0 => {
-                    self.load_local_double(0);
+                    self.push_local(0, ValueKind::Double);
                     pc += 1;
                 }
                 OpCode::dload_1 => {
-                    self.load_local_double(1);
+                    self.push_local(1, ValueKind::Double);
                     pc += 1;
                 }
                 OpCode::dload_2 => {
-                    self.load_local_double(2);
+                    self.push_local(2, ValueKind::Double);
                     pc += 1;
                 }
                 OpCode::dload_3 => {
-                    self.load_local_double(3);
+                    self.push_local(3, ValueKind::Double);
                     pc += 1;
                 }
                 OpCode::dstore => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.store_local_double(idx);
+                    self.pop_to_local(idx, ValueKind::Double);
                     pc += 2;
                 }
                 OpCode::dstore_0 => {
-                    self.store_local_double(0);
+                    self.pop_to_local(0, ValueKind::Double);
                     pc += 1;
                 }
                 OpCode::dstore_1 => {
-                    self.store_local_double(1);
+                    self.pop_to_local(1, ValueKind::Double);
                     pc += 1;
                 }
                 OpCode::dstore_2 => {
-                    self.store_local_double(2);
+                    self.pop_to_local(2, ValueKind::Double);
                     pc += 1;
                 }
                 OpCode::dstore_3 => {
-                    self.store_local_double(3);
+                    self.pop_to_local(3, ValueKind::Double);
                     pc += 1;
                 }
 
@@ -535,20 +795,14 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 OpCode::idiv => {
                     let v2 = self.pop_int();
                     let v1 = self.pop_int();
-                    let result = self
-                        .builder
-                        .build_int_signed_div(v1, v2, "div")
-                        .expect("idiv failed");
+                    let result = self.build_int_div(v1, v2, false);
                     self.push_int_val(result);
                     pc += 1;
                 }
                 OpCode::irem => {
                     let v2 = self.pop_int();
                     let v1 = self.pop_int();
-                    let result = self
-                        .builder
-                        .build_int_signed_rem(v1, v2, "rem")
-                        .expect("irem failed");
+                    let result = self.build_int_div(v1, v2, true);
                     self.push_int_val(result);
                     pc += 1;
                 }
@@ -572,44 +826,44 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 }
                 OpCode::lload => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.load_local_long(idx);
+                    self.push_local(idx, ValueKind::Long);
                     pc += 2;
                 }
                 OpCode::lload_0 => {
-                    self.load_local_long(0);
+                    self.push_local(0, ValueKind::Long);
                     pc += 1;
                 }
                 OpCode::lload_1 => {
-                    self.load_local_long(1);
+                    self.push_local(1, ValueKind::Long);
                     pc += 1;
                 }
                 OpCode::lload_2 => {
-                    self.load_local_long(2);
+                    self.push_local(2, ValueKind::Long);
                     pc += 1;
                 }
                 OpCode::lload_3 => {
-                    self.load_local_long(3);
+                    self.push_local(3, ValueKind::Long);
                     pc += 1;
                 }
                 OpCode::lstore => {
                     let idx = self.bytecode[pc + 1] as usize;
-                    self.store_local_long(idx);
+                    self.pop_to_local(idx, ValueKind::Long);
                     pc += 2;
                 }
                 OpCode::lstore_0 => {
-                    self.store_local_long(0);
+                    self.pop_to_local(0, ValueKind::Long);
                     pc += 1;
                 }
                 OpCode::lstore_1 => {
-                    self.store_local_long(1);
+                    self.pop_to_local(1, ValueKind::Long);
                     pc += 1;
                 }
                 OpCode::lstore_2 => {
-                    self.store_local_long(2);
+                    self.pop_to_local(2, ValueKind::Long);
                     pc += 1;
                 }
                 OpCode::lstore_3 => {
-                    self.store_local_long(3);
+                    self.pop_to_local(3, ValueKind::Long);
                     pc += 1;
                 }
                 OpCode::ladd => {
@@ -645,20 +899,14 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 OpCode::ldiv => {
                     let v2 = self.pop_long();
                     let v1 = self.pop_long();
-                    let result = self
-                        .builder
-                        .build_int_signed_div(v1, v2, "ldiv")
-                        .expect("ldiv failed");
+                    let result = self.build_int_div(v1, v2, false);
                     self.push_long_val(result);
                     pc += 1;
                 }
                 OpCode::lrem => {
                     let v2 = self.pop_long();
                     let v1 = self.pop_long();
-                    let result = self
-                        .builder
-                        .build_int_signed_rem(v1, v2, "lrem")
-                        .expect("lrem failed");
+                    let result = self.build_int_div(v1, v2, true);
                     self.push_long_val(result);
                     pc += 1;
                 }
@@ -690,9 +938,13 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     pc += 1;
                 }
                 OpCode::lshl => {
-                    let v2 = self.pop_long();
+                    // 移位量是 int，只取低 6 位
+                    let v2 = self.pop_int();
+                    let v2 = self
+                        .builder
+                        .build_int_z_extend(v2, self.context.i64_type(), "lshl_amount")
+                        .expect("zext failed");
                     let v1 = self.pop_long();
-                    // Mask: only low 6 bits are used (mask with 63)
                     let mask = self
                         .builder
                         .build_and(
@@ -709,7 +961,12 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     pc += 1;
                 }
                 OpCode::lshr => {
-                    let v2 = self.pop_long();
+                    // 移位量是 int，只取低 6 位
+                    let v2 = self.pop_int();
+                    let v2 = self
+                        .builder
+                        .build_int_z_extend(v2, self.context.i64_type(), "lshr_amount")
+                        .expect("zext failed");
                     let v1 = self.pop_long();
                     let mask = self
                         .builder
@@ -727,7 +984,12 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     pc += 1;
                 }
                 OpCode::lushr => {
-                    let v2 = self.pop_long();
+                    // 移位量是 int，只取低 6 位
+                    let v2 = self.pop_int();
+                    let v2 = self
+                        .builder
+                        .build_int_z_extend(v2, self.context.i64_type(), "lushr_amount")
+                        .expect("zext failed");
                     let v1 = self.pop_long();
                     let mask = self
                         .builder
@@ -773,66 +1035,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     pc += 1;
                 }
                 OpCode::lreturn => {
-                    let val = self.pop_long();
-                    if let Some(stack_param) = self.stack_param {
-                        let i64_type = self.context.i64_type();
-                        let i32_type = self.context.i32_type();
-                        let bitcast_val = self
-                            .builder
-                            .build_bit_cast(val, i64_type, "lret_bitcast")
-                            .expect("lret bitcast failed")
-                            .into_int_value();
-                        let lo = self
-                            .builder
-                            .build_int_truncate(bitcast_val, i32_type, "lret_lo")
-                            .expect("lret lo trunc failed");
-                        let hi = self
-                            .builder
-                            .build_int_truncate(
-                                self.builder
-                                    .build_right_shift(
-                                        bitcast_val,
-                                        i64_type.const_int(32, false),
-                                        false,
-                                        "lret_hi_shift",
-                                    )
-                                    .expect("lret hi shift failed"),
-                                i32_type,
-                                "lret_hi",
-                            )
-                            .expect("lret hi trunc failed");
-                        let idx_lo = i32_type.const_int(0, false);
-                        let idx_hi = i32_type.const_int(1, false);
-                        let ptr_lo = unsafe {
-                            self.builder
-                                .build_in_bounds_gep(
-                                    i32_type,
-                                    stack_param,
-                                    &[idx_lo],
-                                    "lret_store_lo",
-                                )
-                                .expect("lret gep failed")
-                        };
-                        let ptr_hi = unsafe {
-                            self.builder
-                                .build_in_bounds_gep(
-                                    i32_type,
-                                    stack_param,
-                                    &[idx_hi],
-                                    "lret_store_hi",
-                                )
-                                .expect("lret gep failed")
-                        };
-                        self.builder
-                            .build_store(ptr_lo, lo)
-                            .expect("lret store lo failed");
-                        self.builder
-                            .build_store(ptr_hi, hi)
-                            .expect("lret store hi failed");
-                    }
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("lreturn branch failed");
+                    self.build_return(Some(ValueKind::Long));
                     return;
                 }
 
@@ -1006,91 +1209,11 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     pc += 1;
                 }
                 OpCode::freturn => {
-                    let val = self.pop_float();
-                    if let Some(stack_param) = self.stack_param {
-                        let i32_type = self.context.i32_type();
-                        let bitcast = self
-                            .builder
-                            .build_bit_cast(val, i32_type, "fret_bitcast")
-                            .expect("fret bitcast failed")
-                            .into_int_value();
-                        let idx = i32_type.const_int(0, false);
-                        let ptr = unsafe {
-                            self.builder
-                                .build_in_bounds_gep(i32_type, stack_param, &[idx], "fret_store")
-                                .expect("fret gep failed")
-                        };
-                        self.builder
-                            .build_store(ptr, bitcast)
-                            .expect("fret store failed");
-                    }
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("freturn branch failed");
+                    self.build_return(Some(ValueKind::Float));
                     return;
                 }
                 OpCode::dreturn => {
-                    let val = self.pop_double();
-                    if let Some(stack_param) = self.stack_param {
-                        let f64_type = self.context.f64_type();
-                        let i64_type = self.context.i64_type();
-                        let i32_type = self.context.i32_type();
-                        let bitcast_val = self
-                            .builder
-                            .build_bit_cast(val, i64_type, "dret_bitcast")
-                            .expect("dret bitcast failed")
-                            .into_int_value();
-                        let lo = self
-                            .builder
-                            .build_int_truncate(bitcast_val, i32_type, "dret_lo")
-                            .expect("dret lo trunc failed");
-                        let hi = self
-                            .builder
-                            .build_int_truncate(
-                                self.builder
-                                    .build_right_shift(
-                                        bitcast_val,
-                                        i64_type.const_int(32, false),
-                                        false,
-                                        "dret_hi_shift",
-                                    )
-                                    .expect("dret hi shift failed"),
-                                i32_type,
-                                "dret_hi",
-                            )
-                            .expect("dret hi trunc failed");
-                        let idx_lo = i32_type.const_int(0, false);
-                        let idx_hi = i32_type.const_int(1, false);
-                        let ptr_lo = unsafe {
-                            self.builder
-                                .build_in_bounds_gep(
-                                    i32_type,
-                                    stack_param,
-                                    &[idx_lo],
-                                    "dret_store_lo",
-                                )
-                                .expect("dret gep failed")
-                        };
-                        let ptr_hi = unsafe {
-                            self.builder
-                                .build_in_bounds_gep(
-                                    i32_type,
-                                    stack_param,
-                                    &[idx_hi],
-                                    "dret_store_hi",
-                                )
-                                .expect("dret gep failed")
-                        };
-                        self.builder
-                            .build_store(ptr_lo, lo)
-                            .expect("dret store lo failed");
-                        self.builder
-                            .build_store(ptr_hi, hi)
-                            .expect("dret store hi failed");
-                    }
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("dreturn branch failed");
+                    self.build_return(Some(ValueKind::Double));
                     return;
                 }
 
@@ -1098,22 +1221,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // Reference return
                 // ============================================================
                 OpCode::areturn => {
-                    let val = self.pop_int(); // slot_id
-                    if let Some(stack_param) = self.stack_param {
-                        let i32_type = self.context.i32_type();
-                        let idx = i32_type.const_int(0, false);
-                        let ptr = unsafe {
-                            self.builder
-                                .build_in_bounds_gep(i32_type, stack_param, &[idx], "aret_store")
-                                .expect("aret gep failed")
-                        };
-                        self.builder
-                            .build_store(ptr, val)
-                            .expect("aret store failed");
-                    }
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("areturn branch failed");
+                    self.build_return(Some(ValueKind::Ref));
                     return;
                 }
 
@@ -1123,28 +1231,16 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 OpCode::iinc => {
                     let idx = self.bytecode[pc + 1] as usize;
                     let const_byte = self.bytecode[pc + 2] as i8 as i32;
-                    if idx < self.local_vars.len() {
-                        let current = self
-                            .builder
-                            .build_load(
-                                self.context.i32_type(),
-                                self.local_vars[idx],
-                                &format!("iinc_load_{}", idx),
-                            )
-                            .expect("iinc load failed")
-                            .into_int_value();
-                        let increment = self
-                            .context
-                            .i32_type()
-                            .const_int(const_byte as u64, const_byte < 0);
-                        let result = self
-                            .builder
-                            .build_int_add(current, increment, "iinc_add")
-                            .expect("iinc add failed");
-                        self.builder
-                            .build_store(self.local_vars[idx], result)
-                            .expect("iinc store failed");
-                    }
+                    let current = self.load_local(idx, ValueKind::Int).into_int_value();
+                    let increment = self
+                        .context
+                        .i32_type()
+                        .const_int(const_byte as u64, const_byte < 0);
+                    let result = self
+                        .builder
+                        .build_int_add(current, increment, "iinc_add")
+                        .expect("iinc add failed");
+                    self.store_local(idx, ValueKind::Int, result.into());
                     pc += 3;
                 }
 
@@ -1243,7 +1339,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let v = self.pop_int();
                     let result = self
                         .builder
-                        .build_unsigned_int_to_float(v, self.context.f32_type(), "i2f")
+                        .build_signed_int_to_float(v, self.context.f32_type(), "i2f")
                         .expect("i2f failed");
                     self.push_float_val(result);
                     pc += 1;
@@ -1252,7 +1348,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let v = self.pop_int();
                     let result = self
                         .builder
-                        .build_unsigned_int_to_float(v, self.context.f64_type(), "i2d")
+                        .build_signed_int_to_float(v, self.context.f64_type(), "i2d")
                         .expect("i2d failed");
                     self.push_double_val(result);
                     pc += 1;
@@ -1270,7 +1366,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let v = self.pop_long();
                     let result = self
                         .builder
-                        .build_unsigned_int_to_float(v, self.context.f32_type(), "l2f")
+                        .build_signed_int_to_float(v, self.context.f32_type(), "l2f")
                         .expect("l2f failed");
                     self.push_float_val(result);
                     pc += 1;
@@ -1279,26 +1375,20 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let v = self.pop_long();
                     let result = self
                         .builder
-                        .build_unsigned_int_to_float(v, self.context.f64_type(), "l2d")
+                        .build_signed_int_to_float(v, self.context.f64_type(), "l2d")
                         .expect("l2d failed");
                     self.push_double_val(result);
                     pc += 1;
                 }
                 OpCode::f2i => {
                     let v = self.pop_float();
-                    let result = self
-                        .builder
-                        .build_float_to_unsigned_int(v, self.context.i32_type(), "f2i")
-                        .expect("f2i failed");
+                    let result = self.build_float_to_int(v, self.context.i32_type(), "f2i");
                     self.push_int_val(result);
                     pc += 1;
                 }
                 OpCode::f2l => {
                     let v = self.pop_float();
-                    let result = self
-                        .builder
-                        .build_float_to_unsigned_int(v, self.context.i64_type(), "f2l")
-                        .expect("f2l failed");
+                    let result = self.build_float_to_int(v, self.context.i64_type(), "f2l");
                     self.push_long_val(result);
                     pc += 1;
                 }
@@ -1313,19 +1403,13 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 }
                 OpCode::d2i => {
                     let v = self.pop_double();
-                    let result = self
-                        .builder
-                        .build_float_to_unsigned_int(v, self.context.i32_type(), "d2i")
-                        .expect("d2i failed");
+                    let result = self.build_float_to_int(v, self.context.i32_type(), "d2i");
                     self.push_int_val(result);
                     pc += 1;
                 }
                 OpCode::d2l => {
                     let v = self.pop_double();
-                    let result = self
-                        .builder
-                        .build_float_to_unsigned_int(v, self.context.i64_type(), "d2l")
-                        .expect("d2l failed");
+                    let result = self.build_float_to_int(v, self.context.i64_type(), "d2l");
                     self.push_long_val(result);
                     pc += 1;
                 }
@@ -1621,8 +1705,8 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let offset =
                         i16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]) as i32;
                     let target = (pc as i32 + offset) as usize;
-                    let v2 = self.pop_int();
-                    let v1 = self.pop_int();
+                    let v2 = self.pop_ref();
+                    let v1 = self.pop_ref();
                     let cond = self
                         .builder
                         .build_int_compare(inkwell::IntPredicate::EQ, v1, v2, "acmpeq")
@@ -1638,8 +1722,8 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let offset =
                         i16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]) as i32;
                     let target = (pc as i32 + offset) as usize;
-                    let v2 = self.pop_int();
-                    let v1 = self.pop_int();
+                    let v2 = self.pop_ref();
+                    let v1 = self.pop_ref();
                     let cond = self
                         .builder
                         .build_int_compare(inkwell::IntPredicate::NE, v1, v2, "acmpne")
@@ -1659,7 +1743,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let offset =
                         i16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]) as i32;
                     let target = (pc as i32 + offset) as usize;
-                    let v = self.pop_int();
+                    let v = self.pop_ref();
                     let zero = self.context.i32_type().const_int(0, false);
                     let cond = self
                         .builder
@@ -1676,7 +1760,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let offset =
                         i16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]) as i32;
                     let target = (pc as i32 + offset) as usize;
-                    let v = self.pop_int();
+                    let v = self.pop_ref();
                     let zero = self.context.i32_type().const_int(0, false);
                     let cond = self
                         .builder
@@ -1694,33 +1778,11 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // 返回指令
                 // ============================================================
                 OpCode::ireturn => {
-                    let val = self.pop_int();
-                    // 将返回值写入调用方的 stack 缓冲区
-                    if let Some(stack_param) = self.stack_param {
-                        let idx = self.context.i32_type().const_int(0, false);
-                        let ptr = unsafe {
-                            self.builder
-                                .build_in_bounds_gep(
-                                    self.context.i32_type(),
-                                    stack_param,
-                                    &[idx],
-                                    "ret_store",
-                                )
-                                .expect("ireturn gep failed")
-                        };
-                        self.builder
-                            .build_store(ptr, val)
-                            .expect("ireturn store failed");
-                    }
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("ireturn branch failed");
+                    self.build_return(Some(ValueKind::Int));
                     return;
                 }
                 OpCode::return_void => {
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("return branch failed");
+                    self.build_return(None);
                     return;
                 }
 
@@ -1728,76 +1790,39 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // 栈操作指令
                 // ============================================================
                 OpCode::pop => {
-                    let _val = self.pop_int();
+                    self.shuffle_stack(1, &[]);
                     pc += 1;
                 }
                 OpCode::pop2 => {
-                    let _val = self.pop_int();
-                    let _val2 = self.pop_int();
+                    self.shuffle_stack(2, &[]);
                     pc += 1;
                 }
                 OpCode::dup => {
-                    let val = self.pop_int();
-                    self.push_int_val(val);
-                    self.push_int_val(val);
+                    self.shuffle_stack(1, &[0, 0]);
                     pc += 1;
                 }
                 OpCode::dup_x1 => {
-                    let val1 = self.pop_int();
-                    let val2 = self.pop_int();
-                    self.push_int_val(val1);
-                    self.push_int_val(val2);
-                    self.push_int_val(val1);
+                    self.shuffle_stack(2, &[1, 0, 1]);
                     pc += 1;
                 }
                 OpCode::dup_x2 => {
-                    let val1 = self.pop_int();
-                    let val2 = self.pop_int();
-                    let val3 = self.pop_int();
-                    self.push_int_val(val1);
-                    self.push_int_val(val3);
-                    self.push_int_val(val2);
-                    self.push_int_val(val1);
+                    self.shuffle_stack(3, &[2, 0, 1, 2]);
                     pc += 1;
                 }
                 OpCode::dup2 => {
-                    let val1 = self.pop_int();
-                    let val2 = self.pop_int();
-                    self.push_int_val(val2);
-                    self.push_int_val(val1);
-                    self.push_int_val(val2);
-                    self.push_int_val(val1);
+                    self.shuffle_stack(2, &[0, 1, 0, 1]);
                     pc += 1;
                 }
                 OpCode::dup2_x1 => {
-                    let val1 = self.pop_int();
-                    let val2 = self.pop_int();
-                    let val3 = self.pop_int();
-                    self.push_int_val(val2);
-                    self.push_int_val(val1);
-                    self.push_int_val(val3);
-                    self.push_int_val(val2);
-                    self.push_int_val(val1);
+                    self.shuffle_stack(3, &[1, 2, 0, 1, 2]);
                     pc += 1;
                 }
                 OpCode::dup2_x2 => {
-                    let val1 = self.pop_int();
-                    let val2 = self.pop_int();
-                    let val3 = self.pop_int();
-                    let val4 = self.pop_int();
-                    self.push_int_val(val2);
-                    self.push_int_val(val1);
-                    self.push_int_val(val4);
-                    self.push_int_val(val3);
-                    self.push_int_val(val2);
-                    self.push_int_val(val1);
+                    self.shuffle_stack(4, &[2, 3, 0, 1, 2, 3]);
                     pc += 1;
                 }
                 OpCode::swap => {
-                    let val1 = self.pop_int();
-                    let val2 = self.pop_int();
-                    self.push_int_val(val1);
-                    self.push_int_val(val2);
+                    self.shuffle_stack(2, &[1, 0]);
                     pc += 1;
                 }
 
@@ -1813,7 +1838,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // ============================================================
                 OpCode::aconst_null => {
                     // null 引用用 slot_id = 0 表示
-                    self.push_int_val(self.context.i32_type().const_int(0, false));
+                    self.push_ref(self.context.i32_type().const_zero());
                     pc += 1;
                 }
 
@@ -1822,30 +1847,29 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // ============================================================
                 OpCode::invokevirtual => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
-                    self.call_invoke_runtime("jit_invoke_virtual", cp_idx);
+                    self.translate_invoke_virtual(cp_idx);
                     pc += 3;
                 }
                 OpCode::invokespecial => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
-                    self.call_invoke_runtime("jit_invoke_special", cp_idx);
+                    self.translate_invoke_static_bound(cp_idx, "jit_invoke_special", true);
                     pc += 3;
                 }
                 OpCode::invokestatic => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
-                    self.call_invoke_runtime("jit_invoke_static", cp_idx);
+                    self.translate_invoke_static_bound(cp_idx, "jit_invoke_static", false);
                     pc += 3;
                 }
                 OpCode::invokeinterface => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
+                    // 接口调用无法用 CHA 去虚化，只走内联缓存
+                    let ic = self.new_inline_cache();
+                    self.call_invoke_runtime("jit_invoke_interface", cp_idx, true, ic);
                     // 跳过 count 和 zero 字节
-                    self.call_invoke_runtime("jit_invoke_interface", cp_idx);
-                    pc += 4;
+                    pc += 5;
                 }
                 OpCode::invokedynamic => {
-                    warn!("JIT: invokedynamic not supported, falling back");
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("fallback branch failed");
+                    self.fail(opcode);
                     return;
                 }
 
@@ -1973,12 +1997,12 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     } else {
                         u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]])
                     };
-                    self.call_ldc_runtime(cp_idx);
+                    self.translate_ldc(cp_idx);
                     pc += if opcode == OpCode::ldc { 2 } else { 3 };
                 }
                 OpCode::ldc2_w => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
-                    self.call_ldc2_w_runtime(cp_idx);
+                    self.translate_ldc(cp_idx);
                     pc += 3;
                 }
 
@@ -1986,7 +2010,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 OpCode::new => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
                     let slot_id = self.call_new_inst_runtime(cp_idx);
-                    self.push_int_val(slot_id);
+                    self.push_ref(slot_id);
                     pc += 3;
                 }
 
@@ -1995,20 +2019,20 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                     let ary_type = self.bytecode[pc + 1];
                     let size = self.pop_int();
                     let slot_id = self.call_new_array_runtime(ary_type, size);
-                    self.push_int_val(slot_id);
+                    self.push_ref(slot_id);
                     pc += 2;
                 }
                 OpCode::anewarray => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
                     let size = self.pop_int();
                     let slot_id = self.call_anewarray_runtime(cp_idx, size);
-                    self.push_int_val(slot_id);
+                    self.push_ref(slot_id);
                     pc += 3;
                 }
 
                 // --- 数组长度 ---
                 OpCode::arraylength => {
-                    let obj = self.pop_int();
+                    let obj = self.pop_ref();
                     let len = self.call_array_length_runtime(obj);
                     self.push_int_val(len);
                     pc += 1;
@@ -2017,15 +2041,15 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // --- 类型检查 ---
                 OpCode::checkcast => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
-                    // checkcast peeks without popping; read stack_top - 1
-                    let obj = self.peek_stack_int();
+                    // checkcast 只检查栈顶，不弹出
+                    let obj = self.pop_ref();
+                    self.push_ref(obj);
                     self.call_checkcast_runtime(cp_idx, obj);
                     pc += 3;
                 }
                 OpCode::instanceof => {
                     let cp_idx = u16::from_be_bytes([self.bytecode[pc + 1], self.bytecode[pc + 2]]);
-                    // Pop object, push result
-                    let obj = self.pop_int();
+                    let obj = self.pop_ref();
                     let result = self.call_instanceof_runtime(cp_idx, obj);
                     self.push_int_val(result);
                     pc += 3;
@@ -2058,134 +2082,127 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // --- 数组加载 ---
                 OpCode::iaload => {
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_iaload_runtime(array, index);
+                    let array = self.pop_ref();
+                    self.call_array_load_runtime("jit_iaload", ValueKind::Int, array, index);
                     pc += 1;
                 }
                 OpCode::laload => {
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_laload_runtime(array, index);
+                    let array = self.pop_ref();
+                    self.call_array_load_runtime("jit_laload", ValueKind::Long, array, index);
                     pc += 1;
                 }
                 OpCode::faload => {
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_faload_runtime(array, index);
+                    let array = self.pop_ref();
+                    self.call_array_load_runtime("jit_faload", ValueKind::Float, array, index);
                     pc += 1;
                 }
                 OpCode::daload => {
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_daload_runtime(array, index);
+                    let array = self.pop_ref();
+                    self.call_array_load_runtime("jit_daload", ValueKind::Double, array, index);
                     pc += 1;
                 }
                 OpCode::aaload => {
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_aaload_runtime(array, index);
+                    let array = self.pop_ref();
+                    self.call_array_load_runtime("jit_aaload", ValueKind::Ref, array, index);
                     pc += 1;
                 }
                 OpCode::baload => {
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_baload_runtime(array, index);
+                    let array = self.pop_ref();
+                    self.call_array_load_runtime("jit_baload", ValueKind::Int, array, index);
                     pc += 1;
                 }
                 OpCode::caload => {
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_caload_runtime(array, index);
+                    let array = self.pop_ref();
+                    self.call_array_load_runtime("jit_caload", ValueKind::Int, array, index);
                     pc += 1;
                 }
                 OpCode::saload => {
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_saload_runtime(array, index);
+                    let array = self.pop_ref();
+                    self.call_array_load_runtime("jit_saload", ValueKind::Int, array, index);
                     pc += 1;
                 }
 
                 // --- 数组存储 ---
                 OpCode::iastore => {
-                    let value = self.pop_int();
+                    let value = self.pop(ValueKind::Int);
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_iastore_runtime(array, index, value);
+                    let array = self.pop_ref();
+                    self.call_array_store_runtime("jit_iastore", array, index, value);
                     pc += 1;
                 }
                 OpCode::lastore => {
-                    let val_hi = self.pop_int();
-                    let val_lo = self.pop_int();
+                    let value = self.pop(ValueKind::Long);
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_lastore_runtime(array, index, val_lo, val_hi);
+                    let array = self.pop_ref();
+                    self.call_array_store_runtime("jit_lastore", array, index, value);
                     pc += 1;
                 }
                 OpCode::fastore => {
-                    let value = self.pop_int();
+                    let value = self.pop(ValueKind::Float);
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_fastore_runtime(array, index, value);
+                    let array = self.pop_ref();
+                    self.call_array_store_runtime("jit_fastore", array, index, value);
                     pc += 1;
                 }
                 OpCode::dastore => {
-                    let val_hi = self.pop_int();
-                    let val_lo = self.pop_int();
+                    let value = self.pop(ValueKind::Double);
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_dastore_runtime(array, index, val_lo, val_hi);
+                    let array = self.pop_ref();
+                    self.call_array_store_runtime("jit_dastore", array, index, value);
                     pc += 1;
                 }
                 OpCode::aastore => {
-                    let value = self.pop_int();
+                    let value = self.pop(ValueKind::Ref);
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_aastore_runtime(array, index, value);
+                    let array = self.pop_ref();
+                    self.call_array_store_runtime("jit_aastore", array, index, value);
                     pc += 1;
                 }
                 OpCode::bastore => {
-                    let value = self.pop_int();
+                    let value = self.pop(ValueKind::Int);
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_bastore_runtime(array, index, value);
+                    let array = self.pop_ref();
+                    self.call_array_store_runtime("jit_bastore", array, index, value);
                     pc += 1;
                 }
                 OpCode::castore => {
-                    let value = self.pop_int();
+                    let value = self.pop(ValueKind::Int);
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_castore_runtime(array, index, value);
+                    let array = self.pop_ref();
+                    self.call_array_store_runtime("jit_castore", array, index, value);
                     pc += 1;
                 }
                 OpCode::sastore => {
-                    let value = self.pop_int();
+                    let value = self.pop(ValueKind::Int);
                     let index = self.pop_int();
-                    let array = self.pop_int();
-                    self.call_sastore_runtime(array, index, value);
+                    let array = self.pop_ref();
+                    self.call_array_store_runtime("jit_sastore", array, index, value);
                     pc += 1;
                 }
 
                 // --- 同步 ---
                 OpCode::monitorenter => {
-                    let obj = self.pop_int();
+                    let obj = self.pop_ref();
                     self.call_monitorenter_runtime(obj);
                     pc += 1;
                 }
                 OpCode::monitorexit => {
-                    let obj = self.pop_int();
+                    let obj = self.pop_ref();
                     self.call_monitorexit_runtime(obj);
                     pc += 1;
                 }
 
                 // --- 异常 ---
                 OpCode::athrow => {
-                    // athrow: 弹出异常引用，抛出
-                    let _ex = self.pop_int();
-                    // 回退到解释器处理异常（需要完整的异常帧展开）
-                    warn!("JIT: athrow not fully supported, falling back");
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("athrow fallback branch failed");
+                    // 编译后的代码不做异常分派，含 athrow 的方法留给解释器
+                    self.fail(opcode);
                     return;
                 }
 
@@ -2193,13 +2210,7 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
                 // 未实现的 opcode —— 回退到解释器
                 // ============================================================
                 _ => {
-                    warn!(
-                        "JIT: unsupported opcode {:?} at pc={}, falling back",
-                        opcode, pc
-                    );
-                    self.builder
-                        .build_unconditional_branch(self.return_bb)
-                        .expect("fallback branch failed");
+                    self.fail(opcode);
                     return;
                 }
             }
@@ -2209,49 +2220,54 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
     // ============================================================
     // 栈操作辅助方法
     // ============================================================
+    //
+    // locals 与操作数栈的每个槽按值类型各有一个 alloca（见 `local_ptr` / `stack_ptr`），
+    // 栈深度在编译期由帧状态确定，不需要运行时的栈顶指针。
+    // long/double 只占用低位槽的 alloca，高位槽没有存储。
 
-    /// 将一个 i32 值推入操作数栈。
-    fn push_int_val(&mut self, val: IntValue<'ctx>) {
-        let top_i32 = self
-            .builder
-            .build_load(self.context.i32_type(), self.stack_top, "load_top")
-            .expect("load stack_top failed")
-            .into_int_value();
+    /// 值类型对应的 LLVM 类型。引用是 i32 slot id，但与 int 分开存放。
+    fn kind_type(&self, kind: ValueKind) -> BasicTypeEnum<'ctx> {
+        kind_type(self.context, kind)
+    }
 
-        // 用 GEP 动态计算 stack[slot_idx] 的地址
-        if let Some(stack_param) = self.stack_param {
-            let ptr = unsafe {
-                self.builder
-                    .build_in_bounds_gep(
-                        self.context.i32_type(),
-                        stack_param,
-                        &[top_i32],
-                        "push_store",
-                    )
-                    .expect("push gep failed")
-            };
-            self.builder
-                .build_store(ptr, val)
-                .expect("push_int store failed");
-        } else {
-            // 回退：使用静态 slot 索引
-            let slot_idx = top_i32.get_zero_extended_constant().unwrap_or(0) as usize;
-            if slot_idx < self.stack_vars.len() {
-                self.builder
-                    .build_store(self.stack_vars[slot_idx], val)
-                    .expect("push_int store failed");
-            }
+    /// 第 `idx` 个 local 槽存放 `kind` 类型值的 alloca，首次使用时创建。
+    fn local_ptr(&mut self, idx: usize, kind: ValueKind) -> PointerValue<'ctx> {
+        if let Some(ptr) = self.locals.get(&(idx, kind)) {
+            return *ptr;
         }
+        let ptr = self.build_entry_alloca(self.kind_type(kind), &format!("local_{}", idx));
+        self.locals.insert((idx, kind), ptr);
+        ptr
+    }
 
-        // 更新 stack_top
-        let one = self.context.i32_type().const_int(1, false);
-        let new_top = self
-            .builder
-            .build_int_add(top_i32, one, "inc_top")
-            .expect("inc_top failed");
+    /// 第 `idx` 个操作数栈槽存放 `kind` 类型值的 alloca，首次使用时创建。
+    fn stack_ptr(&mut self, idx: usize, kind: ValueKind) -> PointerValue<'ctx> {
+        if let Some(ptr) = self.stack.get(&(idx, kind)) {
+            return *ptr;
+        }
+        let ptr = self.build_entry_alloca(self.kind_type(kind), &format!("stack_{}", idx));
+        self.stack.insert((idx, kind), ptr);
+        ptr
+    }
+
+    /// 将一个 `kind` 类型的值推入操作数栈。
+    fn push(&mut self, kind: ValueKind, val: BasicValueEnum<'ctx>) {
+        let ptr = self.stack_ptr(self.depth, kind);
+        self.builder.build_store(ptr, val).expect("push failed");
+        self.depth += slot_width(kind);
+    }
+
+    /// 从操作数栈弹出一个 `kind` 类型的值。
+    fn pop(&mut self, kind: ValueKind) -> BasicValueEnum<'ctx> {
+        self.depth -= slot_width(kind);
+        let ptr = self.stack_ptr(self.depth, kind);
         self.builder
-            .build_store(self.stack_top, new_top)
-            .expect("store stack_top failed");
+            .build_load(self.kind_type(kind), ptr, "pop")
+            .expect("pop failed")
+    }
+
+    fn push_int_val(&mut self, val: IntValue<'ctx>) {
+        self.push(ValueKind::Int, val.into());
     }
 
     /// 将一个常量值推入栈的快捷方法
@@ -2260,670 +2276,129 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
         self.push_int_val(llvm_val);
     }
 
-    /// 将一个 i64 值推入操作数栈。
+    fn pop_int(&mut self) -> IntValue<'ctx> {
+        self.pop(ValueKind::Int).into_int_value()
+    }
+
     fn push_long_val(&mut self, val: IntValue<'ctx>) {
-        let i64_type = self.context.i64_type();
-        let i32_type = self.context.i32_type();
-        let bitcast_val = self
-            .builder
-            .build_bit_cast(val, i64_type, "long_bitcast")
-            .expect("bitcast failed")
-            .into_int_value();
-        let lo = self
-            .builder
-            .build_int_truncate(bitcast_val, i32_type, "lo_trunc")
-            .expect("lo trunc failed");
-        let hi = self
-            .builder
-            .build_int_truncate(
-                self.builder
-                    .build_right_shift(
-                        bitcast_val,
-                        i64_type.const_int(32, false),
-                        false,
-                        "hi_shift",
-                    )
-                    .expect("hi shift failed"),
-                i32_type,
-                "hi_i32",
-            )
-            .expect("hi_i32 trunc failed");
-        self.push_two_slots(lo, hi);
+        self.push(ValueKind::Long, val.into());
     }
 
-    /// 从操作数栈弹出一个 i64 值。
     fn pop_long(&mut self) -> IntValue<'ctx> {
-        let (lo, hi) = self.pop_two_slots();
-        let i64_type = self.context.i64_type();
-        let lo_ext = self
-            .builder
-            .build_int_z_extend(lo, i64_type, "lo_ext")
-            .expect("zext failed");
-        let hi_ext = self
-            .builder
-            .build_int_z_extend(hi, i64_type, "hi_ext")
-            .expect("zext failed");
-        let combined = self
-            .builder
-            .build_or(
-                lo_ext,
-                self.builder
-                    .build_left_shift(hi_ext, i64_type.const_int(32, false), "hi_shl")
-                    .expect("shl failed"),
-                "combined",
-            )
-            .expect("or failed");
-        self.builder
-            .build_bit_cast(combined, i64_type, "long_bitcast")
-            .expect("bitcast failed")
-            .into_int_value()
+        self.pop(ValueKind::Long).into_int_value()
     }
 
-    /// 将一个 f32 值推入操作数栈。
-    fn push_float_val(&mut self, val: inkwell::values::FloatValue<'ctx>) {
-        let i32_type = self.context.i32_type();
-        let bitcast = self
-            .builder
-            .build_bit_cast(val, i32_type, "float_bitcast")
-            .expect("bitcast failed")
-            .into_int_value();
-        self.push_one_slot(bitcast);
+    fn push_float_val(&mut self, val: FloatValue<'ctx>) {
+        self.push(ValueKind::Float, val.into());
     }
 
-    /// 从操作数栈弹出一个 f32 值。
-    fn pop_float(&mut self) -> inkwell::values::FloatValue<'ctx> {
-        let val = self.pop_one_slot();
-        self.builder
-            .build_bit_cast(val, self.context.f32_type(), "float_bitcast")
-            .expect("bitcast failed")
-            .into_float_value()
+    fn pop_float(&mut self) -> FloatValue<'ctx> {
+        self.pop(ValueKind::Float).into_float_value()
     }
 
-    /// 将一个 f64 值推入操作数栈。
-    fn push_double_val(&mut self, val: inkwell::values::FloatValue<'ctx>) {
-        let i64_type = self.context.i64_type();
-        let i32_type = self.context.i32_type();
-        let bitcast_val = self
-            .builder
-            .build_bit_cast(val, i64_type, "double_bitcast")
-            .expect("bitcast failed")
-            .into_int_value();
-        let lo = self
-            .builder
-            .build_int_truncate(bitcast_val, i32_type, "lo_trunc")
-            .expect("lo trunc failed");
-        let hi = self
-            .builder
-            .build_int_truncate(
-                self.builder
-                    .build_right_shift(
-                        bitcast_val,
-                        i64_type.const_int(32, false),
-                        false,
-                        "hi_shift",
-                    )
-                    .expect("hi shift failed"),
-                i32_type,
-                "hi_i32",
-            )
-            .expect("hi_i32 trunc failed");
-        self.push_two_slots(lo, hi);
+    fn push_double_val(&mut self, val: FloatValue<'ctx>) {
+        self.push(ValueKind::Double, val.into());
     }
 
-    /// 从操作数栈弹出一个 f64 值。
-    fn pop_double(&mut self) -> inkwell::values::FloatValue<'ctx> {
-        let (lo, hi) = self.pop_two_slots();
-        let i64_type = self.context.i64_type();
-        let lo_ext = self
-            .builder
-            .build_int_z_extend(lo, i64_type, "lo_ext")
-            .expect("zext failed");
-        let hi_ext = self
-            .builder
-            .build_int_z_extend(hi, i64_type, "hi_ext")
-            .expect("zext failed");
-        let combined = self
-            .builder
-            .build_or(
-                lo_ext,
-                self.builder
-                    .build_left_shift(hi_ext, i64_type.const_int(32, false), "hi_shl")
-                    .expect("shl failed"),
-                "combined",
-            )
-            .expect("or failed");
-        self.builder
-            .build_bit_cast(combined, i64_type, "double_bitcast")
-            .expect("bitcast failed")
-            .into_float_value()
+    fn pop_double(&mut self) -> FloatValue<'ctx> {
+        self.pop(ValueKind::Double).into_float_value()
     }
 
-    /// 推入一个 i32 到栈槽。
-    fn push_one_slot(&mut self, val: IntValue<'ctx>) {
-        let top_i32 = self
-            .builder
-            .build_load(self.context.i32_type(), self.stack_top, "load_top")
-            .expect("load stack_top failed")
-            .into_int_value();
-        if let Some(stack_param) = self.stack_param {
-            let ptr = unsafe {
-                self.builder
-                    .build_in_bounds_gep(
-                        self.context.i32_type(),
-                        stack_param,
-                        &[top_i32],
-                        "push_store",
-                    )
-                    .expect("push gep failed")
-            };
-            self.builder
-                .build_store(ptr, val)
-                .expect("push store failed");
-        }
-        let one = self.context.i32_type().const_int(1, false);
-        let new_top = self
-            .builder
-            .build_int_add(top_i32, one, "inc_top")
-            .expect("inc_top failed");
-        self.builder
-            .build_store(self.stack_top, new_top)
-            .expect("store stack_top failed");
+    /// 将一个引用（slot id，0 为 null）推入操作数栈。
+    fn push_ref(&mut self, val: IntValue<'ctx>) {
+        self.push(ValueKind::Ref, val.into());
     }
 
-    /// 推入两个 i32 到栈槽（用于 long/double）。
-    fn push_two_slots(&mut self, lo: IntValue<'ctx>, hi: IntValue<'ctx>) {
-        let top_i32 = self
-            .builder
-            .build_load(self.context.i32_type(), self.stack_top, "load_top")
-            .expect("load stack_top failed")
-            .into_int_value();
-        if let Some(stack_param) = self.stack_param {
-            let ptr_lo = unsafe {
-                self.builder
-                    .build_in_bounds_gep(
-                        self.context.i32_type(),
-                        stack_param,
-                        &[top_i32],
-                        "push_lo",
-                    )
-                    .expect("push gep failed")
-            };
-            self.builder
-                .build_store(ptr_lo, lo)
-                .expect("push lo store failed");
-            let one = self.context.i32_type().const_int(1, false);
-            let top_plus_one = self
-                .builder
-                .build_int_add(top_i32, one, "top_plus_one")
-                .expect("add failed");
-            let ptr_hi = unsafe {
-                self.builder
-                    .build_in_bounds_gep(
-                        self.context.i32_type(),
-                        stack_param,
-                        &[top_plus_one],
-                        "push_hi",
-                    )
-                    .expect("push gep failed")
-            };
-            self.builder
-                .build_store(ptr_hi, hi)
-                .expect("push hi store failed");
-        }
-        let two = self.context.i32_type().const_int(2, false);
-        let new_top = self
-            .builder
-            .build_int_add(top_i32, two, "inc_top")
-            .expect("inc_top failed");
-        self.builder
-            .build_store(self.stack_top, new_top)
-            .expect("store stack_top failed");
-    }
-
-    /// 弹出一个 i32 从栈槽。
-    fn pop_one_slot(&mut self) -> IntValue<'ctx> {
-        let top_i32 = self
-            .builder
-            .build_load(self.context.i32_type(), self.stack_top, "load_top")
-            .expect("load stack_top failed")
-            .into_int_value();
-        let one = self.context.i32_type().const_int(1, false);
-        let slot_idx_val = self
-            .builder
-            .build_int_sub(top_i32, one, "dec_top")
-            .expect("dec_top failed");
-        let val = if let Some(stack_param) = self.stack_param {
-            let ptr = unsafe {
-                self.builder
-                    .build_in_bounds_gep(
-                        self.context.i32_type(),
-                        stack_param,
-                        &[slot_idx_val],
-                        "pop_ptr",
-                    )
-                    .expect("pop gep failed")
-            };
-            self.builder
-                .build_load(self.context.i32_type(), ptr, "pop_val")
-                .expect("pop load failed")
-                .into_int_value()
-        } else {
-            slot_idx_val
-        };
-        self.builder
-            .build_store(self.stack_top, slot_idx_val)
-            .expect("pop store stack_top failed");
-        val
-    }
-
-    /// 弹出两个 i32 从栈槽（用于 long/double）。
-    fn pop_two_slots(&mut self) -> (IntValue<'ctx>, IntValue<'ctx>) {
-        let top_i32 = self
-            .builder
-            .build_load(self.context.i32_type(), self.stack_top, "load_top")
-            .expect("load stack_top failed")
-            .into_int_value();
-        let one = self.context.i32_type().const_int(1, false);
-        let two = self.context.i32_type().const_int(2, false);
-        let slot_idx_val = self
-            .builder
-            .build_int_sub(top_i32, two, "dec_top")
-            .expect("dec_top failed");
-        let hi_idx = self
-            .builder
-            .build_int_add(slot_idx_val, one, "hi_idx")
-            .expect("add failed");
-        let (lo, hi) = if let Some(stack_param) = self.stack_param {
-            let ptr_lo = unsafe {
-                self.builder
-                    .build_in_bounds_gep(
-                        self.context.i32_type(),
-                        stack_param,
-                        &[slot_idx_val],
-                        "pop_lo",
-                    )
-                    .expect("pop gep failed")
-            };
-            let loaded_lo = self
-                .builder
-                .build_load(self.context.i32_type(), ptr_lo, "pop_lo")
-                .expect("pop lo load failed")
-                .into_int_value();
-            let ptr_hi = unsafe {
-                self.builder
-                    .build_in_bounds_gep(self.context.i32_type(), stack_param, &[hi_idx], "pop_hi")
-                    .expect("pop gep failed")
-            };
-            let loaded_hi = self
-                .builder
-                .build_load(self.context.i32_type(), ptr_hi, "pop_hi")
-                .expect("pop hi load failed")
-                .into_int_value();
-            (loaded_lo, loaded_hi)
-        } else {
-            (slot_idx_val, hi_idx)
-        };
-        self.builder
-            .build_store(self.stack_top, slot_idx_val)
-            .expect("pop store stack_top failed");
-        (lo, hi)
-    }
-
-    /// 从操作数栈弹出一个 i32 值。
-    fn pop_int(&mut self) -> IntValue<'ctx> {
-        let top_i32 = self
-            .builder
-            .build_load(self.context.i32_type(), self.stack_top, "load_top")
-            .expect("pop load stack_top failed")
-            .into_int_value();
-
-        // 计算 slot_idx = top - 1
-        let one = self.context.i32_type().const_int(1, false);
-        let slot_idx_val = self
-            .builder
-            .build_int_sub(top_i32, one, "dec_top")
-            .expect("dec_top failed");
-
-        // 用 GEP 动态加载值
-        let val = if let Some(stack_param) = self.stack_param {
-            let ptr = unsafe {
-                self.builder
-                    .build_in_bounds_gep(
-                        self.context.i32_type(),
-                        stack_param,
-                        &[slot_idx_val],
-                        "pop_ptr",
-                    )
-                    .expect("pop gep failed")
-            };
-            self.builder
-                .build_load(self.context.i32_type(), ptr, "pop_val")
-                .expect("pop load failed")
-                .into_int_value()
-        } else {
-            // 回退：使用静态 slot 索引
-            let slot_idx = slot_idx_val.get_zero_extended_constant().unwrap_or(0) as usize;
-            if slot_idx < self.stack_vars.len() {
-                self.builder
-                    .build_load(
-                        self.context.i32_type(),
-                        self.stack_vars[slot_idx],
-                        "pop_val",
-                    )
-                    .expect("pop load failed")
-                    .into_int_value()
-            } else {
-                slot_idx_val
-            }
-        };
-
-        // 更新 stack_top
-        self.builder
-            .build_store(self.stack_top, slot_idx_val)
-            .expect("pop store stack_top failed");
-
-        val
+    fn pop_ref(&mut self) -> IntValue<'ctx> {
+        self.pop(ValueKind::Ref).into_int_value()
     }
 
-    /// 读取栈顶的 i32 值但不弹出（用于 checkcast 等指令）。
-    fn peek_stack_int(&mut self) -> IntValue<'ctx> {
-        let top_i32 = self
-            .builder
-            .build_load(self.context.i32_type(), self.stack_top, "peek_load_top")
-            .expect("peek load stack_top failed")
-            .into_int_value();
-
-        // 计算 slot_idx = top - 1
-        let one = self.context.i32_type().const_int(1, false);
-        let slot_idx_val = self
-            .builder
-            .build_int_sub(top_i32, one, "peek_slot")
-            .expect("peek dec_top failed");
-
-        if let Some(stack_param) = self.stack_param {
-            let ptr = unsafe {
-                self.builder
-                    .build_in_bounds_gep(
-                        self.context.i32_type(),
-                        stack_param,
-                        &[slot_idx_val],
-                        "peek_ptr",
-                    )
-                    .expect("peek gep failed")
-            };
-            self.builder
-                .build_load(self.context.i32_type(), ptr, "peek_val")
-                .expect("peek load failed")
-                .into_int_value()
-        } else {
-            let slot_idx = slot_idx_val.get_zero_extended_constant().unwrap_or(0) as usize;
-            if slot_idx < self.stack_vars.len() {
-                self.builder
-                    .build_load(
-                        self.context.i32_type(),
-                        self.stack_vars[slot_idx],
-                        "peek_val",
-                    )
-                    .expect("peek load failed")
-                    .into_int_value()
-            } else {
-                self.context.i32_type().const_zero()
+    /// pop / dup / swap 系列指令：取出栈顶 `n` 个槽，按 `order` 重新排列后放回
+    /// （`order[i]` 是新的第 i 个槽来自原来的第几个槽，从 n 个槽的底部算起）。
+    ///
+    /// 这些指令不关心值的类型，各槽的类型取自当前指令的帧状态。
+    fn shuffle_stack(&mut self, n: usize, order: &[usize]) {
+        let base = self.depth - n;
+        let kinds: Vec<ValueKind> = self.frame_states[&self.cur_pc].stack[base..].to_vec();
+        let values: Vec<Option<BasicValueEnum<'ctx>>> = kinds
+            .iter()
+            .enumerate()
+            .map(|(i, &kind)| match kind {
+                ValueKind::Top => None,
+                kind => {
+                    let ptr = self.stack_ptr(base + i, kind);
+                    let v = self
+                        .builder
+                        .build_load(self.kind_type(kind), ptr, "shuffle")
+                        .expect("load failed");
+                    Some(v)
+                }
+            })
+            .collect();
+        for (i, &from) in order.iter().enumerate() {
+            if let Some(v) = values[from] {
+                let ptr = self.stack_ptr(base + i, kinds[from]);
+                self.builder.build_store(ptr, v).expect("store failed");
             }
         }
+        self.depth = base + order.len();
     }
 
     // ============================================================
     // 本地变量操作辅助方法
     // ============================================================
 
-    fn load_local_int(&mut self, idx: usize) {
-        if idx < self.local_vars.len() {
-            let val = self
-                .builder
-                .build_load(
-                    self.context.i32_type(),
-                    self.local_vars[idx],
-                    &format!("load_local_{}", idx),
-                )
-                .expect("load_local_int failed")
-                .into_int_value();
-            self.push_int_val(val);
-        }
-    }
-
-    fn store_local_int(&mut self, idx: usize) {
-        if idx < self.local_vars.len() {
-            let val = self.pop_int();
-            self.builder
-                .build_store(self.local_vars[idx], val)
-                .expect("store_local_int failed");
-        }
-    }
-
-    fn load_local_long(&mut self, idx: usize) {
-        // Longs occupy 2 consecutive i32 local slots
-        if idx + 1 < self.local_vars.len() {
-            let lo = self
-                .builder
-                .build_load(
-                    self.context.i32_type(),
-                    self.local_vars[idx],
-                    &format!("load_local_long_lo_{}", idx),
-                )
-                .expect("load local long lo failed")
-                .into_int_value();
-            let hi = self
-                .builder
-                .build_load(
-                    self.context.i32_type(),
-                    self.local_vars[idx + 1],
-                    &format!("load_local_long_hi_{}", idx),
-                )
-                .expect("load local long hi failed")
-                .into_int_value();
-            let i64_type = self.context.i64_type();
-            let lo_ext = self
-                .builder
-                .build_int_z_extend(lo, i64_type, "long_lo_ext")
-                .expect("zext failed");
-            let hi_ext = self
-                .builder
-                .build_int_z_extend(hi, i64_type, "long_hi_ext")
-                .expect("zext failed");
-            let combined = self
-                .builder
-                .build_or(
-                    lo_ext,
-                    self.builder
-                        .build_left_shift(hi_ext, i64_type.const_int(32, false), "long_hi_shl")
-                        .expect("shl failed"),
-                    "long_combined",
-                )
-                .expect("or failed");
-            self.push_long_val(combined);
-        }
-    }
-
-    fn store_local_long(&mut self, idx: usize) {
-        let val = self.pop_long();
-        let i64_type = self.context.i64_type();
-        let i32_type = self.context.i32_type();
-        let bitcast = self
-            .builder
-            .build_bit_cast(val, i64_type, "store_long_bitcast")
-            .expect("bitcast failed")
-            .into_int_value();
-        let lo = self
-            .builder
-            .build_int_truncate(bitcast, i32_type, "store_long_lo")
-            .expect("trunc failed");
-        let hi = self
-            .builder
-            .build_int_truncate(
-                self.builder
-                    .build_right_shift(
-                        bitcast,
-                        i64_type.const_int(32, false),
-                        false,
-                        "store_long_hi_shift",
-                    )
-                    .expect("shift failed"),
-                i32_type,
-                "store_long_hi",
-            )
-            .expect("trunc failed");
-        if idx + 1 < self.local_vars.len() {
-            self.builder
-                .build_store(self.local_vars[idx], lo)
-                .expect("store local long lo failed");
-            self.builder
-                .build_store(self.local_vars[idx + 1], hi)
-                .expect("store local long hi failed");
-        }
-    }
-
-    fn load_local_ref(&mut self, idx: usize) {
-        if idx < self.local_vars.len() {
-            let val = self
-                .builder
-                .build_load(
-                    self.context.i32_type(),
-                    self.local_vars[idx],
-                    &format!("load_local_ref_{}", idx),
-                )
-                .expect("load_local_ref failed")
-                .into_int_value();
-            self.push_int_val(val);
-        }
+    fn load_local(&mut self, idx: usize, kind: ValueKind) -> BasicValueEnum<'ctx> {
+        let ptr = self.local_ptr(idx, kind);
+        self.builder
+            .build_load(self.kind_type(kind), ptr, &format!("load_local_{}", idx))
+            .expect("load local failed")
     }
 
-    fn store_local_ref(&mut self, idx: usize) {
-        if idx < self.local_vars.len() {
-            let val = self.pop_int(); // slot_id
-            self.builder
-                .build_store(self.local_vars[idx], val)
-                .expect("store_local_ref failed");
-        }
+    fn store_local(&mut self, idx: usize, kind: ValueKind, val: BasicValueEnum<'ctx>) {
+        let ptr = self.local_ptr(idx, kind);
+        self.builder
+            .build_store(ptr, val)
+            .expect("store local failed");
     }
 
-    fn load_local_float(&mut self, idx: usize) {
-        if idx < self.local_vars.len() {
-            let raw = self
-                .builder
-                .build_load(
-                    self.context.i32_type(),
-                    self.local_vars[idx],
-                    &format!("load_local_f_raw_{}", idx),
-                )
-                .expect("load_local_float failed")
-                .into_int_value();
-            let fval = self
-                .builder
-                .build_bit_cast(raw, self.context.f32_type(), "f_bitcast")
-                .expect("f bitcast failed")
-                .into_float_value();
-            self.push_float_val(fval);
-        }
+    /// xload：把 local 推入操作数栈。
+    fn push_local(&mut self, idx: usize, kind: ValueKind) {
+        let val = self.load_local(idx, kind);
+        self.push(kind, val);
     }
 
-    fn store_local_float(&mut self, idx: usize) {
-        if idx < self.local_vars.len() {
-            let fval = self.pop_float();
-            let raw = self
-                .builder
-                .build_bit_cast(fval, self.context.i32_type(), "f_store_bitcast")
-                .expect("f store bitcast failed")
-                .into_int_value();
-            self.builder
-                .build_store(self.local_vars[idx], raw)
-                .expect("store_local_float failed");
-        }
+    /// xstore：弹出栈顶写入 local。
+    fn pop_to_local(&mut self, idx: usize, kind: ValueKind) {
+        let val = self.pop(kind);
+        self.store_local(idx, kind, val);
     }
 
-    fn load_local_double(&mut self, idx: usize) {
-        if idx + 1 < self.local_vars.len() {
-            let lo = self
-                .builder
-                .build_load(
-                    self.context.i32_type(),
-                    self.local_vars[idx],
-                    &format!("load_local_d_lo_{}", idx),
-                )
-                .expect("load local double lo failed")
-                .into_int_value();
-            let hi = self
-                .builder
-                .build_load(
-                    self.context.i32_type(),
-                    self.local_vars[idx + 1],
-                    &format!("load_local_d_hi_{}", idx),
-                )
-                .expect("load local double hi failed")
-                .into_int_value();
-            let i64_type = self.context.i64_type();
-            let lo_ext = self
-                .builder
-                .build_int_z_extend(lo, i64_type, "d_lo_ext")
-                .expect("d zext failed");
-            let hi_ext = self
-                .builder
-                .build_int_z_extend(hi, i64_type, "d_hi_ext")
-                .expect("d zext failed");
-            let combined = self
-                .builder
-                .build_or(
-                    lo_ext,
-                    self.builder
-                        .build_left_shift(hi_ext, i64_type.const_int(32, false), "d_hi_shl")
-                        .expect("d shl failed"),
-                    "d_combined",
-                )
-                .expect("d or failed");
-            let dval = self
-                .builder
-                .build_bit_cast(combined, self.context.f64_type(), "d_bitcast")
-                .expect("d bitcast failed")
-                .into_float_value();
-            self.push_double_val(dval);
+    /// *return：返回值写入 `ret_slot`，跳到返回块。
+    fn build_return(&mut self, kind: Option<ValueKind>) {
+        if let Some(kind) = kind {
+            let val = self.pop(kind);
+            if let Some(ret_slot) = self.ret_slot {
+                self.builder
+                    .build_store(ret_slot, val)
+                    .expect("store return value failed");
+            }
         }
+        self.builder
+            .build_unconditional_branch(self.return_bb)
+            .expect("return branch failed");
     }
 
-    fn store_local_double(&mut self, idx: usize) {
-        let dval = self.pop_double();
-        let i64_type = self.context.i64_type();
-        let i32_type = self.context.i32_type();
-        let bitcast = self
-            .builder
-            .build_bit_cast(dval, i64_type, "d_store_bitcast")
-            .expect("d store bitcast failed")
-            .into_int_value();
-        let lo = self
-            .builder
-            .build_int_truncate(bitcast, i32_type, "d_store_lo")
-            .expect("d store lo trunc failed");
-        let hi = self
-            .builder
-            .build_int_truncate(
-                self.builder
-                    .build_right_shift(
-                        bitcast,
-                        i64_type.const_int(32, false),
-                        false,
-                        "d_store_hi_shift",
-                    )
-                    .expect("d store hi shift failed"),
-                i32_type,
-                "d_store_hi",
-            )
-            .expect("d store hi trunc failed");
-        if idx + 1 < self.local_vars.len() {
-            self.builder
-                .build_store(self.local_vars[idx], lo)
-                .expect("store local double lo failed");
-            self.builder
-                .build_store(self.local_vars[idx + 1], hi)
-                .expect("store local double hi failed");
-        }
+    /// 遇到不支持的指令：放弃编译该方法，由解释器执行。
+    fn fail(&mut self, opcode: OpCode) {
+        warn!(
+            "JIT: unsupported opcode {:?} at pc={}, giving up",
+            opcode, self.cur_pc
+        );
+        self.failed = true;
+        self.builder
+            .build_unreachable()
+            .expect("unreachable failed");
     }
 
     // ============================================================
@@ -3021,759 +2496,1066 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
     // ============================================================
 
     /// 生成调用 JIT invoke 运行时函数的 LLVM IR。
-    /// 签名: extern "C" fn(cp_idx: u16, locals: *mut i32, stack: *mut i32, stack_top: u32)
-    fn call_invoke_runtime(&mut self, fn_name: &str, cp_idx: u16) {
-        let i32_type = self.context.i32_type();
+    ///
+    /// 签名: extern "C" fn(cp_idx: u16, args: *const i64[, site: *const u8]) -> i64
+    ///
+    /// 参数（含 this）按调用点的描述符从操作数栈弹出，编码为 i64 写入调用点的
+    /// 参数数组；返回值按返回类型解码后压栈。编码方式见 `encode_i64`。
+    ///
+    /// `site` 是调用点私有数据的地址（`InlineCache` 或 CHA `Assumption`），
+    /// 编译期已知，作为常量嵌入 IR。
+    fn call_invoke_runtime(
+        &mut self,
+        fn_name: &str,
+        cp_idx: u16,
+        has_this: bool,
+        site: Option<u64>,
+    ) {
         let i16_type = self.context.i16_type();
-
-        // 声明外部函数
-        let runtime_fn_type = self.context.void_type().fn_type(
-            &[
-                i16_type.into(),
-                self.context
-                    .i8_type()
-                    .ptr_type(inkwell::AddressSpace::default())
-                    .into(),
-                self.context
-                    .i8_type()
-                    .ptr_type(inkwell::AddressSpace::default())
-                    .into(),
-                i32_type.into(),
-            ],
-            false,
+        let i64_type = self.context.i64_type();
+        let ptr_type = self.context.ptr_type(inkwell::AddressSpace::default());
+
+        let sig = self.invoke_signature(cp_idx);
+        let kinds = arg_kinds(&sig, has_this);
+        let mut values: Vec<BasicValueEnum<'ctx>> =
+            kinds.iter().rev().map(|&kind| self.pop(kind)).collect();
+        values.reverse();
+
+        // 参数数组：alloca 放在 entry block，循环中的调用点不会增长机器栈
+        let args_buf = self.build_entry_alloca(
+            i64_type.array_type(kinds.len().max(1) as u32).into(),
+            "invoke_args",
         );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function(fn_name, runtime_fn_type, None);
+        for (i, (&kind, v)) in kinds.iter().zip(values).enumerate() {
+            let raw = encode_i64(self.context, self.builder, kind, v);
+            let ptr = unsafe {
+                self.builder
+                    .build_in_bounds_gep(
+                        i64_type,
+                        args_buf,
+                        &[i64_type.const_int(i as u64, false)],
+                        "invoke_arg",
+                    )
+                    .expect("invoke arg gep failed")
+            };
+            self.builder
+                .build_store(ptr, raw)
+                .expect("invoke arg store failed");
+        }
 
-        // 获取 locals 指针（即传入的第一个参数）
-        let locals_ptr = self
-            .function
-            .get_first_param()
-            .unwrap()
-            .into_pointer_value();
-        let stack_ptr = self.function.get_nth_param(1).unwrap().into_pointer_value();
+        let mut params: Vec<BasicMetadataTypeEnum<'ctx>> = vec![i16_type.into(), ptr_type.into()];
+        if site.is_some() {
+            params.push(ptr_type.into());
+        }
+        let runtime_fn_type = i64_type.fn_type(&params, false);
+        let runtime_fn = self.declare_runtime_fn(fn_name, runtime_fn_type);
 
-        // 加载当前 stack_top 值
-        let stack_top_val = self
+        let mut args: Vec<BasicMetadataValueEnum<'ctx>> = vec![
+            i16_type.const_int(cp_idx as u64, false).into(),
+            args_buf.into(),
+        ];
+        if let Some(addr) = site {
+            let site_ptr = self
+                .builder
+                .build_int_to_ptr(i64_type.const_int(addr, false), ptr_type, "invoke_site")
+                .expect("int_to_ptr failed");
+            args.push(site_ptr.into());
+        }
+
+        let ret = self
             .builder
-            .build_load(self.context.i32_type(), self.stack_top, "invoke_stack_top")
-            .expect("load stack_top failed")
+            .build_call(runtime_fn, &args, "invoke_call")
+            .expect("invoke call failed")
+            .try_as_basic_value()
+            .unwrap_basic()
             .into_int_value();
+        self.build_exception_check();
+        if let Some(kind) = ValueKind::from_signature(&sig.retype) {
+            let v = decode_i64(self.context, self.builder, kind, ret);
+            self.push(kind, v);
+        }
 
-        // cp_idx 参数
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
-
-        // 调用运行时函数
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[
-                    cp_idx_val.into(),
-                    locals_ptr.into(),
-                    stack_ptr.into(),
-                    stack_top_val.into(),
-                ],
-                "invoke_call",
-            )
-            .expect("invoke call failed");
+        self.build_safepoint();
     }
 
-    // ============================================================
-    // Runtime callout 辅助方法
-    // ============================================================
+    /// 在调用点之后插入安全点：编译后的代码已失效时，把当前状态交给解释器
+    /// 执行完方法的剩余部分，然后返回解释器算出的返回值。
+    fn build_safepoint(&mut self) {
+        if !self.safepoints {
+            return;
+        }
+        let resume_bci = match OpCode::from(self.bytecode[self.cur_pc]) {
+            OpCode::invokeinterface => self.cur_pc + 5,
+            _ => self.cur_pc + 3,
+        };
+        let state = match self.frame_states.get(&resume_bci) {
+            Some(state) => state.clone(),
+            None => return,
+        };
 
-    // --- ldc / ldc_w ---
-    fn call_ldc_runtime(&mut self, cp_idx: u16) {
+        let i8_type = self.context.i8_type();
         let i32_type = self.context.i32_type();
-        let i16_type = self.context.i16_type();
-        let ptr_type = self
-            .context
-            .i8_type()
-            .ptr_type(inkwell::AddressSpace::default());
+        let i64_type = self.context.i64_type();
+        let ptr_type = self.context.ptr_type(inkwell::AddressSpace::default());
 
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i16_type.into(),
-                ptr_type.into(),
-                ptr_type.into(),
-                i32_type.into(),
-            ],
-            false,
-        );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_ldc", fn_type, None);
+        let deopt_bb = self.context.append_basic_block(self.function, "deopt");
+        let cont_bb = self
+            .context
+            .append_basic_block(self.function, "safepoint_cont");
 
-        let stack_ptr = self.function.get_nth_param(1).unwrap().into_pointer_value();
-        let stack_top_val = self
+        let valid_ptr = match self.code_valid {
+            Some(global) => self
+                .builder
+                .build_load(ptr_type, global, "code_valid_ptr")
+                .expect("load code_valid_ptr failed")
+                .into_pointer_value(),
+            None => self
+                .builder
+                .build_int_to_ptr(
+                    i64_type.const_int(self.deopt.valid as *const Assumption as u64, false),
+                    ptr_type,
+                    "code_valid_ptr",
+                )
+                .expect("int_to_ptr failed"),
+        };
+        let valid = self
             .builder
-            .build_load(self.context.i32_type(), self.stack_top, "ldc_stack_top")
-            .expect("load stack_top failed")
+            .build_load(i8_type, valid_ptr, "code_valid")
+            .expect("load code_valid failed")
             .into_int_value();
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
-
+        let valid = self
+            .builder
+            .build_int_compare(
+                inkwell::IntPredicate::NE,
+                valid,
+                i8_type.const_zero(),
+                "is_code_valid",
+            )
+            .expect("compare failed");
         self.builder
+            .build_conditional_branch(valid, cont_bb, deopt_bb)
+            .expect("safepoint branch failed");
+
+        // 去优化路径：locals 与操作数栈各槽的值只在各自的 alloca 中，
+        // 按类型编码后写入缓冲区供运行时读取
+        self.builder.position_at_end(deopt_bb);
+        let (locals_buf, stack_buf) = self.deopt_buffers();
+        for (i, &kind) in state.locals.iter().enumerate() {
+            if kind != ValueKind::Top {
+                let v = self.load_local(i, kind);
+                self.spill(locals_buf, i, kind, v);
+            }
+        }
+        for (i, &kind) in state.stack.iter().enumerate() {
+            if kind != ValueKind::Top {
+                let ptr = self.stack_ptr(i, kind);
+                let v = self
+                    .builder
+                    .build_load(self.kind_type(kind), ptr, "spill_stack")
+                    .expect("load stack failed");
+                self.spill(stack_buf, i, kind, v);
+            }
+        }
+
+        let point = self.deopt.add_point(DeoptPoint {
+            bci: resume_bci,
+            locals: state.locals,
+            stack: state.stack,
+        });
+        let deopt_fn_type =
+            i64_type.fn_type(&[i32_type.into(), ptr_type.into(), ptr_type.into()], false);
+        let deopt_fn = self.declare_runtime_fn("jit_deopt", deopt_fn_type);
+        let ret = self
+            .builder
             .build_call(
-                runtime_fn,
+                deopt_fn,
                 &[
-                    cp_idx_val.into(),
-                    self.function.get_first_param().unwrap().into(),
-                    stack_ptr.into(),
-                    stack_top_val.into(),
+                    i32_type.const_int(point as u64, false).into(),
+                    locals_buf.into(),
+                    stack_buf.into(),
                 ],
-                "ldc_call",
+                "deopt_call",
             )
-            .expect("ldc call failed");
-    }
+            .expect("deopt call failed")
+            .try_as_basic_value()
+            .unwrap_basic()
+            .into_int_value();
+        if let (Some(kind), Some(ret_slot)) = (self.ret_kind, self.ret_slot) {
+            let v = decode_i64(self.context, self.builder, kind, ret);
+            self.builder
+                .build_store(ret_slot, v)
+                .expect("store return value failed");
+        }
+        self.builder
+            .build_unconditional_branch(self.return_bb)
+            .expect("deopt branch failed");
 
-    // --- ldc2_w ---
-    fn call_ldc2_w_runtime(&mut self, cp_idx: u16) {
-        let i32_type = self.context.i32_type();
-        let i16_type = self.context.i16_type();
-        let ptr_type = self
-            .context
-            .i8_type()
-            .ptr_type(inkwell::AddressSpace::default());
+        self.builder.position_at_end(cont_bb);
+    }
 
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i16_type.into(),
-                ptr_type.into(),
-                ptr_type.into(),
-                i32_type.into(),
-            ],
-            false,
+    /// 去优化时写出 locals / 操作数栈的两个 i64 缓冲区，首次使用时在 entry block 分配。
+    fn deopt_buffers(&mut self) -> (PointerValue<'ctx>, PointerValue<'ctx>) {
+        if let Some(bufs) = self.deopt_bufs {
+            return bufs;
+        }
+        let max_locals = self.frame_states[&0].locals.len();
+        let max_stack = self
+            .frame_states
+            .values()
+            .map(|state| state.stack.len())
+            .max()
+            .unwrap_or(0);
+        let i64_type = self.context.i64_type();
+        let locals = self.build_entry_alloca(
+            i64_type.array_type(max_locals.max(1) as u32).into(),
+            "deopt_locals",
         );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_ldc2_w", fn_type, None);
+        let stack = self.build_entry_alloca(
+            i64_type.array_type(max_stack.max(1) as u32).into(),
+            "deopt_stack",
+        );
+        self.deopt_bufs = Some((locals, stack));
+        (locals, stack)
+    }
+
+    /// 把 `kind` 类型的值编码后写入 i64 缓冲区 `buf` 的第 `idx` 项。
+    fn spill(
+        &mut self,
+        buf: PointerValue<'ctx>,
+        idx: usize,
+        kind: ValueKind,
+        v: BasicValueEnum<'ctx>,
+    ) {
+        let i64_type = self.context.i64_type();
+        let raw = encode_i64(self.context, self.builder, kind, v);
+        let ptr = unsafe {
+            self.builder
+                .build_in_bounds_gep(
+                    i64_type,
+                    buf,
+                    &[i64_type.const_int(idx as u64, false)],
+                    "spill_ptr",
+                )
+                .expect("spill gep failed")
+        };
+        self.builder
+            .build_store(ptr, raw)
+            .expect("spill store failed");
+    }
 
-        let stack_ptr = self.function.get_nth_param(1).unwrap().into_pointer_value();
-        let stack_top_val = self
+    /// 插入 safepoint 轮询：读取全局的 safepoint 请求标志，
+    /// 置位时调用 `jit_safepoint_poll` 停下来，直到 safepoint 结束。
+    ///
+    /// 标志按 volatile 读取，避免循环中的读取被提到循环外。
+    fn build_safepoint_poll(&mut self) {
+        let i8_type = self.context.i8_type();
+        let ptr_type = self.context.ptr_type(inkwell::AddressSpace::default());
+
+        // AOT 代码经外部符号引用标志，JIT 代码直接用标志的地址
+        let flag_ptr = if self.aot {
+            let module = unsafe { &*self.module };
+            module
+                .get_global(SAFEPOINT_POLL_SYMBOL)
+                .unwrap_or_else(|| module.add_global(i8_type, None, SAFEPOINT_POLL_SYMBOL))
+                .as_pointer_value()
+        } else {
+            self.builder
+                .build_int_to_ptr(
+                    self.context.i64_type().const_int(
+                        &safepoint::POLL as *const std::sync::atomic::AtomicBool as u64,
+                        false,
+                    ),
+                    ptr_type,
+                    "safepoint_flag_ptr",
+                )
+                .expect("int_to_ptr failed")
+        };
+        let flag = self
             .builder
-            .build_load(self.context.i32_type(), self.stack_top, "ldc2w_stack_top")
-            .expect("load stack_top failed")
-            .into_int_value();
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
+            .build_load(i8_type, flag_ptr, "safepoint_flag")
+            .expect("load safepoint flag failed");
+        flag.as_instruction_value()
+            .unwrap()
+            .set_volatile(true)
+            .expect("set volatile failed");
+        let requested = self
+            .builder
+            .build_int_compare(
+                inkwell::IntPredicate::NE,
+                flag.into_int_value(),
+                i8_type.const_zero(),
+                "safepoint_requested",
+            )
+            .expect("compare failed");
 
+        let poll_bb = self
+            .context
+            .append_basic_block(self.function, "safepoint_poll");
+        let cont_bb = self
+            .context
+            .append_basic_block(self.function, "safepoint_poll_cont");
         self.builder
-            .build_call(
-                runtime_fn,
-                &[
-                    cp_idx_val.into(),
-                    self.function.get_first_param().unwrap().into(),
-                    stack_ptr.into(),
-                    stack_top_val.into(),
-                ],
-                "ldc2_w_call",
-            )
-            .expect("ldc2_w call failed");
+            .build_conditional_branch(requested, poll_bb, cont_bb)
+            .expect("safepoint poll branch failed");
 
-        // ldc2_w pushes 2 slots; advance stack_top by 2
-        let two = self.context.i32_type().const_int(2, false);
-        let new_top = self
-            .builder
-            .build_int_add(stack_top_val, two, "inc_top_ldc2w")
-            .expect("inc_top failed");
+        self.builder.position_at_end(poll_bb);
+        let poll_fn_type = self.context.void_type().fn_type(&[], false);
+        let poll_fn = self.declare_runtime_fn("jit_safepoint_poll", poll_fn_type);
+        self.builder
+            .build_call(poll_fn, &[], "safepoint_poll_call")
+            .expect("safepoint poll call failed");
         self.builder
-            .build_store(self.stack_top, new_top)
-            .expect("store stack_top failed");
+            .build_unconditional_branch(cont_bb)
+            .expect("safepoint poll branch failed");
+
+        self.builder.position_at_end(cont_bb);
     }
 
-    // --- new ---
-    fn call_new_inst_runtime(&mut self, cp_idx: u16) -> IntValue<'ctx> {
-        let i16_type = self.context.i16_type();
-        let i32_type = self.context.i32_type();
-        let fn_type = i32_type.fn_type(&[i16_type.into()], false);
+    /// 按名称声明外部运行时函数；同名函数只声明一次。
+    fn declare_runtime_fn(
+        &self,
+        name: &str,
+        fn_type: inkwell::types::FunctionType<'ctx>,
+    ) -> FunctionValue<'ctx> {
         let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_new_inst", fn_type, None);
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
+        module
+            .get_function(name)
+            .unwrap_or_else(|| module.add_function(name, fn_type, None))
+    }
 
-        let call_site = self
-            .builder
-            .build_call(runtime_fn, &[cp_idx_val.into()], "new_inst_call")
-            .expect("new_inst call failed");
-        call_site
-            .try_as_basic_value()
-            .unwrap_basic()
-            .into_int_value()
+    /// 读取调用点的方法描述符。只读常量池，不触发类加载。
+    fn invoke_signature(&self, cp_idx: u16) -> MethodSignature {
+        let method = self.method.expect("invoke requires method context");
+        let cp = &method.class_file.cp;
+        let (_, _, name_and_type_index) = constant_pool::get_method_ref(cp, cp_idx as usize);
+        let (_, desc) = constant_pool::get_name_and_type(cp, name_and_type_index as usize);
+        MethodSignature::new(desc.as_slice())
     }
 
-    // --- newarray ---
-    fn call_new_array_runtime(&mut self, ary_type: u8, size: IntValue<'ctx>) -> IntValue<'ctx> {
-        let i32_type = self.context.i32_type();
-        let i8_type = self.context.i8_type();
-        let fn_type = i32_type.fn_type(&[i8_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_new_array", fn_type, None);
-        let ary_type_val = i8_type.const_int(ary_type as u64, false);
+    /// 在编译期解析调用点的目标方法。
+    ///
+    /// 只查找已完成初始化的类：编译期间不能触发类加载或 <clinit>，
+    /// 它们会执行 Java 代码并重入 JIT 编译器。解析不到时返回 None，
+    /// 调用点回退到运行时解析。
+    ///
+    /// AOT 代码不能依赖编译时的类层次，总是返回 None。
+    fn resolve_invoke_target(&self, cp_idx: u16) -> Option<MethodIdRef> {
+        if self.aot {
+            return None;
+        }
+        let cp = &self.method?.class_file.cp;
+        let (tag, class_index, name_and_type_index) =
+            constant_pool::get_method_ref(cp, cp_idx as usize);
+        if tag != consts::CONSTANT_METHOD_REF_TAG {
+            return None;
+        }
 
-        let call_site = self
-            .builder
-            .build_call(
-                runtime_fn,
-                &[ary_type_val.into(), size.into()],
-                "new_array_call",
-            )
-            .expect("new_array call failed");
-        call_site
-            .try_as_basic_value()
-            .unwrap_basic()
-            .into_int_value()
+        let class_name = constant_pool::get_class_name(cp, class_index as usize);
+        let class = runtime::sys_dic_find(class_name.as_slice())?;
+        if class.get_class_state() != class::State::FullyIni {
+            return None;
+        }
+
+        let (name, desc) = constant_pool::get_name_and_type(cp, name_and_type_index as usize);
+        class.get_class().get_class_method(name, desc).ok()
     }
 
-    // --- anewarray ---
-    fn call_anewarray_runtime(&mut self, cp_idx: u16, size: IntValue<'ctx>) -> IntValue<'ctx> {
-        let i16_type = self.context.i16_type();
-        let i32_type = self.context.i32_type();
-        let fn_type = i32_type.fn_type(&[i16_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_anewarray", fn_type, None);
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
+    /// 为调用点分配内联缓存，返回其地址。AOT 代码不使用内联缓存。
+    fn new_inline_cache(&self) -> Option<u64> {
+        if self.aot {
+            None
+        } else {
+            Some(InlineCache::new_leaked() as *const InlineCache as u64)
+        }
+    }
+
+    /// 能内联 `target` 时返回它的帧状态，供翻译内联方法体使用。
+    fn inline_plan(&self, target: &MethodIdRef) -> Option<HashMap<usize, FrameState>> {
+        if !inliner::can_inline(self.method?, target) {
+            return None;
+        }
+        frame_state::analyze(&target.method)
+    }
+
+    /// invokevirtual：能去虚化就去虚化（并尽量内联），否则走内联缓存。
+    fn translate_invoke_virtual(&mut self, cp_idx: u16) {
+        if self.translate_intrinsic(cp_idx, true) {
+            return;
+        }
 
-        let call_site = self
+        let target = self
+            .resolve_invoke_target(cp_idx)
+            .filter(cha::is_effectively_final);
+
+        let target = match target {
+            Some(target) => target,
+            None => {
+                let ic = self.new_inline_cache();
+                self.call_invoke_runtime("jit_invoke_virtual", cp_idx, true, ic);
+                return;
+            }
+        };
+
+        let assumption = Assumption::new_leaked();
+        self.dependencies.push(Dependency {
+            target: target.clone(),
+            assumption,
+        });
+
+        let states = match self.inline_plan(&target) {
+            Some(states) => states,
+            None => {
+                self.call_invoke_runtime(
+                    "jit_invoke_devirt",
+                    cp_idx,
+                    true,
+                    Some(assumption as *const Assumption as u64),
+                );
+                return;
+            }
+        };
+
+        // 守卫内联：假设仍成立时执行内联方法体，否则走内联缓存的慢路径
+        let inline_bb = self.context.append_basic_block(self.function, "cha_inline");
+        let slow_bb = self.context.append_basic_block(self.function, "cha_slow");
+        let cont_bb = self.context.append_basic_block(self.function, "cha_cont");
+
+        let i8_type = self.context.i8_type();
+        let flag_ptr = self
             .builder
-            .build_call(
-                runtime_fn,
-                &[cp_idx_val.into(), size.into()],
-                "anewarray_call",
+            .build_int_to_ptr(
+                self.context
+                    .i64_type()
+                    .const_int(assumption as *const Assumption as u64, false),
+                self.context.ptr_type(inkwell::AddressSpace::default()),
+                "cha_flag_ptr",
             )
-            .expect("anewarray call failed");
-        call_site
-            .try_as_basic_value()
-            .unwrap_basic()
-            .into_int_value()
+            .expect("int_to_ptr failed");
+        let flag = self
+            .builder
+            .build_load(i8_type, flag_ptr, "cha_flag")
+            .expect("load cha flag failed")
+            .into_int_value();
+        let valid = self
+            .builder
+            .build_int_compare(
+                inkwell::IntPredicate::NE,
+                flag,
+                i8_type.const_zero(),
+                "cha_valid",
+            )
+            .expect("cha compare failed");
+        self.builder
+            .build_conditional_branch(valid, inline_bb, slow_bb)
+            .expect("cha branch failed");
+
+        // 两条路径从同一栈深度出发，返回值落在同一个栈槽
+        let depth = self.depth;
+        self.builder.position_at_end(slow_bb);
+        let ic = self.new_inline_cache();
+        self.call_invoke_runtime("jit_invoke_virtual", cp_idx, true, ic);
+        self.builder
+            .build_unconditional_branch(cont_bb)
+            .expect("cha slow branch failed");
+        self.depth = depth;
+
+        self.builder.position_at_end(inline_bb);
+        self.inline_callee(cp_idx, &target, states, Some(slow_bb), cont_bb);
     }
 
-    // --- arraylength ---
-    fn call_array_length_runtime(&mut self, obj: IntValue<'ctx>) -> IntValue<'ctx> {
-        let i32_type = self.context.i32_type();
-        let fn_type = i32_type.fn_type(&[i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_array_length", fn_type, None);
+    /// invokestatic / invokespecial：目标在编译期唯一，可直接内联。
+    fn translate_invoke_static_bound(&mut self, cp_idx: u16, fn_name: &str, has_this: bool) {
+        if self.translate_intrinsic(cp_idx, has_this) {
+            return;
+        }
 
-        let call_site = self
-            .builder
-            .build_call(runtime_fn, &[obj.into()], "array_length_call")
-            .expect("array_length call failed");
-        call_site
-            .try_as_basic_value()
-            .unwrap_basic()
-            .into_int_value()
+        let plan = self.resolve_invoke_target(cp_idx).and_then(|target| {
+            let states = self.inline_plan(&target)?;
+            Some((target, states))
+        });
+
+        match plan {
+            Some((target, states)) => {
+                let cont_bb = self
+                    .context
+                    .append_basic_block(self.function, "inline_cont");
+                // 接收者为 null 时走运行时调用，由它抛出 NullPointerException
+                let null_bb = if has_this {
+                    let cur_bb = self.builder.get_insert_block().unwrap();
+                    let null_bb = self
+                        .context
+                        .append_basic_block(self.function, "inline_null");
+                    let depth = self.depth;
+                    self.builder.position_at_end(null_bb);
+                    self.call_invoke_runtime(fn_name, cp_idx, true, None);
+                    self.builder
+                        .build_unconditional_branch(cont_bb)
+                        .expect("inline null branch failed");
+                    self.depth = depth;
+                    self.builder.position_at_end(cur_bb);
+                    Some(null_bb)
+                } else {
+                    None
+                };
+                self.inline_callee(cp_idx, &target, states, null_bb, cont_bb);
+            }
+            None => self.call_invoke_runtime(fn_name, cp_idx, has_this, None),
+        }
     }
 
-    // --- checkcast ---
-    fn call_checkcast_runtime(&mut self, cp_idx: u16, obj: IntValue<'ctx>) {
-        let i16_type = self.context.i16_type();
-        let i32_type = self.context.i32_type();
-        let fn_type = self
-            .context
-            .void_type()
-            .fn_type(&[i16_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_checkcast", fn_type, None);
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
+    /// 按调用点的符号引用查找 intrinsic。只读常量池，不触发类加载。
+    ///
+    /// 可能被覆盖的方法（`Object.hashCode`）不按符号引用替换，
+    /// 由运行时在解析出目标方法后处理。
+    fn symbolic_intrinsic(&self, cp_idx: u16) -> Option<Intrinsic> {
+        let cp = &self.method?.class_file.cp;
+        let (tag, class_index, name_and_type_index) =
+            constant_pool::get_method_ref(cp, cp_idx as usize);
+        if tag != consts::CONSTANT_METHOD_REF_TAG {
+            return None;
+        }
+        let class_name = constant_pool::get_class_name(cp, class_index as usize);
+        let (name, desc) = constant_pool::get_name_and_type(cp, name_and_type_index as usize);
+        intrinsics::find(class_name.as_slice(), name.as_slice(), desc.as_slice())
+            .filter(|intrinsic| !intrinsic.needs_dispatch())
+    }
 
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[cp_idx_val.into(), obj.into()],
-                "checkcast_call",
-            )
-            .expect("checkcast call failed");
+    /// 调用点是 intrinsic 时完成翻译并返回 true。
+    ///
+    /// `Math` 的方法直接生成 LLVM intrinsic，其余方法调用
+    /// `jit_invoke_intrinsic`。
+    fn translate_intrinsic(&mut self, cp_idx: u16, has_this: bool) -> bool {
+        let intrinsic = match self.symbolic_intrinsic(cp_idx) {
+            Some(intrinsic) => intrinsic,
+            None => return false,
+        };
+        let llvm_name = match intrinsic {
+            Intrinsic::MathSqrt => "llvm.sqrt",
+            Intrinsic::MathAbsInt | Intrinsic::MathAbsLong => "llvm.abs",
+            Intrinsic::MathAbsFloat | Intrinsic::MathAbsDouble => "llvm.fabs",
+            Intrinsic::MathMinInt | Intrinsic::MathMinLong => "llvm.smin",
+            Intrinsic::MathMaxInt | Intrinsic::MathMaxLong => "llvm.smax",
+            // llvm.minimum / llvm.maximum 与 Java 语义一致：传播 NaN，-0.0 < 0.0
+            Intrinsic::MathMinFloat | Intrinsic::MathMinDouble => "llvm.minimum",
+            Intrinsic::MathMaxFloat | Intrinsic::MathMaxDouble => "llvm.maximum",
+            _ => {
+                self.call_invoke_runtime("jit_invoke_intrinsic", cp_idx, has_this, None);
+                return true;
+            }
+        };
+        self.build_math_intrinsic(cp_idx, llvm_name);
+        true
     }
 
-    // --- instanceof ---
-    fn call_instanceof_runtime(&mut self, cp_idx: u16, obj: IntValue<'ctx>) -> IntValue<'ctx> {
-        let i16_type = self.context.i16_type();
-        let i32_type = self.context.i32_type();
-        let fn_type = i32_type.fn_type(&[i16_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_instanceof", fn_type, None);
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
+    /// 用 LLVM intrinsic `llvm_name` 计算 `Math` 方法。
+    /// 这些方法的参数与返回值类型相同，该类型也是 intrinsic 的重载类型。
+    fn build_math_intrinsic(&mut self, cp_idx: u16, llvm_name: &str) {
+        let sig = self.invoke_signature(cp_idx);
+        let kind = ValueKind::from_signature(&sig.retype).expect("math intrinsic type");
+        let ty = self.kind_type(kind);
+
+        let mut args: Vec<BasicMetadataValueEnum> = Vec::with_capacity(sig.args.len() + 1);
+        for _ in 0..sig.args.len() {
+            args.insert(0, self.pop(kind).into());
+        }
+        if llvm_name == "llvm.abs" {
+            // is_int_min_poison = false：Math.abs(MIN_VALUE) 返回 MIN_VALUE
+            args.push(self.context.bool_type().const_zero().into());
+        }
 
-        let call_site = self
+        let module = unsafe { &*self.module };
+        let decl = inkwell::intrinsics::Intrinsic::find(llvm_name)
+            .and_then(|i| i.get_declaration(module, &[ty]))
+            .expect("LLVM intrinsic not found");
+        let r = self
             .builder
-            .build_call(
-                runtime_fn,
-                &[cp_idx_val.into(), obj.into()],
-                "instanceof_call",
-            )
-            .expect("instanceof call failed");
-        call_site
+            .build_call(decl, &args, "math_intrinsic")
+            .expect("intrinsic call failed")
             .try_as_basic_value()
-            .unwrap_basic()
-            .into_int_value()
+            .basic()
+            .expect("intrinsic returns a value");
+        self.push(kind, r);
     }
 
-    // --- getfield ---
-    fn call_getfield_runtime(&mut self, cp_idx: u16) {
-        let i16_type = self.context.i16_type();
-        let i32_type = self.context.i32_type();
-        let ptr_type = self
-            .context
-            .i8_type()
-            .ptr_type(inkwell::AddressSpace::default());
-
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i16_type.into(),
-                ptr_type.into(),
-                ptr_type.into(),
-                i32_type.into(),
-            ],
-            false,
-        );
+    /// f2i / f2l / d2i / d2l：NaN 转为 0，超出范围时取目标类型的最值。
+    fn build_float_to_int(
+        &self,
+        v: FloatValue<'ctx>,
+        ty: IntType<'ctx>,
+        name: &str,
+    ) -> IntValue<'ctx> {
         let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_getfield", fn_type, None);
+        let decl = inkwell::intrinsics::Intrinsic::find("llvm.fptosi.sat")
+            .and_then(|i| i.get_declaration(module, &[ty.into(), v.get_type().into()]))
+            .expect("LLVM intrinsic not found");
+        self.builder
+            .build_call(decl, &[v.into()], name)
+            .expect("fptosi.sat call failed")
+            .try_as_basic_value()
+            .unwrap_basic()
+            .into_int_value()
+    }
 
-        let stack_ptr = self.function.get_nth_param(1).unwrap().into_pointer_value();
-        let stack_top_val = self
+    /// idiv / irem / ldiv / lrem。
+    ///
+    /// 除数为 0 时抛出 ArithmeticException。LLVM 的 sdiv / srem 在
+    /// `MIN / -1` 时溢出是未定义行为，而 Java 规定结果为 MIN（取余为 0），
+    /// 所以除数为 -1 时改为除以 1，商取被除数的相反数。
+    fn build_int_div(
+        &mut self,
+        v1: IntValue<'ctx>,
+        v2: IntValue<'ctx>,
+        rem: bool,
+    ) -> IntValue<'ctx> {
+        let ty = v2.get_type();
+        let is_zero = self
             .builder
-            .build_load(
-                self.context.i32_type(),
-                self.stack_top,
-                "getfield_stack_top",
+            .build_int_compare(
+                inkwell::IntPredicate::EQ,
+                v2,
+                ty.const_zero(),
+                "div_by_zero",
             )
-            .expect("load stack_top failed")
-            .into_int_value();
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
-
+            .expect("div compare failed");
+        let zero_bb = self.context.append_basic_block(self.function, "div_zero");
+        let div_bb = self.context.append_basic_block(self.function, "div");
         self.builder
-            .build_call(
-                runtime_fn,
-                &[
-                    cp_idx_val.into(),
-                    self.function.get_first_param().unwrap().into(),
-                    stack_ptr.into(),
-                    stack_top_val.into(),
-                ],
-                "getfield_call",
-            )
-            .expect("getfield call failed");
-    }
-
-    // --- putfield ---
-    fn call_putfield_runtime(&mut self, cp_idx: u16) {
-        let i16_type = self.context.i16_type();
-        let i32_type = self.context.i32_type();
-        let ptr_type = self
-            .context
-            .i8_type()
-            .ptr_type(inkwell::AddressSpace::default());
+            .build_conditional_branch(is_zero, zero_bb, div_bb)
+            .expect("div branch failed");
 
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i16_type.into(),
-                ptr_type.into(),
-                ptr_type.into(),
-                i32_type.into(),
-            ],
-            false,
+        self.builder.position_at_end(zero_bb);
+        let throw_fn = self.declare_runtime_fn(
+            "jit_throw_div_by_zero",
+            self.context.void_type().fn_type(&[], false),
         );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_putfield", fn_type, None);
+        self.builder
+            .build_call(throw_fn, &[], "")
+            .expect("throw call failed");
+        self.builder
+            .build_unconditional_branch(self.unwind_bb)
+            .expect("unwind branch failed");
 
-        let stack_ptr = self.function.get_nth_param(1).unwrap().into_pointer_value();
-        let stack_top_val = self
+        self.builder.position_at_end(div_bb);
+        let is_m1 = self
             .builder
-            .build_load(
-                self.context.i32_type(),
-                self.stack_top,
-                "putfield_stack_top",
+            .build_int_compare(
+                inkwell::IntPredicate::EQ,
+                v2,
+                ty.const_all_ones(),
+                "div_by_m1",
             )
-            .expect("load stack_top failed")
+            .expect("div compare failed");
+        let divisor = self
+            .builder
+            .build_select(is_m1, ty.const_int(1, false), v2, "divisor")
+            .expect("select failed")
             .into_int_value();
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
-
+        if rem {
+            return self
+                .builder
+                .build_int_signed_rem(v1, divisor, "rem")
+                .expect("srem failed");
+        }
+        let q = self
+            .builder
+            .build_int_signed_div(v1, divisor, "div")
+            .expect("sdiv failed");
+        let neg = self
+            .builder
+            .build_int_neg(v1, "div_neg")
+            .expect("neg failed");
         self.builder
-            .build_call(
-                runtime_fn,
-                &[
-                    cp_idx_val.into(),
-                    self.function.get_first_param().unwrap().into(),
-                    stack_ptr.into(),
-                    stack_top_val.into(),
-                ],
-                "putfield_call",
-            )
-            .expect("putfield call failed");
+            .build_select(is_m1, neg, q, "quot")
+            .expect("select failed")
+            .into_int_value()
     }
 
-    // --- getstatic ---
-    fn call_getstatic_runtime(&mut self, cp_idx: u16) {
-        let i16_type = self.context.i16_type();
+    /// 在当前插入点展开 `callee` 的方法体，结束后插入点位于 `cont_bb`。
+    ///
+    /// 参数从调用方的栈槽复制到被调方法的 locals，被调方法有自己的操作数栈槽；
+    /// 它的 *return 把返回值写入调用方参数区起始位置（base）的栈槽，
+    /// 与运行时调用压栈的位置相同。`states` 是被调方法的帧状态。
+    ///
+    /// `null_bb` 是实例方法的接收者为 null 时跳转的慢路径，
+    /// 由它通过运行时调用抛出 NullPointerException。
+    fn inline_callee(
+        &mut self,
+        cp_idx: u16,
+        callee: &MethodIdRef,
+        states: HashMap<usize, FrameState>,
+        null_bb: Option<BasicBlock<'ctx>>,
+        cont_bb: BasicBlock<'ctx>,
+    ) {
         let i32_type = self.context.i32_type();
-        let ptr_type = self
-            .context
-            .i8_type()
-            .ptr_type(inkwell::AddressSpace::default());
+        let code = callee.method.code.as_ref().unwrap();
+        let sig = self.invoke_signature(cp_idx);
+        let kinds = arg_kinds(&sig, null_bb.is_some());
+        let slots: usize = kinds.iter().map(|&kind| slot_width(kind)).sum();
+        let base = self.depth - slots;
+
+        if let Some(null_bb) = null_bb {
+            let this_ptr = self.stack_ptr(base, ValueKind::Ref);
+            let this = self
+                .builder
+                .build_load(i32_type, this_ptr, "inline_this")
+                .expect("inline this load failed")
+                .into_int_value();
+            let is_null = self
+                .builder
+                .build_int_compare(
+                    inkwell::IntPredicate::EQ,
+                    this,
+                    i32_type.const_zero(),
+                    "inline_this_null",
+                )
+                .expect("inline null compare failed");
+            let nonnull_bb = self
+                .context
+                .append_basic_block(self.function, "inline_nonnull");
+            self.builder
+                .build_conditional_branch(is_null, null_bb, nonnull_bb)
+                .expect("inline null branch failed");
+            self.builder.position_at_end(nonnull_bb);
+        }
 
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i16_type.into(),
-                ptr_type.into(),
-                ptr_type.into(),
-                i32_type.into(),
-            ],
-            false,
-        );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_getstatic", fn_type, None);
+        // 被调方法的 locals：alloca 必须放在 entry block，
+        // 否则位于循环中的调用点每次迭代都会增长机器栈
+        let mut callee_locals = HashMap::new();
+        let mut pos = 0;
+        for kind in kinds {
+            let src = self.stack_ptr(base + pos, kind);
+            let v = self
+                .builder
+                .build_load(self.kind_type(kind), src, "inline_arg")
+                .expect("inline arg load failed");
+            let dst =
+                self.build_entry_alloca(self.kind_type(kind), &format!("inline_local_{}", pos));
+            self.builder
+                .build_store(dst, v)
+                .expect("inline arg store failed");
+            callee_locals.insert((pos, kind), dst);
+            pos += slot_width(kind);
+        }
 
-        let stack_ptr = self.function.get_nth_param(1).unwrap().into_pointer_value();
-        let stack_top_val = self
-            .builder
-            .build_load(
-                self.context.i32_type(),
-                self.stack_top,
-                "getstatic_stack_top",
-            )
-            .expect("load stack_top failed")
-            .into_int_value();
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
+        let ret_kind = ValueKind::from_signature(&sig.retype);
+        let ret_slot = ret_kind.map(|kind| self.stack_ptr(base, kind));
 
+        // 被调方法的基本块
+        let bytecode = code.code.as_slice();
+        let bb_map = block_map(self.context, self.function, bytecode, "inline_bb");
         self.builder
-            .build_call(
-                runtime_fn,
-                &[
-                    cp_idx_val.into(),
-                    self.function.get_first_param().unwrap().into(),
-                    stack_ptr.into(),
-                    stack_top_val.into(),
-                ],
-                "getstatic_call",
-            )
-            .expect("getstatic call failed");
+            .build_unconditional_branch(bb_map[&0])
+            .expect("inline entry branch failed");
 
-        // Push result slot (1 slot for getstatic int; for other types this needs expansion)
-        let one = self.context.i32_type().const_int(1, false);
-        let new_top = self
-            .builder
-            .build_int_add(stack_top_val, one, "inc_top_getstatic")
-            .expect("inc_top failed");
-        self.builder
-            .build_store(self.stack_top, new_top)
-            .expect("store stack_top failed");
+        let mut inlined = BytecodeInterpreter {
+            context: self.context,
+            builder: self.builder,
+            bb_map,
+            function: self.function,
+            return_bb: cont_bb,
+            unwind_bb: self.unwind_bb,
+            locals: callee_locals,
+            stack: HashMap::new(),
+            depth: 0,
+            ret_kind,
+            ret_slot,
+            bytecode,
+            module: self.module,
+            method: Some(&callee.method),
+            dependencies: Vec::new(),
+            cur_pc: 0,
+            frame_states: states,
+            safepoints: false,
+            deopt_bufs: None,
+            deopt: DeoptInfo::new(),
+            aot: self.aot,
+            code_valid: None,
+            loop_headers: collect_loop_headers(bytecode),
+            failed: false,
+        };
+        inlined.translate_remaining_blocks();
+        self.failed |= inlined.failed;
+
+        self.builder.position_at_end(cont_bb);
+        self.depth = base + ret_kind.map(slot_width).unwrap_or(0);
     }
 
-    // --- putstatic ---
-    fn call_putstatic_runtime(&mut self, cp_idx: u16) {
-        let i16_type = self.context.i16_type();
-        let i32_type = self.context.i32_type();
-        let ptr_type = self
-            .context
-            .i8_type()
-            .ptr_type(inkwell::AddressSpace::default());
+    /// 在函数 entry block 的开头分配一个 `ty` 类型的 alloca。
+    fn build_entry_alloca(&self, ty: BasicTypeEnum<'ctx>, name: &str) -> PointerValue<'ctx> {
+        let entry = self.function.get_first_basic_block().unwrap();
+        let builder = self.context.create_builder();
+        match entry.get_first_instruction() {
+            Some(inst) => builder.position_before(&inst),
+            None => builder.position_at_end(entry),
+        }
+        builder.build_alloca(ty, name).expect("build_alloca failed")
+    }
 
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i16_type.into(),
-                ptr_type.into(),
-                ptr_type.into(),
-                i32_type.into(),
-            ],
-            false,
-        );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_putstatic", fn_type, None);
+    // ============================================================
+    // Runtime callout 辅助方法
+    // ============================================================
 
-        let stack_ptr = self.function.get_nth_param(1).unwrap().into_pointer_value();
-        let stack_top_val = self
+    /// 声明并调用运行时函数 `name`，参数类型取自 `args`。
+    /// 返回函数的返回值，`ret` 为 None（void 函数）时返回 None。
+    fn call_runtime(
+        &mut self,
+        name: &str,
+        ret: Option<BasicTypeEnum<'ctx>>,
+        args: &[BasicValueEnum<'ctx>],
+    ) -> Option<BasicValueEnum<'ctx>> {
+        let param_types: Vec<BasicMetadataTypeEnum<'ctx>> =
+            args.iter().map(|arg| arg.get_type().into()).collect();
+        let fn_type = match ret {
+            Some(ty) => ty.fn_type(&param_types, false),
+            None => self.context.void_type().fn_type(&param_types, false),
+        };
+        let runtime_fn = self.declare_runtime_fn(name, fn_type);
+        let args: Vec<BasicMetadataValueEnum<'ctx>> = args.iter().map(|&arg| arg.into()).collect();
+        let ret = self
             .builder
-            .build_load(
-                self.context.i32_type(),
-                self.stack_top,
-                "putstatic_stack_top",
-            )
-            .expect("load stack_top failed")
-            .into_int_value();
-        let cp_idx_val = i16_type.const_int(cp_idx as u64, false);
-
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[
-                    cp_idx_val.into(),
-                    self.function.get_first_param().unwrap().into(),
-                    stack_ptr.into(),
-                    stack_top_val.into(),
-                ],
-                "putstatic_call",
-            )
-            .expect("putstatic call failed");
+            .build_call(runtime_fn, &args, &format!("{}_call", name))
+            .expect("runtime call failed")
+            .try_as_basic_value()
+            .basic();
+        self.build_exception_check();
+        ret
     }
 
-    // --- 数组加载（通用模式） ---
-    fn call_array_load_runtime(
-        &mut self,
-        fn_name: &str,
-        array: IntValue<'ctx>,
-        index: IntValue<'ctx>,
-    ) {
+    /// 运行时函数可能抛出了异常：有待处理的异常时直接返回，
+    /// 由调用方的解释器按它的异常表分派。
+    ///
+    /// 含异常表的方法不编译，所以异常总是传播出当前方法。
+    fn build_exception_check(&mut self) {
         let i32_type = self.context.i32_type();
-        let ptr_type = self
-            .context
-            .i8_type()
-            .ptr_type(inkwell::AddressSpace::default());
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i32_type.into(),
-                i32_type.into(),
-                ptr_type.into(),
-                i32_type.into(),
-            ],
-            false,
-        );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function(fn_name, fn_type, None);
-
-        let stack_ptr = self.function.get_nth_param(1).unwrap().into_pointer_value();
-        // After pop array + index, stack_top decreased by 2, result written at stack_top - 2
-        let stack_top_val = self
+        let pending_fn =
+            self.declare_runtime_fn("jit_exception_pending", i32_type.fn_type(&[], false));
+        let pending = self
             .builder
-            .build_load(self.context.i32_type(), self.stack_top, "aload_stack_top")
-            .expect("load stack_top failed")
+            .build_call(pending_fn, &[], "ex_pending")
+            .expect("exception check call failed")
+            .try_as_basic_value()
+            .unwrap_basic()
             .into_int_value();
-
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[
-                    array.into(),
-                    index.into(),
-                    stack_ptr.into(),
-                    stack_top_val.into(),
-                ],
-                "aload_call",
+        let is_pending = self
+            .builder
+            .build_int_compare(
+                inkwell::IntPredicate::NE,
+                pending,
+                i32_type.const_zero(),
+                "is_ex_pending",
             )
-            .expect("array load call failed");
+            .expect("exception check compare failed");
+        let cont_bb = self
+            .context
+            .append_basic_block(self.function, "no_exception");
+        self.builder
+            .build_conditional_branch(is_pending, self.unwind_bb, cont_bb)
+            .expect("exception check branch failed");
+        self.builder.position_at_end(cont_bb);
     }
 
-    fn call_iaload_runtime(&mut self, array: IntValue<'ctx>, index: IntValue<'ctx>) {
-        self.call_array_load_runtime("jit_iaload", array, index);
-    }
-    fn call_laload_runtime(&mut self, array: IntValue<'ctx>, index: IntValue<'ctx>) {
-        self.call_array_load_runtime("jit_laload", array, index);
+    fn cp_idx_val(&self, cp_idx: u16) -> BasicValueEnum<'ctx> {
+        self.context
+            .i16_type()
+            .const_int(cp_idx as u64, false)
+            .into()
     }
-    fn call_faload_runtime(&mut self, array: IntValue<'ctx>, index: IntValue<'ctx>) {
-        self.call_array_load_runtime("jit_faload", array, index);
-    }
-    fn call_daload_runtime(&mut self, array: IntValue<'ctx>, index: IntValue<'ctx>) {
-        self.call_array_load_runtime("jit_daload", array, index);
+
+    // --- ldc / ldc_w / ldc2_w ---
+    /// 数值常量直接生成 LLVM 常量；String / Class 常量调用 `jit_ldc`。
+    fn translate_ldc(&mut self, cp_idx: u16) {
+        let cp = &self
+            .method
+            .expect("ldc requires method context")
+            .class_file
+            .cp;
+        match cp.get(cp_idx as usize) {
+            Some(ConstantPoolType::Integer { v }) => self.push_int(i32::from_be_bytes(*v)),
+            Some(ConstantPoolType::Float { v }) => {
+                let bits = self
+                    .context
+                    .i32_type()
+                    .const_int(u32::from_be_bytes(*v) as u64, false);
+                let f = self
+                    .builder
+                    .build_bit_cast(bits, self.context.f32_type(), "ldc_f")
+                    .expect("bitcast failed");
+                self.push(ValueKind::Float, f);
+            }
+            Some(ConstantPoolType::Long { v }) => {
+                let v = self
+                    .context
+                    .i64_type()
+                    .const_int(u64::from_be_bytes(*v), false);
+                self.push_long_val(v);
+            }
+            Some(ConstantPoolType::Double { v }) => {
+                let bits = self
+                    .context
+                    .i64_type()
+                    .const_int(u64::from_be_bytes(*v), false);
+                let d = self
+                    .builder
+                    .build_bit_cast(bits, self.context.f64_type(), "ldc_d")
+                    .expect("bitcast failed");
+                self.push(ValueKind::Double, d);
+            }
+            _ => {
+                let args = [self.cp_idx_val(cp_idx)];
+                let r = self.call_runtime("jit_ldc", Some(self.context.i32_type().into()), &args);
+                self.push(ValueKind::Ref, r.unwrap());
+            }
+        }
     }
-    fn call_aaload_runtime(&mut self, array: IntValue<'ctx>, index: IntValue<'ctx>) {
-        self.call_array_load_runtime("jit_aaload", array, index);
+
+    // --- new ---
+    fn call_new_inst_runtime(&mut self, cp_idx: u16) -> IntValue<'ctx> {
+        let args = [self.cp_idx_val(cp_idx)];
+        self.call_runtime("jit_new_inst", Some(self.context.i32_type().into()), &args)
+            .unwrap()
+            .into_int_value()
     }
-    fn call_baload_runtime(&mut self, array: IntValue<'ctx>, index: IntValue<'ctx>) {
-        self.call_array_load_runtime("jit_baload", array, index);
+
+    // --- newarray ---
+    fn call_new_array_runtime(&mut self, ary_type: u8, size: IntValue<'ctx>) -> IntValue<'ctx> {
+        let ary_type_val = self.context.i8_type().const_int(ary_type as u64, false);
+        self.call_runtime(
+            "jit_new_array",
+            Some(self.context.i32_type().into()),
+            &[ary_type_val.into(), size.into()],
+        )
+        .unwrap()
+        .into_int_value()
     }
-    fn call_caload_runtime(&mut self, array: IntValue<'ctx>, index: IntValue<'ctx>) {
-        self.call_array_load_runtime("jit_caload", array, index);
+
+    // --- anewarray ---
+    fn call_anewarray_runtime(&mut self, cp_idx: u16, size: IntValue<'ctx>) -> IntValue<'ctx> {
+        let args = [self.cp_idx_val(cp_idx), size.into()];
+        self.call_runtime("jit_anewarray", Some(self.context.i32_type().into()), &args)
+            .unwrap()
+            .into_int_value()
     }
-    fn call_saload_runtime(&mut self, array: IntValue<'ctx>, index: IntValue<'ctx>) {
-        self.call_array_load_runtime("jit_saload", array, index);
+
+    // --- arraylength ---
+    fn call_array_length_runtime(&mut self, obj: IntValue<'ctx>) -> IntValue<'ctx> {
+        self.call_runtime(
+            "jit_array_length",
+            Some(self.context.i32_type().into()),
+            &[obj.into()],
+        )
+        .unwrap()
+        .into_int_value()
     }
 
-    // --- 数组存储 ---
-    fn call_iastore_runtime(
-        &mut self,
-        array: IntValue<'ctx>,
-        index: IntValue<'ctx>,
-        value: IntValue<'ctx>,
-    ) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self
-            .context
-            .void_type()
-            .fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_iastore", fn_type, None);
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[array.into(), index.into(), value.into()],
-                "iastore_call",
-            )
-            .expect("iastore call failed");
+    // --- checkcast ---
+    fn call_checkcast_runtime(&mut self, cp_idx: u16, obj: IntValue<'ctx>) {
+        let args = [self.cp_idx_val(cp_idx), obj.into()];
+        self.call_runtime("jit_checkcast", None, &args);
     }
-    fn call_lastore_runtime(
-        &mut self,
-        array: IntValue<'ctx>,
-        index: IntValue<'ctx>,
-        lo: IntValue<'ctx>,
-        hi: IntValue<'ctx>,
-    ) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i32_type.into(),
-                i32_type.into(),
-                i32_type.into(),
-                i32_type.into(),
-            ],
-            false,
-        );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_lastore", fn_type, None);
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[array.into(), index.into(), lo.into(), hi.into()],
-                "lastore_call",
-            )
-            .expect("lastore call failed");
+
+    // --- instanceof ---
+    fn call_instanceof_runtime(&mut self, cp_idx: u16, obj: IntValue<'ctx>) -> IntValue<'ctx> {
+        let args = [self.cp_idx_val(cp_idx), obj.into()];
+        self.call_runtime(
+            "jit_instanceof",
+            Some(self.context.i32_type().into()),
+            &args,
+        )
+        .unwrap()
+        .into_int_value()
     }
-    fn call_fastore_runtime(
-        &mut self,
-        array: IntValue<'ctx>,
-        index: IntValue<'ctx>,
-        value: IntValue<'ctx>,
-    ) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self
-            .context
-            .void_type()
-            .fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_fastore", fn_type, None);
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[array.into(), index.into(), value.into()],
-                "fastore_call",
-            )
-            .expect("fastore call failed");
+
+    /// 字段引用 `cp_idx` 的值类型。
+    fn field_kind(&self, cp_idx: u16) -> ValueKind {
+        let cp = &self
+            .method
+            .expect("field access requires method context")
+            .class_file
+            .cp;
+        frame_state::field_kind(cp, cp_idx as usize).expect("field descriptor")
     }
-    fn call_dastore_runtime(
-        &mut self,
-        array: IntValue<'ctx>,
-        index: IntValue<'ctx>,
-        lo: IntValue<'ctx>,
-        hi: IntValue<'ctx>,
-    ) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self.context.void_type().fn_type(
-            &[
-                i32_type.into(),
-                i32_type.into(),
-                i32_type.into(),
-                i32_type.into(),
-            ],
-            false,
-        );
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_dastore", fn_type, None);
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[array.into(), index.into(), lo.into(), hi.into()],
-                "dastore_call",
-            )
-            .expect("dastore call failed");
+
+    // --- getfield ---
+    // 字段值统一编码为 i64 传递，见 `encode_i64`
+    fn call_getfield_runtime(&mut self, cp_idx: u16) {
+        let kind = self.field_kind(cp_idx);
+        let obj = self.pop_ref();
+        let args = [self.cp_idx_val(cp_idx), obj.into()];
+        let raw = self
+            .call_runtime("jit_getfield", Some(self.context.i64_type().into()), &args)
+            .unwrap()
+            .into_int_value();
+        let v = decode_i64(self.context, self.builder, kind, raw);
+        self.push(kind, v);
     }
-    fn call_aastore_runtime(
-        &mut self,
-        array: IntValue<'ctx>,
-        index: IntValue<'ctx>,
-        value: IntValue<'ctx>,
-    ) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self
-            .context
-            .void_type()
-            .fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_aastore", fn_type, None);
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[array.into(), index.into(), value.into()],
-                "aastore_call",
-            )
-            .expect("aastore call failed");
+
+    // --- putfield ---
+    fn call_putfield_runtime(&mut self, cp_idx: u16) {
+        let kind = self.field_kind(cp_idx);
+        let v = self.pop(kind);
+        let obj = self.pop_ref();
+        let raw = encode_i64(self.context, self.builder, kind, v);
+        let args = [self.cp_idx_val(cp_idx), obj.into(), raw.into()];
+        self.call_runtime("jit_putfield", None, &args);
     }
-    fn call_bastore_runtime(
-        &mut self,
-        array: IntValue<'ctx>,
-        index: IntValue<'ctx>,
-        value: IntValue<'ctx>,
-    ) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self
-            .context
-            .void_type()
-            .fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_bastore", fn_type, None);
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[array.into(), index.into(), value.into()],
-                "bastore_call",
-            )
-            .expect("bastore call failed");
+
+    // --- getstatic ---
+    fn call_getstatic_runtime(&mut self, cp_idx: u16) {
+        let kind = self.field_kind(cp_idx);
+        let args = [self.cp_idx_val(cp_idx)];
+        let raw = self
+            .call_runtime("jit_getstatic", Some(self.context.i64_type().into()), &args)
+            .unwrap()
+            .into_int_value();
+        let v = decode_i64(self.context, self.builder, kind, raw);
+        self.push(kind, v);
+    }
+
+    // --- putstatic ---
+    fn call_putstatic_runtime(&mut self, cp_idx: u16) {
+        let kind = self.field_kind(cp_idx);
+        let v = self.pop(kind);
+        let raw = encode_i64(self.context, self.builder, kind, v);
+        let args = [self.cp_idx_val(cp_idx), raw.into()];
+        self.call_runtime("jit_putstatic", None, &args);
     }
-    fn call_castore_runtime(
+
+    // --- 数组加载：运行时函数返回 `kind` 类型的元素，压栈 ---
+    fn call_array_load_runtime(
         &mut self,
+        fn_name: &str,
+        kind: ValueKind,
         array: IntValue<'ctx>,
         index: IntValue<'ctx>,
-        value: IntValue<'ctx>,
     ) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self
-            .context
-            .void_type()
-            .fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_castore", fn_type, None);
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[array.into(), index.into(), value.into()],
-                "castore_call",
+        let v = self
+            .call_runtime(
+                fn_name,
+                Some(self.kind_type(kind)),
+                &[array.into(), index.into()],
             )
-            .expect("castore call failed");
+            .unwrap();
+        self.push(kind, v);
     }
-    fn call_sastore_runtime(
+
+    // --- 数组存储 ---
+    fn call_array_store_runtime(
         &mut self,
+        fn_name: &str,
         array: IntValue<'ctx>,
         index: IntValue<'ctx>,
-        value: IntValue<'ctx>,
+        value: BasicValueEnum<'ctx>,
     ) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self
-            .context
-            .void_type()
-            .fn_type(&[i32_type.into(), i32_type.into(), i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_sastore", fn_type, None);
-        self.builder
-            .build_call(
-                runtime_fn,
-                &[array.into(), index.into(), value.into()],
-                "sastore_call",
-            )
-            .expect("sastore call failed");
+        self.call_runtime(fn_name, None, &[array.into(), index.into(), value]);
     }
 
     // --- 同步 ---
     fn call_monitorenter_runtime(&mut self, obj: IntValue<'ctx>) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self.context.void_type().fn_type(&[i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_monitorenter", fn_type, None);
-        self.builder
-            .build_call(runtime_fn, &[obj.into()], "monitorenter_call")
-            .expect("monitorenter call failed");
+        self.call_runtime("jit_monitorenter", None, &[obj.into()]);
     }
 
     fn call_monitorexit_runtime(&mut self, obj: IntValue<'ctx>) {
-        let i32_type = self.context.i32_type();
-        let fn_type = self.context.void_type().fn_type(&[i32_type.into()], false);
-        let module = unsafe { &*self.module };
-        let runtime_fn = module.add_function("jit_monitorexit", fn_type, None);
-        self.builder
-            .build_call(runtime_fn, &[obj.into()], "monitorexit_call")
-            .expect("monitorexit call failed");
+        self.call_runtime("jit_monitorexit", None, &[obj.into()]);
     }
 
     // ============================================================
@@ -3797,28 +3579,80 @@ impl<'ctx, 'a> BytecodeInterpreter<'ctx, 'a> {
         *self.bb_map.get(&offset).unwrap()
     }
 
-    /// 处理因分支指令提前返回而遗漏的基本块。
+    /// 翻译所有还没有终止符的基本块。
+    ///
+    /// 翻译一个块时可能经 `ensure_block` 创建新的块，所以反复扫描直到没有剩余。
     fn translate_remaining_blocks(&mut self) {
-        // 收集所有还没有终止符的 block 及其对应的 PC
-        let pending: Vec<usize> = self
-            .bb_map
-            .keys()
-            .copied()
-            .filter(|&pc| pc != 0) // 跳过 entry block（已处理）
-            .collect();
+        loop {
+            let mut pending: Vec<usize> = self
+                .bb_map
+                .iter()
+                .filter(|(_, bb)| bb.get_terminator().is_none())
+                .map(|(&pc, _)| pc)
+                .collect();
+            if pending.is_empty() {
+                break;
+            }
+            pending.sort_unstable();
 
-        for pc in pending {
-            let bb = self.bb_map[&pc];
-            if bb.get_terminator().is_none() && pc < self.bytecode.len() {
+            for pc in pending {
+                let bb = self.bb_map[&pc];
+                if bb.get_terminator().is_some() {
+                    continue;
+                }
                 self.builder.position_at_end(bb);
+                if self.loop_headers.contains(&pc) {
+                    self.build_safepoint_poll();
+                }
                 self.translate_bytecode(pc);
             }
         }
     }
 }
 
+/// 调用点参数（含 this）的值类型，按压栈顺序排列。
+fn arg_kinds(sig: &MethodSignature, has_this: bool) -> Vec<ValueKind> {
+    let mut kinds = Vec::with_capacity(sig.args.len() + 1);
+    if has_this {
+        kinds.push(ValueKind::Ref);
+    }
+    for arg in &sig.args {
+        kinds.push(ValueKind::from_signature(arg).expect("argument type"));
+    }
+    kinds
+}
+
+/// 一个值占用的 local / 栈槽数。
+fn slot_width(kind: ValueKind) -> usize {
+    if kind.is_wide() {
+        2
+    } else {
+        1
+    }
+}
+
 /// 扫描 bytecode，收集所有跳转目标偏移。
 fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
+    let mut targets: Vec<usize> = collect_branches(bytecode)
+        .into_iter()
+        .map(|(_, target)| target)
+        .collect();
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// 收集循环头：目标不在分支指令之后的跳转（回边）的目标。
+fn collect_loop_headers(bytecode: &[U1]) -> HashSet<usize> {
+    collect_branches(bytecode)
+        .into_iter()
+        .filter(|(pc, target)| target <= pc)
+        .map(|(_, target)| target)
+        .collect()
+}
+
+/// 扫描 bytecode，收集所有 (分支指令 pc, 跳转目标偏移)。
+fn collect_branches(bytecode: &[U1]) -> Vec<(usize, usize)> {
     let mut targets = Vec::new();
     let mut pc = 0;
 
@@ -3846,7 +3680,7 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                 if pc + 2 < bytecode.len() {
                     let offset = i16::from_be_bytes([bytecode[pc + 1], bytecode[pc + 2]]) as i32;
                     let target = (pc as i32 + offset) as usize;
-                    targets.push(target);
+                    targets.push((pc, target));
                     pc += 3;
                 } else {
                     break;
@@ -3856,7 +3690,7 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                 if pc + 2 < bytecode.len() {
                     let offset = i16::from_be_bytes([bytecode[pc + 1], bytecode[pc + 2]]) as i32;
                     let target = (pc as i32 + offset) as usize;
-                    targets.push(target);
+                    targets.push((pc, target));
                     pc += 3;
                 } else {
                     break;
@@ -3871,7 +3705,7 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                         bytecode[pc + 4],
                     ]);
                     let target = (pc as i32 + offset) as usize;
-                    targets.push(target);
+                    targets.push((pc, target));
                     pc += 5;
                 } else {
                     break;
@@ -3881,7 +3715,7 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                 if pc + 2 < bytecode.len() {
                     let offset = i16::from_be_bytes([bytecode[pc + 1], bytecode[pc + 2]]) as i32;
                     let target = (pc as i32 + offset) as usize;
-                    targets.push(target);
+                    targets.push((pc, target));
                     pc += 3;
                 } else {
                     break;
@@ -3911,7 +3745,7 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                         bytecode[ptr + 10],
                         bytecode[ptr + 11],
                     ]);
-                    targets.push((pc as i32 + default) as usize);
+                    targets.push((pc, (pc as i32 + default) as usize));
                     let num_targets = (high - low + 1) as usize;
                     let table_end = ptr + 12 + num_targets * 4;
                     for i in 0..num_targets {
@@ -3923,7 +3757,7 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                                 bytecode[entry_ptr + 2],
                                 bytecode[entry_ptr + 3],
                             ]);
-                            targets.push((pc as i32 + offset) as usize);
+                            targets.push((pc, (pc as i32 + offset) as usize));
                         }
                     }
                     pc = table_end;
@@ -3949,7 +3783,7 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                         bytecode[ptr + 6],
                         bytecode[ptr + 7],
                     ]);
-                    targets.push((pc as i32 + default) as usize);
+                    targets.push((pc, (pc as i32 + default) as usize));
                     for i in 0..npairs {
                         let pair_ptr = ptr + 8 + (i * 8) as usize;
                         if pair_ptr + 8 <= bytecode.len() {
@@ -3959,7 +3793,7 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                                 bytecode[pair_ptr + 6],
                                 bytecode[pair_ptr + 7],
                             ]);
-                            targets.push((pc as i32 + offset) as usize);
+                            targets.push((pc, (pc as i32 + offset) as usize));
                         }
                     }
                     pc = ptr + 8 + (npairs as usize) * 8;
@@ -3967,128 +3801,26 @@ fn collect_jump_targets(bytecode: &[U1]) -> Vec<usize> {
                     break;
                 }
             }
+            OpCode::wide => {
+                pc += match bytecode.get(pc + 1).map(|&op| OpCode::from(op)) {
+                    Some(OpCode::iinc) => 6,
+                    _ => 4,
+                };
+            }
             _ => {
                 pc += opcode_size(opcode);
             }
         }
     }
 
-    targets.sort();
-    targets.dedup();
     targets
 }
 
 /// 返回 opcode 的总字节数（包括操作数）。
+///
+/// tableswitch / lookupswitch 和 wide 的长度取决于操作数，由调用方处理。
 fn opcode_size(opcode: OpCode) -> usize {
     match opcode {
-        OpCode::nop
-        | OpCode::aconst_null
-        | OpCode::iconst_m1
-        | OpCode::iconst_0
-        | OpCode::iconst_1
-        | OpCode::iconst_2
-        | OpCode::iconst_3
-        | OpCode::iconst_4
-        | OpCode::iconst_5
-        | OpCode::lconst_0
-        | OpCode::lconst_1
-        | OpCode::fconst_0
-        | OpCode::fconst_1
-        | OpCode::fconst_2
-        | OpCode::dconst_0
-        | OpCode::dconst_1
-        | OpCode::iaload
-        | OpCode::laload
-        | OpCode::faload
-        | OpCode::daload
-        | OpCode::aaload
-        | OpCode::baload
-        | OpCode::caload
-        | OpCode::saload
-        | OpCode::iastore
-        | OpCode::lastore
-        | OpCode::fastore
-        | OpCode::dastore
-        | OpCode::aastore
-        | OpCode::bastore
-        | OpCode::castore
-        | OpCode::sastore
-        | OpCode::pop
-        | OpCode::pop2
-        | OpCode::dup
-        | OpCode::dup_x1
-        | OpCode::dup_x2
-        | OpCode::dup2
-        | OpCode::dup2_x1
-        | OpCode::dup2_x2
-        | OpCode::swap
-        | OpCode::iadd
-        | OpCode::ladd
-        | OpCode::fadd
-        | OpCode::dadd
-        | OpCode::isub
-        | OpCode::lsub
-        | OpCode::fsub
-        | OpCode::dsub
-        | OpCode::imul
-        | OpCode::lmul
-        | OpCode::fmul
-        | OpCode::dmul
-        | OpCode::idiv
-        | OpCode::ldiv
-        | OpCode::fdiv
-        | OpCode::ddiv
-        | OpCode::irem
-        | OpCode::lrem
-        | OpCode::frem
-        | OpCode::drem
-        | OpCode::ineg
-        | OpCode::lneg
-        | OpCode::fneg
-        | OpCode::dneg
-        | OpCode::ishl
-        | OpCode::lshl
-        | OpCode::ishr
-        | OpCode::lshr
-        | OpCode::iushr
-        | OpCode::lushr
-        | OpCode::iand
-        | OpCode::land
-        | OpCode::ior
-        | OpCode::lor
-        | OpCode::ixor
-        | OpCode::lxor
-        | OpCode::i2l
-        | OpCode::i2f
-        | OpCode::i2d
-        | OpCode::l2i
-        | OpCode::l2f
-        | OpCode::l2d
-        | OpCode::f2i
-        | OpCode::f2l
-        | OpCode::f2d
-        | OpCode::d2i
-        | OpCode::d2l
-        | OpCode::d2f
-        | OpCode::i2b
-        | OpCode::i2c
-        | OpCode::i2s
-        | OpCode::lcmp
-        | OpCode::fcmpl
-        | OpCode::fcmpg
-        | OpCode::dcmpl
-        | OpCode::dcmpg
-        | OpCode::ireturn
-        | OpCode::lreturn
-        | OpCode::freturn
-        | OpCode::dreturn
-        | OpCode::areturn
-        | OpCode::return_void
-        | OpCode::arraylength
-        | OpCode::athrow
-        | OpCode::monitorenter
-        | OpCode::monitorexit => 1,
-
         OpCode::bipush
         | OpCode::ldc
         | OpCode::iload
@@ -4101,22 +3833,13 @@ fn opcode_size(opcode: OpCode) -> usize {
         | OpCode::fstore
         | OpCode::dstore
         | OpCode::astore
-        | OpCode::iinc
         | OpCode::ret
-        | OpCode::newarray
-        | OpCode::anewarray
+        | OpCode::newarray => 2,
+
+        OpCode::sipush
         | OpCode::ldc_w
         | OpCode::ldc2_w
-        | OpCode::new
-        | OpCode::checkcast
-        | OpCode::instanceof
-        | OpCode::getstatic
-        | OpCode::putstatic
-        | OpCode::getfield
-        | OpCode::putfield
-        | OpCode::invokevirtual
-        | OpCode::invokespecial
-        | OpCode::invokestatic
+        | OpCode::iinc
         | OpCode::ifeq
         | OpCode::ifne
         | OpCode::iflt
@@ -4133,18 +3856,23 @@ fn opcode_size(opcode: OpCode) -> usize {
         | OpCode::if_acmpne
         | OpCode::goto
         | OpCode::jsr
+        | OpCode::getstatic
+        | OpCode::putstatic
+        | OpCode::getfield
+        | OpCode::putfield
+        | OpCode::invokevirtual
+        | OpCode::invokespecial
+        | OpCode::invokestatic
+        | OpCode::new
+        | OpCode::anewarray
+        | OpCode::checkcast
+        | OpCode::instanceof
         | OpCode::ifnull
         | OpCode::ifnonnull => 3,
 
-        OpCode::invokeinterface => 5,
+        OpCode::multianewarray => 4,
 
-        OpCode::sipush => 3,
-
-        OpCode::tableswitch
-        | OpCode::lookupswitch
-        | OpCode::multianewarray
-        | OpCode::goto_w
-        | OpCode::jsr_w => 0,
+        OpCode::invokeinterface | OpCode::invokedynamic | OpCode::goto_w | OpCode::jsr_w => 5,
 
         _ => 1,
     }
@@ -4153,7 +3881,17 @@ fn opcode_size(opcode: OpCode) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use inkwell::values::AnyValue;
+    use classfile::attributes::Code;
+    use classfile::ConstantPool;
+    use std::sync::Arc;
+
+    #[test]
+    fn t_mangle() {
+        assert_eq!(mangle(b"Foo$Bar"), "Foo_24Bar");
+        assert_eq!(mangle(b"Foo_Bar"), "Foo_5FBar");
+        assert_eq!(mangle(b"(I)V"), "_28I_29V");
+        assert_ne!(mangle(b"a/b"), mangle(b"a_b"));
+    }
 
     /// 测试：将 `add(int a, int b)` 的 bytecode 编译为 LLVM IR 并执行。
     ///
@@ -4309,6 +4047,70 @@ mod tests {
         assert_eq!(stack[0], 7, "add(3, 4) should return 7, got {}", stack[0]);
     }
 
+    /// 把 `code` 翻译为类型化函数 `name` 并返回。没有方法上下文，
+    /// `code` 不能含方法调用和字段访问。
+    fn translate_code<'ctx>(
+        context: &'ctx Context,
+        module: &Module<'ctx>,
+        name: &str,
+        code: &Code,
+        cp: &ConstantPool,
+        params: &[ValueKind],
+        ret_kind: ValueKind,
+    ) -> FunctionValue<'ctx> {
+        let param_types: Vec<BasicMetadataTypeEnum> = params
+            .iter()
+            .map(|&kind| kind_type(context, kind).into())
+            .collect();
+        let fn_type = kind_type(context, ret_kind).fn_type(&param_types, false);
+        let function = module.add_function(name, fn_type, None);
+
+        let builder = context.create_builder();
+        let bytecode = code.code.as_slice();
+        let bb_map = block_map(context, function, bytecode, "bb");
+        let return_bb = context.append_basic_block(function, "return");
+        let mut interp = BytecodeInterpreter {
+            context,
+            builder: &builder,
+            bb_map,
+            function,
+            return_bb,
+            unwind_bb: return_bb,
+            locals: HashMap::new(),
+            stack: HashMap::new(),
+            depth: 0,
+            ret_kind: Some(ret_kind),
+            ret_slot: None,
+            bytecode,
+            module: module as *const Module<'ctx>,
+            method: None,
+            dependencies: Vec::new(),
+            cur_pc: 0,
+            frame_states: frame_state::analyze_code(code, cp, params)
+                .expect("frame state analysis failed"),
+            safepoints: true,
+            deopt_bufs: None,
+            deopt: DeoptInfo::new(),
+            aot: false,
+            code_valid: None,
+            loop_headers: collect_loop_headers(bytecode),
+            failed: false,
+        };
+        assert!(interp.build_body(params), "translation failed for {}", name);
+        assert!(function.verify(true), "IR verification failed for {}", name);
+        function
+    }
+
+    fn new_code(max_stack: u16, max_locals: u16, bytecode: &[U1]) -> Code {
+        Code {
+            max_stack,
+            max_locals,
+            code: Arc::new(bytecode.to_vec()),
+            exceptions: Vec::new(),
+            attrs: Vec::new(),
+        }
+    }
+
     /// 测试：if_icmpge 控制流翻译。
     ///
     /// Java:
@@ -4331,163 +4133,96 @@ mod tests {
     fn test_compile_and_execute_branch() {
         // collect_jump_targets 计算: target = pc + offset
         // 要从 PC=2 跳转到 PC=7，offset = 7 - 2 = 5
-        let bytecode: &[U1] = &[
-            0x1B, // 0: iload_1
-            0x1C, // 1: iload_2
-            0xA2, 0x00, 0x05, // 2: if_icmpge -> PC=2+5=7
-            0x1C, // 5: iload_2
-            0xAC, // 6: ireturn
-            0x1B, // 7: iload_1
-            0xAC, // 8: ireturn
-        ];
-        let max_locals = 3;
-        let max_stack = 2;
+        let code = new_code(
+            2,
+            3,
+            &[
+                0x1B, // 0: iload_1
+                0x1C, // 1: iload_2
+                0xA2, 0x00, 0x05, // 2: if_icmpge -> PC=2+5=7
+                0x1C, // 5: iload_2
+                0xAC, // 6: ireturn
+                0x1B, // 7: iload_1
+                0xAC, // 8: ireturn
+            ],
+        );
 
         let context = Context::create();
         let leaked = Box::leak(Box::new(context));
         let module = leaked.create_module("test_max_module");
-        let builder = leaked.create_builder();
         let execution_engine = module
             .create_jit_execution_engine(inkwell::OptimizationLevel::None)
             .expect("Failed to create execution engine");
 
-        // 创建函数
-        let i8_type = leaked.i8_type();
-        let ptr_type = i8_type.ptr_type(inkwell::AddressSpace::default());
-        let fn_type = leaked
-            .void_type()
-            .fn_type(&[ptr_type.into(), ptr_type.into()], false);
-        let function = module.add_function("jit_test_max", fn_type, None);
-
-        let jump_targets = collect_jump_targets(bytecode);
-
-        let mut bb_map: HashMap<usize, BasicBlock> = HashMap::new();
-        let entry_bb = leaked.append_basic_block(function, "entry");
-        bb_map.insert(0, entry_bb);
-        for offset in &jump_targets {
-            if *offset != 0 && *offset < bytecode.len() {
-                let bb = leaked.append_basic_block(function, &format!("bb_{}", offset));
-                bb_map.insert(*offset, bb);
-            }
-        }
-        let return_bb = leaked.append_basic_block(function, "return");
-
-        builder.position_at_end(entry_bb);
-
-        let i32_type = leaked.i32_type();
-        let locals_ptr = function.get_first_param().unwrap().into_pointer_value();
-        let stack_ptr = function.get_nth_param(1).unwrap().into_pointer_value();
-        let locals_i32 = builder
-            .build_pointer_cast(
-                locals_ptr,
-                i32_type.ptr_type(inkwell::AddressSpace::default()),
-                "locals_cast",
-            )
-            .expect("cast failed");
-        let stack_i32 = builder
-            .build_pointer_cast(
-                stack_ptr,
-                i32_type.ptr_type(inkwell::AddressSpace::default()),
-                "stack_cast",
-            )
-            .expect("cast failed");
-
-        // 创建 locals alloca
-        let num_locals = max_locals.max(1);
-        let local_vars: Vec<PointerValue> = (0..num_locals)
-            .map(|i| {
-                let alloca = builder
-                    .build_alloca(i32_type, &format!("local_{}", i))
-                    .expect("alloca failed");
-                let idx = i32_type.const_int(i as u64, false);
-                let ptr = unsafe {
-                    builder
-                        .build_in_bounds_gep(i32_type, locals_i32, &[idx], "local_ptr")
-                        .expect("gep failed")
-                };
-                let loaded = builder
-                    .build_load(i32_type, ptr, &format!("load_local_{}", i))
-                    .expect("load failed")
-                    .into_int_value();
-                builder.build_store(alloca, loaded).expect("store failed");
-                alloca
-            })
-            .collect();
-
-        let stack_size = max_stack.max(1);
-        let stack_vars: Vec<PointerValue> = (0..stack_size)
-            .map(|i| {
-                builder
-                    .build_alloca(i32_type, &format!("stack_{}", i))
-                    .expect("alloca failed")
-            })
-            .collect();
-
-        let stack_top = builder
-            .build_alloca(i32_type, "stack_top")
-            .expect("alloca failed");
-        builder
-            .build_store(stack_top, i32_type.const_int(0, false))
-            .expect("store failed");
+        translate_code(
+            leaked,
+            &module,
+            "jit_test_max",
+            &code,
+            &Arc::new(Vec::new()),
+            &[ValueKind::Ref, ValueKind::Int, ValueKind::Int],
+            ValueKind::Int,
+        );
 
-        let mut interp = BytecodeInterpreter {
-            context: leaked,
-            module: &module as *const Module<'_>,
-            builder: &builder,
-            bb_map,
-            function,
-            return_bb,
-            local_vars,
-            stack_vars,
-            stack_top,
-            bytecode,
-            max_stack,
-            stack_param: Some(stack_i32),
+        let jit_fn = unsafe {
+            execution_engine
+                .get_function::<unsafe extern "C" fn(i32, i32, i32) -> i32>("jit_test_max")
+                .expect("get_function failed")
         };
 
-        let entry = *interp.bb_map.get(&0).unwrap();
-        builder.position_at_end(entry);
-        interp.translate_bytecode(0);
-        interp.translate_remaining_blocks();
-
-        if return_bb.get_terminator().is_none() {
-            builder.position_at_end(return_bb);
-            builder.build_return(None).expect("return failed");
+        unsafe {
+            assert_eq!(jit_fn.call(0, 5, 3), 5, "max(5, 3) should be 5");
+            assert_eq!(jit_fn.call(0, 2, 8), 8, "max(2, 8) should be 8");
+            assert_eq!(jit_fn.call(0, 4, 4), 4, "max(4, 4) should be 4");
         }
+    }
 
-        assert!(function.verify(true), "IR verification failed for max()");
+    /// 测试：long 参数、局部变量和操作数栈使用 i64 存储，不会被截断。
+    ///
+    /// Java:
+    /// ```java
+    /// static long mulAdd(long a, long b) { return a * b + 1L; }
+    /// ```
+    ///
+    /// Bytecode:
+    /// - 0: lload_0  — 加载 a（local 0-1）
+    /// - 1: lload_2  — 加载 b（local 2-3）
+    /// - 2: lmul
+    /// - 3: lconst_1
+    /// - 4: ladd
+    /// - 5: lreturn
+    #[test]
+    fn test_compile_and_execute_long() {
+        let code = new_code(4, 4, &[0x1E, 0x20, 0x69, 0x0A, 0x61, 0xAD]);
 
-        let jit_fn: extern "C" fn(*mut i32, *mut i32) = unsafe {
-            let f: inkwell::execution_engine::JitFunction<unsafe extern "C" fn(*mut (), *mut ())> =
-                execution_engine
-                    .get_function("jit_test_max")
-                    .expect("get_function failed");
-            std::mem::transmute(f.as_raw())
-        };
+        let context = Context::create();
+        let leaked = Box::leak(Box::new(context));
+        let module = leaked.create_module("test_long_module");
+        let execution_engine = module
+            .create_jit_execution_engine(inkwell::OptimizationLevel::None)
+            .expect("Failed to create execution engine");
 
-        // 测试 max(5, 3) = 5
-        let mut locals1: [i32; 3] = [0, 5, 3];
-        let mut stack1: [i32; 2] = [0, 0];
-        unsafe {
-            jit_fn(locals1.as_mut_ptr(), stack1.as_mut_ptr());
-        }
-        assert_eq!(stack1[0], 5, "max(5, 3) should be 5");
+        translate_code(
+            leaked,
+            &module,
+            "jit_test_mul_add",
+            &code,
+            &Arc::new(Vec::new()),
+            &[ValueKind::Long, ValueKind::Long],
+            ValueKind::Long,
+        );
 
-        // 测试 max(2, 8) = 8
-        let mut locals2: [i32; 3] = [0, 2, 8];
-        let mut stack2: [i32; 2] = [0, 0];
-        unsafe {
-            jit_fn(locals2.as_mut_ptr(), stack2.as_mut_ptr());
-        }
-        assert_eq!(stack2[0], 8, "max(2, 8) should be 8");
+        let jit_fn = unsafe {
+            execution_engine
+                .get_function::<unsafe extern "C" fn(i64, i64) -> i64>("jit_test_mul_add")
+                .expect("get_function failed")
+        };
 
-        // 测试 max(4, 4) = 4
-        let mut locals3: [i32; 3] = [0, 4, 4];
-        let mut stack3: [i32; 2] = [0, 0];
         unsafe {
-            jit_fn(locals3.as_mut_ptr(), stack3.as_mut_ptr());
+            assert_eq!(jit_fn.call(3, 4), 13);
+            assert_eq!(jit_fn.call(1 << 40, 3), (3 << 40) + 1);
+            assert_eq!(jit_fn.call(-1, 1 << 33), -(1 << 33) + 1);
         }
-        assert_eq!(stack3[0], 4, "max(4, 4) should be 4");
     }
 
     /// 端到端测试：从真实 Java .class 文件加载 bytecode，通过 JIT 编译并执行。
@@ -4523,158 +4258,46 @@ mod tests {
         let code = add_method
             .get_code()
             .expect("add method has no Code attribute");
-        let bytecode = code.code.as_slice();
-        let max_locals = code.max_locals as usize;
-        let max_stack = code.max_stack as usize;
 
         // 3. 通过 JIT 编译并执行
         let context = Context::create();
         let leaked = Box::leak(Box::new(context));
         let module = leaked.create_module("test_e2e_module");
-        let builder = leaked.create_builder();
         let execution_engine = module
             .create_jit_execution_engine(inkwell::OptimizationLevel::None)
             .expect("Failed to create execution engine");
 
         let fn_name = "jit_test_e2e";
-        let i8_type = leaked.i8_type();
-        let ptr_type = i8_type.ptr_type(inkwell::AddressSpace::default());
-        let fn_type = leaked
-            .void_type()
-            .fn_type(&[ptr_type.into(), ptr_type.into()], false);
-        let function = module.add_function(fn_name, fn_type, None);
-
-        let entry_bb = leaked.append_basic_block(function, "entry");
-        let return_bb = leaked.append_basic_block(function, "return");
-        builder.position_at_end(entry_bb);
-
-        let i32_type = leaked.i32_type();
-        let locals_ptr = function.get_first_param().unwrap().into_pointer_value();
-        let stack_ptr = function.get_nth_param(1).unwrap().into_pointer_value();
-        let locals_i32 = builder
-            .build_pointer_cast(
-                locals_ptr,
-                i32_type.ptr_type(inkwell::AddressSpace::default()),
-                "locals_cast",
-            )
-            .expect("cast failed");
-        let stack_i32 = builder
-            .build_pointer_cast(
-                stack_ptr,
-                i32_type.ptr_type(inkwell::AddressSpace::default()),
-                "stack_cast",
-            )
-            .expect("cast failed");
-
-        // 预填 locals：slot0=this, slot1=a, slot2=b
-        let num_locals = max_locals.max(1);
-        let local_vars: Vec<PointerValue> = (0..num_locals)
-            .map(|i| {
-                let alloca = builder
-                    .build_alloca(i32_type, &format!("local_{}", i))
-                    .expect("alloca failed");
-                let idx = i32_type.const_int(i as u64, false);
-                let ptr = unsafe {
-                    builder
-                        .build_in_bounds_gep(i32_type, locals_i32, &[idx], "local_ptr")
-                        .expect("gep failed")
-                };
-                let loaded = builder
-                    .build_load(i32_type, ptr, &format!("load_local_{}", i))
-                    .expect("load failed")
-                    .into_int_value();
-                builder.build_store(alloca, loaded).expect("store failed");
-                alloca
-            })
-            .collect();
-
-        let stack_size = max_stack.max(1);
-        let stack_vars: Vec<PointerValue> = (0..stack_size)
-            .map(|i| {
-                builder
-                    .build_alloca(i32_type, &format!("stack_{}", i))
-                    .expect("alloca failed")
-            })
-            .collect();
-
-        let stack_top = builder
-            .build_alloca(i32_type, "stack_top")
-            .expect("alloca failed");
-        builder
-            .build_store(stack_top, i32_type.const_int(0, false))
-            .expect("store failed");
-
-        let jump_targets = collect_jump_targets(bytecode);
-        let mut bb_map: HashMap<usize, BasicBlock> = HashMap::new();
-        let entry_block = *bb_map.entry(0).or_insert(entry_bb);
-        for offset in &jump_targets {
-            if *offset != 0 && *offset < bytecode.len() {
-                let bb = leaked.append_basic_block(function, &format!("bb_{}", offset));
-                bb_map.insert(*offset, bb);
-            }
-        }
-
-        let mut interp = BytecodeInterpreter {
-            context: leaked,
-            module: &module as *const Module<'_>,
-            builder: &builder,
-            bb_map,
-            function,
-            return_bb,
-            local_vars,
-            stack_vars,
-            stack_top,
-            bytecode,
-            max_stack,
-            stack_param: Some(stack_i32),
-        };
-
-        let entry = *interp.bb_map.get(&0).unwrap();
-        builder.position_at_end(entry);
-        interp.translate_bytecode(0);
-        interp.translate_remaining_blocks();
-
-        if return_bb.get_terminator().is_none() {
-            builder.position_at_end(return_bb);
-            builder.build_return(None).expect("return failed");
-        }
-
-        assert!(
-            function.verify(true),
-            "IR verification failed for class-file method"
+        translate_code(
+            leaked,
+            &module,
+            fn_name,
+            &code,
+            &cf.cp,
+            &[ValueKind::Ref, ValueKind::Int, ValueKind::Int],
+            ValueKind::Int,
         );
 
         // 4. 获取函数指针并执行
-        let jit_fn: extern "C" fn(*mut i32, *mut i32) = unsafe {
-            let f: inkwell::execution_engine::JitFunction<unsafe extern "C" fn(*mut (), *mut ())> =
-                execution_engine
-                    .get_function(fn_name)
-                    .expect("get_function failed");
-            std::mem::transmute(f.as_raw())
+        let jit_fn = unsafe {
+            execution_engine
+                .get_function::<unsafe extern "C" fn(i32, i32, i32) -> i32>(fn_name)
+                .expect("get_function failed")
         };
 
-        // 测试 add(10, 20) = 30
-        let mut locals: [i32; 3] = [0, 10, 20];
-        let mut stack: [i32; 2] = [0, 0];
-        unsafe {
-            jit_fn(locals.as_mut_ptr(), stack.as_mut_ptr());
-        }
-        assert_eq!(
-            stack[0], 30,
-            "SimpleCalc.add(10, 20) should return 30, got {}",
-            stack[0]
-        );
-
-        // 测试 add(-5, 5) = 0
-        let mut locals2: [i32; 3] = [0, -5, 5];
-        let mut stack2: [i32; 2] = [0, 0];
         unsafe {
-            jit_fn(locals2.as_mut_ptr(), stack2.as_mut_ptr());
+            // 测试 add(10, 20) = 30
+            assert_eq!(
+                jit_fn.call(0, 10, 20),
+                30,
+                "SimpleCalc.add(10, 20) should return 30"
+            );
+            // 测试 add(-5, 5) = 0
+            assert_eq!(
+                jit_fn.call(0, -5, 5),
+                0,
+                "SimpleCalc.add(-5, 5) should return 0"
+            );
         }
-        assert_eq!(
-            stack2[0], 0,
-            "SimpleCalc.add(-5, 5) should return 0, got {}",
-            stack2[0]
-        );
     }
 }
@@ -0,0 +1,169 @@
+// ============================================================
+// 类层次分析（Class Hierarchy Analysis, CHA）
+//
+// 对于 invokevirtual，如果目标方法在当前已加载的类层次中没有被任何子类
+// 覆盖（"effectively final"），那么调用点实际上只有一个可能的目标，
+// JIT 可以：
+// - 直接调用该目标，跳过 v-table 解析（去虚化，devirtualization）
+// - 进一步把小方法体内联进调用方
+//
+// 这个结论只对"当前已加载的类"成立。之后如果加载了一个覆盖该方法的
+// 子类，假设就失效了。因此每个去虚化的调用点都依赖一个 `Assumption`：
+//
+// 1. 编译时：`Assumption` 被登记到全局依赖表，key 是被假设的目标方法
+// 2. 加载新类时：`notify_class_loaded` 检查新类是否覆盖了某个被假设的
+//    方法，若是则把对应 `Assumption` 标记为无效（去优化）
+// 3. 运行时：去虚化的调用点每次先检查 `Assumption::is_valid()`，
+//    无效时回退到完整的虚方法解析
+// 4. 依赖该假设的方法经 `deopt::invalidate` 失效：`jit_impl` 被清空，
+//    下次调用时重新编译，正在执行旧代码的帧在下一个安全点回到解释器
+//
+// `Assumption` 的地址被嵌入机器码，所以与 `InlineCache` 一样用
+// `Box::leak` 分配。
+// ============================================================
+
+use super::deopt;
+use crate::runtime::sys_dic;
+use crate::types::{ClassRef, MethodIdRef};
+use classfile::BytesRef;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use crate::runtime::method::MethodId;
+
+/// 一个可被类加载推翻的 CHA 假设。
+///
+/// 内存布局固定为单个 `AtomicBool`，JIT 代码直接以 i8 读取它做守卫。
+#[repr(C)]
+pub struct Assumption {
+    valid: AtomicBool,
+}
+
+impl Assumption {
+    pub fn new_leaked() -> &'static Assumption {
+        Box::leak(Box::new(Assumption {
+            valid: AtomicBool::new(true),
+        }))
+    }
+
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.valid.load(Ordering::Acquire)
+    }
+
+    pub fn invalidate(&self) {
+        self.valid.store(false, Ordering::Release);
+    }
+}
+
+/// 编译期收集的一条依赖：调用点假设 `target` 没有被覆盖。
+pub struct Dependency {
+    pub target: MethodIdRef,
+    pub assumption: &'static Assumption,
+}
+
+struct Registered {
+    holder: ClassRef,
+    name: BytesRef,
+    desc: BytesRef,
+    assumption: &'static Assumption,
+    /// 依赖该假设的已编译方法。
+    dependent: Weak<MethodId>,
+}
+
+static DEPENDENCIES: OnceLock<Mutex<Vec<Registered>>> = OnceLock::new();
+
+fn dependencies() -> &'static Mutex<Vec<Registered>> {
+    DEPENDENCIES.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn is_subclass_of(cls: &ClassRef, holder: &ClassRef) -> bool {
+    let mut cur = cls.get_super_class();
+    while let Some(c) = cur {
+        if Arc::ptr_eq(&c, holder) {
+            return true;
+        }
+        cur = c.get_super_class();
+    }
+    false
+}
+
+fn declares(cls: &ClassRef, name: &BytesRef, desc: &BytesRef) -> bool {
+    cls.is_instance()
+        && cls
+            .get_this_class_method(name, desc)
+            .map(|m| !m.method.is_static())
+            .unwrap_or(false)
+}
+
+/// 判断 `mir` 在当前已加载的类层次中是否只有唯一实现。
+pub fn is_effectively_final(mir: &MethodIdRef) -> bool {
+    let method = &mir.method;
+    if method.is_static() || method.is_abstract() || method.is_native() {
+        return false;
+    }
+    if method.is_final() || method.is_private() || method.class.is_final() {
+        return true;
+    }
+    // 接口的 default 方法：实现类不是接口的子类，
+    // `is_subclass_of` 看不到它们的覆盖，不做假设
+    if method.class.is_interface() {
+        return false;
+    }
+
+    let holder = &method.class;
+    !sys_dic::classes()
+        .iter()
+        .any(|c| is_subclass_of(c, holder) && declares(c, &method.name, &method.desc))
+}
+
+/// 把编译 `dependent` 时收集的依赖登记到全局表。
+///
+/// 在持有依赖表锁的情况下重新检查一次假设：编译期间可能已经有覆盖
+/// 目标方法的子类被加载，此时直接使假设失效，由运行时守卫兜底。
+pub fn register(dependent: &MethodIdRef, deps: Vec<Dependency>) {
+    let mut table = dependencies().lock().unwrap();
+    for dep in deps {
+        if !is_effectively_final(&dep.target) {
+            dep.assumption.invalidate();
+            continue;
+        }
+        table.push(Registered {
+            holder: dep.target.method.class.clone(),
+            name: dep.target.method.name.clone(),
+            desc: dep.target.method.desc.clone(),
+            assumption: dep.assumption,
+            dependent: Arc::downgrade(dependent),
+        });
+    }
+}
+
+/// 新类加载完成后调用，使被它推翻的 CHA 假设失效。
+pub fn notify_class_loaded(class: &ClassRef) {
+    if !class.is_instance() {
+        return;
+    }
+
+    let table = match DEPENDENCIES.get() {
+        Some(table) => table,
+        None => return,
+    };
+    let mut table = table.lock().unwrap();
+
+    table.retain(|dep| {
+        let broken = is_subclass_of(class, &dep.holder) && declares(class, &dep.name, &dep.desc);
+        if broken {
+            dep.assumption.invalidate();
+            if let Some(dependent) = dep.dependent.upgrade() {
+                let reason = format!(
+                    "{} overrides {}.{}",
+                    String::from_utf8_lossy(class.name.as_slice()),
+                    String::from_utf8_lossy(dep.holder.name.as_slice()),
+                    String::from_utf8_lossy(dep.name.as_slice()),
+                );
+                deopt::invalidate(&dependent, &reason);
+            }
+        }
+        !broken
+    });
+}
@@ -0,0 +1,180 @@
+// ============================================================
+// 去优化（Deoptimization）：从 JIT 帧回到解释器
+//
+// 编译后的代码建立在一些假设之上（CHA 去虚化、内联……）。假设被推翻后，
+// 仅仅清空 `MethodId::jit_impl` 只能让**以后的**调用走解释器或重新编译；
+// 已经在执行中的 JIT 帧仍然会跑完旧代码。本模块负责把这些活跃帧就地
+// 转交给解释器。
+//
+// ## 安全点与元数据
+//
+// builder 在每个调用点之后插入一个安全点：
+// 1. 读取本次编译私有的 `valid` 标志（`Assumption`，地址嵌入机器码）
+// 2. 标志有效则继续执行编译后的代码
+// 3. 标志无效则把 locals 与操作数栈各槽的值（按类型编码为 i64）写入
+//    两个缓冲区，调用 `jit_deopt(point, locals, stack)`，返回它的结果
+//
+// 每个安全点对应一条 `DeoptPoint`：恢复执行的 bci，以及该 bci 上 locals /
+// 操作数栈每个槽的类型（由 `frame_state` 分析得出）。
+//
+// ## 帧重建
+//
+// `invoke_java` 在进入 JIT 代码前已经为该方法压入了一个 `Frame`，
+// 去优化直接复用它：
+// - 按元数据把各槽的值还原成 `Local` 与 `Frame::area.stack` 中的 `Slot`
+// - 把 `Frame::pc` 设为恢复点的 bci
+// - 用 `Interp` 从该 bci 继续执行直到方法返回
+// - 返回值交还 JIT 代码，由它作为方法的返回值返回
+//
+// ## 失效
+//
+// `invalidate` 清空 `jit_impl`（下次调用重新编译），并把旧代码的
+// `valid` 标志置为无效，使所有仍在执行旧代码的帧在下一个安全点去优化。
+// 触发来源：类加载推翻 CHA 假设（`cha::notify_class_loaded`）。
+// ============================================================
+
+use super::cha::Assumption;
+use super::dump;
+use super::frame_state::ValueKind;
+use crate::oop::Oop;
+use crate::runtime::local::Local;
+use crate::runtime::{self, Interp};
+use crate::types::MethodIdRef;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 一个安全点上恢复解释执行所需的信息。
+pub struct DeoptPoint {
+    /// 解释器恢复执行的 bci（调用指令的下一条指令）。
+    pub bci: usize,
+    /// 每个 local 槽的类型。
+    pub locals: Vec<ValueKind>,
+    /// 操作数栈每个槽的类型，长度即该 bci 上的栈深度。
+    pub stack: Vec<ValueKind>,
+}
+
+/// 一次编译产物的去优化元数据。
+pub struct DeoptInfo {
+    /// 编译后的代码是否仍然有效。
+    pub valid: &'static Assumption,
+    points: Vec<DeoptPoint>,
+}
+
+impl DeoptInfo {
+    pub fn new() -> Self {
+        Self {
+            valid: Assumption::new_leaked(),
+            points: Vec::new(),
+        }
+    }
+
+    /// 登记一个安全点，返回它的编号。
+    pub fn add_point(&mut self, point: DeoptPoint) -> usize {
+        self.points.push(point);
+        self.points.len() - 1
+    }
+
+    pub fn point(&self, idx: usize) -> Option<&DeoptPoint> {
+        self.points.get(idx)
+    }
+
+    /// 取出全部安全点，按编号排列（AOT 写入清单时使用）。
+    pub fn into_points(self) -> Vec<DeoptPoint> {
+        self.points
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid.is_valid()
+    }
+}
+
+impl Default for DeoptInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 使 `mir` 的编译结果失效：以后的调用重新编译，活跃帧在下一个安全点去优化。
+pub fn invalidate(mir: &MethodIdRef, reason: &str) {
+    let compiled = mir.jit_impl.lock().unwrap().take();
+    if let Some(compiled) = compiled {
+        info!("JIT: invalidate {:?}, reason: {}", mir.method, reason);
+        dump::log_not_entrant(compiled.compile_id, compiled.level, &mir.method, reason);
+        compiled.deopt.valid.invalidate();
+    }
+}
+
+/// 在安全点 `point` 处把 JIT 帧转交给解释器，执行完方法的剩余部分。
+///
+/// `locals` / `stack` 是 JIT 代码在安全点写出的各槽的值，按
+/// `runtime::encode_value` 编码；long/double 的值在低位槽，`Top` 槽的值无意义。
+/// 返回方法的返回值；void 方法或发生异常时为 None。
+pub fn deoptimize(
+    mir: &MethodIdRef,
+    point: &DeoptPoint,
+    locals: &[i64],
+    stack: &[i64],
+) -> Option<Oop> {
+    let jt = runtime::thread::current_java_thread();
+    let frame = jt.read().unwrap().frames.last().cloned();
+    let frame = match frame {
+        Some(frame) if Arc::ptr_eq(&frame.read().unwrap().mir, mir) => frame,
+        _ => {
+            warn!("JIT: deopt {:?}: top frame does not match", mir.method);
+            return None;
+        }
+    };
+
+    info!("JIT: deoptimize {:?} at bci {}", mir.method, point.bci);
+
+    let max_locals = mir.method.get_max_locals();
+    let mut local = Local::new(max_locals);
+    for (i, kind) in point.locals.iter().enumerate() {
+        match kind {
+            ValueKind::Int => local.set_int(i, locals[i] as i32),
+            ValueKind::Float => local.set_float(i, f32::from_bits(locals[i] as u32)),
+            ValueKind::Long => local.set_long(i, locals[i]),
+            ValueKind::Double => local.set_double(i, f64::from_bits(locals[i] as u64)),
+            ValueKind::Ref => local.set_ref(i, ref_oop(locals[i])),
+            ValueKind::Top => (),
+        }
+    }
+
+    let frame_h = frame.try_read().unwrap();
+    {
+        let mut stk = frame_h.area.stack.borrow_mut();
+        stk.clear();
+        for (i, kind) in point.stack.iter().enumerate() {
+            match kind {
+                ValueKind::Int => stk.push_int(stack[i] as i32),
+                ValueKind::Float => stk.push_float(f32::from_bits(stack[i] as u32)),
+                // push_long / push_double 自带高位占位槽
+                ValueKind::Long => stk.push_long(stack[i]),
+                ValueKind::Double => stk.push_double(f64::from_bits(stack[i] as u64)),
+                ValueKind::Ref => stk.push_ref(ref_oop(stack[i]), false),
+                // long/double 的高位槽已随低位槽入栈；其余 Top 是死值，占位即可
+                ValueKind::Top => {
+                    if i == 0 || !point.stack[i - 1].is_wide() {
+                        stk.push_int(0);
+                    }
+                }
+            }
+        }
+    }
+    frame_h.pc.store(point.bci as i32, Ordering::Relaxed);
+
+    let mut interp = Interp::new(frame_h, local);
+    interp.run();
+
+    let return_v = frame.try_read().unwrap().area.return_v.borrow().clone();
+    return_v
+}
+
+fn ref_oop(v: i64) -> Oop {
+    // null 引用用 slot_id = 0 表示
+    match v as u32 {
+        0 => Oop::Null,
+        slot_id => Oop::Ref(slot_id),
+    }
+}
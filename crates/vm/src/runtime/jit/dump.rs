@@ -0,0 +1,179 @@
+// ============================================================
+// 编译日志与 IR / 机器码转储
+//
+// 编译后的方法行为异常时，需要看到 JIT 实际生成了什么。本模块提供两种
+// 诊断输出：
+//
+// 1. 编译日志（`--print-compilation`，对应 HotSpot 的
+//    `-XX:+PrintCompilation`）：每编译一个方法输出一行
+//
+//    ```text
+//        153    7    2   Foo::bar (42 bytes)
+//        160    8    2   Foo::baz (17 bytes)   COMPILE SKIPPED: IR verification failed
+//        201    7    2   Foo::bar (42 bytes)   made not entrant: class loaded: Sub
+//        230    9    2   Foo::qux (12 bytes)   (aot)
+//    ```
+//
+//    列依次为：VM 启动以来的毫秒数、编译编号、JIT 级别、方法、bytecode 长度。
+//
+// 2. 转储（`--jit-dump-dir DIR`，可用 `--jit-dump PATTERN` 过滤方法）：
+//    每次编译在 DIR 下写出
+//    - `<id>_<class>.<method>.ll`：pass pipeline 之后的 LLVM IR
+//    - `<id>_<class>.<method>.s`：本机汇编（机器码的反汇编形式）
+//
+//    IR 验证失败时只写出 `.ll`，便于定位 builder 的问题。
+// ============================================================
+
+use super::opt::{JitLevel, MethodPattern};
+use crate::runtime::method::Method;
+use inkwell::module::Module;
+use inkwell::targets::{FileType, TargetMachine};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::Instant;
+use tracing::warn;
+
+#[derive(Default)]
+struct DumpConfig {
+    print_compilation: bool,
+    dir: Option<PathBuf>,
+    filters: Vec<MethodPattern>,
+}
+
+static CONFIG: OnceLock<RwLock<DumpConfig>> = OnceLock::new();
+static START: OnceLock<Instant> = OnceLock::new();
+static COMPILE_ID: AtomicUsize = AtomicUsize::new(1);
+
+fn config() -> &'static RwLock<DumpConfig> {
+    CONFIG.get_or_init(|| RwLock::new(DumpConfig::default()))
+}
+
+/// 开启编译日志。
+pub fn set_print_compilation(enable: bool) {
+    START.get_or_init(Instant::now);
+    config().write().unwrap().print_compilation = enable;
+}
+
+/// 设置转储目录，目录不存在时创建。
+pub fn set_dump_dir(dir: PathBuf) -> Result<(), String> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("cannot create dump dir '{}': {}", dir.display(), e))?;
+    config().write().unwrap().dir = Some(dir);
+    Ok(())
+}
+
+/// 添加转储过滤模式（`Class.method`，支持 `*`）。没有过滤模式时转储所有方法。
+pub fn add_dump_filter(pattern: &str) -> Result<(), String> {
+    let pattern = pattern.parse()?;
+    config().write().unwrap().filters.push(pattern);
+    Ok(())
+}
+
+/// 分配一个编译编号。
+pub fn next_compile_id() -> usize {
+    COMPILE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn print_enabled() -> bool {
+    config().read().unwrap().print_compilation
+}
+
+fn print_line(id: usize, level: JitLevel, method: &Method, suffix: &str) {
+    let elapsed = START.get_or_init(Instant::now).elapsed().as_millis();
+    let size = method.code.as_ref().map_or(0, |code| code.code.len());
+    println!(
+        "{:>8} {:>4} {:>4}   {}::{} ({} bytes){}",
+        elapsed,
+        id,
+        level,
+        String::from_utf8_lossy(method.cls_name.as_slice()),
+        String::from_utf8_lossy(method.name.as_slice()),
+        size,
+        suffix
+    );
+}
+
+/// 记录一次成功的编译。
+pub fn log_compiled(id: usize, level: JitLevel, method: &Method) {
+    if print_enabled() {
+        print_line(id, level, method, "");
+    }
+}
+
+/// 记录一次失败的编译。
+pub fn log_skipped(id: usize, level: JitLevel, method: &Method, reason: &str) {
+    if print_enabled() {
+        print_line(
+            id,
+            level,
+            method,
+            &format!("   COMPILE SKIPPED: {}", reason),
+        );
+    }
+}
+
+/// 记录一次从 AOT 缓存安装的方法体。
+pub fn log_aot_loaded(id: usize, level: JitLevel, method: &Method) {
+    if print_enabled() {
+        print_line(id, level, method, "   (aot)");
+    }
+}
+
+/// 记录编译结果失效。
+pub fn log_not_entrant(id: usize, level: JitLevel, method: &Method, reason: &str) {
+    if print_enabled() {
+        print_line(
+            id,
+            level,
+            method,
+            &format!("   made not entrant: {}", reason),
+        );
+    }
+}
+
+/// 若 `method` 需要转储，返回转储目录。
+fn dump_dir_for(method: &Method) -> Option<PathBuf> {
+    let config = config().read().unwrap();
+    let dir = config.dir.as_ref()?;
+    if config.filters.is_empty() || config.filters.iter().any(|p| p.matches_method(method)) {
+        Some(dir.clone())
+    } else {
+        None
+    }
+}
+
+fn dump_path(dir: &Path, id: usize, method: &Method, ext: &str) -> PathBuf {
+    let class = String::from_utf8_lossy(method.cls_name.as_slice()).replace('/', ".");
+    let name = String::from_utf8_lossy(method.name.as_slice()).replace(['<', '>'], "");
+    dir.join(format!("{}_{}.{}.{}", id, class, name, ext))
+}
+
+/// 转储 `module` 的 IR；`with_asm` 为 true 时同时输出本机汇编。
+///
+/// 汇编由 module 的副本生成：代码生成会改写 IR，原 module 还要交给
+/// ExecutionEngine。
+pub fn dump_module(
+    id: usize,
+    method: &Method,
+    module: &Module,
+    target_machine: &TargetMachine,
+    with_asm: bool,
+) {
+    let dir = match dump_dir_for(method) {
+        Some(dir) => dir,
+        None => return,
+    };
+    let ll = dump_path(&dir, id, method, "ll");
+    if let Err(e) = module.print_to_file(&ll) {
+        warn!("JIT: dump IR to {} failed: {}", ll.display(), e);
+    }
+
+    if with_asm {
+        let asm = dump_path(&dir, id, method, "s");
+        let copy = module.clone();
+        if let Err(e) = target_machine.write_to_file(&copy, FileType::Assembly, &asm) {
+            warn!("JIT: dump assembly to {} failed: {}", asm.display(), e);
+        }
+    }
+}
@@ -0,0 +1,687 @@
+// ============================================================
+// 字节码帧状态分析
+//
+// JIT 代码为 locals 和操作数栈的每个槽按值的类型分配存储：int/引用是 i32，
+// long 是 i64，float/double 是 float/double，long/double 只占低位槽。
+// 翻译一条指令时必须知道它读写的每个槽在该 bci 上装的是什么类型的值，
+// 去优化时把 JIT 的槽还原成解释器带类型的 `Slot` 也需要同样的信息。
+//
+// 本模块对 bytecode 做一遍简化的类型推导（与 verifier 的数据流分析同构，
+// 但只区分 int/float/long/double/ref 五种类型）：
+// - 入口状态由方法签名决定
+// - 每条指令按其栈效果变换状态
+// - 在控制流汇合点逐槽合并，类型不一致的槽变成 Top（该槽的值已死，
+//   不会再被读取，还原时随便填什么都行）
+//
+// 结果在编译期计算一次，翻译时逐条指令查询，并按安全点截取后存入 `DeoptInfo`。
+// 遇到 jsr/ret 或汇合点栈深度不一致这类分析不了的情况，整体返回 None，
+// 该方法不编译。
+// ============================================================
+
+use crate::runtime::method::Method;
+use class_parser::MethodSignature;
+use classfile::attributes::Code;
+use classfile::constant_pool::{self, Type as CpType};
+use classfile::{ConstantPool, OpCode, SignatureType, U1};
+use std::collections::HashMap;
+
+/// 一个 local / 操作数栈槽中值的类型。
+///
+/// long/double 占两个槽：低位槽记为 `Long` / `Double`，高位槽记为 `Top`。
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Top,
+    Int,
+    Float,
+    Long,
+    Double,
+    Ref,
+}
+
+impl ValueKind {
+    /// 签名类型对应的值类型；void 返回 None。
+    pub fn from_signature(t: &SignatureType) -> Option<ValueKind> {
+        match t {
+            SignatureType::Byte
+            | SignatureType::Boolean
+            | SignatureType::Char
+            | SignatureType::Short
+            | SignatureType::Int => Some(ValueKind::Int),
+            SignatureType::Float => Some(ValueKind::Float),
+            SignatureType::Long => Some(ValueKind::Long),
+            SignatureType::Double => Some(ValueKind::Double),
+            SignatureType::Object(_, _, _) | SignatureType::Array(_) => Some(ValueKind::Ref),
+            SignatureType::Void => None,
+        }
+    }
+
+    /// 字段 / 返回值描述符对应的值类型；void 返回 None。
+    pub fn from_descriptor(desc: &[u8]) -> Option<ValueKind> {
+        match desc.first()? {
+            b'J' => Some(ValueKind::Long),
+            b'D' => Some(ValueKind::Double),
+            b'F' => Some(ValueKind::Float),
+            b'L' | b'[' => Some(ValueKind::Ref),
+            b'V' => None,
+            _ => Some(ValueKind::Int),
+        }
+    }
+
+    pub fn is_wide(self) -> bool {
+        matches!(self, ValueKind::Long | ValueKind::Double)
+    }
+}
+
+/// 某条指令执行前的 locals / 操作数栈类型。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameState {
+    pub locals: Vec<ValueKind>,
+    pub stack: Vec<ValueKind>,
+}
+
+impl FrameState {
+    fn push(&mut self, kind: ValueKind) {
+        self.stack.push(kind);
+        if kind.is_wide() {
+            self.stack.push(ValueKind::Top);
+        }
+    }
+
+    fn pop(&mut self, slots: usize) -> Option<()> {
+        let len = self.stack.len().checked_sub(slots)?;
+        self.stack.truncate(len);
+        Some(())
+    }
+
+    fn store(&mut self, idx: usize, kind: ValueKind) -> Option<()> {
+        self.pop(if kind.is_wide() { 2 } else { 1 })?;
+        let width = if kind.is_wide() { 2 } else { 1 };
+        if idx + width > self.locals.len() {
+            return None;
+        }
+        // 覆盖了某个 long/double 的高位槽，它的低位槽也随之失效
+        if idx > 0 && self.locals[idx - 1].is_wide() {
+            self.locals[idx - 1] = ValueKind::Top;
+        }
+        self.locals[idx] = kind;
+        if kind.is_wide() {
+            self.locals[idx + 1] = ValueKind::Top;
+        }
+        Some(())
+    }
+
+    fn store_arg(&mut self, idx: usize, kind: ValueKind) -> Option<()> {
+        self.push(kind);
+        self.store(idx, kind)
+    }
+
+    /// 把 `other` 合并进来，返回状态是否发生变化。
+    fn merge(&mut self, other: &FrameState) -> Option<bool> {
+        if self.stack.len() != other.stack.len() {
+            return None;
+        }
+        let mut changed = false;
+        let slots = self
+            .locals
+            .iter_mut()
+            .zip(other.locals.iter())
+            .chain(self.stack.iter_mut().zip(other.stack.iter()));
+        for (mine, theirs) in slots {
+            if *mine != *theirs && *mine != ValueKind::Top {
+                *mine = ValueKind::Top;
+                changed = true;
+            }
+        }
+        Some(changed)
+    }
+}
+
+/// 计算方法中每个可达 bci 在指令执行前的帧状态。
+pub fn analyze(method: &Method) -> Option<HashMap<usize, FrameState>> {
+    let code = method.code.as_ref()?;
+    let mut params = Vec::new();
+    if !method.is_static() {
+        params.push(ValueKind::Ref);
+    }
+    for arg in method.signature.args.iter() {
+        params.push(ValueKind::from_signature(arg)?);
+    }
+    analyze_code(code, &method.class_file.cp, &params)
+}
+
+/// 同 `analyze`，参数（含 this）的类型由 `params` 给出。
+pub fn analyze_code(
+    code: &Code,
+    cp: &ConstantPool,
+    params: &[ValueKind],
+) -> Option<HashMap<usize, FrameState>> {
+    let bytecode = code.code.as_slice();
+
+    let mut entry = FrameState {
+        locals: vec![ValueKind::Top; code.max_locals as usize],
+        stack: Vec::new(),
+    };
+    let mut slot = 0;
+    for &kind in params {
+        entry.store_arg(slot, kind)?;
+        slot += slot_width(kind);
+    }
+
+    let mut states: HashMap<usize, FrameState> = HashMap::new();
+    states.insert(0, entry);
+    let mut worklist = vec![0usize];
+
+    while let Some(pc) = worklist.pop() {
+        let before = states[&pc].clone();
+
+        // 异常处理器的入口：locals 与受保护区间内任一指令前相同，栈上只有异常对象
+        for ex in code.exceptions.iter().filter(|ex| ex.contains(pc as u16)) {
+            let handler = FrameState {
+                locals: before.locals.clone(),
+                stack: vec![ValueKind::Ref],
+            };
+            propagate(&mut states, &mut worklist, ex.handler_pc as usize, &handler)?;
+        }
+
+        let mut after = before;
+        let flow = step(&mut after, bytecode, pc, cp)?;
+        for succ in flow {
+            propagate(&mut states, &mut worklist, succ, &after)?;
+        }
+    }
+
+    Some(states)
+}
+
+fn propagate(
+    states: &mut HashMap<usize, FrameState>,
+    worklist: &mut Vec<usize>,
+    pc: usize,
+    state: &FrameState,
+) -> Option<()> {
+    match states.get_mut(&pc) {
+        Some(existing) => {
+            if existing.merge(state)? {
+                worklist.push(pc);
+            }
+        }
+        None => {
+            states.insert(pc, state.clone());
+            worklist.push(pc);
+        }
+    }
+    Some(())
+}
+
+fn read_u16(code: &[U1], pc: usize) -> Option<usize> {
+    Some(u16::from_be_bytes([*code.get(pc)?, *code.get(pc + 1)?]) as usize)
+}
+
+fn read_i16(code: &[U1], pc: usize) -> Option<i32> {
+    Some(i16::from_be_bytes([*code.get(pc)?, *code.get(pc + 1)?]) as i32)
+}
+
+fn read_i32(code: &[U1], pc: usize) -> Option<i32> {
+    Some(i32::from_be_bytes([
+        *code.get(pc)?,
+        *code.get(pc + 1)?,
+        *code.get(pc + 2)?,
+        *code.get(pc + 3)?,
+    ]))
+}
+
+fn offset(pc: usize, delta: i32) -> Option<usize> {
+    usize::try_from(pc as i64 + delta as i64).ok()
+}
+
+fn ldc_kind(cp: &ConstantPool, idx: usize) -> Option<ValueKind> {
+    match cp.get(idx)? {
+        CpType::Integer { .. } => Some(ValueKind::Int),
+        CpType::Float { .. } => Some(ValueKind::Float),
+        CpType::Long { .. } => Some(ValueKind::Long),
+        CpType::Double { .. } => Some(ValueKind::Double),
+        _ => Some(ValueKind::Ref),
+    }
+}
+
+/// 常量池中字段引用 `idx` 的值类型。
+pub fn field_kind(cp: &ConstantPool, idx: usize) -> Option<ValueKind> {
+    let (_, name_and_type_index) = constant_pool::get_field_ref(cp, idx);
+    let (_, desc) = constant_pool::get_name_and_type(cp, name_and_type_index as usize);
+    ValueKind::from_descriptor(desc.as_slice())
+}
+
+fn slot_width(kind: ValueKind) -> usize {
+    if kind.is_wide() {
+        2
+    } else {
+        1
+    }
+}
+
+/// 按指令的栈效果变换 `state`，返回后继 bci 列表。
+fn step(state: &mut FrameState, code: &[U1], pc: usize, cp: &ConstantPool) -> Option<Vec<usize>> {
+    use ValueKind::*;
+
+    let op = OpCode::from(*code.get(pc)?);
+    let mut next = pc + 1;
+    let mut targets = Vec::new();
+    let mut falls_through = true;
+
+    match op {
+        OpCode::nop => {}
+        OpCode::aconst_null => state.push(Ref),
+        OpCode::iconst_m1
+        | OpCode::iconst_0
+        | OpCode::iconst_1
+        | OpCode::iconst_2
+        | OpCode::iconst_3
+        | OpCode::iconst_4
+        | OpCode::iconst_5 => state.push(Int),
+        OpCode::lconst_0 | OpCode::lconst_1 => state.push(Long),
+        OpCode::fconst_0 | OpCode::fconst_1 | OpCode::fconst_2 => state.push(Float),
+        OpCode::dconst_0 | OpCode::dconst_1 => state.push(Double),
+        OpCode::bipush => {
+            state.push(Int);
+            next = pc + 2;
+        }
+        OpCode::sipush => {
+            state.push(Int);
+            next = pc + 3;
+        }
+        OpCode::ldc => {
+            state.push(ldc_kind(cp, *code.get(pc + 1)? as usize)?);
+            next = pc + 2;
+        }
+        OpCode::ldc_w | OpCode::ldc2_w => {
+            state.push(ldc_kind(cp, read_u16(code, pc + 1)?)?);
+            next = pc + 3;
+        }
+
+        OpCode::iload | OpCode::lload | OpCode::fload | OpCode::dload | OpCode::aload => {
+            let kind = load_store_kind(op);
+            state.push(kind);
+            next = pc + 2;
+        }
+        OpCode::iload_0 | OpCode::iload_1 | OpCode::iload_2 | OpCode::iload_3 => state.push(Int),
+        OpCode::lload_0 | OpCode::lload_1 | OpCode::lload_2 | OpCode::lload_3 => state.push(Long),
+        OpCode::fload_0 | OpCode::fload_1 | OpCode::fload_2 | OpCode::fload_3 => state.push(Float),
+        OpCode::dload_0 | OpCode::dload_1 | OpCode::dload_2 | OpCode::dload_3 => state.push(Double),
+        OpCode::aload_0 | OpCode::aload_1 | OpCode::aload_2 | OpCode::aload_3 => state.push(Ref),
+
+        OpCode::iaload | OpCode::baload | OpCode::caload | OpCode::saload => {
+            state.pop(2)?;
+            state.push(Int);
+        }
+        OpCode::laload => {
+            state.pop(2)?;
+            state.push(Long);
+        }
+        OpCode::faload => {
+            state.pop(2)?;
+            state.push(Float);
+        }
+        OpCode::daload => {
+            state.pop(2)?;
+            state.push(Double);
+        }
+        OpCode::aaload => {
+            state.pop(2)?;
+            state.push(Ref);
+        }
+
+        OpCode::istore | OpCode::lstore | OpCode::fstore | OpCode::dstore | OpCode::astore => {
+            let kind = load_store_kind(op);
+            state.store(*code.get(pc + 1)? as usize, kind)?;
+            next = pc + 2;
+        }
+        OpCode::istore_0 | OpCode::istore_1 | OpCode::istore_2 | OpCode::istore_3 => {
+            state.store((op as u8 - OpCode::istore_0 as u8) as usize, Int)?
+        }
+        OpCode::lstore_0 | OpCode::lstore_1 | OpCode::lstore_2 | OpCode::lstore_3 => {
+            state.store((op as u8 - OpCode::lstore_0 as u8) as usize, Long)?
+        }
+        OpCode::fstore_0 | OpCode::fstore_1 | OpCode::fstore_2 | OpCode::fstore_3 => {
+            state.store((op as u8 - OpCode::fstore_0 as u8) as usize, Float)?
+        }
+        OpCode::dstore_0 | OpCode::dstore_1 | OpCode::dstore_2 | OpCode::dstore_3 => {
+            state.store((op as u8 - OpCode::dstore_0 as u8) as usize, Double)?
+        }
+        OpCode::astore_0 | OpCode::astore_1 | OpCode::astore_2 | OpCode::astore_3 => {
+            state.store((op as u8 - OpCode::astore_0 as u8) as usize, Ref)?
+        }
+
+        OpCode::iastore
+        | OpCode::fastore
+        | OpCode::aastore
+        | OpCode::bastore
+        | OpCode::castore
+        | OpCode::sastore => state.pop(3)?,
+        OpCode::lastore | OpCode::dastore => state.pop(4)?,
+
+        OpCode::pop => state.pop(1)?,
+        OpCode::pop2 => state.pop(2)?,
+        OpCode::dup => {
+            let v1 = *state.stack.last()?;
+            state.stack.push(v1);
+        }
+        OpCode::dup_x1 => dup_insert(state, 1, 1)?,
+        OpCode::dup_x2 => dup_insert(state, 1, 2)?,
+        OpCode::dup2 => dup_insert(state, 2, 0)?,
+        OpCode::dup2_x1 => dup_insert(state, 2, 1)?,
+        OpCode::dup2_x2 => dup_insert(state, 2, 2)?,
+        OpCode::swap => {
+            let len = state.stack.len();
+            if len < 2 {
+                return None;
+            }
+            state.stack.swap(len - 1, len - 2);
+        }
+
+        OpCode::iadd
+        | OpCode::isub
+        | OpCode::imul
+        | OpCode::idiv
+        | OpCode::irem
+        | OpCode::ishl
+        | OpCode::ishr
+        | OpCode::iushr
+        | OpCode::iand
+        | OpCode::ior
+        | OpCode::ixor => {
+            state.pop(2)?;
+            state.push(Int);
+        }
+        OpCode::ladd
+        | OpCode::lsub
+        | OpCode::lmul
+        | OpCode::ldiv
+        | OpCode::lrem
+        | OpCode::land
+        | OpCode::lor
+        | OpCode::lxor => {
+            state.pop(4)?;
+            state.push(Long);
+        }
+        OpCode::lshl | OpCode::lshr | OpCode::lushr => {
+            state.pop(3)?;
+            state.push(Long);
+        }
+        OpCode::fadd | OpCode::fsub | OpCode::fmul | OpCode::fdiv | OpCode::frem => {
+            state.pop(2)?;
+            state.push(Float);
+        }
+        OpCode::dadd | OpCode::dsub | OpCode::dmul | OpCode::ddiv | OpCode::drem => {
+            state.pop(4)?;
+            state.push(Double);
+        }
+        OpCode::ineg | OpCode::lneg | OpCode::fneg | OpCode::dneg => {}
+        OpCode::iinc => next = pc + 3,
+
+        OpCode::i2l => convert(state, Int, Long)?,
+        OpCode::i2f => convert(state, Int, Float)?,
+        OpCode::i2d => convert(state, Int, Double)?,
+        OpCode::l2i => convert(state, Long, Int)?,
+        OpCode::l2f => convert(state, Long, Float)?,
+        OpCode::l2d => convert(state, Long, Double)?,
+        OpCode::f2i => convert(state, Float, Int)?,
+        OpCode::f2l => convert(state, Float, Long)?,
+        OpCode::f2d => convert(state, Float, Double)?,
+        OpCode::d2i => convert(state, Double, Int)?,
+        OpCode::d2l => convert(state, Double, Long)?,
+        OpCode::d2f => convert(state, Double, Float)?,
+        OpCode::i2b | OpCode::i2c | OpCode::i2s => {}
+
+        OpCode::lcmp | OpCode::dcmpl | OpCode::dcmpg => {
+            state.pop(4)?;
+            state.push(Int);
+        }
+        OpCode::fcmpl | OpCode::fcmpg => {
+            state.pop(2)?;
+            state.push(Int);
+        }
+
+        OpCode::ifeq
+        | OpCode::ifne
+        | OpCode::iflt
+        | OpCode::ifge
+        | OpCode::ifgt
+        | OpCode::ifle
+        | OpCode::ifnull
+        | OpCode::ifnonnull => {
+            state.pop(1)?;
+            targets.push(offset(pc, read_i16(code, pc + 1)?)?);
+            next = pc + 3;
+        }
+        OpCode::if_icmpeq
+        | OpCode::if_icmpne
+        | OpCode::if_icmplt
+        | OpCode::if_icmpge
+        | OpCode::if_icmpgt
+        | OpCode::if_icmple
+        | OpCode::if_acmpeq
+        | OpCode::if_acmpne => {
+            state.pop(2)?;
+            targets.push(offset(pc, read_i16(code, pc + 1)?)?);
+            next = pc + 3;
+        }
+        OpCode::goto => {
+            targets.push(offset(pc, read_i16(code, pc + 1)?)?);
+            falls_through = false;
+        }
+        OpCode::goto_w => {
+            targets.push(offset(pc, read_i32(code, pc + 1)?)?);
+            falls_through = false;
+        }
+        OpCode::tableswitch => {
+            state.pop(1)?;
+            let base = (pc + 4) & !3;
+            targets.push(offset(pc, read_i32(code, base)?)?);
+            let low = read_i32(code, base + 4)?;
+            let high = read_i32(code, base + 8)?;
+            for i in 0..(high as i64 - low as i64 + 1).max(0) as usize {
+                targets.push(offset(pc, read_i32(code, base + 12 + i * 4)?)?);
+            }
+            falls_through = false;
+        }
+        OpCode::lookupswitch => {
+            state.pop(1)?;
+            let base = (pc + 4) & !3;
+            targets.push(offset(pc, read_i32(code, base)?)?);
+            let npairs = read_i32(code, base + 4)?.max(0) as usize;
+            for i in 0..npairs {
+                targets.push(offset(pc, read_i32(code, base + 12 + i * 8)?)?);
+            }
+            falls_through = false;
+        }
+        OpCode::ireturn
+        | OpCode::lreturn
+        | OpCode::freturn
+        | OpCode::dreturn
+        | OpCode::areturn
+        | OpCode::return_void
+        | OpCode::athrow => falls_through = false,
+
+        OpCode::getstatic => {
+            state.push(field_kind(cp, read_u16(code, pc + 1)?)?);
+            next = pc + 3;
+        }
+        OpCode::putstatic => {
+            state.pop(slot_width(field_kind(cp, read_u16(code, pc + 1)?)?))?;
+            next = pc + 3;
+        }
+        OpCode::getfield => {
+            state.pop(1)?;
+            state.push(field_kind(cp, read_u16(code, pc + 1)?)?);
+            next = pc + 3;
+        }
+        OpCode::putfield => {
+            state.pop(slot_width(field_kind(cp, read_u16(code, pc + 1)?)?) + 1)?;
+            next = pc + 3;
+        }
+        OpCode::invokevirtual
+        | OpCode::invokespecial
+        | OpCode::invokestatic
+        | OpCode::invokeinterface => {
+            let idx = read_u16(code, pc + 1)?;
+            let (_, _, name_and_type_index) = constant_pool::get_method_ref(cp, idx);
+            invoke(
+                state,
+                cp,
+                name_and_type_index as usize,
+                op != OpCode::invokestatic,
+            )?;
+            next = if op == OpCode::invokeinterface {
+                pc + 5
+            } else {
+                pc + 3
+            };
+        }
+        OpCode::new => {
+            state.push(Ref);
+            next = pc + 3;
+        }
+        OpCode::newarray => {
+            state.pop(1)?;
+            state.push(Ref);
+            next = pc + 2;
+        }
+        OpCode::anewarray | OpCode::checkcast | OpCode::instanceof => {
+            state.pop(1)?;
+            state.push(if op == OpCode::instanceof { Int } else { Ref });
+            next = pc + 3;
+        }
+        OpCode::arraylength => {
+            state.pop(1)?;
+            state.push(Int);
+        }
+        OpCode::monitorenter | OpCode::monitorexit => state.pop(1)?,
+        OpCode::multianewarray => {
+            state.pop(*code.get(pc + 3)? as usize)?;
+            state.push(Ref);
+            next = pc + 4;
+        }
+        OpCode::wide => {
+            let inner = OpCode::from(*code.get(pc + 1)?);
+            let idx = read_u16(code, pc + 2)?;
+            match inner {
+                OpCode::iinc => next = pc + 6,
+                OpCode::iload | OpCode::lload | OpCode::fload | OpCode::dload | OpCode::aload => {
+                    state.push(load_store_kind(inner));
+                    next = pc + 4;
+                }
+                OpCode::istore
+                | OpCode::lstore
+                | OpCode::fstore
+                | OpCode::dstore
+                | OpCode::astore => {
+                    state.store(idx, load_store_kind(inner))?;
+                    next = pc + 4;
+                }
+                _ => return None,
+            }
+        }
+
+        // invokedynamic / jsr / ret 等：不分析
+        _ => return None,
+    }
+
+    if falls_through {
+        targets.push(next);
+    }
+    Some(targets)
+}
+
+fn load_store_kind(op: OpCode) -> ValueKind {
+    match op {
+        OpCode::lload | OpCode::lstore => ValueKind::Long,
+        OpCode::fload | OpCode::fstore => ValueKind::Float,
+        OpCode::dload | OpCode::dstore => ValueKind::Double,
+        OpCode::aload | OpCode::astore => ValueKind::Ref,
+        _ => ValueKind::Int,
+    }
+}
+
+fn convert(state: &mut FrameState, from: ValueKind, to: ValueKind) -> Option<()> {
+    state.pop(slot_width(from))?;
+    state.push(to);
+    Some(())
+}
+
+/// dup 系列指令：复制栈顶 `n` 个槽，插到再往下 `depth` 个槽之下。
+fn dup_insert(state: &mut FrameState, n: usize, depth: usize) -> Option<()> {
+    let len = state.stack.len();
+    if len < n + depth {
+        return None;
+    }
+    let top: Vec<ValueKind> = state.stack[len - n..].to_vec();
+    let at = len - n - depth;
+    for (i, kind) in top.into_iter().enumerate() {
+        state.stack.insert(at + i, kind);
+    }
+    Some(())
+}
+
+fn invoke(
+    state: &mut FrameState,
+    cp: &ConstantPool,
+    name_and_type_index: usize,
+    has_this: bool,
+) -> Option<()> {
+    let (_, desc) = constant_pool::get_name_and_type(cp, name_and_type_index);
+    let sig = MethodSignature::new(desc.as_slice());
+    let mut slots = if has_this { 1 } else { 0 };
+    for arg in sig.args.iter() {
+        slots += slot_width(ValueKind::from_signature(arg)?);
+    }
+    state.pop(slots)?;
+    if let Some(kind) = ValueKind::from_signature(&sig.retype) {
+        state.push(kind);
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn run(code: &[U1], mut state: FrameState) -> FrameState {
+        let cp: ConstantPool = Arc::new(Vec::new());
+        let mut pc = 0;
+        while pc < code.len() {
+            let succ = step(&mut state, code, pc, &cp).unwrap();
+            pc = succ[0];
+        }
+        state
+    }
+
+    #[test]
+    fn t_step_long_locals() {
+        let state = FrameState {
+            locals: vec![ValueKind::Top; 3],
+            stack: Vec::new(),
+        };
+        // lconst_1; lstore_0; iconst_2; istore_1; lload_0
+        let state = run(&[0x0A, 0x3F, 0x05, 0x3C, 0x1E], state);
+        // istore_1 覆盖了 long 的高位槽，local 0 随之失效
+        assert_eq!(
+            state.locals,
+            vec![ValueKind::Top, ValueKind::Int, ValueKind::Top]
+        );
+        assert_eq!(state.stack, vec![ValueKind::Long, ValueKind::Top]);
+    }
+
+    #[test]
+    fn t_dup_x1() {
+        let mut state = FrameState {
+            locals: Vec::new(),
+            stack: vec![ValueKind::Ref, ValueKind::Int],
+        };
+        dup_insert(&mut state, 1, 1).unwrap();
+        assert_eq!(
+            state.stack,
+            vec![ValueKind::Int, ValueKind::Ref, ValueKind::Int]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+// ============================================================
+// 调用点内联缓存（Inline Cache）
+//
+// JIT 代码中的每个 invokevirtual / invokeinterface 调用点拥有一个
+// `InlineCache`，缓存"接收者类 → 目标方法"的映射，避免每次调用都
+// 沿着继承链查 v-table。
+//
+// ## 状态机
+//
+// Empty → Monomorphic → Polymorphic（最多 IC_POLY_LIMIT 项）→ Megamorphic
+//
+// - Monomorphic: 只见过一种接收者类，绝大多数调用点停留在这里
+// - Polymorphic: 见过少量几种接收者类，线性比较即可
+// - Megamorphic: 接收者类太多，缓存已无意义，直接走 v-table 解析
+//
+// 缓存以接收者的**精确类**为 key，而一个已加载类的 v-table 不会再变化，
+// 所以新类加载不会使已有缓存项失效，不需要参与去优化。
+//
+// ## 生命周期
+//
+// 编译好的机器码通过裸指针引用 `InlineCache`，而机器码本身不会从
+// ExecutionEngine 中释放。所以 `InlineCache` 用 `Box::leak` 分配，
+// 泄漏量 = O(JIT 调用点数量)。
+// ============================================================
+
+use crate::types::{ClassRef, MethodIdRef};
+use std::sync::{Arc, Mutex};
+
+/// 多态缓存的最大项数，超过后退化为 megamorphic。
+pub const IC_POLY_LIMIT: usize = 4;
+
+struct IcEntry {
+    receiver: ClassRef,
+    target: MethodIdRef,
+}
+
+enum IcState {
+    Empty,
+    Monomorphic(IcEntry),
+    Polymorphic(Vec<IcEntry>),
+    Megamorphic,
+}
+
+/// 单个调用点的内联缓存。
+pub struct InlineCache {
+    state: Mutex<IcState>,
+}
+
+impl InlineCache {
+    /// 为一个新的调用点分配缓存。返回 `'static` 引用，地址可直接嵌入 IR。
+    pub fn new_leaked() -> &'static InlineCache {
+        Box::leak(Box::new(InlineCache {
+            state: Mutex::new(IcState::Empty),
+        }))
+    }
+
+    /// 按接收者类查找缓存的目标方法。
+    pub fn lookup(&self, receiver: &ClassRef) -> Option<MethodIdRef> {
+        let state = self.state.lock().unwrap();
+        match &*state {
+            IcState::Monomorphic(e) if Arc::ptr_eq(&e.receiver, receiver) => Some(e.target.clone()),
+            IcState::Polymorphic(entries) => entries
+                .iter()
+                .find(|e| Arc::ptr_eq(&e.receiver, receiver))
+                .map(|e| e.target.clone()),
+            _ => None,
+        }
+    }
+
+    /// 记录一次 miss 后解析出的目标方法，推进状态机。
+    pub fn record(&self, receiver: ClassRef, target: MethodIdRef) {
+        let mut state = self.state.lock().unwrap();
+        let entry = IcEntry { receiver, target };
+        *state = match std::mem::replace(&mut *state, IcState::Empty) {
+            IcState::Empty => IcState::Monomorphic(entry),
+            IcState::Monomorphic(first) => IcState::Polymorphic(vec![first, entry]),
+            IcState::Polymorphic(mut entries) => {
+                if entries.len() < IC_POLY_LIMIT {
+                    entries.push(entry);
+                    IcState::Polymorphic(entries)
+                } else {
+                    IcState::Megamorphic
+                }
+            }
+            IcState::Megamorphic => IcState::Megamorphic,
+        };
+    }
+
+    pub fn is_megamorphic(&self) -> bool {
+        matches!(*self.state.lock().unwrap(), IcState::Megamorphic)
+    }
+}
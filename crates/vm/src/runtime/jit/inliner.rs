@@ -0,0 +1,168 @@
+// ============================================================
+// 小方法内联的筛选规则
+//
+// builder 在遇到静态绑定（invokestatic / invokespecial）或经 CHA
+// 去虚化的调用点时，会询问本模块被调方法是否适合内联。
+//
+// 内联直接在调用方的 LLVM 函数中翻译被调方法的 bytecode：
+// - 被调方法的 locals 是调用方 entry block 中新分配的 alloca
+// - 被调方法的操作数栈紧接在调用方当前栈顶之上
+// - 被调方法的 *return 写入自己栈的第 0 槽，也就是调用方原先放第一个
+//   参数的位置，恰好是调用返回后结果应在的位置
+//
+// 限制条件：
+// - bytecode 不超过 MAX_INLINE_SIZE 字节（同 HotSpot 的 MaxInlineSize）
+// - 没有异常表、不是 synchronized / native
+// - 不含任何调用、athrow、monitor、jsr/ret、switch、wide
+//   （所以内联深度天然为 1，不会递归展开）
+// - 访问常量池的指令只允许出现在与调用方同类的方法里：运行时回调通过
+//   `JitInvokeCtx` 拿到的是调用方的类，常量池索引必须对得上
+// ============================================================
+
+use crate::runtime::method::Method;
+use crate::types::MethodIdRef;
+use classfile::{OpCode, U1};
+use std::sync::Arc;
+
+/// 可内联方法体的最大字节数。
+pub const MAX_INLINE_SIZE: usize = 35;
+
+/// 判断 `callee` 是否可以内联进 `caller`。
+pub fn can_inline(caller: &Method, callee: &MethodIdRef) -> bool {
+    let method = &callee.method;
+    if method.is_native() || method.is_abstract() || method.is_synchronized() {
+        return false;
+    }
+
+    let code = match &method.code {
+        Some(code) => code,
+        None => return false,
+    };
+    if code.code.len() > MAX_INLINE_SIZE || !code.exceptions.is_empty() {
+        return false;
+    }
+
+    let same_class = Arc::ptr_eq(&caller.class, &method.class);
+    scan(code.code.as_slice(), same_class)
+}
+
+fn scan(code: &[U1], same_class: bool) -> bool {
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = OpCode::from(code[pc]);
+        let len = match inst_len(op) {
+            Some(len) => len,
+            None => return false,
+        };
+        if uses_cp(op) && !same_class {
+            return false;
+        }
+        pc += len;
+    }
+    true
+}
+
+fn uses_cp(op: OpCode) -> bool {
+    matches!(
+        op,
+        OpCode::ldc
+            | OpCode::ldc_w
+            | OpCode::ldc2_w
+            | OpCode::getstatic
+            | OpCode::putstatic
+            | OpCode::getfield
+            | OpCode::putfield
+            | OpCode::new
+            | OpCode::anewarray
+            | OpCode::checkcast
+            | OpCode::instanceof
+    )
+}
+
+/// 可内联指令的长度；不允许出现在内联方法体中的指令返回 None。
+fn inst_len(op: OpCode) -> Option<usize> {
+    match op {
+        OpCode::invokevirtual
+        | OpCode::invokespecial
+        | OpCode::invokestatic
+        | OpCode::invokeinterface
+        | OpCode::invokedynamic
+        | OpCode::athrow
+        | OpCode::monitorenter
+        | OpCode::monitorexit
+        | OpCode::jsr
+        | OpCode::jsr_w
+        | OpCode::ret
+        | OpCode::tableswitch
+        | OpCode::lookupswitch
+        | OpCode::wide
+        | OpCode::goto_w
+        | OpCode::multianewarray => None,
+
+        OpCode::bipush
+        | OpCode::ldc
+        | OpCode::iload
+        | OpCode::lload
+        | OpCode::fload
+        | OpCode::dload
+        | OpCode::aload
+        | OpCode::istore
+        | OpCode::lstore
+        | OpCode::fstore
+        | OpCode::dstore
+        | OpCode::astore
+        | OpCode::newarray => Some(2),
+
+        OpCode::sipush
+        | OpCode::ldc_w
+        | OpCode::ldc2_w
+        | OpCode::iinc
+        | OpCode::getstatic
+        | OpCode::putstatic
+        | OpCode::getfield
+        | OpCode::putfield
+        | OpCode::new
+        | OpCode::anewarray
+        | OpCode::checkcast
+        | OpCode::instanceof
+        | OpCode::ifeq
+        | OpCode::ifne
+        | OpCode::iflt
+        | OpCode::ifge
+        | OpCode::ifgt
+        | OpCode::ifle
+        | OpCode::if_icmpeq
+        | OpCode::if_icmpne
+        | OpCode::if_icmplt
+        | OpCode::if_icmpge
+        | OpCode::if_icmpgt
+        | OpCode::if_icmple
+        | OpCode::if_acmpeq
+        | OpCode::if_acmpne
+        | OpCode::goto
+        | OpCode::ifnull
+        | OpCode::ifnonnull => Some(3),
+
+        _ => Some(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_scan_getter_like() {
+        // iload_0; iconst_1; iadd; ireturn
+        assert!(scan(&[0x1A, 0x04, 0x60, 0xAC], false));
+        // aload_0; getfield #2; ireturn —— 跨类时不可内联
+        assert!(!scan(&[0x2A, 0xB4, 0x00, 0x02, 0xAC], false));
+        assert!(scan(&[0x2A, 0xB4, 0x00, 0x02, 0xAC], true));
+    }
+
+    #[test]
+    fn t_scan_rejects_calls() {
+        // aload_0; invokevirtual #3; ireturn
+        assert!(!scan(&[0x2A, 0xB6, 0x00, 0x03, 0xAC], true));
+    }
+}
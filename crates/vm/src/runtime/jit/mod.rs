@@ -33,6 +33,7 @@
 // ## 编译流程
 //
 // bytecode → LLVM IR (builder.rs + ops.rs)
+//         → pass pipeline 优化（opt.rs，按方法的 JIT 级别）
 //         → ExecutionEngine 编译为机器码
 //         → 返回函数指针 → 缓存到 MethodId.jit_impl
 // ============================================================
@@ -43,8 +44,16 @@ use std::cell::RefCell;
 use std::sync::Arc;
 use tracing::{debug, error, info, trace, warn};
 
+pub mod aot;
 mod builder;
+pub mod cha;
+pub mod deopt;
+pub mod dump;
+pub mod frame_state;
+pub mod inline_cache;
+mod inliner;
 mod ops;
+pub mod opt;
 pub mod runtime;
 
 // inkwell 的 Context 是 LLVM 的上下文对象，持有所有 LLVM 类型、值、模块的
@@ -66,10 +75,12 @@ use inkwell::builder::Builder;
 // 一个 Module 可以包含多个函数，被 ExecutionEngine 编译。
 use inkwell::module::Module;
 
-// OptimizationLevel 用于控制 JIT 编译器的优化级别。
-// None = 无优化（编译最快），Aggressive = 最大优化（执行最快）。
-// MVP 使用 None，后续可以改为 Aggressive。
-use inkwell::OptimizationLevel;
+// TargetMachine 描述本机的目标平台，new pass manager 运行 pass 时需要它
+// 提供目标相关的信息（数据布局、向量宽度等）。
+use inkwell::passes::PassBuilderOptions;
+use inkwell::targets::{CodeModel, InitializationConfig, RelocMode, Target, TargetMachine};
+
+use opt::JitLevel;
 
 /// JIT 编译器主结构体。
 ///
@@ -85,12 +96,17 @@ pub struct JitCompiler {
     /// LLVM 上下文。使用 `'static` 引用，通过 `Box::leak` 确保
     /// 上下文在整个程序生命周期内有效。
     _context: &'static Context,
-    /// LLVM IR 模块。所有编译的方法函数都在这里定义。
-    module: Module<'static>,
+    /// ExecutionEngine 的初始 LLVM IR 模块。每个方法编译到自己的模块里，
+    /// 这个模块只用于创建 ExecutionEngine。
+    _module: Module<'static>,
+    /// 已交给 ExecutionEngine 的方法模块，必须与它同生命周期。
+    modules: Vec<Module<'static>>,
     /// IR 指令构建器。每次编译一个方法时，用它发射 IR 指令。
     builder: Builder<'static>,
     /// 运行时执行引擎。负责将 LLVM Module 编译为本地机器码。
     execution_engine: ExecutionEngine<'static>,
+    /// 本机目标，运行 pass pipeline 时使用。
+    target_machine: TargetMachine,
 }
 
 impl JitCompiler {
@@ -100,7 +116,8 @@ impl JitCompiler {
     /// 1. 创建 `Context` —— LLVM 的根对象
     /// 2. 创建 `Module` —— 命名为 "jvm_jit_module"
     /// 3. 创建 `Builder` —— 用于发射 IR 指令
-    /// 4. 创建 `ExecutionEngine` —— 启用优化
+    /// 4. 创建 `ExecutionEngine` —— 代码生成级别取全局默认 JIT 级别
+    /// 5. 创建本机 `TargetMachine` —— 供 pass pipeline 使用
     ///
     /// 如果 LLVM 初始化失败（如版本不匹配），返回 `Err`。
     pub fn new() -> Result<Self, String> {
@@ -123,15 +140,36 @@ impl JitCompiler {
         // create_jit_execution_engine 会设置 jit_mode = true，
         // 使得 get_function 可以获取编译后的函数指针。
         // create_execution_engine 默认 jit_mode = false，不能用 get_function。
+        //
+        // 代码生成级别对整个 ExecutionEngine 生效，无法按方法区分，
+        // 所以取全局默认级别；按方法的差异体现在 IR 层的 pass pipeline 上。
         let execution_engine = module
-            .create_jit_execution_engine(OptimizationLevel::None)
+            .create_jit_execution_engine(opt::default_level().codegen_level())
             .map_err(|e| format!("Failed to create JIT execution engine: {}", e))?;
 
+        Target::initialize_native(&InitializationConfig::default())
+            .map_err(|e| format!("Failed to initialize native target: {}", e))?;
+        let triple = TargetMachine::get_default_triple();
+        let target = Target::from_triple(&triple)
+            .map_err(|e| format!("Failed to look up native target: {}", e))?;
+        let target_machine = target
+            .create_target_machine(
+                &triple,
+                &TargetMachine::get_host_cpu_name().to_string(),
+                &TargetMachine::get_host_cpu_features().to_string(),
+                opt::default_level().codegen_level(),
+                RelocMode::Default,
+                CodeModel::JITDefault,
+            )
+            .ok_or_else(|| "Failed to create target machine".to_string())?;
+
         Ok(Self {
             _context: leaked,
-            module,
+            _module: module,
+            modules: Vec::new(),
             builder,
             execution_engine,
+            target_machine,
         })
     }
 
@@ -140,76 +178,124 @@ impl JitCompiler {
     /// ## 编译步骤
     ///
     /// 1. 检查方法是否有 bytecode（native/abstract 方法没有）
-    /// 2. 在 LLVM Module 中创建一个新函数
+    /// 2. 为该方法创建独立的 LLVM Module，并在其中创建一个新函数
     /// 3. 为每个字节码位置创建 BasicBlock
     /// 4. 从入口 BasicBlock 开始，逐条翻译 bytecode → LLVM IR
-    /// 5. ExecutionEngine 将函数编译为机器码
-    /// 6. 返回函数指针，缓存到 MethodId.jit_impl
+    /// 5. 按方法的 JIT 级别运行 pass pipeline（见 `opt`）
+    /// 6. 按需转储 IR 与汇编、输出编译日志（见 `dump`）
+    /// 7. Module 交给 ExecutionEngine，编译为机器码
+    /// 8. 返回函数指针，缓存到 MethodId.jit_impl
     ///
     /// ## 函数签名
     ///
-    /// 生成的 LLVM 函数签名是：`void fn(i8* locals, i8* stack)`
-    /// - `locals`: 本地变量数组的起始指针
-    /// - `stack`: 操作数栈的起始指针
-    pub fn compile_method(&mut self, method_id: &MethodIdRef) -> Option<Arc<JITCompiledMethod>> {
-        // 获取方法的 bytecode。native 方法和 abstract 方法没有 bytecode，
-        // 不需要（也不能）编译。
-        let code = method_id.method.code.as_ref()?;
-        let bytecode = code.code.as_slice();
-        let max_locals = code.max_locals as usize;
-        let max_stack = code.max_stack as usize;
+    /// 入口函数签名是 `i64 fn(i64* args)`（即 `JitFn`），它调用按 Java 签名
+    /// 生成的类型化方法体，参数与返回值都在寄存器中传递，见 `builder::compile_method`。
+    pub fn compile_method(
+        &mut self,
+        method_id: &MethodIdRef,
+        level: JitLevel,
+    ) -> Option<Arc<JITCompiledMethod>> {
+        // native 方法和 abstract 方法没有 bytecode，不需要（也不能）编译。
+        method_id.method.code.as_ref()?;
+        let compile_id = dump::next_compile_id();
+        let method = &method_id.method;
+
+        // 每个方法一个 Module：pass pipeline 以 Module 为单位运行，
+        // 这样不同方法可以使用不同的优化级别，也不会重复优化已编译的函数。
+        let module = self
+            ._context
+            .create_module(&format!("jvm_jit_{}", self.modules.len()));
 
         // 调用 builder 模块进行实际的 IR 生成。
         // 注意：我们将 context 的引用传递给 builder，因为 LLVM IR 的
         // 创建（如 BasicBlock、常量等）需要 context。
-        let function = builder::compile_method(
+        let compiled = builder::compile_method(
             self._context,
-            &self.module,
+            &module,
             &self.builder,
-            &method_id.method,
-            bytecode,
-            max_locals,
-            max_stack,
-        )?;
+            method,
+            // 同一方法可能因失效而重新编译，加上编译编号保证函数名唯一
+            &format!("{}__{}", builder::function_name(method), compile_id),
+            false,
+        );
+        let compiled = match compiled {
+            Some(compiled) => compiled,
+            None => {
+                dump::log_skipped(compile_id, level, method, "unsupported bytecode");
+                return None;
+            }
+        };
+        let function = compiled.function;
 
         // 验证生成的 IR 是否正确。
         // LLVM 的 verify_function 检查 IR 的合法性：
         // - 每个 BasicBlock 都以终止符结尾
         // - 类型匹配
         // - PHI 节点位置正确
-        if !function.verify(true) {
-            warn!(
-                "JIT: LLVM IR verification failed for method {:?}",
-                method_id.method
-            );
+        if module.verify().is_err() {
+            warn!("JIT: LLVM IR verification failed for method {:?}", method);
+            dump::dump_module(compile_id, method, &module, &self.target_machine, false);
+            dump::log_skipped(compile_id, level, method, "IR verification failed");
+            return None;
+        }
+
+        if let Some(passes) = level.passes() {
+            let options = PassBuilderOptions::create();
+            options.set_loop_vectorization(level >= JitLevel::O3);
+            options.set_loop_slp_vectorization(level >= JitLevel::O3);
+            if let Err(e) = module.run_passes(passes, &self.target_machine, options) {
+                warn!(
+                    "JIT: running passes '{}' failed for method {:?}: {}",
+                    passes, method, e
+                );
+                dump::log_skipped(compile_id, level, method, "pass pipeline failed");
+                return None;
+            }
+        }
+
+        dump::dump_module(compile_id, method, &module, &self.target_machine, true);
+
+        let fn_name = function.get_name().to_str().unwrap().to_string();
+        if self.execution_engine.add_module(&module).is_err() {
+            warn!("JIT: module for {:?} already added", method);
             return None;
         }
+        self.modules.push(module);
 
         // 通过 ExecutionEngine 查找编译后的函数指针。
         // get_function 会自动触发 JIT 编译（如果尚未编译）。
-        let fn_name = function.get_name().to_str().unwrap().to_string();
         let fn_ptr: JitFn = unsafe {
             self.execution_engine
                 .get_function(&fn_name)
                 .map(
                     |f: inkwell::execution_engine::JitFunction<
-                        unsafe extern "C" fn(*mut (), *mut ()),
+                        unsafe extern "C" fn(*const i64) -> i64,
                     >| {
                         // as_raw() 返回底层 unsafe 函数指针。
-                        // 我们需要将其转换为安全的 JitFn 类型（extern "C" fn(*mut i32, *mut i32)）。
+                        // 我们需要将其转换为安全的 JitFn 类型（extern "C" fn(*const i64) -> i64）。
                         // 这是安全的，因为 JIT 编译的代码确实是一个有效的 C ABI 函数。
-                        std::mem::transmute::<unsafe extern "C" fn(*mut (), *mut ()), JitFn>(
+                        std::mem::transmute::<unsafe extern "C" fn(*const i64) -> i64, JitFn>(
                             f.as_raw(),
                         )
                     },
                 )
                 .map_err(|e| {
                     warn!("JIT: Failed to get compiled function pointer: {:?}", e);
+                    dump::log_skipped(compile_id, level, method, "code generation failed");
                 })
                 .ok()?
         };
 
-        Some(Arc::new(JITCompiledMethod { fn_ptr }))
+        // 编译成功后才登记 CHA 依赖，失败的编译不会留下悬空的假设
+        cha::register(method_id, compiled.dependencies);
+        dump::log_compiled(compile_id, level, method);
+
+        Some(Arc::new(JITCompiledMethod {
+            fn_ptr,
+            deopt: compiled.deopt,
+            compile_id,
+            level,
+        }))
     }
 }
 
@@ -222,13 +308,26 @@ thread_local! {
 }
 
 /// 尝试编译一个方法。
-/// 如果 JIT 编译器不可用、未初始化或编译失败，返回 None。
+/// 如果方法的 JIT 级别为 off、JIT 编译器初始化失败或编译失败，返回 None。
 /// 调用方应回退到解释器执行。
+///
+/// 编译器按线程惰性初始化：第一次需要编译时才创建。
 pub fn try_compile(method_id: &MethodIdRef) -> Option<Arc<JITCompiledMethod>> {
+    let level = opt::level_for(&method_id.method);
+    if level == JitLevel::Off {
+        return None;
+    }
+
+    if let Err(e) = init() {
+        warn!("JIT: init failed: {}", e);
+        opt::set_default_level(JitLevel::Off);
+        return None;
+    }
+
     JIT_COMPILER.with(|cell| {
         let mut guard = cell.borrow_mut();
         let compiler = guard.as_mut()?;
-        compiler.compile_method(method_id)
+        compiler.compile_method(method_id, level)
     })
 }
 
@@ -7,17 +7,20 @@
 //
 // ## 设计原则
 //
-// 1. 函数签名使用原始 i32/u32 类型，匹配 LLVM IR 中的 i32 表示
+// 1. 函数签名使用与 LLVM IR 对应的原始类型：int/引用是 i32/u32（引用为
+//    slot_id，0 为 null），long/float/double 是 i64/f32/f64；
+//    类型由描述符决定的值（字段）统一编码为 i64，见 `runtime::encode_value`
 // 2. 通过 TLS `JitInvokeCtx` 获取方法所属类，用于常量池解析
 // 3. 异常通过 `exception::meet_ex` 设置，JIT 代码在返回后检查
-// 4. 返回值通过修改栈槽传递（引用返回 slot_id，int 直接写入）
+// 4. 结果通过返回值传递，由 JIT 代码压入操作数栈
 // ============================================================
 
-use crate::oop::{self, Oop};
+use crate::oop::{self, Oop, ValueType};
 use crate::runtime::cmp;
 use crate::runtime::exception;
-use crate::runtime::jit::runtime::{get_invoke_ctx, restore_invoke_ctx};
-use crate::runtime::{require_class3, ClassLoader};
+use crate::runtime::jit::runtime::{encode_value, get_invoke_ctx, restore_invoke_ctx};
+use crate::runtime::thread::{self, safepoint};
+use crate::runtime::{module, require_class3, ClassLoader};
 use crate::util;
 use classfile::constant_pool;
 use classfile::consts as cls_const;
@@ -61,6 +64,12 @@ pub extern "C" fn jit_new_inst(cp_idx: u16) -> u32 {
         }
     };
 
+    // 模块访问检查，失败时已抛出 IllegalAccessError
+    if !module::check_access(cls.name.as_slice(), &class) {
+        restore_invoke_ctx(Some(ctx));
+        return 0;
+    }
+
     // 初始化类
     oop::class::init_class(&class);
     oop::class::init_class_fully(&class);
@@ -116,9 +125,7 @@ pub extern "C" fn jit_anewarray(cp_idx: u16, length: i32) -> u32 {
         }
     };
 
-    oop::class::init_class(&class);
-    oop::class::init_class_fully(&class);
-
+    // 与解释器一致，anewarray 不初始化元素类
     // 构建数组类型描述符
     use crate::oop::class::ClassKindType;
     let (name, cl) = {
@@ -208,6 +215,12 @@ pub extern "C" fn jit_checkcast(cp_idx: u16, obj_slot: u32) {
         }
     };
 
+    // 模块访问检查，失败时已抛出 IllegalAccessError
+    if !module::check_access(cls.name.as_slice(), &target_cls) {
+        restore_invoke_ctx(Some(ctx));
+        return;
+    }
+
     let result = oop::with_heap(|heap| {
         let desc = heap.get(obj_slot);
         let guard = desc.read().unwrap();
@@ -220,12 +233,11 @@ pub extern "C" fn jit_checkcast(cp_idx: u16, obj_slot: u32) {
                 let obj_cls = ary.class.clone();
                 cmp::instance_of(obj_cls.clone(), target_cls.clone())
             }
-            oop::RefKind::Mirror(mirror) => {
-                let obj_cls = mirror.target.clone().unwrap();
-                let target_name = target_cls.get_class().name.as_slice();
-                target_name == b"java/lang/Class"
-                    || cmp::instance_of(obj_cls.clone(), target_cls.clone())
-            }
+            // Class 对象（包括基本类型的）是 java/lang/Class 的实例
+            oop::RefKind::Mirror(_) => match require_class3(None, cls_const::J_CLASS) {
+                Some(class_cls) => cmp::instance_of(class_cls, target_cls.clone()),
+                None => false,
+            },
             oop::RefKind::TypeArray(tary) => {
                 let class_name = tary.class_name();
                 match require_class3(None, &class_name) {
@@ -281,6 +293,12 @@ pub extern "C" fn jit_instanceof(cp_idx: u16, obj_slot: u32) -> i32 {
         }
     };
 
+    // 模块访问检查，失败时已抛出 IllegalAccessError
+    if !module::check_access(cls.name.as_slice(), &target_cls) {
+        restore_invoke_ctx(Some(ctx));
+        return 0;
+    }
+
     let result = oop::with_heap(|heap| {
         let desc = heap.get(obj_slot);
         let guard = desc.read().unwrap();
@@ -293,12 +311,11 @@ pub extern "C" fn jit_instanceof(cp_idx: u16, obj_slot: u32) -> i32 {
                 let obj_cls = ary.class.clone();
                 cmp::instance_of(obj_cls.clone(), target_cls.clone())
             }
-            oop::RefKind::Mirror(mirror) => {
-                let obj_cls = mirror.target.clone().unwrap();
-                let target_name = target_cls.get_class().name.as_slice();
-                target_name == b"java/lang/Class"
-                    || cmp::instance_of(obj_cls.clone(), target_cls.clone())
-            }
+            // Class 对象（包括基本类型的）是 java/lang/Class 的实例
+            oop::RefKind::Mirror(_) => match require_class3(None, cls_const::J_CLASS) {
+                Some(class_cls) => cmp::instance_of(class_cls, target_cls.clone()),
+                None => false,
+            },
             oop::RefKind::TypeArray(tary) => {
                 let class_name = tary.class_name();
                 match require_class3(None, &class_name) {
@@ -322,48 +339,56 @@ pub extern "C" fn jit_instanceof(cp_idx: u16, obj_slot: u32) -> i32 {
 // Field access runtime callouts
 // ============================================================
 
-/// JIT `getfield` 指令：读取实例字段。
-/// 栈顶是 object slot_id，读取后推入字段值到 stack。
+/// 按字段类型把 JIT 传来的值解码为 `Oop`，编码方式见 `runtime::encode_value`。
+fn decode_field(value_type: ValueType, raw: i64) -> Oop {
+    match value_type {
+        ValueType::LONG => Oop::Long(raw),
+        ValueType::DOUBLE => Oop::Double(f64::from_bits(raw as u64)),
+        ValueType::FLOAT => Oop::Float(f32::from_bits(raw as u32)),
+        ValueType::ARRAY | ValueType::OBJECT => match raw as u32 {
+            0 => Oop::Null,
+            slot_id => Oop::Ref(slot_id),
+        },
+        _ => Oop::Int(raw as i32),
+    }
+}
+
+/// JIT `getfield` 指令：读取实例字段，返回按字段类型编码的值。
 #[no_mangle]
-pub extern "C" fn jit_getfield(cp_idx: u16, stack: *mut i32, stack_top: u32) {
+pub extern "C" fn jit_getfield(cp_idx: u16, obj_slot: u32) -> i64 {
     let ctx = match get_invoke_ctx() {
         Some(c) => c,
-        None => return,
+        None => return 0,
     };
     let cls = ctx.method_class.clone();
 
-    // Pop object ref
-    let obj_pos = (stack_top - 1) as usize;
-    let obj_slot = unsafe { *stack.add(obj_pos) } as u32;
-
     if obj_slot == 0 {
         exception::meet_ex(cls_const::J_NPE, None);
         restore_invoke_ctx(Some(ctx));
-        return;
+        return 0;
     }
 
     let fir = match cls.get_cp_field(cp_idx as usize, false) {
         Some(f) => f,
         None => {
-            exception::meet_ex(cls_const::J_NSME, None);
+            if !thread::is_meet_ex() {
+                exception::meet_ex(cls_const::J_NSME, None);
+            }
             restore_invoke_ctx(Some(ctx));
-            return;
+            return 0;
         }
     };
 
-    let value_type = fir.field.value_type;
     let v = oop::Class::get_field_value2(obj_slot, fir.offset);
+    fir.after_load();
 
     restore_invoke_ctx(Some(ctx));
-
-    // Push result
-    let new_top = stack_top - 1; // pop obj, push value
-    unsafe { *stack.add(new_top as usize) = v.extract_int() };
+    encode_value(&v)
 }
 
 /// JIT `putfield` 指令：写入实例字段。
 #[no_mangle]
-pub extern "C" fn jit_putfield(cp_idx: u16, stack: *mut i32, stack_top: u32) {
+pub extern "C" fn jit_putfield(cp_idx: u16, obj_slot: u32, value: i64) {
     let ctx = match get_invoke_ctx() {
         Some(c) => c,
         None => return,
@@ -373,73 +398,44 @@ pub extern "C" fn jit_putfield(cp_idx: u16, stack: *mut i32, stack_top: u32) {
     let fir = match cls.get_cp_field(cp_idx as usize, false) {
         Some(f) => f,
         None => {
-            exception::meet_ex(cls_const::J_NSME, None);
+            if !thread::is_meet_ex() {
+                exception::meet_ex(cls_const::J_NSME, None);
+            }
             restore_invoke_ctx(Some(ctx));
             return;
         }
     };
 
-    let value_type = fir.field.value_type;
-    let val_pos = (stack_top - 2) as usize;
-    let obj_pos = (stack_top - 1) as usize;
-
-    let obj_slot = unsafe { *stack.add(obj_pos) } as u32;
-
     if obj_slot == 0 {
         exception::meet_ex(cls_const::J_NPE, None);
         restore_invoke_ctx(Some(ctx));
         return;
     }
 
-    let v = match value_type {
-        crate::oop::ValueType::LONG => {
-            let lo = unsafe { *stack.add(val_pos) } as i64;
-            let hi = unsafe { *stack.add(val_pos + 1) } as i64;
-            Oop::Long(lo | (hi << 32))
-        }
-        crate::oop::ValueType::DOUBLE => {
-            let lo = unsafe { *stack.add(val_pos) } as u64;
-            let hi = unsafe { *stack.add(val_pos + 1) } as u64;
-            Oop::Double(f64::from_bits(lo | (hi << 32)))
-        }
-        crate::oop::ValueType::FLOAT => {
-            let bits = unsafe { *stack.add(val_pos) } as u32;
-            Oop::Float(f32::from_bits(bits))
-        }
-        crate::oop::ValueType::INT
-        | crate::oop::ValueType::BYTE
-        | crate::oop::ValueType::SHORT
-        | crate::oop::ValueType::CHAR
-        | crate::oop::ValueType::BOOLEAN => {
-            let val = unsafe { *stack.add(val_pos) };
-            Oop::Int(val)
-        }
-        crate::oop::ValueType::ARRAY | crate::oop::ValueType::OBJECT => {
-            let slot = unsafe { *stack.add(val_pos) } as u32;
-            Oop::Ref(slot)
-        }
-        _ => Oop::Int(0),
-    };
-
+    let v = decode_field(fir.field.value_type, value);
+    fir.before_store();
     oop::Class::put_field_value2(obj_slot, fir.offset, v);
+    fir.after_store();
     restore_invoke_ctx(Some(ctx));
 }
 
-/// JIT `getstatic` 指令：读取静态字段。
+/// JIT `getstatic` 指令：读取静态字段，返回按字段类型编码的值。
 #[no_mangle]
-pub extern "C" fn jit_getstatic(cp_idx: u16, stack: *mut i32, stack_top: u32) {
+pub extern "C" fn jit_getstatic(cp_idx: u16) -> i64 {
     let ctx = match get_invoke_ctx() {
         Some(c) => c,
-        None => return,
+        None => return 0,
     };
     let cls = ctx.method_class.clone();
 
     let fir = match cls.get_cp_field(cp_idx as usize, true) {
         Some(f) => f,
         None => {
-            exception::meet_ex(cls_const::J_NSME, None);
+            if !thread::is_meet_ex() {
+                exception::meet_ex(cls_const::J_NSME, None);
+            }
             restore_invoke_ctx(Some(ctx));
-            return;
+            return 0;
         }
     };
 
@@ -448,14 +444,14 @@ pub extern "C" fn jit_getstatic(cp_idx: u16, stack: *mut i32, stack_top: u32) {
         .class
         .get_class()
         .get_static_field_value(fir.clone());
+    fir.after_load();
     restore_invoke_ctx(Some(ctx));
-
-    unsafe { *stack.add(stack_top as usize) = v.extract_int() };
+    encode_value(&v)
 }
 
 /// JIT `putstatic` 指令：写入静态字段。
 #[no_mangle]
-pub extern "C" fn jit_putstatic(cp_idx: u16, stack: *mut i32, _stack_top: u32) {
+pub extern "C" fn jit_putstatic(cp_idx: u16, value: i64) {
     let ctx = match get_invoke_ctx() {
         Some(c) => c,
         None => return,
@@ -465,47 +461,21 @@ pub extern "C" fn jit_putstatic(cp_idx: u16, stack: *mut i32, _stack_top: u32) {
     let fir = match cls.get_cp_field(cp_idx as usize, true) {
         Some(f) => f,
         None => {
-            exception::meet_ex(cls_const::J_NSME, None);
+            if !thread::is_meet_ex() {
+                exception::meet_ex(cls_const::J_NSME, None);
+            }
             restore_invoke_ctx(Some(ctx));
             return;
         }
     };
 
-    let value_type = fir.field.value_type;
-    let v = match value_type {
-        crate::oop::ValueType::LONG => {
-            let lo = unsafe { *stack.add(0) } as i64;
-            let hi = unsafe { *stack.add(1) } as i64;
-            Oop::Long(lo | (hi << 32))
-        }
-        crate::oop::ValueType::DOUBLE => {
-            let lo = unsafe { *stack.add(0) } as u64;
-            let hi = unsafe { *stack.add(1) } as u64;
-            Oop::Double(f64::from_bits(lo | (hi << 32)))
-        }
-        crate::oop::ValueType::FLOAT => {
-            let bits = unsafe { *stack.add(0) } as u32;
-            Oop::Float(f32::from_bits(bits))
-        }
-        crate::oop::ValueType::INT
-        | crate::oop::ValueType::BYTE
-        | crate::oop::ValueType::SHORT
-        | crate::oop::ValueType::CHAR
-        | crate::oop::ValueType::BOOLEAN => {
-            let val = unsafe { *stack.add(0) };
-            Oop::Int(val)
-        }
-        crate::oop::ValueType::ARRAY | crate::oop::ValueType::OBJECT => {
-            let slot = unsafe { *stack.add(0) } as u32;
-            Oop::Ref(slot)
-        }
-        _ => Oop::Int(0),
-    };
-
+    let v = decode_field(fir.field.value_type, value);
+    fir.before_store();
     fir.field
         .class
         .get_class()
         .put_static_field_value(fir.clone(), v);
+    fir.after_store();
     restore_invoke_ctx(Some(ctx));
 }
 
@@ -513,19 +483,19 @@ pub extern "C" fn jit_putstatic(cp_idx: u16, stack: *mut i32, _stack_top: u32) {
 // Array load/store runtime callouts
 // ============================================================
 
+/// 读取基本类型数组的元素。越界或 null 时设置异常并返回 `T::default()`，
+/// JIT 代码随后检查异常。
 fn array_load_helper<T>(
     array_slot: u32,
     index: i32,
     extractor: fn(&oop::TypeArrayDesc) -> &[T],
-    stack: *mut i32,
-    stack_top: u32,
-    converter: fn(T) -> i32,
-) where
-    T: Copy,
+) -> T
+where
+    T: Copy + Default,
 {
     if array_slot == 0 {
         exception::meet_ex(cls_const::J_NPE, None);
-        return;
+        return T::default();
     }
     let result = oop::with_heap(|heap| {
         let desc = heap.get(array_slot);
@@ -540,104 +510,42 @@ fn array_load_helper<T>(
         }
     });
     match result {
-        Ok(v) => unsafe {
-            *stack.add((stack_top - 2) as usize) = converter(v);
-        },
-        Err(()) => exception::meet_ex(
-            cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS,
-            Some(format!("length exceeded, index={}", index)),
-        ),
+        Ok(v) => v,
+        Err(()) => {
+            exception::meet_ex(
+                cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS,
+                Some(format!("length exceeded, index={}", index)),
+            );
+            T::default()
+        }
     }
 }
 
 #[no_mangle]
-pub extern "C" fn jit_iaload(array_slot: u32, index: i32, stack: *mut i32, stack_top: u32) {
-    array_load_helper(
-        array_slot,
-        index,
-        |a| a.extract_ints(),
-        stack,
-        stack_top,
-        |v| v,
-    );
+pub extern "C" fn jit_iaload(array_slot: u32, index: i32) -> i32 {
+    array_load_helper(array_slot, index, |a| a.extract_ints())
 }
 
 #[no_mangle]
-pub extern "C" fn jit_laload(array_slot: u32, index: i32, stack: *mut i32, stack_top: u32) {
-    if array_slot == 0 {
-        exception::meet_ex(cls_const::J_NPE, None);
-        return;
-    }
-    let result = oop::with_heap(|heap| {
-        let desc = heap.get(array_slot);
-        let guard = desc.read().unwrap();
-        let ary = guard.v.extract_type_array();
-        let len = ary.len();
-        if index < 0 || index as usize >= len {
-            Err(())
-        } else {
-            Ok(*ary.extract_longs().get(index as usize).unwrap())
-        }
-    });
-    match result {
-        Ok(v) => {
-            let pos = (stack_top - 2) as usize;
-            unsafe {
-                *stack.add(pos) = (v & 0xFFFFFFFF) as i32;
-                *stack.add(pos + 1) = ((v >> 32) & 0xFFFFFFFF) as i32;
-            }
-        }
-        Err(()) => exception::meet_ex(cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS, None),
-    }
+pub extern "C" fn jit_laload(array_slot: u32, index: i32) -> i64 {
+    array_load_helper(array_slot, index, |a| a.extract_longs())
 }
 
 #[no_mangle]
-pub extern "C" fn jit_faload(array_slot: u32, index: i32, stack: *mut i32, stack_top: u32) {
-    array_load_helper(
-        array_slot,
-        index,
-        |a| a.extract_floats(),
-        stack,
-        stack_top,
-        |v: f32| v.to_bits() as i32,
-    );
+pub extern "C" fn jit_faload(array_slot: u32, index: i32) -> f32 {
+    array_load_helper(array_slot, index, |a| a.extract_floats())
 }
 
 #[no_mangle]
-pub extern "C" fn jit_daload(array_slot: u32, index: i32, stack: *mut i32, stack_top: u32) {
-    if array_slot == 0 {
-        exception::meet_ex(cls_const::J_NPE, None);
-        return;
-    }
-    let result = oop::with_heap(|heap| {
-        let desc = heap.get(array_slot);
-        let guard = desc.read().unwrap();
-        let ary = guard.v.extract_type_array();
-        let len = ary.len();
-        if index < 0 || index as usize >= len {
-            Err(())
-        } else {
-            Ok(*ary.extract_doubles().get(index as usize).unwrap())
-        }
-    });
-    match result {
-        Ok(v) => {
-            let bits = v.to_bits();
-            let pos = (stack_top - 2) as usize;
-            unsafe {
-                *stack.add(pos) = (bits & 0xFFFFFFFF) as i32;
-                *stack.add(pos + 1) = ((bits >> 32) & 0xFFFFFFFF) as i32;
-            }
-        }
-        Err(()) => exception::meet_ex(cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS, None),
-    }
+pub extern "C" fn jit_daload(array_slot: u32, index: i32) -> f64 {
+    array_load_helper(array_slot, index, |a| a.extract_doubles())
 }
 
 #[no_mangle]
-pub extern "C" fn jit_aaload(array_slot: u32, index: i32, stack: *mut i32, stack_top: u32) {
+pub extern "C" fn jit_aaload(array_slot: u32, index: i32) -> u32 {
     if array_slot == 0 {
         exception::meet_ex(cls_const::J_NPE, None);
-        return;
+        return 0;
     }
     let result = oop::with_heap(|heap| {
         let desc = heap.get(array_slot);
@@ -651,22 +559,21 @@ pub extern "C" fn jit_aaload(array_slot: u32, index: i32, stack: *mut i32, stack
         }
     });
     match result {
-        Some(Oop::Ref(slot)) => unsafe {
-            *stack.add((stack_top - 2) as usize) = slot as i32;
-        },
-        Some(Oop::Null) => unsafe {
-            *stack.add((stack_top - 2) as usize) = 0;
-        },
+        Some(Oop::Ref(slot)) => slot,
+        Some(Oop::Null) => 0,
         Some(_) => unreachable!(),
-        None => exception::meet_ex(cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS, None),
+        None => {
+            exception::meet_ex(cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS, None);
+            0
+        }
     }
 }
 
 #[no_mangle]
-pub extern "C" fn jit_baload(array_slot: u32, index: i32, stack: *mut i32, stack_top: u32) {
+pub extern "C" fn jit_baload(array_slot: u32, index: i32) -> i32 {
     if array_slot == 0 {
         exception::meet_ex(cls_const::J_NPE, None);
-        return;
+        return 0;
     }
     let result = oop::with_heap(|heap| {
         let desc = heap.get(array_slot);
@@ -684,40 +591,26 @@ pub extern "C" fn jit_baload(array_slot: u32, index: i32, stack: *mut i32, stack
             Ok(v)
         }
     });
-    if let Ok(v) = result {
-        unsafe {
-            *stack.add((stack_top - 2) as usize) = v;
+    match result {
+        Ok(v) => v,
+        Err(()) => {
+            exception::meet_ex(
+                cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS,
+                Some(format!("length exceeded, index={}", index)),
+            );
+            0
         }
-    } else {
-        exception::meet_ex(
-            cls_const::J_ARRAY_INDEX_OUT_OF_BOUNDS,
-            Some(format!("length exceeded, index={}", index)),
-        );
     }
 }
 
 #[no_mangle]
-pub extern "C" fn jit_caload(array_slot: u32, index: i32, stack: *mut i32, stack_top: u32) {
-    array_load_helper(
-        array_slot,
-        index,
-        |a| a.extract_chars(),
-        stack,
-        stack_top,
-        |v: u16| v as i32,
-    );
+pub extern "C" fn jit_caload(array_slot: u32, index: i32) -> i32 {
+    array_load_helper(array_slot, index, |a| a.extract_chars()) as i32
 }
 
 #[no_mangle]
-pub extern "C" fn jit_saload(array_slot: u32, index: i32, stack: *mut i32, stack_top: u32) {
-    array_load_helper(
-        array_slot,
-        index,
-        |a| a.extract_shorts(),
-        stack,
-        stack_top,
-        |v: i16| v as i32,
-    );
+pub extern "C" fn jit_saload(array_slot: u32, index: i32) -> i32 {
+    array_load_helper(array_slot, index, |a| a.extract_shorts()) as i32
 }
 
 fn array_store_helper(
@@ -753,41 +646,39 @@ pub extern "C" fn jit_iastore(array_slot: u32, index: i32, value: i32) {
 }
 
 #[no_mangle]
-pub extern "C" fn jit_lastore(array_slot: u32, index: i32, lo: i32, hi: i32) {
+pub extern "C" fn jit_lastore(array_slot: u32, index: i32, value: i64) {
     array_store_helper(array_slot, index, |ary| {
         let longs = ary.extract_mut_longs();
         if index < 0 || index as usize >= longs.len() {
             Err(())
         } else {
-            let v = (lo as i64 & 0xFFFFFFFF) | ((hi as i64 & 0xFFFFFFFF) << 32);
-            longs[index as usize] = v;
+            longs[index as usize] = value;
             Ok(())
         }
     });
 }
 
 #[no_mangle]
-pub extern "C" fn jit_fastore(array_slot: u32, index: i32, bits: i32) {
+pub extern "C" fn jit_fastore(array_slot: u32, index: i32, value: f32) {
     array_store_helper(array_slot, index, |ary| {
         let floats = ary.extract_mut_floats();
         if index < 0 || index as usize >= floats.len() {
             Err(())
         } else {
-            floats[index as usize] = f32::from_bits(bits as u32);
+            floats[index as usize] = value;
             Ok(())
         }
     });
 }
 
 #[no_mangle]
-pub extern "C" fn jit_dastore(array_slot: u32, index: i32, lo: i32, hi: i32) {
+pub extern "C" fn jit_dastore(array_slot: u32, index: i32, value: f64) {
     array_store_helper(array_slot, index, |ary| {
         let doubles = ary.extract_mut_doubles();
         if index < 0 || index as usize >= doubles.len() {
             Err(())
         } else {
-            let bits = (lo as u64 & 0xFFFFFFFF) | ((hi as u64 & 0xFFFFFFFF) << 32);
-            doubles[index as usize] = f64::from_bits(bits);
+            doubles[index as usize] = value;
             Ok(())
         }
     });
@@ -879,11 +770,8 @@ pub extern "C" fn jit_monitorenter(obj_slot: u32) {
         exception::meet_ex(cls_const::J_NPE, None);
         return;
     }
-    oop::with_heap(|heap| {
-        let desc = heap.get(obj_slot);
-        let guard = desc.read().unwrap();
-        guard.monitor_enter();
-    });
+    // 在堆锁之外阻塞，持有者可能需要堆锁才能释放监视器
+    thread::monitor_enter(&Oop::Ref(obj_slot));
 }
 
 #[no_mangle]
@@ -891,24 +779,53 @@ pub extern "C" fn jit_monitorexit(obj_slot: u32) {
     if obj_slot == 0 {
         return;
     }
-    oop::with_heap(|heap| {
-        let desc = heap.get(obj_slot);
-        let guard = desc.read().unwrap();
-        guard.monitor_exit();
-    });
+    if !Oop::Ref(obj_slot).unlock() {
+        exception::meet_ex(cls_const::J_IMSE, None);
+    }
+}
+
+// ============================================================
+// Safepoint runtime callout
+// ============================================================
+
+/// 编译代码在方法入口和循环头读到 safepoint 请求时调用，
+/// 停在这里直到 safepoint 结束。
+#[no_mangle]
+pub extern "C" fn jit_safepoint_poll() {
+    safepoint::poll();
+}
+
+// ============================================================
+// Exception runtime callouts
+// ============================================================
+
+/// 运行时函数返回后由 JIT 代码调用：当前线程是否有待处理的异常。
+#[no_mangle]
+pub extern "C" fn jit_exception_pending() -> i32 {
+    thread::is_meet_ex() as i32
+}
+
+/// 整数除法 / 取余的除数为 0。
+#[no_mangle]
+pub extern "C" fn jit_throw_div_by_zero() {
+    exception::meet_ex(
+        cls_const::J_ARITHMETIC_EX,
+        Some("divide by zero".to_string()),
+    );
 }
 
 // ============================================================
 // ldc runtime callout
 // ============================================================
 
-/// JIT `ldc` / `ldc_w` 指令：从常量池加载常量。
-/// 支持：String、Integer、Float、Long、Double、Class
+/// JIT `ldc` / `ldc_w` 指令：加载 String / Class 常量，返回引用。
+///
+/// int/float/long/double 常量在编译期直接生成为 LLVM 常量，不经过这里。
 #[no_mangle]
-pub extern "C" fn jit_ldc(cp_idx: u16, stack: *mut i32, stack_top: u32) {
+pub extern "C" fn jit_ldc(cp_idx: u16) -> u32 {
     let ctx = match get_invoke_ctx() {
         Some(c) => c,
-        None => return,
+        None => return 0,
     };
     let cls = ctx.method_class.clone();
 
@@ -916,7 +833,7 @@ pub extern "C" fn jit_ldc(cp_idx: u16, stack: *mut i32, stack_top: u32) {
         Some(cp) => cp,
         None => {
             restore_invoke_ctx(Some(ctx));
-            return;
+            return 0;
         }
     };
     use classfile::ConstantPoolType;
@@ -925,79 +842,26 @@ pub extern "C" fn jit_ldc(cp_idx: u16, stack: *mut i32, stack_top: u32) {
     let result = match entry {
         Some(ConstantPoolType::String { string_index }) => {
             let s = classfile::constant_pool::get_utf8(&cp, *string_index as usize);
-            let oop = util::oop::new_java_lang_string3(s.as_slice());
-            match oop {
-                Oop::Ref(slot_id) => slot_id as i32,
-                Oop::Null => 0,
-                _ => 0,
-            }
+            util::oop::new_java_lang_string3(s.as_slice())
         }
-        Some(ConstantPoolType::Integer { v }) => i32::from_be_bytes(v.clone()),
-        Some(ConstantPoolType::Float { v }) => i32::from_be_bytes(v.clone()),
         Some(ConstantPoolType::Class { name_index }) => {
             let class_name = classfile::constant_pool::get_utf8(&cp, *name_index as usize);
-            let class = resolve_cp_class_from_name(class_name.as_slice());
-            match class {
-                Some(cls) => {
-                    let mirror = cls.get_mirror();
-                    match mirror {
-                        Oop::Ref(slot_id) => slot_id as i32,
-                        _ => 0,
-                    }
-                }
-                None => 0,
+            match resolve_cp_class_from_name(class_name.as_slice()) {
+                // 模块访问检查，失败时已抛出 IllegalAccessError
+                Some(class) if !module::check_access(cls.name.as_slice(), &class) => Oop::Null,
+                Some(class) => class.get_mirror(),
+                None => Oop::Null,
             }
         }
         _ => {
             warn!("JIT: ldc unsupported type {:?}", entry);
-            0
+            Oop::Null
         }
     };
 
     restore_invoke_ctx(Some(ctx));
-    unsafe { *stack.add(stack_top as usize) = result };
-}
-
-/// JIT `ldc2_w` 指令：从常量池加载 long/double 常量。
-#[no_mangle]
-pub extern "C" fn jit_ldc2_w(cp_idx: u16, stack: *mut i32, stack_top: u32) {
-    let ctx = match get_invoke_ctx() {
-        Some(c) => c,
-        None => return,
-    };
-    let cls = ctx.method_class.clone();
-
-    let cp = match cls.get_constant_pool() {
-        Some(cp) => cp,
-        None => {
-            restore_invoke_ctx(Some(ctx));
-            return;
-        }
-    };
-    use classfile::ConstantPoolType;
-    let entry = cp.get(cp_idx as usize);
-
-    match entry {
-        Some(ConstantPoolType::Long { v }) => {
-            let lo = i32::from_be_bytes([v[4], v[5], v[6], v[7]]);
-            let hi = i32::from_be_bytes([v[0], v[1], v[2], v[3]]);
-            unsafe {
-                *stack.add(stack_top as usize) = lo;
-                *stack.add((stack_top + 1) as usize) = hi;
-            }
-        }
-        Some(ConstantPoolType::Double { v }) => {
-            let lo = i32::from_be_bytes([v[4], v[5], v[6], v[7]]);
-            let hi = i32::from_be_bytes([v[0], v[1], v[2], v[3]]);
-            unsafe {
-                *stack.add(stack_top as usize) = lo;
-                *stack.add((stack_top + 1) as usize) = hi;
-            }
-        }
-        _ => {
-            warn!("JIT: ldc2_w unsupported type {:?}", entry);
-        }
+    match result {
+        Oop::Ref(slot_id) => slot_id,
+        _ => 0,
     }
-
-    restore_invoke_ctx(Some(ctx));
 }
@@ -0,0 +1,217 @@
+// ============================================================
+// JIT 优化分级
+//
+// builder 生成的 IR 非常朴素：每个 local / 栈槽都是 alloca，每条 bytecode
+// 都是 load → 运算 → store。不跑任何 pass 的话，机器码就是这些内存读写
+// 的直译。LLVM 的 pass 负责把它们变成像样的代码：
+//
+// - mem2reg:       alloca 提升为 SSA 寄存器（一切后续优化的前提）
+// - instcombine:   窥孔优化，合并冗余指令
+// - simplifycfg:   合并/删除基本块，消除空跳转
+// - gvn:           全局值编号，消除公共子表达式和冗余 load
+// - licm:          循环不变量外提
+// - loop-vectorize: 循环向量化
+//
+// 分级（tier）决定跑哪些 pass，越高的级别编译越慢、代码越快：
+//
+// | 级别 | pass                                          |
+// |------|-----------------------------------------------|
+// | off  | 不编译，始终解释执行                            |
+// | 0    | 无                                            |
+// | 1    | mem2reg, instcombine, simplifycfg             |
+// | 2    | 1 + gvn, licm                                 |
+// | 3    | 2 + loop-vectorize                            |
+//
+// 默认级别为 off（JIT 尚未默认开启）。级别可以全局设置，也可以按方法
+// 覆盖（命令行 --jit-level / --jit-method-level）。
+// ============================================================
+
+use crate::runtime::method::Method;
+use inkwell::OptimizationLevel;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+
+/// JIT 编译级别。
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JitLevel {
+    /// 不编译，始终解释执行。
+    #[default]
+    Off,
+    O0,
+    O1,
+    O2,
+    O3,
+}
+
+impl JitLevel {
+    /// 该级别运行的 LLVM pass pipeline（新 pass manager 的文本格式）。
+    /// 返回 None 表示不跑任何 pass。
+    pub fn passes(self) -> Option<&'static str> {
+        match self {
+            JitLevel::Off | JitLevel::O0 => None,
+            JitLevel::O1 => Some("mem2reg,instcombine,simplifycfg"),
+            JitLevel::O2 => Some("mem2reg,instcombine,simplifycfg,gvn,loop-mssa(licm),simplifycfg"),
+            JitLevel::O3 => Some(
+                "mem2reg,instcombine,simplifycfg,gvn,loop-simplify,lcssa,loop-mssa(licm),\
+                 loop-vectorize,instcombine,simplifycfg",
+            ),
+        }
+    }
+
+    /// 该级别对应的 LLVM 代码生成优化级别。
+    pub fn codegen_level(self) -> OptimizationLevel {
+        match self {
+            JitLevel::Off | JitLevel::O0 => OptimizationLevel::None,
+            JitLevel::O1 => OptimizationLevel::Less,
+            JitLevel::O2 => OptimizationLevel::Default,
+            JitLevel::O3 => OptimizationLevel::Aggressive,
+        }
+    }
+}
+
+impl FromStr for JitLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("off") {
+            return Ok(JitLevel::Off);
+        }
+        match s.strip_prefix(['O', 'o']).unwrap_or(s) {
+            "0" => Ok(JitLevel::O0),
+            "1" => Ok(JitLevel::O1),
+            "2" => Ok(JitLevel::O2),
+            "3" => Ok(JitLevel::O3),
+            _ => Err(format!(
+                "invalid JIT level '{}', expected off, 0, 1, 2 or 3",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for JitLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JitLevel::Off => write!(f, "off"),
+            JitLevel::O0 => write!(f, "0"),
+            JitLevel::O1 => write!(f, "1"),
+            JitLevel::O2 => write!(f, "2"),
+            JitLevel::O3 => write!(f, "3"),
+        }
+    }
+}
+
+/// 方法匹配模式。
+///
+/// 形如 `java/lang/String.hashCode`、`Foo.*` 或 `*.run`，
+/// 类名用 `/` 或 `.` 分隔均可。
+pub struct MethodPattern {
+    class: String,
+    method: String,
+}
+
+impl MethodPattern {
+    pub fn matches(&self, class: &str, method: &str) -> bool {
+        (self.class == "*" || self.class == class) && (self.method == "*" || self.method == method)
+    }
+
+    pub fn matches_method(&self, method: &Method) -> bool {
+        let class = String::from_utf8_lossy(method.cls_name.as_slice());
+        let name = String::from_utf8_lossy(method.name.as_slice());
+        self.matches(&class, &name)
+    }
+}
+
+impl FromStr for MethodPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (class, method) = s
+            .rsplit_once('.')
+            .ok_or_else(|| format!("invalid method pattern '{}', expected Class.method", s))?;
+        Ok(MethodPattern {
+            class: class.replace('.', "/"),
+            method: method.to_string(),
+        })
+    }
+}
+
+/// 按方法覆盖的级别。
+struct MethodLevel {
+    pattern: MethodPattern,
+    level: JitLevel,
+}
+
+#[derive(Default)]
+struct JitConfig {
+    default: JitLevel,
+    methods: Vec<MethodLevel>,
+}
+
+static CONFIG: OnceLock<RwLock<JitConfig>> = OnceLock::new();
+
+fn config() -> &'static RwLock<JitConfig> {
+    CONFIG.get_or_init(|| RwLock::new(JitConfig::default()))
+}
+
+/// 设置全局默认级别。
+pub fn set_default_level(level: JitLevel) {
+    config().write().unwrap().default = level;
+}
+
+pub fn default_level() -> JitLevel {
+    config().read().unwrap().default
+}
+
+/// 解析 `PATTERN=LEVEL` 并登记为按方法覆盖的级别。后登记的优先。
+pub fn add_method_level(spec: &str) -> Result<(), String> {
+    let (pattern, level) = spec
+        .rsplit_once('=')
+        .ok_or_else(|| format!("invalid method level '{}', expected PATTERN=LEVEL", spec))?;
+    let level = level.parse()?;
+    let pattern = pattern.parse()?;
+
+    config()
+        .write()
+        .unwrap()
+        .methods
+        .push(MethodLevel { pattern, level });
+    Ok(())
+}
+
+/// 查询 `method` 的编译级别。
+pub fn level_for(method: &Method) -> JitLevel {
+    let config = config().read().unwrap();
+    if config.methods.is_empty() {
+        return config.default;
+    }
+
+    config
+        .methods
+        .iter()
+        .rev()
+        .find(|m| m.pattern.matches_method(method))
+        .map(|m| m.level)
+        .unwrap_or(config.default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_parse_level() {
+        assert_eq!("off".parse::<JitLevel>(), Ok(JitLevel::Off));
+        assert_eq!("0".parse::<JitLevel>(), Ok(JitLevel::O0));
+        assert_eq!("O2".parse::<JitLevel>(), Ok(JitLevel::O2));
+        assert!("4".parse::<JitLevel>().is_err());
+    }
+
+    #[test]
+    fn t_method_pattern() {
+        let m: MethodPattern = "java.lang.String.*".parse().unwrap();
+        assert!(m.matches("java/lang/String", "hashCode"));
+        assert!(!m.matches("java/lang/Object", "hashCode"));
+    }
+}
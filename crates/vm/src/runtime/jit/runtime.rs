@@ -4,12 +4,17 @@
 // JIT 编译的代码通过 LLVM 外部函数声明调用本模块中的函数。
 // ============================================================
 
+use super::cha::Assumption;
+use super::deopt;
+use super::inline_cache::InlineCache;
 use crate::oop::{self, Oop};
 use crate::runtime::exception;
+use crate::runtime::method::JITCompiledMethod;
 use crate::runtime::thread;
-use crate::runtime::{self, frame::Frame, JavaCall};
-use crate::types::ClassRef;
+use crate::runtime::{DataArea, JavaCall};
+use crate::types::{ClassRef, MethodIdRef};
 use classfile::consts as cls_const;
+use classfile::SignatureType;
 use std::cell::RefCell;
 use std::sync::Arc;
 use tracing::warn;
@@ -19,6 +24,10 @@ use tracing::warn;
 pub struct JitInvokeCtx {
     /// 当前 JIT 编译方法所属的类（用于常量池方法解析）。
     pub method_class: ClassRef,
+    /// 当前 JIT 编译方法。
+    pub mir: MethodIdRef,
+    /// 正在执行的编译产物（用于去优化时查找安全点元数据）。
+    pub compiled: Arc<JITCompiledMethod>,
 }
 
 thread_local! {
@@ -44,329 +53,309 @@ pub fn restore_invoke_ctx(ctx: Option<JitInvokeCtx>) {
     });
 }
 
+/// 调用点的目标方法解析方式。
+enum Dispatch {
+    /// 目标在编译期唯一（invokestatic / invokespecial / 有效的 CHA 假设）。
+    Exact,
+    /// 按接收者类做虚方法解析，可选地经过调用点的内联缓存。
+    Virtual(Option<&'static InlineCache>),
+    /// 按接收者类做接口方法解析，可选地经过调用点的内联缓存。
+    Interface(Option<&'static InlineCache>),
+}
+
 /// 执行 invokevirtual。
+///
+/// invoke 系列函数的参数约定相同：`args` 指向调用方准备的参数数组，
+/// 每个 Java 参数（含 this）一个 i64，按 `encode_value` 编码；
+/// 返回值同样编码为 i64，void 方法或发生异常时返回 0。
+#[no_mangle]
 pub extern "C" fn jit_invoke_virtual(
     cp_idx: u16,
-    _locals: *mut i32,
-    stack: *mut i32,
-    stack_top: u32,
-) {
-    invoke_from_jit_stack(cp_idx, stack, stack_top, false, false, false);
+    args: *const i64,
+    ic: Option<&'static InlineCache>,
+) -> i64 {
+    invoke_from_jit(cp_idx, args, Dispatch::Virtual(ic))
 }
 
-/// 执行 invokespecial。
-pub extern "C" fn jit_invoke_special(
+/// 执行经 CHA 去虚化的 invokevirtual。
+///
+/// 假设失效（加载了覆盖目标方法的子类）时退回完整的虚方法解析。
+#[no_mangle]
+pub extern "C" fn jit_invoke_devirt(
     cp_idx: u16,
-    _locals: *mut i32,
-    stack: *mut i32,
-    stack_top: u32,
-) {
-    invoke_from_jit_stack(cp_idx, stack, stack_top, false, true, false);
+    args: *const i64,
+    assumption: Option<&'static Assumption>,
+) -> i64 {
+    let valid = assumption.map(|a| a.is_valid()).unwrap_or(false);
+    let dispatch = if valid {
+        Dispatch::Exact
+    } else {
+        Dispatch::Virtual(None)
+    };
+    invoke_from_jit(cp_idx, args, dispatch)
+}
+
+/// 执行 invokespecial。
+#[no_mangle]
+pub extern "C" fn jit_invoke_special(cp_idx: u16, args: *const i64) -> i64 {
+    invoke_from_jit(cp_idx, args, Dispatch::Exact)
 }
 
 /// 执行 invokestatic。
-pub extern "C" fn jit_invoke_static(
-    cp_idx: u16,
-    _locals: *mut i32,
-    stack: *mut i32,
-    stack_top: u32,
-) {
-    invoke_from_jit_stack(cp_idx, stack, stack_top, true, true, false);
+#[no_mangle]
+pub extern "C" fn jit_invoke_static(cp_idx: u16, args: *const i64) -> i64 {
+    invoke_from_jit(cp_idx, args, Dispatch::Exact)
+}
+
+/// 调用点的符号引用是 intrinsic：跳过方法解析直接执行 Rust 实现，
+/// 实现不处理时按 invokestatic / invokespecial 的方式调用原方法。
+#[no_mangle]
+pub extern "C" fn jit_invoke_intrinsic(cp_idx: u16, args: *const i64) -> i64 {
+    invoke_from_jit(cp_idx, args, Dispatch::Exact)
 }
 
 /// 执行 invokeinterface。
+#[no_mangle]
 pub extern "C" fn jit_invoke_interface(
     cp_idx: u16,
-    _locals: *mut i32,
-    stack: *mut i32,
-    stack_top: u32,
-) {
-    invoke_from_jit_stack(cp_idx, stack, stack_top, false, false, true);
+    args: *const i64,
+    ic: Option<&'static InlineCache>,
+) -> i64 {
+    invoke_from_jit(cp_idx, args, Dispatch::Interface(ic))
 }
 
-/// 从 JIT 栈构建参数并执行方法调用。
-fn invoke_from_jit_stack(
-    cp_idx: u16,
-    stack: *mut i32,
-    stack_top: u32,
-    _is_static: bool,
-    force_no_resolve: bool,
-    is_interface: bool,
-) {
-    if stack.is_null() {
-        warn!("jit invoke: null stack pointer");
-        return;
+/// 安全点处编译后的代码已失效：转交解释器执行方法的剩余部分。
+///
+/// `locals` / `stack` 是 JIT 代码在安全点写出的各槽的值（按 `encode_value`
+/// 编码）。返回方法的返回值，JIT 代码随后直接返回它。
+#[no_mangle]
+pub extern "C" fn jit_deopt(point: u32, locals: *const i64, stack: *const i64) -> i64 {
+    let ctx = match get_invoke_ctx() {
+        Some(c) => c,
+        None => {
+            warn!("jit deopt: no invoke ctx");
+            return 0;
+        }
+    };
+    let ret = deopt_at(&ctx, point as usize, locals, stack);
+    restore_invoke_ctx(Some(ctx));
+    ret
+}
+
+fn deopt_at(ctx: &JitInvokeCtx, point: usize, locals: *const i64, stack: *const i64) -> i64 {
+    let p = match ctx.compiled.deopt.point(point) {
+        Some(p) => p,
+        None => {
+            warn!("jit deopt: unknown safepoint {}", point);
+            return 0;
+        }
+    };
+
+    let (locals, stk) = unsafe {
+        (
+            std::slice::from_raw_parts(locals, p.locals.len()),
+            std::slice::from_raw_parts(stack, p.stack.len()),
+        )
+    };
+    match deopt::deoptimize(&ctx.mir, p, locals, stk) {
+        Some(v) if !thread::is_meet_ex() => encode_value(&v),
+        _ => 0,
+    }
+}
+
+/// 用调用方上下文中的类解析调用点并执行方法调用。
+fn invoke_from_jit(cp_idx: u16, args: *const i64, dispatch: Dispatch) -> i64 {
+    if args.is_null() {
+        warn!("jit invoke: null args pointer");
+        return 0;
     }
 
-    // 获取当前上下文（消费它，防止递归调用时丢失）
+    // 取出当前上下文，调用结束后恢复：被调方法可能是另一个 JIT 方法，
+    // 它会设置自己的上下文
     let ctx = match get_invoke_ctx() {
         Some(c) => c,
         None => {
             warn!("jit invoke: no invoke ctx");
-            return;
+            return 0;
         }
     };
-
     let cls = ctx.method_class.clone();
+    let ret = invoke_with_class(&cls, cp_idx, args, dispatch);
+    restore_invoke_ctx(Some(ctx));
+    ret
+}
 
+fn invoke_with_class(cls: &ClassRef, cp_idx: u16, raw: *const i64, dispatch: Dispatch) -> i64 {
     // 从常量池解析方法
     let mir = match cls.get_cp_method(cp_idx as usize) {
         Some(m) => m,
         None => {
-            warn!(
-                "jit invoke: method resolution failed at cp index {}",
-                cp_idx
-            );
-            exception::meet_ex(cls_const::J_NSME, None);
-            return;
+            // 访问检查失败时已抛出 IllegalAccessError
+            if !thread::is_meet_ex() {
+                warn!(
+                    "jit invoke: method resolution failed at cp index {}",
+                    cp_idx
+                );
+                exception::meet_ex(cls_const::J_NSME, None);
+            }
+            return 0;
         }
     };
 
     let sig = &mir.method.signature;
-
-    // 从 JIT 栈构建 Oop 参数（逆序弹出）
-    let mut args = Vec::with_capacity(sig.args.len() + 1);
-    let mut pos = stack_top as usize;
-
-    for arg_type in sig.args.iter().rev() {
-        let (v, slots) = read_jit_stack_value(stack, pos, arg_type);
-        if pos < slots {
-            warn!("jit invoke: stack underflow");
-            return;
-        }
-        pos -= slots;
-        args.push(v);
-    }
-    args.reverse();
-
-    // 非静态方法需要 this
     let has_this = !mir.method.is_static();
+    let n = sig.args.len() + has_this as usize;
+    let raw = unsafe { std::slice::from_raw_parts(raw, n) };
+
+    // 按签名解码参数
+    let mut args = Vec::with_capacity(n);
     if has_this {
-        if pos == 0 {
-            warn!("jit invoke: missing 'this' on stack");
+        let slot_id = raw[0] as u32;
+        if slot_id == 0 {
             exception::meet_ex(cls_const::J_NPE, None);
-            return;
+            return 0;
         }
-        let slot_id = unsafe { *stack.add(pos - 1) } as u32;
-        let this = Oop::Ref(slot_id);
-
-        if let Oop::Null = this {
-            exception::meet_ex(cls_const::J_NPE, None);
-            return;
+        args.push(Oop::Ref(slot_id));
+    }
+    for (arg_type, &v) in sig.args.iter().zip(&raw[has_this as usize..]) {
+        match decode_value(arg_type, v) {
+            Some(v) => args.push(v),
+            None => {
+                warn!("jit invoke: unsupported arg type {:?}", arg_type);
+                args.push(Oop::Int(0));
+            }
         }
-
-        args.insert(0, this);
     }
 
     // 虚方法解析
-    let target_mir = if !force_no_resolve && has_this && !mir.method.is_final() {
-        if let Oop::Ref(slot_id) = &args[0] {
-            let sid = *slot_id;
-            let mut resolved = None;
-            oop::with_heap(|heap| {
-                let desc = heap.get(sid);
-                let guard = desc.read().unwrap();
-                if let oop::RefKind::Inst(inst) = &guard.v {
-                    let name = mir.method.name.clone();
-                    let desc_bytes = mir.method.desc.clone();
-                    let actual_cls = inst.class.get_class();
-                    let found = if is_interface {
-                        actual_cls.get_interface_method(&name, &desc_bytes).ok()
-                    } else {
-                        actual_cls.get_virtual_method(&name, &desc_bytes).ok()
-                    };
-                    resolved = found;
-                }
-            });
-            resolved.unwrap_or(mir)
-        } else {
-            mir
+    let target_mir = match dispatch {
+        Dispatch::Exact => mir,
+        Dispatch::Virtual(ic) if has_this && !mir.method.is_final() => {
+            resolve_receiver(mir, &args[0], ic, false)
         }
-    } else {
-        mir
+        Dispatch::Interface(ic) if has_this => resolve_receiver(mir, &args[0], ic, true),
+        _ => mir,
     };
 
-    // 创建 JavaCall
-    let mut jc = JavaCall::new_with_args(target_mir, args);
-    jc.is_interface = is_interface;
+    if let Some(result) = target_mir.intrinsic.and_then(|i| i.invoke(&args)) {
+        return match result {
+            Ok(Some(v)) => encode_value(&v),
+            Ok(None) => 0,
+            Err(ex) => {
+                thread::current_java_thread().write().unwrap().set_ex(ex);
+                0
+            }
+        };
+    }
 
-    // 同步
-    sync_enter(&jc.mir, &jc.args);
+    // 目标方法已解析完毕，强制不再解析
+    let mut jc = JavaCall::new_with_args(target_mir, args);
+    jc.is_interface = matches!(dispatch, Dispatch::Interface(_));
+    let is_return_void = jc.is_return_void;
+    let retype = jc.mir.method.signature.retype.clone();
 
-    let jt = runtime::thread::current_java_thread();
+    let area = DataArea::new(2);
+    jc.invoke(Some(&area), true);
 
-    // 尝试 JIT 路径
-    if jc.try_jit_invoke(None) {
-        sync_exit(&jc.mir, &jc.args);
-        let _ = jt.write().unwrap().frames.pop();
-        return;
+    if is_return_void || thread::is_meet_ex() {
+        return 0;
     }
+    let v = {
+        let mut stk = area.stack.borrow_mut();
+        let v = stk.pop_ref();
+        if matches!(retype, SignatureType::Long | SignatureType::Double) {
+            stk.drop_top();
+        }
+        v
+    };
+    encode_value(&v)
+}
 
-    // 回退到解释器
-    let frame_len = jt.read().unwrap().frames.len();
-    if frame_len >= runtime::consts::THREAD_MAX_STACK_FRAMES {
-        exception::meet_ex(cls_const::J_SOE, None);
-        sync_exit(&jc.mir, &jc.args);
-        let _ = jt.write().unwrap().frames.pop();
-        return;
-    }
+/// 按接收者的实际类解析目标方法。
+///
+/// 有内联缓存时先查缓存，miss 后把解析结果记录进去。
+fn resolve_receiver(
+    mir: MethodIdRef,
+    this: &Oop,
+    ic: Option<&InlineCache>,
+    is_interface: bool,
+) -> MethodIdRef {
+    let sid = match this {
+        Oop::Ref(slot_id) => *slot_id,
+        _ => return mir,
+    };
+    let receiver = oop::with_heap(|heap| {
+        let desc = heap.get(sid);
+        let guard = desc.read().unwrap();
+        match &guard.v {
+            oop::RefKind::Inst(inst) => Some(inst.class.clone()),
+            _ => None,
+        }
+    });
+    let receiver = match receiver {
+        Some(receiver) => receiver,
+        None => return mir,
+    };
 
-    let frame_id = frame_len + 1;
-    let frame = Frame::new(jc.mir.clone(), frame_id);
-    let frame_ref = Arc::new(std::sync::RwLock::new(Box::new(frame)));
-
-    jt.write().unwrap().frames.push(frame_ref.clone());
-
-    // Build locals
-    let max_locals = jc.mir.method.get_max_locals();
-    let mut local = runtime::local::Local::new(max_locals);
-    let mut slot_pos: usize = 0;
-    for v in jc.args.iter() {
-        let step = match v {
-            Oop::Int(v) => {
-                local.set_int(slot_pos, *v);
-                1
-            }
-            Oop::Float(v) => {
-                local.set_float(slot_pos, *v);
-                1
-            }
-            Oop::Double(v) => {
-                local.set_double(slot_pos, *v);
-                2
-            }
-            Oop::Long(v) => {
-                local.set_long(slot_pos, *v);
-                2
-            }
-            _ => {
-                local.set_ref(slot_pos, v.clone());
-                1
-            }
-        };
-        slot_pos += step;
+    if let Some(target) = ic.and_then(|ic| ic.lookup(&receiver)) {
+        return target;
     }
 
-    let frame_h = frame_ref.try_read().unwrap();
-    let mut interp = runtime::Interp::new(frame_h, local);
-    interp.run();
-
-    // 写入返回值到调用方的栈
-    if !jc.is_return_void && !thread::is_meet_ex() {
-        let return_v = {
-            let f = frame_ref.try_read().unwrap();
-            let borrowed = f.area.return_v.borrow();
-            let result = borrowed.clone();
-            drop(borrowed);
-            drop(f);
-            result
-        };
-        if let Some(rv) = return_v {
-            // 通过当前线程栈顶之前的 frame（即调用方）写入
-            if let Some(top_frame) = {
-                let frames = &jt.read().unwrap().frames;
-                if frames.len() >= 2 {
-                    Some(frames[frames.len() - 2].clone())
-                } else {
-                    None
-                }
-            } {
-                let stk = &top_frame.try_read().unwrap().area.stack;
-                match &jc.mir.method.signature.retype {
-                    classfile::SignatureType::Byte
-                    | classfile::SignatureType::Boolean
-                    | classfile::SignatureType::Int
-                    | classfile::SignatureType::Char
-                    | classfile::SignatureType::Short => {
-                        stk.borrow_mut().push_int(rv.extract_int())
-                    }
-                    classfile::SignatureType::Long => stk.borrow_mut().push_long(rv.extract_long()),
-                    classfile::SignatureType::Float => {
-                        stk.borrow_mut().push_float(rv.extract_float())
-                    }
-                    classfile::SignatureType::Double => {
-                        stk.borrow_mut().push_double(rv.extract_double())
-                    }
-                    _ => stk.borrow_mut().push_ref(rv, false),
-                }
-            }
+    let found = {
+        let cls = receiver.get_class();
+        if is_interface {
+            cls.get_interface_method(&mir.method.name, &mir.method.desc)
+                .ok()
+        } else {
+            cls.get_virtual_method(&mir.method.name, &mir.method.desc)
+                .ok()
         }
-    }
-
-    let _ = jt.write().unwrap().frames.pop();
-    sync_exit(&jc.mir, &jc.args);
-}
+    };
 
-/// 从 JIT i32 栈读取一个值。
-fn read_jit_stack_value(
-    stack: *mut i32,
-    pos: usize,
-    sig_type: &classfile::SignatureType,
-) -> (Oop, usize) {
-    match sig_type {
-        classfile::SignatureType::Byte
-        | classfile::SignatureType::Boolean
-        | classfile::SignatureType::Int
-        | classfile::SignatureType::Char
-        | classfile::SignatureType::Short => {
-            let val = unsafe { *stack.add(pos - 1) };
-            (Oop::Int(val), 1)
-        }
-        classfile::SignatureType::Long => {
-            let lo = unsafe { *stack.add(pos - 2) } as i64;
-            let hi = unsafe { *stack.add(pos - 1) } as i64;
-            let combined = lo | (hi << 32);
-            (Oop::Long(combined), 2)
-        }
-        classfile::SignatureType::Float => {
-            let val = unsafe { *stack.add(pos - 1) };
-            (Oop::Float(f32::from_bits(val as u32)), 1)
-        }
-        classfile::SignatureType::Double => {
-            let lo = unsafe { *stack.add(pos - 2) } as u64;
-            let hi = unsafe { *stack.add(pos - 1) } as u64;
-            let bits = lo | (hi << 32);
-            (Oop::Double(f64::from_bits(bits)), 2)
-        }
-        classfile::SignatureType::Object(_, _, _) | classfile::SignatureType::Array(_) => {
-            let slot_id = unsafe { *stack.add(pos - 1) } as u32;
-            (Oop::Ref(slot_id), 1)
-        }
-        t => {
-            warn!("jit invoke: unsupported arg type {:?}", t);
-            (Oop::Int(0), 1)
+    match found {
+        Some(target) => {
+            if let Some(ic) = ic {
+                ic.record(receiver, target.clone());
+            }
+            target
         }
+        None => mir,
     }
 }
 
-fn sync_enter(mir: &crate::types::MethodIdRef, args: &[Oop]) {
-    if mir.method.is_synchronized() {
-        if mir.method.is_static() {
-            let class = mir.method.class.get_class();
-            class.monitor_enter();
-        } else if let Some(Oop::Ref(slot_id)) = args.first() {
-            let sid = *slot_id;
-            oop::with_heap(|heap| {
-                let desc = heap.get(sid);
-                let guard = desc.read().unwrap();
-                guard.monitor_enter();
-            });
+/// 按 `JitFn` 与运行时函数的约定把一个值编码为 i64：
+/// int 与引用（slot id，null 为 0）符号扩展，float 取位模式，
+/// long 原样，double 取位模式。
+pub fn encode_value(v: &Oop) -> i64 {
+    match v {
+        Oop::Int(v) => *v as i64,
+        Oop::Long(v) => *v,
+        Oop::Float(v) => v.to_bits() as i64,
+        Oop::Double(v) => v.to_bits() as i64,
+        Oop::Ref(slot_id) => *slot_id as i32 as i64,
+        Oop::Null => 0,
+        _ => {
+            warn!("JIT: unsupported value {:?}", v);
+            0
         }
     }
 }
 
-fn sync_exit(mir: &crate::types::MethodIdRef, args: &[Oop]) {
-    if mir.method.is_synchronized() {
-        if mir.method.is_static() {
-            let class = mir.method.class.get_class();
-            class.monitor_exit();
-        } else if let Some(Oop::Ref(slot_id)) = args.first() {
-            let sid = *slot_id;
-            oop::with_heap(|heap| {
-                let desc = heap.get(sid);
-                let guard = desc.read().unwrap();
-                guard.monitor_exit();
-            });
-        }
+/// `encode_value` 的逆操作，按类型解码；void 等不支持的类型返回 None。
+pub fn decode_value(t: &SignatureType, v: i64) -> Option<Oop> {
+    match t {
+        SignatureType::Byte
+        | SignatureType::Boolean
+        | SignatureType::Int
+        | SignatureType::Char
+        | SignatureType::Short => Some(Oop::Int(v as i32)),
+        SignatureType::Long => Some(Oop::Long(v)),
+        SignatureType::Float => Some(Oop::Float(f32::from_bits(v as u32))),
+        SignatureType::Double => Some(Oop::Double(f64::from_bits(v as u64))),
+        SignatureType::Object(_, _, _) | SignatureType::Array(_) => match v as u32 {
+            0 => Some(Oop::Null),
+            slot_id => Some(Oop::Ref(slot_id)),
+        },
+        _ => None,
     }
 }
@@ -1,5 +1,8 @@
 use crate::native::JNINativeMethod;
 use crate::oop::{self, ValueType};
+use crate::runtime::intrinsics::{self, Intrinsic};
+use crate::runtime::jit::deopt::DeoptInfo;
+use crate::runtime::jit::opt::JitLevel;
 use crate::runtime::local::Local;
 use crate::runtime::stack::Stack;
 use crate::runtime::{self, require_class2};
@@ -15,20 +18,29 @@ use classfile::{
 use std::fmt;
 use std::fmt::Formatter;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tracing::{debug, error, info, trace, warn};
 
-/// JIT 编译后的函数指针类型。
+/// JIT 编译后方法的入口函数指针类型。
 ///
-/// 签名: `fn(locals: *mut i32, stack: *mut i32)`
-/// - `locals`: 指向 i32 数组的指针（大小 = max_locals）
-/// - `stack`: 指向 i32 数组的指针（大小 = max_stack）
-/// JIT 代码直接读写 i32 值，调用方负责在前后拷贝参数/提取结果。
-pub type JitFn = extern "C" fn(*mut i32, *mut i32);
+/// 签名: `fn(args: *const i64) -> i64`
+/// - `args`: 每个 Java 参数（含 this）占一个 i64：int 与引用（堆 slot id，
+///   null 为 0）符号扩展，long 原样，float/double 取位模式
+/// - 返回值按同样的约定编码，void 方法返回 0
+///
+/// 入口只负责解包参数后调用按 Java 签名生成的类型化方法体，
+/// locals 和操作数栈都在方法体自己的栈帧里。
+pub type JitFn = extern "C" fn(*const i64) -> i64;
 
 /// JIT 编译后的方法封装。
 pub struct JITCompiledMethod {
     pub fn_ptr: JitFn,
+    /// 安全点的去优化元数据及本次编译的有效标志。
+    pub deopt: DeoptInfo,
+    /// 编译编号与级别，用于编译日志。
+    pub compile_id: usize,
+    pub level: JitLevel,
 }
 
 pub fn get_method_ref(cp: &ConstantPool, idx: usize) -> Result<MethodIdRef, ()> {
@@ -58,7 +70,14 @@ pub fn get_method_ref(cp: &ConstantPool, idx: usize) -> Result<MethodIdRef, ()>
     );
     if tag == consts::CONSTANT_METHOD_REF_TAG {
         // invokespecial, invokestatic and invokevirtual
-        class.get_class_method(name, desc)
+        class.get_class_method(name, desc).or_else(|_| {
+            // VarHandle 的访问方法是签名多态的，按调用点的描述符链接
+            if class.name.as_slice() == b"java/lang/invoke/VarHandle" {
+                native::java_lang_invoke_VarHandle::link(name, desc).ok_or(())
+            } else {
+                Err(())
+            }
+        })
     } else {
         // invokeinterface
         class.get_interface_method(name, desc)
@@ -71,6 +90,11 @@ pub struct MethodId {
     pub native_impl: Option<JNINativeMethod>,
     /// JIT 编译后的函数。用 Mutex 保护，允许首次调用时缓存编译结果。
     pub jit_impl: Mutex<Option<Arc<JITCompiledMethod>>>,
+    /// JIT 拒绝编译过该方法（不支持的字节码、带异常表等）。
+    /// 拒绝只取决于字节码，记下来避免每次调用都重新编译。
+    pub not_compilable: AtomicBool,
+    /// 方法在 intrinsic 表中时，调用直接走 Rust 实现。
+    pub intrinsic: Option<Intrinsic>,
 }
 
 impl MethodId {
@@ -81,6 +105,8 @@ impl MethodId {
             method: self.method.clone(),
             native_impl: self.native_impl.clone(),
             jit_impl: Mutex::new(jit_impl.clone()),
+            not_compilable: AtomicBool::new(self.not_compilable.load(Ordering::Relaxed)),
+            intrinsic: self.intrinsic,
         }
     }
 }
@@ -102,11 +128,19 @@ impl MethodId {
             None
         };
 
+        let intrinsic = intrinsics::find(
+            method.cls_name.as_slice(),
+            method.name.as_slice(),
+            method.desc.as_slice(),
+        );
+
         Arc::new(Self {
             offset,
             method,
             native_impl,
             jit_impl: Mutex::new(None),
+            not_compilable: AtomicBool::new(false),
+            intrinsic,
         })
     }
 }
@@ -0,0 +1,752 @@
+//! 模块系统：读取模块描述符，解析 `--module-path` 与 `-m`，维护模块与包的
+//! 运行时表，并在链接时检查可读性与导出（JVMS 5.4.4）。
+//!
+//! 系统模块由 `System.initPhase2` 在 Java 中定义，模块路径上的应用模块由
+//! VM 随后定义，两者都经 `java.lang.Module` 的 native 方法登记到这里。
+//! VM 只有一个类加载器，包按包名全局登记，不在任何模块中的包属于引导类
+//! 加载器的未命名模块。
+
+use crate::oop::{Class, Oop};
+use crate::runtime::{class_path_manager, exception, require_class3};
+use crate::types::ClassRef;
+use crate::util;
+use classfile::{constant_pool, consts as cls_consts, flags as acc};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use tracing::{debug, info};
+use zip::ZipArchive;
+
+/// 模块声明，包名与类名都是内部形式（`a/b/C`）。
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ModuleDescriptor {
+    pub name: String,
+    pub open: bool,
+    /// 模块路径上没有 `module-info.class` 的 jar
+    pub automatic: bool,
+    pub version: Option<String>,
+    /// 依赖的模块及其 `ACC_TRANSITIVE`、`ACC_STATIC_PHASE` 等标志
+    pub requires: Vec<(String, u16)>,
+    /// 导出的包及限定的目标模块，没有目标表示导出给所有模块
+    pub exports: Vec<(String, Vec<String>)>,
+    pub opens: Vec<(String, Vec<String>)>,
+    pub uses: Vec<String>,
+    /// 服务接口及其实现类
+    pub provides: Vec<(String, Vec<String>)>,
+    pub packages: Vec<String>,
+    pub main_class: Option<String>,
+}
+
+impl ModuleDescriptor {
+    /// 解析 `module-info.class` 的 Module、ModulePackages 与 ModuleMainClass 属性。
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let cf = class_parser::parse_class(bytes).map_err(|e| format!("{:?}", e))?;
+        let module = cf
+            .module()
+            .ok_or_else(|| "no Module attribute".to_string())?;
+        let cp = &cf.cp;
+        let text = |bs: &[u8]| String::from_utf8_lossy(bs).to_string();
+        let module_name = |idx: u16| text(constant_pool::get_module_name(cp, idx as usize));
+        let package = |idx: u16| text(constant_pool::get_package_name(cp, idx as usize));
+        let class = |idx: usize| text(constant_pool::get_class_name(cp, idx));
+        let entries = |v: &Vec<classfile::attributes::ModuleExports>| {
+            v.iter()
+                .map(|e| {
+                    let to = e.to.iter().map(|&i| module_name(i)).collect();
+                    (package(e.package_index), to)
+                })
+                .collect()
+        };
+
+        let mut d = Self {
+            name: module_name(module.name_index),
+            open: module.flags & acc::ACC_OPEN != 0,
+            automatic: false,
+            version: (module.version_index != 0)
+                .then(|| text(constant_pool::get_utf8(cp, module.version_index as usize))),
+            requires: module
+                .requires
+                .iter()
+                .map(|r| (module_name(r.requires_index), r.requires_flags))
+                .collect(),
+            exports: entries(&module.exports),
+            opens: entries(&module.opens),
+            uses: module.uses.iter().map(|&i| class(i as usize)).collect(),
+            provides: module
+                .provides
+                .iter()
+                .map(|p| {
+                    let with = p.with.iter().map(|&i| class(i as usize)).collect();
+                    (class(p.provides_index as usize), with)
+                })
+                .collect(),
+            packages: vec![],
+            main_class: cf.module_main_class().map(class),
+        };
+
+        // javac 不生成 ModulePackages，导出、开放的包与主类所在的包总是属于模块
+        let mut packages: Vec<String> = cf
+            .module_packages()
+            .map(|v| v.iter().map(|&i| package(i)).collect())
+            .unwrap_or_default();
+        packages.extend(d.exports.iter().map(|(p, _)| p.clone()));
+        packages.extend(d.opens.iter().map(|(p, _)| p.clone()));
+        packages.extend(d.main_class.as_deref().map(|c| package_of(c).to_string()));
+        for pkg in packages {
+            d.add_package(pkg);
+        }
+        Ok(d)
+    }
+
+    fn automatic(name: String) -> Self {
+        Self {
+            name,
+            automatic: true,
+            ..Self::default()
+        }
+    }
+
+    fn add_package(&mut self, pkg: String) {
+        if !pkg.is_empty() && !self.packages.contains(&pkg) {
+            self.packages.push(pkg);
+        }
+    }
+}
+
+/// 模块路径上的模块。
+#[derive(Debug, Clone)]
+pub struct AppModule {
+    pub descriptor: ModuleDescriptor,
+    /// 展开的模块目录或 jar
+    pub location: String,
+}
+
+/// `-m` 解析出的应用模块。
+static APP_MODULES: OnceLock<Vec<AppModule>> = OnceLock::new();
+
+/// 解析 `-m` 的根模块：在系统模块与模块路径中查找它及其依赖（与 `java`
+/// 一样系统模块优先），把应用模块加入类路径，返回根模块的主类（`a.b.C`）。
+pub fn resolve(module_path: &str, root: &str) -> Result<Option<String>, String> {
+    let system: FxHashSet<String> = class_path_manager::system_module_names()
+        .into_iter()
+        .collect();
+    if system.is_empty() {
+        return Err("-m needs a JDK 9+ runtime image with modules".to_string());
+    }
+
+    let mut candidates = scan_module_path(module_path)?;
+    let mut resolved: Vec<AppModule> = vec![];
+    let mut seen = FxHashSet::default();
+    let mut queue = VecDeque::from([(root.to_string(), None::<String>)]);
+    while let Some((name, required_by)) = queue.pop_front() {
+        if !seen.insert(name.clone()) || system.contains(&name) {
+            continue;
+        }
+        let i = candidates
+            .iter()
+            .position(|m| m.descriptor.name == name)
+            .ok_or_else(|| match &required_by {
+                Some(by) => format!(
+                    "java.lang.module.FindException: Module {} not found, required by {}",
+                    name, by
+                ),
+                None => format!("java.lang.module.FindException: Module {} not found", name),
+            })?;
+        let m = candidates.remove(i);
+        for (dep, flags) in &m.descriptor.requires {
+            // `requires static` 只在编译时需要
+            if flags & acc::ACC_STATIC_PHASE == 0 {
+                queue.push_back((dep.clone(), Some(name.clone())));
+            }
+        }
+        resolved.push(m);
+    }
+
+    // 自动模块读取所有模块，解析到一个时其余的自动模块一并解析
+    if resolved.iter().any(|m| m.descriptor.automatic) {
+        resolved.extend(candidates.into_iter().filter(|m| m.descriptor.automatic));
+    }
+
+    let main_class = match resolved.iter().find(|m| m.descriptor.name == root) {
+        Some(m) => m.descriptor.main_class.clone(),
+        None => system_descriptor(root).and_then(|d| d.main_class),
+    };
+
+    for m in &resolved {
+        class_path_manager::add_module_path(&m.descriptor.name, &m.location).map_err(|e| {
+            format!(
+                "java.lang.module.FindException: Error reading module: {}: {}",
+                m.location, e
+            )
+        })?;
+        info!("module {} from {}", m.descriptor.name, m.location);
+    }
+    let _ = APP_MODULES.set(resolved);
+
+    Ok(main_class.map(|c| c.replace('/', ".")))
+}
+
+/// `-m` 解析出的应用模块，没有 `-m` 时为空。
+pub fn app_modules() -> &'static [AppModule] {
+    APP_MODULES.get().map(|v| v.as_slice()).unwrap_or(&[])
+}
+
+/// 模块读取的模块：直接依赖与 java.base，加上它们 `requires transitive`
+/// 传递的模块。
+pub fn reads_of(d: &ModuleDescriptor) -> Vec<String> {
+    let mut reads: Vec<String> = vec![];
+    let mut pending: Vec<String> = d.requires.iter().map(|(n, _)| n.clone()).collect();
+    pending.push("java.base".to_string());
+    while let Some(name) = pending.pop() {
+        if name == d.name || reads.contains(&name) {
+            continue;
+        }
+        let dep = app_modules()
+            .iter()
+            .find(|m| m.descriptor.name == name)
+            .map(|m| m.descriptor.clone())
+            .or_else(|| system_descriptor(&name));
+        if let Some(dep) = dep {
+            pending.extend(
+                dep.requires
+                    .iter()
+                    .filter(|(_, flags)| flags & acc::ACC_TRANSITIVE != 0)
+                    .map(|(n, _)| n.clone()),
+            );
+        }
+        reads.push(name);
+    }
+    reads
+}
+
+fn system_descriptor(name: &str) -> Option<ModuleDescriptor> {
+    class_path_manager::find_system_module_info(name)
+        .and_then(|bytes| ModuleDescriptor::parse(&bytes).ok())
+}
+
+/// 扫描模块路径：每一项可以是展开的模块目录、模块化或普通的 jar，或者
+/// 包含它们的目录。同名模块以先出现的为准，不存在的路径被忽略。
+fn scan_module_path(paths: &str) -> Result<Vec<AppModule>, String> {
+    let mut found: Vec<AppModule> = vec![];
+    for entry in paths.split(util::PATH_SEP).filter(|p| !p.is_empty()) {
+        let path = Path::new(entry);
+        let mut modules = vec![];
+        if path.join("module-info.class").is_file() {
+            modules.push(read_exploded(path)?);
+        } else if path.is_dir() {
+            let mut children: Vec<_> = std::fs::read_dir(path)
+                .map_err(|e| read_error(path, e))?
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .collect();
+            children.sort();
+            for child in children {
+                if child.join("module-info.class").is_file() {
+                    modules.push(read_exploded(&child)?);
+                } else if child.is_file() && child.extension().is_some_and(|e| e == "jar") {
+                    modules.push(read_jar(&child)?);
+                }
+            }
+        } else if path.is_file() {
+            modules.push(read_jar(path)?);
+        }
+
+        for m in modules {
+            if !found.iter().any(|f| f.descriptor.name == m.descriptor.name) {
+                found.push(m);
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn read_error(path: &Path, e: impl std::fmt::Display) -> String {
+    format!(
+        "java.lang.module.FindException: Error reading module: {}: {}",
+        path.display(),
+        e
+    )
+}
+
+fn read_exploded(dir: &Path) -> Result<AppModule, String> {
+    let bytes = std::fs::read(dir.join("module-info.class")).map_err(|e| read_error(dir, e))?;
+    let mut descriptor = ModuleDescriptor::parse(&bytes).map_err(|e| read_error(dir, e))?;
+    let mut packages = vec![];
+    class_packages(dir, "", &mut packages);
+    for pkg in packages {
+        descriptor.add_package(pkg);
+    }
+    Ok(AppModule {
+        descriptor,
+        location: dir.to_string_lossy().to_string(),
+    })
+}
+
+/// 目录下含 `.class` 文件的包。
+fn class_packages(dir: &Path, pkg: &str, packages: &mut Vec<String>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            let sub = if pkg.is_empty() {
+                name
+            } else {
+                format!("{}/{}", pkg, name)
+            };
+            class_packages(&path, &sub, packages);
+        } else if name.ends_with(".class") && !pkg.is_empty() && !packages.iter().any(|p| p == pkg)
+        {
+            packages.push(pkg.to_string());
+        }
+    }
+}
+
+/// 模块化 jar 读取其 `module-info.class`，普通 jar 作为自动模块，模块名取自
+/// manifest 的 `Automatic-Module-Name` 或文件名。描述符没有主类时使用
+/// manifest 的 `Main-Class`。
+fn read_jar(jar: &Path) -> Result<AppModule, String> {
+    let location = jar.to_string_lossy().to_string();
+    let f = File::open(jar).map_err(|e| read_error(jar, e))?;
+    let mut z = ZipArchive::new(f).map_err(|e| read_error(jar, e))?;
+    let manifest = class_path_manager::read_manifest(&location).map_err(|e| read_error(jar, e))?;
+
+    let mut descriptor = match z.by_name("module-info.class") {
+        Ok(mut zf) => {
+            let mut bytes = Vec::with_capacity(zf.size() as usize);
+            zf.read_to_end(&mut bytes).map_err(|e| read_error(jar, e))?;
+            ModuleDescriptor::parse(&bytes).map_err(|e| read_error(jar, e))?
+        }
+        Err(_) => {
+            let file_name = jar.file_name().unwrap_or_default().to_string_lossy();
+            let name = manifest
+                .automatic_module_name
+                .clone()
+                .or_else(|| automatic_module_name(&file_name))
+                .ok_or_else(|| {
+                    format!(
+                        "java.lang.module.FindException: Unable to derive module descriptor for {}",
+                        location
+                    )
+                })?;
+            ModuleDescriptor::automatic(name)
+        }
+    };
+
+    let classes: Vec<String> = z
+        .file_names()
+        .filter(|n| n.ends_with(".class") && !n.starts_with("META-INF/"))
+        .map(|n| n.to_string())
+        .collect();
+    for class in classes {
+        match class.rsplit_once('/') {
+            Some((pkg, _)) => descriptor.add_package(pkg.to_string()),
+            // module-info.class 本身在顶层
+            None if class == "module-info.class" => (),
+            None => {
+                return Err(format!(
+                    "java.lang.module.FindException: Unable to derive module descriptor for {}\n\
+                     Caused by: java.lang.module.InvalidModuleDescriptorException: \
+                     {} found in top-level directory (unnamed package not allowed in module)",
+                    location, class
+                ))
+            }
+        }
+    }
+    if descriptor.main_class.is_none() {
+        if let Some(main) = manifest.main_class {
+            let main = main.replace('.', "/");
+            descriptor.add_package(package_of(&main).to_string());
+            descriptor.main_class = Some(main);
+        }
+    }
+
+    Ok(AppModule {
+        descriptor,
+        location,
+    })
+}
+
+/// 由 jar 文件名推导自动模块名，规则同 `ModuleFinder.of`：去掉 `.jar` 与
+/// `-数字` 开始的版本号，非字母数字的字符换成 `.`，合并重复的 `.` 并去掉
+/// 首尾的 `.`。
+fn automatic_module_name(file_name: &str) -> Option<String> {
+    let stem = file_name.strip_suffix(".jar")?;
+    let bytes = stem.as_bytes();
+    let is_version = |i: usize| {
+        let digits = bytes[i + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        digits > 0 && matches!(bytes.get(i + 1 + digits), None | Some(b'.'))
+    };
+    let end = (0..bytes.len())
+        .find(|&i| bytes[i] == b'-' && is_version(i))
+        .unwrap_or(bytes.len());
+
+    let name: String = stem[..end]
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '.' })
+        .collect();
+    let name = name
+        .split('.')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(".");
+    (!name.is_empty()).then_some(name)
+}
+
+/// 包名，默认包为空串。
+fn package_of(class_name: &str) -> &str {
+    class_name.rsplit_once('/').map_or("", |(pkg, _)| pkg)
+}
+
+/// 数组取元素类型，基本类型及其数组返回 `None`。
+fn element_name(class_name: &[u8]) -> Option<&str> {
+    let name = std::str::from_utf8(class_name).ok()?;
+    let elm = name.trim_start_matches('[');
+    if elm.len() == name.len() {
+        Some(name)
+    } else {
+        elm.strip_prefix('L')?.strip_suffix(';')
+    }
+}
+
+struct ModuleEntry {
+    module: Oop,
+    /// 开放模块与自动模块在运行时导出所有包
+    open: bool,
+    /// 自动模块读取所有模块
+    automatic: bool,
+    reads: FxHashSet<String>,
+    reads_all_unnamed: bool,
+}
+
+struct PackageEntry {
+    module: String,
+    exported_to_all: bool,
+    exported_to_all_unnamed: bool,
+    exported_to: FxHashSet<String>,
+}
+
+#[derive(Default)]
+struct ModuleTable {
+    modules: FxHashMap<String, ModuleEntry>,
+    /// 命名模块的包，key 为 `java/lang` 形式
+    packages: FxHashMap<String, PackageEntry>,
+    java_base: Option<Oop>,
+    /// 引导类加载器的未命名模块
+    unnamed: Option<Oop>,
+}
+
+static MODULES: OnceLock<RwLock<ModuleTable>> = OnceLock::new();
+
+/// 引导层定义完成后才检查访问，此前 java.base 之外的模块还不完整。
+static CHECK_ACCESS: AtomicBool = AtomicBool::new(false);
+
+pub fn init() {
+    MODULES.get_or_init(|| RwLock::new(ModuleTable::default()));
+}
+
+/// `Module.name`，未命名模块为 `None`。
+pub fn module_name(module: &Oop) -> Option<String> {
+    static NAME_OFFSET: OnceLock<usize> = OnceLock::new();
+    let offset = *NAME_OFFSET.get_or_init(|| {
+        let cls = require_class3(None, b"java/lang/Module").unwrap();
+        let cls = cls.get_class();
+        cls.get_field_id(
+            util::S_NAME.get().unwrap(),
+            util::S_JAVA_LANG_STRING.get().unwrap(),
+            false,
+        )
+        .offset
+    });
+    match Class::get_field_value2(module.extract_ref(), offset) {
+        Oop::Null => None,
+        name => Some(Oop::java_lang_string(name.extract_ref())),
+    }
+}
+
+/// `Module.defineModule0`：登记命名模块及其包（`a.b` 或 `a/b` 形式）。
+/// 模块路径上的模块补上 VM 扫描到的包，自动模块读取所有模块。
+pub fn define_module(module: Oop, name: &str, open: bool, packages: Vec<String>) {
+    let app = app_modules().iter().find(|m| m.descriptor.name == name);
+    let automatic = app.is_some_and(|m| m.descriptor.automatic);
+    let mut table = MODULES.get().unwrap().write().unwrap();
+    let all = packages
+        .iter()
+        .map(|p| p.replace('.', "/"))
+        .chain(app.into_iter().flat_map(|m| m.descriptor.packages.clone()));
+    for pkg in all {
+        table.packages.insert(
+            pkg,
+            PackageEntry {
+                module: name.to_string(),
+                exported_to_all: false,
+                exported_to_all_unnamed: false,
+                exported_to: FxHashSet::default(),
+            },
+        );
+    }
+    table.modules.insert(
+        name.to_string(),
+        ModuleEntry {
+            module: module.clone(),
+            open: open || automatic,
+            automatic,
+            reads: FxHashSet::default(),
+            reads_all_unnamed: automatic,
+        },
+    );
+    if name == "java.base" {
+        table.java_base = Some(module);
+    }
+    debug!("module defined: {}", name);
+}
+
+/// 已定义的命名模块。
+pub fn find_module(name: &str) -> Option<Oop> {
+    let table = MODULES.get().unwrap().read().unwrap();
+    table.modules.get(name).map(|m| m.module.clone())
+}
+
+/// `BootLoader.setBootLoaderUnnamedModule0`
+pub fn set_boot_unnamed_module(module: Oop) {
+    let mut table = MODULES.get().unwrap().write().unwrap();
+    table.unnamed = Some(module);
+}
+
+pub fn boot_unnamed_module() -> Option<Oop> {
+    let table = MODULES.get().unwrap().read().unwrap();
+    table.unnamed.clone()
+}
+
+/// `Module.addReads0`，`to` 为 `None` 表示读取所有未命名模块。
+pub fn add_reads(from: &Oop, to: Option<&Oop>) {
+    let from = match module_name(from) {
+        Some(name) => name,
+        // 未命名模块读取所有模块
+        None => return,
+    };
+    let to = to.and_then(module_name);
+    let mut table = MODULES.get().unwrap().write().unwrap();
+    if let Some(m) = table.modules.get_mut(&from) {
+        match to {
+            Some(to) => {
+                m.reads.insert(to);
+            }
+            None => m.reads_all_unnamed = true,
+        }
+    }
+}
+
+/// 包导出的对象。
+pub enum ExportTarget<'a> {
+    All,
+    AllUnnamed,
+    Module(&'a Oop),
+}
+
+/// `Module.addExports0`、`addExportsToAll0` 与 `addExportsToAllUnnamed0`，
+/// 包按包名登记，不需要导出方的模块。
+pub fn add_exports(pkg: &str, to: ExportTarget) {
+    // 导出给某个未命名模块即导出给所有未命名模块，VM 只有一个类加载器
+    let target = match &to {
+        ExportTarget::Module(m) => module_name(m),
+        _ => None,
+    };
+    let mut table = MODULES.get().unwrap().write().unwrap();
+    if let Some(p) = table.packages.get_mut(&pkg.replace('.', "/")) {
+        match (to, target) {
+            (ExportTarget::All, _) => p.exported_to_all = true,
+            (_, Some(name)) => {
+                p.exported_to.insert(name);
+            }
+            _ => p.exported_to_all_unnamed = true,
+        }
+    }
+}
+
+/// 包（`a/b` 形式）所在的命名模块及该模块是否开放，未定义时为 `None`。
+pub fn package_module(package: &str) -> Option<(String, bool)> {
+    let table = MODULES.get()?.read().unwrap();
+    let p = table.packages.get(package)?;
+    let open = table.modules.get(&p.module).is_some_and(|m| m.open);
+    Some((p.module.clone(), open))
+}
+
+/// 引导层（含应用模块）定义完成，开始检查模块访问。
+pub fn start_access_checks() {
+    CHECK_ACCESS.store(true, Ordering::Relaxed);
+}
+
+/// 类所在模块的 `java.lang.Module`：数组取元素类型的模块，基本类型属于
+/// java.base，不在命名模块中的类属于未命名模块。模块还没定义时为 `None`。
+pub fn module_of(class_name: &[u8]) -> Option<Oop> {
+    let table = MODULES.get().unwrap().read().unwrap();
+    match element_name(class_name) {
+        None => table.java_base.clone(),
+        Some(name) => match table.packages.get(package_of(name)) {
+            Some(p) => table.modules.get(&p.module).map(|m| m.module.clone()),
+            None => table.unnamed.clone(),
+        },
+    }
+}
+
+/// 链接时检查 `from` 类能否访问 `to` 类：`to` 是 public 时还要求 `from` 的
+/// 模块读取 `to` 的模块，且 `to` 的包导出给 `from` 的模块。不能访问时抛出
+/// `IllegalAccessError` 并返回 false。
+pub fn check_access(from: &[u8], to: &ClassRef) -> bool {
+    if !CHECK_ACCESS.load(Ordering::Relaxed) {
+        return true;
+    }
+
+    let to_name = to.get_class().name.clone();
+    let to_name = match element_name(to_name.as_slice()) {
+        Some(name) => name.to_string(),
+        None => return true,
+    };
+    let from = match std::str::from_utf8(from) {
+        Ok(from) => from,
+        Err(_) => return true,
+    };
+    let (from_pkg, to_pkg) = (package_of(from), package_of(&to_name));
+    if from_pkg == to_pkg {
+        return true;
+    }
+
+    // 非 public 的类只能在包内访问，由访问标志的检查负责
+    let is_public = if to_name.len() == to.get_class().name.len() {
+        to.get_class().is_public()
+    } else {
+        require_class3(None, to_name.as_bytes()).is_none_or(|c| c.get_class().is_public())
+    };
+    if !is_public {
+        return true;
+    }
+
+    let table = MODULES.get().unwrap().read().unwrap();
+    let from_m = table.packages.get(from_pkg).map(|p| p.module.as_str());
+    let to_m = table.packages.get(to_pkg).map(|p| p.module.as_str());
+    let reason = match table.verify(from_m, to_m, to_pkg) {
+        Ok(()) => return true,
+        Err(reason) => reason,
+    };
+    let msg = format!(
+        "class {} ({}) cannot access class {} ({}) because {}",
+        from.replace('/', "."),
+        describe(from_m),
+        to_name.replace('/', "."),
+        describe(to_m),
+        reason
+    );
+    drop(table);
+    exception::meet_ex(cls_consts::J_ILLEGAL_ACCESS_ERROR, Some(msg));
+    false
+}
+
+fn describe(module: Option<&str>) -> String {
+    match module {
+        Some(name) => format!("in module {}", name),
+        None => "in unnamed module".to_string(),
+    }
+}
+
+impl ModuleTable {
+    /// 从 `from` 模块访问 `to` 模块中 `to_pkg` 包的 public 类，`None` 为
+    /// 未命名模块。不能访问时返回原因。
+    fn verify(&self, from: Option<&str>, to: Option<&str>, to_pkg: &str) -> Result<(), String> {
+        if from == to {
+            return Ok(());
+        }
+        let from_entry = from.and_then(|name| self.modules.get(name));
+        if from_entry.is_some_and(|m| m.automatic) {
+            return Ok(());
+        }
+
+        let to = match to {
+            Some(to) => to,
+            // 未命名模块导出所有包，命名模块要显式读取它
+            None => {
+                return match from_entry {
+                    Some(m) if !m.reads_all_unnamed => Err(format!(
+                        "module {} does not read unnamed module",
+                        from.unwrap()
+                    )),
+                    _ => Ok(()),
+                };
+            }
+        };
+        if let (Some(name), Some(m)) = (from, from_entry) {
+            if !m.reads.contains(to) {
+                return Err(format!("module {} does not read module {}", name, to));
+            }
+        }
+
+        if self.modules.get(to).is_none_or(|m| m.open) {
+            return Ok(());
+        }
+        let exported = match self.packages.get(to_pkg) {
+            Some(p) => {
+                p.exported_to_all
+                    || match from {
+                        Some(from) => p.exported_to.contains(from),
+                        None => p.exported_to_all_unnamed,
+                    }
+            }
+            None => true,
+        };
+        if exported {
+            Ok(())
+        } else {
+            Err(format!(
+                "module {} does not export {} to {}",
+                to,
+                to_pkg.replace('/', "."),
+                match from {
+                    Some(from) => format!("module {}", from),
+                    None => "unnamed module".to_string(),
+                }
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_automatic_module_name() {
+        let name = |s| automatic_module_name(s);
+        assert_eq!(name("foo.jar").as_deref(), Some("foo"));
+        assert_eq!(name("foo-bar-1.2.3.jar").as_deref(), Some("foo.bar"));
+        assert_eq!(
+            name("commons-lang3-3.12.0.jar").as_deref(),
+            Some("commons.lang3")
+        );
+        assert_eq!(name("foo-2x.jar").as_deref(), Some("foo.2x"));
+        assert_eq!(name("__a__b.jar").as_deref(), Some("a.b"));
+        assert_eq!(name("--.jar"), None);
+        assert_eq!(name("foo.zip"), None);
+    }
+
+    #[test]
+    fn t_element_name() {
+        assert_eq!(element_name(b"java/lang/String"), Some("java/lang/String"));
+        assert_eq!(
+            element_name(b"[[Ljava/lang/String;"),
+            Some("java/lang/String")
+        );
+        assert_eq!(element_name(b"[I"), None);
+        assert_eq!(package_of("java/lang/String"), "java/lang");
+        assert_eq!(package_of("Main"), "");
+    }
+}
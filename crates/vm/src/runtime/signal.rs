@@ -0,0 +1,258 @@
+//! OS signals, dispatched on the "Signal Dispatcher" thread.
+//!
+//! The handler installed with `sigaction` only writes the signal number to a
+//! pipe, which is async-signal-safe. The dispatcher thread reads it and
+//! - SIGQUIT (`kill -3`): prints a thread dump with deadlocks
+//! - signals with a Java handler (`sun.misc.Signal.handle`): calls
+//!   `Signal.dispatch`, which runs the handler on a new Java thread
+
+use crate::new_br;
+use crate::oop::Oop;
+use crate::runtime::thread::{self, safepoint, thread_dump, JavaThread};
+use crate::runtime::{require_class3, vm, JavaCall};
+use crate::util;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Mutex, Once};
+use tracing::{error, warn};
+
+/// `handle0` arguments and results: the default action, ignore, or dispatch
+/// to Java. Any other result is the address of a native handler.
+pub const HANDLER_DEFAULT: i64 = 0;
+pub const HANDLER_IGNORE: i64 = 1;
+pub const HANDLER_JAVA: i64 = 2;
+
+/// Write end of the pipe the handler writes signal numbers to.
+static PIPE_WRITE: AtomicI32 = AtomicI32::new(-1);
+
+static INIT: Once = Once::new();
+
+/// The `Signal` class each Java-handled signal is dispatched to:
+/// `sun/misc/Signal` before JDK 9, `jdk/internal/misc/Signal` after.
+static JAVA_HANDLERS: Mutex<Option<HashMap<i32, &'static [u8]>>> = Mutex::new(None);
+
+const SIGNALS: &[(&str, libc::c_int)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("URG", libc::SIGURG),
+    ("XCPU", libc::SIGXCPU),
+    ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM),
+    ("PROF", libc::SIGPROF),
+    ("WINCH", libc::SIGWINCH),
+    ("IO", libc::SIGIO),
+    ("SYS", libc::SIGSYS),
+];
+
+/// Signals used by the VM or that can't be caught.
+const RESERVED: &[libc::c_int] = &[
+    libc::SIGQUIT,
+    libc::SIGKILL,
+    libc::SIGSTOP,
+    libc::SIGSEGV,
+    libc::SIGBUS,
+    libc::SIGFPE,
+    libc::SIGILL,
+];
+
+/// Start the dispatcher thread and take over SIGQUIT.
+pub fn init() {
+    INIT.call_once(|| {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            error!("signal: pipe failed, signals are not handled");
+            return;
+        }
+        PIPE_WRITE.store(fds[1], Ordering::SeqCst);
+
+        let spawned = std::thread::Builder::new()
+            .name("Signal Dispatcher".to_string())
+            .spawn(move || dispatcher_loop(fds[0]));
+        if spawned.is_err() {
+            error!("signal: can't start the dispatcher thread");
+            return;
+        }
+
+        set_action(libc::SIGQUIT, handler_addr());
+    });
+}
+
+/// Signal number of `name` ("INT", "TERM", ...), or -1.
+pub fn find(name: &str) -> i32 {
+    SIGNALS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, sig)| *sig)
+        .unwrap_or(-1)
+}
+
+/// `Signal.handle0`: install `handler` for `sig` and return the previous
+/// one, or -1 if the signal is reserved. Java-handled signals are dispatched
+/// to `dispatch_class`.
+pub fn install(sig: i32, handler: i64, dispatch_class: &'static [u8]) -> i64 {
+    if sig <= 0 || RESERVED.contains(&sig) {
+        return -1;
+    }
+    init();
+
+    let action = match handler {
+        HANDLER_DEFAULT => libc::SIG_DFL,
+        HANDLER_IGNORE => libc::SIG_IGN,
+        HANDLER_JAVA => handler_addr(),
+        addr => addr as libc::sighandler_t,
+    };
+
+    let mut handlers = JAVA_HANDLERS.lock().unwrap();
+    let handlers = handlers.get_or_insert_with(HashMap::new);
+    let old = match set_action(sig, action) {
+        Some(old) if old == handler_addr() => HANDLER_JAVA,
+        Some(old) => old as i64,
+        None => return -1,
+    };
+    if handler == HANDLER_JAVA {
+        handlers.insert(sig, dispatch_class);
+    } else {
+        handlers.remove(&sig);
+    }
+    old
+}
+
+/// `Signal.raise0`.
+pub fn raise(sig: i32) {
+    unsafe {
+        libc::raise(sig);
+    }
+}
+
+fn set_action(sig: i32, action: libc::sighandler_t) -> Option<libc::sighandler_t> {
+    unsafe {
+        let mut new: libc::sigaction = std::mem::zeroed();
+        let mut old: libc::sigaction = std::mem::zeroed();
+        new.sa_sigaction = action;
+        new.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut new.sa_mask);
+        if libc::sigaction(sig, &new, &mut old) != 0 {
+            warn!("signal: sigaction({}) failed", sig);
+            return None;
+        }
+        Some(old.sa_sigaction)
+    }
+}
+
+fn handler_addr() -> libc::sighandler_t {
+    handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t
+}
+
+extern "C" fn handle_signal(sig: libc::c_int) {
+    let fd = PIPE_WRITE.load(Ordering::Relaxed);
+    let b = sig as u8;
+    unsafe {
+        libc::write(fd, &b as *const u8 as *const libc::c_void, 1);
+    }
+}
+
+fn dispatcher_loop(fd: libc::c_int) {
+    loop {
+        let mut b = 0u8;
+        let n = unsafe { libc::read(fd, &mut b as *mut u8 as *mut libc::c_void, 1) };
+        if n != 1 {
+            if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            error!("signal: dispatcher pipe closed");
+            return;
+        }
+
+        let sig = b as i32;
+        if sig == libc::SIGQUIT {
+            thread_dump::print();
+            continue;
+        }
+
+        let class = JAVA_HANDLERS
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|handlers| handlers.get(&sig).copied());
+        if let Some(class) = class {
+            dispatch_to_java(sig, class);
+        }
+    }
+}
+
+/// Call `Signal.dispatch(sig)`. The dispatcher becomes a Java thread the
+/// first time it runs Java code.
+fn dispatch_to_java(sig: i32, class: &'static [u8]) {
+    static ATTACH: Once = Once::new();
+    ATTACH.call_once(attach_dispatcher);
+
+    let mir = require_class3(None, class).and_then(|cls| {
+        cls.get_class()
+            .get_static_method(&new_br("dispatch"), &new_br("(I)V"))
+            .ok()
+    });
+    match mir {
+        Some(mir) => {
+            let mut jc = JavaCall::new_with_args(mir, vec![Oop::new_int(sig)]);
+            jc.invoke(None, true);
+            if thread::is_meet_ex() {
+                warn!("signal: Signal.dispatch({}) threw", sig);
+                thread::clear_ex();
+            }
+        }
+        None => warn!("signal: no dispatch method for signal {}", sig),
+    }
+}
+
+/// Give the dispatcher a daemon `java.lang.Thread` object, as new handler
+/// threads inherit from it. It isn't one of the `Threads`, so the VM doesn't
+/// wait for it on exit.
+fn attach_dispatcher() {
+    let vm = vm::get_vm();
+    let jt = JavaThread::new(Some("Signal Dispatcher".to_string()), vm.threads.next_id());
+    thread::THREAD.with(|t| *t.borrow_mut() = jt.clone());
+    safepoint::attach_current_thread();
+
+    let thread_cls = require_class3(None, b"java/lang/Thread").unwrap();
+    let obj = Oop::new_inst(thread_cls);
+    let main_obj = vm
+        .threads
+        .find_java_thread(thread::MAIN_THREAD_ID)
+        .and_then(|main| main.read().unwrap().java_thread_obj.clone());
+    if let Some(main_obj) = &main_obj {
+        if let Some(group) = thread::get_thread_field(main_obj, "group", "Ljava/lang/ThreadGroup;")
+        {
+            thread::set_thread_field(&obj, "group", "Ljava/lang/ThreadGroup;", group);
+        }
+    }
+    let name = util::oop::new_java_lang_string2("Signal Dispatcher");
+    thread::set_thread_field(&obj, "name", "Ljava/lang/String;", name);
+    thread::set_thread_field(&obj, "daemon", "Z", Oop::new_int(1));
+    thread::set_thread_field(&obj, "priority", "I", Oop::new_int(9));
+
+    let mut t = jt.write().unwrap();
+    t.bind_os_thread();
+    thread::set_thread_field(&obj, "eetop", "J", Oop::new_long(t.eetop));
+    t.java_thread_obj = Some(obj);
+    t.is_alive = true;
+    t.set_status(thread::status::RUNNABLE);
+}
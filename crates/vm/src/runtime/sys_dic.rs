@@ -27,3 +27,9 @@ pub fn find(key: &[u8]) -> Option<ClassRef> {
 pub fn init() {
     SYS_DIC.get_or_init(|| Mutex::new(FxHashMap::default()));
 }
+
+/// Snapshot of every class currently in the dictionary.
+pub fn classes() -> Vec<ClassRef> {
+    let dict = SYS_DIC.get().unwrap().lock().unwrap();
+    dict.values().cloned().collect()
+}
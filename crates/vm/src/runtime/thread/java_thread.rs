@@ -1,8 +1,13 @@
-use crate::oop::{self, consts, Oop};
+use crate::new_br;
+use crate::oop::{self, consts, Class, Oop};
+use crate::runtime::thread::{priority, Parker};
+use crate::runtime::{require_class3, JavaCall};
 use crate::types::{FrameRef, JavaThreadRef};
+use classfile::consts::J_THREAD;
 use std::cell::RefCell;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
+use std::thread::ThreadId;
 
 thread_local! {
     pub static THREAD: RefCell<JavaThreadRef> = RefCell::new(JavaThread::main());
@@ -23,6 +28,10 @@ fn set_meet_ex(val: bool) {
     IS_MEET_EX.with(|v| v.store(val, Ordering::Relaxed));
 }
 
+/// `eetop` of the main thread. It is never 0: JDK 17+ `Thread.isAlive` is
+/// `eetop != 0`.
+pub const MAIN_THREAD_ID: i64 = 1;
+
 pub struct JavaThread {
     pub frames: Vec<FrameRef>,
     in_safe_point: bool,
@@ -31,10 +40,33 @@ pub struct JavaThread {
     pub ex: Option<Oop>,
     pub is_alive: bool,
     pub eetop: i64,
+    pub parker: Arc<Parker>,
+    /// The OS thread running it, set once it runs; monitors record owners
+    /// by `ThreadId`.
+    pub os_thread: Option<ThreadId>,
+    /// Kernel id of that thread, for priorities.
+    pub tid: Option<i32>,
+    /// `Thread.daemon` when started; the VM doesn't wait for daemon threads
+    /// on exit.
+    pub daemon: bool,
 
     pub tag: String, //for debug
 }
 
+/// Values of `java.lang.Thread.threadStatus` (JVMTI thread state bits),
+/// decoded by `jdk.internal.misc.VM.toThreadState`.
+pub mod status {
+    pub const NEW: i32 = 0;
+    pub const RUNNABLE: i32 = 0x0005;
+    pub const SLEEPING: i32 = 0x00E1;
+    pub const IN_OBJECT_WAIT: i32 = 0x0191;
+    pub const IN_OBJECT_WAIT_TIMED: i32 = 0x01A1;
+    pub const PARKED: i32 = 0x0291;
+    pub const PARKED_TIMED: i32 = 0x02A1;
+    pub const BLOCKED_ON_MONITOR_ENTER: i32 = 0x0401;
+    pub const TERMINATED: i32 = 0x0002;
+}
+
 impl JavaThread {
     pub fn new(tag: Option<String>, eetop: i64) -> JavaThreadRef {
         let tag = tag.unwrap_or_else(|| format!("thread-{}", eetop));
@@ -46,18 +78,145 @@ impl JavaThread {
             ex: None,
             is_alive: false,
             eetop,
+            parker: Parker::new(),
+            os_thread: None,
+            tid: None,
+            daemon: false,
             tag,
         };
         Arc::new(RwLock::new(Box::new(t)))
     }
 
     pub fn main() -> JavaThreadRef {
-        JavaThread::new(Some("main".to_string()), 0)
+        JavaThread::new(Some("main".to_string()), MAIN_THREAD_ID)
     }
 
     pub fn set_java_thread_obj(&mut self, obj: Oop) {
         self.java_thread_obj = Some(obj);
     }
+
+    /// Record the current OS thread as the one running this thread.
+    pub fn bind_os_thread(&mut self) {
+        self.os_thread = Some(std::thread::current().id());
+        self.tid = Some(priority::current_tid());
+    }
+
+    /// `Thread.setPriority`: apply `priority` to the OS thread, if it runs.
+    pub fn set_priority(&self, priority: i32) {
+        if let Some(tid) = self.tid {
+            priority::set_native_priority(tid, priority);
+        }
+    }
+
+    /// Update `threadStatus` of the thread object.
+    pub fn set_status(&self, status: i32) {
+        if let Some(obj) = &self.java_thread_obj {
+            set_thread_field(obj, "threadStatus", "I", Oop::new_int(status));
+        }
+    }
+
+    /// Clear the interrupt status, including the `interrupted` field that
+    /// JDK 14+ keeps in the thread object.
+    pub fn clear_interrupted(&self) {
+        self.parker.is_interrupted(true);
+        if let Some(obj) = &self.java_thread_obj {
+            set_thread_field(obj, "interrupted", "Z", Oop::new_int(0));
+        }
+    }
+}
+
+/// `monitorenter` and synchronized methods: lock `obj`. Uncontended locks
+/// stay thin; a thread that has to wait reports `BLOCKED`, and thread dumps
+/// show the monitor.
+pub fn monitor_enter(obj: &Oop) {
+    let monitor = match obj.try_lock() {
+        Ok(()) => return,
+        Err(monitor) => monitor,
+    };
+
+    let jt = current_java_thread();
+    let parker = {
+        let jt = jt.read().unwrap();
+        jt.set_status(status::BLOCKED_ON_MONITOR_ENTER);
+        jt.parker.clone()
+    };
+    parker.enter(&monitor);
+    jt.read().unwrap().set_status(status::RUNNABLE);
+}
+
+/// Call `Thread.exit` before `thread` terminates: it leaves its
+/// `ThreadGroup` and drops its thread locals.
+pub fn exit_thread(thread: &Oop) {
+    let mir = require_class3(None, J_THREAD).and_then(|cls| {
+        cls.get_class()
+            .get_this_class_method(&new_br("exit"), &new_br("()V"))
+            .ok()
+    });
+    if let Some(mir) = mir {
+        //an uncaught exception stays pending across the call
+        let jt = current_java_thread();
+        let pending = jt.write().unwrap().take_ex();
+        let mut jc = JavaCall::new_with_args(mir, vec![thread.clone()]);
+        jc.invoke(None, false);
+
+        let mut jt = jt.write().unwrap();
+        jt.clear_ex();
+        if let Some(ex) = pending {
+            jt.set_ex(ex);
+        }
+    }
+}
+
+/// Read a field of a `java.lang.Thread` object. JDK 19+ moved some fields
+/// (`threadStatus`, `priority`, `daemon`, ...) into `Thread.holder`.
+pub fn get_thread_field(thread: &Oop, name: &str, desc: &str) -> Option<Oop> {
+    let (obj, offset) = locate_thread_field(thread, name, desc)?;
+    Some(Class::get_field_value2(obj, offset))
+}
+
+/// Write a field of a `java.lang.Thread` object, see `get_thread_field`.
+pub fn set_thread_field(thread: &Oop, name: &str, desc: &str, v: Oop) {
+    if let Some((obj, offset)) = locate_thread_field(thread, name, desc) {
+        Class::put_field_value2(obj, offset, v);
+    }
+}
+
+fn locate_thread_field(thread: &Oop, name: &str, desc: &str) -> Option<(u32, usize)> {
+    let slot_id = match thread {
+        Oop::Ref(slot_id) => *slot_id,
+        _ => return None,
+    };
+    let cls = oop::with_heap(|heap| {
+        let desc = heap.get(slot_id);
+        let guard = desc.read().unwrap();
+        guard.v.extract_inst().class.clone()
+    });
+    let cls = cls.get_class();
+    if let Ok(fid) = cls.get_field_id_safe(&new_br(name), &new_br(desc), false) {
+        return Some((slot_id, fid.offset));
+    }
+
+    let holder_fid = cls
+        .get_field_id_safe(
+            &new_br("holder"),
+            &new_br("Ljava/lang/Thread$FieldHolder;"),
+            false,
+        )
+        .ok()?;
+    let holder = match Class::get_field_value2(slot_id, holder_fid.offset) {
+        Oop::Ref(holder) => holder,
+        _ => return None,
+    };
+    let holder_cls = oop::with_heap(|heap| {
+        let desc = heap.get(holder);
+        let guard = desc.read().unwrap();
+        guard.v.extract_inst().class.clone()
+    });
+    let holder_cls = holder_cls.get_class();
+    let fid = holder_cls
+        .get_field_id_safe(&new_br(name), &new_br(desc), false)
+        .ok()?;
+    Some((holder, fid.offset))
 }
 
 //exception
@@ -1,5 +1,5 @@
 use crate::oop::{self, Class, Oop};
-use crate::runtime::thread::thread_pool;
+use crate::runtime::thread::{status, thread_pool};
 use crate::runtime::{self, init_vm, vm, DataArea, JavaCall, JavaThread};
 use crate::types::{ClassRef, FrameRef, JavaThreadRef, MethodIdRef};
 use crate::{new_br, util};
@@ -21,7 +21,10 @@ impl MainThread {
         }
     }
 
-    pub fn run(&mut self) {
+    /// Run `main`, then wait for the non-daemon threads and run the
+    /// shutdown hooks. Returns the process exit status, unless a thread
+    /// calls `System.exit`, which exits the process itself.
+    pub fn run(&mut self) -> i32 {
         let vm = vm::VM::new(3);
 
         //attach 'main' thread
@@ -31,6 +34,9 @@ impl MainThread {
         init_vm::initialize_jvm();
         info!("init vm end");
 
+        //'kill -3' prints a thread dump
+        runtime::signal::init();
+
         let main_class = oop::class::load_and_init(self.class.as_bytes());
 
         let mir = {
@@ -71,18 +77,63 @@ impl MainThread {
             _ => unreachable!("NotFound \"main\""),
         }
 
+        let mut code = 0;
         if jt.read().unwrap().ex.is_some() {
             self.uncaught_ex(main_class);
+            code = 1;
         }
+        self.exit_main_thread();
+
+        //like 'DestroyJavaVM', the main thread waits for the other
+        //non-daemon threads, then runs the shutdown hooks
+        vm.threads.join_all();
+        self.shutdown();
 
         //detach main thread
         vm.threads.detach_current_thread();
 
-        vm.threads.join_all();
+        code
     }
 }
 
 impl MainThread {
+    //like the launcher detaching 'main': leave its group, wake joiners
+    fn exit_main_thread(&self) {
+        let jt = runtime::thread::current_java_thread();
+        let obj = jt.read().unwrap().java_thread_obj.clone();
+        if let Some(obj) = obj {
+            runtime::thread::exit_thread(&obj);
+
+            let monitor = obj.monitor().unwrap();
+            monitor.lock();
+            jt.read().unwrap().set_status(status::TERMINATED);
+            //JDK 17+ 'isAlive' is 'eetop != 0'
+            runtime::thread::set_thread_field(&obj, "eetop", "J", Oop::new_long(0));
+            monitor.notify_all();
+            monitor.unlock();
+        }
+    }
+
+    //'Shutdown.shutdown' runs the hooks, without halting
+    fn shutdown(&self) {
+        let cls = oop::class::load_and_init(b"java/lang/Shutdown");
+        let mir = {
+            let cls = cls.get_class();
+            cls.get_static_method(&new_br("shutdown"), &new_br("()V"))
+        };
+        match mir {
+            Ok(mir) => {
+                let mut jc = JavaCall::new_with_args(mir, vec![]);
+                jc.invoke(None, true);
+                if runtime::thread::is_meet_ex() {
+                    warn!("shutdown hooks threw");
+                    runtime::thread::clear_ex();
+                }
+            }
+            Err(_) => warn!("NotFound \"Shutdown.shutdown\""),
+        }
+    }
+
     fn build_main_arg(&self) -> Vec<Oop> {
         let args = self
             .args
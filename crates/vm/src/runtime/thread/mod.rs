@@ -2,17 +2,24 @@ mod condvar;
 mod java_thread;
 mod main;
 mod mutex;
+mod parker;
+pub mod priority;
+pub mod safepoint;
+pub mod thread_dump;
 mod thread_pool;
 mod threads;
+pub mod vm_operation;
 
 pub use condvar::Condvar;
 pub use java_thread::current_java_thread;
-pub use java_thread::JavaThread;
 pub use java_thread::THREAD;
+pub use java_thread::{exit_thread, get_thread_field, monitor_enter, set_thread_field, status};
+pub use java_thread::{JavaThread, MAIN_THREAD_ID};
 pub use main::MainThread;
 pub use mutex::raw as mutex_raw;
 pub use mutex::ReentrantMutex;
-pub use thread_pool::ThreadPool;
+pub use parker::Parker;
+pub use thread_pool::{retire_current_worker, ThreadPool};
 pub use threads::Threads;
 
 pub use java_thread::is_meet_ex;
@@ -0,0 +1,143 @@
+use crate::oop::Monitor;
+use crate::runtime::thread::safepoint;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Blocking and interruption support for one Java thread.
+///
+/// A thread blocks in one of three ways, and `interrupt` wakes each of them:
+/// - `Object.wait`: `wait_on` records the monitor so `interrupt` can wake it
+/// - `Thread.sleep`: `sleep` waits on `cond`
+/// - `Unsafe.park`: `park` waits on `cond` for the permit
+///
+/// The interrupt flag is always set before the wake-up, and each blocking
+/// call checks it under the lock it waits with, so an interrupt is never lost.
+///
+/// A thread blocked entering a monitor can't be interrupted, `enter` only
+/// records the monitor for thread dumps.
+pub struct Parker {
+    interrupted: AtomicBool,
+    waiting_on: Mutex<Option<Arc<Monitor>>>,
+    entering: Mutex<Option<Arc<Monitor>>>,
+    permit: Mutex<bool>,
+    cond: Condvar,
+}
+
+impl Parker {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            interrupted: AtomicBool::new(false),
+            waiting_on: Mutex::new(None),
+            entering: Mutex::new(None),
+            permit: Mutex::new(false),
+            cond: Condvar::new(),
+        })
+    }
+
+    /// Set the interrupt status and wake the thread if it is blocked.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+
+        if let Some(monitor) = self.waiting_on.lock().unwrap().as_ref() {
+            monitor.wake_all();
+        }
+
+        let _permit = self.permit.lock().unwrap();
+        self.cond.notify_all();
+    }
+
+    /// `monitorenter` and synchronized methods: lock `monitor`, blocking
+    /// until its owner releases it.
+    pub fn enter(&self, monitor: &Arc<Monitor>) {
+        *self.entering.lock().unwrap() = Some(monitor.clone());
+        //the owner may be stopped at a safepoint
+        safepoint::blocked(|| monitor.lock());
+        *self.entering.lock().unwrap() = None;
+    }
+
+    /// The monitor the thread is blocked entering.
+    pub fn entering(&self) -> Option<Arc<Monitor>> {
+        self.entering.lock().unwrap().clone()
+    }
+
+    /// The monitor the thread is waiting on in `Object.wait`.
+    pub fn waiting_on(&self) -> Option<Arc<Monitor>> {
+        self.waiting_on.lock().unwrap().clone()
+    }
+
+    /// Test the interrupt status, clearing it if `clear` is true.
+    pub fn is_interrupted(&self, clear: bool) -> bool {
+        if clear {
+            self.interrupted.swap(false, Ordering::SeqCst)
+        } else {
+            self.interrupted.load(Ordering::SeqCst)
+        }
+    }
+
+    /// `Object.wait`: release `monitor` and wait for a notification, the
+    /// timeout or an interrupt. The monitor is re-acquired before returning.
+    ///
+    /// Returns false if the thread was interrupted (the status is cleared).
+    /// A thread both notified and interrupted returns true with the interrupt
+    /// still pending, so the notification isn't lost.
+    pub fn wait_on(&self, monitor: &Arc<Monitor>, timeout: Option<Duration>) -> bool {
+        if self.is_interrupted(true) {
+            return false;
+        }
+
+        *self.waiting_on.lock().unwrap() = Some(monitor.clone());
+        let notified = monitor.wait_interruptibly(timeout, &self.interrupted);
+        *self.waiting_on.lock().unwrap() = None;
+
+        notified || !self.is_interrupted(true)
+    }
+
+    /// `Thread.sleep`. Returns false if the thread was interrupted
+    /// (the status is cleared).
+    pub fn sleep(&self, dur: Duration) -> bool {
+        let deadline = Instant::now() + dur;
+        let mut guard = self.permit.lock().unwrap();
+        loop {
+            if self.is_interrupted(true) {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            guard = self.cond.wait_timeout(guard, deadline - now).unwrap().0;
+        }
+    }
+
+    /// `Unsafe.park`: block until the permit is available, `deadline` passes
+    /// or the thread is interrupted. The interrupt status is not cleared.
+    pub fn park(&self, deadline: Option<Instant>) {
+        let mut permit = self.permit.lock().unwrap();
+        loop {
+            if *permit {
+                *permit = false;
+                return;
+            }
+            if self.interrupted.load(Ordering::SeqCst) {
+                return;
+            }
+            permit = match deadline {
+                None => self.cond.wait(permit).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return;
+                    }
+                    self.cond.wait_timeout(permit, deadline - now).unwrap().0
+                }
+            };
+        }
+    }
+
+    /// `Unsafe.unpark`: make the permit available.
+    pub fn unpark(&self) {
+        *self.permit.lock().unwrap() = true;
+        self.cond.notify_all();
+    }
+}
@@ -0,0 +1,108 @@
+//! Java thread priorities mapped to OS scheduling.
+//!
+//! On Linux each thread has its own nice value, set with `setpriority` on its
+//! kernel thread id. Lowering a priority is always allowed, raising it above
+//! the nice value the VM started with needs `CAP_SYS_NICE`: it's tried, and
+//! a refusal is ignored as `Thread.setPriority` can't fail. Elsewhere
+//! priorities are only recorded in the thread object.
+//!
+//! Java threads run on pooled workers, so a worker gets its nice value back
+//! when its thread ends; if that is refused too, the worker is retired.
+
+use tracing::debug;
+
+/// Nice value of each Java priority from `MIN_PRIORITY`.
+const NICE: [i32; 10] = [4, 3, 2, 1, 0, -1, -2, -3, -4, -5];
+
+/// Nice value for a Java priority, clamped to `MIN_PRIORITY..=MAX_PRIORITY`.
+pub fn java_to_nice(priority: i32) -> i32 {
+    NICE[priority.clamp(1, 10) as usize - 1]
+}
+
+/// Kernel id of the current thread, the one `set_native_priority` takes.
+#[cfg(target_os = "linux")]
+pub fn current_tid() -> i32 {
+    unsafe { libc::gettid() }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn current_tid() -> i32 {
+    0
+}
+
+/// Nice value of the current thread.
+#[cfg(target_os = "linux")]
+pub fn current_nice() -> i32 {
+    //-1 is a valid nice value, errno tells it from a failure
+    unsafe {
+        *libc::__errno_location() = 0;
+        let nice = libc::getpriority(libc::PRIO_PROCESS, 0);
+        if nice == -1 && *libc::__errno_location() != 0 {
+            0
+        } else {
+            nice
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn current_nice() -> i32 {
+    0
+}
+
+/// Apply `priority` to thread `tid`; false if the OS refused.
+pub fn set_native_priority(tid: i32, priority: i32) -> bool {
+    set_nice(tid, java_to_nice(priority))
+}
+
+/// Set the nice value of thread `tid`; false if the OS refused.
+#[cfg(target_os = "linux")]
+pub fn set_nice(tid: i32, nice: i32) -> bool {
+    let r = unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) };
+    if r != 0 {
+        debug!(
+            "setpriority(tid={}, nice={}) refused: {}",
+            tid,
+            nice,
+            std::io::Error::last_os_error()
+        );
+    }
+    r == 0
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_nice(_tid: i32, _nice: i32) -> bool {
+    debug!("thread priorities are not mapped on this OS");
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn priorities_map_to_nice_values() {
+        assert_eq!(java_to_nice(5), 0);
+        assert_eq!(java_to_nice(1), 4);
+        assert_eq!(java_to_nice(10), -5);
+        assert_eq!(java_to_nice(0), java_to_nice(1));
+        assert_eq!(java_to_nice(42), java_to_nice(10));
+        for p in 1..10 {
+            assert!(java_to_nice(p) > java_to_nice(p + 1));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn nice_value_of_the_current_thread() {
+        //a thread of its own, the nice value stays with it
+        std::thread::spawn(|| {
+            //refused if the test already runs at a higher nice value
+            if set_native_priority(current_tid(), 1) {
+                assert_eq!(current_nice(), java_to_nice(1));
+            }
+        })
+        .join()
+        .unwrap();
+    }
+}
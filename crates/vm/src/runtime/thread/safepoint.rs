@@ -0,0 +1,277 @@
+//! Safepoints: points where every Java thread is stopped, so that the VM can
+//! look at or change their state consistently.
+//!
+//! Each attached thread publishes one of three states:
+//!
+//! - `IN_JAVA`: running bytecode, interpreted or compiled. Such a thread only
+//!   stops at a poll: method entries and loop back-edges in `Interp` and in
+//!   JIT code read `POLL` and call `poll`/`block` when it is set.
+//! - `IN_NATIVE`: running a native method or VM code outside of Java.
+//! - `BLOCKED`: waiting for a monitor, a class initialization lock or the end
+//!   of a safepoint.
+//!
+//! The last two are safe: the thread doesn't touch Java frames, and once it
+//! wants to go back to Java it first checks `POLL` and waits for the
+//! safepoint to end. `run_at_safepoint` sets `POLL`, waits until no thread is
+//! `IN_JAVA`, runs the operation and releases the threads.
+//!
+//! Safe threads may still run VM code that takes the heap lock, so an
+//! operation that needs the heap frozen takes the heap lock as well.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+pub const IN_JAVA: u8 = 0;
+pub const IN_NATIVE: u8 = 1;
+pub const BLOCKED: u8 = 2;
+
+/// Set while a safepoint is requested. Compiled code reads it through the
+/// exported symbol, so it must stay a plain byte.
+#[export_name = "jvm_safepoint_poll"]
+pub static POLL: AtomicBool = AtomicBool::new(false);
+
+/// States of the attached threads.
+static THREADS: Mutex<Vec<Arc<AtomicU8>>> = Mutex::new(Vec::new());
+
+/// Serializes safepoints.
+static OPERATION: Mutex<()> = Mutex::new(());
+
+/// Guards the hand-off between the requesting thread and the stopping ones.
+static LOCK: Mutex<()> = Mutex::new(());
+static ARRIVED: Condvar = Condvar::new();
+static RESUMED: Condvar = Condvar::new();
+
+/// Number of safepoints completed so far.
+static COUNT: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static STATE: RefCell<Option<Arc<AtomicU8>>> = const { RefCell::new(None) };
+}
+
+/// Register the current thread, initially `IN_NATIVE`.
+pub fn attach_current_thread() {
+    let state = Arc::new(AtomicU8::new(IN_NATIVE));
+    THREADS.lock().unwrap().push(state.clone());
+    STATE.with(|s| *s.borrow_mut() = Some(state));
+}
+
+/// Unregister the current thread; safepoints stop waiting for it.
+pub fn detach_current_thread() {
+    if let Some(state) = STATE.with(|s| s.borrow_mut().take()) {
+        THREADS
+            .lock()
+            .unwrap()
+            .retain(|elem| !Arc::ptr_eq(elem, &state));
+        if POLL.load(Ordering::SeqCst) {
+            let _l = LOCK.lock().unwrap();
+            ARRIVED.notify_all();
+        }
+    }
+}
+
+fn current() -> Option<Arc<AtomicU8>> {
+    STATE.with(|s| s.borrow().clone())
+}
+
+/// The safepoint poll: stop here if a safepoint is requested.
+#[inline]
+pub fn poll() {
+    if POLL.load(Ordering::Acquire) {
+        if let Some(state) = current() {
+            if state.load(Ordering::SeqCst) == IN_JAVA {
+                block(&state);
+            }
+        }
+    }
+}
+
+/// Wait for the current safepoint to end, then go back to `IN_JAVA`.
+fn block(state: &AtomicU8) {
+    let mut guard = LOCK.lock().unwrap();
+    state.store(BLOCKED, Ordering::SeqCst);
+    ARRIVED.notify_all();
+    while POLL.load(Ordering::SeqCst) {
+        guard = RESUMED.wait(guard).unwrap();
+    }
+    state.store(IN_JAVA, Ordering::SeqCst);
+}
+
+/// Switch the current thread to `new` while `f` runs.
+fn with_state<R>(new: u8, f: impl FnOnce() -> R) -> R {
+    let state = match current() {
+        Some(state) => state,
+        None => return f(),
+    };
+    let prev = state.load(Ordering::SeqCst);
+    transition(&state, new);
+    let r = f();
+    transition(&state, prev);
+    r
+}
+
+fn transition(state: &AtomicU8, new: u8) {
+    state.store(new, Ordering::SeqCst);
+    if new == IN_JAVA {
+        //pairs with the store of POLL in 'run_at_safepoint': either this
+        //thread sees the request, or the requester sees it IN_JAVA
+        if POLL.load(Ordering::SeqCst) {
+            block(state);
+        }
+    } else if POLL.load(Ordering::SeqCst) {
+        let _l = LOCK.lock().unwrap();
+        ARRIVED.notify_all();
+    }
+}
+
+/// Run Java code: the thread is only stopped at polls.
+pub fn in_java<R>(f: impl FnOnce() -> R) -> R {
+    with_state(IN_JAVA, f)
+}
+
+/// Run a native method; safepoints don't wait for it.
+pub fn in_native<R>(f: impl FnOnce() -> R) -> R {
+    with_state(IN_NATIVE, f)
+}
+
+/// Wait for a lock held by another Java thread, which may itself be
+/// stopped at a safepoint.
+pub fn blocked<R>(f: impl FnOnce() -> R) -> R {
+    with_state(BLOCKED, f)
+}
+
+fn all_stopped() -> bool {
+    THREADS
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|state| state.load(Ordering::SeqCst) != IN_JAVA)
+}
+
+/// Stop all Java threads, run `op`, and let them continue.
+///
+/// `op` must not run Java code or wait for a Java thread. A Java thread
+/// calling this counts as `IN_NATIVE` until it returns.
+pub fn run_at_safepoint<R>(op: impl FnOnce() -> R) -> R {
+    in_native(|| {
+        let _op = OPERATION.lock().unwrap();
+
+        let mut guard = LOCK.lock().unwrap();
+        POLL.store(true, Ordering::SeqCst);
+        //a thread leaving Java without a poll doesn't always notify
+        while !all_stopped() {
+            guard = ARRIVED
+                .wait_timeout(guard, Duration::from_millis(1))
+                .unwrap()
+                .0;
+        }
+        drop(guard);
+
+        let r = op();
+
+        let _l = LOCK.lock().unwrap();
+        POLL.store(false, Ordering::SeqCst);
+        COUNT.fetch_add(1, Ordering::SeqCst);
+        RESUMED.notify_all();
+        r
+    })
+}
+
+/// Number of safepoints completed so far.
+pub fn count() -> u64 {
+    COUNT.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    //runs 'body' on a new thread, returns once the thread is attached
+    fn spawn_attached<F>(body: F) -> thread::JoinHandle<()>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let ready = Arc::new(AtomicBool::new(false));
+        let flag = ready.clone();
+        let h = thread::spawn(move || {
+            attach_current_thread();
+            flag.store(true, Ordering::SeqCst);
+            body();
+            detach_current_thread();
+        });
+        while !ready.load(Ordering::SeqCst) {
+            thread::yield_now();
+        }
+        h
+    }
+
+    #[test]
+    fn stops_thread_at_poll() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (c, s) = (counter.clone(), stop.clone());
+        let h = spawn_attached(move || {
+            in_java(|| {
+                while !s.load(Ordering::SeqCst) {
+                    c.fetch_add(1, Ordering::SeqCst);
+                    poll();
+                }
+            })
+        });
+        while counter.load(Ordering::SeqCst) == 0 {
+            thread::yield_now();
+        }
+
+        let (before, after) = run_at_safepoint(|| {
+            let before = counter.load(Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            (before, counter.load(Ordering::SeqCst))
+        });
+        assert_eq!(before, after);
+
+        stop.store(true, Ordering::SeqCst);
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn native_thread_does_not_block_safepoint() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let s = stop.clone();
+        let h = spawn_attached(move || {
+            in_java(|| {
+                in_native(|| {
+                    while !s.load(Ordering::SeqCst) {
+                        thread::sleep(Duration::from_millis(1));
+                    }
+                })
+            })
+        });
+
+        run_at_safepoint(|| ());
+        stop.store(true, Ordering::SeqCst);
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn blocked_thread_does_not_block_safepoint() {
+        let lock = Arc::new(Mutex::new(()));
+        let held = lock.lock().unwrap();
+        let l = lock.clone();
+        let h = spawn_attached(move || {
+            in_java(|| {
+                let _g = blocked(|| l.lock().unwrap());
+            })
+        });
+        thread::sleep(Duration::from_millis(10));
+
+        let n = count();
+        run_at_safepoint(|| ());
+        assert!(count() > n);
+
+        drop(held);
+        h.join().unwrap();
+    }
+}
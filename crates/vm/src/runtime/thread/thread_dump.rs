@@ -0,0 +1,388 @@
+//! Thread dumps: the stack, state and monitors of every Java thread, and the
+//! monitor deadlocks among them, taken at a safepoint.
+
+use crate::oop::{self, Monitor, Oop, RefKind, TypeArrayDesc};
+use crate::runtime::thread::{get_thread_field, status, vm_operation, JavaThread};
+use crate::runtime::vm;
+use crate::types::{JavaThreadRef, MethodIdRef};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread::ThreadId;
+
+/// The (method, pc) of every frame of `jt`, innermost first.
+///
+/// Frames being set up or torn down are skipped. Take it at a safepoint
+/// for a consistent stack.
+pub fn snapshot_frames(jt: &JavaThread) -> Vec<(MethodIdRef, i32)> {
+    jt.frames
+        .iter()
+        .rev()
+        .filter_map(|frame| {
+            frame
+                .try_read()
+                .ok()
+                .map(|frame| (frame.mir.clone(), frame.pc.load(Ordering::Relaxed)))
+        })
+        .collect()
+}
+
+/// Print a dump of all threads to stdout, as `kill -3` does.
+pub fn print() {
+    print!("{}", dump());
+}
+
+/// Dump all threads: stacks, states, monitors, and deadlocks if any.
+pub fn dump() -> String {
+    vm_operation::execute("PrintThreads", || {
+        let threads = collect();
+        let mut w = String::new();
+        let _ = writeln!(&mut w, "Full thread dump:");
+        for t in &threads {
+            let _ = writeln!(&mut w);
+            write_thread(&mut w, t);
+        }
+        let cycles = find_cycles(&threads);
+        for cycle in &cycles {
+            let _ = writeln!(&mut w);
+            write_deadlock(&mut w, &threads, cycle);
+        }
+        match cycles.len() {
+            0 => (),
+            1 => {
+                let _ = writeln!(&mut w, "\nFound 1 deadlock.");
+            }
+            n => {
+                let _ = writeln!(&mut w, "\nFound {} deadlocks.", n);
+            }
+        }
+        w
+    })
+}
+
+/// Threads in monitor deadlocks, one list per cycle. Each thread in a cycle
+/// is blocked entering a monitor owned by the next one.
+pub fn find_deadlocks() -> Vec<Vec<JavaThreadRef>> {
+    vm_operation::execute("FindDeadlocks", || {
+        let threads = collect();
+        find_cycles(&threads)
+            .into_iter()
+            .map(|cycle| cycle.iter().map(|&i| threads[i].jt.clone()).collect())
+            .collect()
+    })
+}
+
+struct ThreadInfo {
+    jt: JavaThreadRef,
+    name: String,
+    id: i64,
+    daemon: bool,
+    priority: i32,
+    status: i32,
+    os_thread: Option<ThreadId>,
+    frames: Vec<(MethodIdRef, i32)>,
+    entering: Option<MonitorInfo>,
+    waiting_on: Option<MonitorInfo>,
+    locked: Vec<MonitorInfo>,
+}
+
+#[derive(Clone)]
+struct MonitorInfo {
+    slot_id: u32,
+    class_name: String,
+    owner: Option<ThreadId>,
+}
+
+fn collect() -> Vec<ThreadInfo> {
+    let threads = vm::get_vm().threads.all();
+    let mut infos: Vec<ThreadInfo> = threads
+        .iter()
+        .map(|jt| {
+            let t = jt.read().unwrap();
+            let obj = t.java_thread_obj.clone().unwrap_or(Oop::Null);
+            let int_field = |name, desc| match get_thread_field(&obj, name, desc) {
+                Some(Oop::Int(v)) => Some(v),
+                _ => None,
+            };
+            ThreadInfo {
+                jt: jt.clone(),
+                name: thread_name(&obj).unwrap_or_else(|| t.tag.clone()),
+                id: t.eetop,
+                daemon: int_field("daemon", "Z").unwrap_or(0) != 0,
+                priority: int_field("priority", "I").unwrap_or(5),
+                status: int_field("threadStatus", "I").unwrap_or(status::RUNNABLE),
+                os_thread: t.os_thread,
+                frames: snapshot_frames(&t),
+                entering: None,
+                waiting_on: None,
+                locked: Vec::new(),
+            }
+        })
+        .collect();
+
+    //find the objects of the monitors the threads own or wait for
+    let pending: Vec<_> = threads
+        .iter()
+        .map(|jt| {
+            let parker = jt.read().unwrap().parker.clone();
+            (parker.entering(), parker.waiting_on())
+        })
+        .collect();
+    let owners: HashMap<ThreadId, usize> = infos
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| t.os_thread.map(|id| (id, i)))
+        .collect();
+
+    oop::with_heap(|heap| {
+        for (slot_id, desc) in heap.iter() {
+            let Ok(desc) = desc.try_read() else {
+                continue;
+            };
+            //only an inflated lock has threads blocked or waiting on it
+            let owner = desc.lock_owner();
+            let monitor = desc.inflated_monitor();
+            let is_this = |m: &Option<Arc<Monitor>>| matches!((m, &monitor), (Some(m), Some(monitor)) if Arc::ptr_eq(m, monitor));
+            let is_pending = pending
+                .iter()
+                .any(|(entering, waiting)| is_this(entering) || is_this(waiting));
+            if owner.is_none() && !is_pending {
+                continue;
+            }
+
+            let info = MonitorInfo {
+                slot_id,
+                class_name: class_name(&desc.v),
+                owner,
+            };
+            if let Some(&i) = owner.as_ref().and_then(|id| owners.get(id)) {
+                infos[i].locked.push(info.clone());
+            }
+            for (i, (entering, waiting)) in pending.iter().enumerate() {
+                if is_this(entering) {
+                    infos[i].entering = Some(info.clone());
+                }
+                if is_this(waiting) {
+                    infos[i].waiting_on = Some(info.clone());
+                }
+            }
+        }
+    });
+
+    infos
+}
+
+/// `Thread.name`: a String, or a char[] before JDK 9.
+fn thread_name(obj: &Oop) -> Option<String> {
+    if let Some(Oop::Ref(slot_id)) = get_thread_field(obj, "name", "Ljava/lang/String;") {
+        return Some(Oop::java_lang_string(slot_id));
+    }
+    match get_thread_field(obj, "name", "[C") {
+        Some(Oop::Ref(slot_id)) => oop::with_heap(|heap| {
+            let desc = heap.get(slot_id);
+            let guard = desc.read().unwrap();
+            match &guard.v {
+                RefKind::TypeArray(TypeArrayDesc::Char(chars)) => {
+                    Some(String::from_utf16_lossy(chars))
+                }
+                _ => None,
+            }
+        }),
+        _ => None,
+    }
+}
+
+fn class_name(v: &RefKind) -> String {
+    let name = match v {
+        RefKind::Inst(inst) => inst.class.name.clone(),
+        RefKind::Array(ary) => ary.class.name.clone(),
+        RefKind::TypeArray(_) => return "primitive array".to_string(),
+        RefKind::Mirror(_) => return "java.lang.Class".to_string(),
+    };
+    String::from_utf8_lossy(name.as_slice()).replace('/', ".")
+}
+
+/// `Thread.State` name for a `threadStatus` value.
+fn state_name(status: i32) -> &'static str {
+    const ALIVE: i32 = 0x0001;
+    const TERMINATED: i32 = 0x0002;
+    const RUNNABLE: i32 = 0x0004;
+    const WAITING_INDEFINITELY: i32 = 0x0010;
+    const WAITING_WITH_TIMEOUT: i32 = 0x0020;
+    const BLOCKED_ON_MONITOR_ENTER: i32 = 0x0400;
+
+    if status & RUNNABLE != 0 {
+        "RUNNABLE"
+    } else if status & BLOCKED_ON_MONITOR_ENTER != 0 {
+        "BLOCKED (on object monitor)"
+    } else if status & WAITING_INDEFINITELY != 0 {
+        "WAITING"
+    } else if status & WAITING_WITH_TIMEOUT != 0 {
+        "TIMED_WAITING"
+    } else if status & TERMINATED != 0 {
+        "TERMINATED"
+    } else if status & ALIVE == 0 {
+        "NEW"
+    } else {
+        "RUNNABLE"
+    }
+}
+
+fn write_thread(w: &mut String, t: &ThreadInfo) {
+    let _ = writeln!(
+        w,
+        "\"{}\" #{}{} prio={}",
+        t.name,
+        t.id,
+        if t.daemon { " daemon" } else { "" },
+        t.priority
+    );
+    let _ = writeln!(w, "   java.lang.Thread.State: {}", state_name(t.status));
+    for (mir, pc) in &t.frames {
+        let method = &mir.method;
+        let cls_name = String::from_utf8_lossy(method.cls_name.as_slice()).replace('/', ".");
+        let location = if method.is_native() {
+            "Native Method".to_string()
+        } else {
+            let file = method.class.get_class().get_source_file();
+            let file = file
+                .map(|file| String::from_utf8_lossy(file.as_slice()).to_string())
+                .unwrap_or_else(|| "Unknown Source".to_string());
+            match method.get_line_num((*pc).max(0) as u16) {
+                line if line >= 0 => format!("{}:{}", file, line),
+                _ => file,
+            }
+        };
+        let _ = writeln!(
+            w,
+            "\tat {}.{}({})",
+            cls_name,
+            String::from_utf8_lossy(method.name.as_slice()),
+            location
+        );
+    }
+    if let Some(m) = &t.entering {
+        let _ = writeln!(w, "\t- waiting to lock {}", monitor_desc(m));
+    }
+    if let Some(m) = &t.waiting_on {
+        let _ = writeln!(w, "\t- waiting on {}", monitor_desc(m));
+    }
+    for m in &t.locked {
+        let _ = writeln!(w, "\t- locked {}", monitor_desc(m));
+    }
+}
+
+fn monitor_desc(m: &MonitorInfo) -> String {
+    format!("<{:#010x}> (a {})", m.slot_id, m.class_name)
+}
+
+/// Cycles of threads, each blocked entering a monitor owned by the next.
+fn find_cycles(threads: &[ThreadInfo]) -> Vec<Vec<usize>> {
+    let by_os_thread: HashMap<ThreadId, usize> = threads
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| t.os_thread.map(|id| (id, i)))
+        .collect();
+    //the thread that 'i' waits for
+    let next = |i: usize| {
+        threads[i]
+            .entering
+            .as_ref()
+            .and_then(|m| m.owner)
+            .and_then(|owner| by_os_thread.get(&owner).copied())
+    };
+
+    let mut cycles = Vec::new();
+    let mut seen = vec![false; threads.len()];
+    for start in 0..threads.len() {
+        let mut path = Vec::new();
+        let mut cur = Some(start);
+        while let Some(i) = cur {
+            if seen[i] {
+                //a new cycle closes on the current path only
+                if let Some(pos) = path.iter().position(|&p| p == i) {
+                    cycles.push(path[pos..].to_vec());
+                }
+                break;
+            }
+            seen[i] = true;
+            path.push(i);
+            cur = next(i);
+        }
+    }
+    cycles
+}
+
+fn write_deadlock(w: &mut String, threads: &[ThreadInfo], cycle: &[usize]) {
+    let _ = writeln!(w, "Found one Java-level deadlock:");
+    let _ = writeln!(w, "=============================");
+    for (n, &i) in cycle.iter().enumerate() {
+        let holder = cycle[(n + 1) % cycle.len()];
+        let _ = writeln!(w, "\"{}\":", threads[i].name);
+        if let Some(m) = &threads[i].entering {
+            let _ = writeln!(w, "  waiting to lock monitor {},", monitor_desc(m));
+        }
+        let _ = writeln!(w, "  which is held by \"{}\"", threads[holder].name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thread_ids(n: usize) -> Vec<ThreadId> {
+        (0..n)
+            .map(|_| std::thread::spawn(|| ()).thread().id())
+            .collect()
+    }
+
+    //thread 'i' runs on 'ids[i]' and is blocked on a monitor owned by 'ids[owner]'
+    fn thread(ids: &[ThreadId], i: usize, owner: Option<usize>) -> ThreadInfo {
+        ThreadInfo {
+            jt: JavaThread::new(None, i as i64),
+            name: format!("t{}", i),
+            id: i as i64,
+            daemon: false,
+            priority: 5,
+            status: status::BLOCKED_ON_MONITOR_ENTER,
+            os_thread: Some(ids[i]),
+            frames: Vec::new(),
+            entering: owner.map(|owner| MonitorInfo {
+                slot_id: owner as u32,
+                class_name: "java.lang.Object".to_string(),
+                owner: Some(ids[owner]),
+            }),
+            waiting_on: None,
+            locked: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn finds_monitor_cycles() {
+        let ids = thread_ids(6);
+        //0 -> 1 -> 2 -> 0 is a cycle, 3 waits on it, 4 <-> 5 is another
+        let threads = vec![
+            thread(&ids, 0, Some(1)),
+            thread(&ids, 1, Some(2)),
+            thread(&ids, 2, Some(0)),
+            thread(&ids, 3, Some(0)),
+            thread(&ids, 4, Some(5)),
+            thread(&ids, 5, Some(4)),
+        ];
+        let mut cycles = find_cycles(&threads);
+        cycles.iter_mut().for_each(|cycle| cycle.sort());
+        assert_eq!(cycles, vec![vec![0, 1, 2], vec![4, 5]]);
+    }
+
+    #[test]
+    fn chain_is_not_a_deadlock() {
+        let ids = thread_ids(3);
+        let threads = vec![
+            thread(&ids, 0, Some(1)),
+            thread(&ids, 1, Some(2)),
+            thread(&ids, 2, None),
+        ];
+        assert!(find_cycles(&threads).is_empty());
+    }
+}
@@ -1,17 +1,40 @@
-use crate::types::JavaThreadRef;
-use std::sync::mpsc;
+use std::cell::Cell;
+use std::collections::VecDeque;
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
-use tracing::{debug, error, info, trace, warn};
+use std::time::Duration;
+use tracing::{debug, error, info, trace};
 
+/// How long a worker beyond the core ones stays idle before it exits.
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+/// Workers running Java threads.
+///
+/// A job never waits for a free worker: if every worker is busy, a new one
+/// is spawned, so Java threads waiting on each other can't starve the pool.
+/// Up to `core` idle workers are kept for reuse, the others exit after
+/// `keep_alive` without work.
 pub struct ThreadPool {
-    workers: Vec<Worker>,
-    sender: mpsc::Sender<Message>,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Signaled when a job is queued or the pool shuts down
+    available: Condvar,
+    /// Signaled when a worker exits
+    exited: Condvar,
+    core: usize,
+    keep_alive: Duration,
 }
 
-enum Message {
-    NewJob(Job),
-    Terminate,
+struct State {
+    queue: VecDeque<Job>,
+    workers: usize,
+    idle: usize,
+    busy: usize,
+    next_id: usize,
+    shutdown: bool,
 }
 
 trait FnBox {
@@ -26,85 +49,237 @@ impl<F: FnOnce()> FnBox for F {
 
 type Job = Box<dyn FnBox + Send + 'static>;
 
+thread_local! {
+    static RETIRED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Let the current worker exit after its job instead of being reused, for a
+/// job that changed the OS thread in a way it can't undo.
+pub fn retire_current_worker() {
+    RETIRED.with(|r| r.set(true));
+}
+
 impl ThreadPool {
     /// Create a new ThreadPool.
     ///
-    /// The size is the number of threads in the pool.
-    ///
-    /// # Panics
-    ///
-    /// The `new` function will panic if the size is zero.
-    pub fn new(size: usize) -> ThreadPool {
-        assert!(size > 0);
-
-        let (sender, receiver) = mpsc::channel();
-
-        let receiver = Arc::new(Mutex::new(receiver));
-
-        let mut workers = Vec::with_capacity(size);
+    /// `core` is the number of idle workers kept for reuse; workers are
+    /// spawned on demand.
+    pub fn new(core: usize) -> ThreadPool {
+        Self::with_keep_alive(core, KEEP_ALIVE)
+    }
 
-        for id in 0..size {
-            workers.push(Worker::new(id, Arc::clone(&receiver)));
+    /// Like `new`, with the idle time after which extra workers exit.
+    pub fn with_keep_alive(core: usize, keep_alive: Duration) -> ThreadPool {
+        ThreadPool {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    queue: VecDeque::new(),
+                    workers: 0,
+                    idle: 0,
+                    busy: 0,
+                    next_id: 0,
+                    shutdown: false,
+                }),
+                available: Condvar::new(),
+                exited: Condvar::new(),
+                core,
+                keep_alive,
+            }),
         }
-
-        ThreadPool { workers, sender }
     }
 
     pub fn execute<F>(&self, f: F)
     where
         F: FnOnce() + Send + 'static,
     {
-        let job = Box::new(f);
-        self.sender.send(Message::NewJob(job)).unwrap();
+        let mut state = self.shared.state.lock().unwrap();
+        state.queue.push_back(Box::new(f));
+
+        //an idle worker wakes up for each queued job
+        if state.idle >= state.queue.len() {
+            self.shared.available.notify_one();
+            return;
+        }
+
+        let id = state.next_id;
+        state.next_id += 1;
+        state.workers += 1;
+        drop(state);
+
+        let shared = self.shared.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("jvm-worker-{}", id))
+            .spawn(move || Worker { id, shared }.run());
+        if let Err(e) = spawned {
+            error!("Worker-{} can't be spawned: {}", id, e);
+            self.shared.state.lock().unwrap().workers -= 1;
+        }
+    }
+
+    /// Number of live workers, busy or idle.
+    pub fn worker_count(&self) -> usize {
+        self.shared.state.lock().unwrap().workers
     }
 }
 
 impl Drop for ThreadPool {
+    /// Stop the idle workers. Busy ones run daemon threads, which don't keep
+    /// the VM alive, so they are left to end with the process.
     fn drop(&mut self) {
-        info!("Sending terminate message to all workers.");
+        info!("Shutting down idle workers.");
 
-        for _ in &mut self.workers {
-            self.sender.send(Message::Terminate).unwrap();
+        let mut state = self.shared.state.lock().unwrap();
+        state.shutdown = true;
+        self.shared.available.notify_all();
+        while state.workers > state.busy {
+            state = self.shared.exited.wait(state).unwrap();
         }
 
-        info!("Shutting down all workers.");
-
-        for worker in &mut self.workers {
-            info!("Shutting down worker {}", worker.id);
-
-            if let Some(thread) = worker.thread.take() {
-                thread.join().unwrap();
-            }
+        if state.busy > 0 {
+            info!("{} workers still busy at shutdown", state.busy);
         }
     }
 }
 
 struct Worker {
     id: usize,
-    thread: Option<thread::JoinHandle<()>>,
+    shared: Arc<Shared>,
 }
 
 impl Worker {
-    fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Message>>>) -> Worker {
-        let thread = thread::spawn(move || loop {
-            let message = receiver.lock().unwrap().recv().unwrap();
-
-            match message {
-                Message::NewJob(job) => {
-                    warn!("Worker-{} got a job, executing", id);
-                    job.call_box();
-                    warn!("Worker-{}, fin", id);
-                }
-                Message::Terminate => {
-                    warn!("Worker {} was told to terminate.", id);
+    fn run(self) {
+        let shared = &self.shared;
+        let mut state = shared.state.lock().unwrap();
+        loop {
+            if let Some(job) = state.queue.pop_front() {
+                state.busy += 1;
+                drop(state);
+
+                debug!("Worker-{} got a job, executing", self.id);
+                job.call_box();
+                debug!("Worker-{}, fin", self.id);
+
+                state = shared.state.lock().unwrap();
+                state.busy -= 1;
+                if RETIRED.with(Cell::get) {
+                    debug!("Worker-{} retired", self.id);
                     break;
                 }
+                continue;
             }
+
+            if state.shutdown {
+                break;
+            }
+
+            state.idle += 1;
+            let (s, timeout) = shared
+                .available
+                .wait_timeout(state, shared.keep_alive)
+                .unwrap();
+            state = s;
+            state.idle -= 1;
+
+            //keep 'core' workers, reclaim the others
+            if timeout.timed_out() && state.queue.is_empty() && state.workers > shared.core {
+                break;
+            }
+        }
+
+        trace!("Worker-{} exits", self.id);
+        state.workers -= 1;
+        shared.exited.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Barrier;
+    use std::time::Instant;
+
+    #[test]
+    fn jobs_waiting_on_each_other_get_workers() {
+        let pool = ThreadPool::new(1);
+        let barrier = Arc::new(Barrier::new(4));
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..3 {
+            let barrier = barrier.clone();
+            let tx = tx.clone();
+            pool.execute(move || {
+                barrier.wait();
+                tx.send(()).unwrap();
+            });
+        }
+        barrier.wait();
+        for _ in 0..3 {
+            rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        }
+        assert!(pool.worker_count() >= 3);
+    }
+
+    #[test]
+    fn idle_workers_are_reused_and_reclaimed() {
+        let pool = ThreadPool::with_keep_alive(1, Duration::from_millis(50));
+        let barrier = Arc::new(Barrier::new(5));
+        for _ in 0..4 {
+            let barrier = barrier.clone();
+            pool.execute(move || {
+                barrier.wait();
+            });
+        }
+        barrier.wait();
+        assert_eq!(pool.worker_count(), 4);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while pool.worker_count() > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(pool.worker_count(), 1);
+
+        //the core worker runs the next job
+        let (tx, rx) = mpsc::channel();
+        pool.execute(move || tx.send(()).unwrap());
+        rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(pool.worker_count(), 1);
+    }
+
+    #[test]
+    fn retired_workers_are_not_reused() {
+        let pool = ThreadPool::new(2);
+        let (tx, rx) = mpsc::channel();
+        pool.execute(move || {
+            retire_current_worker();
+            tx.send(thread::current().id()).unwrap();
         });
+        let retired = rx.recv_timeout(Duration::from_secs(10)).unwrap();
 
-        Worker {
-            id,
-            thread: Some(thread),
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while pool.worker_count() > 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
         }
+        assert_eq!(pool.worker_count(), 0);
+
+        let (tx, rx) = mpsc::channel();
+        pool.execute(move || tx.send(thread::current().id()).unwrap());
+        let next = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_ne!(next, retired);
+    }
+
+    #[test]
+    fn shutdown_leaves_busy_workers() {
+        let pool = ThreadPool::new(2);
+        let (started_tx, started_rx) = mpsc::channel();
+        let (_block_tx, block_rx) = mpsc::channel::<()>();
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            //never returns while the test runs, like a daemon thread
+            let _ = block_rx.recv();
+        });
+        started_rx.recv().unwrap();
+        pool.execute(|| ());
+
+        drop(pool);
     }
 }
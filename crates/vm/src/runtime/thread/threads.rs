@@ -1,5 +1,6 @@
+use crate::oop::Oop;
 use crate::runtime;
-use crate::runtime::thread::ThreadPool;
+use crate::runtime::thread::{ThreadPool, MAIN_THREAD_ID};
 use crate::types::JavaThreadRef;
 use std::borrow::Borrow;
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
@@ -13,12 +14,14 @@ pub struct Threads {
 }
 
 impl Threads {
+    /// `thread_pool_count` idle workers are kept for reuse, more are
+    /// spawned when all are busy.
     pub fn new(thread_pool_count: usize) -> Threads {
         Threads {
             pool: Mutex::new(ThreadPool::new(thread_pool_count)),
             threads: Mutex::new(Vec::new()),
             cond_join: Condvar::new(),
-            next_id: AtomicI64::new(1),
+            next_id: AtomicI64::new(MAIN_THREAD_ID + 1),
         }
     }
 }
@@ -29,9 +32,12 @@ impl Threads {
     }
 
     pub fn attach_current_thread(&self) {
+        runtime::thread::safepoint::attach_current_thread();
         runtime::thread::THREAD.with(|thread| {
+            let thread = thread.borrow().clone();
+            thread.write().unwrap().bind_os_thread();
             let mut threads = self.threads.lock().unwrap();
-            threads.push(thread.borrow().clone());
+            threads.push(thread);
         });
     }
 
@@ -41,6 +47,7 @@ impl Threads {
     }
 
     pub fn detach_current_thread(&self) {
+        runtime::thread::safepoint::detach_current_thread();
         runtime::thread::THREAD.with(|thread| {
             let mut threads = self.threads.lock().unwrap();
             threads.retain(|elem| !Arc::ptr_eq(elem, &*thread.borrow()));
@@ -56,10 +63,34 @@ impl Threads {
             .cloned()
     }
 
+    /// Find the thread whose `java.lang.Thread` object is `obj`.
+    pub fn find_by_thread_obj(&self, obj: &Oop) -> Option<JavaThreadRef> {
+        let slot_id = match obj {
+            Oop::Ref(slot_id) => *slot_id,
+            _ => return None,
+        };
+        let threads = self.threads.lock().unwrap();
+        threads
+            .iter()
+            .find(|t| matches!(t.read().unwrap().java_thread_obj, Some(Oop::Ref(id)) if id == slot_id))
+            .cloned()
+    }
+
+    /// All attached threads.
+    pub fn all(&self) -> Vec<JavaThreadRef> {
+        self.threads.lock().unwrap().clone()
+    }
+
+    /// Wait for all non-daemon threads but the current one to end. Daemon
+    /// threads are left running and end with the process.
     pub fn join_all(&self) {
+        let current = runtime::thread::current_java_thread();
         let mut threads = self.threads.lock().unwrap();
 
-        while threads.len() > 0 {
+        while threads
+            .iter()
+            .any(|t| !Arc::ptr_eq(t, &current) && !t.read().unwrap().daemon)
+        {
             threads = self.cond_join.wait(threads).unwrap();
         }
     }
@@ -0,0 +1,66 @@
+//! VM operations: work that must see all Java threads stopped.
+//!
+//! Operations are queued and run by the "VM Thread", which stops the world
+//! once per batch. The requesting thread waits as `IN_NATIVE`, so it doesn't
+//! hold up the safepoint it asked for.
+
+use crate::runtime::thread::safepoint;
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::sync::{Condvar, Mutex, Once};
+use std::thread;
+use tracing::trace;
+
+struct VmOperation {
+    name: &'static str,
+    op: Box<dyn FnOnce() + Send>,
+}
+
+static QUEUE: Mutex<VecDeque<VmOperation>> = Mutex::new(VecDeque::new());
+static PENDING: Condvar = Condvar::new();
+static START: Once = Once::new();
+
+/// Run `op` at a safepoint on the VM Thread and return its result.
+///
+/// `op` must not run Java code or wait for a Java thread.
+pub fn execute<R, F>(name: &'static str, op: F) -> R
+where
+    R: Send + 'static,
+    F: FnOnce() -> R + Send + 'static,
+{
+    START.call_once(|| {
+        thread::Builder::new()
+            .name("VM Thread".to_string())
+            .spawn(vm_thread_loop)
+            .expect("spawn VM Thread");
+    });
+
+    let (tx, rx) = mpsc::channel();
+    let op = Box::new(move || {
+        let _ = tx.send(op());
+    });
+    QUEUE.lock().unwrap().push_back(VmOperation { name, op });
+    PENDING.notify_one();
+
+    safepoint::in_native(|| rx.recv())
+        .unwrap_or_else(|_| panic!("VM operation '{}' did not complete", name))
+}
+
+fn vm_thread_loop() {
+    loop {
+        let batch: Vec<VmOperation> = {
+            let mut queue = QUEUE.lock().unwrap();
+            while queue.is_empty() {
+                queue = PENDING.wait(queue).unwrap();
+            }
+            queue.drain(..).collect()
+        };
+
+        safepoint::run_at_safepoint(|| {
+            for it in batch {
+                trace!("vm operation: {}", it.name);
+                (it.op)();
+            }
+        });
+    }
+}
@@ -1,5 +1,6 @@
 use crate::runtime::thread::Threads;
 use std::ptr;
+use tracing::info;
 
 static mut VM_GLOBAL: *const u8 = ptr::null();
 
@@ -30,3 +31,11 @@ impl VM {
         vm
     }
 }
+
+/// Exit the process right away with `code`. Shutdown hooks have run
+/// already (`Runtime.exit`) or are skipped (`Runtime.halt`); other threads,
+/// daemon or not, are stopped where they are.
+pub fn halt(code: i32) -> ! {
+    info!("halt: {}", code);
+    std::process::exit(code)
+}
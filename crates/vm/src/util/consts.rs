@@ -6,7 +6,6 @@ pub static S_INIT: OnceLock<BytesRef> = OnceLock::new();
 pub static S_CLINIT: OnceLock<BytesRef> = OnceLock::new();
 pub static S_CLINIT_SIG: OnceLock<BytesRef> = OnceLock::new();
 pub static S_RUN_SIG: OnceLock<BytesRef> = OnceLock::new();
-pub static S_NEW_STRING_SIG: OnceLock<BytesRef> = OnceLock::new();
 pub static S_CLAZZ: OnceLock<BytesRef> = OnceLock::new();
 pub static S_FD: OnceLock<BytesRef> = OnceLock::new();
 pub static S_I: OnceLock<BytesRef> = OnceLock::new();
@@ -19,9 +18,11 @@ pub static S_RUN: OnceLock<BytesRef> = OnceLock::new();
 pub static S_ERR: OnceLock<BytesRef> = OnceLock::new();
 pub static S_OUT: OnceLock<BytesRef> = OnceLock::new();
 pub static S_IN: OnceLock<BytesRef> = OnceLock::new();
+pub static S_MODULE: OnceLock<BytesRef> = OnceLock::new();
 pub static S_JAVA_LANG_CLASS: OnceLock<BytesRef> = OnceLock::new();
 pub static S_JAVA_LANG_OBJECT: OnceLock<BytesRef> = OnceLock::new();
 pub static S_JAVA_LANG_STRING: OnceLock<BytesRef> = OnceLock::new();
+pub static S_JAVA_LANG_MODULE: OnceLock<BytesRef> = OnceLock::new();
 pub static S_JAVA_IO_FD: OnceLock<BytesRef> = OnceLock::new();
 pub static S_JAVA_IO_PRINT_STREAM: OnceLock<BytesRef> = OnceLock::new();
 pub static S_JAVA_IO_INPUT_STREAM: OnceLock<BytesRef> = OnceLock::new();
@@ -31,7 +32,6 @@ pub fn init() {
     S_CLINIT.get_or_init(|| new_br("<clinit>"));
     S_CLINIT_SIG.get_or_init(|| new_br("()V"));
     S_RUN_SIG.get_or_init(|| new_br("()Ljava/lang/Object;"));
-    S_NEW_STRING_SIG.get_or_init(|| new_br("([C)V"));
     S_CLAZZ.get_or_init(|| new_br("clazz"));
     S_FD.get_or_init(|| new_br("fd"));
     S_I.get_or_init(|| new_br("I"));
@@ -44,9 +44,11 @@ pub fn init() {
     S_ERR.get_or_init(|| new_br("err"));
     S_OUT.get_or_init(|| new_br("out"));
     S_IN.get_or_init(|| new_br("in"));
+    S_MODULE.get_or_init(|| new_br("module"));
     S_JAVA_LANG_CLASS.get_or_init(|| new_br("Ljava/lang/Class;"));
     S_JAVA_LANG_OBJECT.get_or_init(|| new_br("Ljava/lang/Object;"));
     S_JAVA_LANG_STRING.get_or_init(|| new_br("Ljava/lang/String;"));
+    S_JAVA_LANG_MODULE.get_or_init(|| new_br("Ljava/lang/Module;"));
     S_JAVA_IO_FD.get_or_init(|| new_br("Ljava/io/FileDescriptor;"));
     S_JAVA_IO_PRINT_STREAM.get_or_init(|| new_br("Ljava/io/PrintStream;"));
     S_JAVA_IO_INPUT_STREAM.get_or_init(|| new_br("Ljava/io/InputStream;"));
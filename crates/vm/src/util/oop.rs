@@ -1,17 +1,27 @@
 use crate::oop::class::Class;
-use crate::oop::{self, Oop};
-use crate::runtime::{self, require_class3};
-use crate::util;
+use crate::oop::Oop;
+use crate::runtime::require_class3;
 
 static mut JAVA_LANG_STRING_VALUE_OFFSET: usize = 0;
+static mut JAVA_LANG_STRING_CODER_OFFSET: Option<usize> = None;
 static mut JAVA_LANG_INTEGER_VALUE_OFFSET: usize = 0;
 
+/// `String.coder` of JDK 9+
+pub const STRING_LATIN1: i32 = 0;
+pub const STRING_UTF16: i32 = 1;
+
 pub fn set_java_lang_string_value_offset(offset: usize) {
     unsafe {
         JAVA_LANG_STRING_VALUE_OFFSET = offset;
     }
 }
 
+pub fn set_java_lang_string_coder_offset(offset: usize) {
+    unsafe {
+        JAVA_LANG_STRING_CODER_OFFSET = Some(offset);
+    }
+}
+
 pub fn set_java_lang_integer_value_offset(offset: usize) {
     unsafe {
         JAVA_LANG_INTEGER_VALUE_OFFSET = offset;
@@ -22,11 +32,22 @@ pub fn get_java_lang_string_value_offset() -> usize {
     unsafe { JAVA_LANG_STRING_VALUE_OFFSET }
 }
 
+pub fn get_java_lang_string_coder_offset() -> Option<usize> {
+    unsafe { JAVA_LANG_STRING_CODER_OFFSET }
+}
+
 pub fn get_java_lang_integer_value_offset() -> usize {
     unsafe { JAVA_LANG_INTEGER_VALUE_OFFSET }
 }
 
 pub fn new_java_lang_string_direct(v: &str) -> Oop {
+    let chars: Vec<u16> = v.encode_utf16().collect();
+    new_java_lang_string_utf16(&chars)
+}
+
+/// Builds the String without running a constructor: `String(char[])` of
+/// JDK 9+ loads the "" literal itself, which would come back here.
+pub fn new_java_lang_string_utf16(chars: &[u16]) -> Oop {
     use crate::new_br;
 
     let string_cls = require_class3(None, b"java/lang/String").unwrap();
@@ -37,23 +58,31 @@ pub fn new_java_lang_string_direct(v: &str) -> Oop {
     };
     let cls = string_cls.get_class();
 
-    // Try JDK 9+ byte[] value field
+    // JDK 9+: byte[] value, LATIN1 or UTF16 by coder
     if let Ok(value_fid) = cls.get_field_id_safe(&new_br("value"), &new_br("[B"), false) {
-        let bytes = v.as_bytes().to_vec();
+        let (bytes, coder) = if chars.iter().all(|&c| c < 256) {
+            (chars.iter().map(|&c| c as u8).collect(), STRING_LATIN1)
+        } else {
+            let bytes = chars
+                .iter()
+                .flat_map(|c| {
+                    if cfg!(target_endian = "big") {
+                        c.to_be_bytes()
+                    } else {
+                        c.to_le_bytes()
+                    }
+                })
+                .collect();
+            (bytes, STRING_UTF16)
+        };
         let byte_ary = Oop::new_byte_ary2(bytes);
         Class::put_field_value2(slot_id, value_fid.offset, byte_ary);
 
-        let coder = if v.bytes().all(|b| b < 128) {
-            0i32
-        } else {
-            1i32
-        };
         if let Ok(coder_fid) = cls.get_field_id_safe(&new_br("coder"), &new_br("B"), false) {
-            Class::put_field_value2(slot_id, coder_fid.offset, Oop::Int(coder));
+            Class::put_field_value2(slot_id, coder_fid.offset, Oop::new_int(coder));
         }
     } else {
-        let chars: Vec<u16> = v.encode_utf16().collect();
-        let ary = Oop::char_ary_from1(&chars);
+        let ary = Oop::char_ary_from1(chars);
         if let Ok(value_fid) = cls.get_field_id_safe(&new_br("value"), &new_br("[C"), false) {
             Class::put_field_value2(slot_id, value_fid.offset, ary);
         }
@@ -63,38 +92,10 @@ pub fn new_java_lang_string_direct(v: &str) -> Oop {
 }
 
 pub fn new_java_lang_string2(v: &str) -> Oop {
-    //build "char value[]"
-    let chars: Vec<u16> = v.as_bytes().iter().map(|v| *v as u16).collect();
-    let ary = Oop::char_ary_from1(chars.as_slice());
-
-    //new String(char value[])
-    let string_cls = require_class3(None, b"java/lang/String").unwrap();
-    let string_oop = Oop::new_inst(string_cls.clone());
-    let args = vec![string_oop.clone(), ary];
-    runtime::invoke::invoke_ctor(
-        string_cls,
-        util::S_NEW_STRING_SIG.get().unwrap().clone(),
-        args,
-    );
-
-    string_oop
+    new_java_lang_string_direct(v)
 }
 
 pub fn new_java_lang_string3(bs: &[u8]) -> Oop {
     let buffer = classfile::constant_pool::construct_string_raw(bs);
-
-    //build "char value[]"
-    let ary = Oop::char_ary_from1(buffer.as_slice());
-
-    //new String(char value[])
-    let string_cls = require_class3(None, b"java/lang/String").unwrap();
-    let string_oop = Oop::new_inst(string_cls.clone());
-    let args = vec![string_oop.clone(), ary];
-    runtime::invoke::invoke_ctor(
-        string_cls,
-        util::S_NEW_STRING_SIG.get().unwrap().clone(),
-        args,
-    );
-
-    string_oop
+    new_java_lang_string_utf16(buffer.as_slice())
 }
@@ -0,0 +1,7 @@
+fn main() {
+    // Export the JIT runtime functions (`jit_invoke_*`, ...) from the executable,
+    // so code loaded at run time (JIT modules, AOT libraries) can resolve them.
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux") {
+        println!("cargo:rustc-link-arg-bins=-rdynamic");
+    }
+}
@@ -2,6 +2,7 @@ mod options;
 
 use tracing_subscriber;
 use vm;
+use vm::runtime::jit::opt::JitLevel;
 use vm::runtime::{self, thread::MainThread};
 use vm::util;
 
@@ -13,16 +14,202 @@ fn main() {
 
     let opt = options::parse();
 
-    if let Some(cp) = &opt.cp {
-        runtime::add_class_paths(cp);
+    let share = match opt.share.parse() {
+        Ok(share) => share,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let boot = runtime::BootClassPath {
+        java_home: opt.java_home.clone(),
+        patch_modules: opt.patch_module.clone(),
+        append: opt.bootclasspath_append.clone(),
+        share,
+        shared_archive_file: opt.shared_archive_file.clone(),
+    };
+    if let Err(e) = runtime::init_boot_class_path(&boot) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
     }
 
-    if let Some(classpath) = &opt.classpath {
-        runtime::add_class_path(classpath);
+    // like `java`, -jar ignores the class path options
+    let main_class = match &opt.jar {
+        Some(jar) => Some(jar_main_class(jar)),
+        None => {
+            if let Some(cp) = &opt.cp {
+                runtime::add_class_paths(cp);
+            }
+
+            if let Some(classpath) = &opt.classpath {
+                runtime::add_class_path(classpath);
+            }
+            None
+        }
+    };
+
+    // like `java`, the class path stays the unnamed module of `-m`
+    let main_class = match &opt.module {
+        Some(module) => Some(module_main_class(
+            opt.module_path.as_deref().unwrap_or(""),
+            module,
+        )),
+        None => main_class,
+    };
+
+    if let Some(level) = &opt.jit_level {
+        match level.parse() {
+            Ok(level) => runtime::jit::opt::set_default_level(level),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    for spec in &opt.jit_method_level {
+        if let Err(e) = runtime::jit::opt::add_method_level(spec) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if opt.print_compilation {
+        runtime::jit::dump::set_print_compilation(true);
+    }
+
+    if let Some(dir) = &opt.jit_dump_dir {
+        if let Err(e) = runtime::jit::dump::set_dump_dir(dir.into()) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    for pattern in &opt.jit_dump {
+        if let Err(e) = runtime::jit::dump::add_dump_filter(pattern) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(jar) = &opt.aot_compile {
+        let output = opt.aot_output.clone().unwrap_or_else(|| {
+            std::path::Path::new(jar)
+                .with_extension("so")
+                .to_string_lossy()
+                .to_string()
+        });
+        // AOT compilation always optimizes; `--jit-level` picks how much
+        let level = match runtime::jit::opt::default_level() {
+            JitLevel::Off => JitLevel::O2,
+            level => level,
+        };
+        match runtime::jit::aot::compile_jar(jar, &output, level) {
+            Ok(n) => {
+                println!("compiled {} methods into {}", n, output);
+                return;
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(lib) = &opt.aot_cache {
+        if let Err(e) = runtime::jit::aot::load(lib) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
     }
 
-    let class = opt.class;
+    let class = match main_class.or(opt.class) {
+        Some(class) => class,
+        //`--share dump` alone archives the classes loaded by VM startup
+        None => {
+            report_dump(runtime::cds::dump_boot());
+            return;
+        }
+    };
     let args = opt.args;
     let mut thread = MainThread::new(class.replace(".", util::FILE_SEP), args);
-    thread.run();
+    let code = thread.run();
+    //like `-XX:ArchiveClassesAtExit`, only when main returns, not on System.exit
+    if runtime::cds::dumping() {
+        report_dump(runtime::cds::dump());
+    }
+    std::process::exit(code);
+}
+
+fn report_dump(r: Result<(std::path::PathBuf, usize), String>) {
+    match r {
+        Ok((path, n)) => println!("archived {} classes into {}", n, path.display()),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Put an executable jar and its Class-Path entries on the class path and
+/// return its Main-Class.
+fn jar_main_class(jar: &str) -> String {
+    let manifest = match runtime::read_manifest(jar) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Error: Unable to access jarfile {}: {}", jar, e);
+            std::process::exit(1);
+        }
+    };
+
+    runtime::add_class_path(jar);
+    // missing entries are skipped, as `java` does
+    for path in &manifest.class_path {
+        if std::path::Path::new(path).exists() {
+            runtime::add_class_path(path);
+        }
+    }
+
+    if let Some(agent) = &manifest.launcher_agent_class {
+        eprintln!(
+            "Warning: Launcher-Agent-Class {} ignored, agents are not supported",
+            agent
+        );
+    }
+
+    match manifest.main_class {
+        Some(class) => class,
+        None => {
+            eprintln!("no main manifest attribute, in {}", jar);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Resolve the `-m module[/class]` root from the module path and the runtime
+/// image and return the class to run.
+fn module_main_class(module_path: &str, module: &str) -> String {
+    let (name, class) = match module.split_once('/') {
+        Some((name, class)) => (name, Some(class.to_string())),
+        None => (module, None),
+    };
+
+    let main = match runtime::module::resolve(module_path, name) {
+        Ok(main) => main,
+        Err(e) => {
+            eprintln!("Error occurred during initialization of boot layer\n{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match class.or(main) {
+        Some(class) => class,
+        None => {
+            eprintln!(
+                "module {} does not have a ModuleMainClass attribute, use -m <module>/<main-class>",
+                name
+            );
+            std::process::exit(1);
+        }
+    }
 }
@@ -1,4 +1,5 @@
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 
 #[derive(Parser, Debug)]
 #[command(version)]
@@ -11,12 +12,241 @@ pub struct Opt {
     #[arg(long)]
     pub classpath: Option<String>,
 
-    #[arg(required = true)]
-    pub class: String,
+    /// JIT optimization level: off, 0, 1, 2 or 3
+    #[arg(long)]
+    pub jit_level: Option<String>,
+
+    /// per-method JIT level as Class.method=LEVEL, `*` matches any class or method
+    #[arg(long)]
+    pub jit_method_level: Vec<String>,
+
+    /// print a line for every method compiled by the JIT
+    #[arg(long)]
+    pub print_compilation: bool,
+
+    /// write the LLVM IR (.ll) and assembly (.s) of compiled methods into DIR
+    #[arg(long, value_name = "DIR")]
+    pub jit_dump_dir: Option<String>,
+
+    /// only dump methods matching Class.method, `*` matches any class or method
+    #[arg(long, value_name = "PATTERN")]
+    pub jit_dump: Vec<String>,
+
+    /// compile all methods of JAR ahead of time into a shared library and exit
+    #[arg(long, value_name = "JAR")]
+    pub aot_compile: Option<String>,
+
+    /// output of --aot-compile, defaults to the jar path with a `.so` extension
+    #[arg(long, value_name = "FILE")]
+    pub aot_output: Option<String>,
+
+    /// load precompiled methods from a library built by --aot-compile
+    #[arg(long, value_name = "FILE")]
+    pub aot_cache: Option<String>,
+
+    /// JDK providing the boot classes from lib/modules, or jre/lib/rt.jar of
+    /// JDK 8; defaults to JAVA_HOME, then the usual install locations
+    #[arg(long, value_name = "DIR")]
+    pub java_home: Option<String>,
+
+    /// search PATHS before the runtime image for classes of MODULE
+    #[arg(long, value_name = "MODULE=PATHS")]
+    pub patch_module: Vec<String>,
+
+    /// append PATHS to the boot class path, also accepted as `-Xbootclasspath/a:PATHS`
+    #[arg(long, value_name = "PATHS")]
+    pub bootclasspath_append: Vec<String>,
+
+    /// run the Main-Class of an executable jar, also accepted as `-jar`;
+    /// the jar and its Class-Path entries replace --cp
+    #[arg(long, value_name = "JAR")]
+    pub jar: Option<String>,
+
+    /// directories of modules and modular or plain jars, also accepted as `-p`
+    #[arg(long, value_name = "PATHS")]
+    pub module_path: Option<String>,
+
+    /// run the main class of MODULE, or CLASS in it, also accepted as `-m`;
+    /// the module is resolved from --module-path and the runtime image
+    #[arg(long, value_name = "MODULE[/CLASS]")]
+    pub module: Option<String>,
+
+    /// class data sharing: off, auto, on, or dump the boot classes loaded by
+    /// CLASS (or by VM startup alone) into the archive; also accepted as
+    /// `-Xshare:MODE`
+    #[arg(long, value_name = "MODE", default_value = "auto")]
+    pub share: String,
+
+    /// the class data sharing archive, also accepted as
+    /// `-XX:SharedArchiveFile=FILE`; defaults to a file per runtime image in
+    /// the user cache directory
+    #[arg(long, value_name = "FILE")]
+    pub shared_archive_file: Option<String>,
+
+    /// main class to run; not needed with --jar, --module, --aot-compile or
+    /// --share dump
+    pub class: Option<String>,
 
     pub args: Vec<String>,
 }
 
+/// Options without a value; the argument after any other option is its value.
+const FLAGS: &[&str] = &["--print-compilation", "--help", "--version", "-h", "-V"];
+
 pub fn parse() -> Opt {
-    Opt::parse()
+    parse_from(std::env::args().collect())
+}
+
+fn parse_from(args: Vec<String>) -> Opt {
+    let mut opt = Opt::parse_from(end_of_options(args));
+    //only `--share dump` runs without a main class
+    let has_main = opt.class.is_some() || opt.jar.is_some() || opt.module.is_some();
+    if !has_main && opt.aot_compile.is_none() && opt.share != "dump" {
+        Opt::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the following required arguments were not provided:\n  <CLASS>",
+            )
+            .exit();
+    }
+    //with a jar or a module, every positional is an argument of the program
+    if opt.jar.is_some() || opt.module.is_some() {
+        if let Some(first) = opt.class.take() {
+            opt.args.insert(0, first);
+        }
+    }
+    opt
+}
+
+/// Like `java`, stop option parsing at the main class or after `-jar app.jar`
+/// or `-m module`, so the arguments of the program may look like options.
+/// `java`'s `-jar`, `-p`, `-m`, `-Xbootclasspath/a:`, `-Xshare:` and
+/// `-XX:SharedArchiveFile=` are rewritten to their long forms.
+fn end_of_options(args: Vec<String>) -> Vec<String> {
+    let mut out = Vec::with_capacity(args.len() + 1);
+    let mut args = args.into_iter();
+    out.extend(args.next());
+
+    let mut value_next = false;
+    while let Some(arg) = args.next() {
+        if value_next {
+            value_next = false;
+        } else if let Some(paths) = arg.strip_prefix("-Xbootclasspath/a:") {
+            out.push(format!("--bootclasspath-append={}", paths));
+            continue;
+        } else if let Some(mode) = arg.strip_prefix("-Xshare:") {
+            out.push(format!("--share={}", mode));
+            continue;
+        } else if let Some(file) = arg.strip_prefix("-XX:SharedArchiveFile=") {
+            out.push(format!("--shared-archive-file={}", file));
+            continue;
+        } else if arg == "-p" {
+            out.push("--module-path".to_string());
+            value_next = true;
+            continue;
+        } else if ["-jar", "--jar", "-m", "--module"].contains(&arg.as_str()) {
+            let long = if arg.ends_with("jar") {
+                "--jar"
+            } else {
+                "--module"
+            };
+            out.push(long.to_string());
+            out.extend(args.next());
+            out.push("--".to_string());
+            out.extend(args);
+            break;
+        } else if arg.starts_with("--jar=") || arg.starts_with("--module=") {
+            out.push(arg);
+            out.push("--".to_string());
+            out.extend(args);
+            break;
+        } else if arg == "--" {
+            out.push(arg);
+            out.extend(args);
+            break;
+        } else if !arg.starts_with('-') {
+            out.push("--".to_string());
+            out.push(arg);
+            out.extend(args);
+            break;
+        } else {
+            value_next =
+                arg.starts_with("--") && !arg.contains('=') && !FLAGS.contains(&arg.as_str());
+        }
+        out.push(arg);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Opt {
+        parse_from(args.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn t_jar() {
+        let opt = parse_args(&["jvm", "--jit-level", "off", "-jar", "app.jar", "-v", "x"]);
+        assert_eq!(opt.jar.as_deref(), Some("app.jar"));
+        assert_eq!(opt.jit_level.as_deref(), Some("off"));
+        assert_eq!(opt.class, None);
+        assert_eq!(opt.args, vec!["-v", "x"]);
+
+        let opt = parse_args(&["jvm", "--print-compilation", "--jar", "app.jar"]);
+        assert_eq!(opt.jar.as_deref(), Some("app.jar"));
+        assert!(opt.args.is_empty());
+
+        //a `-jar` after the main class belongs to the program
+        let opt = parse_args(&["jvm", "--cp", "out", "Main", "-jar", "a.jar"]);
+        assert_eq!(opt.jar, None);
+        assert_eq!(opt.class.as_deref(), Some("Main"));
+        assert_eq!(opt.args, vec!["-jar", "a.jar"]);
+    }
+
+    #[test]
+    fn t_boot_class_path() {
+        let opt = parse_args(&[
+            "jvm",
+            "-Xbootclasspath/a:a.jar:b",
+            "--patch-module",
+            "java.base=patch",
+            "--bootclasspath-append",
+            "c",
+            "Main",
+        ]);
+        assert_eq!(opt.bootclasspath_append, vec!["a.jar:b", "c"]);
+        assert_eq!(opt.patch_module, vec!["java.base=patch"]);
+        assert_eq!(opt.class.as_deref(), Some("Main"));
+    }
+
+    #[test]
+    fn t_module() {
+        let opt = parse_args(&["jvm", "-p", "mods:lib", "-m", "app/app.Main", "-p", "x"]);
+        assert_eq!(opt.module_path.as_deref(), Some("mods:lib"));
+        assert_eq!(opt.module.as_deref(), Some("app/app.Main"));
+        assert_eq!(opt.class, None);
+        assert_eq!(opt.args, vec!["-p", "x"]);
+
+        let opt = parse_args(&["jvm", "--module-path", "mods", "--module=app", "a"]);
+        assert_eq!(opt.module_path.as_deref(), Some("mods"));
+        assert_eq!(opt.module.as_deref(), Some("app"));
+        assert_eq!(opt.args, vec!["a"]);
+    }
+
+    #[test]
+    fn t_share() {
+        let opt = parse_args(&["jvm", "Main"]);
+        assert_eq!(opt.share, "auto");
+
+        let opt = parse_args(&["jvm", "-Xshare:dump", "-XX:SharedArchiveFile=a.jsa"]);
+        assert_eq!(opt.share, "dump");
+        assert_eq!(opt.shared_archive_file.as_deref(), Some("a.jsa"));
+        assert_eq!(opt.class, None);
+
+        let opt = parse_args(&["jvm", "-Xshare:off", "Main", "-Xshare:on"]);
+        assert_eq!(opt.share, "off");
+        assert_eq!(opt.args, vec!["-Xshare:on"]);
+    }
 }
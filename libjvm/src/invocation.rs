@@ -352,9 +352,24 @@ extern "C" fn JNI_CreateJavaVM(
 		vm::native::init();
 		vm::oop::init();
 		vm::runtime::init();
-		// To be run from jre dir
-		vm::runtime::add_class_path("./lib/rt.jar");
-		vm::runtime::add_class_path("./lib/jsse.jar");
+		// To be run from a JRE dir, which is then java.home; elsewhere the JDK
+		// is located as the launcher does
+		let mut boot = vm::runtime::BootClassPath::default();
+		if let Ok(cwd) = std::env::current_dir() {
+			if let Some(image) = vm::runtime::probe_runtime_image(&cwd) {
+				if let vm::runtime::RuntimeImage::RtJar(rt) = &image {
+					let jsse = rt.with_file_name("jsse.jar");
+					if jsse.is_file() {
+						boot.append.push(jsse.to_string_lossy().into_owned());
+					}
+				}
+				boot.java_home = Some(cwd.to_string_lossy().into_owned());
+			}
+		}
+		if let Err(e) = vm::runtime::init_boot_class_path(&boot) {
+			eprintln!("Error: {}", e);
+			return -1;
+		}
 		let args = unsafe { &*args };
 		// TODO: Pass to jvm
 		let mut properties: std::collections::HashMap<String, String> =
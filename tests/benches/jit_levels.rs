@@ -0,0 +1,63 @@
+//! Runs `JitBench` once per JIT level and prints the wall-clock time of each run.
+//!
+//! ```bash
+//! cargo build -p jvm && cargo bench -p java-tests --bench jit_levels
+//! ```
+//!
+//! The number of rounds can be overridden with `JIT_BENCH_ROUNDS`.
+
+use std::env;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const LEVELS: &[&str] = &["off", "0", "1", "2", "3"];
+const DEFAULT_ROUNDS: &str = "200";
+
+fn main() {
+    let class_dir = env!("JAVA_TEST_DIR");
+    let jvm_bin = env!("JVM_BIN");
+    let rounds = env::var("JIT_BENCH_ROUNDS").unwrap_or_else(|_| DEFAULT_ROUNDS.to_string());
+
+    println!("JitBench, {} rounds\n", rounds);
+    println!("{:<8} {:>12}", "level", "time");
+
+    let mut failed = false;
+    for level in LEVELS {
+        let start = Instant::now();
+        let output = Command::new(jvm_bin)
+            .arg("--cp")
+            .arg(class_dir)
+            .arg("--jit-level")
+            .arg(level)
+            .arg("JitBench")
+            .arg(&rounds)
+            .output();
+        let elapsed = start.elapsed();
+
+        match output {
+            Ok(out) if out.status.success() => {
+                println!("{:<8} {:>12}", level, format_duration(elapsed));
+            }
+            Ok(out) => {
+                let stderr = String::from_utf8_lossy(&out.stderr);
+                println!("{:<8} {:>12}", level, "FAILED");
+                for line in stderr.lines().take(3) {
+                    println!("    {}", line);
+                }
+                failed = true;
+            }
+            Err(e) => {
+                println!("{:<8} {:>12} ({})", level, "FAILED", e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.3}s", d.as_secs_f64())
+}
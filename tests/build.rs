@@ -1,6 +1,6 @@
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 fn main() {
@@ -30,6 +30,11 @@ fn main() {
     // Build list of testable classes (only files with main methods)
     let mut classes: Vec<String> = java_files
         .iter()
+        .filter(|p| {
+            fs::read_to_string(p)
+                .map(|src| src.contains("public static void main("))
+                .unwrap_or(false)
+        })
         .map(|p| p.file_stem().unwrap().to_str().unwrap().to_string())
         .collect();
     classes.sort();
@@ -38,6 +43,12 @@ fn main() {
     let list_path = out_dir.join("classes.txt");
     fs::write(&list_path, classes.join("\n")).unwrap();
 
+    let resources = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("java/resources");
+    copy_resources(&resources, &out_dir);
+    compile_modules(&out_dir);
+    build_jars(&out_dir);
+    compile_boot_paths(&out_dir);
+
     println!("cargo:rustc-env=JAVA_TEST_DIR={}", out_dir.display());
 
     // Pass the jvm binary path
@@ -45,8 +56,211 @@ fn main() {
         PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("../target/debug/jvm");
     println!("cargo:rustc-env=JVM_BIN={}", jvm_bin.display());
 
-    // Re-run if Java sources change
+    // Re-run if Java sources change or a test class is added
+    println!("cargo:rerun-if-changed={}", src_dir.display());
     for f in &java_files {
         println!("cargo:rerun-if-changed={}", f.display());
     }
 }
+
+/// Copy tests/java/resources onto the class path next to the classes.
+fn copy_resources(src: &Path, dst: &Path) {
+    let entries = match fs::read_dir(src) {
+        Ok(dir) => dir.filter_map(|e| e.ok()),
+        Err(_) => return,
+    };
+    for entry in entries {
+        let to = dst.join(entry.file_name());
+        if entry.path().is_dir() {
+            fs::create_dir_all(&to).unwrap();
+            copy_resources(&entry.path(), &to);
+        } else {
+            fs::copy(entry.path(), &to).unwrap();
+        }
+    }
+    println!("cargo:rerun-if-changed={}", src.display());
+}
+
+/// Compile the modules of tests/java/modules into OUT_DIR/mods and list the
+/// `module/class` of every `Main` class in modules.txt, to be run with `-m`.
+fn compile_modules(out_dir: &Path) {
+    let src_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("java/modules");
+    let modules: Vec<String> = match fs::read_dir(&src_dir) {
+        Ok(dir) => dir
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().join("module-info.java").is_file())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect(),
+        Err(_) => return,
+    };
+    if modules.is_empty() {
+        return;
+    }
+
+    // The tests check that the VM stops access to non-exported packages,
+    // so they are exported at compile time only
+    let status = Command::new("javac")
+        .arg("--module-source-path")
+        .arg(&src_dir)
+        .arg("-d")
+        .arg(out_dir.join("mods"))
+        .arg("--add-exports")
+        .arg("com.example.greet/com.example.greet.internal=com.example.app")
+        .arg("--module")
+        .arg(modules.join(","))
+        .status()
+        .expect("failed to run javac");
+    assert!(status.success(), "javac failed for modules");
+
+    let mut mains = vec![];
+    for module in &modules {
+        find_mains(&src_dir.join(module), module, "", &mut mains);
+    }
+    mains.sort();
+    fs::write(out_dir.join("modules.txt"), mains.join("\n")).unwrap();
+
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+}
+
+/// Compile each directory of tests/java/boot into OUT_DIR/boot/<name>, to be
+/// given to `--patch-module java.base=` or `-Xbootclasspath/a:`. The sources
+/// may be classes of `java.base`, so they compile as a patch of it.
+fn compile_boot_paths(out_dir: &Path) {
+    let src_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("java/boot");
+    let dirs: Vec<PathBuf> = match fs::read_dir(&src_dir) {
+        Ok(dir) => dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect(),
+        Err(_) => return,
+    };
+
+    for dir in &dirs {
+        let name = dir.file_name().unwrap().to_string_lossy().to_string();
+        let classes = out_dir.join("boot").join(&name);
+        let _ = fs::remove_dir_all(&classes);
+
+        let mut sources = vec![];
+        find_sources(dir, &mut sources);
+        let mut patch = std::ffi::OsString::from("java.base=");
+        patch.push(dir);
+        let status = Command::new("javac")
+            .arg("--patch-module")
+            .arg(patch)
+            .arg("-d")
+            .arg(&classes)
+            .args(&sources)
+            .status()
+            .expect("failed to run javac");
+        assert!(status.success(), "javac failed for boot/{}", name);
+    }
+
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+}
+
+fn find_sources(dir: &Path, sources: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        println!("cargo:rerun-if-changed={}", path.display());
+        if path.is_dir() {
+            find_sources(&path, sources);
+        } else if path.extension().is_some_and(|ext| ext == "java") {
+            sources.push(path);
+        }
+    }
+}
+
+/// Build OUT_DIR/jars/<name>.jar from each directory of tests/java/jars: its
+/// sources compiled against the test classes, its other files (such as
+/// `META-INF/services`) packed as they are.
+fn build_jars(out_dir: &Path) {
+    let src_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("java/jars");
+    let jars: Vec<PathBuf> = match fs::read_dir(&src_dir) {
+        Ok(dir) => dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect(),
+        Err(_) => return,
+    };
+
+    // a jar may use classes of the others, which come from their own jars at run time
+    let source_path = env::join_paths(&jars).unwrap();
+    for jar in &jars {
+        let name = jar.file_name().unwrap().to_string_lossy().to_string();
+        let classes = out_dir.join("jars").join(&name);
+        let _ = fs::remove_dir_all(&classes);
+        fs::create_dir_all(&classes).unwrap();
+
+        let mut sources = vec![];
+        copy_jar_files(jar, &classes, &mut sources);
+        if !sources.is_empty() {
+            let status = Command::new("javac")
+                .arg("-cp")
+                .arg(out_dir)
+                .arg("-sourcepath")
+                .arg(&source_path)
+                .arg("-implicit:none")
+                .arg("-d")
+                .arg(&classes)
+                .args(&sources)
+                .status()
+                .expect("failed to run javac");
+            assert!(status.success(), "javac failed for {}.jar", name);
+        }
+
+        let jar_file = out_dir.join("jars").join(format!("{}.jar", name));
+        let mut cmd = Command::new("jar");
+        // the jar's own META-INF/MANIFEST.MF replaces the generated one
+        let manifest = classes.join("META-INF/MANIFEST.MF");
+        if manifest.is_file() {
+            cmd.arg("cfm").arg(&jar_file).arg(&manifest);
+        } else {
+            cmd.arg("cf").arg(&jar_file);
+        }
+        let status = cmd
+            .arg("-C")
+            .arg(&classes)
+            .arg(".")
+            .status()
+            .expect("failed to run jar");
+        assert!(status.success(), "jar failed for {}.jar", name);
+    }
+
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+}
+
+/// Copy the files of a jar directory except its sources, which are
+/// collected into `sources`.
+fn copy_jar_files(src: &Path, dst: &Path, sources: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(src).unwrap().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        println!("cargo:rerun-if-changed={}", path.display());
+        let to = dst.join(entry.file_name());
+        if path.is_dir() {
+            fs::create_dir_all(&to).unwrap();
+            copy_jar_files(&path, &to, sources);
+        } else if path.extension().is_some_and(|ext| ext == "java") {
+            sources.push(path);
+        } else {
+            fs::copy(&path, &to).unwrap();
+        }
+    }
+}
+
+fn find_mains(dir: &Path, module: &str, pkg: &str, mains: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap().filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.path().is_dir() {
+            let sub = if pkg.is_empty() {
+                name
+            } else {
+                format!("{}.{}", pkg, name)
+            };
+            find_mains(&entry.path(), module, &sub, mains);
+        } else if name == "Main.java" {
+            mains.push(format!("{}/{}.Main", module, pkg));
+        }
+    }
+}
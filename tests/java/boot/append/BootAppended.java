@@ -0,0 +1,6 @@
+// Only on the appended boot class path
+public class BootAppended {
+    public static String origin() {
+        return "-Xbootclasspath/a:";
+    }
+}
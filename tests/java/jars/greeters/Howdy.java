@@ -0,0 +1,5 @@
+public class Howdy implements Services.Greeter {
+    public String greet(String who) {
+        return "howdy " + who;
+    }
+}
@@ -0,0 +1,12 @@
+import java.util.Arrays;
+
+// Main-Class of launcher.jar, run with -jar. LauncherLib is only found
+// through the Class-Path of the manifest.
+public class Launcher {
+    public static void main(String[] args) {
+        System.out.println("args = " + Arrays.toString(args));
+        System.out.println("library = " + LauncherLib.describe());
+        System.out.println("same loader = "
+                + (Launcher.class.getClassLoader() == LauncherLib.class.getClassLoader()));
+    }
+}
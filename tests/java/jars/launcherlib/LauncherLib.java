@@ -0,0 +1,5 @@
+public class LauncherLib {
+    static String describe() {
+        return "launcherlib.jar";
+    }
+}
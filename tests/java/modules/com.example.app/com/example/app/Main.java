@@ -0,0 +1,101 @@
+package com.example.app;
+
+import com.example.greet.Greeter;
+import com.example.greet.internal.Secret;
+import javax.xml.XMLConstants;
+
+public class Main {
+    static void check(String what, boolean ok) {
+        if (!ok) {
+            throw new RuntimeException(what);
+        }
+        System.out.println(what + " ok");
+    }
+
+    // hot enough for the JIT, without an exception table of their own
+    static Object cast(Object o) {
+        return (Secret) o;
+    }
+
+    static boolean isSecret(Object o) {
+        return o instanceof Secret;
+    }
+
+    static Object secretClass() {
+        return Secret.class;
+    }
+
+    // calls `op` until it's compiled, every call must throw IllegalAccessError
+    static void checkDenied(String what, int op, Object o) {
+        int denied = 0;
+        for (int i = 0; i < 2000; i++) {
+            try {
+                if (op == 0) {
+                    cast(o);
+                } else if (op == 1) {
+                    isSecret(o);
+                } else {
+                    secretClass();
+                }
+            } catch (IllegalAccessError e) {
+                denied++;
+            }
+        }
+        check(what, denied == 2000);
+    }
+
+    public static void main(String[] args) {
+        Module app = Main.class.getModule();
+        Module greet = Greeter.class.getModule();
+        System.out.println("app: " + app.getName());
+        System.out.println("greet: " + greet.getName());
+        check("named", app.isNamed() && greet.isNamed());
+        check("same module", Main.class.getModule() == app);
+
+        // readability from `requires` and `requires transitive`
+        Module xml = XMLConstants.class.getModule();
+        System.out.println("xml: " + xml.getName());
+        check("reads greet", app.canRead(greet));
+        check("reads java.base", app.canRead(String.class.getModule()));
+        check("reads xml", app.canRead(xml));
+
+        // exports of com.example.greet
+        check("exported", greet.isExported("com.example.greet", app));
+        check("not exported", !greet.isExported("com.example.greet.internal", app));
+        check("java.lang exported", String.class.getModule().isExported("java.lang"));
+
+        Greeter greeter = new Greeter("modules");
+        System.out.println(greeter.greet());
+        check("xml prefix", greeter.xmlPrefix().equals("xml"));
+
+        // compiled with --add-exports, so only the VM stops these at link time
+        try {
+            System.out.println(Secret.reveal());
+            throw new RuntimeException("invoke not checked");
+        } catch (IllegalAccessError e) {
+            System.out.println("invoke: " + e.getClass().getName());
+        }
+        try {
+            System.out.println(Secret.value);
+            throw new RuntimeException("getstatic not checked");
+        } catch (IllegalAccessError e) {
+            System.out.println("getstatic: " + e.getClass().getName());
+        }
+        try {
+            System.out.println(new Secret());
+            throw new RuntimeException("new not checked");
+        } catch (IllegalAccessError e) {
+            System.out.println("new: " + e.getClass().getName());
+        }
+
+        Object secret = greeter.secret();
+        checkDenied("checkcast denied", 0, secret);
+        checkDenied("instanceof denied", 1, secret);
+        checkDenied("ldc denied", 2, null);
+        // a null reference is cast without resolving the class
+        check("null cast", cast(null) == null);
+        check("null instanceof", !isSecret(null));
+
+        System.out.println("args: " + String.join(" ", args));
+    }
+}
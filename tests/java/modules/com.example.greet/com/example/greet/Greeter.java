@@ -0,0 +1,26 @@
+package com.example.greet;
+
+import com.example.greet.internal.Secret;
+import javax.xml.XMLConstants;
+
+public class Greeter {
+    private final String name;
+
+    public Greeter(String name) {
+        this.name = name;
+    }
+
+    public String greet() {
+        return "hello, " + name;
+    }
+
+    // java.xml is read by every module that requires com.example.greet
+    public String xmlPrefix() {
+        return XMLConstants.XML_NS_PREFIX;
+    }
+
+    // an instance of a class in a package that isn't exported
+    public Object secret() {
+        return new Secret();
+    }
+}
@@ -0,0 +1,10 @@
+package com.example.greet.internal;
+
+// public, but in a package com.example.greet does not export
+public class Secret {
+    public static String value = "secret";
+
+    public static String reveal() {
+        return value;
+    }
+}
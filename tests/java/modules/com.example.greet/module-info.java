@@ -0,0 +1,4 @@
+module com.example.greet {
+    requires transitive java.xml;
+    exports com.example.greet;
+}
@@ -0,0 +1,16 @@
+import java.util.EventObject;
+
+// Runs with java.util.EventObject and BootAppended in -Xbootclasspath/a:
+// (see BOOT_CLASS_PATH_TESTS of the integration test)
+public class BootAppend {
+    public static void main(String[] args) throws Exception {
+        // the runtime image is searched before the appended path
+        Check.isTrue("EventObject from the image",
+                new EventObject("x").toString().startsWith("java.util.EventObject["));
+
+        // a class only in the appended path is found by the boot loader
+        Class<?> appended = Class.forName("BootAppended");
+        Check.isTrue("boot loader", appended.getClassLoader() == null);
+        Check.equal("BootAppended", "-Xbootclasspath/a:", appended.getMethod("origin").invoke(null));
+    }
+}
@@ -0,0 +1,26 @@
+import java.util.Objects;
+
+// Checks shared by the tests. A passing check prints one line, so the output
+// can be compared with the JDK's; a failing one throws.
+public class Check {
+    static void equal(String what, long expected, long actual) {
+        if (expected != actual) {
+            throw new RuntimeException(what + ": expected " + expected + ", got " + actual);
+        }
+        System.out.println(what + " = " + actual);
+    }
+
+    static void equal(String what, Object expected, Object actual) {
+        if (!Objects.equals(expected, actual)) {
+            throw new RuntimeException(what + ": expected " + expected + ", got " + actual);
+        }
+        System.out.println(what + " = " + actual);
+    }
+
+    static void isTrue(String what, boolean ok) {
+        if (!ok) {
+            throw new RuntimeException(what);
+        }
+        System.out.println(what + " ok");
+    }
+}
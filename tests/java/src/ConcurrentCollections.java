@@ -0,0 +1,184 @@
+import java.lang.reflect.Field;
+import java.util.Arrays;
+import java.util.concurrent.ConcurrentHashMap;
+import java.util.concurrent.CountDownLatch;
+import java.util.concurrent.atomic.AtomicInteger;
+import java.util.concurrent.atomic.AtomicLong;
+import java.util.concurrent.locks.Condition;
+import java.util.concurrent.locks.ReentrantLock;
+
+public class ConcurrentCollections {
+    static final int THREADS = 3;
+    static final int KEYS = 200;
+
+    // Every thread increments every key, retrying on lost races
+    static class MapWriter implements Runnable {
+        final ConcurrentHashMap<Integer, Integer> map;
+        final CountDownLatch done;
+
+        MapWriter(ConcurrentHashMap<Integer, Integer> map, CountDownLatch done) {
+            this.map = map;
+            this.done = done;
+        }
+
+        public void run() {
+            for (int k = 0; k < KEYS; k++) {
+                Integer key = k;
+                while (true) {
+                    Integer old = map.putIfAbsent(key, 1);
+                    if (old == null || map.replace(key, old, old + 1)) {
+                        break;
+                    }
+                }
+            }
+            done.countDown();
+        }
+    }
+
+    static class Adder implements Runnable {
+        static final AtomicInteger ints = new AtomicInteger();
+        static final AtomicLong longs = new AtomicLong();
+        static final AtomicInteger last = new AtomicInteger();
+
+        public void run() {
+            for (int i = 0; i < 1000; i++) {
+                ints.getAndAdd(3);
+                longs.getAndIncrement();
+                last.getAndSet(i);
+            }
+        }
+    }
+
+    // A one-slot handoff guarded by a ReentrantLock and two conditions
+    static class Slot {
+        final ReentrantLock lock = new ReentrantLock();
+        final Condition notEmpty = lock.newCondition();
+        final Condition notFull = lock.newCondition();
+        Integer value;
+
+        void put(int v) throws InterruptedException {
+            lock.lock();
+            try {
+                while (value != null) {
+                    notFull.await();
+                }
+                value = v;
+                notEmpty.signal();
+            } finally {
+                lock.unlock();
+            }
+        }
+
+        int take() throws InterruptedException {
+            lock.lock();
+            try {
+                while (value == null) {
+                    notEmpty.await();
+                }
+                int v = value;
+                value = null;
+                notFull.signal();
+                return v;
+            } finally {
+                lock.unlock();
+            }
+        }
+    }
+
+    static class SlotProducer implements Runnable {
+        final Slot slot;
+
+        SlotProducer(Slot slot) {
+            this.slot = slot;
+        }
+
+        public void run() {
+            try {
+                for (int i = 1; i <= 100; i++) {
+                    slot.put(i);
+                }
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        ConcurrentHashMap<Integer, Integer> map = new ConcurrentHashMap<>();
+        CountDownLatch done = new CountDownLatch(THREADS);
+        for (int i = 0; i < THREADS; i++) {
+            new Thread(new MapWriter(map, done)).start();
+        }
+        done.await();
+        Check.equal("map size", KEYS, map.size());
+        long total = 0;
+        for (Integer v : map.values()) {
+            total += v;
+        }
+        Check.equal("map total", KEYS * THREADS, total);
+        Check.equal("map remove", THREADS, (int) map.remove(7));
+        Check.equal("map contains", 0, map.containsKey(7) ? 1 : 0);
+
+        Thread[] adders = new Thread[THREADS];
+        for (int i = 0; i < THREADS; i++) {
+            adders[i] = new Thread(new Adder());
+            adders[i].start();
+        }
+        for (Thread t : adders) {
+            t.join();
+        }
+        Check.equal("getAndAdd", 3 * 1000 * THREADS, Adder.ints.get());
+        Check.equal("getAndIncrement", 1000 * THREADS, Adder.longs.get());
+        Check.equal("getAndSet", 999, Adder.last.get());
+
+        AtomicLong cas = new AtomicLong(Long.MAX_VALUE);
+        Check.equal("cas long hit", 1, cas.compareAndSet(Long.MAX_VALUE, -1) ? 1 : 0);
+        Check.equal("cas long miss", 0, cas.compareAndSet(Long.MAX_VALUE, 0) ? 1 : 0);
+        Check.equal("cas long value", -1, cas.get());
+
+        Slot slot = new Slot();
+        Thread producer = new Thread(new SlotProducer(slot));
+        producer.start();
+        long sum = 0;
+        for (int i = 0; i < 100; i++) {
+            sum += slot.take();
+        }
+        producer.join();
+        Check.equal("lock condition sum", 5050, sum);
+        Check.equal("lock released", 0, slot.lock.isLocked() ? 1 : 0);
+
+        // Arrays.equals/mismatch compare primitive arrays in words through Unsafe
+        long[] a = new long[37];
+        long[] b = new long[37];
+        byte[] c = new byte[101];
+        byte[] d = new byte[101];
+        for (int i = 0; i < a.length; i++) {
+            a[i] = b[i] = i * 0x0101010101L;
+        }
+        for (int i = 0; i < c.length; i++) {
+            c[i] = d[i] = (byte) i;
+        }
+        Check.equal("long[] equal", 1, Arrays.equals(a, b) ? 1 : 0);
+        Check.equal("byte[] equal", 1, Arrays.equals(c, d) ? 1 : 0);
+        b[29] = -1;
+        d[77] = -1;
+        Check.equal("long[] mismatch", 29, Arrays.mismatch(a, b));
+        Check.equal("byte[] mismatch", 77, Arrays.mismatch(c, d));
+
+        // allocateInstance only makes instances of concrete classes
+        Field f = sun.misc.Unsafe.class.getDeclaredField("theUnsafe");
+        f.setAccessible(true);
+        sun.misc.Unsafe unsafe = (sun.misc.Unsafe) f.get(null);
+        Slot allocated = (Slot) unsafe.allocateInstance(Slot.class);
+        Check.equal("allocated without constructor", 1, allocated.lock == null ? 1 : 0);
+        Class<?>[] abstracts = {int.class, long[].class, Runnable.class, Number.class};
+        for (Class<?> cls : abstracts) {
+            try {
+                unsafe.allocateInstance(cls);
+                Check.equal("allocate " + cls.getName(), 0, 1);
+            } catch (InstantiationException e) {
+                Check.equal("allocate " + cls.getName(), 0, 0);
+            }
+        }
+    }
+}
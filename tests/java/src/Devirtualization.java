@@ -0,0 +1,119 @@
+public class Devirtualization {
+    static class Counter {
+        private int value;
+
+        int get() {
+            return value;
+        }
+
+        void inc() {
+            value = value + 1;
+        }
+    }
+
+    static class Base {
+        int id() {
+            return 1;
+        }
+    }
+
+    // Loaded only after the call site in sumIds() has been compiled,
+    // so the devirtualized call must fall back to v-table dispatch
+    static class Late extends Base {
+        int id() {
+            return 100;
+        }
+    }
+
+    static class Plain {
+        int value() {
+            return 1;
+        }
+    }
+
+    // Loaded by makeOverride() while mixedValues() is running, so the
+    // compiled frame must continue in the interpreter
+    static class Override extends Plain {
+        int value() {
+            return 100;
+        }
+    }
+
+    interface Shape {
+        int sides();
+    }
+
+    static class Triangle implements Shape {
+        public int sides() {
+            return 3;
+        }
+    }
+
+    static class Square implements Shape {
+        public int sides() {
+            return 4;
+        }
+    }
+
+    static class Pentagon implements Shape {
+        public int sides() {
+            return 5;
+        }
+    }
+
+    static int sumIds(Base b, int n) {
+        int sum = 0;
+        for (int i = 0; i < n; i++) {
+            sum += b.id();
+        }
+        return sum;
+    }
+
+    static Plain makeOverride() {
+        return new Override();
+    }
+
+    static int mixedValues(Plain p, int n) {
+        int sum = 0;
+        for (int i = 0; i < n; i++) {
+            sum += p.value();
+            if (i == n / 2) {
+                p = makeOverride();
+            }
+        }
+        return sum;
+    }
+
+    static int countSides(Shape[] shapes) {
+        int sum = 0;
+        for (int i = 0; i < shapes.length; i++) {
+            sum += shapes[i].sides();
+        }
+        return sum;
+    }
+
+    public static void main(String[] args) {
+        // Small getter/mutator bodies are candidates for inlining
+        Counter c = new Counter();
+        for (int i = 0; i < 1000; i++) {
+            c.inc();
+        }
+        Check.equal("counter", 1000, c.get());
+
+        // Base.id() has no override yet
+        Check.equal("base ids", 10, sumIds(new Base(), 10));
+
+        // Loading Late overrides Base.id() and invalidates the assumption
+        Check.equal("late ids", 1000, sumIds(new Late(), 10));
+        Check.equal("base ids again", 10, sumIds(new Base(), 10));
+
+        // Override is loaded half way through the loop
+        Check.equal("mixed values", 406, mixedValues(new Plain(), 10));
+
+        // Monomorphic, then polymorphic interface call site
+        Shape[] mono = { new Triangle(), new Triangle(), new Triangle() };
+        Check.equal("mono sides", 9, countSides(mono));
+        Shape[] poly = { new Triangle(), new Square(), new Pentagon(), new Square() };
+        Check.equal("poly sides", 16, countSides(poly));
+    }
+}
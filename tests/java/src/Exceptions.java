@@ -6,6 +6,10 @@ public class Exceptions {
             System.out.println("ERROR: should have thrown");
         } catch (ArithmeticException e) {
             System.out.println("ArithmeticException caught: divide by zero");
+            // exceptions raised by the VM carry their message
+            if (e.getMessage() == null) {
+                throw new RuntimeException("ArithmeticException without a message");
+            }
         }
 
         // NullPointerException
@@ -24,6 +28,9 @@ public class Exceptions {
             System.out.println("ERROR: should have thrown");
         } catch (ArrayIndexOutOfBoundsException e) {
             System.out.println("ArrayIndexOutOfBoundsException caught");
+            if (e.getMessage() == null || !e.getMessage().contains("10")) {
+                throw new RuntimeException("bad message: " + e.getMessage());
+            }
         }
 
         // Multiple catch blocks
@@ -0,0 +1,124 @@
+import java.util.concurrent.CompletableFuture;
+import java.util.concurrent.CompletionException;
+import java.util.concurrent.ForkJoinPool;
+import java.util.concurrent.RecursiveTask;
+import java.util.concurrent.TimeUnit;
+import java.util.concurrent.atomic.AtomicInteger;
+import java.util.function.BiFunction;
+import java.util.function.Function;
+import java.util.function.Supplier;
+
+public class ForkJoin {
+    // Splits the range until it is small, forking the left half
+    static class Sum extends RecursiveTask<Long> {
+        final int from;
+        final int to;
+
+        Sum(int from, int to) {
+            this.from = from;
+            this.to = to;
+        }
+
+        protected Long compute() {
+            if (to - from <= 64) {
+                long s = 0;
+                for (int i = from; i < to; i++) {
+                    s += i;
+                }
+                return s;
+            }
+            int mid = (from + to) >>> 1;
+            Sum left = new Sum(from, mid);
+            left.fork();
+            long right = new Sum(mid, to).compute();
+            return left.join() + right;
+        }
+    }
+
+    // The VM doesn't spin lambda classes, so the functions are plain classes
+    static class Constant implements Supplier<Integer> {
+        final int v;
+
+        Constant(int v) {
+            this.v = v;
+        }
+
+        public Integer get() {
+            return v;
+        }
+    }
+
+    static class Count implements Runnable {
+        final AtomicInteger ran;
+
+        Count(AtomicInteger ran) {
+            this.ran = ran;
+        }
+
+        public void run() {
+            ran.incrementAndGet();
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        ForkJoinPool pool = new ForkJoinPool(3);
+        Check.equal("pool sum", 4999950000L, (long) pool.invoke(new Sum(0, 100000)));
+        Check.equal("pool parallelism", 3, pool.getParallelism());
+
+        AtomicInteger ran = new AtomicInteger();
+        for (int i = 0; i < 20; i++) {
+            pool.execute(new Count(ran));
+        }
+        Check.isTrue("quiescent", pool.awaitQuiescence(10, TimeUnit.SECONDS));
+        Check.equal("executed", 20, ran.get());
+        pool.shutdown();
+        Check.isTrue("terminated", pool.awaitTermination(10, TimeUnit.SECONDS));
+
+        Check.equal("common pool sum", 500500, (long) ForkJoinPool.commonPool().invoke(new Sum(1, 1001)));
+
+        CompletableFuture<Integer> a = CompletableFuture.supplyAsync(new Constant(20));
+        CompletableFuture<Integer> b = a.thenApply(new Function<Integer, Integer>() {
+            public Integer apply(Integer x) {
+                return x + 1;
+            }
+        });
+        CompletableFuture<Integer> c = b.thenCombine(CompletableFuture.supplyAsync(new Constant(2)),
+                new BiFunction<Integer, Integer, Integer>() {
+                    public Integer apply(Integer x, Integer y) {
+                        return x * y;
+                    }
+                });
+        Check.equal("thenCombine", 42, (int) c.join());
+
+        CompletableFuture<Void> all = CompletableFuture.allOf(a, b, c);
+        all.get(10, TimeUnit.SECONDS);
+        Check.isTrue("allOf", all.isDone() && !all.isCompletedExceptionally());
+
+        CompletableFuture<Integer> failed = CompletableFuture.supplyAsync(new Supplier<Integer>() {
+            public Integer get() {
+                throw new IllegalStateException("boom");
+            }
+        });
+        Check.equal("exceptionally", -1, (int) failed.exceptionally(new Function<Throwable, Integer>() {
+            public Integer apply(Throwable e) {
+                return -1;
+            }
+        }).join());
+        try {
+            failed.join();
+            Check.isTrue("join rethrows", false);
+        } catch (CompletionException e) {
+            Check.isTrue("join rethrows", e.getCause() instanceof IllegalStateException);
+        }
+
+        CompletableFuture<String> manual = new CompletableFuture<>();
+        CompletableFuture<String> upper = manual.thenApplyAsync(new Function<String, String>() {
+            public String apply(String s) {
+                return s.toUpperCase();
+            }
+        });
+        Check.isTrue("complete", manual.complete("done"));
+        Check.isTrue("already complete", !manual.complete("again"));
+        System.out.println("thenApplyAsync = " + upper.get(10, TimeUnit.SECONDS));
+    }
+}
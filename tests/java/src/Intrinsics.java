@@ -0,0 +1,112 @@
+public class Intrinsics {
+    static class Key {
+        final int id;
+
+        Key(int id) {
+            this.id = id;
+        }
+
+        // Overrides Object.hashCode, so the call must not use the identity hash
+        public int hashCode() {
+            return id * 7;
+        }
+    }
+
+    static int mathKernel(int n) {
+        int sum = 0;
+        for (int i = -n; i < n; i++) {
+            sum += Math.abs(i) + Math.max(i, 0) - Math.min(i, 0);
+        }
+        return sum;
+    }
+
+    static long longKernel(long n) {
+        long sum = 0;
+        for (long i = -n; i < n; i++) {
+            sum += Math.abs(i) + Math.max(i, 1L) + Math.min(i, -1L);
+        }
+        return sum;
+    }
+
+    public static void main(String[] args) {
+        // Math
+        Check.equal("abs int", 5, Math.abs(-5));
+        Check.equal("abs min int", Integer.MIN_VALUE, Math.abs(Integer.MIN_VALUE));
+        Check.equal("abs long", 7L, Math.abs(-7L));
+        Check.equal("max int", 3, Math.max(-2, 3));
+        Check.equal("min long", -9L, Math.min(-9L, 4L));
+        Check.equal("sqrt", 12, (long) Math.sqrt(144.0));
+        Check.isTrue("abs double", Math.abs(-2.5) == 2.5);
+        Check.isTrue("abs float", Math.abs(-1.5f) == 1.5f);
+        Check.isTrue("min NaN", Double.isNaN(Math.min(1.0, Double.NaN)));
+        Check.isTrue("max NaN", Float.isNaN(Math.max(Float.NaN, 1.0f)));
+        Check.isTrue("min -0.0", Double.doubleToRawLongBits(Math.min(0.0, -0.0)) == Double.doubleToRawLongBits(-0.0));
+        Check.isTrue("max -0.0", Float.floatToRawIntBits(Math.max(-0.0f, 0.0f)) == 0);
+        for (int round = 0; round < 3; round++) {
+            Check.equal("math kernel", 200, mathKernel(10));
+            Check.equal("long kernel", 91L, longKernel(10L));
+        }
+
+        // System.arraycopy
+        int[] src = { 1, 2, 3, 4, 5 };
+        int[] dst = new int[5];
+        System.arraycopy(src, 1, dst, 0, 3);
+        Check.equal("arraycopy", 234, dst[0] * 100 + dst[1] * 10 + dst[2]);
+        System.arraycopy(src, 0, src, 1, 4);
+        Check.equal("arraycopy overlap", 11234, src[0] * 10000 + src[1] * 1000 + src[2] * 100 + src[3] * 10 + src[4]);
+        long[] longs = { 10L, 20L, 30L };
+        long[] longsCopy = new long[3];
+        System.arraycopy(longs, 0, longsCopy, 0, 3);
+        Check.equal("arraycopy long", 60L, longsCopy[0] + longsCopy[1] + longsCopy[2]);
+        String[] names = { "a", "b", "c" };
+        Object[] objs = new Object[3];
+        System.arraycopy(names, 0, objs, 0, 3);
+        Check.isTrue("arraycopy refs", objs[2] == names[2]);
+        try {
+            System.arraycopy(src, 3, dst, 0, 5);
+            throw new RuntimeException("arraycopy: expected ArrayIndexOutOfBoundsException");
+        } catch (ArrayIndexOutOfBoundsException e) {
+            System.out.println("arraycopy bounds = caught");
+        }
+        try {
+            System.arraycopy(null, 0, dst, 0, 1);
+            throw new RuntimeException("arraycopy: expected NullPointerException");
+        } catch (NullPointerException e) {
+            System.out.println("arraycopy null = caught");
+        }
+
+        // Arrays.fill
+        int[] filled = new int[4];
+        java.util.Arrays.fill(filled, 9);
+        Check.equal("fill int", 36, filled[0] + filled[1] + filled[2] + filled[3]);
+        double[] doubles = new double[2];
+        java.util.Arrays.fill(doubles, 0.5);
+        Check.isTrue("fill double", doubles[0] + doubles[1] == 1.0);
+        char[] chars = new char[3];
+        java.util.Arrays.fill(chars, 'x');
+        Check.isTrue("fill char", chars[2] == 'x');
+
+        // String.equals / String.hashCode
+        String hello = "hello";
+        String built = new StringBuilder("hel").append("lo").toString();
+        Check.isTrue("equals", hello.equals(built));
+        Check.isTrue("not equals", !hello.equals("help!"));
+        Check.isTrue("equals null", !hello.equals(null));
+        Check.isTrue("equals other type", !hello.equals(Integer.valueOf(5)));
+        Check.equal("hashCode", 99162322, hello.hashCode());
+        Check.equal("hashCode built", hello.hashCode(), built.hashCode());
+        Check.equal("hashCode empty", 0, "".hashCode());
+
+        // Integer.valueOf keeps the cache identity in [-128, 127]
+        Check.isTrue("valueOf cached", Integer.valueOf(100) == Integer.valueOf(100));
+        Check.isTrue("valueOf uncached", Integer.valueOf(1000) != Integer.valueOf(1000));
+        Check.equal("valueOf value", 1000, Integer.valueOf(1000).intValue());
+        Check.equal("valueOf negative", -128, Integer.valueOf(-128).intValue());
+
+        // Object.hashCode: identity hash is stable, overrides still dispatch
+        Object o = new Object();
+        Check.isTrue("identity hash", o.hashCode() == o.hashCode());
+        Object key = new Key(6);
+        Check.equal("overridden hash", 42, key.hashCode());
+    }
+}
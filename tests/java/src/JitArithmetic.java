@@ -0,0 +1,119 @@
+public class JitArithmetic {
+    static int sideEffects;
+
+    static class Box {
+        int v = 7;
+    }
+
+    static float i2f(int x) {
+        return (float) x;
+    }
+
+    static double l2d(long x) {
+        return (double) x;
+    }
+
+    static int f2i(float f) {
+        return (int) f;
+    }
+
+    static long d2l(double d) {
+        return (long) d;
+    }
+
+    static int div(int a, int b) {
+        return a / b;
+    }
+
+    static int rem(int a, int b) {
+        return a % b;
+    }
+
+    static long ldiv(long a, long b) {
+        return a / b;
+    }
+
+    static long lrem(long a, long b) {
+        return a % b;
+    }
+
+    static long shifts(long v, int s) {
+        return (v << s) ^ (v >> s) ^ (v >>> s);
+    }
+
+    // the store after the division must not run when it throws
+    static int divThenCount(int a, int b) {
+        int q = a / b;
+        sideEffects++;
+        return q;
+    }
+
+    static int fieldThenCount(Box b) {
+        int v = b.v;
+        sideEffects++;
+        return v;
+    }
+
+    static int loadThenCount(int[] a, int i) {
+        int v = a[i];
+        sideEffects++;
+        return v;
+    }
+
+    static void storeThenCount(Object[] a, Object o) {
+        a[0] = o;
+        sideEffects++;
+    }
+
+    // has an exception table, so it stays interpreted
+    static int divOrMinusOne(int a, int b) {
+        try {
+            return a / b;
+        } catch (ArithmeticException e) {
+            return -1;
+        }
+    }
+
+    public static void main(String[] args) {
+        System.out.println("i2f = " + i2f(-5) + " " + i2f(Integer.MIN_VALUE));
+        System.out.println("l2d = " + l2d(-3L) + " " + l2d(Long.MIN_VALUE));
+        System.out.println("f2i = " + f2i(-2.7f) + " " + f2i(Float.NaN) + " " + f2i(1e20f) + " " + f2i(-1e20f));
+        System.out.println("d2l = " + d2l(-9.9) + " " + d2l(Double.NaN) + " " + d2l(Double.POSITIVE_INFINITY)
+                + " " + d2l(Double.NEGATIVE_INFINITY));
+        System.out.println("div = " + div(-7, 2) + " " + div(Integer.MIN_VALUE, -1));
+        System.out.println("rem = " + rem(-7, 2) + " " + rem(Integer.MIN_VALUE, -1));
+        System.out.println("ldiv = " + ldiv(-7L, 2L) + " " + ldiv(Long.MIN_VALUE, -1L));
+        System.out.println("lrem = " + lrem(-7L, 2L) + " " + lrem(Long.MIN_VALUE, -1L));
+        System.out.println("shifts = " + shifts(-123456789L, 3) + " " + shifts(1L, 65));
+
+        try {
+            divThenCount(1, 0);
+            System.out.println("no exception");
+        } catch (ArithmeticException e) {
+            System.out.println("div by zero: " + e.getClass().getName());
+        }
+        try {
+            fieldThenCount(null);
+            System.out.println("no exception");
+        } catch (NullPointerException e) {
+            System.out.println("null field: " + e.getClass().getName());
+        }
+        try {
+            loadThenCount(new int[2], 2);
+            System.out.println("no exception");
+        } catch (ArrayIndexOutOfBoundsException e) {
+            System.out.println("index: " + e.getClass().getName());
+        }
+        try {
+            storeThenCount(null, "x");
+            System.out.println("no exception");
+        } catch (NullPointerException e) {
+            System.out.println("null store: " + e.getClass().getName());
+        }
+        System.out.println("side effects = " + sideEffects);
+
+        System.out.println("handled = " + divOrMinusOne(6, 3) + " " + divOrMinusOne(6, 0));
+        System.out.println("after = " + divThenCount(6, 3) + " " + fieldThenCount(new Box()) + " "
+                + loadThenCount(new int[] {4, 5}, 1) + " " + sideEffects);
+    }
+}
@@ -0,0 +1,44 @@
+public class JitBench {
+    static int loopSum(int n) {
+        int sum = 0;
+        for (int i = 0; i < n; i++) {
+            sum += i * 3 + (i & 7);
+        }
+        return sum;
+    }
+
+    static int fib(int n) {
+        if (n <= 1) return n;
+        return fib(n - 1) + fib(n - 2);
+    }
+
+    static long arraySum(int n) {
+        int[] a = new int[n];
+        for (int i = 0; i < n; i++) {
+            a[i] = i % 100;
+        }
+        long sum = 0;
+        for (int i = 0; i < n; i++) {
+            sum += a[i];
+        }
+        return sum;
+    }
+
+    // Usage: JitBench [rounds]
+    // The integration test runs a single round; the jit_levels bench passes more.
+    public static void main(String[] args) {
+        int rounds = args.length > 0 ? Integer.parseInt(args[0]) : 1;
+
+        // Summed over the rounds, so the timed loop prints nothing
+        long loop = 0, fibs = 0, array = 0;
+        for (int r = 0; r < rounds; r++) {
+            loop += loopSum(32);
+            fibs += fib(15);
+            array += arraySum(100);
+        }
+        Check.equal("loopSum", 1600L * rounds, loop);
+        Check.equal("fib", 610L * rounds, fibs);
+        Check.equal("arraySum", 4950L * rounds, array);
+        System.out.println("JitBench rounds = " + rounds);
+    }
+}
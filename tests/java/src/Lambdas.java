@@ -0,0 +1,108 @@
+import java.security.AccessController;
+import java.security.PrivilegedAction;
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.Comparator;
+import java.util.List;
+import java.util.function.BiFunction;
+import java.util.function.Function;
+import java.util.function.IntBinaryOperator;
+import java.util.function.LongSupplier;
+import java.util.function.Supplier;
+import java.util.function.ToIntFunction;
+
+public class Lambdas {
+    interface Shape {
+        double area();
+    }
+
+    static class Square implements Shape {
+        final int side;
+
+        Square(int side) {
+            this.side = side;
+        }
+
+        public double area() {
+            return side * side;
+        }
+    }
+
+    final String name = "lambdas";
+
+    static String greet(String who) {
+        return "hello " + who;
+    }
+
+    static long twice(int v) {
+        return 2L * v;
+    }
+
+    String named(String suffix) {
+        return name + suffix;
+    }
+
+    Supplier<String> capturingThis() {
+        return () -> named("!");
+    }
+
+    static String describe(String s) {
+        return s.toUpperCase();
+    }
+
+    public static void main(String[] args) {
+        Runnable r = () -> System.out.println("runnable ran");
+        r.run();
+
+        // captured int, long, double and String
+        int i = 3;
+        long l = 40L;
+        double d = 0.5;
+        String s = "x";
+        Supplier<String> captured = () -> s + i + l + d;
+        Check.equal("captured", "x3400.5", captured.get());
+        Check.equal("this", "lambdas!", new Lambdas().capturingThis().get());
+
+        // static, bound, unbound and constructor method references
+        Function<String, String> stat = Lambdas::greet;
+        Check.equal("static ref", "hello jvm", stat.apply("jvm"));
+        Function<String, String> bound = new Lambdas()::named;
+        Check.equal("bound ref", "lambdas?", bound.apply("?"));
+        Function<String, Integer> unbound = String::length;
+        Check.equal("unbound ref", 5, (int) unbound.apply("hello"));
+        Function<Integer, Shape> ctor = Square::new;
+        Check.equal("constructor ref", 16.0, ctor.apply(4).area());
+        ToIntFunction<List<String>> size = List::size;
+        Check.equal("interface ref", 2, size.applyAsInt(Arrays.asList("a", "b")));
+
+        // boxing, unboxing and widening between the interface and the method
+        BiFunction<Integer, Integer, Integer> add = (a, b) -> a + b;
+        Check.equal("boxed", 7, (int) add.apply(3, 4));
+        IntBinaryOperator mul = (a, b) -> a * b;
+        Check.equal("primitive", 12, mul.applyAsInt(3, 4));
+        LongSupplier widened = () -> i;
+        Check.equal("widened", 3L, widened.getAsLong());
+        Function<Integer, Long> unboxWiden = Lambdas::twice;
+        Check.equal("unbox and widen", 10L, (long) unboxWiden.apply(5));
+
+        // the same call site twice gives objects of one class
+        List<Supplier<Integer>> suppliers = new ArrayList<>();
+        for (int k = 0; k < 3; k++) {
+            final int v = k;
+            suppliers.add(() -> v * 10);
+        }
+        Check.equal("loop", 20, (int) suppliers.get(2).get());
+        Check.equal("one class", true, suppliers.get(0).getClass() == suppliers.get(1).getClass());
+
+        // default methods of the interface and JDK code taking lambdas
+        Comparator<String> byLength = (a, b) -> a.length() - b.length();
+        List<String> words = new ArrayList<>(Arrays.asList("ccc", "a", "bb"));
+        words.sort(byLength.reversed());
+        Check.equal("sorted", "[ccc, bb, a]", words.toString());
+        words.replaceAll(Lambdas::describe);
+        Check.equal("replaceAll", "[CCC, BB, A]", words.toString());
+
+        PrivilegedAction<String> action = () -> "privileged";
+        Check.equal("doPrivileged", "privileged", AccessController.doPrivileged(action));
+    }
+}
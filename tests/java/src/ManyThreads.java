@@ -0,0 +1,77 @@
+import java.util.concurrent.CountDownLatch;
+import java.util.concurrent.CyclicBarrier;
+import java.util.concurrent.TimeUnit;
+
+public class ManyThreads {
+    static final int N = 16;
+
+    static class Party implements Runnable {
+        final CyclicBarrier barrier;
+        final CountDownLatch done;
+
+        Party(CyclicBarrier barrier, CountDownLatch done) {
+            this.barrier = barrier;
+            this.done = done;
+        }
+
+        public void run() {
+            try {
+                // every thread waits for all the others, so they must all
+                // run at the same time
+                barrier.await(30, TimeUnit.SECONDS);
+                done.countDown();
+            } catch (Exception e) {
+                throw new RuntimeException(e);
+            }
+        }
+    }
+
+    static class Sleeper implements Runnable {
+        public void run() {
+            try {
+                Thread.sleep(200);
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+            System.out.println("sleeper done");
+        }
+    }
+
+    static class Daemon implements Runnable {
+        public void run() {
+            Object lock = new Object();
+            synchronized (lock) {
+                try {
+                    // never notified, the VM exits without waiting for it
+                    lock.wait();
+                } catch (InterruptedException e) {
+                    throw new RuntimeException(e);
+                }
+            }
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        CyclicBarrier barrier = new CyclicBarrier(N);
+        for (int round = 0; round < 2; round++) {
+            CountDownLatch done = new CountDownLatch(N);
+            Thread[] threads = new Thread[N];
+            for (int i = 0; i < N; i++) {
+                threads[i] = new Thread(new Party(barrier, done), "party-" + i);
+                threads[i].start();
+            }
+            for (int i = 0; i < N; i++) {
+                threads[i].join();
+            }
+            Check.isTrue("round " + round, done.getCount() == 0);
+        }
+
+        Thread daemon = new Thread(new Daemon(), "daemon");
+        daemon.setDaemon(true);
+        daemon.start();
+        Check.isTrue("daemon alive", daemon.isAlive());
+
+        // main returns first, the VM still waits for this one
+        new Thread(new Sleeper(), "sleeper").start();
+    }
+}
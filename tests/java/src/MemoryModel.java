@@ -0,0 +1,136 @@
+import java.util.concurrent.atomic.AtomicInteger;
+import java.util.concurrent.atomic.AtomicLong;
+
+public class MemoryModel {
+    // A plain write published by a later volatile write
+    static class Message {
+        int data;
+        volatile boolean ready;
+    }
+
+    static class Writer implements Runnable {
+        final Message msg;
+
+        Writer(Message msg) {
+            this.msg = msg;
+        }
+
+        public void run() {
+            msg.data = 42;
+            msg.ready = true;
+        }
+    }
+
+    // Final fields are visible once the reference is, even without volatile
+    static class Holder {
+        final int x;
+        final int[] values;
+
+        Holder(int x) {
+            this.x = x;
+            this.values = new int[] { x, x + 1 };
+        }
+    }
+
+    static Holder shared;
+
+    static class Publisher implements Runnable {
+        public void run() {
+            shared = new Holder(7);
+        }
+    }
+
+    static class Incrementer implements Runnable {
+        static final AtomicInteger ints = new AtomicInteger();
+        static final AtomicLong longs = new AtomicLong();
+        static volatile int racy;
+
+        public void run() {
+            for (int i = 0; i < 1000; i++) {
+                ints.incrementAndGet();
+                longs.addAndGet(2);
+                // a volatile increment is not atomic: only bounded above
+                racy++;
+            }
+        }
+    }
+
+    // Dekker: with volatile x and y, both threads can't read 0
+    static class Dekker implements Runnable {
+        static volatile int x, y;
+        static int r1, r2;
+        final boolean first;
+
+        Dekker(boolean first) {
+            this.first = first;
+        }
+
+        public void run() {
+            if (first) {
+                x = 1;
+                r1 = y;
+            } else {
+                y = 1;
+                r2 = x;
+            }
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        int seen = 0;
+        for (int i = 0; i < 20; i++) {
+            Message msg = new Message();
+            Thread t = new Thread(new Writer(msg));
+            t.start();
+            while (!msg.ready) {
+                Thread.yield();
+            }
+            seen += msg.data;
+            t.join();
+        }
+        Check.equal("volatile publication", 20 * 42, seen);
+
+        Thread p = new Thread(new Publisher());
+        p.start();
+        Holder h;
+        while ((h = shared) == null) {
+            Thread.yield();
+        }
+        Check.equal("final field", 7, h.x);
+        Check.equal("final array", 15, h.values[0] + h.values[1]);
+        p.join();
+
+        Thread[] workers = new Thread[3];
+        for (int i = 0; i < workers.length; i++) {
+            workers[i] = new Thread(new Incrementer());
+            workers[i].start();
+        }
+        for (Thread w : workers) {
+            w.join();
+        }
+        Check.equal("AtomicInteger", 3000, Incrementer.ints.get());
+        Check.equal("AtomicLong", 6000, Incrementer.longs.get());
+        Check.equal("racy bounded", 1, Incrementer.racy <= 3000 ? 1 : 0);
+
+        AtomicInteger cas = new AtomicInteger(5);
+        Check.equal("cas hit", 1, cas.compareAndSet(5, 6) ? 1 : 0);
+        Check.equal("cas miss", 0, cas.compareAndSet(5, 7) ? 1 : 0);
+        Check.equal("cas value", 6, cas.get());
+
+        int bothZero = 0;
+        for (int i = 0; i < 20; i++) {
+            Dekker.x = 0;
+            Dekker.y = 0;
+            Thread a = new Thread(new Dekker(true));
+            Thread b = new Thread(new Dekker(false));
+            a.start();
+            b.start();
+            a.join();
+            b.join();
+            if (Dekker.r1 == 0 && Dekker.r2 == 0) {
+                bothZero++;
+            }
+        }
+        Check.equal("dekker both zero", 0, bothZero);
+    }
+}
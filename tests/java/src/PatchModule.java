@@ -0,0 +1,12 @@
+import java.util.EventObject;
+
+// Runs with java.util.EventObject in --patch-module java.base= and in
+// -Xbootclasspath/a: (see BOOT_CLASS_PATH_TESTS of the integration test)
+public class PatchModule {
+    public static void main(String[] args) {
+        // the patch is searched before the runtime image and the appended path
+        Check.equal("EventObject", "patched EventObject", new EventObject("x").toString());
+        Check.isTrue("boot loader", EventObject.class.getClassLoader() == null);
+        Check.isTrue("java.base", "java.base".equals(EventObject.class.getModule().getName()));
+    }
+}
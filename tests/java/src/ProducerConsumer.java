@@ -0,0 +1,219 @@
+public class ProducerConsumer {
+    static final int ITEMS = 200;
+
+    // Bounded buffer guarded by its own monitor, waiting with the usual loop
+    static class Buffer {
+        private final int[] items = new int[2];
+        private int head, count;
+
+        synchronized void put(int v) throws InterruptedException {
+            while (count == items.length) {
+                wait();
+            }
+            items[(head + count) % items.length] = v;
+            count++;
+            notifyAll();
+        }
+
+        synchronized int take() throws InterruptedException {
+            while (count == 0) {
+                wait();
+            }
+            int v = items[head];
+            head = (head + 1) % items.length;
+            count--;
+            notifyAll();
+            return v;
+        }
+    }
+
+    static class Producer implements Runnable {
+        final Buffer buffer;
+        final int from;
+
+        Producer(Buffer buffer, int from) {
+            this.buffer = buffer;
+            this.from = from;
+        }
+
+        public void run() {
+            try {
+                for (int i = 0; i < ITEMS / 2; i++) {
+                    buffer.put(from + i);
+                }
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+        }
+    }
+
+    static class Consumer implements Runnable {
+        final Buffer buffer;
+        long sum;
+
+        Consumer(Buffer buffer) {
+            this.buffer = buffer;
+        }
+
+        public void run() {
+            try {
+                for (int i = 0; i < ITEMS; i++) {
+                    sum += buffer.take();
+                }
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+        }
+    }
+
+    // Two threads taking turns, each notify() wakes exactly the other one
+    static class PingPong implements Runnable {
+        static final Object lock = new Object();
+        static int turn;
+        static int rounds;
+        final int me;
+
+        PingPong(int me) {
+            this.me = me;
+        }
+
+        public void run() {
+            synchronized (lock) {
+                try {
+                    for (int i = 0; i < 50; i++) {
+                        while (turn != me) {
+                            lock.wait();
+                        }
+                        rounds++;
+                        turn = 1 - me;
+                        lock.notify();
+                    }
+                } catch (InterruptedException e) {
+                    throw new RuntimeException(e);
+                }
+            }
+        }
+    }
+
+    // Waits inside nested synchronized blocks
+    static class Nested implements Runnable {
+        static final Object lock = new Object();
+        static boolean released;
+        static boolean ownedAfterWait;
+
+        public void run() {
+            synchronized (lock) {
+                synchronized (lock) {
+                    synchronized (lock) {
+                        try {
+                            while (!released) {
+                                lock.wait();
+                            }
+                        } catch (InterruptedException e) {
+                            throw new RuntimeException(e);
+                        }
+                        ownedAfterWait = Thread.holdsLock(lock);
+                    }
+                }
+                // Still held once after leaving two levels
+                ownedAfterWait &= Thread.holdsLock(lock);
+            }
+        }
+    }
+
+    static class Counter implements Runnable {
+        static int value;
+
+        static synchronized void increment() {
+            int v = value;
+            Thread.yield();
+            value = v + 1;
+        }
+
+        public void run() {
+            for (int i = 0; i < 500; i++) {
+                increment();
+            }
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        // Two producers, one consumer over a buffer of two slots
+        Buffer buffer = new Buffer();
+        Consumer consumer = new Consumer(buffer);
+        Thread c = new Thread(consumer);
+        Thread p1 = new Thread(new Producer(buffer, 0));
+        Thread p2 = new Thread(new Producer(buffer, 1000));
+        c.start();
+        p1.start();
+        p2.start();
+        p1.join();
+        p2.join();
+        c.join();
+        // 0..99 + 1000..1099
+        Check.equal("consumed sum", 4950 + 100 * 1000 + 4950, consumer.sum);
+
+        // notify wakes a single waiter
+        Thread ping = new Thread(new PingPong(0));
+        Thread pong = new Thread(new PingPong(1));
+        ping.start();
+        pong.start();
+        ping.join();
+        pong.join();
+        Check.equal("ping pong rounds", 100, PingPong.rounds);
+
+        // Reentrancy count is restored after wait
+        Thread nested = new Thread(new Nested());
+        nested.start();
+        while (nested.getState() != Thread.State.WAITING) {
+            Thread.sleep(1);
+        }
+        synchronized (Nested.lock) {
+            Nested.released = true;
+            Nested.lock.notifyAll();
+        }
+        nested.join();
+        Check.isTrue("owned after wait", Nested.ownedAfterWait);
+        synchronized (Nested.lock) {
+            Check.isTrue("lock released", Thread.holdsLock(Nested.lock));
+        }
+
+        // Timed wait returns without a notification
+        Object timed = new Object();
+        long start = System.currentTimeMillis();
+        synchronized (timed) {
+            timed.wait(30);
+            timed.notify();
+        }
+        Check.isTrue("timed wait", System.currentTimeMillis() - start >= 30);
+
+        // static synchronized locks the class
+        Thread a = new Thread(new Counter());
+        Thread b = new Thread(new Counter());
+        a.start();
+        b.start();
+        a.join();
+        b.join();
+        Check.equal("static synchronized", 1000, Counter.value);
+
+        // The caller must own the monitor
+        Object unowned = new Object();
+        int caught = 0;
+        try {
+            unowned.wait();
+        } catch (IllegalMonitorStateException e) {
+            caught++;
+        }
+        try {
+            unowned.notify();
+        } catch (IllegalMonitorStateException e) {
+            caught++;
+        }
+        try {
+            unowned.notifyAll();
+        } catch (IllegalMonitorStateException e) {
+            caught++;
+        }
+        Check.equal("illegal monitor state", 3, caught);
+    }
+}
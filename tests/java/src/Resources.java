@@ -0,0 +1,42 @@
+import java.io.InputStream;
+import java.net.URL;
+import java.util.Enumeration;
+import java.util.Properties;
+
+public class Resources {
+    public static void main(String[] args) throws Exception {
+        // a properties file in a class path directory
+        URL url = ClassLoader.getSystemResource("conf/app.properties");
+        Check.isTrue("file url", url != null && "file".equals(url.getProtocol())
+                && url.getPath().endsWith("/conf/app.properties"));
+        Properties props = new Properties();
+        try (InputStream in = ClassLoader.getSystemResourceAsStream("conf/app.properties")) {
+            props.load(in);
+        }
+        Check.isTrue("properties", "resources".equals(props.getProperty("name")));
+        Check.isTrue("absolute name", Resources.class.getResourceAsStream("/conf/app.properties") != null);
+        Check.isTrue("relative name", Resources.class.getResource("Resources.class") != null);
+        Check.isTrue("url stream", url.openStream().read() == 'n');
+
+        int n = 0;
+        for (Enumeration<URL> e = ClassLoader.getSystemResources("conf/app.properties"); e.hasMoreElements(); e.nextElement()) {
+            n++;
+        }
+        Check.isTrue("getResources", n == 1);
+        Check.isTrue("missing", ClassLoader.getSystemResource("conf/missing.properties") == null);
+
+        // resources of the runtime image
+        URL object = Object.class.getResource("Object.class");
+        Check.isTrue("jrt url", object != null && "jrt".equals(object.getProtocol()));
+        byte[] bytes;
+        try (InputStream in = Object.class.getResourceAsStream("Object.class")) {
+            bytes = in.readAllBytes();
+        }
+        Check.isTrue("class bytes", bytes.length > 4 && (bytes[0] & 0xff) == 0xca && (bytes[1] & 0xff) == 0xfe);
+        Check.isTrue("jrt stream", object.openStream().read() == 0xca);
+        Check.isTrue("other module", ClassLoader.getSystemResource("javax/xml/XMLConstants.class") != null);
+
+        // only class files of a package in a module that isn't open are visible
+        Check.isTrue("encapsulated", ClassLoader.getSystemResource("java/lang/uniName.dat") == null);
+    }
+}
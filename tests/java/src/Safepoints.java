@@ -0,0 +1,99 @@
+import java.util.Map;
+
+public class Safepoints {
+    static final Object lock = new Object();
+    static volatile boolean stop;
+    static volatile long spins;
+
+    // Never calls a method in its loop: only back-edge polls can stop it
+    static class Spinner implements Runnable {
+        public void run() {
+            long n = 0;
+            while (!stop) {
+                n++;
+                if ((n & 0xfff) == 0) {
+                    spins = n;
+                }
+            }
+        }
+    }
+
+    // Blocks entering a monitor held by main
+    static class Contender implements Runnable {
+        public void run() {
+            synchronized (lock) {
+                spins = spins;
+            }
+        }
+    }
+
+    // Blocks in a native sleep
+    static class Sleeper implements Runnable {
+        public void run() {
+            try {
+                Thread.sleep(60000);
+            } catch (InterruptedException e) {
+                // expected
+            }
+        }
+    }
+
+    static boolean hasFrame(StackTraceElement[] trace, String method) {
+        for (StackTraceElement e : trace) {
+            if (e.getMethodName().equals(method)) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    static void waitFor(Thread t, Thread.State state) throws InterruptedException {
+        while (t.getState() != state) {
+            Thread.sleep(1);
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        Thread spinner = new Thread(new Spinner(), "spinner");
+        Thread contender = new Thread(new Contender(), "contender");
+        Thread sleeper = new Thread(new Sleeper(), "sleeper");
+
+        synchronized (lock) {
+            spinner.start();
+            contender.start();
+            sleeper.start();
+            while (spins == 0) {
+                Thread.sleep(1);
+            }
+            // let the contender reach the monitor
+            Thread.sleep(50);
+            waitFor(sleeper, Thread.State.TIMED_WAITING);
+
+            // each stack trace stops every thread, whatever it is doing
+            for (int i = 0; i < 20; i++) {
+                Check.isTrue("spinner trace " + i, hasFrame(spinner.getStackTrace(), "run"));
+                Check.isTrue("contender trace " + i, hasFrame(contender.getStackTrace(), "run"));
+                Check.isTrue("sleeper trace " + i, hasFrame(sleeper.getStackTrace(), "sleep"));
+            }
+
+            Map<Thread, StackTraceElement[]> all = Thread.getAllStackTraces();
+            Check.isTrue("all traces", all.containsKey(spinner) && all.containsKey(contender)
+                    && all.containsKey(sleeper) && all.containsKey(Thread.currentThread()));
+            Check.isTrue("main trace", hasFrame(all.get(Thread.currentThread()), "main"));
+
+            // the spinner runs again after the safepoints
+            long before = spins;
+            while (spins == before) {
+                Thread.sleep(1);
+            }
+            Check.isTrue("spinner resumed", true);
+        }
+
+        stop = true;
+        sleeper.interrupt();
+        spinner.join();
+        contender.join();
+        sleeper.join();
+        Check.isTrue("all joined", !spinner.isAlive() && !contender.isAlive() && !sleeper.isAlive());
+    }
+}
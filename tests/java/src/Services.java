@@ -0,0 +1,70 @@
+import java.net.URL;
+import java.nio.charset.spi.CharsetProvider;
+import java.nio.file.spi.FileSystemProvider;
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+import java.util.ServiceLoader;
+
+public class Services {
+    public interface Greeter {
+        String greet(String who);
+    }
+
+    public static class Hello implements Greeter {
+        public String greet(String who) {
+            return "hello " + who;
+        }
+    }
+
+    public static class Hi implements Greeter {
+        public String greet(String who) {
+            return "hi " + who;
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        // META-INF/services in a class path directory, then in greeters.jar
+        // with Howdy, which is only in the jar; duplicates dropped
+        List<String> greetings = new ArrayList<>();
+        for (Greeter g : ServiceLoader.load(Greeter.class)) {
+            greetings.add(g.greet("x"));
+        }
+        Check.isTrue("class path providers", greetings.equals(List.of("hello x", "hi x", "howdy x")));
+        Check.isTrue("findFirst", ServiceLoader.load(Greeter.class).findFirst().get() instanceof Hello);
+        Check.isTrue("stream", ServiceLoader.load(Greeter.class).stream().count() == 3);
+        List<String> protocols = new ArrayList<>();
+        for (URL url : Collections.list(ClassLoader.getSystemResources("META-INF/services/Services$Greeter"))) {
+            protocols.add(url.getProtocol());
+        }
+        Check.isTrue("services files", protocols.equals(List.of("file", "jar")));
+        Check.isTrue("provider from jar", ClassLoader.getSystemResource("Howdy.class").getProtocol().equals("jar"));
+
+        // a loader of the application's own, asked through loadClass
+        ClassLoader custom = new ClassLoader(ClassLoader.getSystemClassLoader()) {};
+        List<String> viaCustom = new ArrayList<>();
+        for (Greeter g : ServiceLoader.load(Greeter.class, custom)) {
+            viaCustom.add(g.greet("x"));
+        }
+        Check.isTrue("custom loader providers", viaCustom.equals(greetings));
+        Check.isTrue("custom loader forName", Class.forName("Howdy", false, custom).getName().equals("Howdy"));
+        try {
+            Class.forName("NoSuchGreeter", false, custom);
+            Check.isTrue("custom loader missing class", false);
+        } catch (ClassNotFoundException e) {
+            Check.isTrue("custom loader missing class", e.getMessage().equals("NoSuchGreeter"));
+        }
+
+        // provides clauses of the modules in the runtime image
+        List<String> fs = new ArrayList<>();
+        for (FileSystemProvider p : ServiceLoader.load(FileSystemProvider.class)) {
+            fs.add(p.getClass().getName());
+        }
+        Check.isTrue("zipfs", fs.contains("jdk.nio.zipfs.ZipFileSystemProvider"));
+        boolean ext = false;
+        for (CharsetProvider p : ServiceLoader.load(CharsetProvider.class, ClassLoader.getSystemClassLoader())) {
+            ext |= p.getClass().getName().equals("sun.nio.cs.ext.ExtendedCharsets");
+        }
+        Check.isTrue("charsets", ext);
+    }
+}
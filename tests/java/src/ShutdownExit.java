@@ -0,0 +1,18 @@
+public class ShutdownExit {
+    static class Hook extends Thread {
+        public void run() {
+            System.out.println("hook ran");
+        }
+    }
+
+    public static void main(String[] args) {
+        Runtime.getRuntime().addShutdownHook(new Hook());
+        try {
+            System.out.println("exiting with 3");
+            System.exit(3);
+        } finally {
+            // System.exit doesn't return, not even through finally
+            System.out.println("finally ran");
+        }
+    }
+}
@@ -0,0 +1,68 @@
+public class ShutdownHooks {
+    static final Object forever = new Object();
+
+    static class Hook extends Thread {
+        Hook(String name) {
+            super(name);
+        }
+
+        public void run() {
+            System.out.println("hook " + getName() + " ran");
+        }
+    }
+
+    static class Blocker implements Runnable {
+        public void run() {
+            synchronized (forever) {
+                try {
+                    // never notified, only System.exit ends the VM
+                    forever.wait();
+                } catch (InterruptedException e) {
+                    throw new RuntimeException(e);
+                }
+            }
+        }
+    }
+
+    static class Exiter implements Runnable {
+        public void run() {
+            try {
+                Thread.sleep(100);
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+            System.out.println("exiting");
+            System.exit(0);
+            throw new RuntimeException("System.exit returned");
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        Runtime rt = Runtime.getRuntime();
+
+        Hook kept = new Hook("kept");
+        rt.addShutdownHook(kept);
+
+        Hook removed = new Hook("removed");
+        rt.addShutdownHook(removed);
+        Check.isTrue("remove hook", rt.removeShutdownHook(removed));
+        Check.isTrue("remove twice", !rt.removeShutdownHook(removed));
+
+        boolean duplicate = false;
+        try {
+            rt.addShutdownHook(kept);
+        } catch (IllegalArgumentException e) {
+            duplicate = true;
+        }
+        Check.isTrue("duplicate hook", duplicate);
+
+        Thread blocker = new Thread(new Blocker(), "blocker");
+        blocker.start();
+        Check.isTrue("blocker not daemon", !blocker.isDaemon());
+
+        // main returns first, the VM waits for the non-daemon threads until
+        // the exiter runs the hooks and halts
+        new Thread(new Exiter(), "exiter").start();
+        System.out.println("main done");
+    }
+}
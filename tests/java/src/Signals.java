@@ -0,0 +1,69 @@
+import java.util.concurrent.CountDownLatch;
+import java.util.concurrent.TimeUnit;
+import sun.misc.Signal;
+import sun.misc.SignalHandler;
+
+public class Signals {
+    static final Object lock = new Object();
+
+    static class Handler implements SignalHandler {
+        final CountDownLatch handled = new CountDownLatch(1);
+        volatile String name;
+
+        public void handle(Signal sig) {
+            name = sig.getName();
+            handled.countDown();
+        }
+    }
+
+    static class Contender implements Runnable {
+        public void run() {
+            synchronized (lock) {
+                System.out.println("contender got the lock");
+            }
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        Check.isTrue("find USR2", new Signal("USR2").getNumber() > 0);
+        boolean unknown = false;
+        try {
+            new Signal("NO_SUCH_SIGNAL");
+        } catch (IllegalArgumentException e) {
+            unknown = true;
+        }
+        Check.isTrue("unknown signal", unknown);
+
+        // a Java handler runs on its own thread
+        Handler handler = new Handler();
+        Signal usr2 = new Signal("USR2");
+        Signal.handle(usr2, handler);
+        Signal.raise(usr2);
+        Check.isTrue("handler ran", handler.handled.await(10, TimeUnit.SECONDS));
+        Check.isTrue("handler signal", "USR2".equals(handler.name));
+
+        // the previous handler is returned when replaced
+        SignalHandler old = Signal.handle(usr2, SignalHandler.SIG_IGN);
+        Check.isTrue("old handler", old == handler);
+
+        // SIGQUIT prints a thread dump, Java code can't take it over
+        boolean reserved = false;
+        try {
+            Signal.handle(new Signal("QUIT"), handler);
+        } catch (IllegalArgumentException e) {
+            reserved = true;
+        }
+        Check.isTrue("QUIT reserved", reserved);
+
+        Thread contender = new Thread(new Contender(), "contender");
+        synchronized (lock) {
+            contender.start();
+            while (contender.getState() != Thread.State.BLOCKED) {
+                Thread.sleep(1);
+            }
+            Check.isTrue("contender blocked", true);
+        }
+        contender.join();
+        Check.isTrue("contender done", contender.getState() == Thread.State.TERMINATED);
+    }
+}
@@ -0,0 +1,36 @@
+import java.io.File;
+import java.nio.charset.Charset;
+import java.util.Properties;
+
+public class SystemInit {
+    public static void main(String[] args) throws Exception {
+        // initPhase1: system properties from the VM and the platform
+        Properties props = System.getProperties();
+        Check.isTrue("java.home", new File(props.getProperty("java.home")).isDirectory());
+        Check.isTrue("java.class.path", props.getProperty("java.class.path") != null);
+        Check.isTrue("os.name", props.getProperty("os.name") != null);
+        Check.isTrue("line.separator", System.lineSeparator().equals(props.getProperty("line.separator")));
+        Check.isTrue("user.dir", new File(System.getProperty("user.dir")).isAbsolute());
+        Check.isTrue("file.encoding", Charset.isSupported(System.getProperty("file.encoding")));
+        Check.isTrue("default charset", Charset.defaultCharset() != null);
+
+        System.setProperty("system.init.test", "set");
+        Check.isTrue("setProperty", "set".equals(System.getProperty("system.init.test")));
+        System.clearProperty("system.init.test");
+        Check.isTrue("clearProperty", System.getProperty("system.init.test") == null);
+
+        // initPhase2: java.base is defined and every boot class is in it
+        Module base = Object.class.getModule();
+        Check.isTrue("java.base", base.isNamed() && "java.base".equals(base.getName()));
+        Check.isTrue("same module", String.class.getModule() == base);
+
+        // initPhase3: the system class loader is set up
+        ClassLoader app = ClassLoader.getSystemClassLoader();
+        Check.isTrue("system loader", app != null && app.getParent() == ClassLoader.getPlatformClassLoader());
+
+        // stack traces come from the frames that threw
+        StackTraceElement[] trace = new Throwable().getStackTrace();
+        Check.isTrue("stack trace", trace.length == 1 && "main".equals(trace[0].getMethodName())
+                && "SystemInit.java".equals(trace[0].getFileName()));
+    }
+}
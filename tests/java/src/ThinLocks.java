@@ -0,0 +1,111 @@
+import java.util.Hashtable;
+import java.util.Vector;
+
+public class ThinLocks {
+    static final Object lock = new Object();
+    static int counter;
+    static int staticCounter;
+
+    static synchronized void incStatic() {
+        staticCounter++;
+    }
+
+    static int nested(Object o, int depth) {
+        synchronized (o) {
+            return depth == 0 ? 0 : 1 + nested(o, depth - 1);
+        }
+    }
+
+    static class Adder implements Runnable {
+        public void run() {
+            for (int i = 0; i < 10000; i++) {
+                synchronized (lock) {
+                    counter++;
+                }
+                incStatic();
+            }
+        }
+    }
+
+    static class Notifier implements Runnable {
+        final Object o;
+
+        Notifier(Object o) {
+            this.o = o;
+        }
+
+        public void run() {
+            synchronized (o) {
+                o.notifyAll();
+            }
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        // uncontended: synchronized JDK collections
+        StringBuffer sb = new StringBuffer();
+        Vector<Integer> v = new Vector<Integer>();
+        Hashtable<Integer, Integer> h = new Hashtable<Integer, Integer>();
+        for (int i = 0; i < 1000; i++) {
+            sb.append(i % 10);
+            v.add(i);
+            h.put(i, i * 2);
+        }
+        Check.isTrue("StringBuffer", sb.length() == 1000 && sb.charAt(999) == '9');
+        Check.isTrue("Vector", v.size() == 1000 && v.get(500) == 500);
+        Check.isTrue("Hashtable", h.size() == 1000 && h.get(21) == 42);
+
+        // recursive locking
+        Object o = new Object();
+        Check.isTrue("nested", nested(o, 50) == 50);
+        synchronized (o) {
+            synchronized (o) {
+                Check.isTrue("holdsLock nested", Thread.holdsLock(o));
+            }
+            Check.isTrue("holdsLock outer", Thread.holdsLock(o));
+        }
+        Check.isTrue("released", !Thread.holdsLock(o));
+
+        // notify without waiters, and without the lock
+        synchronized (o) {
+            o.notify();
+            o.notifyAll();
+        }
+        boolean imse = false;
+        try {
+            o.notify();
+        } catch (IllegalMonitorStateException e) {
+            imse = true;
+        }
+        Check.isTrue("notify unlocked", imse);
+
+        // contention inflates the lock, mutual exclusion still holds
+        Thread[] adders = new Thread[4];
+        for (int i = 0; i < adders.length; i++) {
+            adders[i] = new Thread(new Adder(), "adder-" + i);
+            adders[i].start();
+        }
+        for (int i = 0; i < adders.length; i++) {
+            adders[i].join();
+        }
+        Check.isTrue("contended counter", counter == 40000);
+        Check.isTrue("static synchronized", staticCounter == 40000);
+        synchronized (lock) {
+            Check.isTrue("holdsLock inflated", Thread.holdsLock(lock));
+        }
+
+        // wait on a thin-locked object, held recursively
+        Object w = new Object();
+        synchronized (w) {
+            synchronized (w) {
+                Thread n = new Thread(new Notifier(w), "notifier");
+                n.start();
+                w.wait(10000);
+                Check.isTrue("wait keeps recursion", Thread.holdsLock(w));
+                n.join();
+            }
+            Check.isTrue("outer still held", Thread.holdsLock(w));
+        }
+        Check.isTrue("wait released", !Thread.holdsLock(w));
+    }
+}
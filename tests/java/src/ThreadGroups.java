@@ -0,0 +1,151 @@
+import java.io.BufferedReader;
+import java.io.FileReader;
+import java.io.IOException;
+import java.util.concurrent.CountDownLatch;
+
+public class ThreadGroups {
+    static final ThreadLocal<String> local = new ThreadLocal<String>() {
+        protected String initialValue() {
+            return "initial";
+        }
+    };
+
+    static final InheritableThreadLocal<String> inherited = new InheritableThreadLocal<String>() {
+        protected String childValue(String parentValue) {
+            return parentValue + "+child";
+        }
+    };
+
+    static class Locals implements Runnable {
+        volatile String seenLocal;
+        volatile String seenInherited;
+        volatile String afterSet;
+        volatile int priority;
+
+        public void run() {
+            seenLocal = local.get();
+            seenInherited = inherited.get();
+            local.set("worker");
+            afterSet = local.get();
+            priority = Thread.currentThread().getPriority();
+        }
+    }
+
+    // Nice value of the current thread, field 19 of /proc/thread-self/stat
+    static int nice() {
+        try (BufferedReader in = new BufferedReader(new FileReader("/proc/thread-self/stat"))) {
+            String stat = in.readLine();
+            String[] fields = stat.substring(stat.lastIndexOf(')') + 2).split(" ");
+            return Integer.parseInt(fields[16]);
+        } catch (IOException e) {
+            throw new RuntimeException(e);
+        }
+    }
+
+    static class Nice implements Runnable {
+        volatile int nice;
+
+        public void run() {
+            nice = nice();
+        }
+    }
+
+    static class Waiter implements Runnable {
+        final CountDownLatch started;
+        final CountDownLatch release;
+
+        Waiter(CountDownLatch started, CountDownLatch release) {
+            this.started = started;
+            this.release = release;
+        }
+
+        public void run() {
+            started.countDown();
+            try {
+                release.await();
+            } catch (InterruptedException e) {
+                throw new RuntimeException(e);
+            }
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        Thread main = Thread.currentThread();
+        ThreadGroup mainGroup = main.getThreadGroup();
+        Check.isTrue("main group", mainGroup != null && "main".equals(mainGroup.getName()));
+        Check.isTrue("system group", "system".equals(mainGroup.getParent().getName()));
+        Check.isTrue("main name", "main".equals(main.getName()));
+        Check.isTrue("main priority", main.getPriority() == Thread.NORM_PRIORITY);
+
+        // the main thread is a member of its group
+        Thread[] found = new Thread[mainGroup.activeCount() + 4];
+        int n = mainGroup.enumerate(found, false);
+        boolean hasMain = false;
+        for (int i = 0; i < n; i++) {
+            hasMain |= found[i] == main;
+        }
+        Check.isTrue("main enumerated", hasMain);
+
+        // ThreadLocal values are per thread, InheritableThreadLocal values
+        // are copied when a thread is created
+        local.set("main");
+        inherited.set("main");
+        Locals locals = new Locals();
+        Thread t = new Thread(locals, "locals");
+        t.setPriority(Thread.MIN_PRIORITY);
+        t.start();
+        t.join();
+        Check.isTrue("local initial", "initial".equals(locals.seenLocal));
+        Check.isTrue("local set", "worker".equals(locals.afterSet));
+        Check.isTrue("local unchanged", "main".equals(local.get()));
+        Check.isTrue("inherited", "main+child".equals(locals.seenInherited));
+        local.remove();
+        Check.isTrue("local removed", "initial".equals(local.get()));
+
+        // priorities: set before start, and capped by the group
+        Check.isTrue("min priority", locals.priority == Thread.MIN_PRIORITY);
+        // the worker that ran it doesn't keep a lower priority for the next
+        // thread, which gets it once it is idle again
+        Thread.sleep(200);
+        Nice nice = new Nice();
+        Thread next = new Thread(nice, "next");
+        next.start();
+        next.join();
+        Check.isTrue("next thread's nice value", nice.nice == nice());
+        ThreadGroup workers = new ThreadGroup("workers");
+        Check.isTrue("group parent", workers.getParent() == mainGroup);
+        workers.setMaxPriority(Thread.NORM_PRIORITY - 1);
+
+        CountDownLatch started = new CountDownLatch(3);
+        CountDownLatch release = new CountDownLatch(1);
+        Thread[] waiters = new Thread[3];
+        for (int i = 0; i < waiters.length; i++) {
+            waiters[i] = new Thread(workers, new Waiter(started, release), "waiter-" + i);
+            waiters[i].setPriority(Thread.MAX_PRIORITY);
+            waiters[i].start();
+        }
+        started.await();
+        Check.isTrue("capped priority", waiters[0].getPriority() == Thread.NORM_PRIORITY - 1);
+        Check.isTrue("thread group", waiters[1].getThreadGroup() == workers);
+        Check.isTrue("active count", workers.activeCount() == 3);
+
+        Thread[] list = new Thread[8];
+        n = workers.enumerate(list);
+        Check.isTrue("enumerate", n == 3);
+        ThreadGroup[] groups = new ThreadGroup[4];
+        int g = mainGroup.enumerate(groups, false);
+        boolean hasWorkers = false;
+        for (int i = 0; i < g; i++) {
+            hasWorkers |= groups[i] == workers;
+        }
+        Check.isTrue("enumerate groups", hasWorkers);
+
+        release.countDown();
+        for (int i = 0; i < waiters.length; i++) {
+            waiters[i].join();
+        }
+        // terminated threads leave their group
+        Check.isTrue("group empty", workers.activeCount() == 0);
+        Check.isTrue("no group after exit", waiters[2].getThreadGroup() == null);
+    }
+}
@@ -0,0 +1,131 @@
+import java.util.concurrent.locks.LockSupport;
+
+public class ThreadLifecycle {
+    static final Object lock = new Object();
+    static volatile boolean ready;
+
+    static final int[] result = new int[1];
+    static final boolean[] caught = new boolean[3];
+
+    static class Worker implements Runnable {
+        public void run() {
+            result[0] = 42;
+        }
+    }
+
+    static class Sleeper implements Runnable {
+        public void run() {
+            try {
+                Thread.sleep(60_000);
+            } catch (InterruptedException e) {
+                caught[0] = !Thread.currentThread().isInterrupted();
+            }
+        }
+    }
+
+    static class Waiter implements Runnable {
+        public void run() {
+            synchronized (lock) {
+                try {
+                    ready = true;
+                    lock.wait();
+                } catch (InterruptedException e) {
+                    caught[1] = Thread.holdsLock(lock);
+                }
+            }
+        }
+    }
+
+    static class Parker implements Runnable {
+        public void run() {
+            LockSupport.park();
+            caught[2] = Thread.interrupted() && !Thread.currentThread().isInterrupted();
+        }
+    }
+
+    static class Blocked implements Runnable {
+        public void run() {
+            LockSupport.park();
+        }
+    }
+
+    static void awaitState(Thread t, Thread.State state) throws InterruptedException {
+        while (t.getState() != state) {
+            Thread.sleep(1);
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        // start / join / isAlive
+        Thread worker = new Thread(new Worker());
+        Check.isTrue("new state", worker.getState() == Thread.State.NEW);
+        worker.start();
+        worker.join();
+        Check.isTrue("join result", result[0] == 42);
+        Check.isTrue("not alive", !worker.isAlive());
+        Check.isTrue("terminated state", worker.getState() == Thread.State.TERMINATED);
+
+        // sleep
+        long start = System.currentTimeMillis();
+        Thread.sleep(20);
+        Check.isTrue("sleep", System.currentTimeMillis() - start >= 20);
+        Thread.yield();
+
+        // interrupt wakes sleep
+        Thread sleeper = new Thread(new Sleeper());
+        sleeper.start();
+        awaitState(sleeper, Thread.State.TIMED_WAITING);
+        sleeper.interrupt();
+        sleeper.join();
+        Check.isTrue("interrupt sleep", caught[0]);
+
+        // interrupt wakes Object.wait
+        Thread waiter = new Thread(new Waiter());
+        waiter.start();
+        awaitState(waiter, Thread.State.WAITING);
+        Check.isTrue("waiter ready", ready);
+        waiter.interrupt();
+        waiter.join();
+        Check.isTrue("interrupt wait", caught[1]);
+
+        // interrupt wakes LockSupport.park, keeping the status set
+        Thread parker = new Thread(new Parker());
+        parker.start();
+        awaitState(parker, Thread.State.WAITING);
+        parker.interrupt();
+        parker.join();
+        Check.isTrue("interrupt park", caught[2]);
+
+        // unpark before park doesn't block
+        LockSupport.unpark(Thread.currentThread());
+        LockSupport.park();
+        Check.isTrue("unpark permit", true);
+
+        // interrupted before sleeping
+        Thread.currentThread().interrupt();
+        try {
+            Thread.sleep(10);
+            throw new RuntimeException("sleep: expected InterruptedException");
+        } catch (InterruptedException e) {
+            Check.isTrue("pending interrupt", !Thread.interrupted());
+        }
+
+        // holdsLock
+        Check.isTrue("holdsLock outside", !Thread.holdsLock(lock));
+        synchronized (lock) {
+            Check.isTrue("holdsLock inside", Thread.holdsLock(lock));
+        }
+
+        // stack trace of another thread
+        Thread blocked = new Thread(new Blocked(), "blocked-worker");
+        blocked.start();
+        awaitState(blocked, Thread.State.WAITING);
+        StackTraceElement[] trace = blocked.getStackTrace();
+        Check.isTrue("stack trace", trace.length > 0);
+        Check.isTrue("thread name", blocked.getName().equals("blocked-worker"));
+        Check.isTrue("all threads", Thread.getAllStackTraces().containsKey(blocked));
+        LockSupport.unpark(blocked);
+        blocked.join();
+        Check.isTrue("unpark", !blocked.isAlive());
+    }
+}
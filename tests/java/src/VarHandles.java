@@ -0,0 +1,121 @@
+import java.lang.invoke.MethodHandles;
+import java.lang.invoke.VarHandle;
+import java.util.Objects;
+
+public class VarHandles {
+    volatile int count;
+    long total;
+    final String name = "fixed";
+    Object ref;
+    static int shared;
+
+    static final VarHandle COUNT;
+    static final VarHandle TOTAL;
+    static final VarHandle NAME;
+    static final VarHandle REF;
+    static final VarHandle SHARED;
+    static final VarHandle LONGS = MethodHandles.arrayElementVarHandle(long[].class);
+    static final VarHandle STRINGS = MethodHandles.arrayElementVarHandle(String[].class);
+
+    static {
+        try {
+            MethodHandles.Lookup l = MethodHandles.lookup();
+            COUNT = l.findVarHandle(VarHandles.class, "count", int.class);
+            TOTAL = l.findVarHandle(VarHandles.class, "total", long.class);
+            NAME = l.findVarHandle(VarHandles.class, "name", String.class);
+            REF = l.findVarHandle(VarHandles.class, "ref", Object.class);
+            SHARED = l.findStaticVarHandle(VarHandles.class, "shared", int.class);
+        } catch (ReflectiveOperationException e) {
+            throw new ExceptionInInitializerError(e);
+        }
+    }
+
+    static class Nested {
+        private int hidden = 4;
+    }
+
+    public static void main(String[] args) throws Exception {
+        VarHandles v = new VarHandles();
+
+        Check.equal("cas", true, COUNT.compareAndSet(v, 0, 5));
+        Check.equal("failed cas", false, COUNT.compareAndSet(v, 0, 6));
+        Check.equal("getAndAdd", 5, (int) COUNT.getAndAdd(v, 3));
+        COUNT.setVolatile(v, 10);
+        Check.equal("getVolatile", 10, (int) COUNT.getVolatile(v));
+        Check.equal("getAndBitwiseOr", 10, (int) COUNT.getAndBitwiseOr(v, 5));
+        Check.equal("field", 15, v.count);
+
+        // int arguments widen to the long of the field, the result is discarded
+        TOTAL.getAndAdd(v, 7);
+        TOTAL.set(v, (long) TOTAL.get(v) * 6);
+        Check.equal("long field", 42L, v.total);
+        // boxed for a call site typed Object
+        Object boxed = TOTAL.getAcquire(v);
+        Check.equal("boxed", Long.valueOf(42), boxed);
+
+        Check.equal("exchange", null, REF.compareAndExchange(v, null, "a"));
+        Check.equal("getAndSet", "a", REF.getAndSet(v, "b"));
+        Check.equal("ref", "b", v.ref);
+
+        SHARED.set(3);
+        Check.equal("static getAndAdd", 3, (int) SHARED.getAndAdd(4));
+        Check.equal("static field", 7, shared);
+
+        long[] longs = new long[3];
+        LONGS.set(longs, 1, 9L);
+        Check.equal("long element cas", true, LONGS.compareAndSet(longs, 1, 9L, 11L));
+        Check.equal("long element", 11L, longs[1]);
+
+        String[] strings = new String[2];
+        STRINGS.setRelease(strings, 0, "x");
+        Check.equal("element", "x", (String) STRINGS.getAcquire(strings, 0));
+        try {
+            Object[] objects = strings;
+            STRINGS.set(objects, 1, Integer.valueOf(1));
+            Check.equal("element store", "ClassCastException", "none");
+        } catch (ClassCastException e) {
+            Check.equal("element store", "ClassCastException", e.getClass().getSimpleName());
+        }
+        try {
+            LONGS.get(longs, 3);
+            Check.equal("element bounds", "IndexOutOfBoundsException", "none");
+        } catch (IndexOutOfBoundsException e) {
+            Check.equal("element bounds", "IndexOutOfBoundsException", "IndexOutOfBoundsException");
+        }
+
+        Check.equal("final field", "fixed", (String) NAME.get(v));
+        try {
+            NAME.set(v, "changed");
+            Check.equal("final field set", "UnsupportedOperationException", "none");
+        } catch (UnsupportedOperationException e) {
+            Check.equal("final field set", "UnsupportedOperationException", e.getClass().getSimpleName());
+        }
+
+        try {
+            MethodHandles.lookup().findVarHandle(VarHandles.class, "missing", int.class);
+            Check.equal("missing field", "NoSuchFieldException", "none");
+        } catch (NoSuchFieldException e) {
+            Check.equal("missing field", "NoSuchFieldException", e.getClass().getSimpleName());
+        }
+        try {
+            MethodHandles.lookup().findVarHandle(VarHandles.class, "shared", int.class);
+            Check.equal("static as instance", "IllegalAccessException", "none");
+        } catch (IllegalAccessException e) {
+            Check.equal("static as instance", "IllegalAccessException", e.getClass().getSimpleName());
+        }
+
+        // private fields are accessible within the nest only
+        VarHandle hidden = MethodHandles.lookup().findVarHandle(Nested.class, "hidden", int.class);
+        Check.equal("nest mate", 4, (int) hidden.get(new Nested()));
+        try {
+            MethodHandles.lookup().findVarHandle(Outsider.class, "hidden", int.class);
+            Check.equal("outside the nest", "IllegalAccessException", "none");
+        } catch (IllegalAccessException e) {
+            Check.equal("outside the nest", "IllegalAccessException", e.getClass().getSimpleName());
+        }
+    }
+}
+
+class Outsider {
+    private int hidden;
+}
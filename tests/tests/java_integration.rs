@@ -3,6 +3,77 @@ use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
 
+/// Tests run a second time with the JIT on: the methods that must show up
+/// compiled in the `--print-compilation` log, and the reasons of the
+/// invalidations that must be logged as `made not entrant`.
+const JIT_TESTS: &[(&str, &[&str], &[&str])] = &[
+    (
+        "Devirtualization",
+        &[
+            "Devirtualization::sumIds",
+            "Devirtualization::countSides",
+            "Devirtualization::mixedValues",
+        ],
+        &[
+            "Devirtualization$Late overrides Devirtualization$Base.id",
+            "Devirtualization$Override overrides Devirtualization$Plain.value",
+        ],
+    ),
+    ("Intrinsics", &["Intrinsics::mathKernel"], &[]),
+    (
+        "JitArithmetic",
+        &[
+            "JitArithmetic::f2i",
+            "JitArithmetic::div",
+            "JitArithmetic::lrem",
+            "JitArithmetic::divThenCount",
+            "JitArithmetic::fieldThenCount",
+        ],
+        &[],
+    ),
+    (
+        "JitBench",
+        &["JitBench::loopSum", "JitBench::fib", "JitBench::arraySum"],
+        &[],
+    ),
+];
+
+/// Modular tests also run with the JIT on, and the methods that must show
+/// up compiled.
+const JIT_MODULE_TESTS: &[(&str, &[&str])] = &[(
+    "com.example.app/com.example.app.Main",
+    &[
+        "com/example/app/Main::cast",
+        "com/example/app/Main::isSecret",
+        "com/example/app/Main::secretClass",
+    ],
+)];
+
+/// Tests that end the VM with `System.exit` and a non-zero status.
+const EXIT_STATUS: &[(&str, i32)] = &[("ShutdownExit", 3)];
+
+/// Jars built from tests/java/jars that go on the class path of a test,
+/// after the test classes.
+const CLASS_PATH_JARS: &[(&str, &[&str])] = &[("Services", &["greeters"])];
+
+/// Tests run with their boot class path changed: directories built from
+/// tests/java/boot given to `--patch-module java.base=` and to
+/// `-Xbootclasspath/a:`.
+const BOOT_CLASS_PATH_TESTS: &[(&str, &[&str], &[&str])] = &[
+    ("PatchModule", &["patch"], &["append"]),
+    ("BootAppend", &[], &["append"]),
+];
+
+/// Jars built from tests/java/jars that run as `-jar`: the jar, the
+/// `Main-Class` of its manifest, whose java/expected file the output must
+/// match, the program arguments, and lines the stderr must contain.
+const JAR_TESTS: &[(&str, &str, &[&str], &[&str])] = &[(
+    "launcher",
+    "Launcher",
+    &["a", "b c"],
+    &["Warning: Launcher-Agent-Class LauncherAgent ignored"],
+)];
+
 fn main() {
     let class_list = env!("JAVA_TEST_DIR");
     let jvm_bin = env!("JVM_BIN");
@@ -17,59 +88,267 @@ fn main() {
     println!("Running {} Java tests against the JVM...\n", classes.len());
 
     for class in &classes {
-        println!("Running java::{}...", class);
-        let output = Command::new(jvm_bin)
-            .arg("--cp")
-            .arg(class_list)
-            .arg(class)
-            .output();
-        println!("cmd: {} --cp {} {}", jvm_bin, class_list, class);
-
-        match output {
-            Ok(out) if out.status.success() => {
-                let stderr = String::from_utf8_lossy(&out.stderr);
-                // Check stderr for uncaught exceptions or errors that indicate JVM bugs.
-                // Some tests legitimately print warnings or exceptions they catch themselves,
-                // so we only fail on ERROR-level messages that indicate unhandled problems.
-                let has_error = stderr.lines().any(|line| {
-                    line.contains("ERROR")
-                        && (line.contains("Uncaught exception")
-                            || line.contains("java.lang.NullPointerException")
-                            || line.contains("java.lang.NoSuchMethodError")
-                            || line.contains("panic"))
-                        && !line.contains("java/lang/UnsupportedOperationException")
-                    // known: invokedynamic not supported
-                });
-
-                if has_error {
-                    println!("  java::{} ... FAILED (stderr errors)", class);
-                    for line in stderr.lines().filter(|l| l.contains("ERROR")) {
-                        println!("    {}", line);
-                    }
-                    fail += 1;
-                } else {
-                    println!("  java::{} ... ok", class);
-                    pass += 1;
-                }
+        let name = format!("java::{}", class);
+        let expected = expected_output(class);
+        let status = EXIT_STATUS
+            .iter()
+            .find(|(c, _)| c == class)
+            .map_or(0, |(_, status)| *status);
+        let mut class_path = class_list.to_string();
+        for (_, jars) in CLASS_PATH_JARS.iter().filter(|(c, _)| c == class) {
+            for jar in jars.iter() {
+                let jar = PathBuf::from(class_list).join(format!("jars/{}.jar", jar));
+                class_path = format!("{}:{}", class_path, jar.display());
             }
-            Ok(out) => {
-                println!("  java::{} ... FAILED", class);
-                let stderr = String::from_utf8_lossy(&out.stderr);
-                for line in stderr.lines().take(3) {
-                    println!("    {}", line);
-                }
-                fail += 1;
+        }
+        let mut args = vec![];
+        for (_, patches, appends) in BOOT_CLASS_PATH_TESTS.iter().filter(|(c, _, _)| c == class) {
+            for dir in patches.iter() {
+                let dir = PathBuf::from(class_list).join("boot").join(dir);
+                args.push("--patch-module".to_string());
+                args.push(format!("java.base={}", dir.display()));
+            }
+            for dir in appends.iter() {
+                let dir = PathBuf::from(class_list).join("boot").join(dir);
+                args.push(format!("-Xbootclasspath/a:{}", dir.display()));
             }
-            Err(e) => {
-                println!("  java::{} ... FAILED ({})", class, e);
+        }
+        args.extend(["--cp".to_string(), class_path, class.to_string()]);
+        let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+        if run(
+            jvm_bin,
+            &name,
+            &args,
+            expected.as_deref(),
+            status,
+            &Log::default(),
+            &[],
+        ) {
+            pass += 1;
+        } else {
+            fail += 1;
+        }
+    }
+
+    // the JIT is off by default, so inline caches, CHA, inlining and deopt
+    // only run here
+    for (class, compiled, not_entrant) in JIT_TESTS {
+        let name = format!("jit::{}", class);
+        let expected = expected_output(class);
+        let args = [
+            "--cp",
+            class_list,
+            "--jit-level",
+            "2",
+            "--print-compilation",
+            class,
+        ];
+        let log = Log {
+            compiled,
+            not_entrant,
+        };
+        if run(jvm_bin, &name, &args, expected.as_deref(), 0, &log, &[]) {
+            pass += 1;
+        } else {
+            fail += 1;
+        }
+    }
+
+    // modular tests, run as `-m module/class` from the module path
+    let mods = PathBuf::from(class_list).join("mods");
+    let mods = mods.to_str().unwrap();
+    let mains =
+        fs::read_to_string(PathBuf::from(class_list).join("modules.txt")).unwrap_or_default();
+    for main in mains.lines().filter(|s| !s.is_empty()) {
+        let name = format!("modules::{}", main);
+        if run(
+            jvm_bin,
+            &name,
+            &["--module-path", mods, "-m", main],
+            None,
+            0,
+            &Log::default(),
+            &[],
+        ) {
+            pass += 1;
+        } else {
+            fail += 1;
+        }
+
+        for (_, compiled) in JIT_MODULE_TESTS.iter().filter(|(m, _)| *m == main) {
+            let name = format!("jit::{}", main);
+            let args = [
+                "--module-path",
+                mods,
+                "--jit-level",
+                "2",
+                "--print-compilation",
+                "-m",
+                main,
+            ];
+            let log = Log {
+                compiled,
+                not_entrant: &[],
+            };
+            if run(jvm_bin, &name, &args, None, 0, &log, &[]) {
+                pass += 1;
+            } else {
                 fail += 1;
             }
         }
     }
 
+    // executable jars, with the class path taken from their manifest
+    for (jar, main_class, program_args, stderr) in JAR_TESTS {
+        let name = format!("jar::{}", jar);
+        let expected = expected_output(main_class);
+        let jar = PathBuf::from(class_list).join(format!("jars/{}.jar", jar));
+        let mut args = vec!["-jar", jar.to_str().unwrap()];
+        args.extend_from_slice(program_args);
+        if run(
+            jvm_bin,
+            &name,
+            &args,
+            expected.as_deref(),
+            0,
+            &Log::default(),
+            stderr,
+        ) {
+            pass += 1;
+        } else {
+            fail += 1;
+        }
+    }
+
     println!("\n{} passed, {} failed\n", pass, fail);
 
     if fail > 0 {
         std::process::exit(1);
     }
 }
+
+/// The stdout a test must print, from tests/java/expected/<Class>.txt.
+fn expected_output(class: &str) -> Option<String> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("java/expected")
+        .join(format!("{}.txt", class));
+    fs::read_to_string(path).ok()
+}
+
+/// A line of the `--print-compilation` log, e.g.
+/// `      12    3    2   JitBench::fib (23 bytes)`.
+fn is_compilation_log(line: &str) -> bool {
+    line.contains("::") && line.contains(" bytes)")
+}
+
+/// What the `--print-compilation` log of a run must contain.
+#[derive(Default)]
+struct Log<'a> {
+    compiled: &'a [&'a str],
+    not_entrant: &'a [&'a str],
+}
+
+/// Check stdout against `expected` (with the compilation log taken out) and
+/// against the lines `log` asks for.
+fn check_stdout(name: &str, stdout: &str, expected: Option<&str>, log: &Log) -> bool {
+    let (lines, output): (Vec<&str>, Vec<&str>) =
+        stdout.lines().partition(|l| is_compilation_log(l));
+
+    if let Some(expected) = expected {
+        if !output.iter().copied().eq(expected.lines()) {
+            println!("  {} ... FAILED (unexpected output)", name);
+            for line in &output {
+                println!("    {}", line);
+            }
+            return false;
+        }
+    }
+
+    for method in log.compiled {
+        let prefix = format!("{} (", method);
+        let found = lines
+            .iter()
+            .any(|l| l.contains(&prefix) && !l.contains("COMPILE SKIPPED"));
+        if !found {
+            println!("  {} ... FAILED ({} not compiled)", name, method);
+            return false;
+        }
+    }
+
+    for reason in log.not_entrant {
+        let reason = format!("made not entrant: {}", reason);
+        if !lines.iter().any(|l| l.contains(&reason)) {
+            println!("  {} ... FAILED (no '{}')", name, reason);
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Run the VM and check that it exits with `status`, prints `expected` and
+/// writes every line of `stderr_lines` to stderr.
+fn run(
+    jvm_bin: &str,
+    name: &str,
+    args: &[&str],
+    expected: Option<&str>,
+    status: i32,
+    log: &Log,
+    stderr_lines: &[&str],
+) -> bool {
+    println!("Running {}...", name);
+    let output = Command::new(jvm_bin).args(args).output();
+    println!("cmd: {} {}", jvm_bin, args.join(" "));
+
+    match output {
+        Ok(out) if out.status.code() == Some(status) => {
+            let stdout = String::from_utf8_lossy(&out.stdout);
+            if !check_stdout(name, &stdout, expected, log) {
+                return false;
+            }
+
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            if let Some(line) = stderr_lines.iter().find(|l| !stderr.contains(*l)) {
+                println!("  {} ... FAILED (no '{}' on stderr)", name, line);
+                return false;
+            }
+
+            // Check stderr for uncaught exceptions or errors that indicate JVM bugs.
+            // Some tests legitimately print warnings or exceptions they catch themselves,
+            // so we only fail on ERROR-level messages that indicate unhandled problems.
+            let has_error = stderr.lines().any(|line| {
+                line.contains("ERROR")
+                    && (line.contains("Uncaught exception")
+                        || line.contains("java.lang.NullPointerException")
+                        || line.contains("java.lang.NoSuchMethodError")
+                        || line.contains("panic"))
+                    && !line.contains("java/lang/UnsupportedOperationException")
+                // known: invokedynamic not supported
+            });
+
+            if has_error {
+                println!("  {} ... FAILED (stderr errors)", name);
+                for line in stderr.lines().filter(|l| l.contains("ERROR")) {
+                    println!("    {}", line);
+                }
+                false
+            } else {
+                println!("  {} ... ok", name);
+                true
+            }
+        }
+        Ok(out) => {
+            println!("  {} ... FAILED (exit status {:?})", name, out.status.code());
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            for line in stderr.lines().take(3) {
+                println!("    {}", line);
+            }
+            false
+        }
+        Err(e) => {
+            println!("  {} ... FAILED ({})", name, e);
+            false
+        }
+    }
+}
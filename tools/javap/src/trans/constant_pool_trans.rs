@@ -197,6 +197,22 @@ impl<'a> Translator<'a> {
                 } => {
                     pool.push("todo: InvokeDynamic".to_string());
                 }
+                Type::Module { name_index } | Type::Package { name_index } => {
+                    let kind = match it {
+                        Type::Module { .. } => "Module",
+                        _ => "Package",
+                    };
+                    let index = format!("#{}", *name_index);
+                    let name = constant_pool::get_utf8(&self.cf.cp, *name_index as usize);
+                    let v = format!(
+                        "{:>6} = {:18} {:14} // {}",
+                        pos,
+                        kind,
+                        index,
+                        String::from_utf8_lossy(name.as_slice())
+                    );
+                    pool.push(v);
+                }
                 Type::Unknown => (),
             }
         }
@@ -606,6 +606,8 @@ impl InstructionInfo {
                 bootstrap_method_attr_index: _,
                 name_and_type_index: _,
             } => "todo: InvokeDynamic".to_string(),
+            //only in module-info, which has no code
+            Type::Module { .. } | Type::Package { .. } => unreachable!(),
             Type::Unknown => unreachable!(),
         }
     }